@@ -16,6 +16,28 @@ use datahighway_runtime::{
     GrandpaConfig,
     ImOnlineConfig,
     IndicesConfig,
+    MiningClaimsTokenConfig,
+    RoamingAccountingPoliciesConfig,
+    RoamingAccountingPolicyRegistryConfig,
+    RoamingAgreementPolicyRegistryConfig,
+    RoamingBillingPolicyRegistryConfig,
+    RoamingChargingPolicyRegistryConfig,
+    RoamingDeviceProfileRegistryConfig,
+    RoamingDeviceRegistryConfig,
+    RoamingDevicesConfig,
+    RoamingInvoicesConfig,
+    RoamingNetworkProfileRegistryConfig,
+    RoamingNetworkRegistryConfig,
+    RoamingNetworkServerRegistryConfig,
+    RoamingOperatorRegistryConfig,
+    RoamingOrganizationRegistryConfig,
+    RoamingPacketBundleRegistryConfig,
+    RoamingPacketBundlesConfig,
+    RoamingRoutingProfileRegistryConfig,
+    RoamingRoutingProfilesConfig,
+    RoamingServiceProfileRegistryConfig,
+    RoamingSessionRegistryConfig,
+    RoamingSessionsConfig,
     SessionConfig,
     SessionKeys,
     StakerStatus,
@@ -1325,6 +1347,28 @@ fn testnet_genesis(
             phantom: Default::default(),
         }),
         pallet_treasury: Some(TreasuryConfig::default()),
+        roaming_registry_Instance1: Some(RoamingOperatorRegistryConfig::default()),
+        roaming_registry_Instance2: Some(RoamingNetworkRegistryConfig::default()),
+        roaming_registry_Instance3: Some(RoamingOrganizationRegistryConfig::default()),
+        roaming_registry_Instance4: Some(RoamingNetworkServerRegistryConfig::default()),
+        roaming_registry_Instance5: Some(RoamingDeviceRegistryConfig::default()),
+        roaming_registry_Instance6: Some(RoamingRoutingProfileRegistryConfig::default()),
+        roaming_registry_Instance7: Some(RoamingServiceProfileRegistryConfig::default()),
+        roaming_registry_Instance8: Some(RoamingAccountingPolicyRegistryConfig::default()),
+        roaming_registry_Instance9: Some(RoamingAgreementPolicyRegistryConfig::default()),
+        roaming_registry_Instance10: Some(RoamingNetworkProfileRegistryConfig::default()),
+        roaming_registry_Instance11: Some(RoamingDeviceProfileRegistryConfig::default()),
+        roaming_registry_Instance12: Some(RoamingSessionRegistryConfig::default()),
+        roaming_registry_Instance13: Some(RoamingBillingPolicyRegistryConfig::default()),
+        roaming_registry_Instance14: Some(RoamingChargingPolicyRegistryConfig::default()),
+        roaming_registry_Instance15: Some(RoamingPacketBundleRegistryConfig::default()),
+        roaming_devices: Some(RoamingDevicesConfig::default()),
+        roaming_routing_profiles: Some(RoamingRoutingProfilesConfig::default()),
+        roaming_accounting_policies: Some(RoamingAccountingPoliciesConfig::default()),
+        roaming_sessions: Some(RoamingSessionsConfig::default()),
+        roaming_packet_bundles: Some(RoamingPacketBundlesConfig::default()),
+        roaming_invoices: Some(RoamingInvoicesConfig::default()),
+        mining_claims_token: Some(MiningClaimsTokenConfig::default()),
 	}
 }
 
@@ -1413,5 +1457,27 @@ fn mainnet_genesis(
             phantom: Default::default(),
         }),
         pallet_treasury: Some(TreasuryConfig::default()),
+        roaming_registry_Instance1: Some(RoamingOperatorRegistryConfig::default()),
+        roaming_registry_Instance2: Some(RoamingNetworkRegistryConfig::default()),
+        roaming_registry_Instance3: Some(RoamingOrganizationRegistryConfig::default()),
+        roaming_registry_Instance4: Some(RoamingNetworkServerRegistryConfig::default()),
+        roaming_registry_Instance5: Some(RoamingDeviceRegistryConfig::default()),
+        roaming_registry_Instance6: Some(RoamingRoutingProfileRegistryConfig::default()),
+        roaming_registry_Instance7: Some(RoamingServiceProfileRegistryConfig::default()),
+        roaming_registry_Instance8: Some(RoamingAccountingPolicyRegistryConfig::default()),
+        roaming_registry_Instance9: Some(RoamingAgreementPolicyRegistryConfig::default()),
+        roaming_registry_Instance10: Some(RoamingNetworkProfileRegistryConfig::default()),
+        roaming_registry_Instance11: Some(RoamingDeviceProfileRegistryConfig::default()),
+        roaming_registry_Instance12: Some(RoamingSessionRegistryConfig::default()),
+        roaming_registry_Instance13: Some(RoamingBillingPolicyRegistryConfig::default()),
+        roaming_registry_Instance14: Some(RoamingChargingPolicyRegistryConfig::default()),
+        roaming_registry_Instance15: Some(RoamingPacketBundleRegistryConfig::default()),
+        roaming_devices: Some(RoamingDevicesConfig::default()),
+        roaming_routing_profiles: Some(RoamingRoutingProfilesConfig::default()),
+        roaming_accounting_policies: Some(RoamingAccountingPoliciesConfig::default()),
+        roaming_sessions: Some(RoamingSessionsConfig::default()),
+        roaming_packet_bundles: Some(RoamingPacketBundlesConfig::default()),
+        roaming_invoices: Some(RoamingInvoicesConfig::default()),
+        mining_claims_token: Some(MiningClaimsTokenConfig::default()),
 	}
 }
@@ -13,6 +13,18 @@ use datahighway_runtime::{
     Hash,
     Index,
 };
+use jsonrpc_core::{
+    Error as RpcError,
+    ErrorCode,
+    Result as RpcResult,
+};
+use jsonrpc_derive::rpc;
+use roaming_runtime_api::{
+    RoamingApi as RoamingRuntimeApi,
+    RoamingNetworkSubtree,
+    RoamingOperatorSubtree,
+    RoamingPage,
+};
 pub use sc_rpc_api::DenyUnsafe;
 use sc_client_api::AuxStore;
 use sc_consensus_babe::{
@@ -39,6 +51,7 @@ use sp_blockchain::{
 use sp_consensus::SelectChain;
 use sp_consensus_babe::BabeApi;
 use sp_keystore::SyncCryptoStorePtr;
+use sp_runtime::generic::BlockId;
 use sp_transaction_pool::TransactionPool;
 
 /// Light client extra dependencies.
@@ -95,6 +108,164 @@ pub struct FullDeps<C, P, SC, B> {
     pub grandpa: GrandpaDeps<B>,
 }
 
+/// Roaming graph queries, which resolve the children of roaming entities a page at a time
+#[rpc]
+pub trait RoamingRpcApi<BlockHash> {
+    /// A page of the networks of a roaming operator, with their network servers and devices
+    #[rpc(name = "roaming_operatorSubtree")]
+    fn operator_subtree(
+        &self,
+        operator_id: u64,
+        start: Option<u64>,
+        limit: u32,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Option<RoamingOperatorSubtree<u64>>>;
+
+    /// A page of the network servers of a roaming network, with their devices
+    #[rpc(name = "roaming_networkSubtree")]
+    fn network_subtree(
+        &self,
+        network_id: u64,
+        start: Option<u64>,
+        limit: u32,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Option<RoamingNetworkSubtree<u64>>>;
+
+    /// A page of the devices of a roaming network server
+    #[rpc(name = "roaming_networkServerDevices")]
+    fn network_server_devices(
+        &self,
+        network_server_id: u64,
+        start: Option<u64>,
+        limit: u32,
+        at: Option<BlockHash>,
+    ) -> RpcResult<RoamingPage<u64>>;
+
+    /// A page of the sessions of a roaming device
+    #[rpc(name = "roaming_deviceSessions")]
+    fn device_sessions(
+        &self,
+        device_id: u64,
+        start: Option<u64>,
+        limit: u32,
+        at: Option<BlockHash>,
+    ) -> RpcResult<RoamingPage<u64>>;
+
+    /// A page of the packet bundles of a roaming session
+    #[rpc(name = "roaming_sessionPacketBundles")]
+    fn session_packet_bundles(
+        &self,
+        session_id: u64,
+        start: Option<u64>,
+        limit: u32,
+        at: Option<BlockHash>,
+    ) -> RpcResult<RoamingPage<u64>>;
+}
+
+/// Error code of the roaming graph queries when the runtime API call fails
+const ROAMING_RUNTIME_ERROR: i64 = 1;
+
+fn roaming_runtime_error(e: impl fmt::Debug) -> RpcError {
+    RpcError {
+        code: ErrorCode::ServerError(ROAMING_RUNTIME_ERROR),
+        message: "Unable to query the roaming graph".into(),
+        data: Some(format!("{:?}", e).into()),
+    }
+}
+
+/// Implements the `RoamingRpcApi` with the `RoamingApi` runtime API
+pub struct Roaming<C> {
+    client: Arc<C>,
+}
+
+impl<C> Roaming<C> {
+    /// Create a new `Roaming` with the given reference to the client.
+    pub fn new(client: Arc<C>) -> Self {
+        Self {
+            client,
+        }
+    }
+
+    /// The given block, or the best block if none is given
+    fn block_id(&self, at: Option<Hash>) -> BlockId<Block>
+    where
+        C: HeaderBackend<Block>,
+    {
+        BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash))
+    }
+}
+
+impl<C> RoamingRpcApi<Hash> for Roaming<C>
+where
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+    C::Api: RoamingRuntimeApi<Block, u64>,
+{
+    fn operator_subtree(
+        &self,
+        operator_id: u64,
+        start: Option<u64>,
+        limit: u32,
+        at: Option<Hash>,
+    ) -> RpcResult<Option<RoamingOperatorSubtree<u64>>> {
+        self.client
+            .runtime_api()
+            .roaming_operator_subtree(&self.block_id(at), operator_id, start, limit)
+            .map_err(roaming_runtime_error)
+    }
+
+    fn network_subtree(
+        &self,
+        network_id: u64,
+        start: Option<u64>,
+        limit: u32,
+        at: Option<Hash>,
+    ) -> RpcResult<Option<RoamingNetworkSubtree<u64>>> {
+        self.client
+            .runtime_api()
+            .roaming_network_subtree(&self.block_id(at), network_id, start, limit)
+            .map_err(roaming_runtime_error)
+    }
+
+    fn network_server_devices(
+        &self,
+        network_server_id: u64,
+        start: Option<u64>,
+        limit: u32,
+        at: Option<Hash>,
+    ) -> RpcResult<RoamingPage<u64>> {
+        self.client
+            .runtime_api()
+            .roaming_network_server_devices(&self.block_id(at), network_server_id, start, limit)
+            .map_err(roaming_runtime_error)
+    }
+
+    fn device_sessions(
+        &self,
+        device_id: u64,
+        start: Option<u64>,
+        limit: u32,
+        at: Option<Hash>,
+    ) -> RpcResult<RoamingPage<u64>> {
+        self.client
+            .runtime_api()
+            .roaming_device_sessions(&self.block_id(at), device_id, start, limit)
+            .map_err(roaming_runtime_error)
+    }
+
+    fn session_packet_bundles(
+        &self,
+        session_id: u64,
+        start: Option<u64>,
+        limit: u32,
+        at: Option<Hash>,
+    ) -> RpcResult<RoamingPage<u64>> {
+        self.client
+            .runtime_api()
+            .roaming_session_packet_bundles(&self.block_id(at), session_id, start, limit)
+            .map_err(roaming_runtime_error)
+    }
+}
+
 /// A IO handler that uses all Full RPC extensions.
 pub type IoHandler = jsonrpc_core::IoHandler<sc_rpc::Metadata>;
 
@@ -112,6 +283,7 @@ where
     C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
     C::Api: BabeApi<Block>,
     C::Api: BlockBuilder<Block>,
+    C::Api: RoamingRuntimeApi<Block, u64>,
     P: TransactionPool + 'static,
     SC: SelectChain<Block> + 'static,
     B: sc_client_api::Backend<Block> + Send + Sync + 'static,
@@ -158,6 +330,7 @@ where
 
     io.extend_with(SystemApi::to_delegate(FullSystem::new(client.clone(), pool, deny_unsafe))); // TODO#ILYA
     io.extend_with(TransactionPaymentApi::to_delegate(TransactionPayment::new(client.clone())));
+    io.extend_with(RoamingRpcApi::to_delegate(Roaming::new(client.clone())));
     io.extend_with(sc_consensus_babe_rpc::BabeApi::to_delegate(BabeRpcHandler::new(
         client.clone(),
         shared_epoch_changes.clone(),
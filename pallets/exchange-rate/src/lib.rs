@@ -51,6 +51,7 @@ pub struct ExchangeRateSetting<H, D, I, F, P> {
 
 pub trait Config: frame_system::Config + roaming_operators::Config {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
+    type Randomness: Randomness<Self::Hash>;
     type ExchangeRateIndex: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
     type HBTCRate: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
     type DOTRate: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
@@ -269,7 +270,7 @@ impl<T: Config> Module<T> {
 
     fn random_value(sender: &T::AccountId) -> [u8; 16] {
         let payload = (
-            T::Randomness::random(&[0]),
+            <T as Config>::Randomness::random(&[0]),
             sender,
             <frame_system::Module<T>>::extrinsic_index(),
             <frame_system::Module<T>>::block_number(),
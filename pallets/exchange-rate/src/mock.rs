@@ -87,11 +87,23 @@ impl pallet_transaction_payment::Config for Test {
     type TransactionByteFee = TransactionByteFee;
     type WeightToFee = IdentityFee<u64>;
 }
-impl roaming_operators::Config for Test {
+impl roaming_registry::Config<roaming_registry::Instance1> for Test {
     type Currency = Balances;
+    type EntityDeposit = ();
     type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
     type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+impl roaming_operators::Config for Test {
+    type Currency = Balances;
+    type Event = ();
+    type RemovalPolicy = ();
+    type RoamingOperatorChildren = ();
     type RoamingOperatorIndex = u64;
+    type RoamingOperatorRegistry = RoamingOperatorRegistry;
 }
 impl Config for Test {
     type DOTRate = u64;
@@ -101,9 +113,11 @@ impl Config for Test {
     type FILRate = u64;
     type HBTCRate = u64;
     type IOTARate = u64;
+    type Randomness = RandomnessCollectiveFlip;
 }
 
 pub type ExchangeRateTestModule = Module<Test>;
+pub type RoamingOperatorRegistry = roaming_registry::Module<Test, roaming_registry::Instance1>;
 
 // This function basically just builds a genesis storage key/value store according to
 // our desired mockup.
@@ -11,9 +11,11 @@ use frame_support::{
     decl_storage,
     ensure,
     traits::{
+        Currency,
         Get,
         Randomness,
     },
+    transactional,
     Parameter,
 };
 use frame_system::ensure_signed;
@@ -26,6 +28,7 @@ use sp_runtime::{
         One,
     },
     DispatchError,
+    DispatchResult,
 };
 use sp_std::prelude::*; // Imports Vec
 
@@ -52,7 +55,10 @@ pub trait Config:
     + mining_sampling_hardware::Config
 {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
+    type Randomness: Randomness<Self::Hash>;
     type MiningClaimsHardwareIndex: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
+    /// The deposit reserved from the creator of a mining claims hardware and held by its owner
+    type EntityDeposit: Get<BalanceOf<Self>>;
     type MiningClaimsHardwareClaimAmount: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
 }
 
@@ -83,6 +89,8 @@ decl_event!(
         Created(AccountId, MiningClaimsHardwareIndex),
         /// A mining_claims_hardware is transferred. (from, to, mining_claims_hardware_id)
         Transferred(AccountId, AccountId, MiningClaimsHardwareIndex),
+        /// A mining_claims_hardware is removed. (owner, mining_claims_hardware_id)
+        Removed(AccountId, MiningClaimsHardwareIndex),
         MiningClaimsHardwareClaimResultSet(
             AccountId, MiningSettingHardwareIndex, MiningClaimsHardwareIndex,
             MiningClaimsHardwareClaimAmount, BlockNumber
@@ -105,6 +113,9 @@ decl_storage! {
         /// Stores mining_claims_hardware owner
         pub MiningClaimsHardwareOwners get(fn mining_claims_hardware_owner): map hasher(opaque_blake2_256) T::MiningClaimsHardwareIndex => Option<T::AccountId>;
 
+        /// Get the deposit reserved for a mining claims hardware, which is held by its owner
+        pub MiningClaimsHardwareDeposits get(fn mining_claims_hardware_deposit): map hasher(opaque_blake2_256) T::MiningClaimsHardwareIndex => Option<BalanceOf<T>>;
+
         /// Stores mining_claims_hardware_claims_result
         pub MiningClaimsHardwareClaimResults get(fn mining_claims_hardware_claims_results): map hasher(opaque_blake2_256) (T::MiningSettingHardwareIndex, T::MiningClaimsHardwareIndex) =>
             Option<MiningClaimsHardwareClaimResult<
@@ -132,12 +143,17 @@ decl_module! {
             let sender = ensure_signed(origin)?;
             let mining_claims_hardware_id = Self::next_mining_claims_hardware_id()?;
 
+            // Reserve a deposit from the creator for the storage used by the mining claims hardware
+            let deposit = <T as Config>::EntityDeposit::get();
+            <roaming_operators::Module<T>>::reserve_entity_deposit(&sender, deposit)?;
+
             // Generate a random 128bit value
             let unique_id = Self::random_value(&sender);
 
             // Create and store mining_claims_hardware
             let mining_claims_hardware = MiningClaimsHardware(unique_id);
             Self::insert_mining_claims_hardware(&sender, mining_claims_hardware_id, mining_claims_hardware);
+            <MiningClaimsHardwareDeposits<T>>::insert(mining_claims_hardware_id, deposit);
 
             Self::deposit_event(RawEvent::Created(sender, mining_claims_hardware_id));
         }
@@ -149,11 +165,23 @@ decl_module! {
 
             ensure!(Self::mining_claims_hardware_owner(mining_claims_hardware_id) == Some(sender.clone()), "Only owner can transfer mining mining_claims_hardware");
 
-            Self::update_owner(&to, mining_claims_hardware_id);
+            Self::update_owner(&to, mining_claims_hardware_id)?;
 
             Self::deposit_event(RawEvent::Transferred(sender, to, mining_claims_hardware_id));
         }
 
+        /// Remove a mining_claims_hardware, returning its deposit to its owner
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(4, 6)]
+        pub fn remove(origin, mining_claims_hardware_id: T::MiningClaimsHardwareIndex) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(Self::mining_claims_hardware_owner(mining_claims_hardware_id) == Some(sender.clone()), "Only owner can remove mining mining_claims_hardware");
+
+            Self::remove_mining_claims_hardware(mining_claims_hardware_id);
+
+            Self::deposit_event(RawEvent::Removed(sender, mining_claims_hardware_id));
+        }
+
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
         pub fn claim(
             origin,
@@ -248,6 +276,7 @@ decl_module! {
         }
 
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        #[transactional]
         pub fn assign_claim_to_configuration(
           origin,
           mining_claims_hardware_id: T::MiningClaimsHardwareIndex,
@@ -266,8 +295,7 @@ decl_module! {
                 "Only the configuration_hardware owner can assign itself a claim"
             );
 
-            Self::associate_hardware_claim_with_configuration(mining_claims_hardware_id, mining_setting_hardware_id)
-                .expect("Unable to associate claim with configuration");
+            Self::associate_hardware_claim_with_configuration(mining_claims_hardware_id, mining_setting_hardware_id)?;
 
             // Ensure that the given mining_claims_hardware_id already exists
             let hardware_claim = Self::mining_claims_hardware(mining_claims_hardware_id);
@@ -374,7 +402,7 @@ impl<T: Config> Module<T> {
 
     fn random_value(sender: &T::AccountId) -> [u8; 16] {
         let payload = (
-            T::Randomness::random(&[0]),
+            <T as Config>::Randomness::random(&[0]),
             sender,
             <frame_system::Module<T>>::extrinsic_index(),
             <frame_system::Module<T>>::block_number(),
@@ -401,7 +429,37 @@ impl<T: Config> Module<T> {
         <MiningClaimsHardwareOwners<T>>::insert(mining_claims_hardware_id, owner.clone());
     }
 
-    fn update_owner(to: &T::AccountId, mining_claims_hardware_id: T::MiningClaimsHardwareIndex) {
+    /// Remove a mining_claims_hardware and return its deposit to its owner
+    pub fn remove_mining_claims_hardware(mining_claims_hardware_id: T::MiningClaimsHardwareIndex) {
+        if let (Some(owner), Some(deposit)) = (
+            <MiningClaimsHardwareOwners<T>>::take(mining_claims_hardware_id),
+            <MiningClaimsHardwareDeposits<T>>::take(mining_claims_hardware_id),
+        ) {
+            <roaming_operators::Module<T>>::unreserve_entity_deposit(&owner, deposit);
+        }
+        <MiningClaimsHardwares<T>>::remove(mining_claims_hardware_id);
+        if let Some(mining_setting_hardware_id) = <HardwareClaimConfiguration<T>>::take(mining_claims_hardware_id) {
+            <MiningClaimsHardwareClaimResults<T>>::remove((mining_setting_hardware_id, mining_claims_hardware_id));
+            <HardwareSettingClaims<T>>::mutate_exists(mining_setting_hardware_id, |claim_ids| {
+                if let Some(ids) = claim_ids.as_mut() {
+                    ids.retain(|id| *id != mining_claims_hardware_id);
+                }
+                if claim_ids.as_ref().map_or(false, |ids| ids.is_empty()) {
+                    *claim_ids = None;
+                }
+            });
+        }
+    }
+
+    fn update_owner(to: &T::AccountId, mining_claims_hardware_id: T::MiningClaimsHardwareIndex) -> DispatchResult {
+        // The deposit of the mining claims hardware moves with its ownership
+        if let (Some(from), Some(deposit)) = (
+            Self::mining_claims_hardware_owner(mining_claims_hardware_id),
+            Self::mining_claims_hardware_deposit(mining_claims_hardware_id),
+        ) {
+            <roaming_operators::Module<T>>::move_entity_deposit(&from, to, deposit)?;
+        }
         <MiningClaimsHardwareOwners<T>>::insert(mining_claims_hardware_id, to);
+        Ok(())
     }
 }
@@ -10,22 +10,31 @@ use frame_support::{
     decl_module,
     decl_storage,
     ensure,
+    storage::migration::take_storage_value,
     traits::{
+        Currency,
         Get,
         Randomness,
     },
+    transactional,
+    weights::Weight,
     Parameter,
 };
 use frame_system::ensure_signed;
-use sp_io::hashing::blake2_128;
+use sp_io::hashing::{
+    blake2_128,
+    blake2_256,
+};
 use sp_runtime::{
     traits::{
         AtLeast32Bit,
         Bounded,
         Member,
         One,
+        Zero,
     },
     DispatchError,
+    DispatchResult,
 };
 use sp_std::prelude::*; // Imports Vec
 
@@ -52,13 +61,32 @@ pub trait Config:
     + mining_sampling_token::Config
 {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
+    type Randomness: Randomness<Self::Hash>;
     type MiningClaimsTokenIndex: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
+    /// The deposit reserved from the creator of a mining claims token and held by its owner
+    type EntityDeposit: Get<BalanceOf<Self>>;
     type MiningClaimsTokenClaimAmount: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
 }
 
 // type BalanceOf<T> = <<T as roaming_operators::Config>::Currency as Currency<<T as
 // frame_system::Config>::AccountId>>::Balance;
 
+/// Storage version of the mining claims token pallet
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum Releases {
+    /// The claims of each mining setting token are stored as a vector
+    V1_0_0,
+    /// The claims of each mining setting token are keys of a double map
+    V2_0_0,
+}
+
+impl Default for Releases {
+    fn default() -> Self {
+        Releases::V1_0_0
+    }
+}
+
 #[derive(Encode, Decode, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "std", derive(Debug))]
 pub struct MiningClaimsToken(pub [u8; 16]);
@@ -83,6 +111,8 @@ decl_event!(
         Created(AccountId, MiningClaimsTokenIndex),
         /// A mining_claims_token is transferred. (from, to, mining_claims_token_id)
         Transferred(AccountId, AccountId, MiningClaimsTokenIndex),
+        /// A mining_claims_token is removed. (owner, mining_claims_token_id)
+        Removed(AccountId, MiningClaimsTokenIndex),
         MiningClaimsTokenClaimResultSet(
             AccountId, MiningSettingTokenIndex, MiningClaimsTokenIndex,
             MiningClaimsTokenClaimAmount, BlockNumber
@@ -105,6 +135,9 @@ decl_storage! {
         /// Stores mining_claims_token owner
         pub MiningClaimsTokenOwners get(fn mining_claims_token_owner): map hasher(opaque_blake2_256) T::MiningClaimsTokenIndex => Option<T::AccountId>;
 
+        /// Get the deposit reserved for a mining claims token, which is held by its owner
+        pub MiningClaimsTokenDeposits get(fn mining_claims_token_deposit): map hasher(opaque_blake2_256) T::MiningClaimsTokenIndex => Option<BalanceOf<T>>;
+
         /// Stores mining_claims_token_claims_result
         pub MiningClaimsTokenClaimResults get(fn mining_claims_token_claims_results): map hasher(opaque_blake2_256) (T::MiningSettingTokenIndex, T::MiningClaimsTokenIndex) =>
             Option<MiningClaimsTokenClaimResult<
@@ -115,8 +148,13 @@ decl_storage! {
         /// Get mining_setting_token_id belonging to a mining_claims_token_id
         pub TokenClaimConfiguration get(fn token_claim_configuration): map hasher(opaque_blake2_256) T::MiningClaimsTokenIndex => Option<T::MiningSettingTokenIndex>;
 
-        /// Get mining_claims_token_id's belonging to a mining_setting_token_id
-        pub TokenSettingClaims get(fn token_setting_claims): map hasher(opaque_blake2_256) T::MiningSettingTokenIndex => Option<Vec<T::MiningClaimsTokenIndex>>
+        /// The mining_claims_token_id's belonging to each mining_setting_token_id, stored as keys so that a claim
+        /// is added without reading the other claims of the configuration
+        pub TokenSettingClaims: double_map hasher(blake2_128_concat) T::MiningSettingTokenIndex, hasher(blake2_128_concat) T::MiningClaimsTokenIndex => ();
+
+        /// Storage version of the pallet, used to determine which migrations to run on runtime upgrade. A new chain
+        /// starts at the latest version since it has no claims to migrate.
+        pub StorageVersion get(fn storage_version) build(|_| Releases::V2_0_0): Releases;
     }
 }
 
@@ -126,18 +164,31 @@ decl_module! {
     pub struct Module<T: Config> for enum Call where origin: T::Origin {
         fn deposit_event() = default;
 
+        fn on_runtime_upgrade() -> Weight {
+            let mut weight = T::DbWeight::get().reads(1);
+            if Self::storage_version() == Releases::V1_0_0 {
+                weight = weight.saturating_add(Self::migrate_to_token_setting_claim_keys());
+            }
+            weight
+        }
+
         /// Create a new mining mining_claims_token
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
         pub fn create(origin) {
             let sender = ensure_signed(origin)?;
             let mining_claims_token_id = Self::next_mining_claims_token_id()?;
 
+            // Reserve a deposit from the creator for the storage used by the mining claims token
+            let deposit = <T as Config>::EntityDeposit::get();
+            <roaming_operators::Module<T>>::reserve_entity_deposit(&sender, deposit)?;
+
             // Generate a random 128bit value
             let unique_id = Self::random_value(&sender);
 
             // Create and store mining_claims_token
             let mining_claims_token = MiningClaimsToken(unique_id);
             Self::insert_mining_claims_token(&sender, mining_claims_token_id, mining_claims_token);
+            <MiningClaimsTokenDeposits<T>>::insert(mining_claims_token_id, deposit);
 
             Self::deposit_event(RawEvent::Created(sender, mining_claims_token_id));
         }
@@ -149,11 +200,23 @@ decl_module! {
 
             ensure!(Self::mining_claims_token_owner(mining_claims_token_id) == Some(sender.clone()), "Only owner can transfer mining mining_claims_token");
 
-            Self::update_owner(&to, mining_claims_token_id);
+            Self::update_owner(&to, mining_claims_token_id)?;
 
             Self::deposit_event(RawEvent::Transferred(sender, to, mining_claims_token_id));
         }
 
+        /// Remove a mining_claims_token, returning its deposit to its owner
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(4, 6)]
+        pub fn remove(origin, mining_claims_token_id: T::MiningClaimsTokenIndex) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(Self::mining_claims_token_owner(mining_claims_token_id) == Some(sender.clone()), "Only owner can remove mining mining_claims_token");
+
+            Self::remove_mining_claims_token(mining_claims_token_id);
+
+            Self::deposit_event(RawEvent::Removed(sender, mining_claims_token_id));
+        }
+
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
         pub fn claim(
             origin,
@@ -196,11 +259,10 @@ decl_module! {
 
             // Check that the provided eligibility amount has not already been claimed
             // i.e. there should only be a single claim instance for each configuration and eligibility in the MVP
-            if let Some(token_setting_claims) = Self::token_setting_claims(mining_setting_token_id) {
-              ensure!(token_setting_claims.len() == 1, "Cannot have zero or more than one claim associated with configuration/eligibility");
-            } else {
-              return Err(DispatchError::Other("Cannot find configuration_claims associated with the claim"));
-            }
+            // Only the first two claims are read since any more than one is rejected
+            let token_setting_claims_count = <TokenSettingClaims<T>>::iter_prefix(mining_setting_token_id).take(2).count();
+            ensure!(token_setting_claims_count != 0, "Cannot find configuration_claims associated with the claim");
+            ensure!(token_setting_claims_count == 1, "Cannot have zero or more than one claim associated with configuration/eligibility");
 
             // Record the claim associated with their configuration/eligibility
             let token_claim_amount: T::MiningClaimsTokenClaimAmount = 0u32.into();
@@ -351,6 +413,7 @@ decl_module! {
         }
 
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        #[transactional]
         pub fn assign_claim_to_configuration(
           origin,
           mining_claims_token_id: T::MiningClaimsTokenIndex,
@@ -369,8 +432,7 @@ decl_module! {
                 "Only the configuration_token owner can assign itself a claim"
             );
 
-            Self::associate_token_claim_with_configuration(mining_claims_token_id, mining_setting_token_id)
-                .expect("Unable to associate claim with configuration");
+            Self::associate_token_claim_with_configuration(mining_claims_token_id, mining_setting_token_id)?;
 
             // Ensure that the given mining_claims_token_id already exists
             let token_claim = Self::mining_claims_token(mining_claims_token_id);
@@ -434,48 +496,60 @@ impl<T: Config> Module<T> {
         Err(DispatchError::Other("No value for mining_claims_token_claims_result"))
     }
 
-    /// Only push the claim id onto the end of the vector if it does not already exist
+    /// Add the claim id to the claims of the configuration if it is not already one of them
     pub fn associate_token_claim_with_configuration(
         mining_claims_token_id: T::MiningClaimsTokenIndex,
         mining_setting_token_id: T::MiningSettingTokenIndex,
     ) -> Result<(), DispatchError> {
-        // Early exit with error since do not want to append if the given configuration id already exists as a key,
-        // and where its corresponding value is a vector that already contains the given claim id
-        if let Some(configuration_claims) = Self::token_setting_claims(mining_setting_token_id) {
-            debug::info!(
-                "Configuration id key {:?} exists with value {:?}",
-                mining_setting_token_id,
-                configuration_claims
-            );
-            let not_configuration_contains_claim = !configuration_claims.contains(&mining_claims_token_id);
-            ensure!(not_configuration_contains_claim, "Configuration already contains the given claim id");
-            debug::info!("Configuration id key exists but its vector value does not contain the given claim id");
-            <TokenSettingClaims<T>>::mutate(mining_setting_token_id, |v| {
-                if let Some(value) = v {
-                    value.push(mining_claims_token_id);
-                }
-            });
-            debug::info!(
-                "Associated claim {:?} with configuration {:?}",
-                mining_claims_token_id,
-                mining_setting_token_id
-            );
-            Ok(())
-        } else {
-            debug::info!(
-                "Configuration id key does not yet exist. Creating the configuration key {:?} and appending the claim \
-                 id {:?} to its vector value",
-                mining_setting_token_id,
-                mining_claims_token_id
-            );
-            <TokenSettingClaims<T>>::insert(mining_setting_token_id, &vec![mining_claims_token_id]);
-            Ok(())
+        ensure!(
+            !<TokenSettingClaims<T>>::contains_key(mining_setting_token_id, mining_claims_token_id),
+            "Configuration already contains the given claim id"
+        );
+        <TokenSettingClaims<T>>::insert(mining_setting_token_id, mining_claims_token_id, ());
+        debug::info!("Associated claim {:?} with configuration {:?}", mining_claims_token_id, mining_setting_token_id);
+        Ok(())
+    }
+
+    /// The claims of a configuration in ascending order
+    pub fn token_setting_claims(mining_setting_token_id: T::MiningSettingTokenIndex) -> Vec<T::MiningClaimsTokenIndex> {
+        let mut mining_claims_token_ids: Vec<T::MiningClaimsTokenIndex> =
+            <TokenSettingClaims<T>>::iter_prefix(mining_setting_token_id).map(|(claim_id, _)| claim_id).collect();
+        mining_claims_token_ids.sort();
+        mining_claims_token_ids
+    }
+
+    /// Move the claims of each configuration from a vector into the keys of a double map
+    fn migrate_to_token_setting_claim_keys() -> Weight {
+        let mining_setting_tokens_count = <mining_setting_token::Module<T>>::mining_setting_token_count();
+        let mut migrated_count: Weight = 0;
+        let mut claim_count: Weight = 0;
+
+        let mut mining_setting_token_id: T::MiningSettingTokenIndex = Zero::zero();
+        while mining_setting_token_id < mining_setting_tokens_count {
+            let mining_claims_token_ids = take_storage_value::<Vec<T::MiningClaimsTokenIndex>>(
+                b"MiningClaimsToken",
+                b"TokenSettingClaims",
+                &blake2_256(&mining_setting_token_id.encode()),
+            )
+            .unwrap_or_default();
+            for mining_claims_token_id in mining_claims_token_ids {
+                <TokenSettingClaims<T>>::insert(mining_setting_token_id, mining_claims_token_id, ());
+                claim_count += 1;
+            }
+
+            migrated_count += 1;
+            mining_setting_token_id = mining_setting_token_id + One::one();
         }
+
+        StorageVersion::put(Releases::V2_0_0);
+        debug::info!("Migrated the claims of {:?} mining setting tokens to double map keys", migrated_count);
+
+        T::DbWeight::get().reads_writes(2 + migrated_count, 1 + migrated_count + claim_count)
     }
 
     fn random_value(sender: &T::AccountId) -> [u8; 16] {
         let payload = (
-            T::Randomness::random(&[0]),
+            <T as Config>::Randomness::random(&[0]),
             sender,
             <frame_system::Module<T>>::extrinsic_index(),
             <frame_system::Module<T>>::block_number(),
@@ -502,7 +576,30 @@ impl<T: Config> Module<T> {
         <MiningClaimsTokenOwners<T>>::insert(mining_claims_token_id, owner.clone());
     }
 
-    fn update_owner(to: &T::AccountId, mining_claims_token_id: T::MiningClaimsTokenIndex) {
+    /// Remove a mining_claims_token and return its deposit to its owner
+    pub fn remove_mining_claims_token(mining_claims_token_id: T::MiningClaimsTokenIndex) {
+        if let (Some(owner), Some(deposit)) = (
+            <MiningClaimsTokenOwners<T>>::take(mining_claims_token_id),
+            <MiningClaimsTokenDeposits<T>>::take(mining_claims_token_id),
+        ) {
+            <roaming_operators::Module<T>>::unreserve_entity_deposit(&owner, deposit);
+        }
+        <MiningClaimsTokens<T>>::remove(mining_claims_token_id);
+        if let Some(mining_setting_token_id) = <TokenClaimConfiguration<T>>::take(mining_claims_token_id) {
+            <MiningClaimsTokenClaimResults<T>>::remove((mining_setting_token_id, mining_claims_token_id));
+            <TokenSettingClaims<T>>::remove(mining_setting_token_id, mining_claims_token_id);
+        }
+    }
+
+    fn update_owner(to: &T::AccountId, mining_claims_token_id: T::MiningClaimsTokenIndex) -> DispatchResult {
+        // The deposit of the mining claims token moves with its ownership
+        if let (Some(from), Some(deposit)) = (
+            Self::mining_claims_token_owner(mining_claims_token_id),
+            Self::mining_claims_token_deposit(mining_claims_token_id),
+        ) {
+            <roaming_operators::Module<T>>::move_entity_deposit(&from, to, deposit)?;
+        }
         <MiningClaimsTokenOwners<T>>::insert(mining_claims_token_id, to);
+        Ok(())
     }
 }
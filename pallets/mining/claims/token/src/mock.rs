@@ -1,6 +1,7 @@
 // Creating mock runtime here
 
 use crate::{
+    GenesisConfig,
     Module,
     Config,
 };
@@ -88,31 +89,47 @@ impl pallet_transaction_payment::Config for Test {
     type WeightToFee = IdentityFee<u64>;
 }
 // FIXME - remove this when figure out how to use these types within mining-speed-boost runtime module itself
-impl roaming_operators::Config for Test {
+impl roaming_registry::Config<roaming_registry::Instance1> for Test {
     type Currency = Balances;
+    type EntityDeposit = ();
     type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
     type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+impl roaming_operators::Config for Test {
+    type Currency = Balances;
+    type Event = ();
+    type RemovalPolicy = ();
+    type RoamingOperatorChildren = ();
     type RoamingOperatorIndex = u64;
+    type RoamingOperatorRegistry = RoamingOperatorRegistry;
 }
 impl mining_setting_token::Config for Test {
+    type EntityDeposit = ();
     type Event = ();
     // FIXME - restore when stop temporarily using roaming-operators
     // type Currency = Balances;
-    // type Randomness = RandomnessCollectiveFlip;
     type MiningSettingTokenIndex = u64;
     type MiningSettingTokenLockAmount = u64;
     // Mining Speed Boost Token Mining Config
     // FIXME - how to use this enum from std? (including importing `use std::str::FromStr;`)
     type MiningSettingTokenType = Vec<u8>;
+    type Randomness = RandomnessCollectiveFlip;
 }
 impl mining_eligibility_token::Config for Test {
+    type EntityDeposit = ();
     type Event = ();
     type MiningEligibilityTokenCalculatedEligibility = u64;
     type MiningEligibilityTokenIndex = u64;
     type MiningEligibilityTokenLockedPercentage = u32;
     // type MiningEligibilityTokenAuditorAccountID = u64;
+    type Randomness = RandomnessCollectiveFlip;
 }
 impl mining_rates_token::Config for Test {
+    type EntityDeposit = ();
     type Event = ();
     type MiningRatesTokenIndex = u64;
     type MiningRatesTokenMaxLoyalty = u32;
@@ -122,19 +139,25 @@ impl mining_rates_token::Config for Test {
     type MiningRatesTokenTokenIOTA = u32;
     // Mining Speed Boost Rate
     type MiningRatesTokenTokenMXC = u32;
+    type Randomness = RandomnessCollectiveFlip;
 }
 impl mining_sampling_token::Config for Test {
+    type EntityDeposit = ();
     type Event = ();
     type MiningSamplingTokenIndex = u64;
     type MiningSamplingTokenSampleLockedAmount = u64;
+    type Randomness = RandomnessCollectiveFlip;
 }
 impl Config for Test {
+    type EntityDeposit = ();
     type Event = ();
     type MiningClaimsTokenClaimAmount = u64;
     type MiningClaimsTokenIndex = u64;
+    type Randomness = RandomnessCollectiveFlip;
 }
 
 pub type MiningClaimsTokenTestModule = Module<Test>;
+pub type RoamingOperatorRegistry = roaming_registry::Module<Test, roaming_registry::Instance1>;
 
 // This function basically just builds a genesis storage key/value store according to
 // our desired mockup.
@@ -145,6 +168,7 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
     }
     .assimilate_storage(&mut t)
     .unwrap();
+    GenesisConfig::default().assimilate_storage::<Test>(&mut t).unwrap();
     let mut ext = sp_io::TestExternalities::new(t);
     ext.execute_with(|| System::set_block_number(1));
     ext
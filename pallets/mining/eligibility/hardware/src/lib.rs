@@ -11,9 +11,11 @@ use frame_support::{
     decl_storage,
     ensure,
     traits::{
+        Currency,
         Get,
         Randomness,
     },
+    transactional,
     Parameter,
 };
 use frame_system::ensure_signed;
@@ -26,6 +28,7 @@ use sp_runtime::{
         One,
     },
     DispatchError,
+    DispatchResult,
 };
 use sp_std::prelude::*; // Imports Vec
 
@@ -50,7 +53,10 @@ pub trait Config:
     + mining_sampling_hardware::Config
 {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
+    type Randomness: Randomness<Self::Hash>;
     type MiningEligibilityHardwareIndex: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
+    /// The deposit reserved from the creator of a mining eligibility hardware and held by its owner
+    type EntityDeposit: Get<BalanceOf<Self>>;
     type MiningEligibilityHardwareCalculatedEligibility: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
     type MiningEligibilityHardwareUptimePercentage: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
     // type MiningEligibilityHardwareAuditorAccountID: Parameter + Member + AtLeast32Bit +
@@ -88,6 +94,8 @@ decl_event!(
         Created(AccountId, MiningEligibilityHardwareIndex),
         /// A mining_eligibility_hardware is transferred. (from, to, mining_eligibility_hardware_id)
         Transferred(AccountId, AccountId, MiningEligibilityHardwareIndex),
+        /// A mining_eligibility_hardware is removed. (owner, mining_eligibility_hardware_id)
+        Removed(AccountId, MiningEligibilityHardwareIndex),
         // MiningEligibilityHardwareResultSet(
         //   AccountId, MiningSettingHardwareIndex, MiningEligibilityHardwareIndex,
         //   MiningEligibilityHardwareCalculatedEligibility, MiningEligibilityHardwareUptimePercentage,
@@ -116,6 +124,9 @@ decl_storage! {
         /// Stores mining_eligibility_hardware owner
         pub MiningEligibilityHardwareOwners get(fn mining_eligibility_hardware_owner): map hasher(opaque_blake2_256) T::MiningEligibilityHardwareIndex => Option<T::AccountId>;
 
+        /// Get the deposit reserved for a mining eligibility hardware, which is held by its owner
+        pub MiningEligibilityHardwareDeposits get(fn mining_eligibility_hardware_deposit): map hasher(opaque_blake2_256) T::MiningEligibilityHardwareIndex => Option<BalanceOf<T>>;
+
         /// Stores mining_eligibility_hardware_result
         pub MiningEligibilityHardwareResults get(fn mining_eligibility_hardware_eligibility_results): map hasher(opaque_blake2_256) (T::MiningSettingHardwareIndex, T::MiningEligibilityHardwareIndex) =>
             Option<MiningEligibilityHardwareResult<
@@ -145,12 +156,17 @@ decl_module! {
             let sender = ensure_signed(origin)?;
             let mining_eligibility_hardware_id = Self::next_mining_eligibility_hardware_id()?;
 
+            // Reserve a deposit from the creator for the storage used by the mining eligibility hardware
+            let deposit = <T as Config>::EntityDeposit::get();
+            <roaming_operators::Module<T>>::reserve_entity_deposit(&sender, deposit)?;
+
             // Geneeligibility a random 128bit value
             let unique_id = Self::random_value(&sender);
 
             // Create and store mining_eligibility_hardware
             let mining_eligibility_hardware = MiningEligibilityHardware(unique_id);
             Self::insert_mining_eligibility_hardware(&sender, mining_eligibility_hardware_id, mining_eligibility_hardware);
+            <MiningEligibilityHardwareDeposits<T>>::insert(mining_eligibility_hardware_id, deposit);
 
             Self::deposit_event(RawEvent::Created(sender, mining_eligibility_hardware_id));
         }
@@ -162,11 +178,23 @@ decl_module! {
 
             ensure!(Self::mining_eligibility_hardware_owner(mining_eligibility_hardware_id) == Some(sender.clone()), "Only owner can transfer mining mining_eligibility_hardware");
 
-            Self::update_owner(&to, mining_eligibility_hardware_id);
+            Self::update_owner(&to, mining_eligibility_hardware_id)?;
 
             Self::deposit_event(RawEvent::Transferred(sender, to, mining_eligibility_hardware_id));
         }
 
+        /// Remove a mining_eligibility_hardware, returning its deposit to its owner
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(4, 6)]
+        pub fn remove(origin, mining_eligibility_hardware_id: T::MiningEligibilityHardwareIndex) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(Self::mining_eligibility_hardware_owner(mining_eligibility_hardware_id) == Some(sender.clone()), "Only owner can remove mining mining_eligibility_hardware");
+
+            Self::remove_mining_eligibility_hardware(mining_eligibility_hardware_id);
+
+            Self::deposit_event(RawEvent::Removed(sender, mining_eligibility_hardware_id));
+        }
+
         // FIXME - implement this and fix the type errors and uncomment it in the integration tests
         // /// Calculate mining_eligibility_hardware_result
         // pub fn calculate_mining_eligibility_hardware_result(
@@ -416,6 +444,7 @@ decl_module! {
         }
 
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        #[transactional]
         pub fn assign_eligibility_to_configuration(
           origin,
           mining_eligibility_hardware_id: T::MiningEligibilityHardwareIndex,
@@ -434,8 +463,10 @@ decl_module! {
                 "Only the configuration_hardware owner can assign itself a eligibility"
             );
 
-            Self::associate_token_eligibility_with_configuration(mining_eligibility_hardware_id, mining_setting_hardware_id)
-                .expect("Unable to associate eligibility with configuration");
+            Self::associate_token_eligibility_with_configuration(
+                mining_eligibility_hardware_id,
+                mining_setting_hardware_id,
+            )?;
 
             // Ensure that the given mining_eligibility_hardware_id already exists
             let token_eligibility = Self::mining_eligibility_hardware(mining_eligibility_hardware_id);
@@ -546,7 +577,7 @@ impl<T: Config> Module<T> {
 
     fn random_value(sender: &T::AccountId) -> [u8; 16] {
         let payload = (
-            T::Randomness::random(&[0]),
+            <T as Config>::Randomness::random(&[0]),
             sender,
             <frame_system::Module<T>>::extrinsic_index(),
             <frame_system::Module<T>>::block_number(),
@@ -573,7 +604,42 @@ impl<T: Config> Module<T> {
         <MiningEligibilityHardwareOwners<T>>::insert(mining_eligibility_hardware_id, owner.clone());
     }
 
-    fn update_owner(to: &T::AccountId, mining_eligibility_hardware_id: T::MiningEligibilityHardwareIndex) {
+    /// Remove a mining_eligibility_hardware and return its deposit to its owner
+    pub fn remove_mining_eligibility_hardware(mining_eligibility_hardware_id: T::MiningEligibilityHardwareIndex) {
+        if let (Some(owner), Some(deposit)) = (
+            <MiningEligibilityHardwareOwners<T>>::take(mining_eligibility_hardware_id),
+            <MiningEligibilityHardwareDeposits<T>>::take(mining_eligibility_hardware_id),
+        ) {
+            <roaming_operators::Module<T>>::unreserve_entity_deposit(&owner, deposit);
+        }
+        <MiningEligibilityHardwares<T>>::remove(mining_eligibility_hardware_id);
+        if let Some(mining_setting_hardware_id) =
+            <HardwareEligibilityConfiguration<T>>::take(mining_eligibility_hardware_id)
+        {
+            <MiningEligibilityHardwareResults<T>>::remove((mining_setting_hardware_id, mining_eligibility_hardware_id));
+            <HardwareSettingEligibilities<T>>::mutate_exists(mining_setting_hardware_id, |eligibility_ids| {
+                if let Some(ids) = eligibility_ids.as_mut() {
+                    ids.retain(|id| *id != mining_eligibility_hardware_id);
+                }
+                if eligibility_ids.as_ref().map_or(false, |ids| ids.is_empty()) {
+                    *eligibility_ids = None;
+                }
+            });
+        }
+    }
+
+    fn update_owner(
+        to: &T::AccountId,
+        mining_eligibility_hardware_id: T::MiningEligibilityHardwareIndex,
+    ) -> DispatchResult {
+        // The deposit of the mining eligibility hardware moves with its ownership
+        if let (Some(from), Some(deposit)) = (
+            Self::mining_eligibility_hardware_owner(mining_eligibility_hardware_id),
+            Self::mining_eligibility_hardware_deposit(mining_eligibility_hardware_id),
+        ) {
+            <roaming_operators::Module<T>>::move_entity_deposit(&from, to, deposit)?;
+        }
         <MiningEligibilityHardwareOwners<T>>::insert(mining_eligibility_hardware_id, to);
+        Ok(())
     }
 }
@@ -88,13 +88,26 @@ impl pallet_transaction_payment::Config for Test {
     type WeightToFee = IdentityFee<u64>;
 }
 // FIXME - remove this when figure out how to use these types within mining-speed-boost runtime module itself
-impl roaming_operators::Config for Test {
+impl roaming_registry::Config<roaming_registry::Instance1> for Test {
     type Currency = Balances;
+    type EntityDeposit = ();
     type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
     type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+impl roaming_operators::Config for Test {
+    type Currency = Balances;
+    type Event = ();
+    type RemovalPolicy = ();
+    type RoamingOperatorChildren = ();
     type RoamingOperatorIndex = u64;
+    type RoamingOperatorRegistry = RoamingOperatorRegistry;
 }
 impl mining_rates_hardware::Config for Test {
+    type EntityDeposit = ();
     type Event = ();
     type MiningRatesHardwareCategory1MaxTokenBonusPerGateway = u32;
     type MiningRatesHardwareCategory2MaxTokenBonusPerGateway = u32;
@@ -105,13 +118,17 @@ impl mining_rates_hardware::Config for Test {
     type MiningRatesHardwareMaxHardware = u32;
     // Mining Speed Boost Rate
     type MiningRatesHardwareSecure = u32;
+    type Randomness = RandomnessCollectiveFlip;
 }
 impl mining_sampling_hardware::Config for Test {
+    type EntityDeposit = ();
     type Event = ();
     type MiningSamplingHardwareIndex = u64;
     type MiningSamplingHardwareSampleHardwareOnline = u64;
+    type Randomness = RandomnessCollectiveFlip;
 }
 impl mining_setting_hardware::Config for Test {
+    type EntityDeposit = ();
     type Event = ();
     type MiningSettingHardwareDevEUI = u64;
     // type MiningSettingHardwareType =
@@ -119,22 +136,25 @@ impl mining_setting_hardware::Config for Test {
     type MiningSettingHardwareID = u64;
     // FIXME - restore when stop temporarily using roaming-operators
     // type Currency = Balances;
-    // type Randomness = RandomnessCollectiveFlip;
     type MiningSettingHardwareIndex = u64;
     // Mining Speed Boost Hardware Mining Config
     type MiningSettingHardwareSecure = bool;
     // FIXME - how to use this enum from std? (including importing `use std::str::FromStr;`)
     type MiningSettingHardwareType = Vec<u8>;
+    type Randomness = RandomnessCollectiveFlip;
 }
 impl Config for Test {
+    type EntityDeposit = ();
     type Event = ();
     type MiningEligibilityHardwareCalculatedEligibility = u64;
     type MiningEligibilityHardwareIndex = u64;
     type MiningEligibilityHardwareUptimePercentage = u32;
     // type MiningEligibilityHardwareAuditorAccountID = u64;
+    type Randomness = RandomnessCollectiveFlip;
 }
 
 pub type MiningEligibilityHardwareTestModule = Module<Test>;
+pub type RoamingOperatorRegistry = roaming_registry::Module<Test, roaming_registry::Instance1>;
 
 // This function basically just builds a genesis storage key/value store according to
 // our desired mockup.
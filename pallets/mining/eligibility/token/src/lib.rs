@@ -11,9 +11,11 @@ use frame_support::{
     decl_storage,
     ensure,
     traits::{
+        Currency,
         Get,
         Randomness,
     },
+    transactional,
     Parameter,
 };
 use frame_system::ensure_signed;
@@ -26,6 +28,7 @@ use sp_runtime::{
         One,
     },
     DispatchError,
+    DispatchResult,
 };
 use sp_std::prelude::*; // Imports Vec
 
@@ -50,7 +53,10 @@ pub trait Config:
     + mining_sampling_token::Config
 {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
+    type Randomness: Randomness<Self::Hash>;
     type MiningEligibilityTokenIndex: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
+    /// The deposit reserved from the creator of a mining eligibility token and held by its owner
+    type EntityDeposit: Get<BalanceOf<Self>>;
     type MiningEligibilityTokenCalculatedEligibility: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
     type MiningEligibilityTokenLockedPercentage: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
     // type MiningEligibilityTokenAuditorAccountID: Parameter + Member + AtLeast32Bit +
@@ -88,6 +94,8 @@ decl_event!(
         Created(AccountId, MiningEligibilityTokenIndex),
         /// A mining_eligibility_token is transferred. (from, to, mining_eligibility_token_id)
         Transferred(AccountId, AccountId, MiningEligibilityTokenIndex),
+        /// A mining_eligibility_token is removed. (owner, mining_eligibility_token_id)
+        Removed(AccountId, MiningEligibilityTokenIndex),
         // MiningEligibilityTokenResultSet(
         //   AccountId, MiningSettingTokenIndex, MiningEligibilityTokenIndex,
         //   MiningEligibilityTokenCalculatedEligibility, MiningEligibilityTokenLockedPercentage,
@@ -116,6 +124,9 @@ decl_storage! {
         /// Stores mining_eligibility_token owner
         pub MiningEligibilityTokenOwners get(fn mining_eligibility_token_owner): map hasher(opaque_blake2_256) T::MiningEligibilityTokenIndex => Option<T::AccountId>;
 
+        /// Get the deposit reserved for a mining eligibility token, which is held by its owner
+        pub MiningEligibilityTokenDeposits get(fn mining_eligibility_token_deposit): map hasher(opaque_blake2_256) T::MiningEligibilityTokenIndex => Option<BalanceOf<T>>;
+
         /// Stores mining_eligibility_token_result
         pub MiningEligibilityTokenResults get(fn mining_eligibility_token_eligibility_results): map hasher(opaque_blake2_256) (T::MiningSettingTokenIndex, T::MiningEligibilityTokenIndex) =>
             Option<MiningEligibilityTokenResult<
@@ -145,12 +156,17 @@ decl_module! {
             let sender = ensure_signed(origin)?;
             let mining_eligibility_token_id = Self::next_mining_eligibility_token_id()?;
 
+            // Reserve a deposit from the creator for the storage used by the mining eligibility token
+            let deposit = <T as Config>::EntityDeposit::get();
+            <roaming_operators::Module<T>>::reserve_entity_deposit(&sender, deposit)?;
+
             // Geneeligibility a random 128bit value
             let unique_id = Self::random_value(&sender);
 
             // Create and store mining_eligibility_token
             let mining_eligibility_token = MiningEligibilityToken(unique_id);
             Self::insert_mining_eligibility_token(&sender, mining_eligibility_token_id, mining_eligibility_token);
+            <MiningEligibilityTokenDeposits<T>>::insert(mining_eligibility_token_id, deposit);
 
             Self::deposit_event(RawEvent::Created(sender, mining_eligibility_token_id));
         }
@@ -162,11 +178,23 @@ decl_module! {
 
             ensure!(Self::mining_eligibility_token_owner(mining_eligibility_token_id) == Some(sender.clone()), "Only owner can transfer mining mining_eligibility_token");
 
-            Self::update_owner(&to, mining_eligibility_token_id);
+            Self::update_owner(&to, mining_eligibility_token_id)?;
 
             Self::deposit_event(RawEvent::Transferred(sender, to, mining_eligibility_token_id));
         }
 
+        /// Remove a mining_eligibility_token, returning its deposit to its owner
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(4, 6)]
+        pub fn remove(origin, mining_eligibility_token_id: T::MiningEligibilityTokenIndex) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(Self::mining_eligibility_token_owner(mining_eligibility_token_id) == Some(sender.clone()), "Only owner can remove mining mining_eligibility_token");
+
+            Self::remove_mining_eligibility_token(mining_eligibility_token_id);
+
+            Self::deposit_event(RawEvent::Removed(sender, mining_eligibility_token_id));
+        }
+
         // FIXME - implement this and fix the type errors and uncomment it in the integration tests
         // /// Calculate mining_eligibility_token_result
         // pub fn calculate_mining_eligibility_token_result(
@@ -416,6 +444,7 @@ decl_module! {
         }
 
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        #[transactional]
         pub fn assign_eligibility_to_configuration(
           origin,
           mining_eligibility_token_id: T::MiningEligibilityTokenIndex,
@@ -434,8 +463,7 @@ decl_module! {
                 "Only the configuration_token owner can assign itself a eligibility"
             );
 
-            Self::associate_token_eligibility_with_configuration(mining_eligibility_token_id, mining_setting_token_id)
-                .expect("Unable to associate eligibility with configuration");
+            Self::associate_token_eligibility_with_configuration(mining_eligibility_token_id, mining_setting_token_id)?;
 
             // Ensure that the given mining_eligibility_token_id already exists
             let token_eligibility = Self::mining_eligibility_token(mining_eligibility_token_id);
@@ -544,7 +572,7 @@ impl<T: Config> Module<T> {
 
     fn random_value(sender: &T::AccountId) -> [u8; 16] {
         let payload = (
-            T::Randomness::random(&[0]),
+            <T as Config>::Randomness::random(&[0]),
             sender,
             <frame_system::Module<T>>::extrinsic_index(),
             <frame_system::Module<T>>::block_number(),
@@ -571,7 +599,37 @@ impl<T: Config> Module<T> {
         <MiningEligibilityTokenOwners<T>>::insert(mining_eligibility_token_id, owner.clone());
     }
 
-    fn update_owner(to: &T::AccountId, mining_eligibility_token_id: T::MiningEligibilityTokenIndex) {
+    /// Remove a mining_eligibility_token and return its deposit to its owner
+    pub fn remove_mining_eligibility_token(mining_eligibility_token_id: T::MiningEligibilityTokenIndex) {
+        if let (Some(owner), Some(deposit)) = (
+            <MiningEligibilityTokenOwners<T>>::take(mining_eligibility_token_id),
+            <MiningEligibilityTokenDeposits<T>>::take(mining_eligibility_token_id),
+        ) {
+            <roaming_operators::Module<T>>::unreserve_entity_deposit(&owner, deposit);
+        }
+        <MiningEligibilityTokens<T>>::remove(mining_eligibility_token_id);
+        if let Some(mining_setting_token_id) = <TokenEligibilityConfiguration<T>>::take(mining_eligibility_token_id) {
+            <MiningEligibilityTokenResults<T>>::remove((mining_setting_token_id, mining_eligibility_token_id));
+            <TokenSettingEligibilities<T>>::mutate_exists(mining_setting_token_id, |eligibility_ids| {
+                if let Some(ids) = eligibility_ids.as_mut() {
+                    ids.retain(|id| *id != mining_eligibility_token_id);
+                }
+                if eligibility_ids.as_ref().map_or(false, |ids| ids.is_empty()) {
+                    *eligibility_ids = None;
+                }
+            });
+        }
+    }
+
+    fn update_owner(to: &T::AccountId, mining_eligibility_token_id: T::MiningEligibilityTokenIndex) -> DispatchResult {
+        // The deposit of the mining eligibility token moves with its ownership
+        if let (Some(from), Some(deposit)) = (
+            Self::mining_eligibility_token_owner(mining_eligibility_token_id),
+            Self::mining_eligibility_token_deposit(mining_eligibility_token_id),
+        ) {
+            <roaming_operators::Module<T>>::move_entity_deposit(&from, to, deposit)?;
+        }
         <MiningEligibilityTokenOwners<T>>::insert(mining_eligibility_token_id, to);
+        Ok(())
     }
 }
@@ -88,13 +88,26 @@ impl pallet_transaction_payment::Config for Test {
     type WeightToFee = IdentityFee<u64>;
 }
 // FIXME - remove this when figure out how to use these types within mining-speed-boost runtime module itself
-impl roaming_operators::Config for Test {
+impl roaming_registry::Config<roaming_registry::Instance1> for Test {
     type Currency = Balances;
+    type EntityDeposit = ();
     type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
     type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+impl roaming_operators::Config for Test {
+    type Currency = Balances;
+    type Event = ();
+    type RemovalPolicy = ();
+    type RoamingOperatorChildren = ();
     type RoamingOperatorIndex = u64;
+    type RoamingOperatorRegistry = RoamingOperatorRegistry;
 }
 impl mining_rates_token::Config for Test {
+    type EntityDeposit = ();
     type Event = ();
     type MiningRatesTokenIndex = u64;
     type MiningRatesTokenMaxLoyalty = u32;
@@ -102,32 +115,39 @@ impl mining_rates_token::Config for Test {
     type MiningRatesTokenTokenDOT = u32;
     type MiningRatesTokenTokenIOTA = u32;
     type MiningRatesTokenTokenMXC = u32;
+    type Randomness = RandomnessCollectiveFlip;
 }
 impl mining_sampling_token::Config for Test {
+    type EntityDeposit = ();
     type Event = ();
     type MiningSamplingTokenIndex = u64;
     type MiningSamplingTokenSampleLockedAmount = u64;
+    type Randomness = RandomnessCollectiveFlip;
 }
 impl mining_setting_token::Config for Test {
+    type EntityDeposit = ();
     type Event = ();
     // FIXME - restore when stop temporarily using roaming-operators
     // type Currency = Balances;
-    // type Randomness = RandomnessCollectiveFlip;
     type MiningSettingTokenIndex = u64;
     type MiningSettingTokenLockAmount = u64;
     // Mining Speed Boost Token Mining Config
     // FIXME - how to use this enum from std? (including importing `use std::str::FromStr;`)
     type MiningSettingTokenType = Vec<u8>;
+    type Randomness = RandomnessCollectiveFlip;
 }
 impl Config for Test {
+    type EntityDeposit = ();
     type Event = ();
     type MiningEligibilityTokenCalculatedEligibility = u64;
     type MiningEligibilityTokenIndex = u64;
     type MiningEligibilityTokenLockedPercentage = u32;
     // type MiningEligibilityTokenAuditorAccountID = u64;
+    type Randomness = RandomnessCollectiveFlip;
 }
 
 pub type MiningEligibilityTokenTestModule = Module<Test>;
+pub type RoamingOperatorRegistry = roaming_registry::Module<Test, roaming_registry::Instance1>;
 
 // This function basically just builds a genesis storage key/value store according to
 // our desired mockup.
@@ -11,9 +11,11 @@ use frame_support::{
     decl_storage,
     ensure,
     traits::{
+        Currency,
         Get,
         Randomness,
     },
+    transactional,
     Parameter,
 };
 use frame_system::ensure_signed;
@@ -26,6 +28,7 @@ use sp_runtime::{
         One,
     },
     DispatchError,
+    DispatchResult,
 };
 use sp_std::prelude::*; // Imports Vec
 
@@ -54,7 +57,10 @@ pub trait Config:
     + mining_claims_token::Config
 {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
+    type Randomness: Randomness<Self::Hash>;
     type MiningExecutionTokenIndex: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
+    /// The deposit reserved from the creator of a mining execution token and held by its owner
+    type EntityDeposit: Get<BalanceOf<Self>>;
     // type MiningExecutionTokenExecutorAccountID: Parameter
     //     + Member
     //     + AtLeast32Bit
@@ -91,6 +97,8 @@ decl_event!(
         Created(AccountId, MiningExecutionTokenIndex),
         /// A mining_execution_token is transferred. (from, to, mining_execution_token_id)
         Transferred(AccountId, AccountId, MiningExecutionTokenIndex),
+        /// A mining_execution_token is removed. (owner, mining_execution_token_id)
+        Removed(AccountId, MiningExecutionTokenIndex),
         MiningExecutionTokenExecutionResultSet(
             AccountId, MiningSettingTokenIndex, MiningExecutionTokenIndex,
             AccountId, BlockNumber, BlockNumber
@@ -113,6 +121,9 @@ decl_storage! {
         /// Stores mining_execution_token owner
         pub MiningExecutionTokenOwners get(fn mining_execution_token_owner): map hasher(opaque_blake2_256) T::MiningExecutionTokenIndex => Option<T::AccountId>;
 
+        /// Get the deposit reserved for a mining execution token, which is held by its owner
+        pub MiningExecutionTokenDeposits get(fn mining_execution_token_deposit): map hasher(opaque_blake2_256) T::MiningExecutionTokenIndex => Option<BalanceOf<T>>;
+
         /// Stores mining_execution_token_execution_result
         pub MiningExecutionTokenExecutionResults get(fn mining_execution_token_execution_results): map hasher(opaque_blake2_256) (T::MiningSettingTokenIndex, T::MiningExecutionTokenIndex) =>
             Option<MiningExecutionTokenExecutionResult<
@@ -141,12 +152,17 @@ decl_module! {
             let sender = ensure_signed(origin)?;
             let mining_execution_token_id = Self::next_mining_execution_token_id()?;
 
+            // Reserve a deposit from the creator for the storage used by the mining execution token
+            let deposit = <T as Config>::EntityDeposit::get();
+            <roaming_operators::Module<T>>::reserve_entity_deposit(&sender, deposit)?;
+
             // Generate a random 128bit value
             let unique_id = Self::random_value(&sender);
 
             // Create and store mining_execution_token
             let mining_execution_token = MiningExecutionToken(unique_id);
             Self::insert_mining_execution_token(&sender, mining_execution_token_id, mining_execution_token);
+            <MiningExecutionTokenDeposits<T>>::insert(mining_execution_token_id, deposit);
 
             Self::deposit_event(RawEvent::Created(sender, mining_execution_token_id));
         }
@@ -158,11 +174,23 @@ decl_module! {
 
             ensure!(Self::mining_execution_token_owner(mining_execution_token_id) == Some(sender.clone()), "Only owner can transfer mining mining_execution_token");
 
-            Self::update_owner(&to, mining_execution_token_id);
+            Self::update_owner(&to, mining_execution_token_id)?;
 
             Self::deposit_event(RawEvent::Transferred(sender, to, mining_execution_token_id));
         }
 
+        /// Remove a mining_execution_token, returning its deposit to its owner
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(4, 6)]
+        pub fn remove(origin, mining_execution_token_id: T::MiningExecutionTokenIndex) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(Self::mining_execution_token_owner(mining_execution_token_id) == Some(sender.clone()), "Only owner can remove mining mining_execution_token");
+
+            Self::remove_mining_execution_token(mining_execution_token_id);
+
+            Self::deposit_event(RawEvent::Removed(sender, mining_execution_token_id));
+        }
+
         /// Set mining_execution_token_execution_result
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
         pub fn set_mining_execution_token_execution_result(
@@ -285,6 +313,7 @@ decl_module! {
         }
 
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        #[transactional]
         pub fn assign_execution_to_configuration(
           origin,
           mining_execution_token_id: T::MiningExecutionTokenIndex,
@@ -303,8 +332,7 @@ decl_module! {
                 "Only the configuration_token owner can assign itself a execution"
             );
 
-            Self::associate_token_execution_with_configuration(mining_execution_token_id, mining_setting_token_id)
-                .expect("Unable to associate execution with configuration");
+            Self::associate_token_execution_with_configuration(mining_execution_token_id, mining_setting_token_id)?;
 
             // Ensure that the given mining_execution_token_id already exists
             let token_execution = Self::mining_execution_token(mining_execution_token_id);
@@ -529,7 +557,7 @@ impl<T: Config> Module<T> {
 
     fn random_value(sender: &T::AccountId) -> [u8; 16] {
         let payload = (
-            T::Randomness::random(&[0]),
+            <T as Config>::Randomness::random(&[0]),
             sender,
             <frame_system::Module<T>>::extrinsic_index(),
             <frame_system::Module<T>>::block_number(),
@@ -556,7 +584,37 @@ impl<T: Config> Module<T> {
         <MiningExecutionTokenOwners<T>>::insert(mining_execution_token_id, owner.clone());
     }
 
-    fn update_owner(to: &T::AccountId, mining_execution_token_id: T::MiningExecutionTokenIndex) {
+    /// Remove a mining_execution_token and return its deposit to its owner
+    pub fn remove_mining_execution_token(mining_execution_token_id: T::MiningExecutionTokenIndex) {
+        if let (Some(owner), Some(deposit)) = (
+            <MiningExecutionTokenOwners<T>>::take(mining_execution_token_id),
+            <MiningExecutionTokenDeposits<T>>::take(mining_execution_token_id),
+        ) {
+            <roaming_operators::Module<T>>::unreserve_entity_deposit(&owner, deposit);
+        }
+        <MiningExecutionTokens<T>>::remove(mining_execution_token_id);
+        if let Some(mining_setting_token_id) = <TokenExecutionConfiguration<T>>::take(mining_execution_token_id) {
+            <MiningExecutionTokenExecutionResults<T>>::remove((mining_setting_token_id, mining_execution_token_id));
+            <TokenSettingExecution<T>>::mutate_exists(mining_setting_token_id, |execution_ids| {
+                if let Some(ids) = execution_ids.as_mut() {
+                    ids.retain(|id| *id != mining_execution_token_id);
+                }
+                if execution_ids.as_ref().map_or(false, |ids| ids.is_empty()) {
+                    *execution_ids = None;
+                }
+            });
+        }
+    }
+
+    fn update_owner(to: &T::AccountId, mining_execution_token_id: T::MiningExecutionTokenIndex) -> DispatchResult {
+        // The deposit of the mining execution token moves with its ownership
+        if let (Some(from), Some(deposit)) = (
+            Self::mining_execution_token_owner(mining_execution_token_id),
+            Self::mining_execution_token_deposit(mining_execution_token_id),
+        ) {
+            <roaming_operators::Module<T>>::move_entity_deposit(&from, to, deposit)?;
+        }
         <MiningExecutionTokenOwners<T>>::insert(mining_execution_token_id, to);
+        Ok(())
     }
 }
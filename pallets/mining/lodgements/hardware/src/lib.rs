@@ -14,6 +14,7 @@ use frame_support::{
         Get,
         Randomness,
     },
+    transactional,
     Parameter,
 };
 use frame_system::ensure_signed;
@@ -52,6 +53,7 @@ pub trait Config:
     + mining_sampling_hardware::Config
 {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
+    type Randomness: Randomness<Self::Hash>;
     type MiningClaimsHardwareIndex: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
     type MiningClaimsHardwareClaimAmount: Parameter
         + Member
@@ -253,6 +255,7 @@ decl_module! {
         }
 
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        #[transactional]
         pub fn assign_claim_to_configuration(
           origin,
           mining_claims_hardware_id: T::MiningClaimsHardwareIndex,
@@ -271,8 +274,7 @@ decl_module! {
                 "Only the configuration_hardware owner can assign itself a claim"
             );
 
-            Self::associate_hardware_claim_with_configuration(mining_claims_hardware_id, mining_setting_hardware_id)
-                .expect("Unable to associate claim with configuration");
+            Self::associate_hardware_claim_with_configuration(mining_claims_hardware_id, mining_setting_hardware_id)?;
 
             // Ensure that the given mining_claims_hardware_id already exists
             let hardware_claim = Self::mining_claims_hardware(mining_claims_hardware_id);
@@ -398,7 +400,7 @@ impl<T: Config> Module<T> {
 
     fn random_value(sender: &T::AccountId) -> [u8; 16] {
         let payload = (
-            T::Randomness::random(&[0]),
+            <T as Config>::Randomness::random(&[0]),
             sender,
             <frame_system::Module<T>>::extrinsic_index(),
             <frame_system::Module<T>>::block_number(),
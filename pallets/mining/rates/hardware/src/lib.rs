@@ -11,6 +11,7 @@ use frame_support::{
     decl_storage,
     ensure,
     traits::{
+        Currency,
         Get,
         Randomness,
     },
@@ -26,6 +27,7 @@ use sp_runtime::{
         One,
     },
     DispatchError,
+    DispatchResult,
 };
 use sp_std::prelude::*; // Imports Vec
 
@@ -41,7 +43,10 @@ mod tests;
 /// The module's configuration trait.
 pub trait Config: frame_system::Config + roaming_operators::Config {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
+    type Randomness: Randomness<Self::Hash>;
     type MiningRatesHardwareIndex: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
+    /// The deposit reserved from the creator of a mining rates hardware and held by its owner
+    type EntityDeposit: Get<BalanceOf<Self>>;
     type MiningRatesHardwareSecure: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
     type MiningRatesHardwareInsecure: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
     type MiningRatesHardwareMaxHardware: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
@@ -99,6 +104,8 @@ decl_event!(
         Created(AccountId, MiningRatesHardwareIndex),
         /// A mining_rates_hardware is transferred. (from, to, mining_rates_hardware_id)
         Transferred(AccountId, AccountId, MiningRatesHardwareIndex),
+        /// A mining_rates_hardware is removed. (owner, mining_rates_hardware_id)
+        Removed(AccountId, MiningRatesHardwareIndex),
         MiningRatesHardwareSettingSet(
             AccountId, MiningRatesHardwareIndex, MiningRatesHardwareSecure,
             MiningRatesHardwareInsecure, MiningRatesHardwareMaxHardware,
@@ -121,6 +128,9 @@ decl_storage! {
         /// Stores mining_rates_hardware owner
         pub MiningRatesHardwareOwners get(fn mining_rates_hardware_owner): map hasher(opaque_blake2_256) T::MiningRatesHardwareIndex => Option<T::AccountId>;
 
+        /// Get the deposit reserved for a mining rates hardware, which is held by its owner
+        pub MiningRatesHardwareDeposits get(fn mining_rates_hardware_deposit): map hasher(opaque_blake2_256) T::MiningRatesHardwareIndex => Option<BalanceOf<T>>;
+
         /// Stores mining_rates_hardware_rates_config
         pub MiningRatesHardwareSettings get(fn mining_rates_hardware_rates_configs): map hasher(opaque_blake2_256) T::MiningRatesHardwareIndex =>
             Option<MiningRatesHardwareSetting<T::MiningRatesHardwareSecure,
@@ -143,12 +153,17 @@ decl_module! {
             let sender = ensure_signed(origin)?;
             let mining_rates_hardware_id = Self::next_mining_rates_hardware_id()?;
 
+            // Reserve a deposit from the creator for the storage used by the mining rates hardware
+            let deposit = <T as Config>::EntityDeposit::get();
+            <roaming_operators::Module<T>>::reserve_entity_deposit(&sender, deposit)?;
+
             // Generate a random 128bit value
             let unique_id = Self::random_value(&sender);
 
             // Create and store mining_rates_hardware
             let mining_rates_hardware = MiningRatesHardware(unique_id);
             Self::insert_mining_rates_hardware(&sender, mining_rates_hardware_id, mining_rates_hardware);
+            <MiningRatesHardwareDeposits<T>>::insert(mining_rates_hardware_id, deposit);
 
             Self::deposit_event(RawEvent::Created(sender, mining_rates_hardware_id));
         }
@@ -160,11 +175,23 @@ decl_module! {
 
             ensure!(Self::mining_rates_hardware_owner(mining_rates_hardware_id) == Some(sender.clone()), "Only owner can transfer mining mining_rates_hardware");
 
-            Self::update_owner(&to, mining_rates_hardware_id);
+            Self::update_owner(&to, mining_rates_hardware_id)?;
 
             Self::deposit_event(RawEvent::Transferred(sender, to, mining_rates_hardware_id));
         }
 
+        /// Remove a mining_rates_hardware, returning its deposit to its owner
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(2, 4)]
+        pub fn remove(origin, mining_rates_hardware_id: T::MiningRatesHardwareIndex) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(Self::mining_rates_hardware_owner(mining_rates_hardware_id) == Some(sender.clone()), "Only owner can remove mining mining_rates_hardware");
+
+            Self::remove_mining_rates_hardware(mining_rates_hardware_id);
+
+            Self::deposit_event(RawEvent::Removed(sender, mining_rates_hardware_id));
+        }
+
         /// Set mining_rates_hardware_rates_config
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
         pub fn set_mining_rates_hardware_rates_config(
@@ -328,7 +355,7 @@ impl<T: Config> Module<T> {
 
     fn random_value(sender: &T::AccountId) -> [u8; 16] {
         let payload = (
-            T::Randomness::random(&[0]),
+            <T as Config>::Randomness::random(&[0]),
             sender,
             <frame_system::Module<T>>::extrinsic_index(),
             <frame_system::Module<T>>::block_number(),
@@ -355,7 +382,27 @@ impl<T: Config> Module<T> {
         <MiningRatesHardwareOwners<T>>::insert(mining_rates_hardware_id, owner.clone());
     }
 
-    fn update_owner(to: &T::AccountId, mining_rates_hardware_id: T::MiningRatesHardwareIndex) {
+    /// Remove a mining_rates_hardware and return its deposit to its owner
+    pub fn remove_mining_rates_hardware(mining_rates_hardware_id: T::MiningRatesHardwareIndex) {
+        if let (Some(owner), Some(deposit)) = (
+            <MiningRatesHardwareOwners<T>>::take(mining_rates_hardware_id),
+            <MiningRatesHardwareDeposits<T>>::take(mining_rates_hardware_id),
+        ) {
+            <roaming_operators::Module<T>>::unreserve_entity_deposit(&owner, deposit);
+        }
+        <MiningRatesHardwares<T>>::remove(mining_rates_hardware_id);
+        <MiningRatesHardwareSettings<T>>::remove(mining_rates_hardware_id);
+    }
+
+    fn update_owner(to: &T::AccountId, mining_rates_hardware_id: T::MiningRatesHardwareIndex) -> DispatchResult {
+        // The deposit of the mining rates hardware moves with its ownership
+        if let (Some(from), Some(deposit)) = (
+            Self::mining_rates_hardware_owner(mining_rates_hardware_id),
+            Self::mining_rates_hardware_deposit(mining_rates_hardware_id),
+        ) {
+            <roaming_operators::Module<T>>::move_entity_deposit(&from, to, deposit)?;
+        }
         <MiningRatesHardwareOwners<T>>::insert(mining_rates_hardware_id, to);
+        Ok(())
     }
 }
@@ -11,6 +11,7 @@ use frame_support::{
     decl_storage,
     ensure,
     traits::{
+        Currency,
         Get,
         Randomness,
     },
@@ -26,6 +27,7 @@ use sp_runtime::{
         One,
     },
     DispatchError,
+    DispatchResult,
 };
 use sp_std::prelude::*; // Imports Vec
 
@@ -41,7 +43,10 @@ mod tests;
 /// The module's configuration trait.
 pub trait Config: frame_system::Config + roaming_operators::Config {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
+    type Randomness: Randomness<Self::Hash>;
     type MiningRatesTokenIndex: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
+    /// The deposit reserved from the creator of a mining rates token and held by its owner
+    type EntityDeposit: Get<BalanceOf<Self>>;
     type MiningRatesTokenTokenMXC: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
     type MiningRatesTokenTokenIOTA: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
     type MiningRatesTokenTokenDOT: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
@@ -81,6 +86,8 @@ decl_event!(
         Created(AccountId, MiningRatesTokenIndex),
         /// A mining_rates_token is transferred. (from, to, mining_rates_token_id)
         Transferred(AccountId, AccountId, MiningRatesTokenIndex),
+        /// A mining_rates_token is removed. (owner, mining_rates_token_id)
+        Removed(AccountId, MiningRatesTokenIndex),
         MiningRatesTokenSettingSet(
             AccountId, MiningRatesTokenIndex, MiningRatesTokenTokenMXC,
             MiningRatesTokenTokenIOTA, MiningRatesTokenTokenDOT,
@@ -101,6 +108,9 @@ decl_storage! {
         /// Stores mining_rates_token owner
         pub MiningRatesTokenOwners get(fn mining_rates_token_owner): map hasher(opaque_blake2_256) T::MiningRatesTokenIndex => Option<T::AccountId>;
 
+        /// Get the deposit reserved for a mining rates token, which is held by its owner
+        pub MiningRatesTokenDeposits get(fn mining_rates_token_deposit): map hasher(opaque_blake2_256) T::MiningRatesTokenIndex => Option<BalanceOf<T>>;
+
         /// Stores mining_rates_token_rates_config
         pub MiningRatesTokenSettings get(fn mining_rates_token_rates_configs): map hasher(opaque_blake2_256) T::MiningRatesTokenIndex =>
             Option<MiningRatesTokenSetting<T::MiningRatesTokenTokenMXC, T::MiningRatesTokenTokenIOTA,
@@ -120,12 +130,17 @@ decl_module! {
             let sender = ensure_signed(origin)?;
             let mining_rates_token_id = Self::next_mining_rates_token_id()?;
 
+            // Reserve a deposit from the creator for the storage used by the mining rates token
+            let deposit = <T as Config>::EntityDeposit::get();
+            <roaming_operators::Module<T>>::reserve_entity_deposit(&sender, deposit)?;
+
             // Generate a random 128bit value
             let unique_id = Self::random_value(&sender);
 
             // Create and store mining_rates_token
             let mining_rates_token = MiningRatesToken(unique_id);
             Self::insert_mining_rates_token(&sender, mining_rates_token_id, mining_rates_token);
+            <MiningRatesTokenDeposits<T>>::insert(mining_rates_token_id, deposit);
 
             Self::deposit_event(RawEvent::Created(sender, mining_rates_token_id));
         }
@@ -137,11 +152,23 @@ decl_module! {
 
             ensure!(Self::mining_rates_token_owner(mining_rates_token_id) == Some(sender.clone()), "Only owner can transfer mining mining_rates_token");
 
-            Self::update_owner(&to, mining_rates_token_id);
+            Self::update_owner(&to, mining_rates_token_id)?;
 
             Self::deposit_event(RawEvent::Transferred(sender, to, mining_rates_token_id));
         }
 
+        /// Remove a mining_rates_token, returning its deposit to its owner
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(2, 4)]
+        pub fn remove(origin, mining_rates_token_id: T::MiningRatesTokenIndex) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(Self::mining_rates_token_owner(mining_rates_token_id) == Some(sender.clone()), "Only owner can remove mining mining_rates_token");
+
+            Self::remove_mining_rates_token(mining_rates_token_id);
+
+            Self::deposit_event(RawEvent::Removed(sender, mining_rates_token_id));
+        }
+
         /// Set mining_rates_token_rates_config
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
         pub fn set_mining_rates_token_rates_config(
@@ -303,7 +330,7 @@ impl<T: Config> Module<T> {
 
     fn random_value(sender: &T::AccountId) -> [u8; 16] {
         let payload = (
-            T::Randomness::random(&[0]),
+            <T as Config>::Randomness::random(&[0]),
             sender,
             <frame_system::Module<T>>::extrinsic_index(),
             <frame_system::Module<T>>::block_number(),
@@ -330,7 +357,27 @@ impl<T: Config> Module<T> {
         <MiningRatesTokenOwners<T>>::insert(mining_rates_token_id, owner.clone());
     }
 
-    fn update_owner(to: &T::AccountId, mining_rates_token_id: T::MiningRatesTokenIndex) {
+    /// Remove a mining_rates_token and return its deposit to its owner
+    pub fn remove_mining_rates_token(mining_rates_token_id: T::MiningRatesTokenIndex) {
+        if let (Some(owner), Some(deposit)) = (
+            <MiningRatesTokenOwners<T>>::take(mining_rates_token_id),
+            <MiningRatesTokenDeposits<T>>::take(mining_rates_token_id),
+        ) {
+            <roaming_operators::Module<T>>::unreserve_entity_deposit(&owner, deposit);
+        }
+        <MiningRatesTokens<T>>::remove(mining_rates_token_id);
+        <MiningRatesTokenSettings<T>>::remove(mining_rates_token_id);
+    }
+
+    fn update_owner(to: &T::AccountId, mining_rates_token_id: T::MiningRatesTokenIndex) -> DispatchResult {
+        // The deposit of the mining rates token moves with its ownership
+        if let (Some(from), Some(deposit)) = (
+            Self::mining_rates_token_owner(mining_rates_token_id),
+            Self::mining_rates_token_deposit(mining_rates_token_id),
+        ) {
+            <roaming_operators::Module<T>>::move_entity_deposit(&from, to, deposit)?;
+        }
         <MiningRatesTokenOwners<T>>::insert(mining_rates_token_id, to);
+        Ok(())
     }
 }
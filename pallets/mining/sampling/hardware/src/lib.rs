@@ -11,9 +11,11 @@ use frame_support::{
     decl_storage,
     ensure,
     traits::{
+        Currency,
         Get,
         Randomness,
     },
+    transactional,
     Parameter,
 };
 use frame_system::ensure_signed;
@@ -26,6 +28,7 @@ use sp_runtime::{
         One,
     },
     DispatchError,
+    DispatchResult,
 };
 use sp_std::prelude::*; // Imports Vec
 
@@ -42,7 +45,10 @@ mod tests;
 /// The module's configuration trait.
 pub trait Config: frame_system::Config + roaming_operators::Config + mining_setting_hardware::Config {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
+    type Randomness: Randomness<Self::Hash>;
     type MiningSamplingHardwareIndex: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
+    /// The deposit reserved from the creator of a mining sampling hardware and held by its owner
+    type EntityDeposit: Get<BalanceOf<Self>>;
     type MiningSamplingHardwareSampleHardwareOnline: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
 }
 
@@ -73,6 +79,8 @@ decl_event!(
         Created(AccountId, MiningSamplingHardwareIndex),
         /// A mining_samplings_hardware is transferred. (from, to, mining_samplings_hardware_id)
         Transferred(AccountId, AccountId, MiningSamplingHardwareIndex),
+        /// A mining_samplings_hardware is removed. (owner, mining_samplings_hardware_id)
+        Removed(AccountId, MiningSamplingHardwareIndex),
         MiningSamplingHardwareSettingSet(
             AccountId, MiningSettingHardwareIndex, MiningSamplingHardwareIndex,
             BlockNumber, MiningSamplingHardwareSampleHardwareOnline
@@ -95,6 +103,9 @@ decl_storage! {
         /// Stores mining_samplings_hardware owner
         pub MiningSamplingHardwareOwners get(fn mining_samplings_hardware_owner): map hasher(opaque_blake2_256) T::MiningSamplingHardwareIndex => Option<T::AccountId>;
 
+        /// Get the deposit reserved for a mining sampling hardware, which is held by its owner
+        pub MiningSamplingHardwareDeposits get(fn mining_samplings_hardware_deposit): map hasher(opaque_blake2_256) T::MiningSamplingHardwareIndex => Option<BalanceOf<T>>;
+
         /// Stores mining_samplings_hardware_samplings_config
         pub MiningSamplingHardwareSettings get(fn mining_samplings_hardware_samplings_configs): map hasher(opaque_blake2_256) (T::MiningSettingHardwareIndex, T::MiningSamplingHardwareIndex) =>
             Option<MiningSamplingHardwareSetting<
@@ -122,12 +133,17 @@ decl_module! {
             let sender = ensure_signed(origin)?;
             let mining_samplings_hardware_id = Self::next_mining_samplings_hardware_id()?;
 
+            // Reserve a deposit from the creator for the storage used by the mining sampling hardware
+            let deposit = <T as Config>::EntityDeposit::get();
+            <roaming_operators::Module<T>>::reserve_entity_deposit(&sender, deposit)?;
+
             // Generate a random 128bit value
             let unique_id = Self::random_value(&sender);
 
             // Create and store mining_samplings_hardware
             let mining_samplings_hardware = MiningSamplingHardware(unique_id);
             Self::insert_mining_samplings_hardware(&sender, mining_samplings_hardware_id, mining_samplings_hardware);
+            <MiningSamplingHardwareDeposits<T>>::insert(mining_samplings_hardware_id, deposit);
 
             Self::deposit_event(RawEvent::Created(sender, mining_samplings_hardware_id));
         }
@@ -139,11 +155,23 @@ decl_module! {
 
             ensure!(Self::mining_samplings_hardware_owner(mining_samplings_hardware_id) == Some(sender.clone()), "Only owner can transfer mining mining_samplings_hardware");
 
-            Self::update_owner(&to, mining_samplings_hardware_id);
+            Self::update_owner(&to, mining_samplings_hardware_id)?;
 
             Self::deposit_event(RawEvent::Transferred(sender, to, mining_samplings_hardware_id));
         }
 
+        /// Remove a mining_samplings_hardware, returning its deposit to its owner
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(4, 6)]
+        pub fn remove(origin, mining_samplings_hardware_id: T::MiningSamplingHardwareIndex) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(Self::mining_samplings_hardware_owner(mining_samplings_hardware_id) == Some(sender.clone()), "Only owner can remove mining mining_samplings_hardware");
+
+            Self::remove_mining_samplings_hardware(mining_samplings_hardware_id);
+
+            Self::deposit_event(RawEvent::Removed(sender, mining_samplings_hardware_id));
+        }
+
         /// Set mining_samplings_hardware_samplings_config
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
         pub fn set_mining_samplings_hardware_samplings_config(
@@ -223,6 +251,7 @@ decl_module! {
         }
 
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        #[transactional]
         pub fn assign_sampling_to_configuration(
           origin,
           mining_samplings_hardware_id: T::MiningSamplingHardwareIndex,
@@ -241,8 +270,10 @@ decl_module! {
                 "Only the configuration_hardware owner can assign itself a sampling"
             );
 
-            Self::associate_hardware_sampling_with_configuration(mining_samplings_hardware_id, mining_setting_hardware_id)
-                .expect("Unable to associate sampling with configuration");
+            Self::associate_hardware_sampling_with_configuration(
+                mining_samplings_hardware_id,
+                mining_setting_hardware_id,
+            )?;
 
             // Ensure that the given mining_samplings_hardware_id already exists
             let hardware_sampling = Self::mining_samplings_hardware(mining_samplings_hardware_id);
@@ -352,7 +383,7 @@ impl<T: Config> Module<T> {
 
     fn random_value(sender: &T::AccountId) -> [u8; 16] {
         let payload = (
-            T::Randomness::random(&[0]),
+            <T as Config>::Randomness::random(&[0]),
             sender,
             <frame_system::Module<T>>::extrinsic_index(),
             <frame_system::Module<T>>::block_number(),
@@ -379,7 +410,38 @@ impl<T: Config> Module<T> {
         <MiningSamplingHardwareOwners<T>>::insert(mining_samplings_hardware_id, owner.clone());
     }
 
-    fn update_owner(to: &T::AccountId, mining_samplings_hardware_id: T::MiningSamplingHardwareIndex) {
+    /// Remove a mining_samplings_hardware and return its deposit to its owner
+    pub fn remove_mining_samplings_hardware(mining_samplings_hardware_id: T::MiningSamplingHardwareIndex) {
+        if let (Some(owner), Some(deposit)) = (
+            <MiningSamplingHardwareOwners<T>>::take(mining_samplings_hardware_id),
+            <MiningSamplingHardwareDeposits<T>>::take(mining_samplings_hardware_id),
+        ) {
+            <roaming_operators::Module<T>>::unreserve_entity_deposit(&owner, deposit);
+        }
+        <MiningSamplingHardwares<T>>::remove(mining_samplings_hardware_id);
+        if let Some(mining_setting_hardware_id) = <HardwareSamplingConfiguration<T>>::take(mining_samplings_hardware_id)
+        {
+            <MiningSamplingHardwareSettings<T>>::remove((mining_setting_hardware_id, mining_samplings_hardware_id));
+            <HardwareSettingSamplings<T>>::mutate_exists(mining_setting_hardware_id, |sampling_ids| {
+                if let Some(ids) = sampling_ids.as_mut() {
+                    ids.retain(|id| *id != mining_samplings_hardware_id);
+                }
+                if sampling_ids.as_ref().map_or(false, |ids| ids.is_empty()) {
+                    *sampling_ids = None;
+                }
+            });
+        }
+    }
+
+    fn update_owner(to: &T::AccountId, mining_samplings_hardware_id: T::MiningSamplingHardwareIndex) -> DispatchResult {
+        // The deposit of the mining sampling hardware moves with its ownership
+        if let (Some(from), Some(deposit)) = (
+            Self::mining_samplings_hardware_owner(mining_samplings_hardware_id),
+            Self::mining_samplings_hardware_deposit(mining_samplings_hardware_id),
+        ) {
+            <roaming_operators::Module<T>>::move_entity_deposit(&from, to, deposit)?;
+        }
         <MiningSamplingHardwareOwners<T>>::insert(mining_samplings_hardware_id, to);
+        Ok(())
     }
 }
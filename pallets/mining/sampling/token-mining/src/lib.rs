@@ -14,6 +14,7 @@ use frame_support::{
         Get,
         Randomness,
     },
+    transactional,
     Parameter,
 };
 use frame_system::ensure_signed;
@@ -44,6 +45,7 @@ pub trait Config:
     frame_system::Config + roaming_operators::Config + mining_setting_token::Config
 {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
+    type Randomness: Randomness<Self::Hash>;
     type MiningSamplingTokenIndex: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
     type MiningSamplingTokenSampleLockedAmount: Parameter
         + Member
@@ -230,6 +232,7 @@ decl_module! {
         }
 
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        #[transactional]
         pub fn assign_sampling_to_configuration(
           origin,
           mining_samplings_token_id: T::MiningSamplingTokenIndex,
@@ -248,8 +251,7 @@ decl_module! {
                 "Only the configuration_token owner can assign itself a sampling"
             );
 
-            Self::associate_token_sampling_with_configuration(mining_samplings_token_id, mining_setting_token_id)
-                .expect("Unable to associate sampling with configuration");
+            Self::associate_token_sampling_with_configuration(mining_samplings_token_id, mining_setting_token_id)?;
 
             // Ensure that the given mining_samplings_token_id already exists
             let token_sampling = Self::mining_samplings_token(mining_samplings_token_id);
@@ -370,7 +372,7 @@ impl<T: Config> Module<T> {
 
     fn random_value(sender: &T::AccountId) -> [u8; 16] {
         let payload = (
-            T::Randomness::random(&[0]),
+            <T as Config>::Randomness::random(&[0]),
             sender,
             <frame_system::Module<T>>::extrinsic_index(),
             <frame_system::Module<T>>::block_number(),
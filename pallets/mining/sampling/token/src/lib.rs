@@ -11,9 +11,11 @@ use frame_support::{
     decl_storage,
     ensure,
     traits::{
+        Currency,
         Get,
         Randomness,
     },
+    transactional,
     Parameter,
 };
 use frame_system::ensure_signed;
@@ -26,6 +28,7 @@ use sp_runtime::{
         One,
     },
     DispatchError,
+    DispatchResult,
 };
 use sp_std::prelude::*; // Imports Vec
 
@@ -42,7 +45,10 @@ mod tests;
 /// The module's configuration trait.
 pub trait Config: frame_system::Config + roaming_operators::Config + mining_setting_token::Config {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
+    type Randomness: Randomness<Self::Hash>;
     type MiningSamplingTokenIndex: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
+    /// The deposit reserved from the creator of a mining sampling token and held by its owner
+    type EntityDeposit: Get<BalanceOf<Self>>;
     type MiningSamplingTokenSampleLockedAmount: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
 }
 
@@ -73,6 +79,8 @@ decl_event!(
         Created(AccountId, MiningSamplingTokenIndex),
         /// A mining_samplings_token is transferred. (from, to, mining_samplings_token_id)
         Transferred(AccountId, AccountId, MiningSamplingTokenIndex),
+        /// A mining_samplings_token is removed. (owner, mining_samplings_token_id)
+        Removed(AccountId, MiningSamplingTokenIndex),
         MiningSamplingTokenSettingSet(
             AccountId, MiningSettingTokenIndex, MiningSamplingTokenIndex,
             BlockNumber, MiningSamplingTokenSampleLockedAmount
@@ -95,6 +103,9 @@ decl_storage! {
         /// Stores mining_samplings_token owner
         pub MiningSamplingTokenOwners get(fn mining_samplings_token_owner): map hasher(opaque_blake2_256) T::MiningSamplingTokenIndex => Option<T::AccountId>;
 
+        /// Get the deposit reserved for a mining sampling token, which is held by its owner
+        pub MiningSamplingTokenDeposits get(fn mining_samplings_token_deposit): map hasher(opaque_blake2_256) T::MiningSamplingTokenIndex => Option<BalanceOf<T>>;
+
         /// Stores mining_samplings_token_samplings_config
         pub MiningSamplingTokenSettings get(fn mining_samplings_token_samplings_configs): map hasher(opaque_blake2_256) (T::MiningSettingTokenIndex, T::MiningSamplingTokenIndex) =>
             Option<MiningSamplingTokenSetting<
@@ -122,12 +133,17 @@ decl_module! {
             let sender = ensure_signed(origin)?;
             let mining_samplings_token_id = Self::next_mining_samplings_token_id()?;
 
+            // Reserve a deposit from the creator for the storage used by the mining sampling token
+            let deposit = <T as Config>::EntityDeposit::get();
+            <roaming_operators::Module<T>>::reserve_entity_deposit(&sender, deposit)?;
+
             // Generate a random 128bit value
             let unique_id = Self::random_value(&sender);
 
             // Create and store mining_samplings_token
             let mining_samplings_token = MiningSamplingToken(unique_id);
             Self::insert_mining_samplings_token(&sender, mining_samplings_token_id, mining_samplings_token);
+            <MiningSamplingTokenDeposits<T>>::insert(mining_samplings_token_id, deposit);
 
             Self::deposit_event(RawEvent::Created(sender, mining_samplings_token_id));
         }
@@ -139,11 +155,23 @@ decl_module! {
 
             ensure!(Self::mining_samplings_token_owner(mining_samplings_token_id) == Some(sender.clone()), "Only owner can transfer mining mining_samplings_token");
 
-            Self::update_owner(&to, mining_samplings_token_id);
+            Self::update_owner(&to, mining_samplings_token_id)?;
 
             Self::deposit_event(RawEvent::Transferred(sender, to, mining_samplings_token_id));
         }
 
+        /// Remove a mining_samplings_token, returning its deposit to its owner
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(4, 6)]
+        pub fn remove(origin, mining_samplings_token_id: T::MiningSamplingTokenIndex) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(Self::mining_samplings_token_owner(mining_samplings_token_id) == Some(sender.clone()), "Only owner can remove mining mining_samplings_token");
+
+            Self::remove_mining_samplings_token(mining_samplings_token_id);
+
+            Self::deposit_event(RawEvent::Removed(sender, mining_samplings_token_id));
+        }
+
         /// Set mining_samplings_token_samplings_config
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
         pub fn set_mining_samplings_token_samplings_config(
@@ -223,6 +251,7 @@ decl_module! {
         }
 
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        #[transactional]
         pub fn assign_sampling_to_configuration(
           origin,
           mining_samplings_token_id: T::MiningSamplingTokenIndex,
@@ -241,8 +270,7 @@ decl_module! {
                 "Only the configuration_token owner can assign itself a sampling"
             );
 
-            Self::associate_token_sampling_with_configuration(mining_samplings_token_id, mining_setting_token_id)
-                .expect("Unable to associate sampling with configuration");
+            Self::associate_token_sampling_with_configuration(mining_samplings_token_id, mining_setting_token_id)?;
 
             // Ensure that the given mining_samplings_token_id already exists
             let token_sampling = Self::mining_samplings_token(mining_samplings_token_id);
@@ -349,7 +377,7 @@ impl<T: Config> Module<T> {
 
     fn random_value(sender: &T::AccountId) -> [u8; 16] {
         let payload = (
-            T::Randomness::random(&[0]),
+            <T as Config>::Randomness::random(&[0]),
             sender,
             <frame_system::Module<T>>::extrinsic_index(),
             <frame_system::Module<T>>::block_number(),
@@ -376,7 +404,37 @@ impl<T: Config> Module<T> {
         <MiningSamplingTokenOwners<T>>::insert(mining_samplings_token_id, owner.clone());
     }
 
-    fn update_owner(to: &T::AccountId, mining_samplings_token_id: T::MiningSamplingTokenIndex) {
+    /// Remove a mining_samplings_token and return its deposit to its owner
+    pub fn remove_mining_samplings_token(mining_samplings_token_id: T::MiningSamplingTokenIndex) {
+        if let (Some(owner), Some(deposit)) = (
+            <MiningSamplingTokenOwners<T>>::take(mining_samplings_token_id),
+            <MiningSamplingTokenDeposits<T>>::take(mining_samplings_token_id),
+        ) {
+            <roaming_operators::Module<T>>::unreserve_entity_deposit(&owner, deposit);
+        }
+        <MiningSamplingTokens<T>>::remove(mining_samplings_token_id);
+        if let Some(mining_setting_token_id) = <TokenSamplingConfiguration<T>>::take(mining_samplings_token_id) {
+            <MiningSamplingTokenSettings<T>>::remove((mining_setting_token_id, mining_samplings_token_id));
+            <TokenSettingSamplings<T>>::mutate_exists(mining_setting_token_id, |sampling_ids| {
+                if let Some(ids) = sampling_ids.as_mut() {
+                    ids.retain(|id| *id != mining_samplings_token_id);
+                }
+                if sampling_ids.as_ref().map_or(false, |ids| ids.is_empty()) {
+                    *sampling_ids = None;
+                }
+            });
+        }
+    }
+
+    fn update_owner(to: &T::AccountId, mining_samplings_token_id: T::MiningSamplingTokenIndex) -> DispatchResult {
+        // The deposit of the mining sampling token moves with its ownership
+        if let (Some(from), Some(deposit)) = (
+            Self::mining_samplings_token_owner(mining_samplings_token_id),
+            Self::mining_samplings_token_deposit(mining_samplings_token_id),
+        ) {
+            <roaming_operators::Module<T>>::move_entity_deposit(&from, to, deposit)?;
+        }
         <MiningSamplingTokenOwners<T>>::insert(mining_samplings_token_id, to);
+        Ok(())
     }
 }
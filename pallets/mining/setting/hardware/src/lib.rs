@@ -11,6 +11,7 @@ use frame_support::{
     decl_storage,
     ensure,
     traits::{
+        Currency,
         Get,
         Randomness,
     },
@@ -26,6 +27,7 @@ use sp_runtime::{
         One,
     },
     DispatchError,
+    DispatchResult,
 };
 use sp_std::prelude::*; // Imports Vec
 
@@ -41,7 +43,10 @@ mod tests;
 /// The module's configuration trait.
 pub trait Config: frame_system::Config + roaming_operators::Config {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
+    type Randomness: Randomness<Self::Hash>;
     type MiningSettingHardwareIndex: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
+    /// The deposit reserved from the creator of a mining setting hardware and held by its owner
+    type EntityDeposit: Get<BalanceOf<Self>>;
     // Mining Speed Boost Hardware Mining Config
     type MiningSettingHardwareSecure: Parameter + Member + Default + Copy; // bool
     type MiningSettingHardwareType: Parameter + Member + Default;
@@ -85,6 +90,8 @@ decl_event!(
         Created(AccountId, MiningSettingHardwareIndex),
         /// A mining_setting_hardware is transferred. (from, to, mining_setting_hardware_id)
         Transferred(AccountId, AccountId, MiningSettingHardwareIndex),
+        /// A mining_setting_hardware is removed. (owner, mining_setting_hardware_id)
+        Removed(AccountId, MiningSettingHardwareIndex),
         MiningSettingHardwareSettingSet(
           AccountId, MiningSettingHardwareIndex, MiningSettingHardwareSecure,
           MiningSettingHardwareType, MiningSettingHardwareID,
@@ -105,6 +112,9 @@ decl_storage! {
         /// Stores mining_setting_hardware owner
         pub MiningSettingHardwareOwners get(fn mining_setting_hardware_owner): map hasher(opaque_blake2_256) T::MiningSettingHardwareIndex => Option<T::AccountId>;
 
+        /// Get the deposit reserved for a mining setting hardware, which is held by its owner
+        pub MiningSettingHardwareDeposits get(fn mining_setting_hardware_deposit): map hasher(opaque_blake2_256) T::MiningSettingHardwareIndex => Option<BalanceOf<T>>;
+
         /// Stores mining_setting_hardware_hardware_config
         pub MiningSettingHardwareSettings get(fn mining_setting_hardware_hardware_configs): map hasher(opaque_blake2_256) T::MiningSettingHardwareIndex =>
             Option<MiningSettingHardwareSetting<T::MiningSettingHardwareSecure, T::MiningSettingHardwareType,
@@ -125,12 +135,17 @@ decl_module! {
             let sender = ensure_signed(origin)?;
             let mining_setting_hardware_id = Self::next_mining_setting_hardware_id()?;
 
+            // Reserve a deposit from the creator for the storage used by the mining setting hardware
+            let deposit = <T as Config>::EntityDeposit::get();
+            <roaming_operators::Module<T>>::reserve_entity_deposit(&sender, deposit)?;
+
             // Generate a random 128bit value
             let unique_id = Self::random_value(&sender);
 
             // Create and store mining_setting_hardware
             let mining_setting_hardware = MiningSettingHardware(unique_id);
             Self::insert_mining_setting_hardware(&sender, mining_setting_hardware_id, mining_setting_hardware);
+            <MiningSettingHardwareDeposits<T>>::insert(mining_setting_hardware_id, deposit);
 
             Self::deposit_event(RawEvent::Created(sender, mining_setting_hardware_id));
         }
@@ -142,11 +157,23 @@ decl_module! {
 
             ensure!(Self::mining_setting_hardware_owner(mining_setting_hardware_id) == Some(sender.clone()), "Only owner can transfer mining mining_setting_hardware");
 
-            Self::update_owner(&to, mining_setting_hardware_id);
+            Self::update_owner(&to, mining_setting_hardware_id)?;
 
             Self::deposit_event(RawEvent::Transferred(sender, to, mining_setting_hardware_id));
         }
 
+        /// Remove a mining_setting_hardware, returning its deposit to its owner
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(2, 4)]
+        pub fn remove(origin, mining_setting_hardware_id: T::MiningSettingHardwareIndex) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(Self::mining_setting_hardware_owner(mining_setting_hardware_id) == Some(sender.clone()), "Only owner can remove mining mining_setting_hardware");
+
+            Self::remove_mining_setting_hardware(mining_setting_hardware_id);
+
+            Self::deposit_event(RawEvent::Removed(sender, mining_setting_hardware_id));
+        }
+
         /// Set mining_setting_hardware_hardware_config
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
         pub fn set_mining_setting_hardware_hardware_config(
@@ -315,7 +342,7 @@ impl<T: Config> Module<T> {
 
     fn random_value(sender: &T::AccountId) -> [u8; 16] {
         let payload = (
-            T::Randomness::random(&[0]),
+            <T as Config>::Randomness::random(&[0]),
             sender,
             <frame_system::Module<T>>::extrinsic_index(),
             <frame_system::Module<T>>::block_number(),
@@ -342,7 +369,27 @@ impl<T: Config> Module<T> {
         <MiningSettingHardwareOwners<T>>::insert(mining_setting_hardware_id, owner.clone());
     }
 
-    fn update_owner(to: &T::AccountId, mining_setting_hardware_id: T::MiningSettingHardwareIndex) {
+    /// Remove a mining_setting_hardware and return its deposit to its owner
+    pub fn remove_mining_setting_hardware(mining_setting_hardware_id: T::MiningSettingHardwareIndex) {
+        if let (Some(owner), Some(deposit)) = (
+            <MiningSettingHardwareOwners<T>>::take(mining_setting_hardware_id),
+            <MiningSettingHardwareDeposits<T>>::take(mining_setting_hardware_id),
+        ) {
+            <roaming_operators::Module<T>>::unreserve_entity_deposit(&owner, deposit);
+        }
+        <MiningSettingHardwares<T>>::remove(mining_setting_hardware_id);
+        <MiningSettingHardwareSettings<T>>::remove(mining_setting_hardware_id);
+    }
+
+    fn update_owner(to: &T::AccountId, mining_setting_hardware_id: T::MiningSettingHardwareIndex) -> DispatchResult {
+        // The deposit of the mining setting hardware moves with its ownership
+        if let (Some(from), Some(deposit)) = (
+            Self::mining_setting_hardware_owner(mining_setting_hardware_id),
+            Self::mining_setting_hardware_deposit(mining_setting_hardware_id),
+        ) {
+            <roaming_operators::Module<T>>::move_entity_deposit(&from, to, deposit)?;
+        }
         <MiningSettingHardwareOwners<T>>::insert(mining_setting_hardware_id, to);
+        Ok(())
     }
 }
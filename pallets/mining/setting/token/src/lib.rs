@@ -27,6 +27,7 @@ use sp_runtime::{
         One,
     },
     DispatchError,
+    DispatchResult,
 };
 use sp_std::prelude::*; // Imports Vec
 
@@ -42,7 +43,10 @@ mod tests;
 /// The module's configuration trait.
 pub trait Config: frame_system::Config + roaming_operators::Config {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
+    type Randomness: Randomness<Self::Hash>;
     type MiningSettingTokenIndex: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
+    /// The deposit reserved from the creator of a mining setting token and held by its owner
+    type EntityDeposit: Get<BalanceOf<Self>>;
     // Mining Speed Boost Token Mining Config
     type MiningSettingTokenType: Parameter + Member + Default;
     type MiningSettingTokenLockAmount: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
@@ -85,6 +89,8 @@ decl_event!(
         Created(AccountId, MiningSettingTokenIndex),
         /// A mining_setting_token is transferred. (from, to, mining_setting_token_id)
         Transferred(AccountId, AccountId, MiningSettingTokenIndex),
+        /// A mining_setting_token is removed. (owner, mining_setting_token_id)
+        Removed(AccountId, MiningSettingTokenIndex),
         MiningSettingTokenSettingSet(
             AccountId, MiningSettingTokenIndex, MiningSettingTokenType, Balance, BlockNumber, BlockNumber
         ),
@@ -107,6 +113,9 @@ decl_storage! {
         /// Stores mining_setting_token owner
         pub MiningSettingTokenOwners get(fn mining_setting_token_owner): map hasher(opaque_blake2_256) T::MiningSettingTokenIndex => Option<T::AccountId>;
 
+        /// Get the deposit reserved for a mining setting token, which is held by its owner
+        pub MiningSettingTokenDeposits get(fn mining_setting_token_deposit): map hasher(opaque_blake2_256) T::MiningSettingTokenIndex => Option<BalanceOf<T>>;
+
         /// Stores mining_setting_token_token_setting
         pub MiningSettingTokenSettings get(fn mining_setting_token_token_settings): map hasher(opaque_blake2_256) T::MiningSettingTokenIndex =>
             Option<MiningSettingTokenSetting<T::MiningSettingTokenType, BalanceOf<T>, T::BlockNumber, T::BlockNumber>>;
@@ -129,12 +138,17 @@ decl_module! {
             let sender = ensure_signed(origin)?;
             let mining_setting_token_id = Self::next_mining_setting_token_id()?;
 
+            // Reserve a deposit from the creator for the storage used by the mining setting token
+            let deposit = <T as Config>::EntityDeposit::get();
+            <roaming_operators::Module<T>>::reserve_entity_deposit(&sender, deposit)?;
+
             // Generate a random 128bit value
             let unique_id = Self::random_value(&sender);
 
             // Create and store mining_setting_token
             let mining_setting_token = MiningSettingToken(unique_id);
             Self::insert_mining_setting_token(&sender, mining_setting_token_id, mining_setting_token);
+            <MiningSettingTokenDeposits<T>>::insert(mining_setting_token_id, deposit);
 
             Self::deposit_event(RawEvent::Created(sender, mining_setting_token_id));
         }
@@ -146,11 +160,23 @@ decl_module! {
 
             ensure!(Self::mining_setting_token_owner(mining_setting_token_id) == Some(sender.clone()), "Only owner can transfer mining mining_setting_token");
 
-            Self::update_owner(&to, mining_setting_token_id);
+            Self::update_owner(&to, mining_setting_token_id)?;
 
             Self::deposit_event(RawEvent::Transferred(sender, to, mining_setting_token_id));
         }
 
+        /// Remove a mining_setting_token, returning its deposit to its owner
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(2, 5)]
+        pub fn remove(origin, mining_setting_token_id: T::MiningSettingTokenIndex) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(Self::mining_setting_token_owner(mining_setting_token_id) == Some(sender.clone()), "Only owner can remove mining mining_setting_token");
+
+            Self::remove_mining_setting_token(mining_setting_token_id);
+
+            Self::deposit_event(RawEvent::Removed(sender, mining_setting_token_id));
+        }
+
         /// Set mining_setting_token_token_setting
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
         pub fn set_mining_setting_token_token_setting(
@@ -403,7 +429,7 @@ impl<T: Config> Module<T> {
 
     fn random_value(sender: &T::AccountId) -> [u8; 16] {
         let payload = (
-            T::Randomness::random(&[0]),
+            <T as Config>::Randomness::random(&[0]),
             sender,
             <frame_system::Module<T>>::extrinsic_index(),
             <frame_system::Module<T>>::block_number(),
@@ -430,7 +456,28 @@ impl<T: Config> Module<T> {
         <MiningSettingTokenOwners<T>>::insert(mining_setting_token_id, owner.clone());
     }
 
-    fn update_owner(to: &T::AccountId, mining_setting_token_id: T::MiningSettingTokenIndex) {
+    /// Remove a mining_setting_token and return its deposit to its owner
+    pub fn remove_mining_setting_token(mining_setting_token_id: T::MiningSettingTokenIndex) {
+        if let (Some(owner), Some(deposit)) = (
+            <MiningSettingTokenOwners<T>>::take(mining_setting_token_id),
+            <MiningSettingTokenDeposits<T>>::take(mining_setting_token_id),
+        ) {
+            <roaming_operators::Module<T>>::unreserve_entity_deposit(&owner, deposit);
+        }
+        <MiningSettingTokens<T>>::remove(mining_setting_token_id);
+        <MiningSettingTokenSettings<T>>::remove(mining_setting_token_id);
+        <MiningSettingTokenRequirementsSettings<T>>::remove(mining_setting_token_id);
+    }
+
+    fn update_owner(to: &T::AccountId, mining_setting_token_id: T::MiningSettingTokenIndex) -> DispatchResult {
+        // The deposit of the mining setting token moves with its ownership
+        if let (Some(from), Some(deposit)) = (
+            Self::mining_setting_token_owner(mining_setting_token_id),
+            Self::mining_setting_token_deposit(mining_setting_token_id),
+        ) {
+            <roaming_operators::Module<T>>::move_entity_deposit(&from, to, deposit)?;
+        }
         <MiningSettingTokenOwners<T>>::insert(mining_setting_token_id, to);
+        Ok(())
     }
 }
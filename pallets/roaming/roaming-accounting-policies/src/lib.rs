@@ -6,27 +6,48 @@ use codec::{
 };
 use frame_support::{
     debug,
+    decl_error,
     decl_event,
     decl_module,
     decl_storage,
     ensure,
+    storage::migration::{
+        get_storage_value,
+        take_storage_value,
+    },
     traits::{
         Currency,
+        ExistenceRequirement,
         Get,
-        Randomness,
     },
+    transactional,
+    weights::Weight,
     Parameter,
 };
 use frame_system::ensure_signed;
-use sp_io::hashing::blake2_128;
+use sp_io::hashing::blake2_256;
 use sp_runtime::{
     traits::{
         AtLeast32Bit,
         Bounded,
         Member,
         One,
+        Saturating,
+        Zero,
     },
     DispatchError,
+    DispatchResult,
+};
+use roaming_operators::{
+    without_roaming_entity,
+    RoamingAccountingPolicyEntity,
+    RoamingAuthority,
+    RoamingEntityChildren,
+    RoamingNetworkEntity,
+};
+use roaming_registry::{
+    RoamingRegistry,
+    RoamingTradable,
 };
 use sp_std::prelude::*; // Imports Vec
 
@@ -40,69 +61,163 @@ mod tests;
 pub trait Config: frame_system::Config + roaming_operators::Config + roaming_networks::Config {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
     type RoamingAccountingPolicyIndex: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
-    type RoamingAccountingPolicyType: Parameter + Member + Default;
+    /// The registry that holds the roaming accounting policies and their owners
+    type RoamingAccountingPolicyRegistry: RoamingRegistry<Self::AccountId, Self::RoamingAccountingPolicyIndex>;
+    /// The pallets that hold the children of roaming accounting policys
+    type RoamingAccountingPolicyChildren: RoamingEntityChildren<
+        RoamingAccountingPolicyEntity,
+        Self::RoamingAccountingPolicyIndex,
+    >;
     type RoamingAccountingPolicyUplinkFeeFactor: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
     type RoamingAccountingPolicyDownlinkFeeFactor: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
+    /// The number of blocks that the subscription fee of a subscription roaming accounting policy pays for
+    type SubscriptionBillingPeriod: Get<Self::BlockNumber>;
+    /// The maximum number of subscriptions that may be renewed in a block. New subscriptions may not fall due at a
+    /// block that is full, and any renewals over the limit are carried over to the next block.
+    type MaxSubscriptionRenewalsPerBlock: Get<u32>;
 }
 
-type BalanceOf<T> =
-    <<T as roaming_operators::Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+/// Storage version of the roaming accounting policies pallet
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum Releases {
+    /// The policy type of a roaming accounting policy is free-form bytes
+    V1_0_0,
+    /// The policy type of a roaming accounting policy is a `RoamingAccountingPolicyType`
+    V2_0_0,
+}
 
-#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+impl Default for Releases {
+    fn default() -> Self {
+        Releases::V1_0_0
+    }
+}
+
+/// How the roaming of visiting devices is charged for by a roaming accounting policy
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "std", derive(Debug))]
-pub struct RoamingAccountingPolicy(pub [u8; 16]);
+pub enum RoamingAccountingPolicyType {
+    /// Only the packets of visiting devices are charged for
+    Adhoc,
+    /// Home networks subscribe to the policy and are also charged its subscription fee every billing period
+    Subscription,
+}
+
+impl Default for RoamingAccountingPolicyType {
+    fn default() -> Self {
+        RoamingAccountingPolicyType::Adhoc
+    }
+}
+
+type BalanceOf<T> =
+    <<T as roaming_operators::Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 
 #[cfg_attr(feature = "std", derive(Debug))]
 #[derive(Encode, Decode, Default, Clone, PartialEq)]
 // Generic type parameters - Balance
 pub struct RoamingAccountingPolicySetting<U, V, W, X> {
-    pub policy_type: U, // "adhoc" or "subscription"
+    pub policy_type: U,
     pub subscription_fee: V,
     pub uplink_fee_factor: W,
     pub downlink_fee_factor: X,
 }
 
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum SubscriptionStatus {
+    /// The subscription fee has been paid for the current billing period
+    Active,
+    /// The subscription fee could not be charged, which suspends roaming between the two networks
+    Lapsed,
+}
+
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq)]
+// Generic type parameters - BlockNumber
+pub struct RoamingAccountingPolicySubscription<U> {
+    pub subscription_status: SubscriptionStatus,
+    pub subscription_started_at_block: U,
+    pub subscription_paid_until_block: U,
+}
+
 decl_event!(
     pub enum Event<T> where
         <T as frame_system::Config>::AccountId,
         <T as Config>::RoamingAccountingPolicyIndex,
-        <T as Config>::RoamingAccountingPolicyType,
         <T as Config>::RoamingAccountingPolicyUplinkFeeFactor,
         <T as Config>::RoamingAccountingPolicyDownlinkFeeFactor,
         <T as roaming_networks::Config>::RoamingNetworkIndex,
         Balance = BalanceOf<T>,
     {
-        /// A roaming accounting_policy is created. (owner, roaming_accounting_policy_id)
-        Created(AccountId, RoamingAccountingPolicyIndex),
-        /// A roaming accounting_policy is transferred. (from, to, roaming_accounting_policy_id)
-        Transferred(AccountId, AccountId, RoamingAccountingPolicyIndex),
         /// A roaming accounting_policy configuration
         RoamingAccountingPolicySettingSet(AccountId, RoamingAccountingPolicyIndex, RoamingAccountingPolicyType, Balance, RoamingAccountingPolicyUplinkFeeFactor, RoamingAccountingPolicyDownlinkFeeFactor),
         /// A roaming accounting_policy is assigned to a network. (owner of network, roaming_accounting_policy_id, roaming_network_id)
         AssignedAccountingPolicyToNetwork(AccountId, RoamingAccountingPolicyIndex, RoamingNetworkIndex),
+        /// A roaming accounting_policy is removed. (owner, roaming_accounting_policy_id)
+        Removed(AccountId, RoamingAccountingPolicyIndex),
+        /// A home network subscribed to a roaming accounting_policy and paid the subscription fee of its first billing
+        /// period. (subscriber, roaming_accounting_policy_id, home roaming_network_id, subscription_fee)
+        Subscribed(AccountId, RoamingAccountingPolicyIndex, RoamingNetworkIndex, Balance),
+        /// A home network unsubscribed from a roaming accounting_policy. (subscriber, roaming_accounting_policy_id,
+        /// home roaming_network_id)
+        Unsubscribed(AccountId, RoamingAccountingPolicyIndex, RoamingNetworkIndex),
+        /// The subscription fee of the next billing period of a subscription was charged. (roaming_accounting_policy_id,
+        /// home roaming_network_id, subscription_fee)
+        SubscriptionRenewed(RoamingAccountingPolicyIndex, RoamingNetworkIndex, Balance),
+        /// The subscription fee of a subscription could not be charged, so roaming between the home network and the
+        /// network of the accounting policy is suspended. (roaming_accounting_policy_id, home roaming_network_id,
+        /// visited roaming_network_id)
+        SubscriptionLapsed(RoamingAccountingPolicyIndex, RoamingNetworkIndex, RoamingNetworkIndex),
+        /// A subscription ended because its roaming accounting_policy no longer charges a subscription fee or no
+        /// longer belongs to a network. (roaming_accounting_policy_id, home roaming_network_id)
+        SubscriptionEnded(RoamingAccountingPolicyIndex, RoamingNetworkIndex),
     }
 );
 
+decl_error! {
+    pub enum Error for Module<T: Config> {
+        /// The roaming accounting policy does not charge a subscription fee
+        NotSubscriptionPolicy,
+        /// The roaming accounting policy has not been assigned to a roaming network
+        AccountingPolicyWithoutNetwork,
+        /// A network may not subscribe to an accounting policy of its own
+        SubscriptionToOwnNetwork,
+        /// The home network already has an active subscription to the roaming accounting policy
+        AlreadySubscribed,
+        /// The home network has no subscription to the roaming accounting policy
+        SubscriptionDoesNotExist,
+        /// A lapsed subscription suspends roaming until it is paid by subscribing again, so it may not be cancelled
+        SubscriptionLapsed,
+        /// Too many subscriptions are already due at the end of the first billing period
+        TooManySubscriptionsDue,
+        /// Only an account authorized for the home network may change its subscriptions
+        NotAuthorizedForHomeNetwork,
+        /// The roaming network has not been assigned to a roaming operator whose owner could pay or be paid
+        NetworkWithoutOperator,
+    }
+}
+
 // This module's storage items.
 decl_storage! {
     trait Store for Module<T: Config> as RoamingAccountingPolicies {
-        /// Stores all the roaming accounting_policies, key is the roaming accounting_policy id / index
-        pub RoamingAccountingPolicies get(fn roaming_accounting_policy): map hasher(opaque_blake2_256) T::RoamingAccountingPolicyIndex => Option<RoamingAccountingPolicy>;
-
-        /// Stores the total number of roaming accounting_policies. i.e. the next roaming accounting_policy index
-        pub RoamingAccountingPoliciesCount get(fn roaming_accounting_policies_count): T::RoamingAccountingPolicyIndex;
-
-        /// Get roaming accounting_policy owner
-        pub RoamingAccountingPolicyOwners get(fn roaming_accounting_policy_owner): map hasher(opaque_blake2_256) T::RoamingAccountingPolicyIndex => Option<T::AccountId>;
-
         /// Get roaming accounting_policy config
-        pub RoamingAccountingPolicySettings get(fn roaming_accounting_policy_settings): map hasher(opaque_blake2_256) T::RoamingAccountingPolicyIndex => Option<RoamingAccountingPolicySetting<T::RoamingAccountingPolicyType, BalanceOf<T>, T::RoamingAccountingPolicyUplinkFeeFactor, T::RoamingAccountingPolicyDownlinkFeeFactor>>;
+        pub RoamingAccountingPolicySettings get(fn roaming_accounting_policy_settings): map hasher(opaque_blake2_256) T::RoamingAccountingPolicyIndex => Option<RoamingAccountingPolicySetting<RoamingAccountingPolicyType, BalanceOf<T>, T::RoamingAccountingPolicyUplinkFeeFactor, T::RoamingAccountingPolicyDownlinkFeeFactor>>;
 
         /// Get roaming accounting_policy network
         pub RoamingAccountingPolicyNetwork get(fn roaming_accounting_policy_network): map hasher(opaque_blake2_256) T::RoamingAccountingPolicyIndex => Option<T::RoamingNetworkIndex>;
 
         /// Get roaming network's accounting policies
-        pub RoamingNetworkAccountingPolicies get(fn roaming_network_accounting_policies): map hasher(opaque_blake2_256) T::RoamingNetworkIndex => Option<Vec<T::RoamingAccountingPolicyIndex>>
+        pub RoamingNetworkAccountingPolicies get(fn roaming_network_accounting_policies): map hasher(opaque_blake2_256) T::RoamingNetworkIndex => Option<Vec<T::RoamingAccountingPolicyIndex>>;
+
+        /// Get the subscription of a home roaming network to a roaming accounting_policy
+        pub RoamingAccountingPolicySubscriptions get(fn roaming_accounting_policy_subscription): double_map hasher(opaque_blake2_256) T::RoamingAccountingPolicyIndex, hasher(opaque_blake2_256) T::RoamingNetworkIndex => Option<RoamingAccountingPolicySubscription<T::BlockNumber>>;
+
+        /// Get the subscriptions whose subscription fee of the next billing period is due at a block
+        pub RoamingAccountingPolicySubscriptionsDue get(fn roaming_accounting_policy_subscriptions_due): map hasher(opaque_blake2_256) T::BlockNumber => Vec<(T::RoamingAccountingPolicyIndex, T::RoamingNetworkIndex)>;
+
+        /// Storage version of the pallet, used to determine which migrations to run on runtime upgrade. A new chain
+        /// starts at the latest version since it has no policy types to migrate.
+        pub StorageVersion get(fn storage_version) build(|_| Releases::V2_0_0): Releases;
     }
 }
 
@@ -110,34 +225,39 @@ decl_storage! {
 decl_module! {
     /// The module declaration.
     pub struct Module<T: Config> for enum Call where origin: T::Origin {
-        fn deposit_event() = default;
+        type Error = Error<T>;
 
-        /// Create a new roaming accounting_policy
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn create(origin) {
-            let sender = ensure_signed(origin)?;
-            let roaming_accounting_policy_id = Self::next_roaming_accounting_policy_id()?;
+        fn deposit_event() = default;
 
-            // Generate a random 128bit value
-            let unique_id = Self::random_value(&sender);
+        const SubscriptionBillingPeriod: T::BlockNumber = T::SubscriptionBillingPeriod::get();
 
-            // Create and store roaming accounting_policy
-            let roaming_accounting_policy = RoamingAccountingPolicy(unique_id);
-            Self::insert_roaming_accounting_policy(&sender, roaming_accounting_policy_id, roaming_accounting_policy);
+        const MaxSubscriptionRenewalsPerBlock: u32 = T::MaxSubscriptionRenewalsPerBlock::get();
 
-            Self::deposit_event(RawEvent::Created(sender, roaming_accounting_policy_id));
+        fn on_runtime_upgrade() -> Weight {
+            let mut weight = T::DbWeight::get().reads(1);
+            if Self::storage_version() == Releases::V1_0_0 {
+                weight = weight.saturating_add(Self::migrate_to_typed_policy_types());
+            }
+            weight
         }
 
-        /// Transfer a roaming accounting_policy to new owner
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn transfer(origin, to: T::AccountId, roaming_accounting_policy_id: T::RoamingAccountingPolicyIndex) {
-            let sender = ensure_signed(origin)?;
-
-            ensure!(Self::roaming_accounting_policy_owner(roaming_accounting_policy_id) == Some(sender.clone()), "Only owner can transfer roaming accounting_policy");
-
-            Self::update_owner(&to, roaming_accounting_policy_id);
+        fn on_initialize(current_block_number: T::BlockNumber) -> Weight {
+            let mut due_subscriptions = <RoamingAccountingPolicySubscriptionsDue<T>>::take(current_block_number);
+            let max_renewals = T::MaxSubscriptionRenewalsPerBlock::get() as usize;
+            if due_subscriptions.len() > max_renewals {
+                // Renewals scheduled into a full block are renewed first in the next block
+                let mut carried_over_subscriptions = due_subscriptions.split_off(max_renewals);
+                let next_block_number = current_block_number.saturating_add(One::one());
+                let next_due_subscriptions = <RoamingAccountingPolicySubscriptionsDue<T>>::take(next_block_number);
+                carried_over_subscriptions.extend(next_due_subscriptions);
+                <RoamingAccountingPolicySubscriptionsDue<T>>::insert(next_block_number, carried_over_subscriptions);
+            }
+            let due_subscriptions_count = due_subscriptions.len() as Weight;
+            for (roaming_accounting_policy_id, home_roaming_network_id) in due_subscriptions {
+                Self::renew_subscription(roaming_accounting_policy_id, home_roaming_network_id, current_block_number);
+            }
 
-            Self::deposit_event(RawEvent::Transferred(sender, to, roaming_accounting_policy_id));
+            T::DbWeight::get().reads_writes(2 + due_subscriptions_count * 8, 2 + due_subscriptions_count * 4)
         }
 
         /// Set roaming account_policy config
@@ -145,7 +265,7 @@ decl_module! {
         pub fn set_config(
             origin,
             roaming_accounting_policy_id: T::RoamingAccountingPolicyIndex,
-            _policy_type: Option<T::RoamingAccountingPolicyType>, // "adhoc" or "subscription"
+            _policy_type: Option<RoamingAccountingPolicyType>,
             _subscription_fee: Option<BalanceOf<T>>,
             _uplink_fee_factor: Option<T::RoamingAccountingPolicyUplinkFeeFactor>,
             _downlink_fee_factor: Option<T::RoamingAccountingPolicyDownlinkFeeFactor>,
@@ -156,8 +276,8 @@ decl_module! {
             let is_roaming_accounting_policy = Self::exists_roaming_accounting_policy(roaming_accounting_policy_id).is_ok();
             ensure!(is_roaming_accounting_policy, "RoamingAccountingPolicy does not exist");
 
-            // Ensure that the caller is owner of the accounting policy config they are trying to change
-            ensure!(Self::roaming_accounting_policy_owner(roaming_accounting_policy_id) == Some(sender.clone()), "Only owner can set config for roaming accounting_policy");
+            // Ensure that the caller is authorized for the accounting policy config they are trying to change
+            ensure!(Self::is_authorized(roaming_accounting_policy_id, &sender), "Only an account authorized for the roaming accounting_policy can set its config");
 
             // let is_owned_by_parent_relationship = Self::is_owned_by_required_parent_relationship(roaming_accounting_policy_id, sender.clone()).is_ok();
             // ensure!(is_owned_by_parent_relationship, "Ownership by parent does not exist");
@@ -239,6 +359,7 @@ decl_module! {
         }
 
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        #[transactional]
         pub fn assign_accounting_policy_to_network(
             origin,
             roaming_accounting_policy_id: T::RoamingAccountingPolicyIndex,
@@ -251,18 +372,16 @@ decl_module! {
                 ::exists_roaming_network(roaming_network_id).is_ok();
             ensure!(is_roaming_network, "RoamingNetwork does not exist");
 
-            // Ensure that caller of the function is the owner of the network id to assign the accounting_policy to
+            // Ensure that caller of the function is authorized for the network id to assign the accounting_policy to
             ensure!(
-                <roaming_networks::Module<T>>::is_roaming_network_owner(roaming_network_id, sender.clone()).is_ok(),
-                "Only the roaming network owner can assign itself a roaming accounting policy"
+                <roaming_networks::Module<T>>::is_authorized(roaming_network_id, &sender),
+                "Only an account authorized for the roaming network can assign itself a roaming accounting policy"
             );
 
-            Self::associate_accounting_policy_with_network(roaming_accounting_policy_id, roaming_network_id)
-                .expect("Unable to associate accounting policy with network");
+            Self::associate_accounting_policy_with_network(roaming_accounting_policy_id, roaming_network_id)?;
 
             // Ensure that the given accounting_policy id already exists
-            let roaming_accounting_policy = Self::roaming_accounting_policy(roaming_accounting_policy_id);
-            ensure!(roaming_accounting_policy.is_some(), "Invalid roaming_accounting_policy_id");
+            ensure!(T::RoamingAccountingPolicyRegistry::exists(roaming_accounting_policy_id), "Invalid roaming_accounting_policy_id");
 
             // Ensure that the accounting_policy is not already owned by a different network
             // Unassign the accounting_policy from any existing network since it may only be owned by one network
@@ -273,6 +392,94 @@ decl_module! {
 
             Self::deposit_event(RawEvent::AssignedAccountingPolicyToNetwork(sender, roaming_accounting_policy_id, roaming_network_id));
         }
+
+        /// Remove a roaming accounting policy. Its children are either removed with it or prevent its removal,
+        /// depending on the removal policy.
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(4, 5)]
+        #[transactional]
+        pub fn remove(origin, roaming_accounting_policy_id: T::RoamingAccountingPolicyIndex) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(Self::is_authorized(roaming_accounting_policy_id, &sender), "Only an account authorized for the roaming accounting_policy can remove it");
+
+            Self::remove_roaming_accounting_policy(roaming_accounting_policy_id)?;
+
+            Self::deposit_event(RawEvent::Removed(sender, roaming_accounting_policy_id));
+        }
+
+        /// Subscribe a home network to a subscription roaming accounting policy of another network. The operator of the
+        /// home network pays the subscription fee of the first billing period to the operator of the other network
+        /// straight away, and the fee of each following billing period when it is due. A lapsed subscription is
+        /// renewed by subscribing again.
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(10, 4)]
+        #[transactional]
+        pub fn subscribe(
+            origin,
+            roaming_accounting_policy_id: T::RoamingAccountingPolicyIndex,
+            home_roaming_network_id: T::RoamingNetworkIndex
+        ) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(
+                <roaming_networks::Module<T>>::is_authorized(home_roaming_network_id, &sender),
+                Error::<T>::NotAuthorizedForHomeNetwork
+            );
+            let (subscription_fee, visited_roaming_network_id) = Self::subscription_terms(roaming_accounting_policy_id)?;
+            ensure!(visited_roaming_network_id != home_roaming_network_id, Error::<T>::SubscriptionToOwnNetwork);
+            let is_subscribed = Self::roaming_accounting_policy_subscription(roaming_accounting_policy_id, home_roaming_network_id)
+                .map_or(false, |subscription| subscription.subscription_status == SubscriptionStatus::Active);
+            ensure!(!is_subscribed, Error::<T>::AlreadySubscribed);
+            let current_block = <frame_system::Module<T>>::block_number();
+            let paid_until_block = current_block.saturating_add(Self::subscription_billing_period());
+            ensure!(
+                (Self::roaming_accounting_policy_subscriptions_due(paid_until_block).len() as u32) <
+                    T::MaxSubscriptionRenewalsPerBlock::get(),
+                Error::<T>::TooManySubscriptionsDue
+            );
+
+            Self::charge_subscription_fee(home_roaming_network_id, visited_roaming_network_id, subscription_fee)?;
+
+            <RoamingAccountingPolicySubscriptions<T>>::insert(
+                roaming_accounting_policy_id,
+                home_roaming_network_id,
+                RoamingAccountingPolicySubscription {
+                    subscription_status: SubscriptionStatus::Active,
+                    subscription_started_at_block: current_block,
+                    subscription_paid_until_block: paid_until_block,
+                },
+            );
+            <RoamingAccountingPolicySubscriptionsDue<T>>::append(
+                paid_until_block,
+                (roaming_accounting_policy_id, home_roaming_network_id),
+            );
+
+            Self::deposit_event(RawEvent::Subscribed(sender, roaming_accounting_policy_id, home_roaming_network_id, subscription_fee));
+        }
+
+        /// Unsubscribe a home network from a roaming accounting policy. The subscription fee of the current billing
+        /// period is not refunded. A lapsed subscription must be renewed by subscribing again before it may be
+        /// cancelled, since cancelling it would otherwise lift the suspension of roaming without payment.
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(4, 1)]
+        pub fn unsubscribe(
+            origin,
+            roaming_accounting_policy_id: T::RoamingAccountingPolicyIndex,
+            home_roaming_network_id: T::RoamingNetworkIndex
+        ) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(
+                <roaming_networks::Module<T>>::is_authorized(home_roaming_network_id, &sender),
+                Error::<T>::NotAuthorizedForHomeNetwork
+            );
+            let subscription = Self::roaming_accounting_policy_subscription(roaming_accounting_policy_id, home_roaming_network_id)
+                .ok_or(Error::<T>::SubscriptionDoesNotExist)?;
+            ensure!(subscription.subscription_status == SubscriptionStatus::Active, Error::<T>::SubscriptionLapsed);
+
+            // Any subscription fee that is still due is skipped since the subscription no longer exists
+            <RoamingAccountingPolicySubscriptions<T>>::remove(roaming_accounting_policy_id, home_roaming_network_id);
+
+            Self::deposit_event(RawEvent::Unsubscribed(sender, roaming_accounting_policy_id, home_roaming_network_id));
+        }
     }
 }
 
@@ -282,7 +489,7 @@ impl<T: Config> Module<T> {
         sender: T::AccountId,
     ) -> Result<(), DispatchError> {
         ensure!(
-            Self::roaming_accounting_policy_owner(&roaming_accounting_policy_id)
+            T::RoamingAccountingPolicyRegistry::owner_of(roaming_accounting_policy_id)
                 .map(|owner| owner == sender)
                 .unwrap_or(false),
             "Sender is not owner of RoamingAccountingPolicy"
@@ -312,11 +519,12 @@ impl<T: Config> Module<T> {
 
     pub fn exists_roaming_accounting_policy(
         roaming_accounting_policy_id: T::RoamingAccountingPolicyIndex,
-    ) -> Result<RoamingAccountingPolicy, DispatchError> {
-        match Self::roaming_accounting_policy(roaming_accounting_policy_id) {
-            Some(value) => Ok(value),
-            None => Err(DispatchError::Other("RoamingAccountingPolicy does not exist")),
-        }
+    ) -> Result<(), DispatchError> {
+        ensure!(
+            T::RoamingAccountingPolicyRegistry::exists(roaming_accounting_policy_id),
+            "RoamingAccountingPolicy does not exist"
+        );
+        Ok(())
     }
 
     pub fn exists_roaming_accounting_policy_setting(
@@ -377,36 +585,270 @@ impl<T: Config> Module<T> {
         }
     }
 
-    fn random_value(sender: &T::AccountId) -> [u8; 16] {
-        let payload = (
-            T::Randomness::random(&[0]),
-            sender,
-            <frame_system::Module<T>>::extrinsic_index(),
-            <frame_system::Module<T>>::block_number(),
+    /// Remove a roaming accounting policy along with its link to its network, cleaning up its children according to the
+    /// removal policy
+    pub fn remove_roaming_accounting_policy(
+        roaming_accounting_policy_id: T::RoamingAccountingPolicyIndex,
+    ) -> DispatchResult {
+        Self::exists_roaming_accounting_policy(roaming_accounting_policy_id)?;
+        T::RoamingAccountingPolicyChildren::remove_children_with_policy(
+            <T as roaming_operators::Config>::RemovalPolicy::get(),
+            roaming_accounting_policy_id,
+        )?;
+
+        if let Some(roaming_network_id) = <RoamingAccountingPolicyNetwork<T>>::take(roaming_accounting_policy_id) {
+            <RoamingNetworkAccountingPolicies<T>>::mutate_exists(roaming_network_id, |accounting_policy_ids| {
+                *accounting_policy_ids =
+                    without_roaming_entity(accounting_policy_ids.take(), &roaming_accounting_policy_id)
+            });
+        }
+        T::RoamingAccountingPolicyRegistry::deregister(roaming_accounting_policy_id)?;
+        <RoamingAccountingPolicySettings<T>>::remove(roaming_accounting_policy_id);
+        <RoamingAccountingPolicySubscriptions<T>>::remove_prefix(roaming_accounting_policy_id);
+        Ok(())
+    }
+
+    /// Whether roaming from a home network to a visited network is suspended because a subscription of the home
+    /// network to an accounting policy of the visited network has lapsed
+    pub fn is_roaming_suspended(
+        home_roaming_network_id: T::RoamingNetworkIndex,
+        visited_roaming_network_id: T::RoamingNetworkIndex,
+    ) -> bool {
+        Self::roaming_network_accounting_policies(visited_roaming_network_id)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|roaming_accounting_policy_id| {
+                Self::roaming_accounting_policy_network(roaming_accounting_policy_id) ==
+                    Some(visited_roaming_network_id)
+            })
+            .any(|roaming_accounting_policy_id| {
+                Self::roaming_accounting_policy_subscription(roaming_accounting_policy_id, home_roaming_network_id)
+                    .map_or(false, |subscription| subscription.subscription_status == SubscriptionStatus::Lapsed)
+            })
+    }
+
+    /// The account of the owner of the operator of a roaming network
+    pub fn network_operator_account(roaming_network_id: T::RoamingNetworkIndex) -> Result<T::AccountId, Error<T>> {
+        <roaming_networks::Module<T>>::roaming_network_operator(roaming_network_id)
+            .and_then(|roaming_operator_id| {
+                <T as roaming_operators::Config>::RoamingOperatorRegistry::owner_of(roaming_operator_id)
+            })
+            .ok_or(Error::<T>::NetworkWithoutOperator)
+    }
+
+    /// The subscription fee of a subscription roaming accounting policy and the network that it belongs to
+    fn subscription_terms(
+        roaming_accounting_policy_id: T::RoamingAccountingPolicyIndex,
+    ) -> Result<(BalanceOf<T>, T::RoamingNetworkIndex), Error<T>> {
+        let subscription_fee = Self::roaming_accounting_policy_settings(roaming_accounting_policy_id)
+            .filter(|policy_setting| policy_setting.policy_type == RoamingAccountingPolicyType::Subscription)
+            .map(|policy_setting| policy_setting.subscription_fee)
+            .ok_or(Error::<T>::NotSubscriptionPolicy)?;
+        let roaming_network_id = Self::roaming_accounting_policy_network(roaming_accounting_policy_id)
+            .ok_or(Error::<T>::AccountingPolicyWithoutNetwork)?;
+        Ok((subscription_fee, roaming_network_id))
+    }
+
+    /// A billing period is at least one block long
+    fn subscription_billing_period() -> T::BlockNumber {
+        T::SubscriptionBillingPeriod::get().max(One::one())
+    }
+
+    fn charge_subscription_fee(
+        home_roaming_network_id: T::RoamingNetworkIndex,
+        visited_roaming_network_id: T::RoamingNetworkIndex,
+        subscription_fee: BalanceOf<T>,
+    ) -> DispatchResult {
+        let home_operator_account_id = Self::network_operator_account(home_roaming_network_id)?;
+        let visited_operator_account_id = Self::network_operator_account(visited_roaming_network_id)?;
+        <T as roaming_operators::Config>::Currency::transfer(
+            &home_operator_account_id,
+            &visited_operator_account_id,
+            subscription_fee,
+            ExistenceRequirement::KeepAlive,
+        )
+    }
+
+    /// Charge the subscription fee of the next billing period of a subscription that is due. The subscription lapses
+    /// if the fee cannot be charged, and ends if its accounting policy no longer charges a subscription fee.
+    fn renew_subscription(
+        roaming_accounting_policy_id: T::RoamingAccountingPolicyIndex,
+        home_roaming_network_id: T::RoamingNetworkIndex,
+        current_block_number: T::BlockNumber,
+    ) {
+        let mut subscription =
+            match Self::roaming_accounting_policy_subscription(roaming_accounting_policy_id, home_roaming_network_id) {
+                // The subscription may have been cancelled and taken out again since the fee was scheduled, and the
+                // renewal may have been carried over from a full block
+                Some(value)
+                    if value.subscription_status == SubscriptionStatus::Active &&
+                        value.subscription_paid_until_block <= current_block_number =>
+                {
+                    value
+                }
+                _ => return,
+            };
+
+        let (subscription_fee, visited_roaming_network_id) =
+            match Self::subscription_terms(roaming_accounting_policy_id) {
+                Ok(value) => value,
+                Err(_) => {
+                    <RoamingAccountingPolicySubscriptions<T>>::remove(
+                        roaming_accounting_policy_id,
+                        home_roaming_network_id,
+                    );
+                    Self::deposit_event(RawEvent::SubscriptionEnded(
+                        roaming_accounting_policy_id,
+                        home_roaming_network_id,
+                    ));
+                    return;
+                }
+            };
+
+        match Self::charge_subscription_fee(home_roaming_network_id, visited_roaming_network_id, subscription_fee) {
+            Ok(()) => {
+                let paid_until_block = current_block_number.saturating_add(Self::subscription_billing_period());
+                subscription.subscription_paid_until_block = paid_until_block;
+                <RoamingAccountingPolicySubscriptionsDue<T>>::append(
+                    paid_until_block,
+                    (roaming_accounting_policy_id, home_roaming_network_id),
+                );
+                Self::deposit_event(RawEvent::SubscriptionRenewed(
+                    roaming_accounting_policy_id,
+                    home_roaming_network_id,
+                    subscription_fee,
+                ));
+            }
+            Err(e) => {
+                debug::info!(
+                    "Unable to charge the subscription fee of home network {:?} for accounting policy {:?}: {:?}",
+                    home_roaming_network_id,
+                    roaming_accounting_policy_id,
+                    e
+                );
+                subscription.subscription_status = SubscriptionStatus::Lapsed;
+                Self::deposit_event(RawEvent::SubscriptionLapsed(
+                    roaming_accounting_policy_id,
+                    home_roaming_network_id,
+                    visited_roaming_network_id,
+                ));
+            }
+        }
+        <RoamingAccountingPolicySubscriptions<T>>::insert(
+            roaming_accounting_policy_id,
+            home_roaming_network_id,
+            subscription,
         );
-        payload.using_encoded(blake2_128)
     }
 
-    fn next_roaming_accounting_policy_id() -> Result<T::RoamingAccountingPolicyIndex, DispatchError> {
-        let roaming_accounting_policy_id = Self::roaming_accounting_policies_count();
-        if roaming_accounting_policy_id == <T::RoamingAccountingPolicyIndex as Bounded>::max_value() {
-            return Err(DispatchError::Other("RoamingAccountingPolicies count overflow"));
+    /// The number of roaming accounting policies, which this pallet held itself until the registry took them over
+    fn roaming_accounting_policies_count() -> T::RoamingAccountingPolicyIndex {
+        get_storage_value::<T::RoamingAccountingPolicyIndex>(
+            b"RoamingAccountingPolicies",
+            b"RoamingAccountingPoliciesCount",
+            &[],
+        )
+        .unwrap_or_else(T::RoamingAccountingPolicyRegistry::entities_count)
+    }
+
+    /// Turn the free-form policy type of each roaming accounting policy config into a `RoamingAccountingPolicyType`.
+    /// Only a policy type of "subscription" becomes a subscription policy; any other policy type becomes adhoc.
+    /// The typed configs are stored under the same keys, so a config that already decodes as typed is kept as is
+    /// rather than being decoded as a free-form one. A free-form config never decodes as typed, other than one with
+    /// an empty policy type, which decodes as adhoc just like it would be migrated.
+    fn migrate_to_typed_policy_types() -> Weight {
+        let roaming_accounting_policies_count = Self::roaming_accounting_policies_count();
+        let mut migrated_count: Weight = 0;
+
+        let mut roaming_accounting_policy_id: T::RoamingAccountingPolicyIndex = Zero::zero();
+        while roaming_accounting_policy_id < roaming_accounting_policies_count {
+            // The configs were hashed with opaque_blake2_256
+            let hash = blake2_256(&roaming_accounting_policy_id.encode());
+            let is_typed = get_storage_value::<
+                RoamingAccountingPolicySetting<
+                    RoamingAccountingPolicyType,
+                    BalanceOf<T>,
+                    T::RoamingAccountingPolicyUplinkFeeFactor,
+                    T::RoamingAccountingPolicyDownlinkFeeFactor,
+                >,
+            >(b"RoamingAccountingPolicies", b"RoamingAccountingPolicySettings", &hash)
+            .is_some();
+            if is_typed {
+                debug::info!(
+                    "Roaming accounting policy {:?} already has a typed policy type",
+                    roaming_accounting_policy_id
+                );
+            } else if let Some(legacy_policy_setting) =
+                take_storage_value::<
+                    RoamingAccountingPolicySetting<
+                        Vec<u8>,
+                        BalanceOf<T>,
+                        T::RoamingAccountingPolicyUplinkFeeFactor,
+                        T::RoamingAccountingPolicyDownlinkFeeFactor,
+                    >,
+                >(b"RoamingAccountingPolicies", b"RoamingAccountingPolicySettings", &hash)
+            {
+                let policy_type = if legacy_policy_setting.policy_type.as_slice() == b"subscription" {
+                    RoamingAccountingPolicyType::Subscription
+                } else {
+                    RoamingAccountingPolicyType::Adhoc
+                };
+                <RoamingAccountingPolicySettings<T>>::insert(
+                    roaming_accounting_policy_id,
+                    RoamingAccountingPolicySetting {
+                        policy_type,
+                        subscription_fee: legacy_policy_setting.subscription_fee,
+                        uplink_fee_factor: legacy_policy_setting.uplink_fee_factor,
+                        downlink_fee_factor: legacy_policy_setting.downlink_fee_factor,
+                    },
+                );
+            }
+
+            migrated_count += 1;
+            roaming_accounting_policy_id = roaming_accounting_policy_id + One::one();
         }
-        Ok(roaming_accounting_policy_id)
+
+        StorageVersion::put(Releases::V2_0_0);
+        debug::info!("Migrated the policy types of {:?} roaming accounting policies", migrated_count);
+
+        T::DbWeight::get().reads_writes(2 + migrated_count * 2, 1 + migrated_count * 2)
     }
+}
 
-    fn insert_roaming_accounting_policy(
-        owner: &T::AccountId,
+impl<T: Config> RoamingAuthority<T::AccountId, T::RoamingAccountingPolicyIndex> for Module<T> {
+    fn is_owner_or_admin(
         roaming_accounting_policy_id: T::RoamingAccountingPolicyIndex,
-        roaming_accounting_policy: RoamingAccountingPolicy,
-    ) {
-        // Create and store roaming accounting_policy
-        <RoamingAccountingPolicies<T>>::insert(roaming_accounting_policy_id, roaming_accounting_policy);
-        <RoamingAccountingPoliciesCount<T>>::put(roaming_accounting_policy_id + One::one());
-        <RoamingAccountingPolicyOwners<T>>::insert(roaming_accounting_policy_id, owner.clone());
+        account: &T::AccountId,
+    ) -> bool {
+        T::RoamingAccountingPolicyRegistry::is_owner_or_admin(roaming_accounting_policy_id, account)
     }
 
-    fn update_owner(to: &T::AccountId, roaming_accounting_policy_id: T::RoamingAccountingPolicyIndex) {
-        <RoamingAccountingPolicyOwners<T>>::insert(roaming_accounting_policy_id, to);
+    /// The owner and admins of a roaming accounting policy are authorized, as is any account authorized for its
+    /// network
+    fn is_authorized(roaming_accounting_policy_id: T::RoamingAccountingPolicyIndex, account: &T::AccountId) -> bool {
+        Self::is_owner_or_admin(roaming_accounting_policy_id, account) ||
+            Self::roaming_accounting_policy_network(roaming_accounting_policy_id)
+                .map_or(false, |parent_id| <roaming_networks::Module<T>>::is_authorized(parent_id, account))
+    }
+}
+
+/// The accounting policies of a roaming network, other than those that have since been assigned to another network
+impl<T: Config> RoamingEntityChildren<RoamingNetworkEntity, T::RoamingNetworkIndex> for Module<T> {
+    fn has_children(roaming_network_id: T::RoamingNetworkIndex) -> bool {
+        Self::roaming_network_accounting_policies(roaming_network_id).unwrap_or_default().into_iter().any(
+            |accounting_policy_id| {
+                Self::roaming_accounting_policy_network(accounting_policy_id) == Some(roaming_network_id)
+            },
+        )
+    }
+
+    fn remove_children(roaming_network_id: T::RoamingNetworkIndex) -> DispatchResult {
+        let accounting_policy_ids = <RoamingNetworkAccountingPolicies<T>>::take(roaming_network_id).unwrap_or_default();
+        for accounting_policy_id in accounting_policy_ids {
+            if Self::roaming_accounting_policy_network(accounting_policy_id) == Some(roaming_network_id) {
+                Self::remove_roaming_accounting_policy(accounting_policy_id)?;
+            }
+        }
+        Ok(())
     }
 }
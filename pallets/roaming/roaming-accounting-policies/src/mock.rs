@@ -1,6 +1,7 @@
 // Creating mock runtime here
 
 use crate::{
+    GenesisConfig,
     Module,
     Config,
 };
@@ -87,25 +88,70 @@ impl pallet_transaction_payment::Config for Test {
     type TransactionByteFee = TransactionByteFee;
     type WeightToFee = IdentityFee<u64>;
 }
-impl roaming_operators::Config for Test {
+impl roaming_registry::Config<roaming_registry::Instance1> for Test {
     type Currency = Balances;
+    type EntityDeposit = ();
     type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
     type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+impl roaming_operators::Config for Test {
+    type Currency = Balances;
+    type Event = ();
+    type RemovalPolicy = ();
+    type RoamingOperatorChildren = ();
     type RoamingOperatorIndex = u64;
+    type RoamingOperatorRegistry = RoamingOperatorRegistry;
+}
+impl roaming_registry::Config<roaming_registry::Instance2> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
 }
 impl roaming_networks::Config for Test {
     type Event = ();
+    type RoamingNetworkChildren = ();
     type RoamingNetworkIndex = u64;
+    type RoamingNetworkRegistry = RoamingNetworkRegistry;
+}
+impl roaming_registry::Config<roaming_registry::Instance8> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+parameter_types! {
+    pub const MaxSubscriptionRenewalsPerBlock: u32 = 2;
+    pub const SubscriptionBillingPeriod: u64 = 10;
 }
 impl Config for Test {
     type Event = ();
+    type MaxSubscriptionRenewalsPerBlock = MaxSubscriptionRenewalsPerBlock;
+    type RoamingAccountingPolicyChildren = ();
     type RoamingAccountingPolicyDownlinkFeeFactor = u32;
     type RoamingAccountingPolicyIndex = u64;
-    type RoamingAccountingPolicyType = Vec<u8>;
+    type RoamingAccountingPolicyRegistry = RoamingAccountingPolicyRegistry;
     type RoamingAccountingPolicyUplinkFeeFactor = u32;
+    type SubscriptionBillingPeriod = SubscriptionBillingPeriod;
 }
 
 pub type RoamingAccountingPolicyModule = Module<Test>;
+pub type RoamingNetworkModule = roaming_networks::Module<Test>;
+pub type RoamingOperatorRegistry = roaming_registry::Module<Test, roaming_registry::Instance1>;
+pub type RoamingNetworkRegistry = roaming_registry::Module<Test, roaming_registry::Instance2>;
+pub type RoamingAccountingPolicyRegistry = roaming_registry::Module<Test, roaming_registry::Instance8>;
 
 // This function basically just builds a genesis storage key/value store according to
 // our desired mockup.
@@ -116,6 +162,7 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
     }
     .assimilate_storage(&mut t)
     .unwrap();
+    GenesisConfig::default().assimilate_storage::<Test>(&mut t).unwrap();
     let mut ext = sp_io::TestExternalities::new(t);
     ext.execute_with(|| System::set_block_number(1));
     ext
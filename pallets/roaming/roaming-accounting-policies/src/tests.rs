@@ -5,80 +5,367 @@ use crate::mock::*;
 use frame_support::{
     assert_noop,
     assert_ok,
+    storage::migration::put_storage_value,
+    traits::{
+        OnInitialize,
+        OnRuntimeUpgrade,
+    },
 };
+use sp_io::hashing::blake2_256;
 
 #[test]
 fn basic_setup_works() {
     new_test_ext().execute_with(|| {
         // Verify Initial Storage
-        assert_eq!(RoamingAccountingPolicyModule::roaming_accounting_policies_count(), 0);
-        assert!(RoamingAccountingPolicyModule::roaming_accounting_policy(0).is_none());
-        assert_eq!(RoamingAccountingPolicyModule::roaming_accounting_policy_owner(0), None);
+        assert_eq!(RoamingAccountingPolicyRegistry::roaming_entities_count(), 0);
+        assert!(RoamingAccountingPolicyRegistry::roaming_entity(0).is_none());
+        assert_eq!(RoamingAccountingPolicyRegistry::roaming_entity_owner(0), None);
         assert_eq!(Balances::free_balance(1), 10);
         assert_eq!(Balances::free_balance(2), 20);
     });
 }
 
 #[test]
-fn create_works() {
+fn operator_owner_manages_accounting_policies_of_its_networks() {
     new_test_ext().execute_with(|| {
+        // Setup - network 0 of operator 0 and its accounting policy are owned by account 3, not operator owner 1
+        assert_ok!(RoamingOperatorRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkRegistry::create(Origin::signed(3)));
+        assert_ok!(RoamingNetworkModule::assign_network_to_operator(Origin::signed(1), 0, 0));
+        assert_ok!(RoamingAccountingPolicyRegistry::create(Origin::signed(3)));
+        assert_ok!(RoamingAccountingPolicyModule::assign_accounting_policy_to_network(Origin::signed(3), 0, 0));
         // Call Functions
-        assert_ok!(RoamingAccountingPolicyModule::create(Origin::signed(1)));
+        assert_noop!(
+            RoamingAccountingPolicyModule::set_config(Origin::signed(4), 0, None, Some(3), None, None),
+            "Only an account authorized for the roaming accounting_policy can set its config"
+        );
+        assert_ok!(RoamingAccountingPolicyModule::set_config(Origin::signed(1), 0, None, Some(3), None, None));
+        assert_eq!(
+            RoamingAccountingPolicyModule::roaming_accounting_policy_settings(0)
+                .map(|policy_setting| policy_setting.subscription_fee),
+            Some(3)
+        );
+        assert_noop!(
+            RoamingAccountingPolicyModule::remove(Origin::signed(4), 0),
+            "Only an account authorized for the roaming accounting_policy can remove it"
+        );
+        assert_ok!(RoamingAccountingPolicyModule::remove(Origin::signed(1), 0));
+        // Verify Storage
+        assert!(RoamingAccountingPolicyRegistry::roaming_entity(0).is_none());
+    });
+}
+
+// Setup home network 0 of operator 0 owned by 1 and visited network 1 of operator 1 owned by 2, where accounting
+// policy 0 of the visited network has a subscription fee of 3
+fn setup_subscription_accounting_policy_of_visited_network() {
+    assert_ok!(RoamingOperatorRegistry::create(Origin::signed(1)));
+    assert_ok!(RoamingOperatorRegistry::create(Origin::signed(2)));
+    assert_ok!(RoamingNetworkRegistry::create(Origin::signed(1)));
+    assert_ok!(RoamingNetworkRegistry::create(Origin::signed(2)));
+    assert_ok!(RoamingNetworkModule::assign_network_to_operator(Origin::signed(1), 0, 0));
+    assert_ok!(RoamingNetworkModule::assign_network_to_operator(Origin::signed(2), 1, 1));
+    assert_ok!(RoamingAccountingPolicyRegistry::create(Origin::signed(2)));
+    assert_ok!(RoamingAccountingPolicyModule::set_config(
+        Origin::signed(2),
+        0,
+        Some(RoamingAccountingPolicyType::Subscription),
+        Some(3),
+        None,
+        None
+    ));
+    assert_ok!(RoamingAccountingPolicyModule::assign_accounting_policy_to_network(Origin::signed(2), 0, 1));
+}
+
+#[test]
+fn subscription_fee_is_charged_every_billing_period() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        setup_subscription_accounting_policy_of_visited_network();
+        // Call Functions
+        assert_ok!(RoamingAccountingPolicyModule::subscribe(Origin::signed(1), 0, 0));
         // Verify Storage
-        assert_eq!(RoamingAccountingPolicyModule::roaming_accounting_policies_count(), 1);
-        assert!(RoamingAccountingPolicyModule::roaming_accounting_policy(0).is_some());
-        assert_eq!(RoamingAccountingPolicyModule::roaming_accounting_policy_owner(0), Some(1));
+        assert_eq!(Balances::free_balance(1), 7);
+        assert_eq!(Balances::free_balance(2), 23);
+        assert_eq!(
+            RoamingAccountingPolicyModule::roaming_accounting_policy_subscription(0, 0),
+            Some(RoamingAccountingPolicySubscription {
+                subscription_status: SubscriptionStatus::Active,
+                subscription_started_at_block: 1,
+                subscription_paid_until_block: 11,
+            })
+        );
+        assert_eq!(RoamingAccountingPolicyModule::roaming_accounting_policy_subscriptions_due(11), vec![(0, 0)]);
+
+        RoamingAccountingPolicyModule::on_initialize(11);
+        assert_eq!(Balances::free_balance(1), 4);
+        assert_eq!(Balances::free_balance(2), 26);
+        assert_eq!(
+            RoamingAccountingPolicyModule::roaming_accounting_policy_subscription(0, 0)
+                .map(|subscription| subscription.subscription_paid_until_block),
+            Some(21)
+        );
+        assert!(RoamingAccountingPolicyModule::roaming_accounting_policy_subscriptions_due(11).is_empty());
+        assert_eq!(RoamingAccountingPolicyModule::roaming_accounting_policy_subscriptions_due(21), vec![(0, 0)]);
+        assert!(!RoamingAccountingPolicyModule::is_roaming_suspended(0, 1));
     });
 }
 
 #[test]
-fn create_handles_basic_errors() {
+fn lapsed_subscription_suspends_roaming_until_renewed() {
     new_test_ext().execute_with(|| {
         // Setup
-        <RoamingAccountingPoliciesCount<Test>>::put(u64::max_value());
+        setup_subscription_accounting_policy_of_visited_network();
+        assert_ok!(RoamingAccountingPolicyModule::subscribe(Origin::signed(1), 0, 0));
+        RoamingAccountingPolicyModule::on_initialize(11);
+        RoamingAccountingPolicyModule::on_initialize(21);
+        assert_eq!(Balances::free_balance(1), 1);
         // Call Functions
+        RoamingAccountingPolicyModule::on_initialize(31);
+        // Verify Storage
+        assert_eq!(Balances::free_balance(1), 1);
+        assert_eq!(
+            RoamingAccountingPolicyModule::roaming_accounting_policy_subscription(0, 0)
+                .map(|subscription| subscription.subscription_status),
+            Some(SubscriptionStatus::Lapsed)
+        );
+        assert!(RoamingAccountingPolicyModule::roaming_accounting_policy_subscriptions_due(41).is_empty());
+        assert!(RoamingAccountingPolicyModule::is_roaming_suspended(0, 1));
+        assert!(!RoamingAccountingPolicyModule::is_roaming_suspended(1, 0));
+
+        // Cancelling the lapsed subscription may not lift the suspension without payment
+        assert_noop!(
+            RoamingAccountingPolicyModule::unsubscribe(Origin::signed(1), 0, 0),
+            Error::<Test>::SubscriptionLapsed
+        );
+        assert!(RoamingAccountingPolicyModule::is_roaming_suspended(0, 1));
+
+        // Renew the lapsed subscription once the operator of the home network can pay again
+        assert_ok!(Balances::transfer(Origin::signed(3), 1, 10));
+        System::set_block_number(35);
+        assert_ok!(RoamingAccountingPolicyModule::subscribe(Origin::signed(1), 0, 0));
+        assert_eq!(Balances::free_balance(1), 8);
+        assert_eq!(
+            RoamingAccountingPolicyModule::roaming_accounting_policy_subscription(0, 0),
+            Some(RoamingAccountingPolicySubscription {
+                subscription_status: SubscriptionStatus::Active,
+                subscription_started_at_block: 35,
+                subscription_paid_until_block: 45,
+            })
+        );
+        assert!(!RoamingAccountingPolicyModule::is_roaming_suspended(0, 1));
+        assert_ok!(RoamingAccountingPolicyModule::unsubscribe(Origin::signed(1), 0, 0));
+    });
+}
+
+#[test]
+fn subscription_renewals_over_the_limit_are_carried_over_to_the_next_block() {
+    new_test_ext().execute_with(|| {
+        // Setup - home networks 2 and 3 of operators 2 and 3 owned by 3 and 4
+        setup_subscription_accounting_policy_of_visited_network();
+        for account_id in 3..5 {
+            assert_ok!(RoamingOperatorRegistry::create(Origin::signed(account_id)));
+            assert_ok!(RoamingNetworkRegistry::create(Origin::signed(account_id)));
+            assert_ok!(RoamingNetworkModule::assign_network_to_operator(
+                Origin::signed(account_id),
+                account_id - 1,
+                account_id - 1
+            ));
+        }
+        assert_ok!(RoamingAccountingPolicyModule::subscribe(Origin::signed(1), 0, 0));
+        assert_ok!(RoamingAccountingPolicyModule::subscribe(Origin::signed(3), 0, 2));
         assert_noop!(
-            RoamingAccountingPolicyModule::create(Origin::signed(1)),
-            "RoamingAccountingPolicies count overflow"
+            RoamingAccountingPolicyModule::subscribe(Origin::signed(4), 0, 3),
+            Error::<Test>::TooManySubscriptionsDue
         );
+        // A renewal of the subscription of home network 3 was scheduled into the full block
+        <RoamingAccountingPolicySubscriptions<Test>>::insert(0, 3, RoamingAccountingPolicySubscription {
+            subscription_status: SubscriptionStatus::Active,
+            subscription_started_at_block: 1,
+            subscription_paid_until_block: 11,
+        });
+        <RoamingAccountingPolicySubscriptionsDue<Test>>::append(11, (0, 3));
+        // Call Functions
+        RoamingAccountingPolicyModule::on_initialize(11);
+        // Verify Storage
+        assert_eq!(Balances::free_balance(1), 4);
+        assert_eq!(Balances::free_balance(3), 24);
+        assert_eq!(Balances::free_balance(4), 40);
+        assert!(RoamingAccountingPolicyModule::roaming_accounting_policy_subscriptions_due(11).is_empty());
+        assert_eq!(RoamingAccountingPolicyModule::roaming_accounting_policy_subscriptions_due(12), vec![(0, 3)]);
+
+        // Call Functions
+        RoamingAccountingPolicyModule::on_initialize(12);
         // Verify Storage
-        assert_eq!(RoamingAccountingPolicyModule::roaming_accounting_policies_count(), u64::max_value());
-        assert!(RoamingAccountingPolicyModule::roaming_accounting_policy(0).is_none());
-        assert_eq!(RoamingAccountingPolicyModule::roaming_accounting_policy_owner(0), None);
+        assert_eq!(Balances::free_balance(4), 37);
+        assert_eq!(Balances::free_balance(2), 35);
+        assert_eq!(
+            RoamingAccountingPolicyModule::roaming_accounting_policy_subscription(0, 3)
+                .map(|subscription| subscription.subscription_paid_until_block),
+            Some(22)
+        );
+        assert_eq!(RoamingAccountingPolicyModule::roaming_accounting_policy_subscriptions_due(22), vec![(0, 3)]);
     });
 }
 
 #[test]
-fn transfer_works() {
+fn unsubscribe_stops_subscription_fee_charges() {
     new_test_ext().execute_with(|| {
         // Setup
-        assert_ok!(RoamingAccountingPolicyModule::create(Origin::signed(1)));
+        setup_subscription_accounting_policy_of_visited_network();
+        assert_ok!(RoamingAccountingPolicyModule::subscribe(Origin::signed(1), 0, 0));
         // Call Functions
-        assert_ok!(RoamingAccountingPolicyModule::transfer(Origin::signed(1), 2, 0));
+        assert_ok!(RoamingAccountingPolicyModule::unsubscribe(Origin::signed(1), 0, 0));
+        RoamingAccountingPolicyModule::on_initialize(11);
         // Verify Storage
-        assert_eq!(RoamingAccountingPolicyModule::roaming_accounting_policies_count(), 1);
-        assert!(RoamingAccountingPolicyModule::roaming_accounting_policy(0).is_some());
-        assert_eq!(RoamingAccountingPolicyModule::roaming_accounting_policy_owner(0), Some(2));
+        assert_eq!(Balances::free_balance(1), 7);
+        assert_eq!(Balances::free_balance(2), 23);
+        assert_eq!(RoamingAccountingPolicyModule::roaming_accounting_policy_subscription(0, 0), None);
+        assert!(!RoamingAccountingPolicyModule::is_roaming_suspended(0, 1));
     });
 }
 
 #[test]
-fn transfer_handles_basic_errors() {
+fn subscribe_handles_basic_errors() {
     new_test_ext().execute_with(|| {
         // Setup
-        assert_ok!(RoamingAccountingPolicyModule::create(Origin::signed(1)));
+        setup_subscription_accounting_policy_of_visited_network();
+        assert_ok!(RoamingAccountingPolicyRegistry::create(Origin::signed(2)));
+        assert_ok!(RoamingAccountingPolicyModule::set_config(
+            Origin::signed(2),
+            1,
+            Some(RoamingAccountingPolicyType::Adhoc),
+            Some(3),
+            None,
+            None
+        ));
+        assert_ok!(RoamingAccountingPolicyModule::assign_accounting_policy_to_network(Origin::signed(2), 1, 1));
+        assert_ok!(RoamingAccountingPolicyRegistry::create(Origin::signed(2)));
+        assert_ok!(RoamingAccountingPolicyModule::set_config(
+            Origin::signed(2),
+            2,
+            Some(RoamingAccountingPolicyType::Subscription),
+            Some(3),
+            None,
+            None
+        ));
         // Call Functions
         assert_noop!(
-            RoamingAccountingPolicyModule::transfer(Origin::signed(2), 2, 0),
-            "Only owner can transfer roaming accounting_policy"
+            RoamingAccountingPolicyModule::subscribe(Origin::signed(3), 0, 0),
+            Error::<Test>::NotAuthorizedForHomeNetwork
+        );
+        assert_noop!(
+            RoamingAccountingPolicyModule::subscribe(Origin::signed(1), 1, 0),
+            Error::<Test>::NotSubscriptionPolicy
+        );
+        assert_noop!(
+            RoamingAccountingPolicyModule::subscribe(Origin::signed(1), 2, 0),
+            Error::<Test>::AccountingPolicyWithoutNetwork
+        );
+        assert_noop!(
+            RoamingAccountingPolicyModule::subscribe(Origin::signed(2), 0, 1),
+            Error::<Test>::SubscriptionToOwnNetwork
+        );
+        assert_noop!(
+            RoamingAccountingPolicyModule::unsubscribe(Origin::signed(1), 0, 0),
+            Error::<Test>::SubscriptionDoesNotExist
+        );
+        assert_ok!(RoamingAccountingPolicyModule::subscribe(Origin::signed(1), 0, 0));
+        assert_noop!(
+            RoamingAccountingPolicyModule::subscribe(Origin::signed(1), 0, 0),
+            Error::<Test>::AlreadySubscribed
         );
         assert_noop!(
-            RoamingAccountingPolicyModule::transfer(Origin::signed(1), 2, 1),
-            "Only owner can transfer roaming accounting_policy"
+            RoamingAccountingPolicyModule::unsubscribe(Origin::signed(2), 0, 0),
+            Error::<Test>::NotAuthorizedForHomeNetwork
+        );
+    });
+}
+
+#[test]
+fn migration_types_free_form_policy_types() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        for _ in 0..3 {
+            assert_ok!(RoamingAccountingPolicyRegistry::create(Origin::signed(1)));
+        }
+        let policy_types: [&[u8]; 2] = [b"subscription", b"adhoc"];
+        for (roaming_accounting_policy_id, policy_type) in policy_types.iter().enumerate() {
+            put_storage_value(
+                b"RoamingAccountingPolicies",
+                b"RoamingAccountingPolicySettings",
+                &blake2_256(&(roaming_accounting_policy_id as u64).encode()),
+                RoamingAccountingPolicySetting {
+                    policy_type: policy_type.to_vec(),
+                    subscription_fee: 5u64,
+                    uplink_fee_factor: 2u32,
+                    downlink_fee_factor: 1u32,
+                },
+            );
+        }
+        StorageVersion::put(Releases::V1_0_0);
+        // Call Functions
+        RoamingAccountingPolicyModule::on_runtime_upgrade();
+        // Verify Storage
+        assert_eq!(
+            RoamingAccountingPolicyModule::roaming_accounting_policy_settings(0),
+            Some(RoamingAccountingPolicySetting {
+                policy_type: RoamingAccountingPolicyType::Subscription,
+                subscription_fee: 5,
+                uplink_fee_factor: 2,
+                downlink_fee_factor: 1,
+            })
         );
+        assert_eq!(
+            RoamingAccountingPolicyModule::roaming_accounting_policy_settings(1)
+                .map(|policy_setting| policy_setting.policy_type),
+            Some(RoamingAccountingPolicyType::Adhoc)
+        );
+        assert_eq!(RoamingAccountingPolicyModule::roaming_accounting_policy_settings(2), None);
+        assert_eq!(RoamingAccountingPolicyModule::storage_version(), Releases::V2_0_0);
+    });
+}
+
+#[test]
+fn migration_keeps_typed_policy_types() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        for _ in 0..2 {
+            assert_ok!(RoamingAccountingPolicyRegistry::create(Origin::signed(1)));
+        }
+        assert_ok!(RoamingAccountingPolicyModule::set_config(
+            Origin::signed(1),
+            0,
+            Some(RoamingAccountingPolicyType::Subscription),
+            Some(5),
+            Some(2),
+            Some(1)
+        ));
+        assert_ok!(RoamingAccountingPolicyModule::set_config(
+            Origin::signed(1),
+            1,
+            Some(RoamingAccountingPolicyType::Adhoc),
+            Some(5),
+            Some(2),
+            Some(1)
+        ));
+        StorageVersion::put(Releases::V1_0_0);
+        // Call Functions
+        RoamingAccountingPolicyModule::on_runtime_upgrade();
         // Verify Storage
-        assert_eq!(RoamingAccountingPolicyModule::roaming_accounting_policies_count(), 1);
-        assert!(RoamingAccountingPolicyModule::roaming_accounting_policy(0).is_some());
-        assert_eq!(RoamingAccountingPolicyModule::roaming_accounting_policy_owner(0), Some(1));
+        assert_eq!(
+            RoamingAccountingPolicyModule::roaming_accounting_policy_settings(0),
+            Some(RoamingAccountingPolicySetting {
+                policy_type: RoamingAccountingPolicyType::Subscription,
+                subscription_fee: 5,
+                uplink_fee_factor: 2,
+                downlink_fee_factor: 1,
+            })
+        );
+        assert_eq!(
+            RoamingAccountingPolicyModule::roaming_accounting_policy_settings(1)
+                .map(|policy_setting| policy_setting.policy_type),
+            Some(RoamingAccountingPolicyType::Adhoc)
+        );
+        assert_eq!(RoamingAccountingPolicyModule::storage_version(), Releases::V2_0_0);
     });
 }
@@ -6,26 +6,42 @@ use codec::{
 };
 use frame_support::{
     debug,
+    decl_error,
     decl_event,
     decl_module,
     decl_storage,
     ensure,
     traits::{
+        BalanceStatus,
+        Currency,
         Get,
-        Randomness,
+        ReservableCurrency,
     },
+    transactional,
     Parameter,
 };
 use frame_system::ensure_signed;
-use sp_io::hashing::blake2_128;
 use sp_runtime::{
     traits::{
         AtLeast32Bit,
         Bounded,
         Member,
-        One,
+        Saturating,
+        Zero,
     },
     DispatchError,
+    DispatchResult,
+};
+use roaming_operators::{
+    without_roaming_entity,
+    RoamingAccountingPolicyEntity,
+    RoamingAuthority,
+    RoamingEntityChildren,
+    RoamingNetworkEntity,
+};
+use roaming_registry::{
+    RoamingRegistry,
+    RoamingTradable,
 };
 use sp_std::prelude::*; // Imports Vec
 #[macro_use]
@@ -43,12 +59,46 @@ pub trait Config:
 {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
     type RoamingAgreementPolicyIndex: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
-    type RoamingAgreementPolicyActivationType: Parameter + Member + Default;
+    /// The registry that holds the roaming agreement policies and their owners
+    type RoamingAgreementPolicyRegistry: RoamingRegistry<Self::AccountId, Self::RoamingAgreementPolicyIndex>;
+    /// The activation type of an agreement policy, e.g. "passive" or "handover"
+    type RoamingAgreementPolicyActivationType: Parameter + Member + Default + AsRef<[u8]>;
+    type Currency: ReservableCurrency<Self::AccountId>;
+    /// The pallet that settles the packet bundles of home networks from their agreement deposits
+    type RoamingAgreementSettlements: RoamingAgreementSettlements<Self::RoamingNetworkIndex, Self::BlockNumber>;
+}
+
+/// The settlements of the packet bundles that a home network owes a visited network, which are paid from the
+/// agreement deposits of the home network for the visited network first
+pub trait RoamingAgreementSettlements<RoamingNetworkIndex, BlockNumber> {
+    /// The number of blocks after a packet bundle was received that the home network has to settle it
+    fn settlement_period() -> BlockNumber;
+
+    /// Whether the visited network received packet bundles of the home network that have not yet been settled
+    fn has_unsettled_packet_bundles(
+        home_roaming_network_id: RoamingNetworkIndex,
+        visited_roaming_network_id: RoamingNetworkIndex,
+    ) -> bool;
+}
+
+impl<RoamingNetworkIndex, BlockNumber: Zero> RoamingAgreementSettlements<RoamingNetworkIndex, BlockNumber> for () {
+    fn settlement_period() -> BlockNumber {
+        Zero::zero()
+    }
+
+    fn has_unsettled_packet_bundles(
+        _home_roaming_network_id: RoamingNetworkIndex,
+        _visited_roaming_network_id: RoamingNetworkIndex,
+    ) -> bool {
+        false
+    }
 }
 
-#[derive(Encode, Decode, Clone, PartialEq, Eq)]
-#[cfg_attr(feature = "std", derive(Debug))]
-pub struct RoamingAgreementPolicy(pub [u8; 16]);
+/// The activation type of the agreement policies whose activated agreements allow roaming sessions to be handed over
+/// between the network of the policy and the visited network
+pub const HANDOVER_ACTIVATION_TYPE: &[u8] = b"handover";
+
+type BalanceOf<T> = <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 
 #[derive(Encode, Debug, Decode, Default, Clone, PartialEq)]
 // Generic type parameters - Balance
@@ -57,6 +107,13 @@ pub struct RoamingAgreementPolicySetting<U, V> {
     pub policy_expiry_block: V,
 }
 
+#[derive(Encode, Debug, Decode, Default, Clone, PartialEq)]
+// Generic type parameters - AccountId, Balance
+pub struct RoamingAgreementDeposit<U, V> {
+    pub deposit_depositor: U,
+    pub deposit_amount: V,
+}
+
 decl_event!(
     pub enum Event<T> where
         <T as frame_system::Config>::AccountId,
@@ -65,32 +122,59 @@ decl_event!(
         <T as roaming_accounting_policies::Config>::RoamingAccountingPolicyIndex,
         <T as roaming_networks::Config>::RoamingNetworkIndex,
         <T as frame_system::Config>::BlockNumber,
+        Balance = BalanceOf<T>,
     {
-        /// A roaming agreement_policy is created. (owner, roaming_agreement_policy_id)
-        Created(AccountId, RoamingAgreementPolicyIndex),
-        /// A roaming agreement_policy is transferred. (from, to, roaming_agreement_policy_id)
-        Transferred(AccountId, AccountId, RoamingAgreementPolicyIndex),
         /// A roaming agreement_policy configuration
         RoamingAgreementPolicySettingSet(AccountId, RoamingAgreementPolicyIndex, RoamingAgreementPolicyActivationType, BlockNumber),
         /// A roaming agreement_policy is assigned to a accounting_policy. (owner of network, roaming_agreement_policy_id, roaming_accounting_policy_id)
         AssignedAgreementPolicyToAccountingPolicy(AccountId, RoamingAgreementPolicyIndex, RoamingAccountingPolicyIndex),
         /// A roaming agreement_policy is assigned to a network. (owner of network, roaming_agreement_policy_id, roaming_network_id)
         AssignedAgreementPolicyToNetwork(AccountId, RoamingAgreementPolicyIndex, RoamingNetworkIndex),
+        /// A roaming agreement is activated with a visited network and a deposit is reserved.
+        /// (owner of home network, roaming_agreement_policy_id, visited roaming_network_id, deposit)
+        AgreementActivated(AccountId, RoamingAgreementPolicyIndex, RoamingNetworkIndex, Balance),
+        /// An amount of a roaming agreement deposit is paid to the visited network.
+        /// (beneficiary, roaming_agreement_policy_id, visited roaming_network_id, amount)
+        AgreementDepositDrawn(AccountId, RoamingAgreementPolicyIndex, RoamingNetworkIndex, Balance),
+        /// The remainder of a roaming agreement deposit is returned after the agreement policy expired and its packet
+        /// bundles were settled.
+        /// (depositor, roaming_agreement_policy_id, visited roaming_network_id, remainder)
+        AgreementDepositReleased(AccountId, RoamingAgreementPolicyIndex, RoamingNetworkIndex, Balance),
+        /// A roaming agreement_policy is removed. (owner, roaming_agreement_policy_id)
+        Removed(AccountId, RoamingAgreementPolicyIndex),
     }
 );
 
+decl_error! {
+    pub enum Error for Module<T: Config> {
+        /// The agreement policy has not been assigned to a home network
+        AgreementPolicyWithoutNetwork,
+        /// The agreement policy has no config or its policy expiry block has passed
+        AgreementPolicyExpired,
+        /// The agreement policy has not yet expired
+        AgreementPolicyNotExpired,
+        /// An agreement cannot be activated between a network and itself
+        AgreementWithOwnNetwork,
+        /// The agreement has already been activated with the visited network
+        AgreementAlreadyActivated,
+        /// The deposit must be greater than zero
+        ZeroDeposit,
+        /// There is no deposit for the agreement with the visited network
+        NoAgreementDeposit,
+        /// The agreement policy still holds deposits for agreements with visited networks
+        AgreementDepositsHeld,
+        /// The policy expiry block may not be brought forward while the agreement policy holds deposits
+        ExpiryBroughtForwardWhileDepositsHeld,
+        /// The settlement period of the packet bundles received up to the policy expiry block has not yet elapsed
+        SettlementPeriodNotElapsed,
+        /// The visited network received packet bundles of the home network that have not yet been settled
+        UnsettledPacketBundles,
+    }
+}
+
 // This module's storage items.
 decl_storage! {
     trait Store for Module<T: Config> as RoamingAgreementPolicies {
-        /// Stores all the roaming agreement_policy, key is the roaming agreement_policy id / index
-        pub RoamingAgreementPolicies get(fn roaming_agreement_policy): map hasher(opaque_blake2_256) T::RoamingAgreementPolicyIndex => Option<RoamingAgreementPolicy>;
-
-        /// Stores the total number of roaming agreement_policies. i.e. the next roaming agreement_policy index
-        pub RoamingAgreementPoliciesCount get(fn roaming_agreement_policies_count): T::RoamingAgreementPolicyIndex;
-
-        /// Get roaming agreement_policy owner
-        pub RoamingAgreementPolicyOwners get(fn roaming_agreement_policy_owner): map hasher(opaque_blake2_256) T::RoamingAgreementPolicyIndex => Option<T::AccountId>;
-
         /// Get roaming agreement_policy config
         pub RoamingAgreementPolicySettings get(fn roaming_agreement_policy_settings): map hasher(opaque_blake2_256) T::RoamingAgreementPolicyIndex => Option<RoamingAgreementPolicySetting<T::RoamingAgreementPolicyActivationType, T::BlockNumber>>;
 
@@ -104,7 +188,13 @@ decl_storage! {
         pub RoamingAgreementPolicyAccountingPolicy get(fn roaming_agreement_policy_accounting_policy): map hasher(opaque_blake2_256) T::RoamingAgreementPolicyIndex => Option<T::RoamingAccountingPolicyIndex>;
 
         /// Get roaming accounting_policy's agreement policies
-        pub RoamingAccountingPolicyAgreementPolicies get(fn roaming_accounting_policy_agreement_policies): map hasher(opaque_blake2_256) T::RoamingAccountingPolicyIndex => Option<Vec<T::RoamingAgreementPolicyIndex>>
+        pub RoamingAccountingPolicyAgreementPolicies get(fn roaming_accounting_policy_agreement_policies): map hasher(opaque_blake2_256) T::RoamingAccountingPolicyIndex => Option<Vec<T::RoamingAgreementPolicyIndex>>;
+
+        /// Get the deposit reserved by the home network of an agreement policy for its agreement with a visited network
+        pub RoamingAgreementDeposits get(fn roaming_agreement_deposit): map hasher(opaque_blake2_256) (T::RoamingAgreementPolicyIndex, T::RoamingNetworkIndex) => Option<RoamingAgreementDeposit<T::AccountId, BalanceOf<T>>>;
+
+        /// Get the visited networks that an agreement policy holds deposits for
+        pub RoamingAgreementPolicyVisitedNetworks get(fn roaming_agreement_policy_visited_networks): map hasher(opaque_blake2_256) T::RoamingAgreementPolicyIndex => Option<Vec<T::RoamingNetworkIndex>>
     }
 }
 
@@ -114,38 +204,7 @@ decl_module! {
     pub struct Module<T: Config> for enum Call where origin: T::Origin {
         fn deposit_event() = default;
 
-        /// Create a new roaming agreement_policy
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn create(origin) {
-            let sender = ensure_signed(origin)?;
-            let roaming_agreement_policy_id = Self::next_roaming_agreement_policy_id()?;
-
-            let unique_id = Self::random_value(&sender);
-            // if env::config::get_env() == "TEST" {
-            //     unique_id = [0; 16];
-            // } else {
-                // Generate a random 128bit value
-                // unique_id = Self::random_value(&sender);
-            // }
-
-            // Create and store roaming agreement_policy
-            let roaming_agreement_policy = RoamingAgreementPolicy(unique_id);
-            Self::insert_roaming_agreement_policy(&sender, roaming_agreement_policy_id, roaming_agreement_policy);
-
-            Self::deposit_event(RawEvent::Created(sender, roaming_agreement_policy_id));
-        }
-
-        /// Transfer a roaming agreement_policy to new owner
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn transfer(origin, to: T::AccountId, roaming_agreement_policy_id: T::RoamingAgreementPolicyIndex) {
-            let sender = ensure_signed(origin)?;
-
-            ensure!(Self::roaming_agreement_policy_owner(roaming_agreement_policy_id) == Some(sender.clone()), "Only owner can transfer roaming agreement_policy");
-
-            Self::update_owner(&to, roaming_agreement_policy_id);
-
-            Self::deposit_event(RawEvent::Transferred(sender, to, roaming_agreement_policy_id));
-        }
+        type Error = Error<T>;
 
         /// Set roaming agreement_policy config
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
@@ -162,7 +221,7 @@ decl_module! {
             ensure!(is_roaming_agreement_policy, "RoamingAgreementPolicy does not exist");
 
             // Ensure that the caller is owner of the agreement policy config they are trying to change
-            ensure!(Self::roaming_agreement_policy_owner(roaming_agreement_policy_id) == Some(sender.clone()), "Only owner can set config for roaming agreement_policy");
+            ensure!(T::RoamingAgreementPolicyRegistry::owner_of(roaming_agreement_policy_id) == Some(sender.clone()), "Only owner can set config for roaming agreement_policy");
 
             let policy_activation_type = match _policy_activation_type {
                 Some(value) => value,
@@ -173,6 +232,15 @@ decl_module! {
                 None => Default::default() // <timestamp::Module<T>>::get() // Default
             };
 
+            // Packet bundles may be settled from the deposits held by the agreement policy until it expires
+            if let Some(policy_setting) = Self::roaming_agreement_policy_settings(roaming_agreement_policy_id) {
+                ensure!(
+                    policy_expiry_block >= policy_setting.policy_expiry_block ||
+                        Self::roaming_agreement_policy_visited_networks(roaming_agreement_policy_id).is_none(),
+                    Error::<T>::ExpiryBroughtForwardWhileDepositsHeld
+                );
+            }
+
             // Check if a roaming agreement policy config already exists with the given roaming agreement policy id
             // to determine whether to insert new or mutate existing.
             if Self::has_value_for_agreement_policy_setting_index(roaming_agreement_policy_id).is_ok() {
@@ -226,6 +294,7 @@ decl_module! {
         // Since we want users to be allowed to create and configure multiple policies and profiles for reuse.
         // They will then be associated with any specific networks when the user creates each network (roaming base) profile.
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        #[transactional]
         pub fn assign_agreement_policy_to_network(
             origin,
             roaming_agreement_policy_id: T::RoamingAgreementPolicyIndex,
@@ -238,18 +307,16 @@ decl_module! {
                 ::exists_roaming_network(roaming_network_id).is_ok();
             ensure!(is_roaming_network, "RoamingNetwork does not exist");
 
-            // Ensure that caller of the function is the owner of the network id to assign the agreement_policy to
+            // Ensure that caller of the function is authorized for the network id to assign the agreement_policy to
             ensure!(
-                <roaming_networks::Module<T>>::is_roaming_network_owner(roaming_network_id, sender.clone()).is_ok(),
-                "Only the roaming network owner can assign itself a roaming agreement policy"
+                <roaming_networks::Module<T>>::is_authorized(roaming_network_id, &sender),
+                "Only an account authorized for the roaming network can assign itself a roaming agreement policy"
             );
 
-            Self::associate_agreement_policy_with_network(roaming_agreement_policy_id, roaming_network_id)
-                .expect("Unable to associate agreement policy with network");
+            Self::associate_agreement_policy_with_network(roaming_agreement_policy_id, roaming_network_id)?;
 
             // Ensure that the given agreement_policy id already exists
-            let roaming_agreement_policy = Self::roaming_agreement_policy(roaming_agreement_policy_id);
-            ensure!(roaming_agreement_policy.is_some(), "Invalid roaming_agreement_policy_id");
+            ensure!(T::RoamingAgreementPolicyRegistry::exists(roaming_agreement_policy_id), "Invalid roaming_agreement_policy_id");
 
             // Ensure that the agreement_policy is not already owned by a different network
             // Unassign the agreement_policy from any existing network since it may only be owned by one network
@@ -262,6 +329,7 @@ decl_module! {
         }
 
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        #[transactional]
         pub fn assign_agreement_policy_to_accounting_policy(
             origin,
             roaming_agreement_policy_id: T::RoamingAgreementPolicyIndex,
@@ -274,18 +342,19 @@ decl_module! {
                 ::exists_roaming_accounting_policy(roaming_accounting_policy_id).is_ok();
             ensure!(is_roaming_accounting_policy, "RoamingAccountingPolicy does not exist");
 
-            // Ensure that caller of the function is the owner of the accounting_policy id to assign the agreement_policy to
+            // Ensure that caller of the function is authorized for the accounting_policy id to assign the agreement_policy to
             ensure!(
-                <roaming_accounting_policies::Module<T>>::is_roaming_accounting_policy_owner(roaming_accounting_policy_id, sender.clone()).is_ok(),
-                "Only the roaming accounting_policy owner can assign itself a roaming agreement policy"
+                <roaming_accounting_policies::Module<T>>::is_authorized(roaming_accounting_policy_id, &sender),
+                "Only an account authorized for the roaming accounting_policy can assign itself a roaming agreement policy"
             );
 
-            Self::associate_agreement_policy_with_accounting_policy(roaming_agreement_policy_id, roaming_accounting_policy_id)
-                .expect("Unable to associate agreement policy with accounting_policy");
+            Self::associate_agreement_policy_with_accounting_policy(
+                roaming_agreement_policy_id,
+                roaming_accounting_policy_id,
+            )?;
 
             // Ensure that the given agreement_policy id already exists
-            let roaming_agreement_policy = Self::roaming_agreement_policy(roaming_agreement_policy_id);
-            ensure!(roaming_agreement_policy.is_some(), "Invalid roaming_agreement_policy_id");
+            ensure!(T::RoamingAgreementPolicyRegistry::exists(roaming_agreement_policy_id), "Invalid roaming_agreement_policy_id");
 
             // Ensure that the agreement_policy is not already owned by a different accounting_policy
             // Unassign the agreement_policy from any existing accounting_policy since it may only be owned by one accounting_policy
@@ -296,6 +365,103 @@ decl_module! {
 
             Self::deposit_event(RawEvent::AssignedAgreementPolicyToAccountingPolicy(sender, roaming_agreement_policy_id, roaming_accounting_policy_id));
         }
+
+        /// Activate the agreement of a home network's agreement policy with a visited network by reserving a deposit
+        /// from an account authorized for the home network. Packet bundle settlements with the visited network draw
+        /// from the deposit first.
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(5, 2)]
+        pub fn activate_agreement(
+            origin,
+            roaming_agreement_policy_id: T::RoamingAgreementPolicyIndex,
+            visited_roaming_network_id: T::RoamingNetworkIndex,
+            deposit: BalanceOf<T>
+        ) {
+            let sender = ensure_signed(origin)?;
+
+            Self::exists_roaming_agreement_policy(roaming_agreement_policy_id)?;
+            let home_roaming_network_id = Self::roaming_agreement_policy_network(roaming_agreement_policy_id)
+                .ok_or(Error::<T>::AgreementPolicyWithoutNetwork)?;
+
+            ensure!(
+                <roaming_networks::Module<T>>::is_authorized(home_roaming_network_id, &sender),
+                "Only an account authorized for the home network of the roaming agreement policy can activate the agreement"
+            );
+            <roaming_networks::Module<T>>::exists_roaming_network(visited_roaming_network_id)?;
+            ensure!(home_roaming_network_id != visited_roaming_network_id, Error::<T>::AgreementWithOwnNetwork);
+            ensure!(Self::is_unexpired_agreement_policy(roaming_agreement_policy_id), Error::<T>::AgreementPolicyExpired);
+            ensure!(
+                Self::roaming_agreement_deposit((roaming_agreement_policy_id, visited_roaming_network_id)).is_none(),
+                Error::<T>::AgreementAlreadyActivated
+            );
+            ensure!(!deposit.is_zero(), Error::<T>::ZeroDeposit);
+
+            <T as Config>::Currency::reserve(&sender, deposit)?;
+
+            <RoamingAgreementDeposits<T>>::insert((roaming_agreement_policy_id, visited_roaming_network_id), RoamingAgreementDeposit {
+                deposit_depositor: sender.clone(),
+                deposit_amount: deposit,
+            });
+            <RoamingAgreementPolicyVisitedNetworks<T>>::append(roaming_agreement_policy_id, visited_roaming_network_id);
+
+            Self::deposit_event(RawEvent::AgreementActivated(sender, roaming_agreement_policy_id, visited_roaming_network_id, deposit));
+        }
+
+        /// Return the remainder of an agreement deposit to its depositor once the settlement period after the policy
+        /// expiry block of the agreement policy has passed and the packet bundles of its home network received by the
+        /// visited network have been settled. May be called by anyone.
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(5, 2)]
+        pub fn release_agreement_deposit(
+            origin,
+            roaming_agreement_policy_id: T::RoamingAgreementPolicyIndex,
+            visited_roaming_network_id: T::RoamingNetworkIndex
+        ) {
+            let _sender = ensure_signed(origin)?;
+
+            let agreement_deposit = Self::roaming_agreement_deposit((roaming_agreement_policy_id, visited_roaming_network_id))
+                .ok_or(Error::<T>::NoAgreementDeposit)?;
+            ensure!(!Self::is_unexpired_agreement_policy(roaming_agreement_policy_id), Error::<T>::AgreementPolicyNotExpired);
+            let policy_expiry_block = Self::roaming_agreement_policy_settings(roaming_agreement_policy_id)
+                .map(|policy_setting| policy_setting.policy_expiry_block)
+                .unwrap_or_default();
+            ensure!(
+                policy_expiry_block.saturating_add(T::RoamingAgreementSettlements::settlement_period()) <=
+                    <frame_system::Module<T>>::block_number(),
+                Error::<T>::SettlementPeriodNotElapsed
+            );
+            if let Some(home_roaming_network_id) = Self::roaming_agreement_policy_network(roaming_agreement_policy_id) {
+                ensure!(
+                    !T::RoamingAgreementSettlements::has_unsettled_packet_bundles(
+                        home_roaming_network_id,
+                        visited_roaming_network_id
+                    ),
+                    Error::<T>::UnsettledPacketBundles
+                );
+            }
+
+            let remainder = agreement_deposit.deposit_amount;
+            <T as Config>::Currency::unreserve(&agreement_deposit.deposit_depositor, remainder);
+            Self::remove_agreement_deposit(roaming_agreement_policy_id, visited_roaming_network_id);
+
+            Self::deposit_event(RawEvent::AgreementDepositReleased(
+                agreement_deposit.deposit_depositor,
+                roaming_agreement_policy_id,
+                visited_roaming_network_id,
+                remainder
+            ));
+        }
+
+        /// Remove a roaming agreement policy
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(4, 7)]
+        #[transactional]
+        pub fn remove(origin, roaming_agreement_policy_id: T::RoamingAgreementPolicyIndex) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(T::RoamingAgreementPolicyRegistry::owner_of(roaming_agreement_policy_id) == Some(sender.clone()), "Only owner can remove roaming agreement_policy");
+
+            Self::remove_roaming_agreement_policy(roaming_agreement_policy_id)?;
+
+            Self::deposit_event(RawEvent::Removed(sender, roaming_agreement_policy_id));
+        }
     }
 }
 
@@ -305,7 +471,7 @@ impl<T: Config> Module<T> {
         sender: T::AccountId,
     ) -> Result<(), DispatchError> {
         ensure!(
-            Self::roaming_agreement_policy_owner(&roaming_agreement_policy_id)
+            T::RoamingAgreementPolicyRegistry::owner_of(roaming_agreement_policy_id)
                 .map(|owner| owner == sender)
                 .unwrap_or(false),
             "Sender is not owner of RoamingAgreementPolicy"
@@ -335,11 +501,12 @@ impl<T: Config> Module<T> {
 
     pub fn exists_roaming_agreement_policy(
         roaming_agreement_policy_id: T::RoamingAgreementPolicyIndex,
-    ) -> Result<RoamingAgreementPolicy, DispatchError> {
-        match Self::roaming_agreement_policy(roaming_agreement_policy_id) {
-            Some(value) => Ok(value),
-            None => Err(DispatchError::Other("RoamingAgreementPolicy does not exist")),
-        }
+    ) -> Result<(), DispatchError> {
+        ensure!(
+            T::RoamingAgreementPolicyRegistry::exists(roaming_agreement_policy_id),
+            "RoamingAgreementPolicy does not exist"
+        );
+        Ok(())
     }
 
     pub fn exists_roaming_agreement_policy_setting(
@@ -364,6 +531,118 @@ impl<T: Config> Module<T> {
         Err(DispatchError::Other("No value for agreement policy config"))
     }
 
+    /// Check that the given network has at least one agreement policy whose config has not yet expired
+    pub fn has_unexpired_agreement_policy_for_network(
+        roaming_network_id: T::RoamingNetworkIndex,
+    ) -> Result<(), DispatchError> {
+        let has_unexpired_agreement_policy = Self::roaming_network_agreement_policies(roaming_network_id)
+            .unwrap_or_default()
+            .into_iter()
+            .any(|roaming_agreement_policy_id| Self::is_unexpired_agreement_policy(roaming_agreement_policy_id));
+        ensure!(has_unexpired_agreement_policy, "RoamingNetwork does not have an unexpired RoamingAgreementPolicy");
+        Ok(())
+    }
+
+    /// Check whether either of the given networks has an unexpired "handover" agreement policy whose agreement with
+    /// the other network is activated
+    pub fn has_handover_agreement_between_networks(
+        roaming_network_id: T::RoamingNetworkIndex,
+        other_roaming_network_id: T::RoamingNetworkIndex,
+    ) -> bool {
+        Self::has_activated_handover_agreement(roaming_network_id, other_roaming_network_id) ||
+            Self::has_activated_handover_agreement(other_roaming_network_id, roaming_network_id)
+    }
+
+    fn has_activated_handover_agreement(
+        home_roaming_network_id: T::RoamingNetworkIndex,
+        visited_roaming_network_id: T::RoamingNetworkIndex,
+    ) -> bool {
+        Self::roaming_network_agreement_policies(home_roaming_network_id).unwrap_or_default().into_iter().any(
+            |roaming_agreement_policy_id| {
+                Self::is_unexpired_agreement_policy(roaming_agreement_policy_id) &&
+                    Self::roaming_agreement_policy_settings(roaming_agreement_policy_id)
+                        .map_or(false, |policy_setting| {
+                            policy_setting.policy_activation_type.as_ref() == HANDOVER_ACTIVATION_TYPE
+                        }) &&
+                    Self::roaming_agreement_policy_visited_networks(roaming_agreement_policy_id)
+                        .unwrap_or_default()
+                        .contains(&visited_roaming_network_id)
+            },
+        )
+    }
+
+    /// Check that the config of the given agreement policy exists and its policy expiry block has not yet passed
+    pub fn is_unexpired_agreement_policy(roaming_agreement_policy_id: T::RoamingAgreementPolicyIndex) -> bool {
+        let current_block_number = <frame_system::Module<T>>::block_number();
+        Self::roaming_agreement_policy_settings(roaming_agreement_policy_id)
+            .map(|policy_setting| policy_setting.policy_expiry_block > current_block_number)
+            .unwrap_or(false)
+    }
+
+    /// Pay up to the given amount to the beneficiary from the deposits that the home network reserved for its
+    /// agreements with the visited network. Deposits of expired agreement policies are drawn from until they are
+    /// released. Returns the amount that was paid from the deposits.
+    pub fn draw_agreement_deposit(
+        home_roaming_network_id: T::RoamingNetworkIndex,
+        visited_roaming_network_id: T::RoamingNetworkIndex,
+        beneficiary: &T::AccountId,
+        amount: BalanceOf<T>,
+    ) -> BalanceOf<T> {
+        let mut drawn_amount: BalanceOf<T> = Zero::zero();
+        let roaming_agreement_policy_ids =
+            Self::roaming_network_agreement_policies(home_roaming_network_id).unwrap_or_default();
+        for roaming_agreement_policy_id in roaming_agreement_policy_ids {
+            let outstanding_amount = amount.saturating_sub(drawn_amount);
+            if outstanding_amount.is_zero() {
+                break;
+            }
+            let key = (roaming_agreement_policy_id, visited_roaming_network_id);
+            let mut agreement_deposit = match Self::roaming_agreement_deposit(key) {
+                Some(agreement_deposit) => agreement_deposit,
+                None => continue,
+            };
+
+            let draw_amount = outstanding_amount.min(agreement_deposit.deposit_amount);
+            // The amount that could not be moved because less than expected was actually reserved
+            let unmoved_amount = <T as Config>::Currency::repatriate_reserved(
+                &agreement_deposit.deposit_depositor,
+                beneficiary,
+                draw_amount,
+                BalanceStatus::Free,
+            )
+            .unwrap_or(draw_amount);
+            let moved_amount = draw_amount.saturating_sub(unmoved_amount);
+
+            agreement_deposit.deposit_amount = agreement_deposit.deposit_amount.saturating_sub(draw_amount);
+            if agreement_deposit.deposit_amount.is_zero() {
+                Self::remove_agreement_deposit(roaming_agreement_policy_id, visited_roaming_network_id);
+            } else {
+                <RoamingAgreementDeposits<T>>::insert(key, agreement_deposit);
+            }
+
+            if !moved_amount.is_zero() {
+                drawn_amount = drawn_amount.saturating_add(moved_amount);
+                Self::deposit_event(RawEvent::AgreementDepositDrawn(
+                    beneficiary.clone(),
+                    roaming_agreement_policy_id,
+                    visited_roaming_network_id,
+                    moved_amount,
+                ));
+            }
+        }
+        drawn_amount
+    }
+
+    fn remove_agreement_deposit(
+        roaming_agreement_policy_id: T::RoamingAgreementPolicyIndex,
+        visited_roaming_network_id: T::RoamingNetworkIndex,
+    ) {
+        <RoamingAgreementDeposits<T>>::remove((roaming_agreement_policy_id, visited_roaming_network_id));
+        <RoamingAgreementPolicyVisitedNetworks<T>>::mutate_exists(roaming_agreement_policy_id, |network_ids| {
+            *network_ids = without_roaming_entity(network_ids.take(), &visited_roaming_network_id)
+        });
+    }
+
     /// Only push the agreement policy id onto the end of the vector if it does not already exist
     pub fn associate_agreement_policy_with_network(
         roaming_agreement_policy_id: T::RoamingAgreementPolicyIndex,
@@ -450,36 +729,82 @@ impl<T: Config> Module<T> {
         }
     }
 
-    fn random_value(sender: &T::AccountId) -> [u8; 16] {
-        let payload = (
-            T::Randomness::random(&[0]),
-            sender,
-            <frame_system::Module<T>>::extrinsic_index(),
-            <frame_system::Module<T>>::block_number(),
+    /// Remove a roaming agreement policy along with its links to its network and accounting policy
+    pub fn remove_roaming_agreement_policy(
+        roaming_agreement_policy_id: T::RoamingAgreementPolicyIndex,
+    ) -> DispatchResult {
+        Self::exists_roaming_agreement_policy(roaming_agreement_policy_id)?;
+        ensure!(
+            Self::roaming_agreement_policy_visited_networks(roaming_agreement_policy_id).is_none(),
+            Error::<T>::AgreementDepositsHeld
         );
-        payload.using_encoded(blake2_128)
+
+        if let Some(roaming_network_id) = <RoamingAgreementPolicyNetwork<T>>::take(roaming_agreement_policy_id) {
+            <RoamingNetworkAgreementPolicies<T>>::mutate_exists(roaming_network_id, |agreement_policy_ids| {
+                *agreement_policy_ids =
+                    without_roaming_entity(agreement_policy_ids.take(), &roaming_agreement_policy_id)
+            });
+        }
+        if let Some(roaming_accounting_policy_id) =
+            <RoamingAgreementPolicyAccountingPolicy<T>>::take(roaming_agreement_policy_id)
+        {
+            <RoamingAccountingPolicyAgreementPolicies<T>>::mutate_exists(
+                roaming_accounting_policy_id,
+                |agreement_policy_ids| {
+                    *agreement_policy_ids =
+                        without_roaming_entity(agreement_policy_ids.take(), &roaming_agreement_policy_id)
+                },
+            );
+        }
+        T::RoamingAgreementPolicyRegistry::deregister(roaming_agreement_policy_id)?;
+        <RoamingAgreementPolicySettings<T>>::remove(roaming_agreement_policy_id);
+        Ok(())
+    }
+}
+
+/// The agreement policies of a roaming network, other than those that have since been assigned to another network
+impl<T: Config> RoamingEntityChildren<RoamingNetworkEntity, T::RoamingNetworkIndex> for Module<T> {
+    fn has_children(roaming_network_id: T::RoamingNetworkIndex) -> bool {
+        Self::roaming_network_agreement_policies(roaming_network_id).unwrap_or_default().into_iter().any(
+            |agreement_policy_id| {
+                Self::roaming_agreement_policy_network(agreement_policy_id) == Some(roaming_network_id)
+            },
+        )
     }
 
-    fn next_roaming_agreement_policy_id() -> Result<T::RoamingAgreementPolicyIndex, DispatchError> {
-        let roaming_agreement_policy_id = Self::roaming_agreement_policies_count();
-        if roaming_agreement_policy_id == <T::RoamingAgreementPolicyIndex as Bounded>::max_value() {
-            return Err(DispatchError::Other("RoamingAgreementPolicies count overflow"));
+    fn remove_children(roaming_network_id: T::RoamingNetworkIndex) -> DispatchResult {
+        for agreement_policy_id in <RoamingNetworkAgreementPolicies<T>>::take(roaming_network_id).unwrap_or_default() {
+            if Self::roaming_agreement_policy_network(agreement_policy_id) == Some(roaming_network_id) {
+                Self::remove_roaming_agreement_policy(agreement_policy_id)?;
+            }
         }
-        Ok(roaming_agreement_policy_id)
+        Ok(())
     }
+}
 
-    fn insert_roaming_agreement_policy(
-        owner: &T::AccountId,
-        roaming_agreement_policy_id: T::RoamingAgreementPolicyIndex,
-        roaming_agreement_policy: RoamingAgreementPolicy,
-    ) {
-        // Create and store roaming agreement_policy
-        <RoamingAgreementPolicies<T>>::insert(roaming_agreement_policy_id, roaming_agreement_policy);
-        <RoamingAgreementPoliciesCount<T>>::put(roaming_agreement_policy_id + One::one());
-        <RoamingAgreementPolicyOwners<T>>::insert(roaming_agreement_policy_id, owner.clone());
+/// The agreement policies of a roaming accounting policy, other than those that have since been assigned to another
+/// accounting policy
+impl<T: Config> RoamingEntityChildren<RoamingAccountingPolicyEntity, T::RoamingAccountingPolicyIndex> for Module<T> {
+    fn has_children(roaming_accounting_policy_id: T::RoamingAccountingPolicyIndex) -> bool {
+        Self::roaming_accounting_policy_agreement_policies(roaming_accounting_policy_id)
+            .unwrap_or_default()
+            .into_iter()
+            .any(|agreement_policy_id| {
+                Self::roaming_agreement_policy_accounting_policy(agreement_policy_id) ==
+                    Some(roaming_accounting_policy_id)
+            })
     }
 
-    fn update_owner(to: &T::AccountId, roaming_agreement_policy_id: T::RoamingAgreementPolicyIndex) {
-        <RoamingAgreementPolicyOwners<T>>::insert(roaming_agreement_policy_id, to);
+    fn remove_children(roaming_accounting_policy_id: T::RoamingAccountingPolicyIndex) -> DispatchResult {
+        let agreement_policy_ids =
+            <RoamingAccountingPolicyAgreementPolicies<T>>::take(roaming_accounting_policy_id).unwrap_or_default();
+        for agreement_policy_id in agreement_policy_ids {
+            if Self::roaming_agreement_policy_accounting_policy(agreement_policy_id) ==
+                Some(roaming_accounting_policy_id)
+            {
+                Self::remove_roaming_agreement_policy(agreement_policy_id)?;
+            }
+        }
+        Ok(())
     }
 }
@@ -3,10 +3,12 @@
 use crate::{
     Module,
     Config,
+    RoamingAgreementSettlements,
 };
 
 use frame_support::{
     parameter_types,
+    traits::Get,
     weights::{
         IdentityFee,
         Weight,
@@ -88,30 +90,104 @@ impl pallet_transaction_payment::Config for Test {
     type WeightToFee = IdentityFee<u64>;
 }
 
-impl roaming_operators::Config for Test {
+impl roaming_registry::Config<roaming_registry::Instance1> for Test {
     type Currency = Balances;
+    type EntityDeposit = ();
     type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
     type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+impl roaming_operators::Config for Test {
+    type Currency = Balances;
+    type Event = ();
+    type RemovalPolicy = ();
+    type RoamingOperatorChildren = ();
     type RoamingOperatorIndex = u64;
+    type RoamingOperatorRegistry = RoamingOperatorRegistry;
+}
+impl roaming_registry::Config<roaming_registry::Instance2> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
 }
 impl roaming_networks::Config for Test {
     type Event = ();
+    type RoamingNetworkChildren = ();
     type RoamingNetworkIndex = u64;
+    type RoamingNetworkRegistry = RoamingNetworkRegistry;
+}
+impl roaming_registry::Config<roaming_registry::Instance8> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+parameter_types! {
+    pub const MaxSubscriptionRenewalsPerBlock: u32 = 2;
+    pub const SubscriptionBillingPeriod: u64 = 10;
 }
 impl roaming_accounting_policies::Config for Test {
     type Event = ();
+    type MaxSubscriptionRenewalsPerBlock = MaxSubscriptionRenewalsPerBlock;
+    type RoamingAccountingPolicyChildren = ();
     type RoamingAccountingPolicyDownlinkFeeFactor = u32;
     type RoamingAccountingPolicyIndex = u64;
-    type RoamingAccountingPolicyType = Vec<u8>;
+    type RoamingAccountingPolicyRegistry = RoamingAccountingPolicyRegistry;
     type RoamingAccountingPolicyUplinkFeeFactor = u32;
+    type SubscriptionBillingPeriod = SubscriptionBillingPeriod;
+}
+impl roaming_registry::Config<roaming_registry::Instance9> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+parameter_types! {
+    pub const PacketBundleSettlementPeriod: u64 = 5;
+}
+/// Settles packet bundles within a fixed period and never has any unsettled packet bundles
+pub struct MockAgreementSettlements;
+impl RoamingAgreementSettlements<u64, u64> for MockAgreementSettlements {
+    fn settlement_period() -> u64 {
+        PacketBundleSettlementPeriod::get()
+    }
+
+    fn has_unsettled_packet_bundles(_home_roaming_network_id: u64, _visited_roaming_network_id: u64) -> bool {
+        false
+    }
 }
 impl Config for Test {
+    type Currency = Balances;
     type Event = ();
     type RoamingAgreementPolicyActivationType = Vec<u8>;
     type RoamingAgreementPolicyIndex = u64;
+    type RoamingAgreementPolicyRegistry = RoamingAgreementPolicyRegistry;
+    type RoamingAgreementSettlements = MockAgreementSettlements;
 }
 
 pub type RoamingAgreementPolicyModule = Module<Test>;
+pub type RoamingNetworkModule = roaming_networks::Module<Test>;
+pub type RoamingAccountingPolicyModule = roaming_accounting_policies::Module<Test>;
+pub type RoamingOperatorRegistry = roaming_registry::Module<Test, roaming_registry::Instance1>;
+pub type RoamingNetworkRegistry = roaming_registry::Module<Test, roaming_registry::Instance2>;
+pub type RoamingAccountingPolicyRegistry = roaming_registry::Module<Test, roaming_registry::Instance8>;
+pub type RoamingAgreementPolicyRegistry = roaming_registry::Module<Test, roaming_registry::Instance9>;
 
 // This function basically just builds a genesis storage key/value store according to
 // our desired mockup.
@@ -11,74 +11,225 @@ use frame_support::{
 fn basic_setup_works() {
     new_test_ext().execute_with(|| {
         // Verify Initial Storage
-        assert_eq!(RoamingAgreementPolicyModule::roaming_agreement_policies_count(), 0);
-        assert!(RoamingAgreementPolicyModule::roaming_agreement_policy(0).is_none());
-        assert_eq!(RoamingAgreementPolicyModule::roaming_agreement_policy_owner(0), None);
+        assert_eq!(RoamingAgreementPolicyRegistry::roaming_entities_count(), 0);
+        assert!(RoamingAgreementPolicyRegistry::roaming_entity(0).is_none());
+        assert_eq!(RoamingAgreementPolicyRegistry::roaming_entity_owner(0), None);
         assert_eq!(Balances::free_balance(1), 10);
         assert_eq!(Balances::free_balance(2), 20);
     });
 }
 
+// Setup agreement policy 0 of home network 0 owned by 1 that expires at the given block, and visited network 1
+// owned by 2
+fn setup_agreement_policy_of_home_network(policy_expiry_block: u64) {
+    assert_ok!(RoamingNetworkRegistry::create(Origin::signed(1)));
+    assert_ok!(RoamingNetworkRegistry::create(Origin::signed(2)));
+    assert_ok!(RoamingAgreementPolicyRegistry::create(Origin::signed(1)));
+    assert_ok!(RoamingAgreementPolicyModule::set_config(
+        Origin::signed(1),
+        0,
+        Some(b"passive".to_vec()),
+        Some(policy_expiry_block)
+    ));
+    assert_ok!(RoamingAgreementPolicyModule::assign_agreement_policy_to_network(Origin::signed(1), 0, 0));
+}
+
+#[test]
+fn activate_agreement_works() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        setup_agreement_policy_of_home_network(10);
+        // Call Functions
+        assert_ok!(RoamingAgreementPolicyModule::activate_agreement(Origin::signed(1), 0, 1, 4));
+        // Verify Storage
+        assert_eq!(
+            RoamingAgreementPolicyModule::roaming_agreement_deposit((0, 1)),
+            Some(RoamingAgreementDeposit {
+                deposit_depositor: 1,
+                deposit_amount: 4,
+            })
+        );
+        assert_eq!(Balances::free_balance(1), 6);
+        assert_eq!(Balances::reserved_balance(1), 4);
+    });
+}
+
 #[test]
-fn create_works() {
+fn operator_owner_manages_agreements_of_its_networks() {
     new_test_ext().execute_with(|| {
+        // Setup - home network 0 of operator 0 and its accounting policy are owned by account 3, not operator owner 1
+        assert_ok!(RoamingOperatorRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkRegistry::create(Origin::signed(3)));
+        assert_ok!(RoamingNetworkRegistry::create(Origin::signed(2)));
+        assert_ok!(RoamingNetworkModule::assign_network_to_operator(Origin::signed(1), 0, 0));
+        assert_ok!(RoamingAccountingPolicyRegistry::create(Origin::signed(3)));
+        assert_ok!(RoamingAccountingPolicyModule::assign_accounting_policy_to_network(Origin::signed(3), 0, 0));
+        assert_ok!(RoamingAgreementPolicyRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingAgreementPolicyModule::set_config(Origin::signed(1), 0, Some(b"passive".to_vec()), Some(10)));
+        assert_ok!(RoamingAgreementPolicyModule::assign_agreement_policy_to_network(Origin::signed(1), 0, 0));
         // Call Functions
-        assert_ok!(RoamingAgreementPolicyModule::create(Origin::signed(1)));
+        assert_noop!(
+            RoamingAgreementPolicyModule::assign_agreement_policy_to_accounting_policy(Origin::signed(4), 0, 0),
+            "Only an account authorized for the roaming accounting_policy can assign itself a roaming agreement policy"
+        );
+        assert_ok!(RoamingAgreementPolicyModule::assign_agreement_policy_to_accounting_policy(Origin::signed(1), 0, 0));
+        assert_noop!(
+            RoamingAgreementPolicyModule::activate_agreement(Origin::signed(4), 0, 1, 4),
+            "Only an account authorized for the home network of the roaming agreement policy can activate the \
+             agreement"
+        );
+        assert_ok!(RoamingAgreementPolicyModule::activate_agreement(Origin::signed(1), 0, 1, 4));
         // Verify Storage
-        assert_eq!(RoamingAgreementPolicyModule::roaming_agreement_policies_count(), 1);
-        assert!(RoamingAgreementPolicyModule::roaming_agreement_policy(0).is_some());
-        assert_eq!(RoamingAgreementPolicyModule::roaming_agreement_policy_owner(0), Some(1));
+        assert_eq!(RoamingAgreementPolicyModule::roaming_agreement_policy_accounting_policy(0), Some(0));
+        assert_eq!(
+            RoamingAgreementPolicyModule::roaming_agreement_deposit((0, 1)),
+            Some(RoamingAgreementDeposit {
+                deposit_depositor: 1,
+                deposit_amount: 4,
+            })
+        );
     });
 }
 
 #[test]
-fn create_handles_basic_errors() {
+fn activate_agreement_handles_basic_errors() {
     new_test_ext().execute_with(|| {
         // Setup
-        <RoamingAgreementPoliciesCount<Test>>::put(u64::max_value());
+        setup_agreement_policy_of_home_network(10);
         // Call Functions
         assert_noop!(
-            RoamingAgreementPolicyModule::create(Origin::signed(1)),
-            "RoamingAgreementPolicies count overflow"
+            RoamingAgreementPolicyModule::activate_agreement(Origin::signed(2), 0, 1, 4),
+            "Only an account authorized for the home network of the roaming agreement policy can activate the \
+             agreement"
+        );
+        assert_noop!(
+            RoamingAgreementPolicyModule::activate_agreement(Origin::signed(1), 0, 2, 4),
+            "RoamingNetwork does not exist"
+        );
+        assert_noop!(
+            RoamingAgreementPolicyModule::activate_agreement(Origin::signed(1), 0, 0, 4),
+            Error::<Test>::AgreementWithOwnNetwork
+        );
+        assert_noop!(
+            RoamingAgreementPolicyModule::activate_agreement(Origin::signed(1), 0, 1, 0),
+            Error::<Test>::ZeroDeposit
+        );
+        assert_noop!(
+            RoamingAgreementPolicyModule::activate_agreement(Origin::signed(1), 0, 1, 11),
+            pallet_balances::Error::<Test, pallet_balances::DefaultInstance>::InsufficientBalance
+        );
+        assert_ok!(RoamingAgreementPolicyModule::activate_agreement(Origin::signed(1), 0, 1, 4));
+        assert_noop!(
+            RoamingAgreementPolicyModule::activate_agreement(Origin::signed(1), 0, 1, 4),
+            Error::<Test>::AgreementAlreadyActivated
+        );
+        System::set_block_number(10);
+        assert_ok!(RoamingNetworkRegistry::create(Origin::signed(2)));
+        assert_noop!(
+            RoamingAgreementPolicyModule::activate_agreement(Origin::signed(1), 0, 2, 4),
+            Error::<Test>::AgreementPolicyExpired
         );
         // Verify Storage
-        assert_eq!(RoamingAgreementPolicyModule::roaming_agreement_policies_count(), u64::max_value());
-        assert!(RoamingAgreementPolicyModule::roaming_agreement_policy(0).is_none());
-        assert_eq!(RoamingAgreementPolicyModule::roaming_agreement_policy_owner(0), None);
+        assert_eq!(Balances::reserved_balance(1), 4);
     });
 }
 
 #[test]
-fn transfer_works() {
+fn draw_agreement_deposit_works() {
     new_test_ext().execute_with(|| {
         // Setup
-        assert_ok!(RoamingAgreementPolicyModule::create(Origin::signed(1)));
+        setup_agreement_policy_of_home_network(10);
+        assert_ok!(RoamingAgreementPolicyModule::activate_agreement(Origin::signed(1), 0, 1, 4));
         // Call Functions
-        assert_ok!(RoamingAgreementPolicyModule::transfer(Origin::signed(1), 2, 0));
+        assert_eq!(RoamingAgreementPolicyModule::draw_agreement_deposit(0, 1, &2, 3), 3);
+        assert_eq!(RoamingAgreementPolicyModule::draw_agreement_deposit(0, 1, &2, 3), 1);
+        assert_eq!(RoamingAgreementPolicyModule::draw_agreement_deposit(0, 1, &2, 3), 0);
         // Verify Storage
-        assert_eq!(RoamingAgreementPolicyModule::roaming_agreement_policies_count(), 1);
-        assert!(RoamingAgreementPolicyModule::roaming_agreement_policy(0).is_some());
-        assert_eq!(RoamingAgreementPolicyModule::roaming_agreement_policy_owner(0), Some(2));
+        assert_eq!(RoamingAgreementPolicyModule::roaming_agreement_deposit((0, 1)), None);
+        assert_eq!(Balances::free_balance(1), 6);
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(2), 24);
     });
 }
 
 #[test]
-fn transfer_handles_basic_errors() {
+fn draw_agreement_deposit_works_after_policy_expiry() {
     new_test_ext().execute_with(|| {
         // Setup
-        assert_ok!(RoamingAgreementPolicyModule::create(Origin::signed(1)));
+        setup_agreement_policy_of_home_network(10);
+        assert_ok!(RoamingAgreementPolicyModule::activate_agreement(Origin::signed(1), 0, 1, 4));
+        System::set_block_number(10);
+        // Call Functions
+        assert_eq!(RoamingAgreementPolicyModule::draw_agreement_deposit(0, 1, &2, 3), 3);
+        // Verify Storage
+        assert_eq!(Balances::reserved_balance(1), 1);
+        assert_eq!(Balances::free_balance(2), 23);
+    });
+}
+
+#[test]
+fn set_config_refuses_to_bring_expiry_forward_while_agreement_deposits_are_held() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        setup_agreement_policy_of_home_network(10);
+        assert_ok!(RoamingAgreementPolicyModule::activate_agreement(Origin::signed(1), 0, 1, 4));
+        // Call Functions
+        assert_noop!(
+            RoamingAgreementPolicyModule::set_config(Origin::signed(1), 0, Some(b"passive".to_vec()), Some(5)),
+            Error::<Test>::ExpiryBroughtForwardWhileDepositsHeld
+        );
+        assert_ok!(RoamingAgreementPolicyModule::set_config(Origin::signed(1), 0, Some(b"passive".to_vec()), Some(20)));
+        // Verify Storage
+        assert_eq!(RoamingAgreementPolicyModule::roaming_agreement_policy_settings(0).unwrap().policy_expiry_block, 20);
+    });
+}
+
+#[test]
+fn release_agreement_deposit_works() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        setup_agreement_policy_of_home_network(10);
+        assert_ok!(RoamingAgreementPolicyModule::activate_agreement(Origin::signed(1), 0, 1, 4));
+        assert_eq!(RoamingAgreementPolicyModule::draw_agreement_deposit(0, 1, &2, 1), 1);
         // Call Functions
         assert_noop!(
-            RoamingAgreementPolicyModule::transfer(Origin::signed(2), 2, 0),
-            "Only owner can transfer roaming agreement_policy"
+            RoamingAgreementPolicyModule::release_agreement_deposit(Origin::signed(3), 0, 1),
+            Error::<Test>::AgreementPolicyNotExpired
         );
+        System::set_block_number(10);
         assert_noop!(
-            RoamingAgreementPolicyModule::transfer(Origin::signed(1), 2, 1),
-            "Only owner can transfer roaming agreement_policy"
+            RoamingAgreementPolicyModule::release_agreement_deposit(Origin::signed(3), 0, 1),
+            Error::<Test>::SettlementPeriodNotElapsed
         );
+        System::set_block_number(15);
+        assert_ok!(RoamingAgreementPolicyModule::release_agreement_deposit(Origin::signed(3), 0, 1));
+        assert_noop!(
+            RoamingAgreementPolicyModule::release_agreement_deposit(Origin::signed(3), 0, 1),
+            Error::<Test>::NoAgreementDeposit
+        );
+        // Verify Storage
+        assert_eq!(RoamingAgreementPolicyModule::roaming_agreement_deposit((0, 1)), None);
+        assert_eq!(Balances::free_balance(1), 9);
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(2), 21);
+    });
+}
+
+#[test]
+fn remove_refuses_while_agreement_deposits_are_held() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        setup_agreement_policy_of_home_network(10);
+        assert_ok!(RoamingAgreementPolicyModule::activate_agreement(Origin::signed(1), 0, 1, 4));
+        // Call Functions
+        assert_noop!(RoamingAgreementPolicyModule::remove(Origin::signed(1), 0), Error::<Test>::AgreementDepositsHeld);
+        System::set_block_number(15);
+        assert_ok!(RoamingAgreementPolicyModule::release_agreement_deposit(Origin::signed(3), 0, 1));
+        assert_ok!(RoamingAgreementPolicyModule::remove(Origin::signed(1), 0));
         // Verify Storage
-        assert_eq!(RoamingAgreementPolicyModule::roaming_agreement_policies_count(), 1);
-        assert!(RoamingAgreementPolicyModule::roaming_agreement_policy(0).is_some());
-        assert_eq!(RoamingAgreementPolicyModule::roaming_agreement_policy_owner(0), Some(1));
+        assert!(RoamingAgreementPolicyRegistry::roaming_entity(0).is_none());
+        assert_eq!(RoamingAgreementPolicyModule::roaming_agreement_policy_visited_networks(0), None);
+        assert_eq!(RoamingAgreementPolicyModule::roaming_agreement_policy_network(0), None);
+        assert_eq!(Balances::reserved_balance(1), 0);
     });
 }
@@ -10,22 +10,32 @@ use frame_support::{
     decl_module,
     decl_storage,
     ensure,
-    traits::{
-        Get,
-        Randomness,
-    },
+    traits::Get,
+    transactional,
     Parameter,
 };
 use frame_system::ensure_signed;
-use sp_io::hashing::blake2_128;
 use sp_runtime::{
     traits::{
         AtLeast32Bit,
         Bounded,
         Member,
         One,
+        Zero,
     },
     DispatchError,
+    DispatchResult,
+};
+use roaming_operators::{
+    without_roaming_entity,
+    RoamingAuthority,
+    RoamingEntityChildren,
+    RoamingNetworkEntity,
+    RoamingOperatorEntity,
+};
+use roaming_registry::{
+    RoamingRegistry,
+    RoamingTradable,
 };
 use sp_std::prelude::*; // Imports Vec
 #[macro_use]
@@ -41,12 +51,12 @@ mod tests;
 pub trait Config: frame_system::Config + roaming_operators::Config + roaming_networks::Config {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
     type RoamingBillingPolicyIndex: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
+    /// The registry that holds the roaming billing policies and their owners
+    type RoamingBillingPolicyRegistry: RoamingRegistry<Self::AccountId, Self::RoamingBillingPolicyIndex>;
+    /// The maximum number of billing policies whose billing point is processed in a single block
+    type MaxBillingPoliciesPerBlock: Get<u32>;
 }
 
-#[derive(Encode, Decode, Clone, PartialEq, Eq)]
-#[cfg_attr(feature = "std", derive(Debug))]
-pub struct RoamingBillingPolicy(pub [u8; 16]);
-
 #[cfg_attr(feature = "std", derive(Debug))]
 #[derive(Encode, Decode, Default, Clone, PartialEq)]
 // Generic type parameters - Balance
@@ -63,31 +73,20 @@ decl_event!(
         <T as roaming_operators::Config>::RoamingOperatorIndex,
         <T as frame_system::Config>::BlockNumber,
     {
-        /// A roaming billing_policy is created. (owner, roaming_billing_policy_id)
-        Created(AccountId, RoamingBillingPolicyIndex),
-        /// A roaming billing_policy is transferred. (from, to, roaming_billing_policy_id)
-        Transferred(AccountId, AccountId, RoamingBillingPolicyIndex),
         /// A roaming billing_policy configuration
         RoamingBillingPolicySettingSet(AccountId, RoamingBillingPolicyIndex, BlockNumber, BlockNumber),
         /// A roaming billing_policy is assigned to a operator. (owner of network, roaming_billing_policy_id, roaming_operator_id)
         AssignedBillingPolicyToOperator(AccountId, RoamingBillingPolicyIndex, RoamingOperatorIndex),
         /// A roaming billing_policy is assigned to a network. (owner of network, roaming_billing_policy_id, roaming_network_id)
         AssignedBillingPolicyToNetwork(AccountId, RoamingBillingPolicyIndex, RoamingNetworkIndex),
+        /// A roaming billing_policy is removed. (owner, roaming_billing_policy_id)
+        Removed(AccountId, RoamingBillingPolicyIndex),
     }
 );
 
 // This module's storage items.
 decl_storage! {
     trait Store for Module<T: Config> as RoamingBillingPolicies {
-        /// Stores all the roaming billing_policy, key is the roaming billing_policy id / index
-        pub RoamingBillingPolicies get(fn roaming_billing_policy): map hasher(opaque_blake2_256) T::RoamingBillingPolicyIndex => Option<RoamingBillingPolicy>;
-
-        /// Stores the total number of roaming billing_policies. i.e. the next roaming billing_policy index
-        pub RoamingBillingPoliciesCount get(fn roaming_billing_policies_count): T::RoamingBillingPolicyIndex;
-
-        /// Get roaming billing_policy owner
-        pub RoamingBillingPolicyOwners get(fn roaming_billing_policy_owner): map hasher(opaque_blake2_256) T::RoamingBillingPolicyIndex => Option<T::AccountId>;
-
         /// Get roaming billing_policy config
         pub RoamingBillingPolicySettings get(fn roaming_billing_policy_settings): map hasher(opaque_blake2_256) T::RoamingBillingPolicyIndex => Option<RoamingBillingPolicySetting<T::BlockNumber, T::BlockNumber>>;
 
@@ -101,7 +100,13 @@ decl_storage! {
         pub RoamingBillingPolicyOperator get(fn roaming_billing_policy_operator): map hasher(opaque_blake2_256) T::RoamingBillingPolicyIndex => Option<T::RoamingOperatorIndex>;
 
         /// Get roaming operator's billing policies
-        pub RoamingOperatorBillingPolicies get(fn roaming_operator_billing_policies): map hasher(opaque_blake2_256) T::RoamingOperatorIndex => Option<Vec<T::RoamingBillingPolicyIndex>>
+        pub RoamingOperatorBillingPolicies get(fn roaming_operator_billing_policies): map hasher(opaque_blake2_256) T::RoamingOperatorIndex => Option<Vec<T::RoamingBillingPolicyIndex>>;
+
+        /// Get the block that the next billing point of a roaming billing_policy is scheduled to be processed at
+        pub RoamingBillingPolicyScheduledBlocks get(fn roaming_billing_policy_scheduled_block): map hasher(opaque_blake2_256) T::RoamingBillingPolicyIndex => Option<T::BlockNumber>;
+
+        /// Get the roaming billing_policies whose billing point is scheduled to be processed at a block
+        pub RoamingBillingPolicySchedule get(fn roaming_billing_policy_schedule): map hasher(opaque_blake2_256) T::BlockNumber => Vec<T::RoamingBillingPolicyIndex>
     }
 }
 
@@ -111,33 +116,7 @@ decl_module! {
     pub struct Module<T: Config> for enum Call where origin: T::Origin {
         fn deposit_event() = default;
 
-        /// Create a new roaming billing_policy
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn create(origin) {
-            let sender = ensure_signed(origin)?;
-            let roaming_billing_policy_id = Self::next_roaming_billing_policy_id()?;
-
-            // Generate a random 128bit value
-            let unique_id = Self::random_value(&sender);
-
-            // Create and store roaming billing_policy
-            let roaming_billing_policy = RoamingBillingPolicy(unique_id);
-            Self::insert_roaming_billing_policy(&sender, roaming_billing_policy_id, roaming_billing_policy);
-
-            Self::deposit_event(RawEvent::Created(sender, roaming_billing_policy_id));
-        }
-
-        /// Transfer a roaming billing_policy to new owner
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn transfer(origin, to: T::AccountId, roaming_billing_policy_id: T::RoamingBillingPolicyIndex) {
-            let sender = ensure_signed(origin)?;
-
-            ensure!(Self::roaming_billing_policy_owner(roaming_billing_policy_id) == Some(sender.clone()), "Only owner can transfer roaming billing_policy");
-
-            Self::update_owner(&to, roaming_billing_policy_id);
-
-            Self::deposit_event(RawEvent::Transferred(sender, to, roaming_billing_policy_id));
-        }
+        const MaxBillingPoliciesPerBlock: u32 = T::MaxBillingPoliciesPerBlock::get();
 
         /// Set roaming billing_policy config
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
@@ -154,7 +133,7 @@ decl_module! {
             ensure!(is_roaming_billing_policy, "RoamingBillingPolicy does not exist");
 
             // Ensure that the caller is owner of the billing policy config they are trying to change
-            ensure!(Self::roaming_billing_policy_owner(roaming_billing_policy_id) == Some(sender.clone()), "Only owner can set config for roaming billing_policy");
+            ensure!(T::RoamingBillingPolicyRegistry::owner_of(roaming_billing_policy_id) == Some(sender.clone()), "Only owner can set config for roaming billing_policy");
 
             // let is_owned_by_parent_relationship = Self::is_owned_by_required_parent_relationship(roaming_billing_policy_id, sender.clone()).is_ok();
             // ensure!(is_owned_by_parent_relationship, "Ownership by parent does not exist");
@@ -209,6 +188,8 @@ decl_module! {
                 }
             }
 
+            Self::schedule_billing_policy(roaming_billing_policy_id, policy_next_billing_at_block);
+
             Self::deposit_event(RawEvent::RoamingBillingPolicySettingSet(
                 sender,
                 roaming_billing_policy_id,
@@ -218,6 +199,7 @@ decl_module! {
         }
 
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        #[transactional]
         pub fn assign_billing_policy_to_network(
             origin,
             roaming_billing_policy_id: T::RoamingBillingPolicyIndex,
@@ -230,18 +212,16 @@ decl_module! {
                 ::exists_roaming_network(roaming_network_id).is_ok();
             ensure!(is_roaming_network, "RoamingNetwork does not exist");
 
-            // Ensure that caller of the function is the owner of the network id to assign the billing_policy to
+            // Ensure that caller of the function is authorized for the network id to assign the billing_policy to
             ensure!(
-                <roaming_networks::Module<T>>::is_roaming_network_owner(roaming_network_id, sender.clone()).is_ok(),
-                "Only the roaming network owner can assign itself a roaming billing policy"
+                <roaming_networks::Module<T>>::is_authorized(roaming_network_id, &sender),
+                "Only an account authorized for the roaming network can assign itself a roaming billing policy"
             );
 
-            Self::associate_billing_policy_with_network(roaming_billing_policy_id, roaming_network_id)
-                .expect("Unable to associate billing policy with network");
+            Self::associate_billing_policy_with_network(roaming_billing_policy_id, roaming_network_id)?;
 
             // Ensure that the given billing_policy id already exists
-            let roaming_billing_policy = Self::roaming_billing_policy(roaming_billing_policy_id);
-            ensure!(roaming_billing_policy.is_some(), "Invalid roaming_billing_policy_id");
+            ensure!(T::RoamingBillingPolicyRegistry::exists(roaming_billing_policy_id), "Invalid roaming_billing_policy_id");
 
             // Ensure that the billing_policy is not already owned by a different network
             // Unassign the billing_policy from any existing network since it may only be owned by one network
@@ -254,6 +234,7 @@ decl_module! {
         }
 
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        #[transactional]
         pub fn assign_billing_policy_to_operator(
             origin,
             roaming_billing_policy_id: T::RoamingBillingPolicyIndex,
@@ -266,18 +247,16 @@ decl_module! {
                 ::exists_roaming_operator(roaming_operator_id).is_ok();
             ensure!(is_roaming_operator, "RoamingOperator does not exist");
 
-            // Ensure that caller of the function is the owner of the operator id to assign the billing_policy to
+            // Ensure that caller of the function is authorized for the operator id to assign the billing_policy to
             ensure!(
-                <roaming_operators::Module<T>>::is_roaming_operator_owner(roaming_operator_id, sender.clone()).is_ok(),
-                "Only the roaming operator owner can assign itself a roaming billing policy"
+                <roaming_operators::Module<T>>::is_authorized(roaming_operator_id, &sender),
+                "Only an account authorized for the roaming operator can assign itself a roaming billing policy"
             );
 
-            Self::associate_billing_policy_with_operator(roaming_billing_policy_id, roaming_operator_id)
-                .expect("Unable to associate billing policy with operator");
+            Self::associate_billing_policy_with_operator(roaming_billing_policy_id, roaming_operator_id)?;
 
             // Ensure that the given billing_policy id already exists
-            let roaming_billing_policy = Self::roaming_billing_policy(roaming_billing_policy_id);
-            ensure!(roaming_billing_policy.is_some(), "Invalid roaming_billing_policy_id");
+            ensure!(T::RoamingBillingPolicyRegistry::exists(roaming_billing_policy_id), "Invalid roaming_billing_policy_id");
 
             // Ensure that the billing_policy is not already owned by a different operator
             // Unassign the billing_policy from any existing operator since it may only be owned by one operator
@@ -288,6 +267,19 @@ decl_module! {
 
             Self::deposit_event(RawEvent::AssignedBillingPolicyToOperator(sender, roaming_billing_policy_id, roaming_operator_id));
         }
+
+        /// Remove a roaming billing policy
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(5, 9)]
+        #[transactional]
+        pub fn remove(origin, roaming_billing_policy_id: T::RoamingBillingPolicyIndex) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(T::RoamingBillingPolicyRegistry::owner_of(roaming_billing_policy_id) == Some(sender.clone()), "Only owner can remove roaming billing_policy");
+
+            Self::remove_roaming_billing_policy(roaming_billing_policy_id)?;
+
+            Self::deposit_event(RawEvent::Removed(sender, roaming_billing_policy_id));
+        }
     }
 }
 
@@ -297,7 +289,7 @@ impl<T: Config> Module<T> {
         sender: T::AccountId,
     ) -> Result<(), DispatchError> {
         ensure!(
-            Self::roaming_billing_policy_owner(&roaming_billing_policy_id)
+            T::RoamingBillingPolicyRegistry::owner_of(roaming_billing_policy_id)
                 .map(|owner| owner == sender)
                 .unwrap_or(false),
             "Sender is not owner of RoamingBillingPolicy"
@@ -326,11 +318,12 @@ impl<T: Config> Module<T> {
 
     pub fn exists_roaming_billing_policy(
         roaming_billing_policy_id: T::RoamingBillingPolicyIndex,
-    ) -> Result<RoamingBillingPolicy, DispatchError> {
-        match Self::roaming_billing_policy(roaming_billing_policy_id) {
-            Some(value) => Ok(value),
-            None => Err(DispatchError::Other("RoamingBillingPolicy does not exist")),
-        }
+    ) -> Result<(), DispatchError> {
+        ensure!(
+            T::RoamingBillingPolicyRegistry::exists(roaming_billing_policy_id),
+            "RoamingBillingPolicy does not exist"
+        );
+        Ok(())
     }
 
     pub fn exists_roaming_billing_policy_setting(
@@ -355,6 +348,73 @@ impl<T: Config> Module<T> {
         Err(DispatchError::Other("No value for billing policy config"))
     }
 
+    /// Schedule the billing point of a roaming billing policy to be processed at the given block, or at the next
+    /// block with capacity if the maximum number of billing policies per block has been reached.
+    /// Any previously scheduled billing point of the billing policy is replaced.
+    pub fn schedule_billing_policy(roaming_billing_policy_id: T::RoamingBillingPolicyIndex, billing_block: T::BlockNumber) {
+        Self::unschedule_billing_policy(roaming_billing_policy_id);
+
+        // A billing point that is not in the future cannot be processed by `on_initialize`
+        if billing_block <= <frame_system::Module<T>>::block_number() {
+            debug::info!("Not scheduling billing policy {:?} since its billing point is not in the future", roaming_billing_policy_id);
+            return;
+        }
+
+        let max_billing_policies_per_block = T::MaxBillingPoliciesPerBlock::get().max(1) as usize;
+        let mut scheduled_block = billing_block;
+        while <RoamingBillingPolicySchedule<T>>::decode_len(scheduled_block).unwrap_or(0) >= max_billing_policies_per_block {
+            scheduled_block = scheduled_block + One::one();
+        }
+
+        debug::info!("Scheduling billing policy {:?} at block {:?}", roaming_billing_policy_id, scheduled_block);
+        <RoamingBillingPolicySchedule<T>>::append(scheduled_block, roaming_billing_policy_id);
+        <RoamingBillingPolicyScheduledBlocks<T>>::insert(roaming_billing_policy_id, scheduled_block);
+    }
+
+    /// Remove the scheduled billing point of a roaming billing policy if it has one
+    pub fn unschedule_billing_policy(roaming_billing_policy_id: T::RoamingBillingPolicyIndex) {
+        if let Some(scheduled_block) = <RoamingBillingPolicyScheduledBlocks<T>>::take(roaming_billing_policy_id) {
+            <RoamingBillingPolicySchedule<T>>::mutate_exists(scheduled_block, |schedule| {
+                if let Some(billing_policy_ids) = schedule.as_mut() {
+                    billing_policy_ids.retain(|id| *id != roaming_billing_policy_id);
+                }
+                if schedule.as_ref().map_or(false, |billing_policy_ids| billing_policy_ids.is_empty()) {
+                    *schedule = None;
+                }
+            });
+        }
+    }
+
+    /// Take the roaming billing policies whose billing point is scheduled to be processed at the given block
+    pub fn take_billing_policies_due_at(block_number: T::BlockNumber) -> Vec<T::RoamingBillingPolicyIndex> {
+        let billing_policy_ids = <RoamingBillingPolicySchedule<T>>::take(block_number);
+        for roaming_billing_policy_id in billing_policy_ids.iter() {
+            <RoamingBillingPolicyScheduledBlocks<T>>::remove(roaming_billing_policy_id);
+        }
+        billing_policy_ids
+    }
+
+    /// Advance the next billing point of a roaming billing policy by its frequency and schedule it.
+    /// Returns the next billing point, or None if the billing policy has no config or no frequency.
+    pub fn advance_billing_policy(roaming_billing_policy_id: T::RoamingBillingPolicyIndex) -> Option<T::BlockNumber> {
+        let mut policy_setting = Self::roaming_billing_policy_settings(roaming_billing_policy_id)?;
+        if policy_setting.policy_frequency_in_blocks.is_zero() {
+            return None;
+        }
+
+        let current_block_number = <frame_system::Module<T>>::block_number();
+        let mut next_billing_at_block =
+            policy_setting.policy_next_billing_at_block + policy_setting.policy_frequency_in_blocks;
+        if next_billing_at_block <= current_block_number {
+            next_billing_at_block = current_block_number + policy_setting.policy_frequency_in_blocks;
+        }
+        policy_setting.policy_next_billing_at_block = next_billing_at_block;
+        <RoamingBillingPolicySettings<T>>::insert(roaming_billing_policy_id, &policy_setting);
+        Self::schedule_billing_policy(roaming_billing_policy_id, next_billing_at_block);
+
+        Some(next_billing_at_block)
+    }
+
     /// Only push the billing policy id onto the end of the vector if it does not already exist
     pub fn associate_billing_policy_with_network(
         roaming_billing_policy_id: T::RoamingBillingPolicyIndex,
@@ -425,36 +485,58 @@ impl<T: Config> Module<T> {
         }
     }
 
-    fn random_value(sender: &T::AccountId) -> [u8; 16] {
-        let payload = (
-            T::Randomness::random(&[0]),
-            sender,
-            <frame_system::Module<T>>::extrinsic_index(),
-            <frame_system::Module<T>>::block_number(),
-        );
-        payload.using_encoded(blake2_128)
+    /// Remove a roaming billing policy along with its links to its network and operator
+    pub fn remove_roaming_billing_policy(roaming_billing_policy_id: T::RoamingBillingPolicyIndex) -> DispatchResult {
+        Self::exists_roaming_billing_policy(roaming_billing_policy_id)?;
+        Self::unschedule_billing_policy(roaming_billing_policy_id);
+        if let Some(roaming_network_id) = <RoamingBillingPolicyNetwork<T>>::take(roaming_billing_policy_id) {
+            <RoamingNetworkBillingPolicies<T>>::mutate_exists(roaming_network_id, |billing_policy_ids| {
+                *billing_policy_ids = without_roaming_entity(billing_policy_ids.take(), &roaming_billing_policy_id)
+            });
+        }
+        if let Some(roaming_operator_id) = <RoamingBillingPolicyOperator<T>>::take(roaming_billing_policy_id) {
+            <RoamingOperatorBillingPolicies<T>>::mutate_exists(roaming_operator_id, |billing_policy_ids| {
+                *billing_policy_ids = without_roaming_entity(billing_policy_ids.take(), &roaming_billing_policy_id)
+            });
+        }
+        T::RoamingBillingPolicyRegistry::deregister(roaming_billing_policy_id)?;
+        <RoamingBillingPolicySettings<T>>::remove(roaming_billing_policy_id);
+        Ok(())
+    }
+}
+
+/// The billing policies of a roaming network, other than those that have since been assigned to another network
+impl<T: Config> RoamingEntityChildren<RoamingNetworkEntity, T::RoamingNetworkIndex> for Module<T> {
+    fn has_children(roaming_network_id: T::RoamingNetworkIndex) -> bool {
+        Self::roaming_network_billing_policies(roaming_network_id).unwrap_or_default().into_iter().any(
+            |billing_policy_id| Self::roaming_billing_policy_network(billing_policy_id) == Some(roaming_network_id),
+        )
     }
 
-    fn next_roaming_billing_policy_id() -> Result<T::RoamingBillingPolicyIndex, DispatchError> {
-        let roaming_billing_policy_id = Self::roaming_billing_policies_count();
-        if roaming_billing_policy_id == <T::RoamingBillingPolicyIndex as Bounded>::max_value() {
-            return Err(DispatchError::Other("RoamingBillingPolicies count overflow"));
+    fn remove_children(roaming_network_id: T::RoamingNetworkIndex) -> DispatchResult {
+        for billing_policy_id in <RoamingNetworkBillingPolicies<T>>::take(roaming_network_id).unwrap_or_default() {
+            if Self::roaming_billing_policy_network(billing_policy_id) == Some(roaming_network_id) {
+                Self::remove_roaming_billing_policy(billing_policy_id)?;
+            }
         }
-        Ok(roaming_billing_policy_id)
+        Ok(())
     }
+}
 
-    fn insert_roaming_billing_policy(
-        owner: &T::AccountId,
-        roaming_billing_policy_id: T::RoamingBillingPolicyIndex,
-        roaming_billing_policy: RoamingBillingPolicy,
-    ) {
-        // Create and store roaming billing_policy
-        <RoamingBillingPolicies<T>>::insert(roaming_billing_policy_id, roaming_billing_policy);
-        <RoamingBillingPoliciesCount<T>>::put(roaming_billing_policy_id + One::one());
-        <RoamingBillingPolicyOwners<T>>::insert(roaming_billing_policy_id, owner.clone());
+/// The billing policies of a roaming operator, other than those that have since been assigned to another operator
+impl<T: Config> RoamingEntityChildren<RoamingOperatorEntity, T::RoamingOperatorIndex> for Module<T> {
+    fn has_children(roaming_operator_id: T::RoamingOperatorIndex) -> bool {
+        Self::roaming_operator_billing_policies(roaming_operator_id).unwrap_or_default().into_iter().any(
+            |billing_policy_id| Self::roaming_billing_policy_operator(billing_policy_id) == Some(roaming_operator_id),
+        )
     }
 
-    fn update_owner(to: &T::AccountId, roaming_billing_policy_id: T::RoamingBillingPolicyIndex) {
-        <RoamingBillingPolicyOwners<T>>::insert(roaming_billing_policy_id, to);
+    fn remove_children(roaming_operator_id: T::RoamingOperatorIndex) -> DispatchResult {
+        for billing_policy_id in <RoamingOperatorBillingPolicies<T>>::take(roaming_operator_id).unwrap_or_default() {
+            if Self::roaming_billing_policy_operator(billing_policy_id) == Some(roaming_operator_id) {
+                Self::remove_roaming_billing_policy(billing_policy_id)?;
+            }
+        }
+        Ok(())
     }
 }
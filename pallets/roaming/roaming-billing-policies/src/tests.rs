@@ -11,71 +11,76 @@ use frame_support::{
 fn basic_setup_works() {
     new_test_ext().execute_with(|| {
         // Verify Initial Storage
-        assert_eq!(RoamingBillingPolicyModule::roaming_billing_policies_count(), 0);
-        assert!(RoamingBillingPolicyModule::roaming_billing_policy(0).is_none());
-        assert_eq!(RoamingBillingPolicyModule::roaming_billing_policy_owner(0), None);
+        assert_eq!(RoamingBillingPolicyRegistry::roaming_entities_count(), 0);
+        assert!(RoamingBillingPolicyRegistry::roaming_entity(0).is_none());
+        assert_eq!(RoamingBillingPolicyRegistry::roaming_entity_owner(0), None);
         assert_eq!(Balances::free_balance(1), 10);
         assert_eq!(Balances::free_balance(2), 20);
     });
 }
 
 #[test]
-fn create_works() {
-    new_test_ext().execute_with(|| {
-        // Call Functions
-        assert_ok!(RoamingBillingPolicyModule::create(Origin::signed(1)));
-        // Verify Storage
-        assert_eq!(RoamingBillingPolicyModule::roaming_billing_policies_count(), 1);
-        assert!(RoamingBillingPolicyModule::roaming_billing_policy(0).is_some());
-        assert_eq!(RoamingBillingPolicyModule::roaming_billing_policy_owner(0), Some(1));
-    });
-}
-
-#[test]
-fn create_handles_basic_errors() {
+fn set_config_schedules_billing_point() {
     new_test_ext().execute_with(|| {
         // Setup
-        <RoamingBillingPoliciesCount<Test>>::put(u64::max_value());
+        assert_ok!(RoamingBillingPolicyRegistry::create(Origin::signed(1)));
         // Call Functions
-        assert_noop!(RoamingBillingPolicyModule::create(Origin::signed(1)), "RoamingBillingPolicies count overflow");
+        assert_ok!(RoamingBillingPolicyModule::set_config(Origin::signed(1), 0, Some(10), Some(5)));
         // Verify Storage
-        assert_eq!(RoamingBillingPolicyModule::roaming_billing_policies_count(), u64::max_value());
-        assert!(RoamingBillingPolicyModule::roaming_billing_policy(0).is_none());
-        assert_eq!(RoamingBillingPolicyModule::roaming_billing_policy_owner(0), None);
+        assert_eq!(RoamingBillingPolicyModule::roaming_billing_policy_scheduled_block(0), Some(10));
+        assert_eq!(RoamingBillingPolicyModule::roaming_billing_policy_schedule(10), vec![0]);
+
+        // Rescheduling replaces the previous billing point
+        assert_ok!(RoamingBillingPolicyModule::set_config(Origin::signed(1), 0, Some(20), Some(5)));
+        assert!(RoamingBillingPolicyModule::roaming_billing_policy_schedule(10).is_empty());
+        assert_eq!(RoamingBillingPolicyModule::roaming_billing_policy_schedule(20), vec![0]);
+
+        // A billing point that is not in the future is not scheduled
+        assert_ok!(RoamingBillingPolicyModule::set_config(Origin::signed(1), 0, Some(1), Some(5)));
+        assert_eq!(RoamingBillingPolicyModule::roaming_billing_policy_scheduled_block(0), None);
+        assert!(RoamingBillingPolicyModule::roaming_billing_policy_schedule(20).is_empty());
     });
 }
 
 #[test]
-fn transfer_works() {
+fn schedule_billing_policy_is_bounded_per_block() {
     new_test_ext().execute_with(|| {
         // Setup
-        assert_ok!(RoamingBillingPolicyModule::create(Origin::signed(1)));
-        // Call Functions
-        assert_ok!(RoamingBillingPolicyModule::transfer(Origin::signed(1), 2, 0));
+        for roaming_billing_policy_id in 0..3 {
+            assert_ok!(RoamingBillingPolicyRegistry::create(Origin::signed(1)));
+            assert_ok!(RoamingBillingPolicyModule::set_config(
+                Origin::signed(1),
+                roaming_billing_policy_id,
+                Some(10),
+                Some(5)
+            ));
+        }
         // Verify Storage
-        assert_eq!(RoamingBillingPolicyModule::roaming_billing_policies_count(), 1);
-        assert!(RoamingBillingPolicyModule::roaming_billing_policy(0).is_some());
-        assert_eq!(RoamingBillingPolicyModule::roaming_billing_policy_owner(0), Some(2));
+        assert_eq!(RoamingBillingPolicyModule::roaming_billing_policy_schedule(10), vec![0, 1]);
+        assert_eq!(RoamingBillingPolicyModule::roaming_billing_policy_schedule(11), vec![2]);
+        assert_eq!(RoamingBillingPolicyModule::roaming_billing_policy_scheduled_block(2), Some(11));
     });
 }
 
 #[test]
-fn transfer_handles_basic_errors() {
+fn advance_billing_policy_works() {
     new_test_ext().execute_with(|| {
         // Setup
-        assert_ok!(RoamingBillingPolicyModule::create(Origin::signed(1)));
+        assert_ok!(RoamingBillingPolicyRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingBillingPolicyModule::set_config(Origin::signed(1), 0, Some(10), Some(5)));
+        System::set_block_number(10);
         // Call Functions
-        assert_noop!(
-            RoamingBillingPolicyModule::transfer(Origin::signed(2), 2, 0),
-            "Only owner can transfer roaming billing_policy"
-        );
-        assert_noop!(
-            RoamingBillingPolicyModule::transfer(Origin::signed(1), 2, 1),
-            "Only owner can transfer roaming billing_policy"
-        );
+        assert_eq!(RoamingBillingPolicyModule::take_billing_policies_due_at(10), vec![0]);
+        assert_eq!(RoamingBillingPolicyModule::advance_billing_policy(0), Some(15));
         // Verify Storage
-        assert_eq!(RoamingBillingPolicyModule::roaming_billing_policies_count(), 1);
-        assert!(RoamingBillingPolicyModule::roaming_billing_policy(0).is_some());
-        assert_eq!(RoamingBillingPolicyModule::roaming_billing_policy_owner(0), Some(1));
+        assert_eq!(
+            RoamingBillingPolicyModule::roaming_billing_policy_settings(0).map(|s| s.policy_next_billing_at_block),
+            Some(15)
+        );
+        assert_eq!(RoamingBillingPolicyModule::roaming_billing_policy_schedule(15), vec![0]);
+
+        // A billing policy without a frequency is only billed once
+        assert_ok!(RoamingBillingPolicyModule::set_config(Origin::signed(1), 0, Some(20), Some(0)));
+        assert_eq!(RoamingBillingPolicyModule::advance_billing_policy(0), None);
     });
 }
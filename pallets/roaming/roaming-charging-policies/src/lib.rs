@@ -10,22 +10,30 @@ use frame_support::{
     decl_module,
     decl_storage,
     ensure,
-    traits::{
-        Get,
-        Randomness,
-    },
+    traits::Get,
+    transactional,
     Parameter,
 };
 use frame_system::ensure_signed;
-use sp_io::hashing::blake2_128;
 use sp_runtime::{
     traits::{
         AtLeast32Bit,
         Bounded,
         Member,
-        One,
     },
     DispatchError,
+    DispatchResult,
+};
+use roaming_operators::{
+    without_roaming_entity,
+    RoamingAuthority,
+    RoamingEntityChildren,
+    RoamingNetworkEntity,
+    RoamingOperatorEntity,
+};
+use roaming_registry::{
+    RoamingRegistry,
+    RoamingTradable,
 };
 use sp_std::prelude::*; // Imports Vec
 #[macro_use]
@@ -41,12 +49,10 @@ mod tests;
 pub trait Config: frame_system::Config + roaming_operators::Config + roaming_networks::Config {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
     type RoamingChargingPolicyIndex: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
+    /// The registry that holds the roaming charging policies and their owners
+    type RoamingChargingPolicyRegistry: RoamingRegistry<Self::AccountId, Self::RoamingChargingPolicyIndex>;
 }
 
-#[derive(Encode, Decode, Clone, PartialEq, Eq)]
-#[cfg_attr(feature = "std", derive(Debug))]
-pub struct RoamingChargingPolicy(pub [u8; 16]);
-
 #[cfg_attr(feature = "std", derive(Debug))]
 #[derive(Encode, Decode, Default, Clone, PartialEq)]
 // Generic type parameters - Balance
@@ -63,31 +69,20 @@ decl_event!(
         <T as roaming_operators::Config>::RoamingOperatorIndex,
         <T as frame_system::Config>::BlockNumber,
     {
-        /// A roaming charging_policy is created. (owner, roaming_charging_policy_id)
-        Created(AccountId, RoamingChargingPolicyIndex),
-        /// A roaming charging_policy is transferred. (from, to, roaming_charging_policy_id)
-        Transferred(AccountId, AccountId, RoamingChargingPolicyIndex),
         /// A roaming charging_policy configuration
         RoamingChargingPolicySettingSet(AccountId, RoamingChargingPolicyIndex, BlockNumber, BlockNumber),
         /// A roaming charging_policy is assigned to a operator. (owner of network, roaming_charging_policy_id, roaming_operator_id)
         AssignedChargingPolicyToOperator(AccountId, RoamingChargingPolicyIndex, RoamingOperatorIndex),
         /// A roaming charging_policy is assigned to a network. (owner of network, roaming_charging_policy_id, roaming_network_id)
         AssignedChargingPolicyToNetwork(AccountId, RoamingChargingPolicyIndex, RoamingNetworkIndex),
+        /// A roaming charging_policy is removed. (owner, roaming_charging_policy_id)
+        Removed(AccountId, RoamingChargingPolicyIndex),
     }
 );
 
 // This module's storage items.
 decl_storage! {
     trait Store for Module<T: Config> as RoamingChargingPolicies {
-        /// Stores all the roaming charging_policy, key is the roaming charging_policy id / index
-        pub RoamingChargingPolicies get(fn roaming_charging_policy): map hasher(opaque_blake2_256) T::RoamingChargingPolicyIndex => Option<RoamingChargingPolicy>;
-
-        /// Stores the total number of roaming charging_policies. i.e. the next roaming charging_policy index
-        pub RoamingChargingPoliciesCount get(fn roaming_charging_policies_count): T::RoamingChargingPolicyIndex;
-
-        /// Get roaming charging_policy owner
-        pub RoamingChargingPolicyOwners get(fn roaming_charging_policy_owner): map hasher(opaque_blake2_256) T::RoamingChargingPolicyIndex => Option<T::AccountId>;
-
         /// Get roaming charging_policy config
         pub RoamingChargingPolicySettings get(fn roaming_charging_policy_settings): map hasher(opaque_blake2_256) T::RoamingChargingPolicyIndex => Option<RoamingChargingPolicySetting<T::BlockNumber, T::BlockNumber>>;
 
@@ -111,34 +106,6 @@ decl_module! {
     pub struct Module<T: Config> for enum Call where origin: T::Origin {
         fn deposit_event() = default;
 
-        /// Create a new roaming charging_policy
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn create(origin) {
-            let sender = ensure_signed(origin)?;
-            let roaming_charging_policy_id = Self::next_roaming_charging_policy_id()?;
-
-            // Generate a random 128bit value
-            let unique_id = Self::random_value(&sender);
-
-            // Create and store roaming charging_policy
-            let roaming_charging_policy = RoamingChargingPolicy(unique_id);
-            Self::insert_roaming_charging_policy(&sender, roaming_charging_policy_id, roaming_charging_policy);
-
-            Self::deposit_event(RawEvent::Created(sender, roaming_charging_policy_id));
-        }
-
-        /// Transfer a roaming charging_policy to new owner
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn transfer(origin, to: T::AccountId, roaming_charging_policy_id: T::RoamingChargingPolicyIndex) {
-            let sender = ensure_signed(origin)?;
-
-            ensure!(Self::roaming_charging_policy_owner(roaming_charging_policy_id) == Some(sender.clone()), "Only owner can transfer roaming charging_policy");
-
-            Self::update_owner(&to, roaming_charging_policy_id);
-
-            Self::deposit_event(RawEvent::Transferred(sender, to, roaming_charging_policy_id));
-        }
-
         /// Set roaming charging_policy config
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
         pub fn set_config(
@@ -154,7 +121,7 @@ decl_module! {
             ensure!(is_roaming_charging_policy, "RoamingChargingPolicy does not exist");
 
             // Ensure that the caller is owner of the charging policy config they are trying to change
-            ensure!(Self::roaming_charging_policy_owner(roaming_charging_policy_id) == Some(sender.clone()), "Only owner can set config for roaming charging_policy");
+            ensure!(T::RoamingChargingPolicyRegistry::owner_of(roaming_charging_policy_id) == Some(sender.clone()), "Only owner can set config for roaming charging_policy");
 
             // let is_owned_by_parent_relationship = Self::is_owned_by_required_parent_relationship(roaming_charging_policy_id, sender.clone()).is_ok();
             // ensure!(is_owned_by_parent_relationship, "Ownership by parent does not exist");
@@ -218,6 +185,7 @@ decl_module! {
         }
 
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        #[transactional]
         pub fn assign_charging_policy_to_network(
             origin,
             roaming_charging_policy_id: T::RoamingChargingPolicyIndex,
@@ -230,18 +198,16 @@ decl_module! {
                 ::exists_roaming_network(roaming_network_id).is_ok();
             ensure!(is_roaming_network, "RoamingNetwork does not exist");
 
-            // Ensure that caller of the function is the owner of the network id to assign the charging_policy to
+            // Ensure that caller of the function is authorized for the network id to assign the charging_policy to
             ensure!(
-                <roaming_networks::Module<T>>::is_roaming_network_owner(roaming_network_id, sender.clone()).is_ok(),
-                "Only the roaming network owner can assign itself a roaming charging policy"
+                <roaming_networks::Module<T>>::is_authorized(roaming_network_id, &sender),
+                "Only an account authorized for the roaming network can assign itself a roaming charging policy"
             );
 
-            Self::associate_charging_policy_with_network(roaming_charging_policy_id, roaming_network_id)
-                .expect("Unable to associate charging policy with network");
+            Self::associate_charging_policy_with_network(roaming_charging_policy_id, roaming_network_id)?;
 
             // Ensure that the given charging_policy id already exists
-            let roaming_charging_policy = Self::roaming_charging_policy(roaming_charging_policy_id);
-            ensure!(roaming_charging_policy.is_some(), "Invalid roaming_charging_policy_id");
+            ensure!(T::RoamingChargingPolicyRegistry::exists(roaming_charging_policy_id), "Invalid roaming_charging_policy_id");
 
             // Ensure that the charging_policy is not already owned by a different network
             // Unassign the charging_policy from any existing network since it may only be owned by one network
@@ -254,6 +220,7 @@ decl_module! {
         }
 
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        #[transactional]
         pub fn assign_charging_policy_to_operator(
             origin,
             roaming_charging_policy_id: T::RoamingChargingPolicyIndex,
@@ -266,18 +233,16 @@ decl_module! {
                 ::exists_roaming_operator(roaming_operator_id).is_ok();
             ensure!(is_roaming_operator, "RoamingOperator does not exist");
 
-            // Ensure that caller of the function is the owner of the operator id to assign the charging_policy to
+            // Ensure that caller of the function is authorized for the operator id to assign the charging_policy to
             ensure!(
-                <roaming_operators::Module<T>>::is_roaming_operator_owner(roaming_operator_id, sender.clone()).is_ok(),
-                "Only the roaming operator owner can assign itself a roaming charging policy"
+                <roaming_operators::Module<T>>::is_authorized(roaming_operator_id, &sender),
+                "Only an account authorized for the roaming operator can assign itself a roaming charging policy"
             );
 
-            Self::associate_charging_policy_with_operator(roaming_charging_policy_id, roaming_operator_id)
-                .expect("Unable to associate charging policy with operator");
+            Self::associate_charging_policy_with_operator(roaming_charging_policy_id, roaming_operator_id)?;
 
             // Ensure that the given charging_policy id already exists
-            let roaming_charging_policy = Self::roaming_charging_policy(roaming_charging_policy_id);
-            ensure!(roaming_charging_policy.is_some(), "Invalid roaming_charging_policy_id");
+            ensure!(T::RoamingChargingPolicyRegistry::exists(roaming_charging_policy_id), "Invalid roaming_charging_policy_id");
 
             // Ensure that the charging_policy is not already owned by a different operator
             // Unassign the charging_policy from any existing operator since it may only be owned by one operator
@@ -288,6 +253,19 @@ decl_module! {
 
             Self::deposit_event(RawEvent::AssignedChargingPolicyToOperator(sender, roaming_charging_policy_id, roaming_operator_id));
         }
+
+        /// Remove a roaming charging policy
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(4, 7)]
+        #[transactional]
+        pub fn remove(origin, roaming_charging_policy_id: T::RoamingChargingPolicyIndex) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(T::RoamingChargingPolicyRegistry::owner_of(roaming_charging_policy_id) == Some(sender.clone()), "Only owner can remove roaming charging_policy");
+
+            Self::remove_roaming_charging_policy(roaming_charging_policy_id)?;
+
+            Self::deposit_event(RawEvent::Removed(sender, roaming_charging_policy_id));
+        }
     }
 }
 
@@ -297,7 +275,7 @@ impl<T: Config> Module<T> {
         sender: T::AccountId,
     ) -> Result<(), DispatchError> {
         ensure!(
-            Self::roaming_charging_policy_owner(&roaming_charging_policy_id)
+            T::RoamingChargingPolicyRegistry::owner_of(roaming_charging_policy_id)
                 .map(|owner| owner == sender)
                 .unwrap_or(false),
             "Sender is not owner of RoamingChargingPolicy"
@@ -334,11 +312,12 @@ impl<T: Config> Module<T> {
 
     pub fn exists_roaming_charging_policy(
         roaming_charging_policy_id: T::RoamingChargingPolicyIndex,
-    ) -> Result<RoamingChargingPolicy, DispatchError> {
-        match Self::roaming_charging_policy(roaming_charging_policy_id) {
-            Some(value) => Ok(value),
-            None => Err(DispatchError::Other("RoamingChargingPolicy does not exist")),
-        }
+    ) -> Result<(), DispatchError> {
+        ensure!(
+            T::RoamingChargingPolicyRegistry::exists(roaming_charging_policy_id),
+            "RoamingChargingPolicy does not exist"
+        );
+        Ok(())
     }
 
     pub fn exists_roaming_charging_policy_setting(
@@ -363,6 +342,30 @@ impl<T: Config> Module<T> {
         Err(DispatchError::Other("No value for charging policy config"))
     }
 
+    /// Get the first roaming charging policy of the given network that has a config
+    pub fn network_charging_policy(
+        roaming_network_id: T::RoamingNetworkIndex,
+    ) -> Option<(T::RoamingChargingPolicyIndex, RoamingChargingPolicySetting<T::BlockNumber, T::BlockNumber>)> {
+        Self::roaming_network_charging_policies(roaming_network_id).unwrap_or_default().into_iter().find_map(
+            |roaming_charging_policy_id| {
+                Self::roaming_charging_policy_settings(roaming_charging_policy_id)
+                    .map(|policy_setting| (roaming_charging_policy_id, policy_setting))
+            },
+        )
+    }
+
+    /// Advance the next charging point of a roaming charging policy that has a config
+    pub fn advance_charging_policy(
+        roaming_charging_policy_id: T::RoamingChargingPolicyIndex,
+        next_charging_at_block: T::BlockNumber,
+    ) {
+        <RoamingChargingPolicySettings<T>>::mutate(roaming_charging_policy_id, |policy_setting| {
+            if let Some(_policy_setting) = policy_setting {
+                _policy_setting.policy_next_charging_at_block = next_charging_at_block;
+            }
+        });
+    }
+
     /// Only push the charging policy id onto the end of the vector if it does not already exist
     pub fn associate_charging_policy_with_network(
         roaming_charging_policy_id: T::RoamingChargingPolicyIndex,
@@ -438,36 +441,59 @@ impl<T: Config> Module<T> {
         }
     }
 
-    fn random_value(sender: &T::AccountId) -> [u8; 16] {
-        let payload = (
-            T::Randomness::random(&[0]),
-            sender,
-            <frame_system::Module<T>>::extrinsic_index(),
-            <frame_system::Module<T>>::block_number(),
-        );
-        payload.using_encoded(blake2_128)
+    /// Remove a roaming charging policy along with its links to its network and operator
+    pub fn remove_roaming_charging_policy(roaming_charging_policy_id: T::RoamingChargingPolicyIndex) -> DispatchResult {
+        Self::exists_roaming_charging_policy(roaming_charging_policy_id)?;
+        if let Some(roaming_network_id) = <RoamingChargingPolicyNetwork<T>>::take(roaming_charging_policy_id) {
+            <RoamingNetworkChargingPolicies<T>>::mutate_exists(roaming_network_id, |charging_policy_ids| {
+                *charging_policy_ids = without_roaming_entity(charging_policy_ids.take(), &roaming_charging_policy_id)
+            });
+        }
+        if let Some(roaming_operator_id) = <RoamingChargingPolicyOperator<T>>::take(roaming_charging_policy_id) {
+            <RoamingOperatorChargingPolicies<T>>::mutate_exists(roaming_operator_id, |charging_policy_ids| {
+                *charging_policy_ids = without_roaming_entity(charging_policy_ids.take(), &roaming_charging_policy_id)
+            });
+        }
+        T::RoamingChargingPolicyRegistry::deregister(roaming_charging_policy_id)?;
+        <RoamingChargingPolicySettings<T>>::remove(roaming_charging_policy_id);
+        Ok(())
     }
+}
 
-    fn next_roaming_charging_policy_id() -> Result<T::RoamingChargingPolicyIndex, DispatchError> {
-        let roaming_charging_policy_id = Self::roaming_charging_policies_count();
-        if roaming_charging_policy_id == <T::RoamingChargingPolicyIndex as Bounded>::max_value() {
-            return Err(DispatchError::Other("RoamingChargingPolicies count overflow"));
+/// The charging policies of a roaming network, other than those that have since been assigned to another network
+impl<T: Config> RoamingEntityChildren<RoamingNetworkEntity, T::RoamingNetworkIndex> for Module<T> {
+    fn has_children(roaming_network_id: T::RoamingNetworkIndex) -> bool {
+        Self::roaming_network_charging_policies(roaming_network_id).unwrap_or_default().into_iter().any(
+            |charging_policy_id| Self::roaming_charging_policy_network(charging_policy_id) == Some(roaming_network_id),
+        )
+    }
+
+    fn remove_children(roaming_network_id: T::RoamingNetworkIndex) -> DispatchResult {
+        for charging_policy_id in <RoamingNetworkChargingPolicies<T>>::take(roaming_network_id).unwrap_or_default() {
+            if Self::roaming_charging_policy_network(charging_policy_id) == Some(roaming_network_id) {
+                Self::remove_roaming_charging_policy(charging_policy_id)?;
+            }
         }
-        Ok(roaming_charging_policy_id)
+        Ok(())
     }
+}
 
-    fn insert_roaming_charging_policy(
-        owner: &T::AccountId,
-        roaming_charging_policy_id: T::RoamingChargingPolicyIndex,
-        roaming_charging_policy: RoamingChargingPolicy,
-    ) {
-        // Create and store roaming charging_policy
-        <RoamingChargingPolicies<T>>::insert(roaming_charging_policy_id, roaming_charging_policy);
-        <RoamingChargingPoliciesCount<T>>::put(roaming_charging_policy_id + One::one());
-        <RoamingChargingPolicyOwners<T>>::insert(roaming_charging_policy_id, owner.clone());
+/// The charging policies of a roaming operator, other than those that have since been assigned to another operator
+impl<T: Config> RoamingEntityChildren<RoamingOperatorEntity, T::RoamingOperatorIndex> for Module<T> {
+    fn has_children(roaming_operator_id: T::RoamingOperatorIndex) -> bool {
+        Self::roaming_operator_charging_policies(roaming_operator_id).unwrap_or_default().into_iter().any(
+            |charging_policy_id| {
+                Self::roaming_charging_policy_operator(charging_policy_id) == Some(roaming_operator_id)
+            },
+        )
     }
 
-    fn update_owner(to: &T::AccountId, roaming_charging_policy_id: T::RoamingChargingPolicyIndex) {
-        <RoamingChargingPolicyOwners<T>>::insert(roaming_charging_policy_id, to);
+    fn remove_children(roaming_operator_id: T::RoamingOperatorIndex) -> DispatchResult {
+        for charging_policy_id in <RoamingOperatorChargingPolicies<T>>::take(roaming_operator_id).unwrap_or_default() {
+            if Self::roaming_charging_policy_operator(charging_policy_id) == Some(roaming_operator_id) {
+                Self::remove_roaming_charging_policy(charging_policy_id)?;
+            }
+        }
+        Ok(())
     }
 }
@@ -87,22 +87,61 @@ impl pallet_transaction_payment::Config for Test {
     type TransactionByteFee = TransactionByteFee;
     type WeightToFee = IdentityFee<u64>;
 }
-impl roaming_operators::Config for Test {
+impl roaming_registry::Config<roaming_registry::Instance1> for Test {
     type Currency = Balances;
+    type EntityDeposit = ();
     type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
     type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+impl roaming_operators::Config for Test {
+    type Currency = Balances;
+    type Event = ();
+    type RemovalPolicy = ();
+    type RoamingOperatorChildren = ();
     type RoamingOperatorIndex = u64;
+    type RoamingOperatorRegistry = RoamingOperatorRegistry;
+}
+impl roaming_registry::Config<roaming_registry::Instance2> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
 }
 impl roaming_networks::Config for Test {
     type Event = ();
+    type RoamingNetworkChildren = ();
     type RoamingNetworkIndex = u64;
+    type RoamingNetworkRegistry = RoamingNetworkRegistry;
+}
+impl roaming_registry::Config<roaming_registry::Instance14> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
 }
 impl Config for Test {
     type Event = ();
     type RoamingChargingPolicyIndex = u64;
+    type RoamingChargingPolicyRegistry = RoamingChargingPolicyRegistry;
 }
 
 pub type RoamingChargingPolicyModule = Module<Test>;
+pub type RoamingNetworkModule = roaming_networks::Module<Test>;
+pub type RoamingOperatorRegistry = roaming_registry::Module<Test, roaming_registry::Instance1>;
+pub type RoamingNetworkRegistry = roaming_registry::Module<Test, roaming_registry::Instance2>;
+pub type RoamingChargingPolicyRegistry = roaming_registry::Module<Test, roaming_registry::Instance14>;
 
 // This function basically just builds a genesis storage key/value store according to
 // our desired mockup.
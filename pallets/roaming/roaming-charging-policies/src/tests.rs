@@ -11,71 +11,35 @@ use frame_support::{
 fn basic_setup_works() {
     new_test_ext().execute_with(|| {
         // Verify Initial Storage
-        assert_eq!(RoamingChargingPolicyModule::roaming_charging_policies_count(), 0);
-        assert!(RoamingChargingPolicyModule::roaming_charging_policy(0).is_none());
-        assert_eq!(RoamingChargingPolicyModule::roaming_charging_policy_owner(0), None);
+        assert_eq!(RoamingChargingPolicyRegistry::roaming_entities_count(), 0);
+        assert!(RoamingChargingPolicyRegistry::roaming_entity(0).is_none());
+        assert_eq!(RoamingChargingPolicyRegistry::roaming_entity_owner(0), None);
         assert_eq!(Balances::free_balance(1), 10);
         assert_eq!(Balances::free_balance(2), 20);
     });
 }
 
 #[test]
-fn create_works() {
-    new_test_ext().execute_with(|| {
-        // Call Functions
-        assert_ok!(RoamingChargingPolicyModule::create(Origin::signed(1)));
-        // Verify Storage
-        assert_eq!(RoamingChargingPolicyModule::roaming_charging_policies_count(), 1);
-        assert!(RoamingChargingPolicyModule::roaming_charging_policy(0).is_some());
-        assert_eq!(RoamingChargingPolicyModule::roaming_charging_policy_owner(0), Some(1));
-    });
-}
-
-#[test]
-fn create_handles_basic_errors() {
-    new_test_ext().execute_with(|| {
-        // Setup
-        <RoamingChargingPoliciesCount<Test>>::put(u64::max_value());
-        // Call Functions
-        assert_noop!(RoamingChargingPolicyModule::create(Origin::signed(1)), "RoamingChargingPolicies count overflow");
-        // Verify Storage
-        assert_eq!(RoamingChargingPolicyModule::roaming_charging_policies_count(), u64::max_value());
-        assert!(RoamingChargingPolicyModule::roaming_charging_policy(0).is_none());
-        assert_eq!(RoamingChargingPolicyModule::roaming_charging_policy_owner(0), None);
-    });
-}
-
-#[test]
-fn transfer_works() {
+fn advance_charging_policy_works() {
     new_test_ext().execute_with(|| {
         // Setup
-        assert_ok!(RoamingChargingPolicyModule::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingChargingPolicyRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingChargingPolicyRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingChargingPolicyModule::assign_charging_policy_to_network(Origin::signed(1), 0, 0));
+        assert_ok!(RoamingChargingPolicyModule::assign_charging_policy_to_network(Origin::signed(1), 1, 0));
+        assert_ok!(RoamingChargingPolicyModule::set_config(Origin::signed(1), 1, Some(10), Some(3)));
         // Call Functions
-        assert_ok!(RoamingChargingPolicyModule::transfer(Origin::signed(1), 2, 0));
+        assert_eq!(RoamingChargingPolicyModule::network_charging_policy(0).map(|(id, _)| id), Some(1));
+        RoamingChargingPolicyModule::advance_charging_policy(1, 13);
         // Verify Storage
-        assert_eq!(RoamingChargingPolicyModule::roaming_charging_policies_count(), 1);
-        assert!(RoamingChargingPolicyModule::roaming_charging_policy(0).is_some());
-        assert_eq!(RoamingChargingPolicyModule::roaming_charging_policy_owner(0), Some(2));
-    });
-}
-
-#[test]
-fn transfer_handles_basic_errors() {
-    new_test_ext().execute_with(|| {
-        // Setup
-        assert_ok!(RoamingChargingPolicyModule::create(Origin::signed(1)));
-        // Call Functions
-        assert_noop!(
-            RoamingChargingPolicyModule::transfer(Origin::signed(2), 2, 0),
-            "Only owner can transfer roaming charging_policy"
-        );
-        assert_noop!(
-            RoamingChargingPolicyModule::transfer(Origin::signed(1), 2, 1),
-            "Only owner can transfer roaming charging_policy"
+        assert_eq!(
+            RoamingChargingPolicyModule::roaming_charging_policy_settings(1),
+            Some(RoamingChargingPolicySetting {
+                policy_next_charging_at_block: 13,
+                policy_delay_after_billing_in_blocks: 3,
+            })
         );
-        // Verify Storage
-        assert_eq!(RoamingChargingPolicyModule::roaming_charging_policies_count(), 1);
-        assert!(RoamingChargingPolicyModule::roaming_charging_policy(0).is_some());
-        assert_eq!(RoamingChargingPolicyModule::roaming_charging_policy_owner(0), Some(1));
+        assert_eq!(RoamingChargingPolicyModule::network_charging_policy(1), None);
     });
 }
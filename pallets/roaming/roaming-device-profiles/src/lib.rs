@@ -6,26 +6,34 @@ use codec::{
 };
 use frame_support::{
     debug,
+    decl_error,
     decl_event,
     decl_module,
     decl_storage,
     ensure,
-    traits::{
-        Get,
-        Randomness,
-    },
+    traits::Get,
+    transactional,
     Parameter,
 };
 use frame_system::ensure_signed;
-use sp_io::hashing::blake2_128;
 use sp_runtime::{
     traits::{
         AtLeast32Bit,
         Bounded,
         Member,
-        One,
     },
     DispatchError,
+    DispatchResult,
+};
+use roaming_operators::{
+    without_roaming_entity,
+    RoamingAuthority,
+    RoamingDeviceEntity,
+    RoamingEntityChildren,
+};
+use roaming_registry::{
+    RoamingRegistry,
+    RoamingTradable,
 };
 use sp_std::prelude::*; // Imports Vec
 
@@ -39,15 +47,18 @@ mod tests;
 pub trait Config: frame_system::Config + roaming_operators::Config + roaming_devices::Config {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
     type RoamingDeviceProfileIndex: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
-    type RoamingDeviceProfileDevAddr: Parameter + Member + Default;
-    type RoamingDeviceProfileDevEUI: Parameter + Member + Default;
-    type RoamingDeviceProfileJoinEUI: Parameter + Member + Default;
+    /// The registry that holds the roaming device profiles and their owners
+    type RoamingDeviceProfileRegistry: RoamingRegistry<Self::AccountId, Self::RoamingDeviceProfileIndex>;
+    type RoamingDeviceProfileDevAddr: Parameter + Member + Default + AsRef<[u8]>;
+    type RoamingDeviceProfileDevEUI: Parameter + Member + Default + AsRef<[u8]>;
+    type RoamingDeviceProfileJoinEUI: Parameter + Member + Default + AsRef<[u8]>;
     type RoamingDeviceProfileVendorID: Parameter + Member + Default;
 }
 
-#[derive(Encode, Decode, Clone, PartialEq, Eq)]
-#[cfg_attr(feature = "std", derive(Debug))]
-pub struct RoamingDeviceProfile(pub [u8; 16]);
+/// The length in bytes of a DevEUI (EUI-64)
+pub const DEVEUI_LENGTH: usize = 8;
+/// The length in bytes of a JoinEUI (EUI-64)
+pub const JOINEUI_LENGTH: usize = 8;
 
 #[cfg_attr(feature = "std", derive(Debug))]
 #[derive(Encode, Decode, Default, Clone, PartialEq)]
@@ -69,29 +80,33 @@ decl_event!(
         <T as Config>::RoamingDeviceProfileVendorID,
         <T as roaming_devices::Config>::RoamingDeviceIndex,
     {
-        /// A roaming device_profile is created. (owner, roaming_device_profile_id)
-        Created(AccountId, RoamingDeviceProfileIndex),
-        /// A roaming device_profile is transferred. (from, to, roaming_device_profile_id)
-        Transferred(AccountId, AccountId, RoamingDeviceProfileIndex),
         /// A roaming device_profile configuration
         RoamingDeviceProfileSettingSet(AccountId, RoamingDeviceProfileIndex, RoamingDeviceProfileDevAddr, RoamingDeviceProfileDevEUI, RoamingDeviceProfileJoinEUI, RoamingDeviceProfileVendorID),
         /// A roaming device_profile is assigned to a device. (owner of device, roaming_device_profile_id, roaming_device_id)
         AssignedDeviceProfileToDevice(AccountId, RoamingDeviceProfileIndex, RoamingDeviceIndex),
+        /// A roaming device_profile is removed. (owner, roaming_device_profile_id)
+        Removed(AccountId, RoamingDeviceProfileIndex),
     }
 );
 
+decl_error! {
+    pub enum Error for Module<T: Config> {
+        /// The DevEUI must be 8 bytes and not all zeros
+        InvalidDevEUI,
+        /// The JoinEUI must be 8 bytes
+        InvalidJoinEUI,
+        /// The DevAddr must be 4 bytes starting with the AddrPrefix of a NetID type
+        InvalidDevAddr,
+        /// The DevEUI is already registered to another device
+        DevEUIAlreadyRegistered,
+        /// The DevAddr is already registered to another device
+        DevAddrAlreadyRegistered,
+    }
+}
+
 // This module's storage items.
 decl_storage! {
     trait Store for Module<T: Config> as RoamingDeviceProfiles {
-        /// Stores all the roaming device_profiles, key is the roaming device_profile id / index
-        pub RoamingDeviceProfiles get(fn roaming_device_profile): map hasher(opaque_blake2_256) T::RoamingDeviceProfileIndex => Option<RoamingDeviceProfile>;
-
-        /// Stores the total number of roaming device_profiles. i.e. the next roaming device_profile index
-        pub RoamingDeviceProfilesCount get(fn roaming_device_profiles_count): T::RoamingDeviceProfileIndex;
-
-        /// Get roaming device_profile owner
-        pub RoamingDeviceProfileOwners get(fn roaming_device_profile_owner): map hasher(opaque_blake2_256) T::RoamingDeviceProfileIndex => Option<T::AccountId>;
-
         /// Get roaming device_profile config
         pub RoamingDeviceProfileSettings get(fn roaming_device_profile_settings): map hasher(opaque_blake2_256) T::RoamingDeviceProfileIndex => Option<RoamingDeviceProfileSetting<T::RoamingDeviceProfileDevAddr, T::RoamingDeviceProfileDevEUI, T::RoamingDeviceProfileJoinEUI, T::RoamingDeviceProfileVendorID>>;
 
@@ -99,7 +114,13 @@ decl_storage! {
         pub RoamingDeviceProfileDevice get(fn roaming_device_profile_device): map hasher(opaque_blake2_256) T::RoamingDeviceProfileIndex => Option<T::RoamingDeviceIndex>;
 
         /// Get roaming device device_profiles
-        pub RoamingDeviceDeviceProfiles get(fn roaming_device_device_profiles): map hasher(opaque_blake2_256) T::RoamingDeviceIndex => Option<Vec<T::RoamingDeviceProfileIndex>>
+        pub RoamingDeviceDeviceProfiles get(fn roaming_device_device_profiles): map hasher(opaque_blake2_256) T::RoamingDeviceIndex => Option<Vec<T::RoamingDeviceProfileIndex>>;
+
+        /// Get the device that a DevEUI is registered to by its device profiles
+        pub RoamingDevEUIDevice get(fn roaming_deveui_device): map hasher(opaque_blake2_256) T::RoamingDeviceProfileDevEUI => Option<T::RoamingDeviceIndex>;
+
+        /// Get the device that a DevAddr is registered to by its device profiles
+        pub RoamingDevAddrDevice get(fn roaming_devaddr_device): map hasher(opaque_blake2_256) T::RoamingDeviceProfileDevAddr => Option<T::RoamingDeviceIndex>
     }
 }
 
@@ -109,36 +130,12 @@ decl_module! {
     pub struct Module<T: Config> for enum Call where origin: T::Origin {
         fn deposit_event() = default;
 
-        /// Create a new roaming device_profile
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn create(origin) {
-            let sender = ensure_signed(origin)?;
-            let roaming_device_profile_id = Self::next_roaming_device_profile_id()?;
-
-            // Generate a random 128bit value
-            let unique_id = Self::random_value(&sender);
-
-            // Create and store roaming device_profile
-            let roaming_device_profile = RoamingDeviceProfile(unique_id);
-            Self::insert_roaming_device_profile(&sender, roaming_device_profile_id, roaming_device_profile);
-
-            Self::deposit_event(RawEvent::Created(sender, roaming_device_profile_id));
-        }
+        type Error = Error<T>;
 
-        /// Transfer a roaming device_profile to new owner
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn transfer(origin, to: T::AccountId, roaming_device_profile_id: T::RoamingDeviceProfileIndex) {
-            let sender = ensure_signed(origin)?;
-
-            ensure!(Self::roaming_device_profile_owner(roaming_device_profile_id) == Some(sender.clone()), "Only owner can transfer roaming device_profile");
-
-            Self::update_owner(&to, roaming_device_profile_id);
-
-            Self::deposit_event(RawEvent::Transferred(sender, to, roaming_device_profile_id));
-        }
-
-        /// Set roaming device_profile config
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        /// Set roaming device_profile config. The DevEUI, JoinEUI and DevAddr are validated when provided, and
+        /// the DevEUI and DevAddr are registered to the device of the device profile so that they may not be
+        /// claimed by another device.
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(6, 5)]
         pub fn set_config(
             origin,
             roaming_device_profile_id: T::RoamingDeviceProfileIndex,
@@ -154,7 +151,7 @@ decl_module! {
             ensure!(is_roaming_device_profile, "RoamingDeviceProfile does not exist");
 
             // Ensure that the caller is owner of the device profile config they are trying to change
-            ensure!(Self::roaming_device_profile_owner(roaming_device_profile_id) == Some(sender.clone()), "Only owner can set config for roaming device_profile");
+            ensure!(T::RoamingDeviceProfileRegistry::owner_of(roaming_device_profile_id) == Some(sender.clone()), "Only owner can set config for roaming device_profile");
 
             let is_owned_by_parent_relationship = Self::is_owned_by_required_parent_relationship(roaming_device_profile_id, sender.clone()).is_ok();
             ensure!(is_owned_by_parent_relationship, "Ownership by parent does not exist");
@@ -176,6 +173,23 @@ decl_module! {
                 None => Default::default()
             };
 
+            Self::validate_identifiers(&device_profile_devaddr, &device_profile_deveui, &device_profile_joineui)?;
+
+            let roaming_device_id = Self::roaming_device_profile_device(roaming_device_profile_id)
+                .ok_or("RoamingDeviceProfileDevice does not exist")?;
+            Self::ensure_identifiers_available(roaming_device_id, &device_profile_devaddr, &device_profile_deveui)?;
+
+            // Release the identifiers of the previous config before registering those of the new config
+            if let Some(profile_setting) = Self::roaming_device_profile_settings(roaming_device_profile_id) {
+                Self::release_identifiers(
+                    roaming_device_id,
+                    roaming_device_profile_id,
+                    &profile_setting.device_profile_devaddr,
+                    &profile_setting.device_profile_deveui
+                );
+            }
+            Self::register_identifiers(roaming_device_id, &device_profile_devaddr, &device_profile_deveui);
+
             // Check if a roaming device profile config already exists with the given roaming device profile id
             // to determine whether to insert new or mutate existing.
             if Self::has_value_for_device_profile_setting_index(roaming_device_profile_id).is_ok() {
@@ -235,7 +249,8 @@ decl_module! {
             ));
         }
 
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(6, 5)]
+        #[transactional]
         pub fn assign_device_profile_to_device(
             origin,
             roaming_device_profile_id: T::RoamingDeviceProfileIndex,
@@ -248,39 +263,78 @@ decl_module! {
                 ::exists_roaming_device(roaming_device_id).is_ok();
             ensure!(is_roaming_device, "RoamingDevice does not exist");
 
-            // Ensure that caller of the function is the owner of the device id to assign the device_profile to
+            // Ensure that caller of the function is authorized for the device id to assign the device_profile to
             ensure!(
-                <roaming_devices::Module<T>>::is_roaming_device_owner(roaming_device_id, sender.clone()).is_ok(),
-                "Only the roaming device owner can assign itself a roaming device_profile"
+                <roaming_devices::Module<T>>::is_authorized(roaming_device_id, &sender),
+                "Only an account authorized for the roaming device can assign itself a roaming device_profile"
             );
 
-            Self::associate_device_profile_with_device(roaming_device_profile_id, roaming_device_id)
-                .expect("Unable to associate device_profile with device");
+            // Ensure that the identifiers of the device profile config may be registered to the given device
+            let profile_setting = Self::roaming_device_profile_settings(roaming_device_profile_id);
+            if let Some(ref profile_setting) = profile_setting {
+                Self::ensure_identifiers_available(
+                    roaming_device_id,
+                    &profile_setting.device_profile_devaddr,
+                    &profile_setting.device_profile_deveui
+                )?;
+            }
+
+            Self::associate_device_profile_with_device(roaming_device_profile_id, roaming_device_id)?;
 
             // Ensure that the given device_profile id already exists
-            let roaming_device_profile = Self::roaming_device_profile(roaming_device_profile_id);
-            ensure!(roaming_device_profile.is_some(), "Invalid roaming_device_profile_id");
+            ensure!(T::RoamingDeviceProfileRegistry::exists(roaming_device_profile_id), "Invalid roaming_device_profile_id");
 
             // Ensure that the device_profile is not already owned by a different device
             // Unassign the device_profile from any existing device since it may only be owned by one device
-            <RoamingDeviceProfileDevice<T>>::remove(roaming_device_profile_id);
+            let previous_roaming_device_id = <RoamingDeviceProfileDevice<T>>::take(roaming_device_profile_id);
 
             // Assign the device_profile owner to the given device (even if already belongs to them)
             <RoamingDeviceProfileDevice<T>>::insert(roaming_device_profile_id, roaming_device_id);
 
+            // Move the identifiers of the device profile config to the given device
+            if let Some(profile_setting) = profile_setting {
+                if let Some(previous_roaming_device_id) = previous_roaming_device_id {
+                    Self::release_identifiers(
+                        previous_roaming_device_id,
+                        roaming_device_profile_id,
+                        &profile_setting.device_profile_devaddr,
+                        &profile_setting.device_profile_deveui
+                    );
+                }
+                Self::register_identifiers(
+                    roaming_device_id,
+                    &profile_setting.device_profile_devaddr,
+                    &profile_setting.device_profile_deveui
+                );
+            }
+
             Self::deposit_event(RawEvent::AssignedDeviceProfileToDevice(sender, roaming_device_profile_id, roaming_device_id));
         }
+
+        /// Remove a roaming device profile
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(5, 7)]
+        #[transactional]
+        pub fn remove(origin, roaming_device_profile_id: T::RoamingDeviceProfileIndex) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(T::RoamingDeviceProfileRegistry::owner_of(roaming_device_profile_id) == Some(sender.clone()), "Only owner can remove roaming device_profile");
+
+            Self::remove_roaming_device_profile(roaming_device_profile_id)?;
+
+            Self::deposit_event(RawEvent::Removed(sender, roaming_device_profile_id));
+        }
     }
 }
 
 impl<T: Config> Module<T> {
     pub fn exists_roaming_device_profile(
         roaming_device_profile_id: T::RoamingDeviceProfileIndex,
-    ) -> Result<RoamingDeviceProfile, DispatchError> {
-        match Self::roaming_device_profile(roaming_device_profile_id) {
-            Some(roaming_device_profile) => Ok(roaming_device_profile),
-            None => Err(DispatchError::Other("RoamingDeviceProfile does not exist")),
-        }
+    ) -> Result<(), DispatchError> {
+        ensure!(
+            T::RoamingDeviceProfileRegistry::exists(roaming_device_profile_id),
+            "RoamingDeviceProfile does not exist"
+        );
+        Ok(())
     }
 
     pub fn is_owned_by_required_parent_relationship(
@@ -291,15 +345,11 @@ impl<T: Config> Module<T> {
         let device_profile_device_id = Self::roaming_device_profile_device(roaming_device_profile_id);
 
         if let Some(_device_profile_device_id) = device_profile_device_id {
-            // Ensure that the caller is owner of the device id associated with the device profile
+            // Ensure that the caller is authorized for the device id associated with the device profile
             ensure!(
-                (<roaming_devices::Module<T>>::is_roaming_device_owner(
-                    _device_profile_device_id.clone(),
-                    sender.clone()
-                ))
-                .is_ok(),
-                "Only owner of the device id associated with the given device profile can set an associated roaming \
-                 device profile config"
+                <roaming_devices::Module<T>>::is_authorized(_device_profile_device_id, &sender),
+                "Only an account authorized for the device id associated with the given device profile can set an \
+                 associated roaming device profile config"
             );
         } else {
             // There must be a device id associated with the device profile
@@ -330,6 +380,92 @@ impl<T: Config> Module<T> {
         Err(DispatchError::Other("No value for device profile config"))
     }
 
+    /// Validate the identifiers of a device profile config that were provided. Identifiers that were not provided
+    /// are empty.
+    pub fn validate_identifiers(
+        devaddr: &T::RoamingDeviceProfileDevAddr,
+        deveui: &T::RoamingDeviceProfileDevEUI,
+        joineui: &T::RoamingDeviceProfileJoinEUI,
+    ) -> DispatchResult {
+        let devaddr = devaddr.as_ref();
+        let deveui = deveui.as_ref();
+        let joineui = joineui.as_ref();
+        ensure!(
+            deveui.is_empty() || (deveui.len() == DEVEUI_LENGTH && deveui.iter().any(|byte| *byte != 0)),
+            Error::<T>::InvalidDevEUI
+        );
+        ensure!(joineui.is_empty() || joineui.len() == JOINEUI_LENGTH, Error::<T>::InvalidJoinEUI);
+        ensure!(
+            devaddr.is_empty() || <roaming_networks::Module<T>>::devaddr_nwkid(devaddr).is_some(),
+            Error::<T>::InvalidDevAddr
+        );
+        Ok(())
+    }
+
+    /// Ensure that a DevAddr and DevEUI are not registered to a device other than the given device
+    pub fn ensure_identifiers_available(
+        roaming_device_id: T::RoamingDeviceIndex,
+        devaddr: &T::RoamingDeviceProfileDevAddr,
+        deveui: &T::RoamingDeviceProfileDevEUI,
+    ) -> DispatchResult {
+        if !devaddr.as_ref().is_empty() {
+            ensure!(
+                Self::roaming_devaddr_device(devaddr).map_or(true, |device_id| device_id == roaming_device_id),
+                Error::<T>::DevAddrAlreadyRegistered
+            );
+        }
+        if !deveui.as_ref().is_empty() {
+            ensure!(
+                Self::roaming_deveui_device(deveui).map_or(true, |device_id| device_id == roaming_device_id),
+                Error::<T>::DevEUIAlreadyRegistered
+            );
+        }
+        Ok(())
+    }
+
+    fn register_identifiers(
+        roaming_device_id: T::RoamingDeviceIndex,
+        devaddr: &T::RoamingDeviceProfileDevAddr,
+        deveui: &T::RoamingDeviceProfileDevEUI,
+    ) {
+        if !devaddr.as_ref().is_empty() {
+            <RoamingDevAddrDevice<T>>::insert(devaddr, roaming_device_id);
+        }
+        if !deveui.as_ref().is_empty() {
+            <RoamingDevEUIDevice<T>>::insert(deveui, roaming_device_id);
+        }
+    }
+
+    /// Release the DevAddr and DevEUI of a device profile from a device unless another device profile of the
+    /// device has the same identifier
+    fn release_identifiers(
+        roaming_device_id: T::RoamingDeviceIndex,
+        roaming_device_profile_id: T::RoamingDeviceProfileIndex,
+        devaddr: &T::RoamingDeviceProfileDevAddr,
+        deveui: &T::RoamingDeviceProfileDevEUI,
+    ) {
+        let other_profile_settings: Vec<_> = Self::roaming_device_device_profiles(roaming_device_id)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|other_profile_id| {
+                *other_profile_id != roaming_device_profile_id &&
+                    Self::roaming_device_profile_device(other_profile_id) == Some(roaming_device_id)
+            })
+            .filter_map(|other_profile_id| Self::roaming_device_profile_settings(other_profile_id))
+            .collect();
+
+        if !devaddr.as_ref().is_empty() &&
+            !other_profile_settings.iter().any(|profile_setting| profile_setting.device_profile_devaddr == *devaddr)
+        {
+            <RoamingDevAddrDevice<T>>::remove(devaddr);
+        }
+        if !deveui.as_ref().is_empty() &&
+            !other_profile_settings.iter().any(|profile_setting| profile_setting.device_profile_deveui == *deveui)
+        {
+            <RoamingDevEUIDevice<T>>::remove(deveui);
+        }
+    }
+
     /// Only push the device_profile id onto the end of the vector if it does not already exist
     pub fn associate_device_profile_with_device(
         roaming_device_profile_id: T::RoamingDeviceProfileIndex,
@@ -365,36 +501,45 @@ impl<T: Config> Module<T> {
         }
     }
 
-    fn random_value(sender: &T::AccountId) -> [u8; 16] {
-        let payload = (
-            T::Randomness::random(&[0]),
-            sender,
-            <frame_system::Module<T>>::extrinsic_index(),
-            <frame_system::Module<T>>::block_number(),
-        );
-        payload.using_encoded(blake2_128)
-    }
-
-    fn next_roaming_device_profile_id() -> Result<T::RoamingDeviceProfileIndex, DispatchError> {
-        let roaming_device_profile_id = Self::roaming_device_profiles_count();
-        if roaming_device_profile_id == <T::RoamingDeviceProfileIndex as Bounded>::max_value() {
-            return Err(DispatchError::Other("RoamingDeviceProfiles count overflow"));
+    /// Remove a roaming device profile along with its link to its device
+    pub fn remove_roaming_device_profile(roaming_device_profile_id: T::RoamingDeviceProfileIndex) -> DispatchResult {
+        Self::exists_roaming_device_profile(roaming_device_profile_id)?;
+        if let Some(roaming_device_id) = Self::roaming_device_profile_device(roaming_device_profile_id) {
+            if let Some(profile_setting) = Self::roaming_device_profile_settings(roaming_device_profile_id) {
+                Self::release_identifiers(
+                    roaming_device_id,
+                    roaming_device_profile_id,
+                    &profile_setting.device_profile_devaddr,
+                    &profile_setting.device_profile_deveui,
+                );
+            }
+        }
+        if let Some(roaming_device_id) = <RoamingDeviceProfileDevice<T>>::take(roaming_device_profile_id) {
+            <RoamingDeviceDeviceProfiles<T>>::mutate_exists(roaming_device_id, |device_profile_ids| {
+                *device_profile_ids = without_roaming_entity(device_profile_ids.take(), &roaming_device_profile_id)
+            });
         }
-        Ok(roaming_device_profile_id)
+        T::RoamingDeviceProfileRegistry::deregister(roaming_device_profile_id)?;
+        <RoamingDeviceProfileSettings<T>>::remove(roaming_device_profile_id);
+        Ok(())
     }
+}
 
-    fn insert_roaming_device_profile(
-        owner: &T::AccountId,
-        roaming_device_profile_id: T::RoamingDeviceProfileIndex,
-        roaming_device_profile: RoamingDeviceProfile,
-    ) {
-        // Create and store roaming device_profile
-        <RoamingDeviceProfiles<T>>::insert(roaming_device_profile_id, roaming_device_profile);
-        <RoamingDeviceProfilesCount<T>>::put(roaming_device_profile_id + One::one());
-        <RoamingDeviceProfileOwners<T>>::insert(roaming_device_profile_id, owner.clone());
+/// The device profiles of a roaming device, other than those that have since been assigned to another device
+impl<T: Config> RoamingEntityChildren<RoamingDeviceEntity, T::RoamingDeviceIndex> for Module<T> {
+    fn has_children(roaming_device_id: T::RoamingDeviceIndex) -> bool {
+        Self::roaming_device_device_profiles(roaming_device_id)
+            .unwrap_or_default()
+            .into_iter()
+            .any(|device_profile_id| Self::roaming_device_profile_device(device_profile_id) == Some(roaming_device_id))
     }
 
-    fn update_owner(to: &T::AccountId, roaming_device_profile_id: T::RoamingDeviceProfileIndex) {
-        <RoamingDeviceProfileOwners<T>>::insert(roaming_device_profile_id, to);
+    fn remove_children(roaming_device_id: T::RoamingDeviceIndex) -> DispatchResult {
+        for device_profile_id in <RoamingDeviceDeviceProfiles<T>>::take(roaming_device_id).unwrap_or_default() {
+            if Self::roaming_device_profile_device(device_profile_id) == Some(roaming_device_id) {
+                Self::remove_roaming_device_profile(device_profile_id)?;
+            }
+        }
+        Ok(())
     }
 }
@@ -11,71 +11,186 @@ use frame_support::{
 fn basic_setup_works() {
     new_test_ext().execute_with(|| {
         // Verify Initial Storage
-        assert_eq!(RoamingDeviceProfileModule::roaming_device_profiles_count(), 0);
-        assert!(RoamingDeviceProfileModule::roaming_device_profile(0).is_none());
-        assert_eq!(RoamingDeviceProfileModule::roaming_device_profile_owner(0), None);
+        assert_eq!(RoamingDeviceProfileRegistry::roaming_entities_count(), 0);
+        assert!(RoamingDeviceProfileRegistry::roaming_entity(0).is_none());
+        assert_eq!(RoamingDeviceProfileRegistry::roaming_entity_owner(0), None);
         assert_eq!(Balances::free_balance(1), 10);
         assert_eq!(Balances::free_balance(2), 20);
     });
 }
 
+const DEVADDR: [u8; 4] = [0x26, 0x01, 0x1B, 0xDA];
+const DEVEUI: [u8; 8] = [0x70, 0xB3, 0xD5, 0x7E, 0xD0, 0x00, 0x00, 0x01];
+const JOINEUI: [u8; 8] = [0x70, 0xB3, 0xD5, 0x7E, 0xD0, 0x00, 0x00, 0x00];
+
+// Devices 0 and 1 of account 1 are each assigned a device profile with the same id
+fn setup_device_profiles() {
+    for roaming_device_id in 0..2 {
+        assert_ok!(RoamingDeviceRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingDeviceProfileRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingDeviceProfileModule::assign_device_profile_to_device(
+            Origin::signed(1),
+            roaming_device_id,
+            roaming_device_id
+        ));
+    }
+}
+
 #[test]
-fn create_works() {
+fn set_config_registers_identifiers() {
     new_test_ext().execute_with(|| {
+        // Setup
+        setup_device_profiles();
         // Call Functions
-        assert_ok!(RoamingDeviceProfileModule::create(Origin::signed(1)));
+        assert_ok!(RoamingDeviceProfileModule::set_config(
+            Origin::signed(1),
+            0,
+            Some(DEVADDR.to_vec()),
+            Some(DEVEUI.to_vec()),
+            Some(JOINEUI.to_vec()),
+            None
+        ));
         // Verify Storage
-        assert_eq!(RoamingDeviceProfileModule::roaming_device_profiles_count(), 1);
-        assert!(RoamingDeviceProfileModule::roaming_device_profile(0).is_some());
-        assert_eq!(RoamingDeviceProfileModule::roaming_device_profile_owner(0), Some(1));
+        assert_eq!(RoamingDeviceProfileModule::roaming_devaddr_device(DEVADDR.to_vec()), Some(0));
+        assert_eq!(RoamingDeviceProfileModule::roaming_deveui_device(DEVEUI.to_vec()), Some(0));
+
+        // Changing the identifiers releases the previous ones
+        let mut other_deveui = DEVEUI;
+        other_deveui[7] = 0x02;
+        assert_ok!(RoamingDeviceProfileModule::set_config(
+            Origin::signed(1),
+            0,
+            None,
+            Some(other_deveui.to_vec()),
+            Some(JOINEUI.to_vec()),
+            None
+        ));
+        assert_eq!(RoamingDeviceProfileModule::roaming_devaddr_device(DEVADDR.to_vec()), None);
+        assert_eq!(RoamingDeviceProfileModule::roaming_deveui_device(DEVEUI.to_vec()), None);
+        assert_eq!(RoamingDeviceProfileModule::roaming_deveui_device(other_deveui.to_vec()), Some(0));
     });
 }
 
 #[test]
-fn create_handles_basic_errors() {
+fn set_config_handles_invalid_identifiers() {
     new_test_ext().execute_with(|| {
         // Setup
-        <RoamingDeviceProfilesCount<Test>>::put(u64::max_value());
+        setup_device_profiles();
         // Call Functions
-        assert_noop!(RoamingDeviceProfileModule::create(Origin::signed(1)), "RoamingDeviceProfiles count overflow");
+        assert_noop!(
+            RoamingDeviceProfileModule::set_config(Origin::signed(1), 0, None, Some(DEVEUI[..7].to_vec()), None, None),
+            Error::<Test>::InvalidDevEUI
+        );
+        assert_noop!(
+            RoamingDeviceProfileModule::set_config(Origin::signed(1), 0, None, Some(vec![0; 8]), None, None),
+            Error::<Test>::InvalidDevEUI
+        );
+        assert_noop!(
+            RoamingDeviceProfileModule::set_config(Origin::signed(1), 0, None, None, Some(vec![1; 9]), None),
+            Error::<Test>::InvalidJoinEUI
+        );
+        assert_noop!(
+            RoamingDeviceProfileModule::set_config(Origin::signed(1), 0, Some(DEVADDR[..3].to_vec()), None, None, None),
+            Error::<Test>::InvalidDevAddr
+        );
+        assert_noop!(
+            RoamingDeviceProfileModule::set_config(Origin::signed(1), 0, Some(vec![0xFF; 4]), None, None, None),
+            Error::<Test>::InvalidDevAddr
+        );
         // Verify Storage
-        assert_eq!(RoamingDeviceProfileModule::roaming_device_profiles_count(), u64::max_value());
-        assert!(RoamingDeviceProfileModule::roaming_device_profile(0).is_none());
-        assert_eq!(RoamingDeviceProfileModule::roaming_device_profile_owner(0), None);
+        assert!(RoamingDeviceProfileModule::roaming_device_profile_settings(0).is_none());
     });
 }
 
 #[test]
-fn transfer_works() {
+fn set_config_handles_registered_identifiers() {
     new_test_ext().execute_with(|| {
         // Setup
-        assert_ok!(RoamingDeviceProfileModule::create(Origin::signed(1)));
+        setup_device_profiles();
+        assert_ok!(RoamingDeviceProfileModule::set_config(
+            Origin::signed(1),
+            0,
+            Some(DEVADDR.to_vec()),
+            Some(DEVEUI.to_vec()),
+            None,
+            None
+        ));
         // Call Functions
-        assert_ok!(RoamingDeviceProfileModule::transfer(Origin::signed(1), 2, 0));
+        assert_noop!(
+            RoamingDeviceProfileModule::set_config(Origin::signed(1), 1, None, Some(DEVEUI.to_vec()), None, None),
+            Error::<Test>::DevEUIAlreadyRegistered
+        );
+        assert_noop!(
+            RoamingDeviceProfileModule::set_config(Origin::signed(1), 1, Some(DEVADDR.to_vec()), None, None, None),
+            Error::<Test>::DevAddrAlreadyRegistered
+        );
         // Verify Storage
-        assert_eq!(RoamingDeviceProfileModule::roaming_device_profiles_count(), 1);
-        assert!(RoamingDeviceProfileModule::roaming_device_profile(0).is_some());
-        assert_eq!(RoamingDeviceProfileModule::roaming_device_profile_owner(0), Some(2));
+        assert!(RoamingDeviceProfileModule::roaming_device_profile_settings(1).is_none());
+        assert_eq!(RoamingDeviceProfileModule::roaming_deveui_device(DEVEUI.to_vec()), Some(0));
     });
 }
 
 #[test]
-fn transfer_handles_basic_errors() {
+fn assign_device_profile_to_device_moves_identifiers() {
     new_test_ext().execute_with(|| {
         // Setup
-        assert_ok!(RoamingDeviceProfileModule::create(Origin::signed(1)));
+        setup_device_profiles();
+        assert_ok!(RoamingDeviceProfileModule::set_config(
+            Origin::signed(1),
+            0,
+            Some(DEVADDR.to_vec()),
+            Some(DEVEUI.to_vec()),
+            None,
+            None
+        ));
         // Call Functions
+        assert_ok!(RoamingDeviceProfileModule::assign_device_profile_to_device(Origin::signed(1), 0, 1));
+        // Verify Storage
+        assert_eq!(RoamingDeviceProfileModule::roaming_devaddr_device(DEVADDR.to_vec()), Some(1));
+        assert_eq!(RoamingDeviceProfileModule::roaming_deveui_device(DEVEUI.to_vec()), Some(1));
+    });
+}
+
+#[test]
+fn authority_is_delegated_down_the_roaming_entity_graph() {
+    new_test_ext().execute_with(|| {
+        // Setup operator 0 and network 0 of account 1, network server 0 of account 2 and device 0 of account 3
+        assert_ok!(RoamingOperatorRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkModule::assign_network_to_operator(Origin::signed(1), 0, 0));
+        assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(2)));
+        // The owners of network server 0 and device 0 let account 1 assign them
+        assert_ok!(RoamingNetworkServerRegistry::grant_admin(Origin::signed(2), 0, 1));
+        assert_ok!(RoamingNetworkServerModule::assign_network_server_to_network(Origin::signed(1), 0, 0));
+        assert_ok!(RoamingNetworkServerRegistry::revoke_admin(Origin::signed(2), 0, 1));
+        assert_ok!(RoamingDeviceRegistry::create(Origin::signed(3)));
+        assert_ok!(RoamingDeviceRegistry::grant_admin(Origin::signed(3), 0, 1));
+        assert_ok!(RoamingDeviceModule::assign_device_to_network_server(Origin::signed(1), 0, 0));
+        assert_ok!(RoamingDeviceRegistry::revoke_admin(Origin::signed(3), 0, 1));
+        assert_ok!(RoamingDeviceProfileRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingDeviceProfileRegistry::create(Origin::signed(4)));
+        // Call Functions
+        assert_ok!(RoamingDeviceProfileModule::assign_device_profile_to_device(Origin::signed(1), 0, 0));
+        assert_ok!(RoamingDeviceProfileModule::set_config(
+            Origin::signed(1),
+            0,
+            Some(DEVADDR.to_vec()),
+            None,
+            None,
+            None
+        ));
         assert_noop!(
-            RoamingDeviceProfileModule::transfer(Origin::signed(2), 2, 0),
-            "Only owner can transfer roaming device_profile"
-        );
-        assert_noop!(
-            RoamingDeviceProfileModule::transfer(Origin::signed(1), 2, 1),
-            "Only owner can transfer roaming device_profile"
+            RoamingDeviceProfileModule::assign_device_profile_to_device(Origin::signed(4), 1, 0),
+            "Only an account authorized for the roaming device can assign itself a roaming device_profile"
         );
+        assert_ok!(RoamingOperatorRegistry::grant_admin(Origin::signed(1), 0, 4));
+        assert_ok!(RoamingDeviceProfileModule::assign_device_profile_to_device(Origin::signed(4), 1, 0));
+        assert_ok!(RoamingOperatorRegistry::revoke_admin(Origin::signed(1), 0, 4));
         // Verify Storage
-        assert_eq!(RoamingDeviceProfileModule::roaming_device_profiles_count(), 1);
-        assert!(RoamingDeviceProfileModule::roaming_device_profile(0).is_some());
-        assert_eq!(RoamingDeviceProfileModule::roaming_device_profile_owner(0), Some(1));
+        assert_eq!(RoamingDeviceProfileModule::roaming_device_profile_device(1), Some(0));
+        assert_eq!(RoamingDeviceProfileModule::roaming_devaddr_device(DEVADDR.to_vec()), Some(0));
+        assert!(RoamingDeviceModule::is_authorized(0, &2));
+        assert!(!RoamingDeviceModule::is_authorized(0, &4));
+        assert!(RoamingDeviceModule::is_authorized(0, &1));
     });
 }
@@ -6,28 +6,45 @@ use codec::{
 };
 use frame_support::{
     debug,
+    decl_error,
     decl_event,
     decl_module,
     decl_storage,
     ensure,
-    traits::{
-        Currency,
-        ExistenceRequirement,
-        Get,
-        Randomness,
+    storage::migration::{
+        get_storage_value,
+        take_storage_value,
     },
+    traits::Get,
+    transactional,
+    weights::Weight,
     Parameter,
 };
 use frame_system::ensure_signed;
-use sp_io::hashing::blake2_128;
+use sp_io::hashing::blake2_256;
 use sp_runtime::{
     traits::{
         AtLeast32Bit,
         Bounded,
         Member,
         One,
+        Zero,
     },
     DispatchError,
+    DispatchResult,
+};
+use roaming_operators::{
+    iter_double_map_keys_from,
+    without_roaming_entity,
+    RoamingAuthority,
+    RoamingDeviceEntity,
+    RoamingEntityChildren,
+    RoamingNetworkServerEntity,
+    RoamingOrganizationEntity,
+};
+use roaming_registry::{
+    RoamingRegistry,
+    RoamingTradable,
 };
 use sp_std::prelude::*; // Imports Vec
 
@@ -43,14 +60,41 @@ pub trait Config:
 {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
     type RoamingDeviceIndex: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
+    /// The registry that holds the roaming devices and their owners
+    type RoamingDeviceRegistry: RoamingRegistry<Self::AccountId, Self::RoamingDeviceIndex>;
+    /// The pallets that hold the children of roaming devices
+    type RoamingDeviceChildren: RoamingEntityChildren<RoamingDeviceEntity, Self::RoamingDeviceIndex>;
+    /// The largest number of networks that the roaming policy of a roaming device may allow
+    type MaxRoamingPolicyNetworks: Get<u32>;
 }
 
-type BalanceOf<T> =
-    <<T as roaming_operators::Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
-
+/// Roaming policy of a roaming device, set by its owner. A device without a roaming policy may roam to any network.
 #[derive(Encode, Decode, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "std", derive(Debug))]
-pub struct RoamingDevice(pub [u8; 16]);
+pub enum RoamingDevicePolicy<U> {
+    /// The device has opted out of roaming, so it may not join any roaming session
+    Disabled,
+    /// The device may only join roaming sessions with network servers of its home network
+    HomeOnly,
+    /// The device may join roaming sessions with network servers of its home network or of the given networks
+    AllowedNetworks(Vec<U>),
+}
+
+/// Storage version of the roaming devices pallet
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum Releases {
+    /// The devices of each roaming network server are stored as a vector
+    V1_0_0,
+    /// The devices of each roaming network server are keys of a double map
+    V2_0_0,
+}
+
+impl Default for Releases {
+    fn default() -> Self {
+        Releases::V1_0_0
+    }
+}
 
 decl_event!(
     pub enum Event<T> where
@@ -58,49 +102,57 @@ decl_event!(
         <T as Config>::RoamingDeviceIndex,
         <T as roaming_network_servers::Config>::RoamingNetworkServerIndex,
         <T as roaming_organizations::Config>::RoamingOrganizationIndex,
-        Balance = BalanceOf<T>,
     {
-        /// A roaming device is created. (owner, roaming_device_id)
-        Created(AccountId, RoamingDeviceIndex),
-        /// A roaming device is transferred. (from, to, roaming_device_id)
-        Transferred(AccountId, AccountId, RoamingDeviceIndex),
-        /// A roaming device is available for sale. (owner, roaming_device_id, price)
-        PriceSet(AccountId, RoamingDeviceIndex, Option<Balance>),
-        /// A roaming device is sold. (from, to, roaming_device_id, price)
-        Sold(AccountId, AccountId, RoamingDeviceIndex, Balance),
         /// A roaming device is assigned to a network_server. (owner of network_server, roaming_device_id, roaming_network_server_id)
         AssignedDeviceToNetworkServer(AccountId, RoamingDeviceIndex, RoamingNetworkServerIndex),
         /// A roaming device is assigned to an organization. (owner of organization, roaming_device_id, roaming_organization_id)
         AssignedDeviceToOrganization(AccountId, RoamingDeviceIndex, RoamingOrganizationIndex),
+        /// A roaming device is removed. (owner, roaming_device_id)
+        Removed(AccountId, RoamingDeviceIndex),
+        /// The roaming policy of a roaming device is set. (owner, roaming_device_id)
+        RoamingPolicySet(AccountId, RoamingDeviceIndex),
+        /// The roaming policy of a roaming device is cleared, so it may roam to any network. (owner, roaming_device_id)
+        RoamingPolicyCleared(AccountId, RoamingDeviceIndex),
     }
 );
 
+decl_error! {
+    pub enum Error for Module<T: Config> {
+        /// The roaming policy allows more than the maximum number of networks
+        TooManyAllowedNetworks,
+        /// A network allowed by the roaming policy does not exist
+        AllowedNetworkDoesNotExist,
+        /// The roaming device has opted out of roaming
+        RoamingDisabled,
+        /// The roaming policy of the device only allows network servers of its home network
+        RoamingRestrictedToHomeNetwork,
+        /// The roaming policy of the device does not allow the network of the network server
+        NetworkNotAllowedForRoaming,
+    }
+}
+
 // This module's storage items.
 decl_storage! {
     trait Store for Module<T: Config> as RoamingDevices {
-        /// Stores all the roaming devices, key is the roaming device id / index
-        pub RoamingDevices get(fn roaming_device): map hasher(opaque_blake2_256) T::RoamingDeviceIndex => Option<RoamingDevice>;
-
-        /// Stores the total number of roaming devices. i.e. the next roaming device index
-        pub RoamingDevicesCount get(fn roaming_devices_count): T::RoamingDeviceIndex;
-
-        /// Get roaming device owner
-        pub RoamingDeviceOwners get(fn roaming_device_owner): map hasher(opaque_blake2_256) T::RoamingDeviceIndex => Option<T::AccountId>;
-
-        /// Get roaming device price. None means not for sale.
-        pub RoamingDevicePrices get(fn roaming_device_price): map hasher(opaque_blake2_256) T::RoamingDeviceIndex => Option<BalanceOf<T>>;
-
         /// Get roaming device network_server
         pub RoamingDeviceNetworkServers get(fn roaming_device_network_server): map hasher(opaque_blake2_256) T::RoamingDeviceIndex => Option<T::RoamingNetworkServerIndex>;
 
         /// Get roaming device organization
         pub RoamingDeviceOrganization get(fn roaming_device_organization): map hasher(opaque_blake2_256) T::RoamingDeviceIndex => Option<T::RoamingOrganizationIndex>;
 
-        /// Get roaming network server's devices
-        pub RoamingNetworkServerDevices get(fn roaming_network_server_devices): map hasher(opaque_blake2_256) T::RoamingNetworkServerIndex => Option<Vec<T::RoamingDeviceIndex>>;
+        /// The roaming devices of each roaming network server, stored as keys so that a device is added or removed
+        /// without reading the other devices of the network server
+        pub RoamingNetworkServerDevices: double_map hasher(blake2_128_concat) T::RoamingNetworkServerIndex, hasher(blake2_128_concat) T::RoamingDeviceIndex => ();
 
         /// Get roaming organization's devices
-        pub RoamingOrganizationDevices get(fn roaming_organization_devices): map hasher(opaque_blake2_256) T::RoamingOrganizationIndex => Option<Vec<T::RoamingDeviceIndex>>
+        pub RoamingOrganizationDevices get(fn roaming_organization_devices): map hasher(opaque_blake2_256) T::RoamingOrganizationIndex => Option<Vec<T::RoamingDeviceIndex>>;
+
+        /// Get the roaming policy of a roaming device. None means the device may roam to any network.
+        pub RoamingDevicePolicies get(fn roaming_device_policy): map hasher(opaque_blake2_256) T::RoamingDeviceIndex => Option<RoamingDevicePolicy<T::RoamingNetworkIndex>>;
+
+        /// Storage version of the pallet, used to determine which migrations to run on runtime upgrade. A new chain
+        /// starts at the latest version since it has no network server devices to migrate.
+        pub StorageVersion get(fn storage_version) build(|_| Releases::V2_0_0): Releases;
     }
 }
 
@@ -110,76 +162,21 @@ decl_module! {
     pub struct Module<T: Config> for enum Call where origin: T::Origin {
         fn deposit_event() = default;
 
-        /// Create a new roaming device
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn create(origin) {
-            let sender = ensure_signed(origin)?;
-            let roaming_device_id = Self::next_roaming_device_id()?;
-
-            // Generate a random 128bit value
-            let unique_id = Self::random_value(&sender);
+        type Error = Error<T>;
 
-            // Create and store roaming device
-            let roaming_device = RoamingDevice(unique_id);
-            Self::insert_roaming_device(&sender, roaming_device_id, roaming_device);
-
-            Self::deposit_event(RawEvent::Created(sender, roaming_device_id));
-        }
-
-        /// Transfer a roaming device to new owner
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn transfer(origin, to: T::AccountId, roaming_device_id: T::RoamingDeviceIndex) {
-            let sender = ensure_signed(origin)?;
-
-            ensure!(Self::roaming_device_owner(roaming_device_id) == Some(sender.clone()), "Only owner can transfer roaming device");
-
-            Self::update_owner(&to, roaming_device_id);
-
-            Self::deposit_event(RawEvent::Transferred(sender, to, roaming_device_id));
-        }
-
-        /// Set a price for a roaming device for sale
-        /// None to delist the roaming device
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn set_price(origin, roaming_device_id: T::RoamingDeviceIndex, price: Option<BalanceOf<T>>) {
-            let sender = ensure_signed(origin)?;
+        /// The largest number of networks that the roaming policy of a roaming device may allow
+        const MaxRoamingPolicyNetworks: u32 = T::MaxRoamingPolicyNetworks::get();
 
-            ensure!(Self::roaming_device_owner(roaming_device_id) == Some(sender.clone()), "Only owner can set price for roaming device");
-
-            if let Some(ref price) = price {
-                <RoamingDevicePrices<T>>::insert(roaming_device_id, price);
-            } else {
-                <RoamingDevicePrices<T>>::remove(roaming_device_id);
+        fn on_runtime_upgrade() -> Weight {
+            let mut weight = T::DbWeight::get().reads(1);
+            if Self::storage_version() == Releases::V1_0_0 {
+                weight = weight.saturating_add(Self::migrate_to_network_server_device_keys());
             }
-
-            Self::deposit_event(RawEvent::PriceSet(sender, roaming_device_id, price));
-        }
-
-        /// Buy a roaming device with max price willing to pay
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn buy(origin, roaming_device_id: T::RoamingDeviceIndex, price: BalanceOf<T>) {
-            let sender = ensure_signed(origin)?;
-
-            let owner = Self::roaming_device_owner(roaming_device_id);
-            ensure!(owner.is_some(), "RoamingDevice owner does not exist");
-            let owner = owner.unwrap();
-
-            let roaming_device_price = Self::roaming_device_price(roaming_device_id);
-            ensure!(roaming_device_price.is_some(), "RoamingDevice not for sale");
-
-            let roaming_device_price = roaming_device_price.unwrap();
-            ensure!(price >= roaming_device_price, "Price is too low");
-
-            T::Currency::transfer(&sender, &owner, roaming_device_price, ExistenceRequirement::AllowDeath)?;
-
-            <RoamingDevicePrices<T>>::remove(roaming_device_id);
-
-            Self::update_owner(&sender, roaming_device_id);
-
-            Self::deposit_event(RawEvent::Sold(owner, sender, roaming_device_id, roaming_device_price));
+            weight
         }
 
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        #[transactional]
         pub fn assign_device_to_network_server(
             origin,
             roaming_device_id: T::RoamingDeviceIndex,
@@ -192,22 +189,30 @@ decl_module! {
                 ::exists_roaming_network_server(roaming_network_server_id).is_ok();
             ensure!(is_roaming_network_server, "RoamingNetworkServer does not exist");
 
-            // Ensure that caller of the function is the owner of the network server id to assign the device to
+            // Ensure that caller of the function is authorized for the network server id to assign the device to
             ensure!(
-                <roaming_network_servers::Module<T>>::is_roaming_network_server_owner(roaming_network_server_id, sender.clone()).is_ok(),
-                "Only the roaming network_server owner can assign itself a roaming device"
+                <roaming_network_servers::Module<T>>::is_authorized(roaming_network_server_id, &sender),
+                "Only an account authorized for the roaming network_server can assign itself a roaming device"
             );
 
-            Self::associate_device_with_network_server(roaming_device_id, roaming_network_server_id)
-                .expect("Unable to associate device with network server");
-
             // Ensure that the given device id already exists
-            let roaming_device = Self::roaming_device(roaming_device_id);
-            ensure!(roaming_device.is_some(), "Invalid roaming_device_id");
+            ensure!(T::RoamingDeviceRegistry::exists(roaming_device_id), "Invalid roaming_device_id");
+
+            // Ensure that the caller is also authorized for the device, so that a device may not be taken over
+            ensure!(
+                Self::is_authorized(roaming_device_id, &sender),
+                "Only an account authorized for the roaming device can assign it to a roaming network_server"
+            );
+
+            Self::associate_device_with_network_server(roaming_device_id, roaming_network_server_id)?;
 
             // Ensure that the device is not already owned by a different network_server
             // Unassign the device from any existing network_server since it may only be owned by one network_server
-            <RoamingDeviceNetworkServers<T>>::remove(roaming_device_id);
+            if let Some(previous_network_server_id) = <RoamingDeviceNetworkServers<T>>::take(roaming_device_id) {
+                if previous_network_server_id != roaming_network_server_id {
+                    <RoamingNetworkServerDevices<T>>::remove(previous_network_server_id, roaming_device_id);
+                }
+            }
 
             // Assign the device owner to the given network_server (even if already belongs to them)
             <RoamingDeviceNetworkServers<T>>::insert(roaming_device_id, roaming_network_server_id);
@@ -216,6 +221,7 @@ decl_module! {
         }
 
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        #[transactional]
         pub fn assign_device_to_organization(
             origin,
             roaming_device_id: T::RoamingDeviceIndex,
@@ -228,18 +234,22 @@ decl_module! {
                 ::exists_roaming_organization(roaming_organization_id).is_ok();
             ensure!(is_roaming_organization, "RoamingOrganization does not exist");
 
-            // Ensure that caller of the function is the owner of the organization id to assign the device to
+            // Ensure that caller of the function is authorized for the organization id to assign the device to
             ensure!(
-                <roaming_organizations::Module<T>>::is_roaming_organization_owner(roaming_organization_id, sender.clone()).is_ok(),
-                "Only the roaming organization owner can assign itself a roaming device"
+                <roaming_organizations::Module<T>>::is_authorized(roaming_organization_id, &sender),
+                "Only an account authorized for the roaming organization can assign itself a roaming device"
             );
 
-            Self::associate_device_with_organization(roaming_device_id, roaming_organization_id)
-                .expect("Unable to associate device with organization");
-
             // Ensure that the given device id already exists
-            let roaming_device = Self::roaming_device(roaming_device_id);
-            ensure!(roaming_device.is_some(), "Invalid roaming_device_id");
+            ensure!(T::RoamingDeviceRegistry::exists(roaming_device_id), "Invalid roaming_device_id");
+
+            // Ensure that the caller is also authorized for the device, so that a device may not be taken over
+            ensure!(
+                Self::is_authorized(roaming_device_id, &sender),
+                "Only an account authorized for the roaming device can assign it to a roaming organization"
+            );
+
+            Self::associate_device_with_organization(roaming_device_id, roaming_organization_id)?;
 
             // Ensure that the device is not already owned by a different organization
             // Unassign the device from any existing organization since it may only be owned by one organization
@@ -250,6 +260,55 @@ decl_module! {
 
             Self::deposit_event(RawEvent::AssignedDeviceToOrganization(sender, roaming_device_id, roaming_organization_id));
         }
+
+        /// Set the roaming policy of a roaming device, or clear it so that the device may roam to any network.
+        /// Only the owner of the device may set its roaming policy.
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(2 + T::MaxRoamingPolicyNetworks::get() as u64, 1)]
+        pub fn set_roaming_policy(
+            origin,
+            roaming_device_id: T::RoamingDeviceIndex,
+            roaming_policy: Option<RoamingDevicePolicy<T::RoamingNetworkIndex>>
+        ) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(T::RoamingDeviceRegistry::owner_of(roaming_device_id) == Some(sender.clone()), "Only owner can set roaming policy of roaming device");
+
+            match roaming_policy {
+                Some(mut roaming_policy) => {
+                    if let RoamingDevicePolicy::AllowedNetworks(network_ids) = &mut roaming_policy {
+                        ensure!(network_ids.len() <= T::MaxRoamingPolicyNetworks::get() as usize, Error::<T>::TooManyAllowedNetworks);
+                        for network_id in network_ids.iter() {
+                            ensure!(
+                                <roaming_networks::Module<T>>::exists_roaming_network(*network_id).is_ok(),
+                                Error::<T>::AllowedNetworkDoesNotExist
+                            );
+                        }
+                        network_ids.sort();
+                        network_ids.dedup();
+                    }
+                    <RoamingDevicePolicies<T>>::insert(roaming_device_id, roaming_policy);
+                    Self::deposit_event(RawEvent::RoamingPolicySet(sender, roaming_device_id));
+                }
+                None => {
+                    <RoamingDevicePolicies<T>>::remove(roaming_device_id);
+                    Self::deposit_event(RawEvent::RoamingPolicyCleared(sender, roaming_device_id));
+                }
+            }
+        }
+
+        /// Remove a roaming device. Its children are either removed with it or prevent its removal, depending on the
+        /// removal policy.
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(5, 8)]
+        #[transactional]
+        pub fn remove(origin, roaming_device_id: T::RoamingDeviceIndex) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(T::RoamingDeviceRegistry::owner_of(roaming_device_id) == Some(sender.clone()), "Only owner can remove roaming device");
+
+            Self::remove_roaming_device(roaming_device_id)?;
+
+            Self::deposit_event(RawEvent::Removed(sender, roaming_device_id));
+        }
     }
 }
 
@@ -259,56 +318,29 @@ impl<T: Config> Module<T> {
         sender: T::AccountId,
     ) -> Result<(), DispatchError> {
         ensure!(
-            Self::roaming_device_owner(&roaming_device_id).map(|owner| owner == sender).unwrap_or(false),
+            T::RoamingDeviceRegistry::owner_of(roaming_device_id).map(|owner| owner == sender).unwrap_or(false),
             "Sender is not owner of RoamingDevice"
         );
         Ok(())
     }
 
-    pub fn exists_roaming_device(roaming_device_id: T::RoamingDeviceIndex) -> Result<RoamingDevice, DispatchError> {
-        match Self::roaming_device(roaming_device_id) {
-            Some(roaming_device) => Ok(roaming_device),
-            None => Err(DispatchError::Other("RoamingDevice does not exist")),
-        }
+    pub fn exists_roaming_device(roaming_device_id: T::RoamingDeviceIndex) -> Result<(), DispatchError> {
+        ensure!(T::RoamingDeviceRegistry::exists(roaming_device_id), "RoamingDevice does not exist");
+        Ok(())
     }
 
-    /// Only push the device id onto the end of the vector if it does not already exist
+    /// Add the device id to the devices of the network server if it is not already one of them
     pub fn associate_device_with_network_server(
         roaming_device_id: T::RoamingDeviceIndex,
         roaming_network_server_id: T::RoamingNetworkServerIndex,
     ) -> Result<(), DispatchError> {
-        // Early exit with error since do not want to append if the given network server id already exists as a key,
-        // and where its corresponding value is a vector that already contains the given device id
-        if let Some(network_server_devices) = Self::roaming_network_server_devices(roaming_network_server_id) {
-            debug::info!(
-                "Network Server id key {:?} exists with value {:?}",
-                roaming_network_server_id,
-                network_server_devices
-            );
-            let not_network_server_contains_device = !network_server_devices.contains(&roaming_device_id);
-            ensure!(not_network_server_contains_device, "Network Server already contains the given device id");
-            debug::info!("Network Server id key exists but its vector value does not contain the given device id");
-            <RoamingNetworkServerDevices<T>>::mutate(roaming_network_server_id, |v| {
-                if let Some(value) = v {
-                    value.push(roaming_device_id);
-                }
-            });
-            debug::info!(
-                "Associated device {:?} with network server {:?}",
-                roaming_device_id,
-                roaming_network_server_id
-            );
-            Ok(())
-        } else {
-            debug::info!(
-                "Network Server id key does not yet exist. Creating the network server key {:?} and appending the \
-                 device id {:?} to its vector value",
-                roaming_network_server_id,
-                roaming_device_id
-            );
-            <RoamingNetworkServerDevices<T>>::insert(roaming_network_server_id, &vec![roaming_device_id]);
-            Ok(())
-        }
+        ensure!(
+            !<RoamingNetworkServerDevices<T>>::contains_key(roaming_network_server_id, roaming_device_id),
+            "Network Server already contains the given device id"
+        );
+        <RoamingNetworkServerDevices<T>>::insert(roaming_network_server_id, roaming_device_id, ());
+        debug::info!("Associated device {:?} with network server {:?}", roaming_device_id, roaming_network_server_id);
+        Ok(())
     }
 
     /// Only push the device id onto the end of the vector if it does not already exist
@@ -346,36 +378,165 @@ impl<T: Config> Module<T> {
         }
     }
 
-    fn random_value(sender: &T::AccountId) -> [u8; 16] {
-        let payload = (
-            T::Randomness::random(&[0]),
-            sender,
-            <frame_system::Module<T>>::extrinsic_index(),
-            <frame_system::Module<T>>::block_number(),
-        );
-        payload.using_encoded(blake2_128)
+    /// Remove a roaming device along with its links to its network server and organization, cleaning up its children
+    /// according to the removal policy
+    pub fn remove_roaming_device(roaming_device_id: T::RoamingDeviceIndex) -> DispatchResult {
+        Self::exists_roaming_device(roaming_device_id)?;
+        T::RoamingDeviceChildren::remove_children_with_policy(
+            <T as roaming_operators::Config>::RemovalPolicy::get(),
+            roaming_device_id,
+        )?;
+
+        if let Some(roaming_network_server_id) = <RoamingDeviceNetworkServers<T>>::take(roaming_device_id) {
+            <RoamingNetworkServerDevices<T>>::remove(roaming_network_server_id, roaming_device_id);
+        }
+        if let Some(roaming_organization_id) = <RoamingDeviceOrganization<T>>::take(roaming_device_id) {
+            <RoamingOrganizationDevices<T>>::mutate_exists(roaming_organization_id, |device_ids| {
+                *device_ids = without_roaming_entity(device_ids.take(), &roaming_device_id)
+            });
+        }
+        <RoamingDevicePolicies<T>>::remove(roaming_device_id);
+        T::RoamingDeviceRegistry::deregister(roaming_device_id)
     }
 
-    fn next_roaming_device_id() -> Result<T::RoamingDeviceIndex, DispatchError> {
-        let roaming_device_id = Self::roaming_devices_count();
-        if roaming_device_id == <T::RoamingDeviceIndex as Bounded>::max_value() {
-            return Err(DispatchError::Other("RoamingDevices count overflow"));
+    /// Ensure that the roaming policy of a device allows it to roam to the network of the given network server,
+    /// where the home network of the device is the network of the network server that it is assigned to
+    pub fn ensure_roaming_allowed(
+        roaming_device_id: T::RoamingDeviceIndex,
+        roaming_network_server_id: Option<T::RoamingNetworkServerIndex>,
+    ) -> Result<(), Error<T>> {
+        let roaming_policy = match Self::roaming_device_policy(roaming_device_id) {
+            Some(roaming_policy) => roaming_policy,
+            None => return Ok(()),
+        };
+        let visited_network_id = roaming_network_server_id.and_then(|network_server_id| {
+            <roaming_network_servers::Module<T>>::roaming_network_server_network(network_server_id)
+        });
+        let home_network_id = Self::roaming_device_network_server(roaming_device_id).and_then(|network_server_id| {
+            <roaming_network_servers::Module<T>>::roaming_network_server_network(network_server_id)
+        });
+        let is_home_network = visited_network_id.is_some() && visited_network_id == home_network_id;
+
+        match roaming_policy {
+            RoamingDevicePolicy::Disabled => Err(Error::<T>::RoamingDisabled),
+            RoamingDevicePolicy::HomeOnly => {
+                ensure!(is_home_network, Error::<T>::RoamingRestrictedToHomeNetwork);
+                Ok(())
+            }
+            RoamingDevicePolicy::AllowedNetworks(network_ids) => {
+                ensure!(
+                    is_home_network || visited_network_id.map_or(false, |network_id| network_ids.contains(&network_id)),
+                    Error::<T>::NetworkNotAllowedForRoaming
+                );
+                Ok(())
+            }
         }
-        Ok(roaming_device_id)
     }
 
-    fn insert_roaming_device(
-        owner: &T::AccountId,
-        roaming_device_id: T::RoamingDeviceIndex,
-        roaming_device: RoamingDevice,
-    ) {
-        // Create and store roaming device
-        <RoamingDevices<T>>::insert(roaming_device_id, roaming_device);
-        <RoamingDevicesCount<T>>::put(roaming_device_id + One::one());
-        <RoamingDeviceOwners<T>>::insert(roaming_device_id, owner.clone());
+    /// The devices of a roaming network server in ascending order
+    pub fn network_server_devices(
+        roaming_network_server_id: T::RoamingNetworkServerIndex,
+    ) -> Vec<T::RoamingDeviceIndex> {
+        let mut device_ids: Vec<T::RoamingDeviceIndex> =
+            <RoamingNetworkServerDevices<T>>::iter_prefix(roaming_network_server_id)
+                .map(|(device_id, _)| device_id)
+                .collect();
+        device_ids.sort();
+        device_ids
+    }
+
+    /// The devices of a roaming network server in the order they are stored, starting at the given device
+    pub fn iter_network_server_devices_from(
+        roaming_network_server_id: T::RoamingNetworkServerIndex,
+        start: Option<T::RoamingDeviceIndex>,
+    ) -> impl Iterator<Item = T::RoamingDeviceIndex> {
+        iter_double_map_keys_from::<RoamingNetworkServerDevices<T>, _, _>(roaming_network_server_id, start)
+    }
+
+    /// Move the devices of each roaming network server from a vector into the keys of a double map, dropping any
+    /// device that has since been assigned to another network server
+    fn migrate_to_network_server_device_keys() -> Weight {
+        let roaming_network_servers_count = get_storage_value::<T::RoamingNetworkServerIndex>(
+            b"RoamingNetworkServers",
+            b"RoamingNetworkServersCount",
+            &[],
+        )
+        .unwrap_or_else(T::RoamingNetworkServerRegistry::entities_count);
+        let mut migrated_count: Weight = 0;
+        let mut device_count: Weight = 0;
+
+        let mut roaming_network_server_id: T::RoamingNetworkServerIndex = Zero::zero();
+        while roaming_network_server_id < roaming_network_servers_count {
+            let device_ids = take_storage_value::<Vec<T::RoamingDeviceIndex>>(
+                b"RoamingDevices",
+                b"RoamingNetworkServerDevices",
+                &blake2_256(&roaming_network_server_id.encode()),
+            )
+            .unwrap_or_default();
+            for roaming_device_id in device_ids {
+                if Self::roaming_device_network_server(roaming_device_id) == Some(roaming_network_server_id) {
+                    <RoamingNetworkServerDevices<T>>::insert(roaming_network_server_id, roaming_device_id, ());
+                }
+                device_count += 1;
+            }
+
+            migrated_count += 1;
+            roaming_network_server_id = roaming_network_server_id + One::one();
+        }
+
+        StorageVersion::put(Releases::V2_0_0);
+        debug::info!("Migrated the devices of {:?} roaming network servers to double map keys", migrated_count);
+
+        T::DbWeight::get().reads_writes(2 + migrated_count + device_count, 1 + migrated_count + device_count)
+    }
+}
+
+impl<T: Config> RoamingAuthority<T::AccountId, T::RoamingDeviceIndex> for Module<T> {
+    fn is_owner_or_admin(roaming_device_id: T::RoamingDeviceIndex, account: &T::AccountId) -> bool {
+        T::RoamingDeviceRegistry::is_owner_or_admin(roaming_device_id, account)
     }
 
-    fn update_owner(to: &T::AccountId, roaming_device_id: T::RoamingDeviceIndex) {
-        <RoamingDeviceOwners<T>>::insert(roaming_device_id, to);
+    /// The owner and admins of a roaming device are authorized, as is any account authorized for its organization or
+    /// its network server
+    fn is_authorized(roaming_device_id: T::RoamingDeviceIndex, account: &T::AccountId) -> bool {
+        Self::is_owner_or_admin(roaming_device_id, account) ||
+            Self::roaming_device_organization(roaming_device_id)
+                .map_or(false, |parent_id| <roaming_organizations::Module<T>>::is_authorized(parent_id, account)) ||
+            Self::roaming_device_network_server(roaming_device_id)
+                .map_or(false, |parent_id| <roaming_network_servers::Module<T>>::is_authorized(parent_id, account))
+    }
+}
+
+/// The devices of a roaming network server
+impl<T: Config> RoamingEntityChildren<RoamingNetworkServerEntity, T::RoamingNetworkServerIndex> for Module<T> {
+    fn has_children(roaming_network_server_id: T::RoamingNetworkServerIndex) -> bool {
+        <RoamingNetworkServerDevices<T>>::iter_prefix(roaming_network_server_id).next().is_some()
+    }
+
+    fn remove_children(roaming_network_server_id: T::RoamingNetworkServerIndex) -> DispatchResult {
+        for device_id in Self::network_server_devices(roaming_network_server_id) {
+            Self::remove_roaming_device(device_id)?;
+        }
+        <RoamingNetworkServerDevices<T>>::remove_prefix(roaming_network_server_id);
+        Ok(())
+    }
+}
+
+/// The devices of a roaming organization, other than those that have since been assigned to another organization
+impl<T: Config> RoamingEntityChildren<RoamingOrganizationEntity, T::RoamingOrganizationIndex> for Module<T> {
+    fn has_children(roaming_organization_id: T::RoamingOrganizationIndex) -> bool {
+        Self::roaming_organization_devices(roaming_organization_id)
+            .unwrap_or_default()
+            .into_iter()
+            .any(|device_id| Self::roaming_device_organization(device_id) == Some(roaming_organization_id))
+    }
+
+    fn remove_children(roaming_organization_id: T::RoamingOrganizationIndex) -> DispatchResult {
+        for device_id in <RoamingOrganizationDevices<T>>::take(roaming_organization_id).unwrap_or_default() {
+            if Self::roaming_device_organization(device_id) == Some(roaming_organization_id) {
+                Self::remove_roaming_device(device_id)?;
+            }
+        }
+        Ok(())
     }
 }
@@ -5,109 +5,234 @@ use crate::mock::*;
 use frame_support::{
     assert_noop,
     assert_ok,
+    storage::migration::put_storage_value,
+    traits::OnRuntimeUpgrade,
 };
+use sp_io::hashing::blake2_256;
 
 #[test]
 fn basic_setup_works() {
     new_test_ext().execute_with(|| {
         // Verify Initial Storage
-        assert_eq!(RoamingDeviceModule::roaming_devices_count(), 0);
-        assert!(RoamingDeviceModule::roaming_device(0).is_none());
-        assert_eq!(RoamingDeviceModule::roaming_device_owner(0), None);
-        assert_eq!(RoamingDeviceModule::roaming_device_price(0), None);
+        assert_eq!(RoamingDeviceRegistry::roaming_entities_count(), 0);
+        assert!(RoamingDeviceRegistry::roaming_entity(0).is_none());
+        assert_eq!(RoamingDeviceRegistry::roaming_entity_owner(0), None);
+        assert_eq!(RoamingDeviceRegistry::roaming_entity_price(0), None);
         assert_eq!(Balances::free_balance(1), 10);
         assert_eq!(Balances::free_balance(2), 20);
     });
 }
 
 #[test]
-fn create_works() {
+fn network_server_devices_skips_reassigned_devices() {
     new_test_ext().execute_with(|| {
+        // Setup
+        assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingDeviceRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingDeviceRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingDeviceModule::assign_device_to_network_server(Origin::signed(1), 0, 0));
+        assert_ok!(RoamingDeviceModule::assign_device_to_network_server(Origin::signed(1), 1, 0));
         // Call Functions
-        assert_ok!(RoamingDeviceModule::create(Origin::signed(1)));
+        assert_ok!(RoamingDeviceModule::assign_device_to_network_server(Origin::signed(1), 0, 1));
         // Verify Storage
-        assert_eq!(RoamingDeviceModule::roaming_devices_count(), 1);
-        assert!(RoamingDeviceModule::roaming_device(0).is_some());
-        assert_eq!(RoamingDeviceModule::roaming_device_owner(0), Some(1));
-        assert_eq!(RoamingDeviceModule::roaming_device_price(0), None);
+        assert_eq!(RoamingDeviceModule::network_server_devices(0), vec![1]);
+        assert_eq!(RoamingDeviceModule::network_server_devices(1), vec![0]);
+        assert_eq!(RoamingDeviceModule::network_server_devices(2), Vec::<u64>::new());
     });
 }
 
 #[test]
-fn create_handles_basic_errors() {
+fn assign_device_to_network_server_handles_basic_errors() {
     new_test_ext().execute_with(|| {
         // Setup
-        <RoamingDevicesCount<Test>>::put(u64::max_value());
+        assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingDeviceRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingDeviceModule::assign_device_to_network_server(Origin::signed(1), 0, 0));
         // Call Functions
-        assert_noop!(RoamingDeviceModule::create(Origin::signed(1)), "RoamingDevices count overflow");
+        assert_noop!(
+            RoamingDeviceModule::assign_device_to_network_server(Origin::signed(1), 0, 0),
+            "Network Server already contains the given device id"
+        );
+        assert_noop!(
+            RoamingDeviceModule::assign_device_to_network_server(Origin::signed(1), 1, 0),
+            "Invalid roaming_device_id"
+        );
         // Verify Storage
-        assert_eq!(RoamingDeviceModule::roaming_devices_count(), u64::max_value());
-        assert!(RoamingDeviceModule::roaming_device(0).is_none());
-        assert_eq!(RoamingDeviceModule::roaming_device_owner(0), None);
-        assert_eq!(RoamingDeviceModule::roaming_device_price(0), None);
+        assert_eq!(RoamingDeviceModule::network_server_devices(0), vec![0]);
     });
 }
 
 #[test]
-fn transfer_works() {
+fn assign_device_refuses_accounts_not_authorized_for_the_device() {
+    new_test_ext().execute_with(|| {
+        // Setup - device 0 of account 1 and network server 0 of account 2
+        assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(2)));
+        assert_ok!(RoamingDeviceRegistry::create(Origin::signed(1)));
+        // Call Functions
+        assert_noop!(
+            RoamingDeviceModule::assign_device_to_network_server(Origin::signed(2), 0, 0),
+            "Only an account authorized for the roaming device can assign it to a roaming network_server"
+        );
+        assert_ok!(RoamingDeviceRegistry::grant_admin(Origin::signed(1), 0, 2));
+        assert_ok!(RoamingDeviceModule::assign_device_to_network_server(Origin::signed(2), 0, 0));
+        // Verify Storage
+        assert_eq!(RoamingDeviceModule::roaming_device_network_server(0), Some(0));
+    });
+}
+
+#[test]
+fn migration_moves_network_server_devices_to_double_map_keys() {
     new_test_ext().execute_with(|| {
         // Setup
-        assert_ok!(RoamingDeviceModule::create(Origin::signed(1)));
+        for _ in 0..2 {
+            assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(1)));
+        }
+        for _ in 0..3 {
+            assert_ok!(RoamingDeviceRegistry::create(Origin::signed(1)));
+        }
+        // Device 1 has since been assigned from network server 0 to network server 1
+        <RoamingDeviceNetworkServers<Test>>::insert(0, 0);
+        <RoamingDeviceNetworkServers<Test>>::insert(1, 1);
+        <RoamingDeviceNetworkServers<Test>>::insert(2, 1);
+        put_storage_value(
+            b"RoamingDevices",
+            b"RoamingNetworkServerDevices",
+            &blake2_256(&0u64.encode()),
+            vec![0u64, 1],
+        );
+        put_storage_value(
+            b"RoamingDevices",
+            b"RoamingNetworkServerDevices",
+            &blake2_256(&1u64.encode()),
+            vec![2u64, 1],
+        );
+        StorageVersion::put(Releases::V1_0_0);
         // Call Functions
-        assert_ok!(RoamingDeviceModule::transfer(Origin::signed(1), 2, 0));
+        RoamingDeviceModule::on_runtime_upgrade();
         // Verify Storage
-        assert_eq!(RoamingDeviceModule::roaming_devices_count(), 1);
-        assert!(RoamingDeviceModule::roaming_device(0).is_some());
-        assert_eq!(RoamingDeviceModule::roaming_device_owner(0), Some(2));
-        assert_eq!(RoamingDeviceModule::roaming_device_price(0), None);
+        assert_eq!(RoamingDeviceModule::storage_version(), Releases::V2_0_0);
+        assert_eq!(RoamingDeviceModule::network_server_devices(0), vec![0]);
+        assert_eq!(RoamingDeviceModule::network_server_devices(1), vec![1, 2]);
+        assert!(!<RoamingNetworkServerDevices<Test>>::contains_key(0, 1));
     });
 }
 
 #[test]
-fn transfer_handles_basic_errors() {
+fn migration_keeps_network_server_devices_already_in_double_map_keys() {
     new_test_ext().execute_with(|| {
         // Setup
-        assert_ok!(RoamingDeviceModule::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingDeviceRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingDeviceModule::assign_device_to_network_server(Origin::signed(1), 0, 0));
+        StorageVersion::put(Releases::V1_0_0);
         // Call Functions
-        assert_noop!(RoamingDeviceModule::transfer(Origin::signed(2), 2, 0), "Only owner can transfer roaming device");
-        assert_noop!(RoamingDeviceModule::transfer(Origin::signed(1), 2, 1), "Only owner can transfer roaming device");
+        RoamingDeviceModule::on_runtime_upgrade();
         // Verify Storage
-        assert_eq!(RoamingDeviceModule::roaming_devices_count(), 1);
-        assert!(RoamingDeviceModule::roaming_device(0).is_some());
-        assert_eq!(RoamingDeviceModule::roaming_device_owner(0), Some(1));
-        assert_eq!(RoamingDeviceModule::roaming_device_price(0), None);
+        assert_eq!(RoamingDeviceModule::storage_version(), Releases::V2_0_0);
+        assert_eq!(RoamingDeviceModule::network_server_devices(0), vec![0]);
     });
 }
 
 #[test]
-fn set_price_works() {
+fn set_roaming_policy_works() {
     new_test_ext().execute_with(|| {
         // Setup
-        assert_ok!(RoamingDeviceModule::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkRegistry::create(Origin::signed(2)));
+        assert_ok!(RoamingDeviceRegistry::create(Origin::signed(1)));
         // Call Functions
-        assert_ok!(RoamingDeviceModule::set_price(Origin::signed(1), 0, Some(10)));
+        assert_ok!(RoamingDeviceModule::set_roaming_policy(
+            Origin::signed(1),
+            0,
+            Some(RoamingDevicePolicy::AllowedNetworks(vec![1, 0, 1]))
+        ));
         // Verify Storage
-        assert_eq!(RoamingDeviceModule::roaming_devices_count(), 1);
-        assert!(RoamingDeviceModule::roaming_device(0).is_some());
-        assert_eq!(RoamingDeviceModule::roaming_device_owner(0), Some(1));
-        assert_eq!(RoamingDeviceModule::roaming_device_price(0), Some(10));
+        assert_eq!(
+            RoamingDeviceModule::roaming_device_policy(0),
+            Some(RoamingDevicePolicy::AllowedNetworks(vec![0, 1]))
+        );
+        // Call Functions
+        assert_ok!(RoamingDeviceModule::set_roaming_policy(Origin::signed(1), 0, None));
+        // Verify Storage
+        assert_eq!(RoamingDeviceModule::roaming_device_policy(0), None);
+    });
+}
+
+#[test]
+fn set_roaming_policy_handles_basic_errors() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        for _ in 0..3 {
+            assert_ok!(RoamingNetworkRegistry::create(Origin::signed(1)));
+        }
+        assert_ok!(RoamingDeviceRegistry::create(Origin::signed(1)));
+        // Call Functions
+        assert_noop!(
+            RoamingDeviceModule::set_roaming_policy(Origin::signed(2), 0, Some(RoamingDevicePolicy::Disabled)),
+            "Only owner can set roaming policy of roaming device"
+        );
+        assert_noop!(
+            RoamingDeviceModule::set_roaming_policy(
+                Origin::signed(1),
+                0,
+                Some(RoamingDevicePolicy::AllowedNetworks(vec![0, 1, 2]))
+            ),
+            Error::<Test>::TooManyAllowedNetworks
+        );
+        assert_noop!(
+            RoamingDeviceModule::set_roaming_policy(
+                Origin::signed(1),
+                0,
+                Some(RoamingDevicePolicy::AllowedNetworks(vec![0, 5]))
+            ),
+            Error::<Test>::AllowedNetworkDoesNotExist
+        );
     });
 }
 
 #[test]
-fn buy_works() {
+fn ensure_roaming_allowed_follows_roaming_policy() {
     new_test_ext().execute_with(|| {
         // Setup
-        assert_ok!(RoamingDeviceModule::create(Origin::signed(1)));
-        assert_ok!(RoamingDeviceModule::set_price(Origin::signed(1), 0, Some(10)));
+        // Network server 0 of home network 0 and network servers 1 and 2 of networks 1 and 2
+        for id in 0..3 {
+            assert_ok!(RoamingNetworkRegistry::create(Origin::signed(1)));
+            assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(1)));
+            assert_ok!(RoamingNetworkServerModule::assign_network_server_to_network(Origin::signed(1), id, id));
+        }
+        assert_ok!(RoamingDeviceRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingDeviceModule::assign_device_to_network_server(Origin::signed(1), 0, 0));
+        // Verify Storage
+        assert_ok!(RoamingDeviceModule::ensure_roaming_allowed(0, Some(1)));
+        // Call Functions
+        assert_ok!(RoamingDeviceModule::set_roaming_policy(Origin::signed(1), 0, Some(RoamingDevicePolicy::Disabled)));
+        // Verify Storage
+        assert!(matches!(RoamingDeviceModule::ensure_roaming_allowed(0, Some(0)), Err(Error::<Test>::RoamingDisabled)));
+        // Call Functions
+        assert_ok!(RoamingDeviceModule::set_roaming_policy(Origin::signed(1), 0, Some(RoamingDevicePolicy::HomeOnly)));
+        // Verify Storage
+        assert_ok!(RoamingDeviceModule::ensure_roaming_allowed(0, Some(0)));
+        assert!(matches!(
+            RoamingDeviceModule::ensure_roaming_allowed(0, Some(1)),
+            Err(Error::<Test>::RoamingRestrictedToHomeNetwork)
+        ));
+        assert!(matches!(
+            RoamingDeviceModule::ensure_roaming_allowed(0, None),
+            Err(Error::<Test>::RoamingRestrictedToHomeNetwork)
+        ));
         // Call Functions
-        assert_ok!(RoamingDeviceModule::buy(Origin::signed(2), 0, 10));
+        assert_ok!(RoamingDeviceModule::set_roaming_policy(
+            Origin::signed(1),
+            0,
+            Some(RoamingDevicePolicy::AllowedNetworks(vec![1]))
+        ));
         // Verify Storage
-        assert_eq!(RoamingDeviceModule::roaming_devices_count(), 1);
-        assert!(RoamingDeviceModule::roaming_device(0).is_some());
-        assert_eq!(RoamingDeviceModule::roaming_device_owner(0), Some(2));
-        assert_eq!(RoamingDeviceModule::roaming_device_price(0), None);
-        assert_eq!(Balances::free_balance(1), 20);
-        assert_eq!(Balances::free_balance(2), 10);
+        assert_ok!(RoamingDeviceModule::ensure_roaming_allowed(0, Some(0)));
+        assert_ok!(RoamingDeviceModule::ensure_roaming_allowed(0, Some(1)));
+        assert!(matches!(
+            RoamingDeviceModule::ensure_roaming_allowed(0, Some(2)),
+            Err(Error::<Test>::NetworkNotAllowedForRoaming)
+        ));
     });
 }
@@ -0,0 +1,835 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{
+    Decode,
+    Encode,
+};
+use frame_support::{
+    debug,
+    decl_error,
+    decl_event,
+    decl_module,
+    decl_storage,
+    ensure,
+    storage::migration::take_storage_value,
+    traits::{
+        Currency,
+        EnsureOrigin,
+        Get,
+    },
+    transactional,
+    weights::Weight,
+    Parameter,
+};
+use frame_system::ensure_signed;
+use roaming_packet_bundles::RoamingPacketBundleBilling;
+use sp_io::hashing::blake2_256;
+use sp_runtime::{
+    traits::{
+        AtLeast32Bit,
+        Bounded,
+        Member,
+        One,
+        Saturating,
+        Zero,
+    },
+    DispatchError,
+    DispatchResult,
+};
+use sp_std::{
+    collections::btree_map::BTreeMap,
+    prelude::*, // Imports Vec
+};
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// The module's configuration trait.
+pub trait Config:
+    frame_system::Config
+    + roaming_packet_bundles::Config
+    + roaming_billing_policies::Config
+    + roaming_charging_policies::Config
+{
+    type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
+    type RoamingInvoiceIndex: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
+    /// The number of blocks after an invoice is billed that the home network may dispute its line items.
+    /// Invoices are never charged before their dispute window ends.
+    type InvoiceDisputeWindow: Get<Self::BlockNumber>;
+    /// The number of blocks after the dispute window of an invoice ends that the visited network may resolve its
+    /// disputed line items. Disputes that are still pending after that may only be resolved by the arbitration origin.
+    type InvoiceDisputeResolutionPeriod: Get<Self::BlockNumber>;
+    /// The origin that may resolve disputed line items on behalf of the visited network
+    type InvoiceArbitrationOrigin: EnsureOrigin<Self::Origin>;
+    /// The maximum number of unbilled packet bundles that may be billed in a block. Billing points with more
+    /// unbilled packet bundles than the limit continue billing them in the next block.
+    type MaxBilledPacketBundlesPerBlock: Get<u32>;
+    /// The maximum number of invoices that may be charged in a block. Invoices scheduled to be charged in a block
+    /// are charged in the order they were billed, and any charges over the limit are carried over to the next block.
+    type MaxInvoiceChargesPerBlock: Get<u32>;
+}
+
+type BalanceOf<T> =
+    <<T as roaming_operators::Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+/// Storage version of the roaming invoices pallet
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum Releases {
+    /// The invoice of each roaming packet bundle is stored by packet bundle
+    V1_0_0,
+    /// The invoice of each roaming packet bundle is stored by packet bundle receiver
+    V2_0_0,
+}
+
+impl Default for Releases {
+    fn default() -> Self {
+        Releases::V1_0_0
+    }
+}
+
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum LineItemStatus {
+    /// The line item is billed at the amount calculated from the visited network's accounting policy
+    Billed,
+    /// The home network contests the line item, so the invoice is not charged until it is resolved
+    Disputed,
+    /// The dispute was resolved and the line item is billed at the resolved amount
+    Resolved,
+}
+
+impl Default for LineItemStatus {
+    fn default() -> Self {
+        LineItemStatus::Billed
+    }
+}
+
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum InvoiceStatus {
+    /// The invoice was created at a billing point and is waiting to be charged
+    Billed,
+    /// At least one line item of the invoice is disputed and has not been resolved
+    Disputed,
+    /// The unsettled packet bundles of the invoice were charged to the home network
+    Charged,
+    /// Charging the home network failed, so its packet bundles remain unsettled
+    Unpaid,
+}
+
+impl Default for InvoiceStatus {
+    fn default() -> Self {
+        InvoiceStatus::Billed
+    }
+}
+
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+// Generic type parameters - RoamingPacketBundleIndex, RoamingNetworkServerIndex, Balance
+pub struct RoamingInvoiceLineItem<U, V, W> {
+    pub line_item_packet_bundle_id: U,
+    pub line_item_network_server_id: V,
+    pub line_item_amount: W,
+    pub line_item_status: LineItemStatus,
+}
+
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+// Generic type parameters - RoamingNetworkIndex, Vec<RoamingInvoiceLineItem>, Balance, BlockNumber
+pub struct RoamingInvoice<U, V, W, X> {
+    pub invoice_home_network_id: U,
+    pub invoice_visited_network_id: U,
+    pub invoice_line_items: V,
+    pub invoice_total_amount: W,
+    pub invoice_billed_at_block: X,
+    pub invoice_dispute_window_ends_at_block: X,
+    pub invoice_charge_at_block: X,
+    pub invoice_status: InvoiceStatus,
+}
+
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+// Generic type parameters - RoamingBillingPolicyIndex, RoamingNetworkIndex, BlockNumber,
+// Vec<(RoamingNetworkServerIndex, u32)>
+pub struct RoamingInvoiceBilling<U, V, W, X> {
+    pub billing_policy_id: U,
+    pub billing_visited_network_id: V,
+    /// The block of the billing point, which keys the invoices of the billing point even when billing it is
+    /// carried over to later blocks
+    pub billing_period_started_at_block: W,
+    pub billing_charge_at_block: W,
+    /// The number of packet bundles left to bill from the unbilled packet bundles of each network server of the
+    /// visited network, counted at the billing point so that packet bundles queued afterwards wait for the next one
+    pub billing_network_servers: X,
+}
+
+decl_event!(
+    pub enum Event<T> where
+        <T as frame_system::Config>::AccountId,
+        <T as Config>::RoamingInvoiceIndex,
+        <T as roaming_packet_bundles::Config>::RoamingPacketBundleIndex,
+        <T as roaming_network_servers::Config>::RoamingNetworkServerIndex,
+        <T as roaming_networks::Config>::RoamingNetworkIndex,
+        Balance = BalanceOf<T>,
+        <T as frame_system::Config>::BlockNumber,
+    {
+        /// The unsettled packet bundles of a home network are billed by a visited network at a billing point.
+        /// (invoice_id, home roaming_network_id, visited roaming_network_id, total amount, charge at block)
+        InvoiceBilled(RoamingInvoiceIndex, RoamingNetworkIndex, RoamingNetworkIndex, Balance, BlockNumber),
+        /// An invoice is charged to the home network. (invoice_id, amount charged)
+        InvoiceCharged(RoamingInvoiceIndex, Balance),
+        /// An invoice could not be charged to the home network. (invoice_id, amount unpaid)
+        InvoiceUnpaid(RoamingInvoiceIndex, Balance),
+        /// A line item of an invoice is disputed by the home network.
+        /// (home network operator owner, invoice_id, roaming_packet_bundle_id, roaming_network_server_id)
+        LineItemDisputed(AccountId, RoamingInvoiceIndex, RoamingPacketBundleIndex, RoamingNetworkServerIndex),
+        /// A disputed line item of an invoice is resolved.
+        /// (invoice_id, roaming_packet_bundle_id, roaming_network_server_id, resolved amount)
+        LineItemDisputeResolved(RoamingInvoiceIndex, RoamingPacketBundleIndex, RoamingNetworkServerIndex, Balance),
+    }
+);
+
+decl_error! {
+    pub enum Error for Module<T: Config> {
+        /// The invoice does not exist
+        InvoiceDoesNotExist,
+        /// The packet bundle receiver is not a line item of the invoice
+        LineItemDoesNotExist,
+        /// Only the owner of the operator of the home network of the invoice may dispute its line items
+        NotHomeNetworkOperator,
+        /// Only the owner of the operator of the visited network of the invoice may resolve its disputes
+        NotVisitedNetworkOperator,
+        /// The dispute window of the invoice has ended
+        DisputeWindowEnded,
+        /// The resolution period of the dispute has ended, so it may only be resolved by the arbitration origin
+        DisputeResolutionPeriodEnded,
+        /// The line item has already been disputed
+        LineItemAlreadyDisputed,
+        /// The line item is not disputed
+        LineItemNotDisputed,
+        /// A dispute may only be resolved by reducing the amount of the line item
+        ResolvedAmountExceedsLineItem,
+    }
+}
+
+// This module's storage items.
+decl_storage! {
+    trait Store for Module<T: Config> as RoamingInvoices {
+        /// Stores all the roaming invoices, key is the invoice id / index
+        pub RoamingInvoices get(fn roaming_invoice): map hasher(opaque_blake2_256) T::RoamingInvoiceIndex =>
+            Option<RoamingInvoice<
+                T::RoamingNetworkIndex,
+                Vec<RoamingInvoiceLineItem<T::RoamingPacketBundleIndex, T::RoamingNetworkServerIndex, BalanceOf<T>>>,
+                BalanceOf<T>,
+                T::BlockNumber
+            >>;
+
+        /// Stores the total number of roaming invoices. i.e. the next invoice index
+        pub RoamingInvoicesCount get(fn roaming_invoices_count): T::RoamingInvoiceIndex;
+
+        /// Get the invoice of a home network billed by a visited network at the billing point that started at a block
+        pub RoamingInvoicePeriods get(fn roaming_invoice_period): map hasher(opaque_blake2_256) (T::RoamingNetworkIndex, T::RoamingNetworkIndex, T::BlockNumber) => Option<T::RoamingInvoiceIndex>;
+
+        /// Get the invoice that the packets received by a network server in a roaming packet_bundle were billed in
+        pub RoamingPacketBundleInvoices get(fn roaming_packet_bundle_invoice): map hasher(opaque_blake2_256) (T::RoamingPacketBundleIndex, T::RoamingNetworkServerIndex) => Option<T::RoamingInvoiceIndex>;
+
+        /// Get the invoices that are scheduled to be charged at a block
+        pub RoamingInvoiceCharges get(fn roaming_invoice_charges): map hasher(opaque_blake2_256) T::BlockNumber => Vec<T::RoamingInvoiceIndex>;
+
+        /// Get the billing points that still have unbilled packet bundles to bill after reaching the limit of billed
+        /// packet bundles of a block, in the order they started
+        pub RoamingInvoiceBillings get(fn roaming_invoice_billings): Vec<RoamingInvoiceBilling<
+            T::RoamingBillingPolicyIndex,
+            T::RoamingNetworkIndex,
+            T::BlockNumber,
+            Vec<(T::RoamingNetworkServerIndex, u32)>
+        >>;
+
+        /// Storage version of the pallet, used to determine which migrations to run on runtime upgrade. A new chain
+        /// starts at the latest version since it has no packet bundle invoices to migrate.
+        pub StorageVersion get(fn storage_version) build(|_| Releases::V2_0_0): Releases;
+    }
+}
+
+// The module's dispatchable functions.
+decl_module! {
+    /// The module declaration.
+    pub struct Module<T: Config> for enum Call where origin: T::Origin {
+        fn deposit_event() = default;
+
+        type Error = Error<T>;
+
+        const InvoiceDisputeWindow: T::BlockNumber = T::InvoiceDisputeWindow::get();
+
+        const InvoiceDisputeResolutionPeriod: T::BlockNumber = T::InvoiceDisputeResolutionPeriod::get();
+
+        const MaxBilledPacketBundlesPerBlock: u32 = T::MaxBilledPacketBundlesPerBlock::get();
+
+        const MaxInvoiceChargesPerBlock: u32 = T::MaxInvoiceChargesPerBlock::get();
+
+        fn on_runtime_upgrade() -> Weight {
+            let mut weight = T::DbWeight::get().reads(1);
+            if Self::storage_version() == Releases::V1_0_0 {
+                weight = weight.saturating_add(Self::migrate_to_packet_bundle_receiver_invoices());
+            }
+            weight
+        }
+
+        fn on_initialize(current_block_number: T::BlockNumber) -> Weight {
+            // Billing points carried over from earlier blocks continue billing before new billing points start
+            let mut billings = <RoamingInvoiceBillings<T>>::take();
+            let carried_over_billings_count = billings.len() as Weight;
+            let due_billing_policies = <roaming_billing_policies::Module<T>>::take_billing_policies_due_at(current_block_number);
+            let due_billing_policies_count = due_billing_policies.len() as Weight;
+            for roaming_billing_policy_id in due_billing_policies {
+                debug::info!("Starting billing point of billing policy {:?}", roaming_billing_policy_id);
+                if let Some(billing) = Self::start_billing_point(roaming_billing_policy_id, current_block_number) {
+                    // The unbilled packet bundles of the visited network are billed by its pending billing point first
+                    if billings.iter().any(|pending| pending.billing_visited_network_id == billing.billing_visited_network_id) {
+                        debug::info!("Billing point of billing policy {:?} is still pending", roaming_billing_policy_id);
+                    } else {
+                        billings.push(billing);
+                    }
+                }
+            }
+
+            let max_billed_packet_bundles = T::MaxBilledPacketBundlesPerBlock::get().max(1);
+            let mut billed_packet_bundles_count: u32 = 0;
+            let mut unfinished_billings = Vec::new();
+            for mut billing in billings {
+                if billed_packet_bundles_count < max_billed_packet_bundles {
+                    billed_packet_bundles_count += Self::bill_unbilled_packet_bundles(
+                        &mut billing,
+                        current_block_number,
+                        max_billed_packet_bundles - billed_packet_bundles_count,
+                    );
+                }
+                if !billing.billing_network_servers.is_empty() {
+                    unfinished_billings.push(billing);
+                }
+            }
+            let unfinished_billings_count = unfinished_billings.len() as Weight;
+            if !unfinished_billings.is_empty() {
+                <RoamingInvoiceBillings<T>>::put(unfinished_billings);
+            }
+            let billed_packet_bundles_count = billed_packet_bundles_count as Weight;
+
+            let mut charged_line_items_count: Weight = 0;
+            let mut due_invoices = <RoamingInvoiceCharges<T>>::take(current_block_number);
+            let max_charges = T::MaxInvoiceChargesPerBlock::get().max(1) as usize;
+            if due_invoices.len() > max_charges {
+                // Charges scheduled into a full block are charged first in the next block
+                let mut carried_over_invoices = due_invoices.split_off(max_charges);
+                let next_block_number = current_block_number.saturating_add(One::one());
+                let next_due_invoices = <RoamingInvoiceCharges<T>>::take(next_block_number);
+                carried_over_invoices.extend(next_due_invoices);
+                <RoamingInvoiceCharges<T>>::insert(next_block_number, carried_over_invoices);
+            }
+            let due_invoices_count = due_invoices.len() as Weight;
+            for roaming_invoice_id in due_invoices {
+                debug::info!("Charging roaming invoice {:?}", roaming_invoice_id);
+                charged_line_items_count += Self::charge_invoice(roaming_invoice_id) as Weight;
+            }
+
+            T::DbWeight::get().reads_writes(
+                4 + carried_over_billings_count * 2 + due_billing_policies_count * 6 + billed_packet_bundles_count * 11 + due_invoices_count * 8 + charged_line_items_count,
+                4 + unfinished_billings_count + due_billing_policies_count * 5 + billed_packet_bundles_count * 3 + due_invoices_count * 4 + charged_line_items_count,
+            )
+        }
+
+        /// Dispute the line item of the packets received by a network server in a packet bundle before the dispute
+        /// window of its invoice ends. The invoice is not charged until all of its disputed line items are resolved.
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(4, 1)]
+        pub fn dispute_line_item(
+            origin,
+            roaming_invoice_id: T::RoamingInvoiceIndex,
+            roaming_packet_bundle_id: T::RoamingPacketBundleIndex,
+            roaming_network_server_id: T::RoamingNetworkServerIndex
+        ) {
+            let sender = ensure_signed(origin)?;
+
+            let mut invoice = Self::roaming_invoice(roaming_invoice_id).ok_or(Error::<T>::InvoiceDoesNotExist)?;
+            let home_operator_account_id =
+                <roaming_packet_bundles::Module<T>>::network_operator_account(invoice.invoice_home_network_id)?;
+            ensure!(home_operator_account_id == sender, Error::<T>::NotHomeNetworkOperator);
+
+            let current_block = <frame_system::Module<T>>::block_number();
+            ensure!(current_block < invoice.invoice_dispute_window_ends_at_block, Error::<T>::DisputeWindowEnded);
+
+            let line_item = invoice.invoice_line_items
+                .iter_mut()
+                .find(|line_item| {
+                    line_item.line_item_packet_bundle_id == roaming_packet_bundle_id &&
+                        line_item.line_item_network_server_id == roaming_network_server_id
+                })
+                .ok_or(Error::<T>::LineItemDoesNotExist)?;
+            ensure!(line_item.line_item_status == LineItemStatus::Billed, Error::<T>::LineItemAlreadyDisputed);
+            line_item.line_item_status = LineItemStatus::Disputed;
+            invoice.invoice_status = InvoiceStatus::Disputed;
+
+            <RoamingInvoices<T>>::insert(roaming_invoice_id, invoice);
+
+            Self::deposit_event(RawEvent::LineItemDisputed(
+                sender,
+                roaming_invoice_id,
+                roaming_packet_bundle_id,
+                roaming_network_server_id
+            ));
+        }
+
+        /// Resolve a disputed line item of an invoice by billing it at an amount no greater than its original
+        /// amount. May be called by the owner of the operator of the visited network until the resolution period
+        /// after the dispute window ends, and by the arbitration origin at any time.
+        /// If no disputes remain and the invoice is past its charging point then it is charged immediately.
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(12, 4)]
+        pub fn resolve_line_item_dispute(
+            origin,
+            roaming_invoice_id: T::RoamingInvoiceIndex,
+            roaming_packet_bundle_id: T::RoamingPacketBundleIndex,
+            roaming_network_server_id: T::RoamingNetworkServerIndex,
+            resolved_amount: BalanceOf<T>
+        ) {
+            let mut invoice = Self::roaming_invoice(roaming_invoice_id).ok_or(Error::<T>::InvoiceDoesNotExist)?;
+            if let Err(origin) = T::InvoiceArbitrationOrigin::try_origin(origin) {
+                let sender = ensure_signed(origin)?;
+                let visited_operator_account_id =
+                    <roaming_packet_bundles::Module<T>>::network_operator_account(invoice.invoice_visited_network_id)?;
+                ensure!(visited_operator_account_id == sender, Error::<T>::NotVisitedNetworkOperator);
+                let resolution_period_ends_at_block =
+                    invoice.invoice_dispute_window_ends_at_block.saturating_add(T::InvoiceDisputeResolutionPeriod::get());
+                ensure!(
+                    <frame_system::Module<T>>::block_number() < resolution_period_ends_at_block,
+                    Error::<T>::DisputeResolutionPeriodEnded
+                );
+            }
+
+            let line_item = invoice.invoice_line_items
+                .iter_mut()
+                .find(|line_item| {
+                    line_item.line_item_packet_bundle_id == roaming_packet_bundle_id &&
+                        line_item.line_item_network_server_id == roaming_network_server_id
+                })
+                .ok_or(Error::<T>::LineItemDoesNotExist)?;
+            ensure!(line_item.line_item_status == LineItemStatus::Disputed, Error::<T>::LineItemNotDisputed);
+            ensure!(resolved_amount <= line_item.line_item_amount, Error::<T>::ResolvedAmountExceedsLineItem);
+            line_item.line_item_amount = resolved_amount;
+            line_item.line_item_status = LineItemStatus::Resolved;
+
+            invoice.invoice_total_amount = invoice.invoice_line_items
+                .iter()
+                .fold(Zero::zero(), |total: BalanceOf<T>, line_item| total.saturating_add(line_item.line_item_amount));
+            let has_disputed_line_items = invoice.invoice_line_items
+                .iter()
+                .any(|line_item| line_item.line_item_status == LineItemStatus::Disputed);
+            if !has_disputed_line_items {
+                invoice.invoice_status = InvoiceStatus::Billed;
+            }
+            let charge_at_block = invoice.invoice_charge_at_block;
+            let invoice_status = invoice.invoice_status;
+
+            <RoamingInvoices<T>>::insert(roaming_invoice_id, invoice);
+
+            Self::deposit_event(RawEvent::LineItemDisputeResolved(
+                roaming_invoice_id,
+                roaming_packet_bundle_id,
+                roaming_network_server_id,
+                resolved_amount
+            ));
+
+            // Charging was deferred at the charging point while the dispute was pending
+            if invoice_status == InvoiceStatus::Billed && charge_at_block <= <frame_system::Module<T>>::block_number() {
+                Self::charge_invoice(roaming_invoice_id);
+            }
+        }
+    }
+}
+
+impl<T: Config> Module<T> {
+    /// Start the billing point of a billing policy by counting the unbilled packet bundles received by the network
+    /// servers of its network, and advance the next billing and charging points of the network's policies. The
+    /// invoices of the billing point are charged after the delay of the network's charging policy or the dispute
+    /// window, whichever ends last.
+    fn start_billing_point(
+        roaming_billing_policy_id: T::RoamingBillingPolicyIndex,
+        current_block_number: T::BlockNumber,
+    ) -> Option<
+        RoamingInvoiceBilling<
+            T::RoamingBillingPolicyIndex,
+            T::RoamingNetworkIndex,
+            T::BlockNumber,
+            Vec<(T::RoamingNetworkServerIndex, u32)>,
+        >,
+    > {
+        <roaming_billing_policies::Module<T>>::advance_billing_policy(roaming_billing_policy_id);
+
+        let visited_roaming_network_id =
+            <roaming_billing_policies::Module<T>>::roaming_billing_policy_network(roaming_billing_policy_id)?;
+
+        let charge_at_block =
+            match <roaming_charging_policies::Module<T>>::network_charging_policy(visited_roaming_network_id) {
+                Some((roaming_charging_policy_id, policy_setting)) => {
+                    let charge_at_block = current_block_number + policy_setting.policy_delay_after_billing_in_blocks;
+                    <roaming_charging_policies::Module<T>>::advance_charging_policy(
+                        roaming_charging_policy_id,
+                        charge_at_block,
+                    );
+                    charge_at_block
+                }
+                None => current_block_number,
+            };
+
+        let billing_network_servers =
+            <roaming_network_servers::Module<T>>::roaming_network_network_servers(visited_roaming_network_id)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|roaming_network_server_id| {
+                    (
+                        roaming_network_server_id,
+                        <roaming_packet_bundles::Module<T>>::unbilled_packet_bundles_count(roaming_network_server_id),
+                    )
+                })
+                .filter(|(_, unbilled_packet_bundles_count)| *unbilled_packet_bundles_count > 0)
+                .collect();
+
+        Some(RoamingInvoiceBilling {
+            billing_policy_id: roaming_billing_policy_id,
+            billing_visited_network_id: visited_roaming_network_id,
+            billing_period_started_at_block: current_block_number,
+            billing_charge_at_block: charge_at_block,
+            billing_network_servers,
+        })
+    }
+
+    /// Aggregate up to the given number of the unbilled and unsettled packet bundles left to bill at a billing point
+    /// into an invoice per home network. Packet bundles billed in a later block than the billing point are added to
+    /// the invoice of their home network for the billing point while it has not been charged yet. Packet bundles
+    /// that cannot be billed yet for any reason other than their disputed packet counts are queued to be billed at a
+    /// later billing point. Returns the number of packet bundles that were processed.
+    fn bill_unbilled_packet_bundles(
+        billing: &mut RoamingInvoiceBilling<
+            T::RoamingBillingPolicyIndex,
+            T::RoamingNetworkIndex,
+            T::BlockNumber,
+            Vec<(T::RoamingNetworkServerIndex, u32)>,
+        >,
+        current_block_number: T::BlockNumber,
+        max_packet_bundles_count: u32,
+    ) -> u32 {
+        debug::info!("Billing unsettled packet bundles of billing policy {:?}", billing.billing_policy_id);
+        let visited_roaming_network_id = billing.billing_visited_network_id;
+        let dispute_window_ends_at_block = current_block_number + T::InvoiceDisputeWindow::get();
+        let charge_at_block = billing.billing_charge_at_block.max(dispute_window_ends_at_block);
+
+        let mut processed_packet_bundles_count: u32 = 0;
+        let mut home_network_line_items: BTreeMap<
+            T::RoamingNetworkIndex,
+            (
+                Vec<RoamingInvoiceLineItem<T::RoamingPacketBundleIndex, T::RoamingNetworkServerIndex, BalanceOf<T>>>,
+                BalanceOf<T>,
+            ),
+        > = BTreeMap::new();
+        while processed_packet_bundles_count < max_packet_bundles_count {
+            let (roaming_network_server_id, unbilled_packet_bundles_count) =
+                match billing.billing_network_servers.first().cloned() {
+                    Some(value) => value,
+                    None => break,
+                };
+            let taken_packet_bundles_count =
+                unbilled_packet_bundles_count.min(max_packet_bundles_count - processed_packet_bundles_count);
+            if taken_packet_bundles_count == unbilled_packet_bundles_count {
+                billing.billing_network_servers.remove(0);
+            } else {
+                billing.billing_network_servers[0].1 = unbilled_packet_bundles_count - taken_packet_bundles_count;
+            }
+            processed_packet_bundles_count += taken_packet_bundles_count;
+
+            for roaming_packet_bundle_id in <roaming_packet_bundles::Module<T>>::take_unbilled_packet_bundles(
+                roaming_network_server_id,
+                taken_packet_bundles_count,
+            ) {
+                if <roaming_packet_bundles::Module<T>>::roaming_packet_bundle_settlement(
+                    roaming_packet_bundle_id,
+                    roaming_network_server_id,
+                )
+                .is_some() ||
+                    Self::roaming_packet_bundle_invoice((roaming_packet_bundle_id, roaming_network_server_id))
+                        .is_some()
+                {
+                    continue;
+                }
+                match <roaming_packet_bundles::Module<T>>::packet_bundle_settlement_amount(
+                    roaming_packet_bundle_id,
+                    roaming_network_server_id,
+                ) {
+                    Ok((home_roaming_network_id, roaming_network_id, settlement_amount))
+                        if roaming_network_id == visited_roaming_network_id &&
+                            home_roaming_network_id != visited_roaming_network_id =>
+                    {
+                        let (line_items, total_amount) =
+                            home_network_line_items.entry(home_roaming_network_id).or_insert((Vec::new(), Zero::zero()));
+                        line_items.push(RoamingInvoiceLineItem {
+                            line_item_packet_bundle_id: roaming_packet_bundle_id,
+                            line_item_network_server_id: roaming_network_server_id,
+                            line_item_amount: settlement_amount,
+                            line_item_status: LineItemStatus::Billed,
+                        });
+                        *total_amount = total_amount.saturating_add(settlement_amount);
+                    }
+                    // A packet bundle with disputed packet counts is queued again once they are reconciled
+                    Err(e) if e != DispatchError::from(roaming_packet_bundles::Error::<T>::PacketCountsDisputed) => {
+                        debug::info!(
+                            "Unable to bill packet bundle {:?} received by network server {:?} yet: {:?}",
+                            roaming_packet_bundle_id,
+                            roaming_network_server_id,
+                            e
+                        );
+                        <roaming_packet_bundles::Module<T>>::requeue_unbilled_packet_bundle(
+                            roaming_network_server_id,
+                            roaming_packet_bundle_id,
+                        );
+                    }
+                    _ => {
+                        debug::info!(
+                            "Not billing packet bundle {:?} received by network server {:?}",
+                            roaming_packet_bundle_id,
+                            roaming_network_server_id
+                        );
+                    }
+                }
+            }
+        }
+
+        for (home_roaming_network_id, (line_items, total_amount)) in home_network_line_items {
+            let period = (home_roaming_network_id, visited_roaming_network_id, billing.billing_period_started_at_block);
+            let billed_invoice = Self::roaming_invoice_period(period).and_then(|roaming_invoice_id| {
+                Self::roaming_invoice(roaming_invoice_id)
+                    .filter(|invoice| invoice.invoice_status == InvoiceStatus::Billed)
+                    .map(|invoice| (roaming_invoice_id, invoice))
+            });
+            let (roaming_invoice_id, invoice) = match billed_invoice {
+                Some((roaming_invoice_id, mut invoice)) => {
+                    Self::insert_packet_bundle_invoices(&line_items, roaming_invoice_id);
+                    // The added line items get the full dispute window, so the invoice is charged when it ends
+                    if charge_at_block > invoice.invoice_charge_at_block {
+                        invoice.invoice_dispute_window_ends_at_block = dispute_window_ends_at_block;
+                        invoice.invoice_charge_at_block = charge_at_block;
+                        <RoamingInvoiceCharges<T>>::append(charge_at_block, roaming_invoice_id);
+                    }
+                    invoice.invoice_line_items.extend(line_items);
+                    invoice.invoice_total_amount = invoice.invoice_total_amount.saturating_add(total_amount);
+                    (roaming_invoice_id, invoice)
+                }
+                None => {
+                    let roaming_invoice_id = match Self::next_roaming_invoice_id() {
+                        Ok(value) => value,
+                        Err(_) => break,
+                    };
+                    Self::insert_packet_bundle_invoices(&line_items, roaming_invoice_id);
+                    <RoamingInvoicesCount<T>>::put(roaming_invoice_id + One::one());
+                    <RoamingInvoicePeriods<T>>::insert(period, roaming_invoice_id);
+                    <RoamingInvoiceCharges<T>>::append(charge_at_block, roaming_invoice_id);
+                    (
+                        roaming_invoice_id,
+                        RoamingInvoice {
+                            invoice_home_network_id: home_roaming_network_id,
+                            invoice_visited_network_id: visited_roaming_network_id,
+                            invoice_line_items: line_items,
+                            invoice_total_amount: total_amount,
+                            invoice_billed_at_block: current_block_number,
+                            invoice_dispute_window_ends_at_block: dispute_window_ends_at_block,
+                            invoice_charge_at_block: charge_at_block,
+                            invoice_status: InvoiceStatus::Billed,
+                        },
+                    )
+                }
+            };
+            let invoice_total_amount = invoice.invoice_total_amount;
+            let invoice_charge_at_block = invoice.invoice_charge_at_block;
+            <RoamingInvoices<T>>::insert(roaming_invoice_id, invoice);
+
+            Self::deposit_event(RawEvent::InvoiceBilled(
+                roaming_invoice_id,
+                home_roaming_network_id,
+                visited_roaming_network_id,
+                invoice_total_amount,
+                invoice_charge_at_block,
+            ));
+        }
+
+        processed_packet_bundles_count
+    }
+
+    fn insert_packet_bundle_invoices(
+        line_items: &[RoamingInvoiceLineItem<
+            T::RoamingPacketBundleIndex,
+            T::RoamingNetworkServerIndex,
+            BalanceOf<T>,
+        >],
+        roaming_invoice_id: T::RoamingInvoiceIndex,
+    ) {
+        for line_item in line_items {
+            <RoamingPacketBundleInvoices<T>>::insert(
+                (line_item.line_item_packet_bundle_id, line_item.line_item_network_server_id),
+                roaming_invoice_id,
+            );
+        }
+    }
+
+    /// Charge the line items of an invoice whose packet bundles have not been settled in the meantime.
+    /// Invoices with disputed line items are left to be charged when the last dispute is resolved, and invoices whose
+    /// charging point was moved by line items billed later are left to be charged at their new charging point. The
+    /// packet bundles of an invoice that cannot be charged are queued to be billed again at a later billing point.
+    /// Returns the number of line items of the invoice.
+    fn charge_invoice(roaming_invoice_id: T::RoamingInvoiceIndex) -> u32 {
+        let mut invoice = match Self::roaming_invoice(roaming_invoice_id) {
+            Some(value) => value,
+            None => return 0,
+        };
+        let line_items_count = invoice.invoice_line_items.len() as u32;
+        if invoice.invoice_status != InvoiceStatus::Billed {
+            debug::info!("Not charging roaming invoice {:?} with status {:?}", roaming_invoice_id, invoice.invoice_status);
+            return line_items_count;
+        }
+        if invoice.invoice_charge_at_block > <frame_system::Module<T>>::block_number() {
+            debug::info!("Not charging roaming invoice {:?} before its charging point", roaming_invoice_id);
+            return line_items_count;
+        }
+
+        let unsettled_line_items: Vec<_> = invoice
+            .invoice_line_items
+            .iter()
+            .filter(|line_item| {
+                <roaming_packet_bundles::Module<T>>::roaming_packet_bundle_settlement(
+                    line_item.line_item_packet_bundle_id,
+                    line_item.line_item_network_server_id,
+                )
+                .is_none()
+            })
+            .cloned()
+            .collect();
+        let charge_amount = unsettled_line_items
+            .iter()
+            .fold(Zero::zero(), |total: BalanceOf<T>, line_item| total.saturating_add(line_item.line_item_amount));
+
+        match Self::pay_invoice_line_items(
+            invoice.invoice_home_network_id,
+            invoice.invoice_visited_network_id,
+            &unsettled_line_items,
+            charge_amount,
+        ) {
+            Ok(()) => {
+                invoice.invoice_status = InvoiceStatus::Charged;
+                Self::deposit_event(RawEvent::InvoiceCharged(roaming_invoice_id, charge_amount));
+            }
+            Err(e) => {
+                debug::info!("Unable to charge invoice {:?}: {:?}", roaming_invoice_id, e);
+                invoice.invoice_status = InvoiceStatus::Unpaid;
+                for line_item in unsettled_line_items {
+                    <RoamingPacketBundleInvoices<T>>::remove((
+                        line_item.line_item_packet_bundle_id,
+                        line_item.line_item_network_server_id,
+                    ));
+                    <roaming_packet_bundles::Module<T>>::requeue_unbilled_packet_bundle(
+                        line_item.line_item_network_server_id,
+                        line_item.line_item_packet_bundle_id,
+                    );
+                }
+                Self::deposit_event(RawEvent::InvoiceUnpaid(roaming_invoice_id, charge_amount));
+            }
+        }
+        <RoamingInvoices<T>>::insert(roaming_invoice_id, invoice);
+
+        line_items_count
+    }
+
+    #[transactional]
+    fn pay_invoice_line_items(
+        home_roaming_network_id: T::RoamingNetworkIndex,
+        visited_roaming_network_id: T::RoamingNetworkIndex,
+        line_items: &[RoamingInvoiceLineItem<
+            T::RoamingPacketBundleIndex,
+            T::RoamingNetworkServerIndex,
+            BalanceOf<T>,
+        >],
+        charge_amount: BalanceOf<T>,
+    ) -> DispatchResult {
+        let home_operator_account_id =
+            <roaming_packet_bundles::Module<T>>::network_operator_account(home_roaming_network_id)?;
+        let visited_operator_account_id =
+            <roaming_packet_bundles::Module<T>>::network_operator_account(visited_roaming_network_id)?;
+
+        <roaming_packet_bundles::Module<T>>::pay_settlement_amount(
+            home_roaming_network_id,
+            visited_roaming_network_id,
+            &home_operator_account_id,
+            &visited_operator_account_id,
+            charge_amount,
+        )?;
+
+        for line_item in line_items {
+            <roaming_packet_bundles::Module<T>>::insert_packet_bundle_settlement(
+                line_item.line_item_packet_bundle_id,
+                line_item.line_item_network_server_id,
+                line_item.line_item_amount,
+                home_operator_account_id.clone(),
+                visited_operator_account_id.clone(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Move the invoice of each billed roaming packet bundle under the network server that received the billed
+    /// packets, taken from the line items of each invoice
+    fn migrate_to_packet_bundle_receiver_invoices() -> Weight {
+        let roaming_invoices_count = Self::roaming_invoices_count();
+        let mut migrated_count: Weight = 0;
+        let mut line_items_count: Weight = 0;
+
+        let mut roaming_invoice_id: T::RoamingInvoiceIndex = Zero::zero();
+        while roaming_invoice_id < roaming_invoices_count {
+            if let Some(invoice) = Self::roaming_invoice(roaming_invoice_id) {
+                for line_item in invoice.invoice_line_items {
+                    take_storage_value::<T::RoamingInvoiceIndex>(
+                        b"RoamingInvoices",
+                        b"RoamingPacketBundleInvoices",
+                        &blake2_256(&line_item.line_item_packet_bundle_id.encode()),
+                    );
+                    <RoamingPacketBundleInvoices<T>>::insert(
+                        (line_item.line_item_packet_bundle_id, line_item.line_item_network_server_id),
+                        roaming_invoice_id,
+                    );
+                    line_items_count += 1;
+                }
+                migrated_count += 1;
+            }
+            roaming_invoice_id = roaming_invoice_id + One::one();
+        }
+
+        StorageVersion::put(Releases::V2_0_0);
+        debug::info!("Migrated the packet bundle invoices of {:?} roaming invoices to receiver keys", migrated_count);
+
+        T::DbWeight::get().reads_writes(2 + migrated_count + line_items_count, 1 + line_items_count * 2)
+    }
+
+    fn next_roaming_invoice_id() -> Result<T::RoamingInvoiceIndex, DispatchError> {
+        let roaming_invoice_id = Self::roaming_invoices_count();
+        if roaming_invoice_id == <T::RoamingInvoiceIndex as Bounded>::max_value() {
+            return Err(DispatchError::Other("RoamingInvoices count overflow"));
+        }
+        Ok(roaming_invoice_id)
+    }
+}
+
+/// A packet bundle receiver is billed once it is a line item of an invoice
+impl<T: Config> RoamingPacketBundleBilling<T::RoamingPacketBundleIndex, T::RoamingNetworkServerIndex> for Module<T> {
+    fn is_billed(
+        roaming_packet_bundle_id: T::RoamingPacketBundleIndex,
+        roaming_network_server_id: T::RoamingNetworkServerIndex,
+    ) -> bool {
+        Self::roaming_packet_bundle_invoice((roaming_packet_bundle_id, roaming_network_server_id)).is_some()
+    }
+}
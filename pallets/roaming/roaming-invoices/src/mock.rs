@@ -0,0 +1,393 @@
+// Creating mock runtime here
+
+use crate::{
+    GenesisConfig,
+    Module,
+    Config,
+};
+
+use frame_support::{
+    parameter_types,
+    weights::{
+        IdentityFee,
+        Weight,
+    },
+};
+
+use sp_core::H256;
+use sp_runtime::{
+    testing::Header,
+    traits::{
+        BlakeTwo256,
+        IdentityLookup,
+    },
+    Perbill,
+    Permill,
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+frame_support::construct_runtime!(
+    pub enum Test where
+        Block = Block,
+        NodeBlock = Block,
+        UncheckedExtrinsic = UncheckedExtrinsic,
+    {
+        System: frame_system::{Module, Call, Config, Storage, Event<T>},
+        Balances: pallet_balances::{Module, Call, Storage, Config<T>, Event<T>},
+        TransactionPayment: pallet_transaction_payment::{Module, Storage},
+        RandomnessCollectiveFlip: pallet_randomness_collective_flip::{Module, Call, Storage},
+    }
+);
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+}
+impl frame_system::Config for Test {
+    type AccountData = pallet_balances::AccountData<u64>;
+    type AccountId = u64;
+    type BaseCallFilter = ();
+    type BlockHashCount = BlockHashCount;
+    type BlockNumber = u64;
+    type BlockLength = ();
+    type BlockWeights = ();
+    type Call = Call;
+    type DbWeight = ();
+    type Event = ();
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type Header = Header;
+    type Index = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type OnKilledAccount = ();
+    type OnNewAccount = ();
+    type Origin = Origin;
+    type PalletInfo = PalletInfo;
+    type SS58Prefix = ();
+    type SystemWeightInfo = ();
+    type Version = ();
+}
+parameter_types! {
+    pub const ExistentialDeposit: u64 = 1;
+}
+impl pallet_balances::Config for Test {
+    type AccountStore = System;
+    type Balance = u64;
+    type DustRemoval = ();
+    type Event = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type MaxLocks = ();
+    type WeightInfo = ();
+}
+parameter_types! {
+    pub const TransactionByteFee: u64 = 1;
+}
+impl pallet_transaction_payment::Config for Test {
+    type FeeMultiplierUpdate = ();
+    type OnChargeTransaction = pallet_transaction_payment::CurrencyAdapter<Balances, ()>;
+    type TransactionByteFee = TransactionByteFee;
+    type WeightToFee = IdentityFee<u64>;
+}
+impl roaming_registry::Config<roaming_registry::Instance1> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+impl roaming_operators::Config for Test {
+    type Currency = Balances;
+    type Event = ();
+    type RemovalPolicy = ();
+    type RoamingOperatorChildren = ();
+    type RoamingOperatorIndex = u64;
+    type RoamingOperatorRegistry = RoamingOperatorRegistry;
+}
+impl roaming_registry::Config<roaming_registry::Instance2> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+impl roaming_networks::Config for Test {
+    type Event = ();
+    type RoamingNetworkChildren = ();
+    type RoamingNetworkIndex = u64;
+    type RoamingNetworkRegistry = RoamingNetworkRegistry;
+}
+impl roaming_registry::Config<roaming_registry::Instance4> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+impl roaming_network_servers::Config for Test {
+    type Event = ();
+    type RoamingNetworkServerChildren = ();
+    type RoamingNetworkServerIndex = u64;
+    type RoamingNetworkServerRegistry = RoamingNetworkServerRegistry;
+}
+impl roaming_registry::Config<roaming_registry::Instance3> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+impl roaming_organizations::Config for Test {
+    type Event = ();
+    type RoamingOrganizationChildren = ();
+    type RoamingOrganizationIndex = u64;
+    type RoamingOrganizationRegistry = RoamingOrganizationRegistry;
+}
+impl roaming_registry::Config<roaming_registry::Instance5> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+impl roaming_devices::Config for Test {
+    type Event = ();
+    type MaxRoamingPolicyNetworks = ();
+    type RoamingDeviceChildren = ();
+    type RoamingDeviceIndex = u64;
+    type RoamingDeviceRegistry = RoamingDeviceRegistry;
+}
+impl roaming_registry::Config<roaming_registry::Instance8> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+parameter_types! {
+    pub const MaxSubscriptionRenewalsPerBlock: u32 = 2;
+    pub const SubscriptionBillingPeriod: u64 = 10;
+}
+impl roaming_accounting_policies::Config for Test {
+    type Event = ();
+    type MaxSubscriptionRenewalsPerBlock = MaxSubscriptionRenewalsPerBlock;
+    type RoamingAccountingPolicyChildren = ();
+    type RoamingAccountingPolicyDownlinkFeeFactor = u32;
+    type RoamingAccountingPolicyIndex = u64;
+    type RoamingAccountingPolicyRegistry = RoamingAccountingPolicyRegistry;
+    type RoamingAccountingPolicyUplinkFeeFactor = u32;
+    type SubscriptionBillingPeriod = SubscriptionBillingPeriod;
+}
+impl roaming_registry::Config<roaming_registry::Instance9> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+impl roaming_agreement_policies::Config for Test {
+    type Currency = Balances;
+    type Event = ();
+    type RoamingAgreementPolicyActivationType = Vec<u8>;
+    type RoamingAgreementPolicyIndex = u64;
+    type RoamingAgreementPolicyRegistry = RoamingAgreementPolicyRegistry;
+    type RoamingAgreementSettlements = RoamingPacketBundleModule;
+}
+impl roaming_registry::Config<roaming_registry::Instance10> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+impl roaming_network_profiles::Config for Test {
+    type Event = ();
+    type RoamingNetworkProfileIndex = u64;
+    type RoamingNetworkProfileRegistry = RoamingNetworkProfileRegistry;
+}
+parameter_types! {
+    pub const MaxSessionDuration: u64 = 100;
+    pub const MaxSessionExpiriesPerBlock: u32 = 2;
+    pub const MaxSessionExpiryDelay: u32 = 2;
+}
+impl roaming_registry::Config<roaming_registry::Instance7> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+impl roaming_service_profiles::Config for Test {
+    type Event = ();
+    type RoamingServiceProfileDownlinkRate = u32;
+    type RoamingServiceProfileIndex = u64;
+    type RoamingServiceProfileRegistry = RoamingServiceProfileRegistry;
+    type RoamingServiceProfileUplinkRate = u32;
+}
+impl roaming_registry::Config<roaming_registry::Instance12> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+impl roaming_sessions::Config for Test {
+    type Event = ();
+    type MaxSessionDuration = MaxSessionDuration;
+    type MaxSessionExpiriesPerBlock = MaxSessionExpiriesPerBlock;
+    type MaxSessionExpiryDelay = MaxSessionExpiryDelay;
+    type RoamingSessionChildren = ();
+    type RoamingSessionIndex = u64;
+    type RoamingSessionRegistry = RoamingSessionRegistry;
+}
+parameter_types! {
+    pub const MaxBillingPoliciesPerBlock: u32 = 2;
+}
+impl roaming_registry::Config<roaming_registry::Instance13> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+impl roaming_billing_policies::Config for Test {
+    type Event = ();
+    type MaxBillingPoliciesPerBlock = MaxBillingPoliciesPerBlock;
+    type RoamingBillingPolicyIndex = u64;
+    type RoamingBillingPolicyRegistry = RoamingBillingPolicyRegistry;
+}
+impl roaming_registry::Config<roaming_registry::Instance14> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+impl roaming_charging_policies::Config for Test {
+    type Event = ();
+    type RoamingChargingPolicyIndex = u64;
+    type RoamingChargingPolicyRegistry = RoamingChargingPolicyRegistry;
+}
+parameter_types! {
+    pub const PacketBundleSettlementPeriod: u64 = 10;
+    pub const PacketCountTolerance: Permill = Permill::from_percent(20);
+    pub const PacketCountChallengeBond: u64 = 5;
+    pub const RateLimitPeriod: u64 = 10;
+    pub const RateOveragePenaltyMultiplier: u32 = 3;
+}
+impl roaming_registry::Config<roaming_registry::Instance15> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+impl roaming_packet_bundles::Config for Test {
+    type Event = ();
+    type PacketBundleSettlementPeriod = PacketBundleSettlementPeriod;
+    type PacketCountArbitrationOrigin = frame_system::EnsureRoot<u64>;
+    type PacketCountChallengeBond = PacketCountChallengeBond;
+    type PacketCountTolerance = PacketCountTolerance;
+    type RateLimitPeriod = RateLimitPeriod;
+    type RateOveragePenaltyMultiplier = RateOveragePenaltyMultiplier;
+    type RoamingPacketBundleBilling = RoamingInvoiceModule;
+    type RoamingPacketBundleExternalDataStorageHash = H256;
+    type RoamingPacketBundleIndex = u64;
+    type RoamingPacketBundleReceivedAtHome = bool;
+    type RoamingPacketBundleReceivedPacketsCount = u64;
+    type RoamingPacketBundleReceivedPacketsOkCount = u64;
+    type RoamingPacketBundleRegistry = RoamingPacketBundleRegistry;
+}
+parameter_types! {
+    pub const InvoiceDisputeWindow: u64 = 3;
+    pub const InvoiceDisputeResolutionPeriod: u64 = 3;
+    pub const MaxBilledPacketBundlesPerBlock: u32 = 2;
+    pub const MaxInvoiceChargesPerBlock: u32 = 1;
+}
+impl Config for Test {
+    type Event = ();
+    type InvoiceArbitrationOrigin = frame_system::EnsureRoot<u64>;
+    type InvoiceDisputeResolutionPeriod = InvoiceDisputeResolutionPeriod;
+    type InvoiceDisputeWindow = InvoiceDisputeWindow;
+    type MaxBilledPacketBundlesPerBlock = MaxBilledPacketBundlesPerBlock;
+    type MaxInvoiceChargesPerBlock = MaxInvoiceChargesPerBlock;
+    type RoamingInvoiceIndex = u64;
+}
+
+pub type RoamingInvoiceModule = Module<Test>;
+pub type RoamingPacketBundleModule = roaming_packet_bundles::Module<Test>;
+pub type RoamingOperatorModule = roaming_operators::Module<Test>;
+pub type RoamingNetworkModule = roaming_networks::Module<Test>;
+pub type RoamingNetworkServerModule = roaming_network_servers::Module<Test>;
+pub type RoamingDeviceModule = roaming_devices::Module<Test>;
+pub type RoamingAccountingPolicyModule = roaming_accounting_policies::Module<Test>;
+pub type RoamingAgreementPolicyModule = roaming_agreement_policies::Module<Test>;
+pub type RoamingBillingPolicyModule = roaming_billing_policies::Module<Test>;
+pub type RoamingChargingPolicyModule = roaming_charging_policies::Module<Test>;
+pub type RoamingOperatorRegistry = roaming_registry::Module<Test, roaming_registry::Instance1>;
+pub type RoamingNetworkRegistry = roaming_registry::Module<Test, roaming_registry::Instance2>;
+pub type RoamingNetworkServerRegistry = roaming_registry::Module<Test, roaming_registry::Instance4>;
+pub type RoamingOrganizationRegistry = roaming_registry::Module<Test, roaming_registry::Instance3>;
+pub type RoamingDeviceRegistry = roaming_registry::Module<Test, roaming_registry::Instance5>;
+pub type RoamingAccountingPolicyRegistry = roaming_registry::Module<Test, roaming_registry::Instance8>;
+pub type RoamingAgreementPolicyRegistry = roaming_registry::Module<Test, roaming_registry::Instance9>;
+pub type RoamingNetworkProfileRegistry = roaming_registry::Module<Test, roaming_registry::Instance10>;
+pub type RoamingServiceProfileRegistry = roaming_registry::Module<Test, roaming_registry::Instance7>;
+pub type RoamingSessionRegistry = roaming_registry::Module<Test, roaming_registry::Instance12>;
+pub type RoamingBillingPolicyRegistry = roaming_registry::Module<Test, roaming_registry::Instance13>;
+pub type RoamingChargingPolicyRegistry = roaming_registry::Module<Test, roaming_registry::Instance14>;
+pub type RoamingPacketBundleRegistry = roaming_registry::Module<Test, roaming_registry::Instance15>;
+
+// This function basically just builds a genesis storage key/value store according to
+// our desired mockup.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+    pallet_balances::GenesisConfig::<Test> {
+        balances: vec![(1, 10), (2, 20), (3, 30), (4, 40), (5, 50), (6, 60)],
+    }
+    .assimilate_storage(&mut t)
+    .unwrap();
+    GenesisConfig::default().assimilate_storage::<Test>(&mut t).unwrap();
+    let mut ext = sp_io::TestExternalities::new(t);
+    ext.execute_with(|| System::set_block_number(1));
+    ext
+}
@@ -0,0 +1,598 @@
+// Tests to be written here
+
+use super::*;
+use crate::mock::*;
+use frame_support::{
+    assert_noop,
+    assert_ok,
+    storage::migration::{
+        get_storage_value,
+        put_storage_value,
+    },
+    traits::{
+        OnInitialize,
+        OnRuntimeUpgrade,
+    },
+};
+use roaming_packet_bundles::{
+    RoamingNetworkServerUnbilledPacketBundles,
+    RoamingPacketBundleReceiver,
+    RoamingPacketBundleReceivers,
+    RoamingPacketBundleSettlement,
+};
+use sp_core::H256;
+use sp_io::hashing::blake2_256;
+
+// Setup a device of home network 0 (operator 0 owned by 1) whose session 0 has packet bundle 0 received by
+// network server 1 of visited network 1 (operator 1 owned by 2) with an uplink fee factor of 3
+fn setup_packet_bundle_received_by_visited_network(packets_ok_count: u64) {
+    assert_ok!(RoamingOperatorRegistry::create(Origin::signed(1)));
+    assert_ok!(RoamingOperatorRegistry::create(Origin::signed(2)));
+    assert_ok!(RoamingNetworkRegistry::create(Origin::signed(1)));
+    assert_ok!(RoamingNetworkRegistry::create(Origin::signed(2)));
+    assert_ok!(RoamingNetworkModule::assign_network_to_operator(Origin::signed(1), 0, 0));
+    assert_ok!(RoamingNetworkModule::assign_network_to_operator(Origin::signed(2), 1, 1));
+    assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(1)));
+    assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(2)));
+    assert_ok!(RoamingNetworkServerModule::assign_network_server_to_network(Origin::signed(1), 0, 0));
+    assert_ok!(RoamingNetworkServerModule::assign_network_server_to_network(Origin::signed(2), 1, 1));
+    assert_ok!(RoamingDeviceRegistry::create(Origin::signed(1)));
+    assert_ok!(RoamingDeviceModule::assign_device_to_network_server(Origin::signed(1), 0, 0));
+    assert_ok!(RoamingAccountingPolicyRegistry::create(Origin::signed(2)));
+    assert_ok!(RoamingAccountingPolicyModule::set_config(Origin::signed(2), 0, None, None, Some(3), None));
+    assert_ok!(RoamingAccountingPolicyModule::assign_accounting_policy_to_network(Origin::signed(2), 0, 1));
+
+    assert_ok!(RoamingSessionRegistry::create(Origin::signed(1)));
+    <roaming_sessions::RoamingSessionDevices<Test>>::insert(0, 0);
+    assert_ok!(RoamingPacketBundleRegistry::create(Origin::signed(1)));
+    assert_ok!(RoamingPacketBundleModule::assign_packet_bundle_to_session(Origin::signed(1), 0, 0));
+    <RoamingPacketBundleReceivers<Test>>::insert((0, 1), RoamingPacketBundleReceiver {
+        packet_bundle_received_at_home: false,
+        packet_bundle_received_packets_count: packets_ok_count,
+        packet_bundle_received_packets_ok_count: packets_ok_count,
+        packet_bundle_received_started_at_block: 1,
+        packet_bundle_received_ended_at_block: 1,
+        packet_bundle_external_data_storage_hash: H256::default(),
+    });
+    <RoamingNetworkServerUnbilledPacketBundles<Test>>::append(1, 0);
+}
+
+// Setup billing policy 0 of visited network 1 with its first billing point at block 5 and a frequency of 10 blocks,
+// and charging policy 0 of visited network 1 that charges 2 blocks after billing
+fn setup_billing_and_charging_policies_of_visited_network() {
+    assert_ok!(RoamingBillingPolicyRegistry::create(Origin::signed(2)));
+    assert_ok!(RoamingBillingPolicyModule::set_config(Origin::signed(2), 0, Some(5), Some(10)));
+    assert_ok!(RoamingBillingPolicyModule::assign_billing_policy_to_network(Origin::signed(2), 0, 1));
+    assert_ok!(RoamingChargingPolicyRegistry::create(Origin::signed(2)));
+    assert_ok!(RoamingChargingPolicyModule::set_config(Origin::signed(2), 0, None, Some(2)));
+    assert_ok!(RoamingChargingPolicyModule::assign_charging_policy_to_network(Origin::signed(2), 0, 1));
+}
+
+// Bill invoice 0 at block 5, whose dispute window ends and which is charged at block 8
+fn setup_invoice_billed_at_block_5(packets_ok_count: u64) {
+    setup_packet_bundle_received_by_visited_network(packets_ok_count);
+    setup_billing_and_charging_policies_of_visited_network();
+    System::set_block_number(5);
+    RoamingInvoiceModule::on_initialize(5);
+}
+
+#[test]
+fn billing_point_invoices_and_charges_unsettled_packet_bundles() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        setup_packet_bundle_received_by_visited_network(2);
+        setup_billing_and_charging_policies_of_visited_network();
+        // Call Functions
+        System::set_block_number(5);
+        RoamingInvoiceModule::on_initialize(5);
+        // Verify Storage
+        assert_eq!(
+            RoamingInvoiceModule::roaming_invoice(0),
+            Some(RoamingInvoice {
+                invoice_home_network_id: 0,
+                invoice_visited_network_id: 1,
+                invoice_line_items: vec![RoamingInvoiceLineItem {
+                    line_item_packet_bundle_id: 0,
+                    line_item_network_server_id: 1,
+                    line_item_amount: 6,
+                    line_item_status: LineItemStatus::Billed,
+                }],
+                invoice_total_amount: 6,
+                invoice_billed_at_block: 5,
+                invoice_dispute_window_ends_at_block: 8,
+                invoice_charge_at_block: 8,
+                invoice_status: InvoiceStatus::Billed,
+            })
+        );
+        assert_eq!(RoamingInvoiceModule::roaming_invoice_period((0, 1, 5)), Some(0));
+        assert_eq!(RoamingInvoiceModule::roaming_packet_bundle_invoice((0, 1)), Some(0));
+        assert!(RoamingPacketBundleModule::roaming_network_server_unbilled_packet_bundles(1).is_empty());
+        assert_eq!(RoamingInvoiceModule::roaming_invoice_charges(8), vec![0]);
+        assert_eq!(
+            RoamingBillingPolicyModule::roaming_billing_policy_settings(0).map(|s| s.policy_next_billing_at_block),
+            Some(15)
+        );
+        assert_eq!(RoamingBillingPolicyModule::roaming_billing_policy_schedule(15), vec![0]);
+        assert_eq!(
+            RoamingChargingPolicyModule::roaming_charging_policy_settings(0).map(|s| s.policy_next_charging_at_block),
+            Some(7)
+        );
+
+        // Call Functions
+        System::set_block_number(8);
+        RoamingInvoiceModule::on_initialize(8);
+        // Verify Storage
+        assert_eq!(
+            RoamingInvoiceModule::roaming_invoice(0).map(|invoice| invoice.invoice_status),
+            Some(InvoiceStatus::Charged)
+        );
+        assert_eq!(
+            RoamingPacketBundleModule::roaming_packet_bundle_settlement(0, 1),
+            Some(RoamingPacketBundleSettlement {
+                settlement_network_server_id: 1,
+                settlement_amount: 6,
+                settlement_settled_at_block: 8,
+            })
+        );
+        assert_eq!(Balances::free_balance(1), 4);
+        assert_eq!(Balances::free_balance(2), 26);
+    });
+}
+
+#[test]
+fn billing_point_skips_settled_packet_bundles() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        setup_packet_bundle_received_by_visited_network(2);
+        setup_billing_and_charging_policies_of_visited_network();
+        assert_ok!(RoamingPacketBundleModule::settle_packet_bundle(Origin::signed(1), 0, 1));
+        // Call Functions
+        System::set_block_number(5);
+        RoamingInvoiceModule::on_initialize(5);
+        // Verify Storage
+        assert_eq!(RoamingInvoiceModule::roaming_invoice(0), None);
+        assert_eq!(RoamingInvoiceModule::roaming_invoices_count(), 0);
+        assert_eq!(Balances::free_balance(1), 4);
+    });
+}
+
+#[test]
+fn billing_point_invoices_unsettled_receivers_of_a_settled_packet_bundle() {
+    new_test_ext().execute_with(|| {
+        // Setup - network server 2 of visited network 1 also received packet bundle 0, and only the packets
+        // received by network server 1 were settled
+        setup_packet_bundle_received_by_visited_network(2);
+        assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(2)));
+        assert_ok!(RoamingNetworkServerModule::assign_network_server_to_network(Origin::signed(2), 2, 1));
+        <RoamingPacketBundleReceivers<Test>>::insert((0, 2), RoamingPacketBundleReceiver {
+            packet_bundle_received_at_home: false,
+            packet_bundle_received_packets_count: 1,
+            packet_bundle_received_packets_ok_count: 1,
+            packet_bundle_received_started_at_block: 1,
+            packet_bundle_received_ended_at_block: 1,
+            packet_bundle_external_data_storage_hash: H256::default(),
+        });
+        <RoamingNetworkServerUnbilledPacketBundles<Test>>::append(2, 0);
+        setup_billing_and_charging_policies_of_visited_network();
+        assert_ok!(RoamingPacketBundleModule::settle_packet_bundle(Origin::signed(1), 0, 1));
+        // Call Functions
+        System::set_block_number(5);
+        RoamingInvoiceModule::on_initialize(5);
+        // Verify Storage
+        assert_eq!(
+            RoamingInvoiceModule::roaming_invoice(0).map(|invoice| invoice.invoice_line_items),
+            Some(vec![RoamingInvoiceLineItem {
+                line_item_packet_bundle_id: 0,
+                line_item_network_server_id: 2,
+                line_item_amount: 3,
+                line_item_status: LineItemStatus::Billed,
+            }])
+        );
+        assert_eq!(RoamingInvoiceModule::roaming_packet_bundle_invoice((0, 1)), None);
+        assert_eq!(RoamingInvoiceModule::roaming_packet_bundle_invoice((0, 2)), Some(0));
+
+        // Call Functions
+        System::set_block_number(8);
+        RoamingInvoiceModule::on_initialize(8);
+        // Verify Storage
+        assert_eq!(
+            RoamingPacketBundleModule::roaming_packet_bundle_settlement(0, 2)
+                .map(|settlement| settlement.settlement_amount),
+            Some(3)
+        );
+        assert_eq!(Balances::free_balance(1), 1);
+        assert_eq!(Balances::free_balance(2), 29);
+    });
+}
+
+#[test]
+fn billing_point_continues_billing_packet_bundles_over_the_block_limit_in_the_next_block() {
+    new_test_ext().execute_with(|| {
+        // Setup - network server 1 of visited network 1 also received packet bundles 1 and 2, which is more than
+        // the 2 packet bundles that may be billed in a block
+        setup_packet_bundle_received_by_visited_network(1);
+        for roaming_packet_bundle_id in 1..3 {
+            assert_ok!(RoamingPacketBundleRegistry::create(Origin::signed(1)));
+            assert_ok!(RoamingPacketBundleModule::assign_packet_bundle_to_session(
+                Origin::signed(1),
+                roaming_packet_bundle_id,
+                0
+            ));
+            <RoamingPacketBundleReceivers<Test>>::insert((roaming_packet_bundle_id, 1), RoamingPacketBundleReceiver {
+                packet_bundle_received_at_home: false,
+                packet_bundle_received_packets_count: 1,
+                packet_bundle_received_packets_ok_count: 1,
+                packet_bundle_received_started_at_block: 1,
+                packet_bundle_received_ended_at_block: 1,
+                packet_bundle_external_data_storage_hash: H256::default(),
+            });
+            <RoamingNetworkServerUnbilledPacketBundles<Test>>::append(1, roaming_packet_bundle_id);
+        }
+        setup_billing_and_charging_policies_of_visited_network();
+        // Call Functions
+        System::set_block_number(5);
+        RoamingInvoiceModule::on_initialize(5);
+        // Verify Storage
+        assert_eq!(
+            RoamingInvoiceModule::roaming_invoice(0)
+                .map(|invoice| (invoice.invoice_total_amount, invoice.invoice_line_items.len())),
+            Some((6, 2))
+        );
+        assert_eq!(RoamingPacketBundleModule::roaming_network_server_unbilled_packet_bundles(1), vec![2]);
+        assert_eq!(
+            RoamingInvoiceModule::roaming_invoice_billings(),
+            vec![RoamingInvoiceBilling {
+                billing_policy_id: 0,
+                billing_visited_network_id: 1,
+                billing_period_started_at_block: 5,
+                billing_charge_at_block: 7,
+                billing_network_servers: vec![(1, 1)],
+            }]
+        );
+
+        // Call Functions
+        System::set_block_number(6);
+        RoamingInvoiceModule::on_initialize(6);
+        // Verify Storage - packet bundle 2 is added to the invoice of the billing point, whose charging point moves to
+        // the end of the dispute window of the added line item
+        assert_eq!(RoamingInvoiceModule::roaming_invoice(1), None);
+        assert_eq!(
+            RoamingInvoiceModule::roaming_invoice(0).map(|invoice| {
+                (
+                    invoice.invoice_line_items.last().cloned(),
+                    invoice.invoice_total_amount,
+                    invoice.invoice_billed_at_block,
+                    invoice.invoice_dispute_window_ends_at_block,
+                    invoice.invoice_charge_at_block,
+                )
+            }),
+            Some((
+                Some(RoamingInvoiceLineItem {
+                    line_item_packet_bundle_id: 2,
+                    line_item_network_server_id: 1,
+                    line_item_amount: 3,
+                    line_item_status: LineItemStatus::Billed,
+                }),
+                9,
+                5,
+                9,
+                9
+            ))
+        );
+        assert_eq!(RoamingInvoiceModule::roaming_invoice_period((0, 1, 5)), Some(0));
+        assert_eq!(RoamingInvoiceModule::roaming_invoice_period((0, 1, 6)), None);
+        assert_eq!(RoamingInvoiceModule::roaming_packet_bundle_invoice((2, 1)), Some(0));
+        assert!(RoamingPacketBundleModule::roaming_network_server_unbilled_packet_bundles(1).is_empty());
+        assert!(RoamingInvoiceModule::roaming_invoice_billings().is_empty());
+
+        // Call Functions
+        System::set_block_number(8);
+        RoamingInvoiceModule::on_initialize(8);
+        // Verify Storage
+        assert_eq!(
+            RoamingInvoiceModule::roaming_invoice(0).map(|invoice| invoice.invoice_status),
+            Some(InvoiceStatus::Billed)
+        );
+
+        // Call Functions
+        System::set_block_number(9);
+        RoamingInvoiceModule::on_initialize(9);
+        // Verify Storage
+        assert_eq!(
+            RoamingInvoiceModule::roaming_invoice(0).map(|invoice| invoice.invoice_status),
+            Some(InvoiceStatus::Charged)
+        );
+        assert_eq!(Balances::free_balance(1), 1);
+        assert_eq!(Balances::free_balance(2), 29);
+    });
+}
+
+#[test]
+fn billing_point_requeues_packet_bundles_that_cannot_be_billed_yet() {
+    new_test_ext().execute_with(|| {
+        // Setup - visited network 1 has no accounting policy at its first billing point
+        setup_packet_bundle_received_by_visited_network(2);
+        setup_billing_and_charging_policies_of_visited_network();
+        <roaming_accounting_policies::RoamingNetworkAccountingPolicies<Test>>::remove(1);
+        // Call Functions
+        System::set_block_number(5);
+        RoamingInvoiceModule::on_initialize(5);
+        // Verify Storage
+        assert_eq!(RoamingInvoiceModule::roaming_invoice(0), None);
+        assert_eq!(RoamingPacketBundleModule::roaming_network_server_unbilled_packet_bundles(1), vec![0]);
+        assert!(RoamingInvoiceModule::roaming_invoice_billings().is_empty());
+
+        // Call Functions
+        <roaming_accounting_policies::RoamingNetworkAccountingPolicies<Test>>::insert(1, vec![0]);
+        System::set_block_number(15);
+        RoamingInvoiceModule::on_initialize(15);
+        // Verify Storage
+        assert_eq!(
+            RoamingInvoiceModule::roaming_invoice(0)
+                .map(|invoice| (invoice.invoice_total_amount, invoice.invoice_billed_at_block)),
+            Some((6, 15))
+        );
+        assert!(RoamingPacketBundleModule::roaming_network_server_unbilled_packet_bundles(1).is_empty());
+    });
+}
+
+#[test]
+fn set_receiver_refuses_updates_once_packet_bundle_is_billed() {
+    new_test_ext().execute_with(|| {
+        // Setup - 1 is an admin of visited network server 1 as well as the owner of packet bundle 0
+        setup_invoice_billed_at_block_5(2);
+        assert_ok!(RoamingNetworkServerRegistry::grant_admin(Origin::signed(2), 1, 1));
+        // Call Functions
+        assert_noop!(
+            RoamingPacketBundleModule::set_receiver(Origin::signed(1), 0, 1, None, Some(5), Some(5), None, None, None),
+            roaming_packet_bundles::Error::<Test>::PacketBundleAlreadyBilled
+        );
+        // Verify Storage
+        assert_eq!(
+            RoamingPacketBundleModule::roaming_packet_bundle_receivers((0, 1))
+                .map(|receiver| receiver.packet_bundle_received_packets_ok_count),
+            Some(2)
+        );
+    });
+}
+
+#[test]
+fn charging_point_carries_over_charges_over_the_block_limit_to_the_next_block() {
+    new_test_ext().execute_with(|| {
+        // Setup - invoice 0 is charged after invoice 1 at block 8, which is more than the 1 invoice that may be
+        // charged in a block
+        setup_invoice_billed_at_block_5(2);
+        <RoamingInvoiceCharges<Test>>::insert(8, vec![1, 0]);
+        <RoamingInvoiceCharges<Test>>::insert(9, vec![2]);
+        // Call Functions
+        System::set_block_number(8);
+        RoamingInvoiceModule::on_initialize(8);
+        // Verify Storage
+        assert_eq!(
+            RoamingInvoiceModule::roaming_invoice(0).map(|invoice| invoice.invoice_status),
+            Some(InvoiceStatus::Billed)
+        );
+        assert_eq!(RoamingInvoiceModule::roaming_invoice_charges(9), vec![0, 2]);
+
+        // Call Functions
+        System::set_block_number(9);
+        RoamingInvoiceModule::on_initialize(9);
+        // Verify Storage
+        assert_eq!(
+            RoamingInvoiceModule::roaming_invoice(0).map(|invoice| invoice.invoice_status),
+            Some(InvoiceStatus::Charged)
+        );
+        assert_eq!(RoamingInvoiceModule::roaming_invoice_charges(10), vec![2]);
+        assert_eq!(Balances::free_balance(1), 4);
+        assert_eq!(Balances::free_balance(2), 26);
+    });
+}
+
+#[test]
+fn migration_moves_packet_bundle_invoices_to_receiver_keys() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        setup_invoice_billed_at_block_5(2);
+        <RoamingPacketBundleInvoices<Test>>::remove((0, 1));
+        put_storage_value(b"RoamingInvoices", b"RoamingPacketBundleInvoices", &blake2_256(&0u64.encode()), 0u64);
+        StorageVersion::put(Releases::V1_0_0);
+        // Call Functions
+        RoamingInvoiceModule::on_runtime_upgrade();
+        // Verify Storage
+        assert_eq!(RoamingInvoiceModule::storage_version(), Releases::V2_0_0);
+        assert_eq!(RoamingInvoiceModule::roaming_packet_bundle_invoice((0, 1)), Some(0));
+        assert_eq!(
+            get_storage_value::<u64>(b"RoamingInvoices", b"RoamingPacketBundleInvoices", &blake2_256(&0u64.encode())),
+            None
+        );
+    });
+}
+
+#[test]
+fn migration_keeps_packet_bundle_invoices_already_in_receiver_keys() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        setup_invoice_billed_at_block_5(2);
+        StorageVersion::put(Releases::V1_0_0);
+        // Call Functions
+        RoamingInvoiceModule::on_runtime_upgrade();
+        // Verify Storage
+        assert_eq!(RoamingInvoiceModule::storage_version(), Releases::V2_0_0);
+        assert_eq!(RoamingInvoiceModule::roaming_packet_bundle_invoice((0, 1)), Some(0));
+    });
+}
+
+#[test]
+fn charging_an_invoice_the_home_network_cannot_pay_marks_it_unpaid() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        setup_invoice_billed_at_block_5(4);
+        assert_ok!(RoamingAgreementPolicyRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingAgreementPolicyModule::set_config(Origin::signed(1), 0, None, Some(20)));
+        assert_ok!(RoamingAgreementPolicyModule::assign_agreement_policy_to_network(Origin::signed(1), 0, 0));
+        assert_ok!(RoamingAgreementPolicyModule::activate_agreement(Origin::signed(1), 0, 1, 4));
+        // Call Functions
+        System::set_block_number(8);
+        RoamingInvoiceModule::on_initialize(8);
+        // Verify Storage
+        assert_eq!(
+            RoamingInvoiceModule::roaming_invoice(0).map(|invoice| invoice.invoice_status),
+            Some(InvoiceStatus::Unpaid)
+        );
+        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_settlement(0, 1), None);
+        assert_eq!(Balances::reserved_balance(1), 4);
+        assert_eq!(Balances::free_balance(2), 20);
+        // The packet bundle is queued to be billed again at the next billing point
+        assert_eq!(RoamingInvoiceModule::roaming_packet_bundle_invoice((0, 1)), None);
+        assert_eq!(RoamingPacketBundleModule::roaming_network_server_unbilled_packet_bundles(1), vec![0]);
+    });
+}
+
+#[test]
+fn disputed_invoice_is_charged_when_resolved_by_visited_network() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        setup_invoice_billed_at_block_5(2);
+        // Call Functions
+        System::set_block_number(6);
+        assert_ok!(RoamingInvoiceModule::dispute_line_item(Origin::signed(1), 0, 0, 1));
+        System::set_block_number(8);
+        RoamingInvoiceModule::on_initialize(8);
+        // Verify Storage
+        assert_eq!(
+            RoamingInvoiceModule::roaming_invoice(0).map(|invoice| invoice.invoice_status),
+            Some(InvoiceStatus::Disputed)
+        );
+        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_settlement(0, 1), None);
+
+        // Call Functions
+        System::set_block_number(9);
+        assert_ok!(RoamingInvoiceModule::resolve_line_item_dispute(Origin::signed(2), 0, 0, 1, 4));
+        // Verify Storage
+        let invoice = RoamingInvoiceModule::roaming_invoice(0).unwrap();
+        assert_eq!(invoice.invoice_status, InvoiceStatus::Charged);
+        assert_eq!(invoice.invoice_total_amount, 4);
+        assert_eq!(invoice.invoice_line_items[0].line_item_status, LineItemStatus::Resolved);
+        assert_eq!(
+            RoamingPacketBundleModule::roaming_packet_bundle_settlement(0, 1)
+                .map(|settlement| settlement.settlement_amount),
+            Some(4)
+        );
+        assert_eq!(Balances::free_balance(1), 6);
+        assert_eq!(Balances::free_balance(2), 24);
+    });
+}
+
+#[test]
+fn dispute_resolved_by_arbitration_before_charging_point_is_charged_at_charging_point() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        setup_invoice_billed_at_block_5(2);
+        // Call Functions
+        assert_ok!(RoamingInvoiceModule::dispute_line_item(Origin::signed(1), 0, 0, 1));
+        assert_ok!(RoamingInvoiceModule::resolve_line_item_dispute(Origin::root(), 0, 0, 1, 3));
+        // Verify Storage
+        assert_eq!(
+            RoamingInvoiceModule::roaming_invoice(0).map(|invoice| invoice.invoice_status),
+            Some(InvoiceStatus::Billed)
+        );
+        assert_eq!(Balances::free_balance(1), 10);
+
+        // Call Functions
+        System::set_block_number(8);
+        RoamingInvoiceModule::on_initialize(8);
+        // Verify Storage
+        assert_eq!(
+            RoamingInvoiceModule::roaming_invoice(0).map(|invoice| invoice.invoice_status),
+            Some(InvoiceStatus::Charged)
+        );
+        assert_eq!(Balances::free_balance(1), 7);
+        assert_eq!(Balances::free_balance(2), 23);
+    });
+}
+
+#[test]
+fn dispute_line_item_handles_basic_errors() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        setup_invoice_billed_at_block_5(2);
+        // Call Functions
+        assert_noop!(
+            RoamingInvoiceModule::dispute_line_item(Origin::signed(1), 1, 0, 1),
+            Error::<Test>::InvoiceDoesNotExist
+        );
+        assert_noop!(
+            RoamingInvoiceModule::dispute_line_item(Origin::signed(2), 0, 0, 1),
+            Error::<Test>::NotHomeNetworkOperator
+        );
+        assert_noop!(
+            RoamingInvoiceModule::dispute_line_item(Origin::signed(1), 0, 1, 1),
+            Error::<Test>::LineItemDoesNotExist
+        );
+        assert_noop!(
+            RoamingInvoiceModule::dispute_line_item(Origin::signed(1), 0, 0, 2),
+            Error::<Test>::LineItemDoesNotExist
+        );
+        assert_ok!(RoamingInvoiceModule::dispute_line_item(Origin::signed(1), 0, 0, 1));
+        assert_noop!(
+            RoamingInvoiceModule::dispute_line_item(Origin::signed(1), 0, 0, 1),
+            Error::<Test>::LineItemAlreadyDisputed
+        );
+        assert_ok!(RoamingInvoiceModule::resolve_line_item_dispute(Origin::signed(2), 0, 0, 1, 6));
+        System::set_block_number(8);
+        assert_noop!(
+            RoamingInvoiceModule::dispute_line_item(Origin::signed(1), 0, 0, 1),
+            Error::<Test>::DisputeWindowEnded
+        );
+    });
+}
+
+#[test]
+fn resolve_line_item_dispute_handles_basic_errors() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        setup_invoice_billed_at_block_5(2);
+        // Call Functions
+        assert_noop!(
+            RoamingInvoiceModule::resolve_line_item_dispute(Origin::signed(2), 0, 0, 1, 6),
+            Error::<Test>::LineItemNotDisputed
+        );
+        assert_ok!(RoamingInvoiceModule::dispute_line_item(Origin::signed(1), 0, 0, 1));
+        assert_noop!(
+            RoamingInvoiceModule::resolve_line_item_dispute(Origin::signed(1), 0, 0, 1, 6),
+            Error::<Test>::NotVisitedNetworkOperator
+        );
+        assert_noop!(
+            RoamingInvoiceModule::resolve_line_item_dispute(Origin::signed(2), 0, 0, 1, 7),
+            Error::<Test>::ResolvedAmountExceedsLineItem
+        );
+        assert_noop!(
+            RoamingInvoiceModule::resolve_line_item_dispute(Origin::root(), 1, 0, 1, 6),
+            Error::<Test>::InvoiceDoesNotExist
+        );
+    });
+}
+
+#[test]
+fn dispute_pending_after_resolution_period_may_only_be_resolved_by_arbitration() {
+    new_test_ext().execute_with(|| {
+        // Setup - the dispute window of invoice 0 ends at block 8 and its resolution period at block 11
+        setup_invoice_billed_at_block_5(2);
+        System::set_block_number(6);
+        assert_ok!(RoamingInvoiceModule::dispute_line_item(Origin::signed(1), 0, 0, 1));
+        // Call Functions
+        System::set_block_number(11);
+        assert_noop!(
+            RoamingInvoiceModule::resolve_line_item_dispute(Origin::signed(2), 0, 0, 1, 4),
+            Error::<Test>::DisputeResolutionPeriodEnded
+        );
+        assert_ok!(RoamingInvoiceModule::resolve_line_item_dispute(Origin::root(), 0, 0, 1, 4));
+        // Verify Storage
+        assert_eq!(
+            RoamingInvoiceModule::roaming_invoice(0).map(|invoice| invoice.invoice_status),
+            Some(InvoiceStatus::Charged)
+        );
+        assert_eq!(Balances::free_balance(1), 6);
+        assert_eq!(Balances::free_balance(2), 24);
+    });
+}
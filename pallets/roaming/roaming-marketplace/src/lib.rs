@@ -0,0 +1,395 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{
+    Decode,
+    Encode,
+};
+use frame_support::{
+    debug,
+    decl_error,
+    decl_event,
+    decl_module,
+    decl_storage,
+    ensure,
+    traits::{
+        BalanceStatus,
+        Currency,
+        Get,
+        OnUnbalanced,
+        ReservableCurrency,
+    },
+    transactional,
+    weights::Weight,
+    Parameter,
+};
+use frame_system::ensure_signed;
+use roaming_registry::RoamingTradable;
+use sp_runtime::{
+    traits::{
+        Member,
+        Saturating,
+        Zero,
+    },
+    DispatchError,
+    Permill,
+};
+use sp_std::prelude::*; // Imports Vec
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// The module's configuration trait. Each instance of the marketplace trades the roaming entities of one pallet.
+pub trait Config<I: Instance = DefaultInstance>: frame_system::Config {
+    type Event: From<Event<Self, I>> + Into<<Self as frame_system::Config>::Event>;
+    type Currency: ReservableCurrency<Self::AccountId>;
+    /// The index of the roaming entities that are traded
+    type RoamingEntityIndex: Parameter + Member + Copy;
+    /// The pallet that holds the roaming entities that are traded
+    type RoamingEntities: RoamingTradable<Self::AccountId, Self::RoamingEntityIndex>;
+    /// The share of the price of every sale that is paid as a marketplace fee
+    type MarketplaceFee: Get<Permill>;
+    /// Where marketplace fees are paid to, such as the treasury
+    type MarketplaceFeeDestination: OnUnbalanced<NegativeImbalanceOf<Self, I>>;
+    /// The minimum number of blocks that an auction runs for
+    type MinAuctionDuration: Get<Self::BlockNumber>;
+    /// The maximum number of auctions that may end at the same block, since they are all settled in that block
+    type MaxAuctionsEndingPerBlock: Get<u32>;
+}
+
+type BalanceOf<T, I> = <<T as Config<I>>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+type NegativeImbalanceOf<T, I> =
+    <<T as Config<I>>::Currency as Currency<<T as frame_system::Config>::AccountId>>::NegativeImbalance;
+
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+// Generic type parameters - AccountId, Balance, BlockNumber
+pub struct RoamingAuction<U, V, W> {
+    pub auction_seller: U,
+    pub auction_min_bid: V,
+    pub auction_highest_bidder: Option<U>,
+    pub auction_highest_bid: V,
+    pub auction_ends_at_block: W,
+}
+
+decl_event!(
+    pub enum Event<T, I = DefaultInstance> where
+        <T as frame_system::Config>::AccountId,
+        <T as frame_system::Config>::BlockNumber,
+        RoamingEntityIndex = <T as Config<I>>::RoamingEntityIndex,
+        Balance = BalanceOf<T, I>,
+    {
+        /// An offer is made for a roaming entity and its amount is reserved. (buyer, roaming_entity_id, amount)
+        OfferMade(AccountId, RoamingEntityIndex, Balance),
+        /// An offer for a roaming entity is withdrawn and its amount is unreserved. (buyer, roaming_entity_id, amount)
+        OfferWithdrawn(AccountId, RoamingEntityIndex, Balance),
+        /// An auction of a roaming entity is created. (seller, roaming_entity_id, minimum bid, ends at block)
+        AuctionCreated(AccountId, RoamingEntityIndex, Balance, BlockNumber),
+        /// An auction of a roaming entity without bids is cancelled by its seller. (seller, roaming_entity_id)
+        AuctionCancelled(AccountId, RoamingEntityIndex),
+        /// A bid is placed in the auction of a roaming entity and its amount is reserved.
+        /// (bidder, roaming_entity_id, amount)
+        BidPlaced(AccountId, RoamingEntityIndex, Balance),
+        /// An auction of a roaming entity ended without it being sold. (roaming_entity_id)
+        AuctionEndedUnsold(RoamingEntityIndex),
+        /// A roaming entity is sold by accepting an offer or at the end of an auction.
+        /// (seller, buyer, roaming_entity_id, price paid, marketplace fee)
+        Sold(AccountId, AccountId, RoamingEntityIndex, Balance, Balance),
+    }
+);
+
+decl_error! {
+    pub enum Error for Module<T: Config<I>, I: Instance> {
+        /// The roaming entity does not exist
+        RoamingEntityDoesNotExist,
+        /// Only the owner of the roaming entity may sell it
+        NotRoamingEntityOwner,
+        /// The owner of a roaming entity may not make offers or bids for it
+        CannotBuyOwnRoamingEntity,
+        /// Offers and bids must be greater than zero
+        ZeroAmount,
+        /// There is no offer by the buyer for the roaming entity
+        NoOffer,
+        /// The offer by the buyer is not for the amount being accepted
+        OfferAmountChanged,
+        /// The roaming entity is already being auctioned
+        AuctionInProgress,
+        /// The roaming entity is not being auctioned
+        NoAuction,
+        /// The auction has ended
+        AuctionEnded,
+        /// Auctions must run for at least the minimum auction duration
+        AuctionTooShort,
+        /// The maximum number of auctions already end at that block
+        TooManyAuctionsEnding,
+        /// Bids must be at least the minimum bid of the auction and more than its highest bid
+        BidTooLow,
+        /// An auction may not be cancelled once it has bids
+        AuctionHasBids,
+        /// The funds that the buyer reserved in the marketplace no longer cover the price
+        InsufficientReservedFunds,
+    }
+}
+
+// This module's storage items.
+decl_storage! {
+    trait Store for Module<T: Config<I>, I: Instance = DefaultInstance> as RoamingMarketplace {
+        /// Get the amount that a buyer offers for a roaming entity, which is reserved until the offer is withdrawn
+        /// or accepted
+        pub Offers get(fn offer): double_map hasher(opaque_blake2_256) T::RoamingEntityIndex, hasher(opaque_blake2_256) T::AccountId => Option<BalanceOf<T, I>>;
+
+        /// Get the auction of a roaming entity
+        pub Auctions get(fn auction): map hasher(opaque_blake2_256) T::RoamingEntityIndex => Option<RoamingAuction<T::AccountId, BalanceOf<T, I>, T::BlockNumber>>;
+
+        /// Get the roaming entities whose auctions end at a block
+        pub AuctionsEndingAt get(fn auctions_ending_at): map hasher(opaque_blake2_256) T::BlockNumber => Vec<T::RoamingEntityIndex>;
+
+        /// Get the total amount that the marketplace has reserved for the offers and bids of an account. The reserved
+        /// balance of an account also holds the deposits of its roaming entities, so the marketplace never pays out or
+        /// unreserves more than this.
+        pub MarketplaceReserves get(fn marketplace_reserve): map hasher(opaque_blake2_256) T::AccountId => BalanceOf<T, I>;
+    }
+}
+
+// The module's dispatchable functions.
+decl_module! {
+    /// The module declaration.
+    pub struct Module<T: Config<I>, I: Instance = DefaultInstance> for enum Call where origin: T::Origin {
+        fn deposit_event() = default;
+
+        type Error = Error<T, I>;
+
+        const MarketplaceFee: Permill = T::MarketplaceFee::get();
+
+        const MinAuctionDuration: T::BlockNumber = T::MinAuctionDuration::get();
+
+        const MaxAuctionsEndingPerBlock: u32 = T::MaxAuctionsEndingPerBlock::get();
+
+        fn on_initialize(current_block_number: T::BlockNumber) -> Weight {
+            let ending_auctions = <AuctionsEndingAt<T, I>>::take(current_block_number);
+            let ending_auctions_count = ending_auctions.len() as Weight;
+            for roaming_entity_id in ending_auctions {
+                debug::info!("Settling auction of roaming entity {:?}", roaming_entity_id);
+                Self::settle_auction(roaming_entity_id);
+            }
+
+            T::DbWeight::get().reads_writes(1 + ending_auctions_count * 8, 1 + ending_auctions_count * 8)
+        }
+
+        /// Offer to buy a roaming entity, reserving the amount offered until the offer is withdrawn or accepted.
+        /// Replaces any previous offer by the buyer for the roaming entity.
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(2, 2)]
+        #[transactional]
+        pub fn make_offer(origin, roaming_entity_id: T::RoamingEntityIndex, amount: BalanceOf<T, I>) {
+            let sender = ensure_signed(origin)?;
+
+            let owner = T::RoamingEntities::owner_of(roaming_entity_id)
+                .ok_or(Error::<T, I>::RoamingEntityDoesNotExist)?;
+            ensure!(owner != sender, Error::<T, I>::CannotBuyOwnRoamingEntity);
+            ensure!(!amount.is_zero(), Error::<T, I>::ZeroAmount);
+
+            if let Some(previous_amount) = <Offers<T, I>>::get(roaming_entity_id, &sender) {
+                Self::unreserve(&sender, previous_amount);
+            }
+            Self::reserve(&sender, amount)?;
+            <Offers<T, I>>::insert(roaming_entity_id, &sender, amount);
+
+            Self::deposit_event(RawEvent::OfferMade(sender, roaming_entity_id, amount));
+        }
+
+        /// Withdraw an offer for a roaming entity, unreserving the amount offered
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(2, 2)]
+        pub fn withdraw_offer(origin, roaming_entity_id: T::RoamingEntityIndex) {
+            let sender = ensure_signed(origin)?;
+
+            let amount = <Offers<T, I>>::take(roaming_entity_id, &sender).ok_or(Error::<T, I>::NoOffer)?;
+            Self::unreserve(&sender, amount);
+
+            Self::deposit_event(RawEvent::OfferWithdrawn(sender, roaming_entity_id, amount));
+        }
+
+        /// Accept the offer of a buyer for a roaming entity. The amount must match the offer, so that an offer
+        /// that is lowered before it is accepted is not accepted at the lower amount.
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(6, 8)]
+        #[transactional]
+        pub fn accept_offer(
+            origin,
+            roaming_entity_id: T::RoamingEntityIndex,
+            buyer: T::AccountId,
+            amount: BalanceOf<T, I>
+        ) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(
+                T::RoamingEntities::owner_of(roaming_entity_id) == Some(sender.clone()),
+                Error::<T, I>::NotRoamingEntityOwner
+            );
+            ensure!(!<Auctions<T, I>>::contains_key(roaming_entity_id), Error::<T, I>::AuctionInProgress);
+            let offer_amount = <Offers<T, I>>::take(roaming_entity_id, &buyer).ok_or(Error::<T, I>::NoOffer)?;
+            ensure!(offer_amount == amount, Error::<T, I>::OfferAmountChanged);
+
+            let fee = Self::sell(roaming_entity_id, &sender, &buyer, amount)?;
+
+            Self::deposit_event(RawEvent::Sold(sender, buyer, roaming_entity_id, amount, fee));
+        }
+
+        /// Auction a roaming entity in an English auction that ends at a block, when it is sold to the highest
+        /// bidder if its seller still owns it
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(3, 2)]
+        pub fn create_auction(
+            origin,
+            roaming_entity_id: T::RoamingEntityIndex,
+            min_bid: BalanceOf<T, I>,
+            ends_at_block: T::BlockNumber
+        ) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(
+                T::RoamingEntities::owner_of(roaming_entity_id) == Some(sender.clone()),
+                Error::<T, I>::NotRoamingEntityOwner
+            );
+            ensure!(!<Auctions<T, I>>::contains_key(roaming_entity_id), Error::<T, I>::AuctionInProgress);
+            let current_block_number = <frame_system::Module<T>>::block_number();
+            ensure!(
+                ends_at_block >= current_block_number.saturating_add(T::MinAuctionDuration::get()),
+                Error::<T, I>::AuctionTooShort
+            );
+            let mut ending_auctions = Self::auctions_ending_at(ends_at_block);
+            ensure!(
+                (ending_auctions.len() as u32) < T::MaxAuctionsEndingPerBlock::get(),
+                Error::<T, I>::TooManyAuctionsEnding
+            );
+
+            ending_auctions.push(roaming_entity_id);
+            <AuctionsEndingAt<T, I>>::insert(ends_at_block, ending_auctions);
+            <Auctions<T, I>>::insert(roaming_entity_id, RoamingAuction {
+                auction_seller: sender.clone(),
+                auction_min_bid: min_bid,
+                auction_highest_bidder: None,
+                auction_highest_bid: Zero::zero(),
+                auction_ends_at_block: ends_at_block,
+            });
+
+            Self::deposit_event(RawEvent::AuctionCreated(sender, roaming_entity_id, min_bid, ends_at_block));
+        }
+
+        /// Cancel an auction of a roaming entity that has no bids
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(2, 2)]
+        pub fn cancel_auction(origin, roaming_entity_id: T::RoamingEntityIndex) {
+            let sender = ensure_signed(origin)?;
+
+            let auction = Self::auction(roaming_entity_id).ok_or(Error::<T, I>::NoAuction)?;
+            ensure!(auction.auction_seller == sender, Error::<T, I>::NotRoamingEntityOwner);
+            ensure!(auction.auction_highest_bidder.is_none(), Error::<T, I>::AuctionHasBids);
+
+            <Auctions<T, I>>::remove(roaming_entity_id);
+            <AuctionsEndingAt<T, I>>::mutate(auction.auction_ends_at_block, |roaming_entity_ids| {
+                roaming_entity_ids.retain(|id| *id != roaming_entity_id)
+            });
+
+            Self::deposit_event(RawEvent::AuctionCancelled(sender, roaming_entity_id));
+        }
+
+        /// Bid in the auction of a roaming entity, reserving the amount bid until the bidder is outbid or the
+        /// auction ends
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(3, 3)]
+        #[transactional]
+        pub fn bid(origin, roaming_entity_id: T::RoamingEntityIndex, amount: BalanceOf<T, I>) {
+            let sender = ensure_signed(origin)?;
+
+            let mut auction = Self::auction(roaming_entity_id).ok_or(Error::<T, I>::NoAuction)?;
+            ensure!(
+                <frame_system::Module<T>>::block_number() < auction.auction_ends_at_block,
+                Error::<T, I>::AuctionEnded
+            );
+            ensure!(auction.auction_seller != sender, Error::<T, I>::CannotBuyOwnRoamingEntity);
+            ensure!(!amount.is_zero(), Error::<T, I>::ZeroAmount);
+            ensure!(amount >= auction.auction_min_bid, Error::<T, I>::BidTooLow);
+            ensure!(
+                auction.auction_highest_bidder.is_none() || amount > auction.auction_highest_bid,
+                Error::<T, I>::BidTooLow
+            );
+
+            if let Some(previous_bidder) = auction.auction_highest_bidder.take() {
+                Self::unreserve(&previous_bidder, auction.auction_highest_bid);
+            }
+            Self::reserve(&sender, amount)?;
+            auction.auction_highest_bidder = Some(sender.clone());
+            auction.auction_highest_bid = amount;
+            <Auctions<T, I>>::insert(roaming_entity_id, auction);
+
+            Self::deposit_event(RawEvent::BidPlaced(sender, roaming_entity_id, amount));
+        }
+    }
+}
+
+impl<T: Config<I>, I: Instance> Module<T, I> {
+    /// Reserve an amount for an offer or bid of an account
+    fn reserve(who: &T::AccountId, amount: BalanceOf<T, I>) -> Result<(), DispatchError> {
+        T::Currency::reserve(who, amount)?;
+        <MarketplaceReserves<T, I>>::mutate(who, |reserved| *reserved = reserved.saturating_add(amount));
+        Ok(())
+    }
+
+    /// Unreserve an amount for an offer or bid of an account, up to what the marketplace has reserved for it
+    fn unreserve(who: &T::AccountId, amount: BalanceOf<T, I>) {
+        let amount = amount.min(Self::marketplace_reserve(who));
+        T::Currency::unreserve(who, amount);
+        Self::release_marketplace_reserve(who, amount);
+    }
+
+    /// Take an amount that has been unreserved or paid out off what the marketplace has reserved for an account
+    fn release_marketplace_reserve(who: &T::AccountId, amount: BalanceOf<T, I>) {
+        <MarketplaceReserves<T, I>>::mutate_exists(who, |reserved| {
+            *reserved = reserved.map(|value| value.saturating_sub(amount)).filter(|value| !value.is_zero())
+        });
+    }
+
+    /// Hand a roaming entity over to a buyer and pay its seller from the funds the buyer reserved for it, less the
+    /// marketplace fee. Returns the marketplace fee. The seller is paid before the roaming entity is handed over, so
+    /// that its deposit, which moves to the reserved balance of the buyer, is never used to pay for it.
+    #[transactional]
+    fn sell(
+        roaming_entity_id: T::RoamingEntityIndex,
+        seller: &T::AccountId,
+        buyer: &T::AccountId,
+        price: BalanceOf<T, I>,
+    ) -> Result<BalanceOf<T, I>, DispatchError> {
+        ensure!(Self::marketplace_reserve(buyer) >= price, Error::<T, I>::InsufficientReservedFunds);
+        Self::release_marketplace_reserve(buyer, price);
+
+        let fee = T::MarketplaceFee::get() * price;
+        let remainder =
+            T::Currency::repatriate_reserved(buyer, seller, price.saturating_sub(fee), BalanceStatus::Free)?;
+        ensure!(remainder.is_zero(), Error::<T, I>::InsufficientReservedFunds);
+        let (fee_imbalance, remainder) = T::Currency::slash_reserved(buyer, fee);
+        ensure!(remainder.is_zero(), Error::<T, I>::InsufficientReservedFunds);
+        T::MarketplaceFeeDestination::on_unbalanced(fee_imbalance);
+
+        T::RoamingEntities::transfer_ownership(roaming_entity_id, buyer)?;
+        Ok(fee)
+    }
+
+    /// Sell a roaming entity whose auction has ended to its highest bidder. If there were no bids, or the seller
+    /// no longer owns the roaming entity, it is not sold and the highest bid is unreserved.
+    fn settle_auction(roaming_entity_id: T::RoamingEntityIndex) {
+        let auction = match <Auctions<T, I>>::take(roaming_entity_id) {
+            Some(value) => value,
+            None => return,
+        };
+        if let Some(buyer) = auction.auction_highest_bidder {
+            let price = auction.auction_highest_bid;
+            let seller = auction.auction_seller;
+            if T::RoamingEntities::owner_of(roaming_entity_id) == Some(seller.clone()) {
+                if let Ok(fee) = Self::sell(roaming_entity_id, &seller, &buyer, price) {
+                    Self::deposit_event(RawEvent::Sold(seller, buyer, roaming_entity_id, price, fee));
+                    return;
+                }
+            }
+            Self::unreserve(&buyer, price);
+        }
+        Self::deposit_event(RawEvent::AuctionEndedUnsold(roaming_entity_id));
+    }
+}
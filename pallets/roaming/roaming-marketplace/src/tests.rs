@@ -0,0 +1,291 @@
+// Tests to be written here
+
+use super::*;
+use crate::mock::*;
+use frame_support::{
+    assert_noop,
+    assert_ok,
+    traits::OnInitialize,
+};
+
+#[test]
+fn basic_setup_works() {
+    new_test_ext().execute_with(|| {
+        // Verify Initial Storage
+        assert_eq!(RoamingMarketplaceModule::offer(0, 2), None);
+        assert_eq!(RoamingMarketplaceModule::auction(0), None);
+        assert!(RoamingMarketplaceModule::auctions_ending_at(6).is_empty());
+    });
+}
+
+#[test]
+fn accept_offer_works() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        assert_ok!(RoamingOperatorRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingOperatorRegistry::set_price(Origin::signed(1), 0, Some(15)));
+        // Call Functions
+        assert_ok!(RoamingMarketplaceModule::make_offer(Origin::signed(2), 0, 10));
+        assert_eq!(RoamingMarketplaceModule::offer(0, 2), Some(10));
+        assert_eq!(Balances::reserved_balance(2), 10);
+        assert_ok!(RoamingMarketplaceModule::accept_offer(Origin::signed(1), 0, 2, 10));
+        // Verify Storage
+        assert_eq!(RoamingOperatorRegistry::roaming_entity_owner(0), Some(2));
+        assert_eq!(RoamingOperatorRegistry::roaming_entity_price(0), None);
+        assert_eq!(RoamingMarketplaceModule::offer(0, 2), None);
+        // The seller is paid the offer less the 10% marketplace fee, which is burned by the mock fee destination
+        assert_eq!(Balances::free_balance(1), 19);
+        assert_eq!(Balances::free_balance(2), 10);
+        assert_eq!(Balances::reserved_balance(2), 0);
+        assert_eq!(Balances::total_issuance(), 209);
+    });
+}
+
+#[test]
+fn make_offer_replaces_previous_offer() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        assert_ok!(RoamingOperatorRegistry::create(Origin::signed(1)));
+        // Call Functions
+        assert_ok!(RoamingMarketplaceModule::make_offer(Origin::signed(2), 0, 5));
+        assert_ok!(RoamingMarketplaceModule::make_offer(Origin::signed(2), 0, 8));
+        // Verify Storage
+        assert_eq!(RoamingMarketplaceModule::offer(0, 2), Some(8));
+        assert_eq!(Balances::reserved_balance(2), 8);
+        assert_noop!(
+            RoamingMarketplaceModule::accept_offer(Origin::signed(1), 0, 2, 5),
+            Error::<Test, DefaultInstance>::OfferAmountChanged
+        );
+    });
+}
+
+#[test]
+fn withdraw_offer_works() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        assert_ok!(RoamingOperatorRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingMarketplaceModule::make_offer(Origin::signed(2), 0, 5));
+        // Call Functions
+        assert_ok!(RoamingMarketplaceModule::withdraw_offer(Origin::signed(2), 0));
+        // Verify Storage
+        assert_eq!(RoamingMarketplaceModule::offer(0, 2), None);
+        assert_eq!(Balances::free_balance(2), 20);
+        assert_eq!(Balances::reserved_balance(2), 0);
+    });
+}
+
+#[test]
+fn accept_offer_fails_when_reserved_funds_no_longer_cover_the_offer() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        assert_ok!(RoamingOperatorRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingMarketplaceModule::make_offer(Origin::signed(2), 0, 10));
+        // Part of the reserved balance of the buyer is slashed elsewhere
+        let _ = Balances::slash_reserved(&2, 4);
+        // Call Functions
+        assert_noop!(
+            RoamingMarketplaceModule::accept_offer(Origin::signed(1), 0, 2, 10),
+            Error::<Test, DefaultInstance>::InsufficientReservedFunds
+        );
+        // Verify Storage
+        assert_eq!(RoamingOperatorRegistry::roaming_entity_owner(0), Some(1));
+        assert_eq!(RoamingMarketplaceModule::offer(0, 2), Some(10));
+        assert_eq!(Balances::free_balance(1), 10);
+    });
+}
+
+#[test]
+fn offers_and_bids_are_reserved_apart_from_other_reserves() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        assert_ok!(RoamingOperatorRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingOperatorRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingMarketplaceModule::create_auction(Origin::signed(1), 1, 5, 6));
+        // The buyer already has funds reserved elsewhere, such as the deposits of its roaming entities
+        assert_ok!(Balances::reserve(&3, 5));
+        // Call Functions
+        assert_ok!(RoamingMarketplaceModule::make_offer(Origin::signed(3), 0, 10));
+        assert_ok!(RoamingMarketplaceModule::bid(Origin::signed(3), 1, 7));
+        assert_eq!(RoamingMarketplaceModule::marketplace_reserve(3), 17);
+        assert_eq!(Balances::reserved_balance(3), 22);
+        assert_ok!(RoamingMarketplaceModule::bid(Origin::signed(4), 1, 8));
+        assert_eq!(RoamingMarketplaceModule::marketplace_reserve(3), 10);
+        assert_ok!(RoamingMarketplaceModule::accept_offer(Origin::signed(1), 0, 3, 10));
+        // Verify Storage
+        assert_eq!(RoamingMarketplaceModule::marketplace_reserve(3), 0);
+        assert_eq!(Balances::reserved_balance(3), 5);
+        assert_eq!(RoamingMarketplaceModule::marketplace_reserve(4), 8);
+        RoamingMarketplaceModule::on_initialize(6);
+        assert_eq!(RoamingMarketplaceModule::marketplace_reserve(4), 0);
+        assert_eq!(Balances::reserved_balance(4), 0);
+    });
+}
+
+#[test]
+fn offers_handle_basic_errors() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        assert_ok!(RoamingOperatorRegistry::create(Origin::signed(1)));
+        // Call Functions
+        assert_noop!(
+            RoamingMarketplaceModule::make_offer(Origin::signed(2), 1, 5),
+            Error::<Test, DefaultInstance>::RoamingEntityDoesNotExist
+        );
+        assert_noop!(
+            RoamingMarketplaceModule::make_offer(Origin::signed(1), 0, 5),
+            Error::<Test, DefaultInstance>::CannotBuyOwnRoamingEntity
+        );
+        assert_noop!(
+            RoamingMarketplaceModule::make_offer(Origin::signed(2), 0, 0),
+            Error::<Test, DefaultInstance>::ZeroAmount
+        );
+        assert_noop!(
+            RoamingMarketplaceModule::make_offer(Origin::signed(2), 0, 100),
+            pallet_balances::Error::<Test, _>::InsufficientBalance
+        );
+        assert_noop!(
+            RoamingMarketplaceModule::withdraw_offer(Origin::signed(2), 0),
+            Error::<Test, DefaultInstance>::NoOffer
+        );
+        assert_noop!(
+            RoamingMarketplaceModule::accept_offer(Origin::signed(1), 0, 2, 5),
+            Error::<Test, DefaultInstance>::NoOffer
+        );
+        assert_ok!(RoamingMarketplaceModule::make_offer(Origin::signed(2), 0, 5));
+        assert_noop!(
+            RoamingMarketplaceModule::accept_offer(Origin::signed(3), 0, 2, 5),
+            Error::<Test, DefaultInstance>::NotRoamingEntityOwner
+        );
+        assert_ok!(RoamingMarketplaceModule::create_auction(Origin::signed(1), 0, 5, 6));
+        assert_noop!(
+            RoamingMarketplaceModule::accept_offer(Origin::signed(1), 0, 2, 5),
+            Error::<Test, DefaultInstance>::AuctionInProgress
+        );
+    });
+}
+
+#[test]
+fn auction_works() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        assert_ok!(RoamingOperatorRegistry::create(Origin::signed(1)));
+        // Call Functions
+        assert_ok!(RoamingMarketplaceModule::create_auction(Origin::signed(1), 0, 5, 6));
+        assert_eq!(RoamingMarketplaceModule::auctions_ending_at(6), vec![0]);
+        assert_ok!(RoamingMarketplaceModule::bid(Origin::signed(2), 0, 5));
+        assert_eq!(Balances::reserved_balance(2), 5);
+        assert_ok!(RoamingMarketplaceModule::bid(Origin::signed(3), 0, 10));
+        // Outbid funds are unreserved
+        assert_eq!(Balances::reserved_balance(2), 0);
+        assert_eq!(Balances::reserved_balance(3), 10);
+        System::set_block_number(6);
+        assert_noop!(
+            RoamingMarketplaceModule::bid(Origin::signed(2), 0, 15),
+            Error::<Test, DefaultInstance>::AuctionEnded
+        );
+        RoamingMarketplaceModule::on_initialize(6);
+        // Verify Storage
+        assert_eq!(RoamingOperatorRegistry::roaming_entity_owner(0), Some(3));
+        assert_eq!(RoamingMarketplaceModule::auction(0), None);
+        assert!(RoamingMarketplaceModule::auctions_ending_at(6).is_empty());
+        assert_eq!(Balances::free_balance(1), 19);
+        assert_eq!(Balances::free_balance(3), 20);
+        assert_eq!(Balances::reserved_balance(3), 0);
+        assert_eq!(Balances::total_issuance(), 209);
+    });
+}
+
+#[test]
+fn auction_ends_unsold_without_bids() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        assert_ok!(RoamingOperatorRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingMarketplaceModule::create_auction(Origin::signed(1), 0, 5, 6));
+        // Call Functions
+        RoamingMarketplaceModule::on_initialize(6);
+        // Verify Storage
+        assert_eq!(RoamingOperatorRegistry::roaming_entity_owner(0), Some(1));
+        assert_eq!(RoamingMarketplaceModule::auction(0), None);
+    });
+}
+
+#[test]
+fn auction_refunds_bid_when_seller_no_longer_owns() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        assert_ok!(RoamingOperatorRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingMarketplaceModule::create_auction(Origin::signed(1), 0, 5, 6));
+        assert_ok!(RoamingMarketplaceModule::bid(Origin::signed(2), 0, 5));
+        assert_ok!(RoamingOperatorRegistry::transfer(Origin::signed(1), 4, 0));
+        // Call Functions
+        RoamingMarketplaceModule::on_initialize(6);
+        // Verify Storage
+        assert_eq!(RoamingOperatorRegistry::roaming_entity_owner(0), Some(4));
+        assert_eq!(Balances::free_balance(1), 10);
+        assert_eq!(Balances::free_balance(2), 20);
+        assert_eq!(Balances::reserved_balance(2), 0);
+    });
+}
+
+#[test]
+fn cancel_auction_works() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        assert_ok!(RoamingOperatorRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingMarketplaceModule::create_auction(Origin::signed(1), 0, 5, 6));
+        // Call Functions
+        assert_noop!(
+            RoamingMarketplaceModule::cancel_auction(Origin::signed(2), 0),
+            Error::<Test, DefaultInstance>::NotRoamingEntityOwner
+        );
+        assert_ok!(RoamingMarketplaceModule::cancel_auction(Origin::signed(1), 0));
+        // Verify Storage
+        assert_eq!(RoamingMarketplaceModule::auction(0), None);
+        assert!(RoamingMarketplaceModule::auctions_ending_at(6).is_empty());
+        assert_noop!(
+            RoamingMarketplaceModule::cancel_auction(Origin::signed(1), 0),
+            Error::<Test, DefaultInstance>::NoAuction
+        );
+    });
+}
+
+#[test]
+fn auctions_handle_basic_errors() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        assert_ok!(RoamingOperatorRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingOperatorRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingOperatorRegistry::create(Origin::signed(1)));
+        // Call Functions
+        assert_noop!(
+            RoamingMarketplaceModule::create_auction(Origin::signed(2), 0, 5, 6),
+            Error::<Test, DefaultInstance>::NotRoamingEntityOwner
+        );
+        assert_noop!(
+            RoamingMarketplaceModule::create_auction(Origin::signed(1), 0, 5, 5),
+            Error::<Test, DefaultInstance>::AuctionTooShort
+        );
+        assert_noop!(RoamingMarketplaceModule::bid(Origin::signed(2), 0, 5), Error::<Test, DefaultInstance>::NoAuction);
+        assert_ok!(RoamingMarketplaceModule::create_auction(Origin::signed(1), 0, 5, 6));
+        assert_noop!(
+            RoamingMarketplaceModule::create_auction(Origin::signed(1), 0, 5, 7),
+            Error::<Test, DefaultInstance>::AuctionInProgress
+        );
+        assert_ok!(RoamingMarketplaceModule::create_auction(Origin::signed(1), 1, 5, 6));
+        assert_noop!(
+            RoamingMarketplaceModule::create_auction(Origin::signed(1), 2, 5, 6),
+            Error::<Test, DefaultInstance>::TooManyAuctionsEnding
+        );
+        assert_noop!(
+            RoamingMarketplaceModule::bid(Origin::signed(1), 0, 5),
+            Error::<Test, DefaultInstance>::CannotBuyOwnRoamingEntity
+        );
+        assert_noop!(RoamingMarketplaceModule::bid(Origin::signed(2), 0, 4), Error::<Test, DefaultInstance>::BidTooLow);
+        assert_ok!(RoamingMarketplaceModule::bid(Origin::signed(2), 0, 5));
+        assert_noop!(RoamingMarketplaceModule::bid(Origin::signed(3), 0, 5), Error::<Test, DefaultInstance>::BidTooLow);
+        assert_noop!(
+            RoamingMarketplaceModule::cancel_auction(Origin::signed(1), 0),
+            Error::<Test, DefaultInstance>::AuctionHasBids
+        );
+    });
+}
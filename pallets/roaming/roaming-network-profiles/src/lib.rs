@@ -10,22 +10,30 @@ use frame_support::{
     decl_module,
     decl_storage,
     ensure,
-    traits::{
-        Get,
-        Randomness,
-    },
+    traits::Get,
+    transactional,
     Parameter,
 };
 use frame_system::ensure_signed;
-use sp_io::hashing::blake2_128;
 use sp_runtime::{
     traits::{
         AtLeast32Bit,
         Bounded,
         Member,
-        One,
     },
     DispatchError,
+    DispatchResult,
+};
+use roaming_operators::{
+    without_roaming_entity,
+    RoamingAuthority,
+    RoamingEntityChildren,
+    RoamingNetworkEntity,
+    RoamingOperatorEntity,
+};
+use roaming_registry::{
+    RoamingRegistry,
+    RoamingTradable,
 };
 use sp_std::prelude::*; // Imports Vec
 
@@ -41,12 +49,10 @@ pub trait Config:
 {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
     type RoamingNetworkProfileIndex: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
+    /// The registry that holds the roaming network profiles and their owners
+    type RoamingNetworkProfileRegistry: RoamingRegistry<Self::AccountId, Self::RoamingNetworkProfileIndex>;
 }
 
-#[derive(Encode, Decode, Clone, PartialEq, Eq)]
-#[cfg_attr(feature = "std", derive(Debug))]
-pub struct RoamingNetworkProfile(pub [u8; 16]);
-
 decl_event!(
     pub enum Event<T> where
         <T as frame_system::Config>::AccountId,
@@ -55,10 +61,6 @@ decl_event!(
         <T as roaming_operators::Config>::RoamingOperatorIndex,
         <T as roaming_devices::Config>::RoamingDeviceIndex,
     {
-        /// A roaming network_profile is created. (owner, roaming_network_profile_id)
-        Created(AccountId, RoamingNetworkProfileIndex),
-        /// A roaming network_profile is transferred. (from, to, roaming_network_profile_id)
-        Transferred(AccountId, AccountId, RoamingNetworkProfileIndex),
         /// A roaming network_profile restricted access to any devices
         RoamingNetworkProfileDeviceAccessAllowedSet(AccountId, RoamingNetworkProfileIndex, bool),
         /// A roaming network_profile whitelisted network for visiting devices was added
@@ -73,21 +75,14 @@ decl_event!(
         AssignedNetworkProfileToNetwork(AccountId, RoamingNetworkProfileIndex, RoamingNetworkIndex),
         /// A roaming network_profile is assigned to an operator. (owner of network, roaming_network_profile_id, roaming_operator_id)
         AssignedNetworkProfileToOperator(AccountId, RoamingNetworkProfileIndex, RoamingOperatorIndex),
+        /// A roaming network_profile is removed. (owner, roaming_network_profile_id)
+        Removed(AccountId, RoamingNetworkProfileIndex),
     }
 );
 
 // This module's storage items.
 decl_storage! {
     trait Store for Module<T: Config> as RoamingNetworkProfiles {
-        /// Stores all the roaming network_profiles, key is the roaming network_profile id / index
-        pub RoamingNetworkProfiles get(fn roaming_network_profile): map hasher(opaque_blake2_256) T::RoamingNetworkProfileIndex => Option<RoamingNetworkProfile>;
-
-        /// Stores the total number of roaming network_profiles. i.e. the next roaming network_profile index
-        pub RoamingNetworkProfilesCount get(fn roaming_network_profiles_count): T::RoamingNetworkProfileIndex;
-
-        /// Get roaming network_profile owner
-        pub RoamingNetworkProfileOwners get(fn roaming_network_profile_owner): map hasher(opaque_blake2_256) T::RoamingNetworkProfileIndex => Option<T::AccountId>;
-
         /// Get roaming network_policy status of whether any device visitors are allowed to roam at all
         pub RoamingNetworkProfileDeviceAccessAllowed get(fn roaming_network_profile_restricted_access): map hasher(opaque_blake2_256) T::RoamingNetworkProfileIndex => Option<bool>;
 
@@ -117,34 +112,6 @@ decl_module! {
     pub struct Module<T: Config> for enum Call where origin: T::Origin {
         fn deposit_event() = default;
 
-        /// Create a new roaming network_profile
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn create(origin) {
-            let sender = ensure_signed(origin)?;
-            let roaming_network_profile_id = Self::next_roaming_network_profile_id()?;
-
-            // Generate a random 128bit value
-            let unique_id = Self::random_value(&sender);
-
-            // Create and store roaming network_profile
-            let roaming_network_profile = RoamingNetworkProfile(unique_id);
-            Self::insert_roaming_network_profile(&sender, roaming_network_profile_id, roaming_network_profile);
-
-            Self::deposit_event(RawEvent::Created(sender, roaming_network_profile_id));
-        }
-
-        /// Transfer a roaming network_profile to new owner
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn transfer(origin, to: T::AccountId, roaming_network_profile_id: T::RoamingNetworkProfileIndex) {
-            let sender = ensure_signed(origin)?;
-
-            ensure!(Self::roaming_network_profile_owner(roaming_network_profile_id) == Some(sender.clone()), "Only owner can transfer roaming network_profile");
-
-            Self::update_owner(&to, roaming_network_profile_id);
-
-            Self::deposit_event(RawEvent::Transferred(sender, to, roaming_network_profile_id));
-        }
-
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
         pub fn set_device_access_allowed(origin, roaming_network_profile_id: T::RoamingNetworkProfileIndex, device_access_allowed: bool) {
             let sender = ensure_signed(origin)?;
@@ -154,7 +121,7 @@ decl_module! {
             ensure!(is_roaming_network_profile, "RoamingNetworkProfile does not exist");
 
             // Ensure that the caller is owner of the network_profile whitelisted network they are trying to change
-            ensure!(Self::roaming_network_profile_owner(roaming_network_profile_id) == Some(sender.clone()), "Only owner can set whitelisted network for roaming network_profile");
+            ensure!(T::RoamingNetworkProfileRegistry::owner_of(roaming_network_profile_id) == Some(sender.clone()), "Only owner can set whitelisted network for roaming network_profile");
 
             let is_owned_by_parent_relationship = Self::is_owned_by_required_parent_relationship(roaming_network_profile_id, sender.clone()).is_ok();
             ensure!(is_owned_by_parent_relationship, "Ownership by parent does not exist");
@@ -181,7 +148,7 @@ decl_module! {
             ensure!(is_roaming_network_profile, "RoamingNetworkProfile does not exist");
 
             // Ensure that the caller is owner of the network_profile whitelisted network they are trying to change
-            ensure!(Self::roaming_network_profile_owner(roaming_network_profile_id) == Some(sender.clone()), "Only owner can set whitelisted network for roaming network_profile");
+            ensure!(T::RoamingNetworkProfileRegistry::owner_of(roaming_network_profile_id) == Some(sender.clone()), "Only owner can set whitelisted network for roaming network_profile");
 
             // Ensure that the given network id already exists
             let is_roaming_network = <roaming_networks::Module<T>>
@@ -274,7 +241,7 @@ decl_module! {
             ensure!(is_roaming_network_profile, "RoamingNetworkProfile does not exist");
 
             // Ensure that the caller is owner of the network_profile whitelisted network they are trying to change
-            ensure!(Self::roaming_network_profile_owner(roaming_network_profile_id) == Some(sender.clone()), "Only owner can set whitelisted network for roaming network_profile");
+            ensure!(T::RoamingNetworkProfileRegistry::owner_of(roaming_network_profile_id) == Some(sender.clone()), "Only owner can set whitelisted network for roaming network_profile");
 
             let is_owned_by_parent_relationship = Self::is_owned_by_required_parent_relationship(roaming_network_profile_id, sender.clone()).is_ok();
             ensure!(is_owned_by_parent_relationship, "Ownership by parent does not exist");
@@ -343,7 +310,7 @@ decl_module! {
             ensure!(is_roaming_network_profile, "RoamingNetworkProfile does not exist");
 
             // Ensure that the caller is owner of the network_profile blacklisted device they are trying to change
-            ensure!(Self::roaming_network_profile_owner(roaming_network_profile_id) == Some(sender.clone()), "Only owner can set blacklisted device for roaming network_profile");
+            ensure!(T::RoamingNetworkProfileRegistry::owner_of(roaming_network_profile_id) == Some(sender.clone()), "Only owner can set blacklisted device for roaming network_profile");
 
             // Ensure that the given network id already exists
             let is_roaming_device = <roaming_devices::Module<T>>
@@ -435,7 +402,7 @@ decl_module! {
             ensure!(is_roaming_network_profile, "RoamingNetworkProfile does not exist");
 
             // Ensure that the caller is owner of the network_profile blacklisted device they are trying to change
-            ensure!(Self::roaming_network_profile_owner(roaming_network_profile_id) == Some(sender.clone()), "Only owner can set blacklisted device for roaming network_profile");
+            ensure!(T::RoamingNetworkProfileRegistry::owner_of(roaming_network_profile_id) == Some(sender.clone()), "Only owner can set blacklisted device for roaming network_profile");
 
             let is_owned_by_parent_relationship = Self::is_owned_by_required_parent_relationship(roaming_network_profile_id, sender.clone()).is_ok();
             ensure!(is_owned_by_parent_relationship, "Ownership by parent does not exist");
@@ -492,6 +459,7 @@ decl_module! {
         }
 
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        #[transactional]
         pub fn assign_network_profile_to_network(
             origin,
             roaming_network_profile_id: T::RoamingNetworkProfileIndex,
@@ -504,18 +472,16 @@ decl_module! {
                 ::exists_roaming_network(roaming_network_id).is_ok();
             ensure!(is_roaming_network, "RoamingNetwork does not exist");
 
-            // Ensure that caller of the function is the owner of the network id to assign the network_profile to
+            // Ensure that caller of the function is authorized for the network id to assign the network_profile to
             ensure!(
-                <roaming_networks::Module<T>>::is_roaming_network_owner(roaming_network_id, sender.clone()).is_ok(),
-                "Only the roaming network owner can assign itself a roaming network profile"
+                <roaming_networks::Module<T>>::is_authorized(roaming_network_id, &sender),
+                "Only an account authorized for the roaming network can assign itself a roaming network profile"
             );
 
-            Self::associate_network_profile_with_network(roaming_network_profile_id, roaming_network_id)
-                .expect("Unable to associate network profile with network");
+            Self::associate_network_profile_with_network(roaming_network_profile_id, roaming_network_id)?;
 
             // Ensure that the given network_profile id already exists
-            let roaming_network_profile = Self::roaming_network_profile(roaming_network_profile_id);
-            ensure!(roaming_network_profile.is_some(), "Invalid roaming_network_profile_id");
+            ensure!(T::RoamingNetworkProfileRegistry::exists(roaming_network_profile_id), "Invalid roaming_network_profile_id");
 
             // Ensure that the network_profile is not already owned by a different network
             // Unassign the network_profile from any existing network since it may only be owned by one network
@@ -528,6 +494,7 @@ decl_module! {
         }
 
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        #[transactional]
         pub fn assign_network_profile_to_operator(
             origin,
             roaming_network_profile_id: T::RoamingNetworkProfileIndex,
@@ -540,18 +507,16 @@ decl_module! {
                 ::exists_roaming_operator(roaming_operator_id).is_ok();
             ensure!(is_roaming_operator, "RoamingOperator does not exist");
 
-            // Ensure that caller of the function is the owner of the operator id to assign the network to
+            // Ensure that caller of the function is authorized for the operator id to assign the network to
             ensure!(
-                <roaming_operators::Module<T>>::is_roaming_operator_owner(roaming_operator_id, sender.clone()).is_ok(),
-                "Only the roaming operator owner can assign itself a roaming network profile"
+                <roaming_operators::Module<T>>::is_authorized(roaming_operator_id, &sender),
+                "Only an account authorized for the roaming operator can assign itself a roaming network profile"
             );
 
-            Self::associate_network_profile_with_operator(roaming_network_profile_id, roaming_operator_id)
-                .expect("Unable to associate network profile with operator");
+            Self::associate_network_profile_with_operator(roaming_network_profile_id, roaming_operator_id)?;
 
             // Ensure that the given network_profile id already exists
-            let roaming_network_profile = Self::roaming_network_profile(roaming_network_profile_id);
-            ensure!(roaming_network_profile.is_some(), "Invalid roaming_network_profile_id");
+            ensure!(T::RoamingNetworkProfileRegistry::exists(roaming_network_profile_id), "Invalid roaming_network_profile_id");
 
             // Ensure that the network_profile is not already owned by a different operator
             // Unassign the network_profile from any existing operator since it may only be owned by one operator
@@ -562,6 +527,19 @@ decl_module! {
 
             Self::deposit_event(RawEvent::AssignedNetworkProfileToOperator(sender, roaming_network_profile_id, roaming_operator_id));
         }
+
+        /// Remove a roaming network profile
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(4, 9)]
+        #[transactional]
+        pub fn remove(origin, roaming_network_profile_id: T::RoamingNetworkProfileIndex) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(T::RoamingNetworkProfileRegistry::owner_of(roaming_network_profile_id) == Some(sender.clone()), "Only owner can remove roaming network_profile");
+
+            Self::remove_roaming_network_profile(roaming_network_profile_id)?;
+
+            Self::deposit_event(RawEvent::Removed(sender, roaming_network_profile_id));
+        }
     }
 }
 
@@ -571,7 +549,7 @@ impl<T: Config> Module<T> {
         sender: T::AccountId,
     ) -> Result<(), DispatchError> {
         ensure!(
-            Self::roaming_network_profile_owner(&roaming_network_profile_id)
+            T::RoamingNetworkProfileRegistry::owner_of(roaming_network_profile_id)
                 .map(|owner| owner == sender)
                 .unwrap_or(false),
             "Sender is not owner of RoamingNetworkProfile"
@@ -606,11 +584,12 @@ impl<T: Config> Module<T> {
 
     pub fn exists_roaming_network_profile(
         roaming_network_profile_id: T::RoamingNetworkProfileIndex,
-    ) -> Result<RoamingNetworkProfile, DispatchError> {
-        match Self::roaming_network_profile(roaming_network_profile_id) {
-            Some(roaming_network_profile) => Ok(roaming_network_profile),
-            None => Err(DispatchError::Other("RoamingNetworkProfile does not exist")),
-        }
+    ) -> Result<(), DispatchError> {
+        ensure!(
+            T::RoamingNetworkProfileRegistry::exists(roaming_network_profile_id),
+            "RoamingNetworkProfile does not exist"
+        );
+        Ok(())
     }
 
     pub fn has_value_for_network_profile_whitelisted_networks(
@@ -712,36 +691,61 @@ impl<T: Config> Module<T> {
         }
     }
 
-    fn random_value(sender: &T::AccountId) -> [u8; 16] {
-        let payload = (
-            T::Randomness::random(&[0]),
-            sender,
-            <frame_system::Module<T>>::extrinsic_index(),
-            <frame_system::Module<T>>::block_number(),
-        );
-        payload.using_encoded(blake2_128)
+    /// Remove a roaming network profile along with its links to its network and operator
+    pub fn remove_roaming_network_profile(roaming_network_profile_id: T::RoamingNetworkProfileIndex) -> DispatchResult {
+        Self::exists_roaming_network_profile(roaming_network_profile_id)?;
+        if let Some(roaming_network_id) = <RoamingNetworkProfileNetwork<T>>::take(roaming_network_profile_id) {
+            <RoamingNetworkNetworkProfiles<T>>::mutate_exists(roaming_network_id, |network_profile_ids| {
+                *network_profile_ids = without_roaming_entity(network_profile_ids.take(), &roaming_network_profile_id)
+            });
+        }
+        if let Some(roaming_operator_id) = <RoamingNetworkProfileOperator<T>>::take(roaming_network_profile_id) {
+            <RoamingOperatorNetworkProfiles<T>>::mutate_exists(roaming_operator_id, |network_profile_ids| {
+                *network_profile_ids = without_roaming_entity(network_profile_ids.take(), &roaming_network_profile_id)
+            });
+        }
+        T::RoamingNetworkProfileRegistry::deregister(roaming_network_profile_id)?;
+        <RoamingNetworkProfileDeviceAccessAllowed<T>>::remove(roaming_network_profile_id);
+        <RoamingNetworkProfileWhitelistedNetworks<T>>::remove(roaming_network_profile_id);
+        <RoamingNetworkProfileBlacklistedDevices<T>>::remove(roaming_network_profile_id);
+        Ok(())
+    }
+}
+
+/// The network profiles of a roaming network, other than those that have since been assigned to another network
+impl<T: Config> RoamingEntityChildren<RoamingNetworkEntity, T::RoamingNetworkIndex> for Module<T> {
+    fn has_children(roaming_network_id: T::RoamingNetworkIndex) -> bool {
+        Self::roaming_network_network_profiles(roaming_network_id).unwrap_or_default().into_iter().any(
+            |network_profile_id| Self::roaming_network_profile_network(network_profile_id) == Some(roaming_network_id),
+        )
     }
 
-    fn next_roaming_network_profile_id() -> Result<T::RoamingNetworkProfileIndex, DispatchError> {
-        let roaming_network_profile_id = Self::roaming_network_profiles_count();
-        if roaming_network_profile_id == <T::RoamingNetworkProfileIndex as Bounded>::max_value() {
-            return Err(DispatchError::Other("RoamingNetworkProfiles count overflow"));
+    fn remove_children(roaming_network_id: T::RoamingNetworkIndex) -> DispatchResult {
+        for network_profile_id in <RoamingNetworkNetworkProfiles<T>>::take(roaming_network_id).unwrap_or_default() {
+            if Self::roaming_network_profile_network(network_profile_id) == Some(roaming_network_id) {
+                Self::remove_roaming_network_profile(network_profile_id)?;
+            }
         }
-        Ok(roaming_network_profile_id)
+        Ok(())
     }
+}
 
-    fn insert_roaming_network_profile(
-        owner: &T::AccountId,
-        roaming_network_profile_id: T::RoamingNetworkProfileIndex,
-        roaming_network_profile: RoamingNetworkProfile,
-    ) {
-        // Create and store roaming network_profile
-        <RoamingNetworkProfiles<T>>::insert(roaming_network_profile_id, roaming_network_profile);
-        <RoamingNetworkProfilesCount<T>>::put(roaming_network_profile_id + One::one());
-        <RoamingNetworkProfileOwners<T>>::insert(roaming_network_profile_id, owner.clone());
+/// The network profiles of a roaming operator, other than those that have since been assigned to another operator
+impl<T: Config> RoamingEntityChildren<RoamingOperatorEntity, T::RoamingOperatorIndex> for Module<T> {
+    fn has_children(roaming_operator_id: T::RoamingOperatorIndex) -> bool {
+        Self::roaming_operator_network_profiles(roaming_operator_id).unwrap_or_default().into_iter().any(
+            |network_profile_id| {
+                Self::roaming_network_profile_operators(network_profile_id) == Some(roaming_operator_id)
+            },
+        )
     }
 
-    fn update_owner(to: &T::AccountId, roaming_network_profile_id: T::RoamingNetworkProfileIndex) {
-        <RoamingNetworkProfileOwners<T>>::insert(roaming_network_profile_id, to);
+    fn remove_children(roaming_operator_id: T::RoamingOperatorIndex) -> DispatchResult {
+        for network_profile_id in <RoamingOperatorNetworkProfiles<T>>::take(roaming_operator_id).unwrap_or_default() {
+            if Self::roaming_network_profile_operators(network_profile_id) == Some(roaming_operator_id) {
+                Self::remove_roaming_network_profile(network_profile_id)?;
+            }
+        }
+        Ok(())
     }
 }
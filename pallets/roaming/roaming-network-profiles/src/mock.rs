@@ -87,34 +87,112 @@ impl pallet_transaction_payment::Config for Test {
     type TransactionByteFee = TransactionByteFee;
     type WeightToFee = IdentityFee<u64>;
 }
-impl roaming_operators::Config for Test {
+impl roaming_registry::Config<roaming_registry::Instance1> for Test {
     type Currency = Balances;
+    type EntityDeposit = ();
     type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
     type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+impl roaming_operators::Config for Test {
+    type Currency = Balances;
+    type Event = ();
+    type RemovalPolicy = ();
+    type RoamingOperatorChildren = ();
     type RoamingOperatorIndex = u64;
+    type RoamingOperatorRegistry = RoamingOperatorRegistry;
+}
+impl roaming_registry::Config<roaming_registry::Instance2> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
 }
 impl roaming_networks::Config for Test {
     type Event = ();
+    type RoamingNetworkChildren = ();
     type RoamingNetworkIndex = u64;
+    type RoamingNetworkRegistry = RoamingNetworkRegistry;
+}
+impl roaming_registry::Config<roaming_registry::Instance4> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
 }
 impl roaming_network_servers::Config for Test {
     type Event = ();
+    type RoamingNetworkServerChildren = ();
     type RoamingNetworkServerIndex = u64;
+    type RoamingNetworkServerRegistry = RoamingNetworkServerRegistry;
+}
+impl roaming_registry::Config<roaming_registry::Instance5> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
 }
 impl roaming_devices::Config for Test {
     type Event = ();
+    type MaxRoamingPolicyNetworks = ();
+    type RoamingDeviceChildren = ();
     type RoamingDeviceIndex = u64;
+    type RoamingDeviceRegistry = RoamingDeviceRegistry;
+}
+impl roaming_registry::Config<roaming_registry::Instance3> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
 }
 impl roaming_organizations::Config for Test {
     type Event = ();
+    type RoamingOrganizationChildren = ();
     type RoamingOrganizationIndex = u64;
+    type RoamingOrganizationRegistry = RoamingOrganizationRegistry;
+}
+impl roaming_registry::Config<roaming_registry::Instance10> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
 }
 impl Config for Test {
     type Event = ();
     type RoamingNetworkProfileIndex = u64;
+    type RoamingNetworkProfileRegistry = RoamingNetworkProfileRegistry;
 }
 
 pub type RoamingNetworkProfileModule = Module<Test>;
+pub type RoamingOperatorRegistry = roaming_registry::Module<Test, roaming_registry::Instance1>;
+pub type RoamingNetworkRegistry = roaming_registry::Module<Test, roaming_registry::Instance2>;
+pub type RoamingNetworkServerRegistry = roaming_registry::Module<Test, roaming_registry::Instance4>;
+pub type RoamingDeviceRegistry = roaming_registry::Module<Test, roaming_registry::Instance5>;
+pub type RoamingOrganizationRegistry = roaming_registry::Module<Test, roaming_registry::Instance3>;
+pub type RoamingNetworkProfileRegistry = roaming_registry::Module<Test, roaming_registry::Instance10>;
 
 // This function basically just builds a genesis storage key/value store according to
 // our desired mockup.
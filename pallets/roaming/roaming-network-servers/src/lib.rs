@@ -10,24 +10,31 @@ use frame_support::{
     decl_module,
     decl_storage,
     ensure,
-    traits::{
-        Currency,
-        ExistenceRequirement,
-        Get,
-        Randomness,
-    },
+    traits::Get,
+    transactional,
     Parameter,
 };
 use frame_system::ensure_signed;
-use sp_io::hashing::blake2_128;
 use sp_runtime::{
     traits::{
         AtLeast32Bit,
         Bounded,
         Member,
-        One,
     },
     DispatchError,
+    DispatchResult,
+};
+use roaming_operators::{
+    without_roaming_entity,
+    RoamingAuthority,
+    RoamingEntityChildren,
+    RoamingNetworkEntity,
+    RoamingNetworkServerEntity,
+    RoamingOperatorEntity,
+};
+use roaming_registry::{
+    RoamingRegistry,
+    RoamingTradable,
 };
 use sp_std::prelude::*; // Imports Vec
 
@@ -41,53 +48,34 @@ mod tests;
 pub trait Config: frame_system::Config + roaming_operators::Config + roaming_networks::Config {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
     type RoamingNetworkServerIndex: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
+    /// The registry that holds the roaming network servers and their owners
+    type RoamingNetworkServerRegistry: RoamingRegistry<Self::AccountId, Self::RoamingNetworkServerIndex>;
+    /// The pallets that hold the children of roaming network servers
+    type RoamingNetworkServerChildren: RoamingEntityChildren<
+        RoamingNetworkServerEntity,
+        Self::RoamingNetworkServerIndex,
+    >;
 }
 
-type BalanceOf<T> =
-    <<T as roaming_operators::Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
-
-#[derive(Encode, Decode, Clone, PartialEq, Eq)]
-#[cfg_attr(feature = "std", derive(Debug))]
-pub struct RoamingNetworkServer(pub [u8; 16]);
-
 decl_event!(
     pub enum Event<T> where
         <T as frame_system::Config>::AccountId,
         <T as Config>::RoamingNetworkServerIndex,
         <T as roaming_networks::Config>::RoamingNetworkIndex,
         <T as roaming_operators::Config>::RoamingOperatorIndex,
-        Balance = BalanceOf<T>,
     {
-        /// A roaming network_server is created. (owner, roaming_network_server_id)
-        Created(AccountId, RoamingNetworkServerIndex),
-        /// A roaming network_server is transferred. (from, to, roaming_network_server_id)
-        Transferred(AccountId, AccountId, RoamingNetworkServerIndex),
-        /// A roaming network_server is available for sale. (owner, roaming_network_server_id, price)
-        PriceSet(AccountId, RoamingNetworkServerIndex, Option<Balance>),
-        /// A roaming network_server is sold. (from, to, roaming_network_server_id, price)
-        Sold(AccountId, AccountId, RoamingNetworkServerIndex, Balance),
         /// A roaming network_server is assigned to a network. (owner of network, roaming_network_server_id, roaming_network_id)
         AssignedNetworkServerToNetwork(AccountId, RoamingNetworkServerIndex, RoamingNetworkIndex),
         /// A roaming network_server is assigned to an operator. (owner of network, roaming_network_server_id, roaming_operator_id)
         AssignedNetworkServerToOperator(AccountId, RoamingNetworkServerIndex, RoamingOperatorIndex),
+        /// A roaming network_server is removed. (owner, roaming_network_server_id)
+        Removed(AccountId, RoamingNetworkServerIndex),
     }
 );
 
 // This module's storage items.
 decl_storage! {
     trait Store for Module<T: Config> as RoamingNetworkServers {
-        /// Stores all the roaming network_servers, key is the roaming network_server id / index
-        pub RoamingNetworkServers get(fn roaming_network_server): map hasher(opaque_blake2_256) T::RoamingNetworkServerIndex => Option<RoamingNetworkServer>;
-
-        /// Stores the total number of roaming network_servers. i.e. the next roaming network_server index
-        pub RoamingNetworkServersCount get(fn roaming_network_servers_count): T::RoamingNetworkServerIndex;
-
-        /// Get roaming network_server owner
-        pub RoamingNetworkServerOwners get(fn roaming_network_server_owner): map hasher(opaque_blake2_256) T::RoamingNetworkServerIndex => Option<T::AccountId>;
-
-        /// Get roaming network_server price. None means not for sale.
-        pub RoamingNetworkServerPrices get(fn roaming_network_server_price): map hasher(opaque_blake2_256) T::RoamingNetworkServerIndex => Option<BalanceOf<T>>;
-
         /// Get roaming network_server network
         pub RoamingNetworkServerNetwork get(fn roaming_network_server_network): map hasher(opaque_blake2_256) T::RoamingNetworkServerIndex => Option<T::RoamingNetworkIndex>;
 
@@ -98,7 +86,7 @@ decl_storage! {
         pub RoamingNetworkNetworkServers get(fn roaming_network_network_servers): map hasher(opaque_blake2_256) T::RoamingNetworkIndex => Option<Vec<T::RoamingNetworkServerIndex>>;
 
         /// Get roaming operator's network servers
-        pub RoamingOperatorNetworkServers get(fn roaming_operator_network_servers): map hasher(opaque_blake2_256) T::RoamingOperatorIndex => Option<Vec<T::RoamingNetworkServerIndex>>
+        pub RoamingOperatorNetworkServers get(fn roaming_operator_network_servers): map hasher(opaque_blake2_256) T::RoamingOperatorIndex => Option<Vec<T::RoamingNetworkServerIndex>>;
     }
 }
 
@@ -108,76 +96,8 @@ decl_module! {
     pub struct Module<T: Config> for enum Call where origin: T::Origin {
         fn deposit_event() = default;
 
-        /// Create a new roaming network_server
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn create(origin) {
-            let sender = ensure_signed(origin)?;
-            let roaming_network_server_id = Self::next_roaming_network_server_id()?;
-
-            // Generate a random 128bit value
-            let unique_id = Self::random_value(&sender);
-
-            // Create and store roaming network_server
-            let roaming_network_server = RoamingNetworkServer(unique_id);
-            Self::insert_roaming_network_server(&sender, roaming_network_server_id, roaming_network_server);
-
-            Self::deposit_event(RawEvent::Created(sender, roaming_network_server_id));
-        }
-
-        /// Transfer a roaming network_server to new owner
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn transfer(origin, to: T::AccountId, roaming_network_server_id: T::RoamingNetworkServerIndex) {
-            let sender = ensure_signed(origin)?;
-
-            ensure!(Self::roaming_network_server_owner(roaming_network_server_id) == Some(sender.clone()), "Only owner can transfer roaming network_server");
-
-            Self::update_owner(&to, roaming_network_server_id);
-
-            Self::deposit_event(RawEvent::Transferred(sender, to, roaming_network_server_id));
-        }
-
-        /// Set a price for a roaming network_server for sale
-        /// None to delist the roaming network_server
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn set_price(origin, roaming_network_server_id: T::RoamingNetworkServerIndex, price: Option<BalanceOf<T>>) {
-            let sender = ensure_signed(origin)?;
-
-            ensure!(Self::roaming_network_server_owner(roaming_network_server_id) == Some(sender.clone()), "Only owner can set price for roaming network_server");
-
-            if let Some(ref price) = price {
-                <RoamingNetworkServerPrices<T>>::insert(roaming_network_server_id, price);
-            } else {
-                <RoamingNetworkServerPrices<T>>::remove(roaming_network_server_id);
-            }
-
-            Self::deposit_event(RawEvent::PriceSet(sender, roaming_network_server_id, price));
-        }
-
-        /// Buy a roaming network_server with max price willing to pay
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn buy(origin, roaming_network_server_id: T::RoamingNetworkServerIndex, price: BalanceOf<T>) {
-            let sender = ensure_signed(origin)?;
-
-            let owner = Self::roaming_network_server_owner(roaming_network_server_id);
-            ensure!(owner.is_some(), "RoamingNetworkServer owner does not exist");
-            let owner = owner.unwrap();
-
-            let roaming_network_server_price = Self::roaming_network_server_price(roaming_network_server_id);
-            ensure!(roaming_network_server_price.is_some(), "RoamingNetworkServer not for sale");
-
-            let roaming_network_server_price = roaming_network_server_price.unwrap();
-            ensure!(price >= roaming_network_server_price, "Price is too low");
-
-            T::Currency::transfer(&sender, &owner, roaming_network_server_price, ExistenceRequirement::AllowDeath)?;
-
-            <RoamingNetworkServerPrices<T>>::remove(roaming_network_server_id);
-
-            Self::update_owner(&sender, roaming_network_server_id);
-
-            Self::deposit_event(RawEvent::Sold(owner, sender, roaming_network_server_id, roaming_network_server_price));
-        }
-
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        #[transactional]
         pub fn assign_network_server_to_network(
             origin,
             roaming_network_server_id: T::RoamingNetworkServerIndex,
@@ -190,18 +110,22 @@ decl_module! {
                 ::exists_roaming_network(roaming_network_id).is_ok();
             ensure!(is_roaming_network, "RoamingNetwork does not exist");
 
-            // Ensure that caller of the function is the owner of the network id to assign the network_server to
+            // Ensure that caller of the function is authorized for the network id to assign the network_server to
             ensure!(
-                <roaming_networks::Module<T>>::is_roaming_network_owner(roaming_network_id, sender.clone()).is_ok(),
-                "Only the roaming network owner can assign itself a roaming network server"
+                <roaming_networks::Module<T>>::is_authorized(roaming_network_id, &sender),
+                "Only an account authorized for the roaming network can assign itself a roaming network server"
             );
 
-            Self::associate_network_server_with_network(roaming_network_server_id, roaming_network_id)
-                .expect("Unable to associate network server with network");
-
             // Ensure that the given network_server id already exists
-            let roaming_network_server = Self::roaming_network_server(roaming_network_server_id);
-            ensure!(roaming_network_server.is_some(), "Invalid roaming_network_server_id");
+            ensure!(T::RoamingNetworkServerRegistry::exists(roaming_network_server_id), "Invalid roaming_network_server_id");
+
+            // Ensure that the caller is also authorized for the network_server, so that it may not be taken over
+            ensure!(
+                Self::is_authorized(roaming_network_server_id, &sender),
+                "Only an account authorized for the roaming network server can assign it to a roaming network"
+            );
+
+            Self::associate_network_server_with_network(roaming_network_server_id, roaming_network_id)?;
 
             // Ensure that the network_server is not already owned by a different network
             // Unassign the network_server from any existing network since it may only be owned by one network
@@ -214,6 +138,7 @@ decl_module! {
         }
 
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        #[transactional]
         pub fn assign_network_server_to_operator(
             origin,
             roaming_network_server_id: T::RoamingNetworkServerIndex,
@@ -226,18 +151,22 @@ decl_module! {
                 ::exists_roaming_operator(roaming_operator_id).is_ok();
             ensure!(is_roaming_operator, "RoamingOperator does not exist");
 
-            // Ensure that caller of the function is the owner of the operator id to assign the network to
+            // Ensure that caller of the function is authorized for the operator id to assign the network to
             ensure!(
-                <roaming_operators::Module<T>>::is_roaming_operator_owner(roaming_operator_id, sender.clone()).is_ok(),
-                "Only the roaming operator owner can assign itself a roaming network server"
+                <roaming_operators::Module<T>>::is_authorized(roaming_operator_id, &sender),
+                "Only an account authorized for the roaming operator can assign itself a roaming network server"
             );
 
-            Self::associate_network_server_with_operator(roaming_network_server_id, roaming_operator_id)
-                .expect("Unable to associate network server with operator");
-
             // Ensure that the given network_server id already exists
-            let roaming_network_server = Self::roaming_network_server(roaming_network_server_id);
-            ensure!(roaming_network_server.is_some(), "Invalid roaming_network_server_id");
+            ensure!(T::RoamingNetworkServerRegistry::exists(roaming_network_server_id), "Invalid roaming_network_server_id");
+
+            // Ensure that the caller is also authorized for the network_server, so that it may not be taken over
+            ensure!(
+                Self::is_authorized(roaming_network_server_id, &sender),
+                "Only an account authorized for the roaming network server can assign it to a roaming operator"
+            );
+
+            Self::associate_network_server_with_operator(roaming_network_server_id, roaming_operator_id)?;
 
             // Ensure that the network_server is not already owned by a different operator
             // Unassign the network_server from any existing operator since it may only be owned by one operator
@@ -248,6 +177,20 @@ decl_module! {
 
             Self::deposit_event(RawEvent::AssignedNetworkServerToOperator(sender, roaming_network_server_id, roaming_operator_id));
         }
+
+        /// Remove a roaming network server. Its children are either removed with it or prevent its removal,
+        /// depending on the removal policy.
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(5, 8)]
+        #[transactional]
+        pub fn remove(origin, roaming_network_server_id: T::RoamingNetworkServerIndex) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(T::RoamingNetworkServerRegistry::owner_of(roaming_network_server_id) == Some(sender.clone()), "Only owner can remove roaming network_server");
+
+            Self::remove_roaming_network_server(roaming_network_server_id)?;
+
+            Self::deposit_event(RawEvent::Removed(sender, roaming_network_server_id));
+        }
     }
 }
 
@@ -257,7 +200,7 @@ impl<T: Config> Module<T> {
         sender: T::AccountId,
     ) -> Result<(), DispatchError> {
         ensure!(
-            Self::roaming_network_server_owner(&roaming_network_server_id)
+            T::RoamingNetworkServerRegistry::owner_of(roaming_network_server_id)
                 .map(|owner| owner == sender)
                 .unwrap_or(false),
             "Sender is not owner of RoamingNetworkServer"
@@ -267,11 +210,12 @@ impl<T: Config> Module<T> {
 
     pub fn exists_roaming_network_server(
         roaming_network_server_id: T::RoamingNetworkServerIndex,
-    ) -> Result<RoamingNetworkServer, DispatchError> {
-        match Self::roaming_network_server(roaming_network_server_id) {
-            Some(roaming_network_server) => Ok(roaming_network_server),
-            None => Err(DispatchError::Other("RoamingNetworkServer does not exist")),
-        }
+    ) -> Result<(), DispatchError> {
+        ensure!(
+            T::RoamingNetworkServerRegistry::exists(roaming_network_server_id),
+            "RoamingNetworkServer does not exist"
+        );
+        Ok(())
     }
 
     /// Only push the network server id onto the end of the vector if it does not already exist
@@ -344,36 +288,97 @@ impl<T: Config> Module<T> {
         }
     }
 
-    fn random_value(sender: &T::AccountId) -> [u8; 16] {
-        let payload = (
-            T::Randomness::random(&[0]),
-            sender,
-            <frame_system::Module<T>>::extrinsic_index(),
-            <frame_system::Module<T>>::block_number(),
-        );
-        payload.using_encoded(blake2_128)
+    /// Remove a roaming network server along with its links to its network and operator, cleaning up its children
+    /// according to the removal policy
+    pub fn remove_roaming_network_server(roaming_network_server_id: T::RoamingNetworkServerIndex) -> DispatchResult {
+        Self::exists_roaming_network_server(roaming_network_server_id)?;
+        T::RoamingNetworkServerChildren::remove_children_with_policy(
+            <T as roaming_operators::Config>::RemovalPolicy::get(),
+            roaming_network_server_id,
+        )?;
+
+        if let Some(roaming_network_id) = <RoamingNetworkServerNetwork<T>>::take(roaming_network_server_id) {
+            <RoamingNetworkNetworkServers<T>>::mutate_exists(roaming_network_id, |network_server_ids| {
+                *network_server_ids = without_roaming_entity(network_server_ids.take(), &roaming_network_server_id)
+            });
+        }
+        if let Some(roaming_operator_id) = <RoamingNetworkServerOperator<T>>::take(roaming_network_server_id) {
+            <RoamingOperatorNetworkServers<T>>::mutate_exists(roaming_operator_id, |network_server_ids| {
+                *network_server_ids = without_roaming_entity(network_server_ids.take(), &roaming_network_server_id)
+            });
+        }
+        T::RoamingNetworkServerRegistry::deregister(roaming_network_server_id)
+    }
+
+    /// The network servers that are still assigned to a roaming network
+    pub fn network_network_servers(roaming_network_id: T::RoamingNetworkIndex) -> Vec<T::RoamingNetworkServerIndex> {
+        Self::iter_network_network_servers_from(roaming_network_id, None).collect()
+    }
+
+    /// The network servers that are still assigned to a roaming network in the order they were assigned, starting at
+    /// the given network server. Each network server is only checked to still be assigned as it is read.
+    pub fn iter_network_network_servers_from(
+        roaming_network_id: T::RoamingNetworkIndex,
+        start: Option<T::RoamingNetworkServerIndex>,
+    ) -> impl Iterator<Item = T::RoamingNetworkServerIndex> {
+        Self::roaming_network_network_servers(roaming_network_id)
+            .unwrap_or_default()
+            .into_iter()
+            .skip_while(move |network_server_id| start.map_or(false, |start| *network_server_id != start))
+            .filter(move |network_server_id| {
+                Self::roaming_network_server_network(network_server_id) == Some(roaming_network_id)
+            })
+    }
+}
+
+impl<T: Config> RoamingAuthority<T::AccountId, T::RoamingNetworkServerIndex> for Module<T> {
+    fn is_owner_or_admin(roaming_network_server_id: T::RoamingNetworkServerIndex, account: &T::AccountId) -> bool {
+        T::RoamingNetworkServerRegistry::is_owner_or_admin(roaming_network_server_id, account)
+    }
+
+    /// The owner and admins of a roaming network_server are authorized, as is any account authorized for its network or
+    /// its operator
+    fn is_authorized(roaming_network_server_id: T::RoamingNetworkServerIndex, account: &T::AccountId) -> bool {
+        Self::is_owner_or_admin(roaming_network_server_id, account) ||
+            Self::roaming_network_server_network(roaming_network_server_id)
+                .map_or(false, |parent_id| <roaming_networks::Module<T>>::is_authorized(parent_id, account)) ||
+            Self::roaming_network_server_operators(roaming_network_server_id)
+                .map_or(false, |parent_id| <roaming_operators::Module<T>>::is_authorized(parent_id, account))
+    }
+}
+
+/// The network servers of a roaming network, other than those that have since been assigned to another network
+impl<T: Config> RoamingEntityChildren<RoamingNetworkEntity, T::RoamingNetworkIndex> for Module<T> {
+    fn has_children(roaming_network_id: T::RoamingNetworkIndex) -> bool {
+        Self::roaming_network_network_servers(roaming_network_id).unwrap_or_default().into_iter().any(
+            |network_server_id| Self::roaming_network_server_network(network_server_id) == Some(roaming_network_id),
+        )
     }
 
-    fn next_roaming_network_server_id() -> Result<T::RoamingNetworkServerIndex, DispatchError> {
-        let roaming_network_server_id = Self::roaming_network_servers_count();
-        if roaming_network_server_id == <T::RoamingNetworkServerIndex as Bounded>::max_value() {
-            return Err(DispatchError::Other("RoamingNetworkServers count overflow"));
+    fn remove_children(roaming_network_id: T::RoamingNetworkIndex) -> DispatchResult {
+        for network_server_id in <RoamingNetworkNetworkServers<T>>::take(roaming_network_id).unwrap_or_default() {
+            if Self::roaming_network_server_network(network_server_id) == Some(roaming_network_id) {
+                Self::remove_roaming_network_server(network_server_id)?;
+            }
         }
-        Ok(roaming_network_server_id)
+        Ok(())
     }
+}
 
-    fn insert_roaming_network_server(
-        owner: &T::AccountId,
-        roaming_network_server_id: T::RoamingNetworkServerIndex,
-        roaming_network_server: RoamingNetworkServer,
-    ) {
-        // Create and store roaming network_server
-        <RoamingNetworkServers<T>>::insert(roaming_network_server_id, roaming_network_server);
-        <RoamingNetworkServersCount<T>>::put(roaming_network_server_id + One::one());
-        <RoamingNetworkServerOwners<T>>::insert(roaming_network_server_id, owner.clone());
+/// The network servers of a roaming operator, other than those that have since been assigned to another operator
+impl<T: Config> RoamingEntityChildren<RoamingOperatorEntity, T::RoamingOperatorIndex> for Module<T> {
+    fn has_children(roaming_operator_id: T::RoamingOperatorIndex) -> bool {
+        Self::roaming_operator_network_servers(roaming_operator_id).unwrap_or_default().into_iter().any(
+            |network_server_id| Self::roaming_network_server_operators(network_server_id) == Some(roaming_operator_id),
+        )
     }
 
-    fn update_owner(to: &T::AccountId, roaming_network_server_id: T::RoamingNetworkServerIndex) {
-        <RoamingNetworkServerOwners<T>>::insert(roaming_network_server_id, to);
+    fn remove_children(roaming_operator_id: T::RoamingOperatorIndex) -> DispatchResult {
+        for network_server_id in <RoamingOperatorNetworkServers<T>>::take(roaming_operator_id).unwrap_or_default() {
+            if Self::roaming_network_server_operators(network_server_id) == Some(roaming_operator_id) {
+                Self::remove_roaming_network_server(network_server_id)?;
+            }
+        }
+        Ok(())
     }
 }
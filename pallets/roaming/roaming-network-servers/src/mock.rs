@@ -7,12 +7,14 @@ use crate::{
 
 use frame_support::{
     parameter_types,
+    traits::Get,
     weights::{
         IdentityFee,
         Weight,
     },
 };
 
+use roaming_operators::RoamingRemovalPolicy;
 use sp_core::H256;
 use sp_runtime::{
     testing::Header,
@@ -22,6 +24,7 @@ use sp_runtime::{
     },
     Perbill,
 };
+use std::cell::RefCell;
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
 type Block = frame_system::mocking::MockBlock<Test>;
@@ -87,22 +90,76 @@ impl pallet_transaction_payment::Config for Test {
     type TransactionByteFee = TransactionByteFee;
     type WeightToFee = IdentityFee<u64>;
 }
-impl roaming_operators::Config for Test {
+thread_local! {
+    static REMOVAL_POLICY: RefCell<RoamingRemovalPolicy> = RefCell::new(RoamingRemovalPolicy::Refuse);
+}
+pub struct RemovalPolicy;
+impl RemovalPolicy {
+    pub fn set(removal_policy: RoamingRemovalPolicy) {
+        REMOVAL_POLICY.with(|v| *v.borrow_mut() = removal_policy);
+    }
+}
+impl Get<RoamingRemovalPolicy> for RemovalPolicy {
+    fn get() -> RoamingRemovalPolicy {
+        REMOVAL_POLICY.with(|v| *v.borrow())
+    }
+}
+impl roaming_registry::Config<roaming_registry::Instance1> for Test {
     type Currency = Balances;
+    type EntityDeposit = ();
     type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
     type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+impl roaming_operators::Config for Test {
+    type Currency = Balances;
+    type Event = ();
+    type RemovalPolicy = RemovalPolicy;
+    type RoamingOperatorChildren = ();
     type RoamingOperatorIndex = u64;
+    type RoamingOperatorRegistry = RoamingOperatorRegistry;
+}
+impl roaming_registry::Config<roaming_registry::Instance2> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
 }
 impl roaming_networks::Config for Test {
     type Event = ();
+    type RoamingNetworkChildren = RoamingNetworkServerModule;
     type RoamingNetworkIndex = u64;
+    type RoamingNetworkRegistry = RoamingNetworkRegistry;
+}
+impl roaming_registry::Config<roaming_registry::Instance4> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
 }
 impl Config for Test {
     type Event = ();
+    type RoamingNetworkServerChildren = ();
     type RoamingNetworkServerIndex = u64;
+    type RoamingNetworkServerRegistry = RoamingNetworkServerRegistry;
 }
 
+pub type RoamingNetworkModule = roaming_networks::Module<Test>;
 pub type RoamingNetworkServerModule = Module<Test>;
+pub type RoamingOperatorRegistry = roaming_registry::Module<Test, roaming_registry::Instance1>;
+pub type RoamingNetworkRegistry = roaming_registry::Module<Test, roaming_registry::Instance2>;
+pub type RoamingNetworkServerRegistry = roaming_registry::Module<Test, roaming_registry::Instance4>;
 
 // This function basically just builds a genesis storage key/value store according to
 // our desired mockup.
@@ -6,114 +6,113 @@ use frame_support::{
     assert_noop,
     assert_ok,
 };
+use roaming_operators::RoamingRemovalPolicy;
 
 #[test]
 fn basic_setup_works() {
     new_test_ext().execute_with(|| {
         // Verify Initial Storage
-        assert_eq!(RoamingNetworkServerModule::roaming_network_servers_count(), 0);
-        assert!(RoamingNetworkServerModule::roaming_network_server(0).is_none());
-        assert_eq!(RoamingNetworkServerModule::roaming_network_server_owner(0), None);
-        assert_eq!(RoamingNetworkServerModule::roaming_network_server_price(0), None);
+        assert_eq!(RoamingNetworkServerRegistry::roaming_entities_count(), 0);
+        assert!(RoamingNetworkServerRegistry::roaming_entity(0).is_none());
+        assert_eq!(RoamingNetworkServerRegistry::roaming_entity_owner(0), None);
+        assert_eq!(RoamingNetworkServerRegistry::roaming_entity_price(0), None);
         assert_eq!(Balances::free_balance(1), 10);
         assert_eq!(Balances::free_balance(2), 20);
     });
 }
 
 #[test]
-fn create_works() {
-    new_test_ext().execute_with(|| {
-        // Call Functions
-        assert_ok!(RoamingNetworkServerModule::create(Origin::signed(1)));
-        // Verify Storage
-        assert_eq!(RoamingNetworkServerModule::roaming_network_servers_count(), 1);
-        assert!(RoamingNetworkServerModule::roaming_network_server(0).is_some());
-        assert_eq!(RoamingNetworkServerModule::roaming_network_server_owner(0), Some(1));
-        assert_eq!(RoamingNetworkServerModule::roaming_network_server_price(0), None);
-    });
-}
-
-#[test]
-fn create_handles_basic_errors() {
+fn remove_works() {
     new_test_ext().execute_with(|| {
         // Setup
-        <RoamingNetworkServersCount<Test>>::put(u64::max_value());
+        assert_ok!(RoamingNetworkRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkServerModule::assign_network_server_to_network(Origin::signed(1), 0, 0));
         // Call Functions
-        assert_noop!(RoamingNetworkServerModule::create(Origin::signed(1)), "RoamingNetworkServers count overflow");
+        assert_ok!(RoamingNetworkServerModule::remove(Origin::signed(1), 0));
         // Verify Storage
-        assert_eq!(RoamingNetworkServerModule::roaming_network_servers_count(), u64::max_value());
-        assert!(RoamingNetworkServerModule::roaming_network_server(0).is_none());
-        assert_eq!(RoamingNetworkServerModule::roaming_network_server_owner(0), None);
-        assert_eq!(RoamingNetworkServerModule::roaming_network_server_price(0), None);
+        assert!(RoamingNetworkServerRegistry::roaming_entity(0).is_none());
+        assert_eq!(RoamingNetworkServerRegistry::roaming_entity_owner(0), None);
+        assert_eq!(RoamingNetworkServerModule::roaming_network_server_network(0), None);
+        assert_eq!(RoamingNetworkServerModule::roaming_network_network_servers(0), None);
+        assert!(RoamingNetworkRegistry::roaming_entity(0).is_some());
     });
 }
 
 #[test]
-fn transfer_works() {
+fn remove_handles_basic_errors() {
     new_test_ext().execute_with(|| {
         // Setup
-        assert_ok!(RoamingNetworkServerModule::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(1)));
         // Call Functions
-        assert_ok!(RoamingNetworkServerModule::transfer(Origin::signed(1), 2, 0));
+        assert_noop!(
+            RoamingNetworkServerModule::remove(Origin::signed(2), 0),
+            "Only owner can remove roaming network_server"
+        );
+        assert_noop!(
+            RoamingNetworkServerModule::remove(Origin::signed(1), 1),
+            "Only owner can remove roaming network_server"
+        );
         // Verify Storage
-        assert_eq!(RoamingNetworkServerModule::roaming_network_servers_count(), 1);
-        assert!(RoamingNetworkServerModule::roaming_network_server(0).is_some());
-        assert_eq!(RoamingNetworkServerModule::roaming_network_server_owner(0), Some(2));
-        assert_eq!(RoamingNetworkServerModule::roaming_network_server_price(0), None);
+        assert!(RoamingNetworkServerRegistry::roaming_entity(0).is_some());
+        assert_eq!(RoamingNetworkServerRegistry::roaming_entity_owner(0), Some(1));
     });
 }
 
 #[test]
-fn transfer_handles_basic_errors() {
+fn assign_network_server_refuses_accounts_not_authorized_for_the_network_server() {
     new_test_ext().execute_with(|| {
-        // Setup
-        assert_ok!(RoamingNetworkServerModule::create(Origin::signed(1)));
+        // Setup - network server 0 of account 1 and network 0 of account 2
+        assert_ok!(RoamingNetworkRegistry::create(Origin::signed(2)));
+        assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(1)));
         // Call Functions
         assert_noop!(
-            RoamingNetworkServerModule::transfer(Origin::signed(2), 2, 0),
-            "Only owner can transfer roaming network_server"
-        );
-        assert_noop!(
-            RoamingNetworkServerModule::transfer(Origin::signed(1), 2, 1),
-            "Only owner can transfer roaming network_server"
+            RoamingNetworkServerModule::assign_network_server_to_network(Origin::signed(2), 0, 0),
+            "Only an account authorized for the roaming network server can assign it to a roaming network"
         );
+        assert_ok!(RoamingNetworkServerRegistry::grant_admin(Origin::signed(1), 0, 2));
+        assert_ok!(RoamingNetworkServerModule::assign_network_server_to_network(Origin::signed(2), 0, 0));
         // Verify Storage
-        assert_eq!(RoamingNetworkServerModule::roaming_network_servers_count(), 1);
-        assert!(RoamingNetworkServerModule::roaming_network_server(0).is_some());
-        assert_eq!(RoamingNetworkServerModule::roaming_network_server_owner(0), Some(1));
-        assert_eq!(RoamingNetworkServerModule::roaming_network_server_price(0), None);
+        assert_eq!(RoamingNetworkServerModule::roaming_network_server_network(0), Some(0));
     });
 }
 
 #[test]
-fn set_price_works() {
+fn remove_network_refuses_while_network_servers_exist() {
     new_test_ext().execute_with(|| {
         // Setup
-        assert_ok!(RoamingNetworkServerModule::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkServerModule::assign_network_server_to_network(Origin::signed(1), 0, 0));
         // Call Functions
-        assert_ok!(RoamingNetworkServerModule::set_price(Origin::signed(1), 0, Some(10)));
+        assert_noop!(RoamingNetworkModule::remove(Origin::signed(1), 0), "Roaming entity has children");
+        assert_ok!(RoamingNetworkServerModule::remove(Origin::signed(1), 0));
+        assert_ok!(RoamingNetworkModule::remove(Origin::signed(1), 0));
         // Verify Storage
-        assert_eq!(RoamingNetworkServerModule::roaming_network_servers_count(), 1);
-        assert!(RoamingNetworkServerModule::roaming_network_server(0).is_some());
-        assert_eq!(RoamingNetworkServerModule::roaming_network_server_owner(0), Some(1));
-        assert_eq!(RoamingNetworkServerModule::roaming_network_server_price(0), Some(10));
+        assert!(RoamingNetworkRegistry::roaming_entity(0).is_none());
     });
 }
 
 #[test]
-fn buy_works() {
+fn remove_network_cascades_to_network_servers() {
     new_test_ext().execute_with(|| {
         // Setup
-        assert_ok!(RoamingNetworkServerModule::create(Origin::signed(1)));
-        assert_ok!(RoamingNetworkServerModule::set_price(Origin::signed(1), 0, Some(10)));
+        RemovalPolicy::set(RoamingRemovalPolicy::Cascade);
+        assert_ok!(RoamingNetworkRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkServerModule::assign_network_server_to_network(Origin::signed(1), 0, 0));
+        assert_ok!(RoamingNetworkServerModule::assign_network_server_to_network(Origin::signed(1), 1, 0));
+        // Reassigned network servers are no longer children of the network they were first assigned to
+        assert_ok!(RoamingNetworkServerModule::assign_network_server_to_network(Origin::signed(1), 1, 1));
         // Call Functions
-        assert_ok!(RoamingNetworkServerModule::buy(Origin::signed(2), 0, 10));
+        assert_ok!(RoamingNetworkModule::remove(Origin::signed(1), 0));
         // Verify Storage
-        assert_eq!(RoamingNetworkServerModule::roaming_network_servers_count(), 1);
-        assert!(RoamingNetworkServerModule::roaming_network_server(0).is_some());
-        assert_eq!(RoamingNetworkServerModule::roaming_network_server_owner(0), Some(2));
-        assert_eq!(RoamingNetworkServerModule::roaming_network_server_price(0), None);
-        assert_eq!(Balances::free_balance(1), 20);
-        assert_eq!(Balances::free_balance(2), 10);
+        assert!(RoamingNetworkRegistry::roaming_entity(0).is_none());
+        assert!(RoamingNetworkServerRegistry::roaming_entity(0).is_none());
+        assert_eq!(RoamingNetworkServerModule::roaming_network_network_servers(0), None);
+        assert!(RoamingNetworkServerRegistry::roaming_entity(1).is_some());
+        assert_eq!(RoamingNetworkServerModule::roaming_network_server_network(1), Some(1));
     });
 }
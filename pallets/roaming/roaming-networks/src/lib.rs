@@ -6,30 +6,36 @@ use codec::{
 };
 use frame_support::{
     debug,
+    decl_error,
     decl_event,
     decl_module,
     decl_storage,
     ensure,
-    traits::{
-        Currency,
-        ExistenceRequirement,
-        Get,
-        Randomness,
-    },
+    traits::Get,
+    transactional,
     Parameter,
     StorageMap,
-    StorageValue,
 };
 use frame_system::ensure_signed;
-use sp_io::hashing::blake2_128;
 use sp_runtime::{
     traits::{
         AtLeast32Bit,
         Bounded,
         Member,
-        One,
     },
     DispatchError,
+    DispatchResult,
+};
+use roaming_operators::{
+    without_roaming_entity,
+    RoamingAuthority,
+    RoamingEntityChildren,
+    RoamingNetworkEntity,
+    RoamingOperatorEntity,
+};
+use roaming_registry::{
+    RoamingRegistry,
+    RoamingTradable,
 };
 use sp_std::prelude::*; // Imports Vec
 
@@ -43,56 +49,74 @@ mod tests;
 pub trait Config: frame_system::Config + roaming_operators::Config {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
     type RoamingNetworkIndex: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
+    /// The registry that holds the roaming networks and their owners
+    type RoamingNetworkRegistry: RoamingRegistry<Self::AccountId, Self::RoamingNetworkIndex>;
+    /// The pallets that hold the children of roaming networks
+    type RoamingNetworkChildren: RoamingEntityChildren<RoamingNetworkEntity, Self::RoamingNetworkIndex>;
 }
 
-type BalanceOf<T> =
-    <<T as roaming_operators::Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+/// The largest 24-bit LoRaWAN NetID
+pub const MAX_NET_ID: u32 = 0x00FF_FFFF;
+/// The length in bytes of a DevAddr
+pub const DEVADDR_LENGTH: usize = 4;
+/// The number of NwkID bits of a NetID and of the DevAddrs it allocates for each NetID type, where the type is
+/// the 3 MSBs of the NetID and the number of leading one bits of the DevAddrs
+pub const NWKID_BITS: [u32; 8] = [6, 6, 9, 10, 11, 13, 15, 17];
 
-#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+/// The LoRaWAN NetID of a roaming network
+#[derive(Encode, Decode, Default, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "std", derive(Debug))]
-pub struct RoamingNetwork(pub [u8; 16]);
+pub struct RoamingNetworkNetID {
+    pub net_id: u32,
+    pub net_id_type: u8,
+}
 
 decl_event!(
     pub enum Event<T> where
         <T as frame_system::Config>::AccountId,
         <T as Config>::RoamingNetworkIndex,
         <T as roaming_operators::Config>::RoamingOperatorIndex,
-        Balance = BalanceOf<T>,
     {
-        /// A roaming network is created. (owner, roaming_network_id)
-        Created(AccountId, RoamingNetworkIndex),
-        /// A roaming network is transferred. (from, to, roaming_network_id)
-        Transferred(AccountId, AccountId, RoamingNetworkIndex),
-        /// A roaming network is available for sale. (owner, roaming_network_id, price)
-        PriceSet(AccountId, RoamingNetworkIndex, Option<Balance>),
-        /// A roaming network is sold. (from, to, roaming_network_id, price)
-        Sold(AccountId, AccountId, RoamingNetworkIndex, Balance),
         /// A roaming network is assigned to an operator. (owner of operator, roaming_network_id, roaming_operator_id)
         AssignedNetworkToOperator(AccountId, RoamingNetworkIndex, RoamingOperatorIndex),
+        /// A LoRaWAN NetID is registered for a roaming network. (owner of operator, roaming_network_id, net_id, net_id_type)
+        NetIDRegistered(AccountId, RoamingNetworkIndex, u32, u8),
+        /// A roaming network is removed. (owner, roaming_network_id)
+        Removed(AccountId, RoamingNetworkIndex),
     }
 );
 
+decl_error! {
+    pub enum Error for Module<T: Config> {
+        /// The NetID is larger than 24 bits
+        InvalidNetID,
+        /// The NetID type is larger than 7 or is not the 3 MSBs of the NetID
+        InvalidNetIDType,
+        /// The NetID or its NwkID is already registered for another roaming network
+        NetIDAlreadyRegistered,
+        /// The roaming network is not assigned to a roaming operator
+        NetworkWithoutOperator,
+    }
+}
+
 // This module's storage items.
 decl_storage! {
     trait Store for Module<T: Config> as RoamingNetworks {
 
-        /// Stores all the roaming networks, key is the roaming network id / index
-        pub RoamingNetworks get(fn roaming_network): map hasher(opaque_blake2_256) T::RoamingNetworkIndex => Option<RoamingNetwork>;
-
-        /// Stores the total number of roaming networks. i.e. the next roaming network index
-        pub RoamingNetworksCount get(fn roaming_networks_count): T::RoamingNetworkIndex;
-
-        /// Get roaming network owner
-        pub RoamingNetworkOwners get(fn roaming_network_owner): map hasher(opaque_blake2_256) T::RoamingNetworkIndex => Option<T::AccountId>;
-
-        /// Get roaming network price. None means not for sale.
-        pub RoamingNetworkPrices get(fn roaming_network_price): map hasher(opaque_blake2_256) T::RoamingNetworkIndex => Option<BalanceOf<T>>;
-
         /// Get roaming operator belonging to a network
         pub RoamingNetworkOperator get(fn roaming_network_operator): map hasher(opaque_blake2_256) T::RoamingNetworkIndex => Option<T::RoamingOperatorIndex>;
 
         /// Get roaming operator networks
-        pub RoamingOperatorNetworks get(fn roaming_operator_networks): map hasher(opaque_blake2_256) T::RoamingOperatorIndex => Option<Vec<T::RoamingNetworkIndex>>
+        pub RoamingOperatorNetworks get(fn roaming_operator_networks): map hasher(opaque_blake2_256) T::RoamingOperatorIndex => Option<Vec<T::RoamingNetworkIndex>>;
+
+        /// Get the LoRaWAN NetID of a roaming network
+        pub RoamingNetworkNetIDs get(fn roaming_network_net_id): map hasher(opaque_blake2_256) T::RoamingNetworkIndex => Option<RoamingNetworkNetID>;
+
+        /// Get the roaming network that a LoRaWAN NetID is registered for
+        pub RoamingNetIDNetwork get(fn roaming_net_id_network): map hasher(opaque_blake2_256) u32 => Option<T::RoamingNetworkIndex>;
+
+        /// Get the roaming network that allocates the DevAddrs of a (NetID type, NwkID)
+        pub RoamingNwkIDNetwork get(fn roaming_nwkid_network): map hasher(opaque_blake2_256) (u8, u32) => Option<T::RoamingNetworkIndex>;
     }
 }
 
@@ -102,76 +126,10 @@ decl_module! {
     pub struct Module<T: Config> for enum Call where origin: T::Origin {
         fn deposit_event() = default;
 
-        /// Create a new roaming network
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn create(origin) {
-            let sender = ensure_signed(origin)?;
-            let roaming_network_id = Self::next_roaming_network_id()?;
-
-            // Generate a random 128bit value
-            let unique_id = Self::random_value(&sender);
-
-            // Create and store roaming network
-            let roaming_network = RoamingNetwork(unique_id);
-            Self::insert_roaming_network(&sender, roaming_network_id, roaming_network);
-
-            Self::deposit_event(RawEvent::Created(sender, roaming_network_id));
-        }
-
-        /// Transfer a roaming network to new owner
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn transfer(origin, to: T::AccountId, roaming_network_id: T::RoamingNetworkIndex) {
-            let sender = ensure_signed(origin)?;
-
-            ensure!(Self::roaming_network_owner(roaming_network_id) == Some(sender.clone()), "Only owner can transfer roaming network");
-
-            Self::update_owner(&to, roaming_network_id);
-
-            Self::deposit_event(RawEvent::Transferred(sender, to, roaming_network_id));
-        }
-
-        /// Set a price for a roaming network for sale
-        /// None to delist the roaming network
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn set_price(origin, roaming_network_id: T::RoamingNetworkIndex, price: Option<BalanceOf<T>>) {
-            let sender = ensure_signed(origin)?;
-
-            ensure!(Self::roaming_network_owner(roaming_network_id) == Some(sender.clone()), "Only owner can set price for roaming network");
-
-            if let Some(ref price) = price {
-                <RoamingNetworkPrices<T>>::insert(roaming_network_id, price);
-            } else {
-                <RoamingNetworkPrices<T>>::remove(roaming_network_id);
-            }
-
-            Self::deposit_event(RawEvent::PriceSet(sender, roaming_network_id, price));
-        }
-
-        /// Buy a roaming network with max price willing to pay
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn buy(origin, roaming_network_id: T::RoamingNetworkIndex, price: BalanceOf<T>) {
-            let sender = ensure_signed(origin)?;
-
-            let owner = Self::roaming_network_owner(roaming_network_id);
-            ensure!(owner.is_some(), "RoamingNetwork does not exist");
-            let owner = owner.unwrap();
-
-            let roaming_network_price = Self::roaming_network_price(roaming_network_id);
-            ensure!(roaming_network_price.is_some(), "RoamingNetwork not for sale");
-
-            let roaming_network_price = roaming_network_price.unwrap();
-            ensure!(price >= roaming_network_price, "Price is too low");
-
-            T::Currency::transfer(&sender, &owner, roaming_network_price, ExistenceRequirement::AllowDeath)?;
-
-            <RoamingNetworkPrices<T>>::remove(roaming_network_id);
-
-            Self::update_owner(&sender, roaming_network_id);
-
-            Self::deposit_event(RawEvent::Sold(owner, sender, roaming_network_id, roaming_network_price));
-        }
+        type Error = Error<T>;
 
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        #[transactional]
         pub fn assign_network_to_operator(
           origin,
           roaming_network_id: T::RoamingNetworkIndex,
@@ -184,18 +142,16 @@ decl_module! {
                 ::exists_roaming_operator(roaming_operator_id).is_ok();
             ensure!(is_roaming_operator, "RoamingOperator does not exist");
 
-            // Ensure that caller of the function is the owner of the operator id to assign the network to
+            // Ensure that caller of the function is authorized for the operator id to assign the network to
             ensure!(
-                <roaming_operators::Module<T>>::is_roaming_operator_owner(roaming_operator_id, sender.clone()).is_ok(),
-                "Only the roaming operator owner can assign itself a roaming network"
+                <roaming_operators::Module<T>>::is_authorized(roaming_operator_id, &sender),
+                "Only an account authorized for the roaming operator can assign itself a roaming network"
             );
 
-            Self::associate_network_with_operator(roaming_network_id, roaming_operator_id)
-                .expect("Unable to associate network with operator");
+            Self::associate_network_with_operator(roaming_network_id, roaming_operator_id)?;
 
             // Ensure that the given network id already exists
-            let roaming_network = Self::roaming_network(roaming_network_id);
-            ensure!(roaming_network.is_some(), "Invalid roaming_network_id");
+            ensure!(T::RoamingNetworkRegistry::exists(roaming_network_id), "Invalid roaming_network_id");
 
             // Ensure that the network is not already owned by a different operator
             // Unassign the network from any existing operator since it may only be owned by one operator
@@ -206,6 +162,68 @@ decl_module! {
 
             Self::deposit_event(RawEvent::AssignedNetworkToOperator(sender, roaming_network_id, roaming_operator_id));
         }
+
+        /// Register the 24-bit LoRaWAN NetID of a roaming network, replacing any NetID that it already has.
+        /// Only the owner of the roaming operator of the network may register it.
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(5, 5)]
+        pub fn register_net_id(
+            origin,
+            roaming_network_id: T::RoamingNetworkIndex,
+            net_id: u32,
+            net_id_type: u8
+        ) {
+            let sender = ensure_signed(origin)?;
+
+            Self::exists_roaming_network(roaming_network_id)?;
+            let roaming_operator_id = Self::roaming_network_operator(roaming_network_id)
+                .ok_or(Error::<T>::NetworkWithoutOperator)?;
+            ensure!(
+                <roaming_operators::Module<T>>::is_authorized(roaming_operator_id, &sender),
+                "Only an account authorized for the roaming operator can register the NetID of its roaming network"
+            );
+
+            ensure!(net_id <= MAX_NET_ID, Error::<T>::InvalidNetID);
+            ensure!(
+                (net_id_type as usize) < NWKID_BITS.len() && net_id >> 21 == net_id_type as u32,
+                Error::<T>::InvalidNetIDType
+            );
+            let nwkid = Self::net_id_nwkid(net_id, net_id_type);
+            ensure!(
+                Self::roaming_net_id_network(net_id).map_or(true, |network_id| network_id == roaming_network_id),
+                Error::<T>::NetIDAlreadyRegistered
+            );
+            ensure!(
+                Self::roaming_nwkid_network((net_id_type, nwkid)).map_or(true, |network_id| network_id == roaming_network_id),
+                Error::<T>::NetIDAlreadyRegistered
+            );
+
+            if let Some(previous) = <RoamingNetworkNetIDs<T>>::take(roaming_network_id) {
+                <RoamingNetIDNetwork<T>>::remove(previous.net_id);
+                <RoamingNwkIDNetwork<T>>::remove((previous.net_id_type, Self::net_id_nwkid(previous.net_id, previous.net_id_type)));
+            }
+            <RoamingNetworkNetIDs<T>>::insert(roaming_network_id, RoamingNetworkNetID {
+                net_id,
+                net_id_type,
+            });
+            <RoamingNetIDNetwork<T>>::insert(net_id, roaming_network_id);
+            <RoamingNwkIDNetwork<T>>::insert((net_id_type, nwkid), roaming_network_id);
+
+            Self::deposit_event(RawEvent::NetIDRegistered(sender, roaming_network_id, net_id, net_id_type));
+        }
+
+        /// Remove a roaming network. Its children are either removed with it or prevent its removal, depending
+        /// on the removal policy.
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(5, 9)]
+        #[transactional]
+        pub fn remove(origin, roaming_network_id: T::RoamingNetworkIndex) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(T::RoamingNetworkRegistry::owner_of(roaming_network_id) == Some(sender.clone()), "Only owner can remove roaming network");
+
+            Self::remove_roaming_network(roaming_network_id)?;
+
+            Self::deposit_event(RawEvent::Removed(sender, roaming_network_id));
+        }
     }
 }
 
@@ -215,17 +233,62 @@ impl<T: Config> Module<T> {
         sender: T::AccountId,
     ) -> Result<(), DispatchError> {
         ensure!(
-            Self::roaming_network_owner(&roaming_network_id).map(|owner| owner == sender).unwrap_or(false),
+            T::RoamingNetworkRegistry::owner_of(roaming_network_id).map(|owner| owner == sender).unwrap_or(false),
             "Sender is not owner of RoamingNetwork"
         );
         Ok(())
     }
 
-    pub fn exists_roaming_network(roaming_network_id: T::RoamingNetworkIndex) -> Result<RoamingNetwork, DispatchError> {
-        match Self::roaming_network(roaming_network_id) {
-            Some(roaming_network) => Ok(roaming_network),
-            None => Err(DispatchError::Other("RoamingNetwork does not exist")),
+    pub fn exists_roaming_network(roaming_network_id: T::RoamingNetworkIndex) -> Result<(), DispatchError> {
+        ensure!(T::RoamingNetworkRegistry::exists(roaming_network_id), "RoamingNetwork does not exist");
+        Ok(())
+    }
+
+    /// Remove a roaming network along with its NetID and its link to its operator, cleaning up its children
+    /// according to the removal policy
+    pub fn remove_roaming_network(roaming_network_id: T::RoamingNetworkIndex) -> DispatchResult {
+        Self::exists_roaming_network(roaming_network_id)?;
+        T::RoamingNetworkChildren::remove_children_with_policy(
+            <T as roaming_operators::Config>::RemovalPolicy::get(),
+            roaming_network_id,
+        )?;
+
+        if let Some(previous) = <RoamingNetworkNetIDs<T>>::take(roaming_network_id) {
+            let nwkid = Self::net_id_nwkid(previous.net_id, previous.net_id_type);
+            <RoamingNetIDNetwork<T>>::remove(previous.net_id);
+            <RoamingNwkIDNetwork<T>>::remove((previous.net_id_type, nwkid));
+        }
+        if let Some(roaming_operator_id) = <RoamingNetworkOperator<T>>::take(roaming_network_id) {
+            <RoamingOperatorNetworks<T>>::mutate_exists(roaming_operator_id, |network_ids| {
+                *network_ids = without_roaming_entity(network_ids.take(), &roaming_network_id)
+            });
+        }
+        T::RoamingNetworkRegistry::deregister(roaming_network_id)
+    }
+
+    /// Get the NwkID of a NetID, which is its LSBs
+    pub fn net_id_nwkid(net_id: u32, net_id_type: u8) -> u32 {
+        let nwkid_bits = NWKID_BITS[net_id_type as usize];
+        net_id & ((1 << nwkid_bits) - 1)
+    }
+
+    /// Get the NetID type and NwkID that the AddrPrefix of a DevAddr identifies, where the type is the number of
+    /// leading one bits and the NwkID is the bits that follow the zero bit that ends the prefix.
+    /// Returns None if the DevAddr is not 4 bytes or all of its first 8 bits are ones.
+    pub fn devaddr_nwkid(devaddr: &[u8]) -> Option<(u8, u32)> {
+        if devaddr.len() != DEVADDR_LENGTH {
+            return None;
         }
+        let devaddr = u32::from_be_bytes([devaddr[0], devaddr[1], devaddr[2], devaddr[3]]);
+        let net_id_type = devaddr.leading_ones();
+        let nwkid_bits = *NWKID_BITS.get(net_id_type as usize)?;
+        let nwkid = (devaddr >> (32 - (net_id_type + 1) - nwkid_bits)) & ((1 << nwkid_bits) - 1);
+        Some((net_id_type as u8, nwkid))
+    }
+
+    /// Get the roaming network whose NetID allocated a DevAddr, which is the home network of the device
+    pub fn devaddr_network(devaddr: &[u8]) -> Option<T::RoamingNetworkIndex> {
+        Self::devaddr_nwkid(devaddr).and_then(|nwkid| Self::roaming_nwkid_network(nwkid))
     }
 
     /// Only push the network id onto the end of the vector if it does not already exist
@@ -259,36 +322,54 @@ impl<T: Config> Module<T> {
         }
     }
 
-    fn random_value(sender: &T::AccountId) -> [u8; 16] {
-        let payload = (
-            T::Randomness::random(&[0]),
-            sender,
-            <frame_system::Module<T>>::extrinsic_index(),
-            <frame_system::Module<T>>::block_number(),
-        );
-        payload.using_encoded(blake2_128)
+    /// The networks that are still assigned to a roaming operator
+    pub fn operator_networks(roaming_operator_id: T::RoamingOperatorIndex) -> Vec<T::RoamingNetworkIndex> {
+        Self::iter_operator_networks_from(roaming_operator_id, None).collect()
     }
 
-    fn next_roaming_network_id() -> Result<T::RoamingNetworkIndex, DispatchError> {
-        let roaming_network_id = Self::roaming_networks_count();
-        if roaming_network_id == <T::RoamingNetworkIndex as Bounded>::max_value() {
-            return Err(DispatchError::Other("RoamingNetworks count overflow"));
-        }
-        Ok(roaming_network_id)
+    /// The networks that are still assigned to a roaming operator in the order they were assigned, starting at the
+    /// given network. Each network is only checked to still be assigned as it is read.
+    pub fn iter_operator_networks_from(
+        roaming_operator_id: T::RoamingOperatorIndex,
+        start: Option<T::RoamingNetworkIndex>,
+    ) -> impl Iterator<Item = T::RoamingNetworkIndex> {
+        Self::roaming_operator_networks(roaming_operator_id)
+            .unwrap_or_default()
+            .into_iter()
+            .skip_while(move |network_id| start.map_or(false, |start| *network_id != start))
+            .filter(move |network_id| Self::roaming_network_operator(network_id) == Some(roaming_operator_id))
+    }
+}
+
+impl<T: Config> RoamingAuthority<T::AccountId, T::RoamingNetworkIndex> for Module<T> {
+    fn is_owner_or_admin(roaming_network_id: T::RoamingNetworkIndex, account: &T::AccountId) -> bool {
+        T::RoamingNetworkRegistry::is_owner_or_admin(roaming_network_id, account)
     }
 
-    fn insert_roaming_network(
-        owner: &T::AccountId,
-        roaming_network_id: T::RoamingNetworkIndex,
-        roaming_network: RoamingNetwork,
-    ) {
-        // Create and store roaming network
-        <RoamingNetworks<T>>::insert(roaming_network_id, roaming_network);
-        <RoamingNetworksCount<T>>::put(roaming_network_id + One::one());
-        <RoamingNetworkOwners<T>>::insert(roaming_network_id, owner.clone());
+    /// The owner and admins of a roaming network are authorized, as is any account authorized for its operator
+    fn is_authorized(roaming_network_id: T::RoamingNetworkIndex, account: &T::AccountId) -> bool {
+        Self::is_owner_or_admin(roaming_network_id, account) ||
+            Self::roaming_network_operator(roaming_network_id)
+                .map_or(false, |parent_id| <roaming_operators::Module<T>>::is_authorized(parent_id, account))
+    }
+}
+
+/// The networks of a roaming operator. A network that has since been assigned to another operator may still be
+/// listed, so only the networks that are still assigned to the operator are its children.
+impl<T: Config> RoamingEntityChildren<RoamingOperatorEntity, T::RoamingOperatorIndex> for Module<T> {
+    fn has_children(roaming_operator_id: T::RoamingOperatorIndex) -> bool {
+        Self::roaming_operator_networks(roaming_operator_id)
+            .unwrap_or_default()
+            .into_iter()
+            .any(|network_id| Self::roaming_network_operator(network_id) == Some(roaming_operator_id))
     }
 
-    fn update_owner(to: &T::AccountId, roaming_network_id: T::RoamingNetworkIndex) {
-        <RoamingNetworkOwners<T>>::insert(roaming_network_id, to);
+    fn remove_children(roaming_operator_id: T::RoamingOperatorIndex) -> DispatchResult {
+        for network_id in <RoamingOperatorNetworks<T>>::take(roaming_operator_id).unwrap_or_default() {
+            if Self::roaming_network_operator(network_id) == Some(roaming_operator_id) {
+                Self::remove_roaming_network(network_id)?;
+            }
+        }
+        Ok(())
     }
 }
@@ -11,109 +11,98 @@ use frame_support::{
 fn basic_setup_works() {
     new_test_ext().execute_with(|| {
         // Verify Initial Storage
-        assert_eq!(RoamingNetworkModule::roaming_networks_count(), 0);
-        assert!(RoamingNetworkModule::roaming_network(0).is_none());
-        assert_eq!(RoamingNetworkModule::roaming_network_owner(0), None);
-        assert_eq!(RoamingNetworkModule::roaming_network_price(0), None);
+        assert_eq!(RoamingNetworkRegistry::roaming_entities_count(), 0);
+        assert!(RoamingNetworkRegistry::roaming_entity(0).is_none());
+        assert_eq!(RoamingNetworkRegistry::roaming_entity_owner(0), None);
+        assert_eq!(RoamingNetworkRegistry::roaming_entity_price(0), None);
         assert_eq!(Balances::free_balance(1), 10);
         assert_eq!(Balances::free_balance(2), 20);
     });
 }
 
-#[test]
-fn create_works() {
-    new_test_ext().execute_with(|| {
-        // Call Functions
-        assert_ok!(RoamingNetworkModule::create(Origin::signed(1)));
-        // Verify Storage
-        assert_eq!(RoamingNetworkModule::roaming_networks_count(), 1);
-        assert!(RoamingNetworkModule::roaming_network(0).is_some());
-        assert_eq!(RoamingNetworkModule::roaming_network_owner(0), Some(1));
-        assert_eq!(RoamingNetworkModule::roaming_network_price(0), None);
-    });
+// Network 0 of account 1 is assigned to operator 0 of account 1
+fn setup_network_with_operator() {
+    assert_ok!(RoamingOperatorRegistry::create(Origin::signed(1)));
+    assert_ok!(RoamingNetworkRegistry::create(Origin::signed(1)));
+    assert_ok!(RoamingNetworkModule::assign_network_to_operator(Origin::signed(1), 0, 0));
 }
 
 #[test]
-fn create_handles_basic_errors() {
+fn register_net_id_works() {
     new_test_ext().execute_with(|| {
         // Setup
-        <RoamingNetworksCount<Test>>::put(u64::max_value());
+        setup_network_with_operator();
         // Call Functions
-        assert_noop!(RoamingNetworkModule::create(Origin::signed(1)), "RoamingNetworks count overflow");
+        assert_ok!(RoamingNetworkModule::register_net_id(Origin::signed(1), 0, 0x000013, 0));
         // Verify Storage
-        assert_eq!(RoamingNetworkModule::roaming_networks_count(), u64::max_value());
-        assert!(RoamingNetworkModule::roaming_network(0).is_none());
-        assert_eq!(RoamingNetworkModule::roaming_network_owner(0), None);
-        assert_eq!(RoamingNetworkModule::roaming_network_price(0), None);
-    });
-}
+        assert_eq!(
+            RoamingNetworkModule::roaming_network_net_id(0),
+            Some(RoamingNetworkNetID {
+                net_id: 0x000013,
+                net_id_type: 0,
+            })
+        );
+        assert_eq!(RoamingNetworkModule::roaming_net_id_network(0x000013), Some(0));
+        assert_eq!(RoamingNetworkModule::devaddr_network(&[0x26, 0x01, 0x1B, 0xDA]), Some(0));
+        assert_eq!(RoamingNetworkModule::devaddr_network(&[0x28, 0x01, 0x1B, 0xDA]), None);
 
-#[test]
-fn transfer_works() {
-    new_test_ext().execute_with(|| {
-        // Setup
-        assert_ok!(RoamingNetworkModule::create(Origin::signed(1)));
-        // Call Functions
-        assert_ok!(RoamingNetworkModule::transfer(Origin::signed(1), 2, 0));
-        // Verify Storage
-        assert_eq!(RoamingNetworkModule::roaming_networks_count(), 1);
-        assert!(RoamingNetworkModule::roaming_network(0).is_some());
-        assert_eq!(RoamingNetworkModule::roaming_network_owner(0), Some(2));
-        assert_eq!(RoamingNetworkModule::roaming_network_price(0), None);
+        // Registering another NetID replaces the previous one
+        assert_ok!(RoamingNetworkModule::register_net_id(Origin::signed(1), 0, 0x600008, 3));
+        assert_eq!(RoamingNetworkModule::roaming_net_id_network(0x000013), None);
+        assert_eq!(RoamingNetworkModule::devaddr_network(&[0x26, 0x01, 0x1B, 0xDA]), None);
+        assert_eq!(RoamingNetworkModule::devaddr_network(&[0xE0, 0x20, 0x00, 0x00]), Some(0));
     });
 }
 
 #[test]
-fn transfer_handles_basic_errors() {
+fn register_net_id_handles_basic_errors() {
     new_test_ext().execute_with(|| {
         // Setup
-        assert_ok!(RoamingNetworkModule::create(Origin::signed(1)));
+        setup_network_with_operator();
+        assert_ok!(RoamingNetworkRegistry::create(Origin::signed(1)));
         // Call Functions
         assert_noop!(
-            RoamingNetworkModule::transfer(Origin::signed(2), 2, 0),
-            "Only owner can transfer roaming network"
+            RoamingNetworkModule::register_net_id(Origin::signed(1), 1, 0x000013, 0),
+            Error::<Test>::NetworkWithoutOperator
         );
         assert_noop!(
-            RoamingNetworkModule::transfer(Origin::signed(1), 2, 1),
-            "Only owner can transfer roaming network"
+            RoamingNetworkModule::register_net_id(Origin::signed(2), 0, 0x000013, 0),
+            "Only an account authorized for the roaming operator can register the NetID of its roaming network"
+        );
+        assert_noop!(
+            RoamingNetworkModule::register_net_id(Origin::signed(1), 0, 0x01000000, 0),
+            Error::<Test>::InvalidNetID
+        );
+        assert_noop!(
+            RoamingNetworkModule::register_net_id(Origin::signed(1), 0, 0x000013, 1),
+            Error::<Test>::InvalidNetIDType
+        );
+        assert_noop!(
+            RoamingNetworkModule::register_net_id(Origin::signed(1), 0, 0x000013, 8),
+            Error::<Test>::InvalidNetIDType
         );
-        // Verify Storage
-        assert_eq!(RoamingNetworkModule::roaming_networks_count(), 1);
-        assert!(RoamingNetworkModule::roaming_network(0).is_some());
-        assert_eq!(RoamingNetworkModule::roaming_network_owner(0), Some(1));
-        assert_eq!(RoamingNetworkModule::roaming_network_price(0), None);
-    });
-}
 
-#[test]
-fn set_price_works() {
-    new_test_ext().execute_with(|| {
-        // Setup
-        assert_ok!(RoamingNetworkModule::create(Origin::signed(1)));
-        // Call Functions
-        assert_ok!(RoamingNetworkModule::set_price(Origin::signed(1), 0, Some(10)));
-        // Verify Storage
-        assert_eq!(RoamingNetworkModule::roaming_networks_count(), 1);
-        assert!(RoamingNetworkModule::roaming_network(0).is_some());
-        assert_eq!(RoamingNetworkModule::roaming_network_owner(0), Some(1));
-        assert_eq!(RoamingNetworkModule::roaming_network_price(0), Some(10));
+        // Network 1 may not take the NetID or the NwkID of network 0
+        assert_ok!(RoamingNetworkModule::assign_network_to_operator(Origin::signed(1), 1, 0));
+        assert_ok!(RoamingNetworkModule::register_net_id(Origin::signed(1), 0, 0x000013, 0));
+        assert_noop!(
+            RoamingNetworkModule::register_net_id(Origin::signed(1), 1, 0x000013, 0),
+            Error::<Test>::NetIDAlreadyRegistered
+        );
+        assert_noop!(
+            RoamingNetworkModule::register_net_id(Origin::signed(1), 1, 0x000053, 0),
+            Error::<Test>::NetIDAlreadyRegistered
+        );
+        assert_eq!(RoamingNetworkModule::roaming_network_net_id(1), None);
     });
 }
 
 #[test]
-fn buy_works() {
-    new_test_ext().execute_with(|| {
-        // Setup
-        assert_ok!(RoamingNetworkModule::create(Origin::signed(1)));
-        assert_ok!(RoamingNetworkModule::set_price(Origin::signed(1), 0, Some(10)));
-        // Call Functions
-        assert_ok!(RoamingNetworkModule::buy(Origin::signed(2), 0, 10));
-        // Verify Storage
-        assert_eq!(RoamingNetworkModule::roaming_networks_count(), 1);
-        assert!(RoamingNetworkModule::roaming_network(0).is_some());
-        assert_eq!(RoamingNetworkModule::roaming_network_owner(0), Some(2));
-        assert_eq!(RoamingNetworkModule::roaming_network_price(0), None);
-        assert_eq!(Balances::free_balance(1), 20);
-        assert_eq!(Balances::free_balance(2), 10);
-    });
+fn devaddr_nwkid_works() {
+    assert_eq!(RoamingNetworkModule::devaddr_nwkid(&[0x26, 0x01, 0x1B, 0xDA]), Some((0, 0x13)));
+    assert_eq!(RoamingNetworkModule::devaddr_nwkid(&[0xBF, 0xFF, 0xFF, 0xFF]), Some((1, 0x3F)));
+    assert_eq!(RoamingNetworkModule::devaddr_nwkid(&[0xE0, 0x20, 0x00, 0x00]), Some((3, 0x008)));
+    assert_eq!(RoamingNetworkModule::devaddr_nwkid(&[0xFE, 0x00, 0x00, 0x80]), Some((7, 0x1)));
+    assert_eq!(RoamingNetworkModule::devaddr_nwkid(&[0xFF, 0x00, 0x00, 0x00]), None);
+    assert_eq!(RoamingNetworkModule::devaddr_nwkid(&[0x26, 0x01, 0x1B]), None);
 }
@@ -2,32 +2,42 @@
 use codec::{
     Decode,
     Encode,
+    FullCodec,
+    FullEncode,
 };
 use frame_support::{
     decl_event,
     decl_module,
-    decl_storage,
-    dispatch,
     ensure,
+    storage::{
+        generator::StorageDoubleMap,
+        unhashed,
+    },
     traits::{
+        BalanceStatus,
         Currency,
-        ExistenceRequirement,
         Get,
-        Randomness,
+        ReservableCurrency,
     },
+    transactional,
     Parameter,
+    ReversibleStorageHasher,
 };
 use frame_system::ensure_signed;
-use sp_io::hashing::blake2_128;
+use roaming_registry::{
+    RoamingRegistry,
+    RoamingTradable,
+};
 use sp_runtime::{
     traits::{
         AtLeast32Bit,
         Bounded,
         Member,
-        One,
     },
     DispatchError,
+    DispatchResult,
 };
+use sp_std::prelude::*; // Imports Vec
 
 #[cfg(test)]
 mod mock;
@@ -39,124 +49,186 @@ mod tests;
 pub trait Config: frame_system::Config {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
     type RoamingOperatorIndex: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
-    type Currency: Currency<Self::AccountId>;
-    type Randomness: Randomness<Self::Hash>;
+    /// The registry that holds the roaming operators and their owners
+    type RoamingOperatorRegistry: RoamingRegistry<Self::AccountId, Self::RoamingOperatorIndex>;
+    type Currency: ReservableCurrency<Self::AccountId>;
+    /// Whether removing a roaming entity that still has children is refused or removes its children with it
+    type RemovalPolicy: Get<RoamingRemovalPolicy>;
+    /// The pallets that hold the children of roaming operators
+    type RoamingOperatorChildren: RoamingEntityChildren<RoamingOperatorEntity, Self::RoamingOperatorIndex>;
 }
 
 type BalanceOf<T> = <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 
-#[derive(Encode, Decode, Clone, PartialEq, Eq)]
-#[cfg_attr(feature = "std", derive(Debug))]
-pub struct RoamingOperator(pub [u8; 16]);
+/// Authority over the entities of the roaming entity graph. The owner and admins of an entity have authority over
+/// it, and authority is delegated down the links of the graph from operators to networks, network servers,
+/// organizations and devices, so that an account authorized for an operator may manage everything under it.
+pub trait RoamingAuthority<AccountId, RoamingEntityIndex> {
+    /// Whether an account is the owner or an admin of the entity itself
+    fn is_owner_or_admin(roaming_entity_id: RoamingEntityIndex, account: &AccountId) -> bool;
 
-decl_event!(
-    pub enum Event<T> where
-        <T as frame_system::Config>::AccountId,
-        <T as Config>::RoamingOperatorIndex,
-        Balance = BalanceOf<T>,
-    {
-        /// A roaming operator is created. (owner, roaming_operator_id)
-        Created(AccountId, RoamingOperatorIndex),
-        /// A roaming operator is transferred. (from, to, roaming_operator_id)
-        Transferred(AccountId, AccountId, RoamingOperatorIndex),
-        /// A roaming operator is available for sale. (owner, roaming_operator_id, price)
-        PriceSet(AccountId, RoamingOperatorIndex, Option<Balance>),
-        /// A roaming operator is sold. (from, to, roaming_operator_id, price)
-        Sold(AccountId, AccountId, RoamingOperatorIndex, Balance),
-    }
-);
+    /// Whether an account is authorized for the entity or for any entity above it in the graph
+    fn is_authorized(roaming_entity_id: RoamingEntityIndex, account: &AccountId) -> bool;
 
-// This module's storage items.
-decl_storage! {
-    trait Store for Module<T: Config> as RoamingOperators {
-        /// Stores all the roaming operators, key is the roaming operator id / index
-        pub RoamingOperators get(fn roaming_operator): map hasher(opaque_blake2_256) T::RoamingOperatorIndex => Option<RoamingOperator>;
-
-        /// Stores the total number of roaming operators. i.e. the next roaming operator index
-        pub RoamingOperatorsCount get(fn roaming_operators_count): T::RoamingOperatorIndex;
-
-        /// Get roaming operator owner
-        pub RoamingOperatorOwners get(fn roaming_operator_owner): map hasher(opaque_blake2_256) T::RoamingOperatorIndex => Option<T::AccountId>;
-
-        /// Get roaming operator price. None means not for sale.
-        pub RoamingOperatorPrices get(fn roaming_operator_price): map hasher(opaque_blake2_256) T::RoamingOperatorIndex => Option<BalanceOf<T>>
+    fn ensure_authorized(roaming_entity_id: RoamingEntityIndex, account: &AccountId) -> DispatchResult {
+        ensure!(Self::is_authorized(roaming_entity_id, account), "Sender is not authorized for the roaming entity");
+        Ok(())
     }
 }
 
-// The module's dispatchable functions.
-decl_module! {
-    /// The module declaration.
-    pub struct Module<T: Config> for enum Call where origin: T::Origin {
-        fn deposit_event() = default;
-
-        /// Create a new roaming operator
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn create(origin) -> dispatch::DispatchResult {
-            let sender = ensure_signed(origin)?;
-            let roaming_operator_id = Self::next_roaming_operator_id()?;
-
-            // Generate a random 128bit value
-            let unique_id = Self::random_value(&sender);
+/// What happens when a roaming entity that still has children is removed
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum RoamingRemovalPolicy {
+    /// The removal is refused until its children are removed
+    Refuse,
+    /// Its children are removed with it, and their children with them
+    Cascade,
+}
 
-            // Create and store roaming operator
-            let roaming_operator = RoamingOperator(unique_id);
-            Self::insert_roaming_operator(&sender, roaming_operator_id, roaming_operator);
+impl Default for RoamingRemovalPolicy {
+    fn default() -> Self {
+        RoamingRemovalPolicy::Refuse
+    }
+}
 
-            Self::deposit_event(RawEvent::Created(sender, roaming_operator_id));
-            Ok(())
-        }
+/// The kinds of roaming entities whose children are held by other pallets
+pub struct RoamingOperatorEntity;
+pub struct RoamingNetworkEntity;
+pub struct RoamingNetworkServerEntity;
+pub struct RoamingOrganizationEntity;
+pub struct RoamingDeviceEntity;
+pub struct RoamingAccountingPolicyEntity;
+pub struct RoamingSessionEntity;
+
+/// The children that a pallet holds of the roaming entities of a kind, which are linked to their parent by the
+/// reverse indexes of the pallet. Pallets that hold children of the same kind are combined as a tuple.
+pub trait RoamingEntityChildren<RoamingEntityKind, RoamingEntityIndex: Copy> {
+    /// Whether the entity has any children
+    fn has_children(roaming_entity_id: RoamingEntityIndex) -> bool;
+
+    /// Remove the children of the entity along with the reverse indexes that link them to it
+    fn remove_children(roaming_entity_id: RoamingEntityIndex) -> DispatchResult;
+
+    /// Clean up the children of an entity that is being removed, refusing if it has children unless the removal
+    /// policy cascades
+    fn remove_children_with_policy(
+        policy: RoamingRemovalPolicy,
+        roaming_entity_id: RoamingEntityIndex,
+    ) -> DispatchResult {
+        ensure!(
+            policy == RoamingRemovalPolicy::Cascade || !Self::has_children(roaming_entity_id),
+            "Roaming entity has children"
+        );
+        Self::remove_children(roaming_entity_id)
+    }
+}
 
-        /// Transfer a roaming operator to new owner
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn transfer(origin, to: T::AccountId, roaming_operator_id: T::RoamingOperatorIndex) {
-            let sender = ensure_signed(origin)?;
+impl<RoamingEntityKind, RoamingEntityIndex: Copy> RoamingEntityChildren<RoamingEntityKind, RoamingEntityIndex> for () {
+    fn has_children(_roaming_entity_id: RoamingEntityIndex) -> bool {
+        false
+    }
 
-            ensure!(Self::roaming_operator_owner(roaming_operator_id) == Some(sender.clone()), "Only owner can transfer roaming operator");
+    fn remove_children(_roaming_entity_id: RoamingEntityIndex) -> DispatchResult {
+        Ok(())
+    }
+}
 
-            Self::update_owner(&to, roaming_operator_id);
+/// Remove an entity from the entities listed in a reverse index, leaving no list rather than an empty one
+pub fn without_roaming_entity<RoamingEntityIndex: PartialEq>(
+    roaming_entity_ids: Option<Vec<RoamingEntityIndex>>,
+    roaming_entity_id: &RoamingEntityIndex,
+) -> Option<Vec<RoamingEntityIndex>> {
+    roaming_entity_ids
+        .map(|mut ids| {
+            ids.retain(|id| id != roaming_entity_id);
+            ids
+        })
+        .filter(|ids| !ids.is_empty())
+}
 
-            Self::deposit_event(RawEvent::Transferred(sender, to, roaming_operator_id));
+/// Iterate the second keys of the entries of a double map under a first key in the order they are stored, starting at
+/// the entry of the given second key, or where it would be stored if it has no entry. Entries are read one at a time,
+/// so a page of them can be read without reading the whole prefix.
+pub fn iter_double_map_keys_from<Map, K1, K2>(k1: K1, start: Option<K2>) -> impl Iterator<Item = K2>
+where
+    Map: StorageDoubleMap<K1, K2, ()>,
+    Map::Hasher2: ReversibleStorageHasher,
+    K1: FullEncode + Clone,
+    K2: FullCodec,
+{
+    let prefix = Map::storage_double_map_final_key1(k1.clone());
+    let first_key = match start {
+        Some(k2) => {
+            let start_key = Map::storage_double_map_final_key(k1, k2);
+            if unhashed::exists(&start_key) {
+                Some(start_key)
+            } else {
+                sp_io::storage::next_key(&start_key)
+            }
         }
+        None => sp_io::storage::next_key(&prefix),
+    };
+    let prefix_len = prefix.len();
+    sp_std::iter::successors(first_key, |key| sp_io::storage::next_key(key))
+        .take_while(move |key| key.starts_with(&prefix))
+        .filter_map(move |key| K2::decode(&mut Map::Hasher2::reverse(&key[prefix_len..])).ok())
+}
 
-        /// Set a price for a roaming operator for sale
-        /// None to delist the roaming operator
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn set_price(origin, roaming_operator_id: T::RoamingOperatorIndex, price: Option<BalanceOf<T>>) {
-            let sender = ensure_signed(origin)?;
-
-            ensure!(Self::roaming_operator_owner(roaming_operator_id) == Some(sender.clone()), "Only owner can set price for roaming operator");
-
-            if let Some(ref price) = price {
-                <RoamingOperatorPrices<T>>::insert(roaming_operator_id, price);
-            } else {
-                <RoamingOperatorPrices<T>>::remove(roaming_operator_id);
+macro_rules! impl_roaming_entity_children_for_tuples {
+    ($($children:ident),+) => {
+        impl<
+            RoamingEntityKind,
+            RoamingEntityIndex: Copy,
+            $($children: RoamingEntityChildren<RoamingEntityKind, RoamingEntityIndex>),+
+        > RoamingEntityChildren<RoamingEntityKind, RoamingEntityIndex> for ($($children,)+) {
+            fn has_children(roaming_entity_id: RoamingEntityIndex) -> bool {
+                $($children::has_children(roaming_entity_id))||+
             }
 
-            Self::deposit_event(RawEvent::PriceSet(sender, roaming_operator_id, price));
+            fn remove_children(roaming_entity_id: RoamingEntityIndex) -> DispatchResult {
+                $($children::remove_children(roaming_entity_id)?;)+
+                Ok(())
+            }
         }
+    };
+}
 
-        /// Buy a roaming operator with max price willing to pay
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn buy(origin, roaming_operator_id: T::RoamingOperatorIndex, price: BalanceOf<T>) {
-            let sender = ensure_signed(origin)?;
-
-            let owner = Self::roaming_operator_owner(roaming_operator_id);
-            ensure!(owner.is_some(), "RoamingOperator owner does not exist");
-            let owner = owner.unwrap();
+impl_roaming_entity_children_for_tuples!(A);
+impl_roaming_entity_children_for_tuples!(A, B);
+impl_roaming_entity_children_for_tuples!(A, B, C);
+impl_roaming_entity_children_for_tuples!(A, B, C, D);
+impl_roaming_entity_children_for_tuples!(A, B, C, D, E);
+impl_roaming_entity_children_for_tuples!(A, B, C, D, E, F);
 
-            let roaming_operator_price = Self::roaming_operator_price(roaming_operator_id);
-            ensure!(roaming_operator_price.is_some(), "RoamingOperator not for sale");
+decl_event!(
+    pub enum Event<T> where
+        <T as frame_system::Config>::AccountId,
+        <T as Config>::RoamingOperatorIndex,
+    {
+        /// A roaming operator is removed. (owner, roaming_operator_id)
+        Removed(AccountId, RoamingOperatorIndex),
+    }
+);
 
-            let roaming_operator_price = roaming_operator_price.unwrap();
-            ensure!(price >= roaming_operator_price, "Price is too low");
+// The module's dispatchable functions.
+decl_module! {
+    /// The module declaration.
+    pub struct Module<T: Config> for enum Call where origin: T::Origin {
+        fn deposit_event() = default;
 
-            T::Currency::transfer(&sender, &owner, roaming_operator_price, ExistenceRequirement::AllowDeath)?;
+        /// Remove a roaming operator. Its children are either removed with it or prevent its removal, depending
+        /// on the removal policy.
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(3, 4)]
+        #[transactional]
+        pub fn remove(origin, roaming_operator_id: T::RoamingOperatorIndex) {
+            let sender = ensure_signed(origin)?;
 
-            <RoamingOperatorPrices<T>>::remove(roaming_operator_id);
+            ensure!(T::RoamingOperatorRegistry::owner_of(roaming_operator_id) == Some(sender.clone()), "Only owner can remove roaming operator");
 
-            Self::update_owner(&sender, roaming_operator_id);
+            Self::remove_roaming_operator(roaming_operator_id)?;
 
-            Self::deposit_event(RawEvent::Sold(owner, sender, roaming_operator_id, roaming_operator_price));
+            Self::deposit_event(RawEvent::Removed(sender, roaming_operator_id));
         }
     }
 }
@@ -167,51 +239,48 @@ impl<T: Config> Module<T> {
         sender: T::AccountId,
     ) -> Result<(), DispatchError> {
         ensure!(
-            Self::roaming_operator_owner(&roaming_operator_id).map(|owner| owner == sender).unwrap_or(false),
+            T::RoamingOperatorRegistry::owner_of(roaming_operator_id).map(|owner| owner == sender).unwrap_or(false),
             "Sender is not owner of RoamingOperator"
         );
         Ok(())
     }
 
-    pub fn exists_roaming_operator(
-        roaming_operator_id: T::RoamingOperatorIndex,
-    ) -> Result<RoamingOperator, DispatchError> {
-        match Self::roaming_operator(roaming_operator_id) {
-            Some(roaming_operator) => Ok(roaming_operator),
-            None => Err(DispatchError::Other("RoamingOperator does not exist")),
-        }
+    pub fn exists_roaming_operator(roaming_operator_id: T::RoamingOperatorIndex) -> Result<(), DispatchError> {
+        ensure!(T::RoamingOperatorRegistry::exists(roaming_operator_id), "RoamingOperator does not exist");
+        Ok(())
     }
 
-    fn random_value(sender: &T::AccountId) -> [u8; 16] {
-        let payload = (
-            T::Randomness::random(&[0]),
-            sender,
-            <frame_system::Module<T>>::extrinsic_index(),
-            <frame_system::Module<T>>::block_number(),
-        );
-        payload.using_encoded(blake2_128)
+    /// Remove a roaming operator, cleaning up its children according to the removal policy
+    pub fn remove_roaming_operator(roaming_operator_id: T::RoamingOperatorIndex) -> DispatchResult {
+        Self::exists_roaming_operator(roaming_operator_id)?;
+        T::RoamingOperatorChildren::remove_children_with_policy(T::RemovalPolicy::get(), roaming_operator_id)?;
+        T::RoamingOperatorRegistry::deregister(roaming_operator_id)
     }
 
-    fn next_roaming_operator_id() -> Result<T::RoamingOperatorIndex, DispatchError> {
-        let roaming_operator_id = Self::roaming_operators_count();
-        if roaming_operator_id == <T::RoamingOperatorIndex as Bounded>::max_value() {
-            return Err(DispatchError::Other("RoamingOperators count overflow"));
-        }
-        Ok(roaming_operator_id)
+    /// Reserve the deposit of a roaming or mining entity from the account that creates it
+    pub fn reserve_entity_deposit(creator: &T::AccountId, deposit: BalanceOf<T>) -> DispatchResult {
+        T::Currency::reserve(creator, deposit)
     }
 
-    fn insert_roaming_operator(
-        owner: &T::AccountId,
-        roaming_operator_id: T::RoamingOperatorIndex,
-        roaming_operator: RoamingOperator,
-    ) {
-        // Create and store roaming operator
-        <RoamingOperators<T>>::insert(roaming_operator_id, roaming_operator);
-        <RoamingOperatorsCount<T>>::put(roaming_operator_id + One::one());
-        <RoamingOperatorOwners<T>>::insert(roaming_operator_id, owner.clone());
+    /// Move the deposit of a roaming or mining entity from its previous owner to its new owner, where it remains
+    /// reserved
+    pub fn move_entity_deposit(from: &T::AccountId, to: &T::AccountId, deposit: BalanceOf<T>) -> DispatchResult {
+        T::Currency::repatriate_reserved(from, to, deposit, BalanceStatus::Reserved)?;
+        Ok(())
+    }
+
+    /// Return the deposit of a removed roaming or mining entity to its owner
+    pub fn unreserve_entity_deposit(owner: &T::AccountId, deposit: BalanceOf<T>) {
+        T::Currency::unreserve(owner, deposit);
+    }
+}
+
+impl<T: Config> RoamingAuthority<T::AccountId, T::RoamingOperatorIndex> for Module<T> {
+    fn is_owner_or_admin(roaming_operator_id: T::RoamingOperatorIndex, account: &T::AccountId) -> bool {
+        T::RoamingOperatorRegistry::is_owner_or_admin(roaming_operator_id, account)
     }
 
-    fn update_owner(to: &T::AccountId, roaming_operator_id: T::RoamingOperatorIndex) {
-        <RoamingOperatorOwners<T>>::insert(roaming_operator_id, to);
+    fn is_authorized(roaming_operator_id: T::RoamingOperatorIndex, account: &T::AccountId) -> bool {
+        Self::is_owner_or_admin(roaming_operator_id, account)
     }
 }
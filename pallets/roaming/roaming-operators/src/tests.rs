@@ -11,109 +11,105 @@ use frame_support::{
 fn basic_setup_works() {
     new_test_ext().execute_with(|| {
         // Verify Initial Storage
-        assert_eq!(RoamingOperatorModule::roaming_operators_count(), 0);
-        assert!(RoamingOperatorModule::roaming_operator(0).is_none());
-        assert_eq!(RoamingOperatorModule::roaming_operator_owner(0), None);
-        assert_eq!(RoamingOperatorModule::roaming_operator_price(0), None);
+        assert_eq!(RoamingOperatorRegistry::roaming_entities_count(), 0);
+        assert!(RoamingOperatorRegistry::roaming_entity(0).is_none());
+        assert_eq!(RoamingOperatorRegistry::roaming_entity_owner(0), None);
+        assert_eq!(RoamingOperatorRegistry::roaming_entity_price(0), None);
         assert_eq!(Balances::free_balance(1), 10);
         assert_eq!(Balances::free_balance(2), 20);
     });
 }
 
 #[test]
-fn create_works() {
+fn grant_admin_works() {
     new_test_ext().execute_with(|| {
+        // Setup
+        assert_ok!(RoamingOperatorRegistry::create(Origin::signed(1)));
         // Call Functions
-        assert_ok!(RoamingOperatorModule::create(Origin::signed(1)));
+        assert_ok!(RoamingOperatorRegistry::grant_admin(Origin::signed(1), 0, 2));
         // Verify Storage
-        assert_eq!(RoamingOperatorModule::roaming_operators_count(), 1);
-        assert!(RoamingOperatorModule::roaming_operator(0).is_some());
-        assert_eq!(RoamingOperatorModule::roaming_operator_owner(0), Some(1));
-        assert_eq!(RoamingOperatorModule::roaming_operator_price(0), None);
+        assert_eq!(RoamingOperatorRegistry::roaming_entity_admins(0), Some(vec![2]));
+        assert!(RoamingOperatorModule::is_authorized(0, &1));
+        assert!(RoamingOperatorModule::is_authorized(0, &2));
+        assert!(!RoamingOperatorModule::is_authorized(0, &3));
     });
 }
 
 #[test]
-fn create_handles_basic_errors() {
+fn revoke_admin_works() {
     new_test_ext().execute_with(|| {
         // Setup
-        <RoamingOperatorsCount<Test>>::put(u64::max_value());
+        assert_ok!(RoamingOperatorRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingOperatorRegistry::grant_admin(Origin::signed(1), 0, 2));
         // Call Functions
-        assert_noop!(RoamingOperatorModule::create(Origin::signed(1)), "RoamingOperators count overflow");
+        assert_noop!(
+            RoamingOperatorRegistry::revoke_admin(Origin::signed(1), 0, 3),
+            "Account is not an admin of roaming entity"
+        );
+        assert_ok!(RoamingOperatorRegistry::revoke_admin(Origin::signed(1), 0, 2));
         // Verify Storage
-        assert_eq!(RoamingOperatorModule::roaming_operators_count(), u64::max_value());
-        assert!(RoamingOperatorModule::roaming_operator(0).is_none());
-        assert_eq!(RoamingOperatorModule::roaming_operator_owner(0), None);
-        assert_eq!(RoamingOperatorModule::roaming_operator_price(0), None);
+        assert_eq!(RoamingOperatorRegistry::roaming_entity_admins(0), None);
+        assert!(!RoamingOperatorModule::is_authorized(0, &2));
     });
 }
 
 #[test]
-fn transfer_works() {
+fn transfer_revokes_admins() {
     new_test_ext().execute_with(|| {
         // Setup
-        assert_ok!(RoamingOperatorModule::create(Origin::signed(1)));
+        assert_ok!(RoamingOperatorRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingOperatorRegistry::grant_admin(Origin::signed(1), 0, 2));
         // Call Functions
-        assert_ok!(RoamingOperatorModule::transfer(Origin::signed(1), 2, 0));
+        assert_ok!(RoamingOperatorRegistry::transfer(Origin::signed(1), 3, 0));
         // Verify Storage
-        assert_eq!(RoamingOperatorModule::roaming_operators_count(), 1);
-        assert!(RoamingOperatorModule::roaming_operator(0).is_some());
-        assert_eq!(RoamingOperatorModule::roaming_operator_owner(0), Some(2));
-        assert_eq!(RoamingOperatorModule::roaming_operator_price(0), None);
+        assert_eq!(RoamingOperatorRegistry::roaming_entity_admins(0), None);
+        assert!(!RoamingOperatorModule::is_authorized(0, &1));
+        assert!(!RoamingOperatorModule::is_authorized(0, &2));
+        assert!(RoamingOperatorModule::is_authorized(0, &3));
     });
 }
 
 #[test]
-fn transfer_handles_basic_errors() {
+fn remove_works() {
     new_test_ext().execute_with(|| {
         // Setup
-        assert_ok!(RoamingOperatorModule::create(Origin::signed(1)));
+        assert_ok!(RoamingOperatorRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingOperatorRegistry::set_price(Origin::signed(1), 0, Some(10)));
         // Call Functions
-        assert_noop!(
-            RoamingOperatorModule::transfer(Origin::signed(2), 2, 0),
-            "Only owner can transfer roaming operator"
-        );
-        assert_noop!(
-            RoamingOperatorModule::transfer(Origin::signed(1), 2, 1),
-            "Only owner can transfer roaming operator"
-        );
+        assert_ok!(RoamingOperatorModule::remove(Origin::signed(1), 0));
         // Verify Storage
-        assert_eq!(RoamingOperatorModule::roaming_operators_count(), 1);
-        assert!(RoamingOperatorModule::roaming_operator(0).is_some());
-        assert_eq!(RoamingOperatorModule::roaming_operator_owner(0), Some(1));
-        assert_eq!(RoamingOperatorModule::roaming_operator_price(0), None);
+        assert_eq!(RoamingOperatorRegistry::roaming_entities_count(), 1);
+        assert!(RoamingOperatorRegistry::roaming_entity(0).is_none());
+        assert_eq!(RoamingOperatorRegistry::roaming_entity_owner(0), None);
+        assert_eq!(RoamingOperatorRegistry::roaming_entity_price(0), None);
     });
 }
 
 #[test]
-fn set_price_works() {
+fn remove_handles_basic_errors() {
     new_test_ext().execute_with(|| {
         // Setup
-        assert_ok!(RoamingOperatorModule::create(Origin::signed(1)));
+        assert_ok!(RoamingOperatorRegistry::create(Origin::signed(1)));
         // Call Functions
-        assert_ok!(RoamingOperatorModule::set_price(Origin::signed(1), 0, Some(10)));
+        assert_noop!(RoamingOperatorModule::remove(Origin::signed(2), 0), "Only owner can remove roaming operator");
+        assert_noop!(RoamingOperatorModule::remove(Origin::signed(1), 1), "Only owner can remove roaming operator");
         // Verify Storage
-        assert_eq!(RoamingOperatorModule::roaming_operators_count(), 1);
-        assert!(RoamingOperatorModule::roaming_operator(0).is_some());
-        assert_eq!(RoamingOperatorModule::roaming_operator_owner(0), Some(1));
-        assert_eq!(RoamingOperatorModule::roaming_operator_price(0), Some(10));
+        assert!(RoamingOperatorRegistry::roaming_entity(0).is_some());
+        assert_eq!(RoamingOperatorRegistry::roaming_entity_owner(0), Some(1));
     });
 }
 
 #[test]
-fn buy_works() {
+fn remove_refunds_deposit() {
     new_test_ext().execute_with(|| {
         // Setup
-        assert_ok!(RoamingOperatorModule::create(Origin::signed(1)));
-        assert_ok!(RoamingOperatorModule::set_price(Origin::signed(1), 0, Some(10)));
+        assert_ok!(RoamingOperatorRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingOperatorRegistry::transfer(Origin::signed(1), 2, 0));
         // Call Functions
-        assert_ok!(RoamingOperatorModule::buy(Origin::signed(2), 0, 10));
+        assert_ok!(RoamingOperatorModule::remove(Origin::signed(2), 0));
         // Verify Storage
-        assert_eq!(RoamingOperatorModule::roaming_operators_count(), 1);
-        assert!(RoamingOperatorModule::roaming_operator(0).is_some());
-        assert_eq!(RoamingOperatorModule::roaming_operator_owner(0), Some(2));
-        assert_eq!(RoamingOperatorModule::roaming_operator_price(0), None);
-        assert_eq!(Balances::free_balance(1), 20);
-        assert_eq!(Balances::free_balance(2), 10);
+        assert_eq!(RoamingOperatorRegistry::roaming_entity_deposit(0), None);
+        assert_eq!(Balances::free_balance(2), 22);
+        assert_eq!(Balances::reserved_balance(2), 0);
     });
 }
@@ -10,24 +10,30 @@ use frame_support::{
     decl_module,
     decl_storage,
     ensure,
-    traits::{
-        Currency,
-        ExistenceRequirement,
-        Get,
-        Randomness,
-    },
+    traits::Get,
+    transactional,
     Parameter,
 };
 use frame_system::ensure_signed;
-use sp_io::hashing::blake2_128;
 use sp_runtime::{
     traits::{
         AtLeast32Bit,
         Bounded,
         Member,
-        One,
     },
     DispatchError,
+    DispatchResult,
+};
+use roaming_operators::{
+    without_roaming_entity,
+    RoamingAuthority,
+    RoamingEntityChildren,
+    RoamingNetworkServerEntity,
+    RoamingOrganizationEntity,
+};
+use roaming_registry::{
+    RoamingRegistry,
+    RoamingTradable,
 };
 use sp_std::prelude::*; // Imports Vec
 
@@ -41,55 +47,33 @@ mod tests;
 pub trait Config: frame_system::Config + roaming_operators::Config + roaming_network_servers::Config {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
     type RoamingOrganizationIndex: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
+    /// The registry that holds the roaming organizations and their owners
+    type RoamingOrganizationRegistry: RoamingRegistry<Self::AccountId, Self::RoamingOrganizationIndex>;
+    /// The pallets that hold the children of roaming organizations
+    type RoamingOrganizationChildren: RoamingEntityChildren<RoamingOrganizationEntity, Self::RoamingOrganizationIndex>;
 }
 
-type BalanceOf<T> =
-    <<T as roaming_operators::Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
-
-#[derive(Encode, Decode, Clone, PartialEq, Eq)]
-#[cfg_attr(feature = "std", derive(Debug))]
-pub struct RoamingOrganization(pub [u8; 16]);
-
 decl_event!(
     pub enum Event<T> where
         <T as frame_system::Config>::AccountId,
         <T as Config>::RoamingOrganizationIndex,
         <T as roaming_network_servers::Config>::RoamingNetworkServerIndex,
-        Balance = BalanceOf<T>,
     {
-        /// A roaming organization is created. (owner, roaming_organization_id)
-        Created(AccountId, RoamingOrganizationIndex),
-        /// A roaming organization is transferred. (from, to, roaming_organization_id)
-        Transferred(AccountId, AccountId, RoamingOrganizationIndex),
-        /// A roaming organization is available for sale. (owner, roaming_organization_id, price)
-        PriceSet(AccountId, RoamingOrganizationIndex, Option<Balance>),
-        /// A roaming organization is sold. (from, to, roaming_organization_id, price)
-        Sold(AccountId, AccountId, RoamingOrganizationIndex, Balance),
         /// A roaming organization is assigned to a network server. (owner of network server, roaming_organization_id, roaming_network_server_id)
         AssignedOrganizationToNetworkServer(AccountId, RoamingOrganizationIndex, RoamingNetworkServerIndex),
+        /// A roaming organization is removed. (owner, roaming_organization_id)
+        Removed(AccountId, RoamingOrganizationIndex),
     }
 );
 
 // This module's storage items.
 decl_storage! {
     trait Store for Module<T: Config> as RoamingOrganizations {
-        /// Stores all the roaming organizations, key is the roaming organization id / index
-        pub RoamingOrganizations get(fn roaming_organization): map hasher(opaque_blake2_256) T::RoamingOrganizationIndex => Option<RoamingOrganization>;
-
-        /// Stores the total number of roaming organizations. i.e. the next roaming organization index
-        pub RoamingOrganizationsCount get(fn roaming_organizations_count): T::RoamingOrganizationIndex;
-
-        /// Get roaming organization owner
-        pub RoamingOrganizationOwners get(fn roaming_organization_owner): map hasher(opaque_blake2_256) T::RoamingOrganizationIndex => Option<T::AccountId>;
-
-        /// Get roaming organization price. None means not for sale.
-        pub RoamingOrganizationPrices get(fn roaming_organization_price): map hasher(opaque_blake2_256) T::RoamingOrganizationIndex => Option<BalanceOf<T>>;
-
         /// Get roaming organization network server
         pub RoamingOrganizationNetworkServers get(fn roaming_organization_network_server): map hasher(opaque_blake2_256) T::RoamingOrganizationIndex => Option<T::RoamingNetworkServerIndex>;
 
         /// Get roaming network server organizations
-        pub RoamingNetworkServerOrganizations get(fn roaming_network_server_organizations): map hasher(opaque_blake2_256) T::RoamingNetworkServerIndex => Option<Vec<T::RoamingOrganizationIndex>>
+        pub RoamingNetworkServerOrganizations get(fn roaming_network_server_organizations): map hasher(opaque_blake2_256) T::RoamingNetworkServerIndex => Option<Vec<T::RoamingOrganizationIndex>>;
     }
 }
 
@@ -99,76 +83,8 @@ decl_module! {
     pub struct Module<T: Config> for enum Call where origin: T::Origin {
         fn deposit_event() = default;
 
-        /// Create a new roaming organization
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn create(origin) {
-            let sender = ensure_signed(origin)?;
-            let roaming_organization_id = Self::next_roaming_organization_id()?;
-
-            // Generate a random 128bit value
-            let unique_id = Self::random_value(&sender);
-
-            // Create and store roaming organization
-            let roaming_organization = RoamingOrganization(unique_id);
-            Self::insert_roaming_organization(&sender, roaming_organization_id, roaming_organization);
-
-            Self::deposit_event(RawEvent::Created(sender, roaming_organization_id));
-        }
-
-        /// Transfer a roaming organization to new owner
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn transfer(origin, to: T::AccountId, roaming_organization_id: T::RoamingOrganizationIndex) {
-            let sender = ensure_signed(origin)?;
-
-            ensure!(Self::roaming_organization_owner(roaming_organization_id) == Some(sender.clone()), "Only owner can transfer roaming organization");
-
-            Self::update_owner(&to, roaming_organization_id);
-
-            Self::deposit_event(RawEvent::Transferred(sender, to, roaming_organization_id));
-        }
-
-        /// Set a price for a roaming organization for sale
-        /// None to delist the roaming organization
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn set_price(origin, roaming_organization_id: T::RoamingOrganizationIndex, price: Option<BalanceOf<T>>) {
-            let sender = ensure_signed(origin)?;
-
-            ensure!(Self::roaming_organization_owner(roaming_organization_id) == Some(sender.clone()), "Only owner can set price for roaming organization");
-
-            if let Some(ref price) = price {
-                <RoamingOrganizationPrices<T>>::insert(roaming_organization_id, price);
-            } else {
-                <RoamingOrganizationPrices<T>>::remove(roaming_organization_id);
-            }
-
-            Self::deposit_event(RawEvent::PriceSet(sender, roaming_organization_id, price));
-        }
-
-        /// Buy a roaming organization with max price willing to pay
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn buy(origin, roaming_organization_id: T::RoamingOrganizationIndex, price: BalanceOf<T>) {
-            let sender = ensure_signed(origin)?;
-
-            let owner = Self::roaming_organization_owner(roaming_organization_id);
-            ensure!(owner.is_some(), "RoamingOrganization owner does not exist");
-            let owner = owner.unwrap();
-
-            let roaming_organization_price = Self::roaming_organization_price(roaming_organization_id);
-            ensure!(roaming_organization_price.is_some(), "RoamingOrganization not for sale");
-
-            let roaming_organization_price = roaming_organization_price.unwrap();
-            ensure!(price >= roaming_organization_price, "Price is too low");
-
-            T::Currency::transfer(&sender, &owner, roaming_organization_price, ExistenceRequirement::AllowDeath)?;
-
-            <RoamingOrganizationPrices<T>>::remove(roaming_organization_id);
-
-            Self::update_owner(&sender, roaming_organization_id);
-
-            Self::deposit_event(RawEvent::Sold(owner, sender, roaming_organization_id, roaming_organization_price));
-        }
-
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        #[transactional]
         pub fn assign_organization_to_network_server(
             origin,
             roaming_organization_id: T::RoamingOrganizationIndex,
@@ -181,18 +97,16 @@ decl_module! {
                 ::exists_roaming_network_server(roaming_network_server_id).is_ok();
             ensure!(is_roaming_network_server, "RoamingNetworkServer does not exist");
 
-            // Ensure that caller of the function is the owner of the network server id to assign the organization to
+            // Ensure that caller of the function is authorized for the network server id to assign the organization to
             ensure!(
-                <roaming_network_servers::Module<T>>::is_roaming_network_server_owner(roaming_network_server_id, sender.clone()).is_ok(),
-                "Only the roaming network_server owner can assign itself a roaming organization"
+                <roaming_network_servers::Module<T>>::is_authorized(roaming_network_server_id, &sender),
+                "Only an account authorized for the roaming network_server can assign itself a roaming organization"
             );
 
-            Self::associate_organization_with_network_server(roaming_organization_id, roaming_network_server_id)
-                .expect("Unable to associate organization with network server");
+            Self::associate_organization_with_network_server(roaming_organization_id, roaming_network_server_id)?;
 
             // Ensure that the given organization id already exists
-            let roaming_organization = Self::roaming_organization(roaming_organization_id);
-            ensure!(roaming_organization.is_some(), "Invalid roaming_organization_id");
+            ensure!(T::RoamingOrganizationRegistry::exists(roaming_organization_id), "Invalid roaming_organization_id");
 
             // Ensure that the organization is not already owned by a different network server
             // Unassign the organization from any existing network since it may only be owned by one network server
@@ -203,6 +117,20 @@ decl_module! {
 
             Self::deposit_event(RawEvent::AssignedOrganizationToNetworkServer(sender, roaming_organization_id, roaming_network_server_id));
         }
+
+        /// Remove a roaming organization. Its children are either removed with it or prevent its removal, depending on
+        /// the removal policy.
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(4, 6)]
+        #[transactional]
+        pub fn remove(origin, roaming_organization_id: T::RoamingOrganizationIndex) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(T::RoamingOrganizationRegistry::owner_of(roaming_organization_id) == Some(sender.clone()), "Only owner can remove roaming organization");
+
+            Self::remove_roaming_organization(roaming_organization_id)?;
+
+            Self::deposit_event(RawEvent::Removed(sender, roaming_organization_id));
+        }
     }
 }
 
@@ -212,7 +140,9 @@ impl<T: Config> Module<T> {
         sender: T::AccountId,
     ) -> Result<(), DispatchError> {
         ensure!(
-            Self::roaming_organization_owner(&roaming_organization_id).map(|owner| owner == sender).unwrap_or(false),
+            T::RoamingOrganizationRegistry::owner_of(roaming_organization_id)
+                .map(|owner| owner == sender)
+                .unwrap_or(false),
             "Sender is not owner of RoamingOrganization"
         );
         Ok(())
@@ -220,11 +150,9 @@ impl<T: Config> Module<T> {
 
     pub fn exists_roaming_organization(
         roaming_organization_id: T::RoamingOrganizationIndex,
-    ) -> Result<RoamingOrganization, DispatchError> {
-        match Self::roaming_organization(roaming_organization_id) {
-            Some(roaming_organization) => Ok(roaming_organization),
-            None => Err(DispatchError::Other("RoamingOrganization does not exist")),
-        }
+    ) -> Result<(), DispatchError> {
+        ensure!(T::RoamingOrganizationRegistry::exists(roaming_organization_id), "RoamingOrganization does not exist");
+        Ok(())
     }
 
     /// Only push the organization id onto the end of the vector if it does not already exist
@@ -274,36 +202,57 @@ impl<T: Config> Module<T> {
         }
     }
 
-    fn random_value(sender: &T::AccountId) -> [u8; 16] {
-        let payload = (
-            T::Randomness::random(&[0]),
-            sender,
-            <frame_system::Module<T>>::extrinsic_index(),
-            <frame_system::Module<T>>::block_number(),
-        );
-        payload.using_encoded(blake2_128)
+    /// Remove a roaming organization along with its link to its network server, cleaning up its children according to
+    /// the removal policy
+    pub fn remove_roaming_organization(roaming_organization_id: T::RoamingOrganizationIndex) -> DispatchResult {
+        Self::exists_roaming_organization(roaming_organization_id)?;
+        T::RoamingOrganizationChildren::remove_children_with_policy(
+            <T as roaming_operators::Config>::RemovalPolicy::get(),
+            roaming_organization_id,
+        )?;
+
+        if let Some(roaming_network_server_id) = <RoamingOrganizationNetworkServers<T>>::take(roaming_organization_id) {
+            <RoamingNetworkServerOrganizations<T>>::mutate_exists(roaming_network_server_id, |organization_ids| {
+                *organization_ids = without_roaming_entity(organization_ids.take(), &roaming_organization_id)
+            });
+        }
+        T::RoamingOrganizationRegistry::deregister(roaming_organization_id)
+    }
+}
+
+impl<T: Config> RoamingAuthority<T::AccountId, T::RoamingOrganizationIndex> for Module<T> {
+    fn is_owner_or_admin(roaming_organization_id: T::RoamingOrganizationIndex, account: &T::AccountId) -> bool {
+        T::RoamingOrganizationRegistry::is_owner_or_admin(roaming_organization_id, account)
     }
 
-    fn next_roaming_organization_id() -> Result<T::RoamingOrganizationIndex, DispatchError> {
-        let roaming_organization_id = Self::roaming_organizations_count();
-        if roaming_organization_id == <T::RoamingOrganizationIndex as Bounded>::max_value() {
-            return Err(DispatchError::Other("RoamingOrganizations count overflow"));
-        }
-        Ok(roaming_organization_id)
+    /// The owner and admins of a roaming organization are authorized, as is any account authorized for its network
+    /// server
+    fn is_authorized(roaming_organization_id: T::RoamingOrganizationIndex, account: &T::AccountId) -> bool {
+        Self::is_owner_or_admin(roaming_organization_id, account) ||
+            Self::roaming_organization_network_server(roaming_organization_id)
+                .map_or(false, |parent_id| <roaming_network_servers::Module<T>>::is_authorized(parent_id, account))
     }
+}
 
-    fn insert_roaming_organization(
-        owner: &T::AccountId,
-        roaming_organization_id: T::RoamingOrganizationIndex,
-        roaming_organization: RoamingOrganization,
-    ) {
-        // Create and store roaming organization
-        <RoamingOrganizations<T>>::insert(roaming_organization_id, roaming_organization);
-        <RoamingOrganizationsCount<T>>::put(roaming_organization_id + One::one());
-        <RoamingOrganizationOwners<T>>::insert(roaming_organization_id, owner.clone());
+/// The organizations of a roaming network server, other than those that have since been assigned to another network
+/// server
+impl<T: Config> RoamingEntityChildren<RoamingNetworkServerEntity, T::RoamingNetworkServerIndex> for Module<T> {
+    fn has_children(roaming_network_server_id: T::RoamingNetworkServerIndex) -> bool {
+        Self::roaming_network_server_organizations(roaming_network_server_id).unwrap_or_default().into_iter().any(
+            |organization_id| {
+                Self::roaming_organization_network_server(organization_id) == Some(roaming_network_server_id)
+            },
+        )
     }
 
-    fn update_owner(to: &T::AccountId, roaming_organization_id: T::RoamingOrganizationIndex) {
-        <RoamingOrganizationOwners<T>>::insert(roaming_organization_id, to);
+    fn remove_children(roaming_network_server_id: T::RoamingNetworkServerIndex) -> DispatchResult {
+        let organization_ids =
+            <RoamingNetworkServerOrganizations<T>>::take(roaming_network_server_id).unwrap_or_default();
+        for organization_id in organization_ids {
+            if Self::roaming_organization_network_server(organization_id) == Some(roaming_network_server_id) {
+                Self::remove_roaming_organization(organization_id)?;
+            }
+        }
+        Ok(())
     }
 }
@@ -0,0 +1,215 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Merkle trees over the per-packet records of roaming packet bundles.
+//!
+//! The leaves of a tree are the hashes of its packet records sorted by (DevAddr, FCnt), so that proofs of two
+//! adjacent leaves show that a packet that would be sorted between them was not included. A node without a
+//! sibling is carried up to the next level as is. An empty tree has a zero root.
+//!
+//! Verifying proofs is available on-chain, whereas building trees and proofs off-chain requires `std`.
+
+use codec::{
+    Decode,
+    Encode,
+};
+use sp_core::H256;
+use sp_io::hashing::blake2_256;
+use sp_std::prelude::*; // Imports Vec
+
+#[cfg(test)]
+mod tests;
+
+const LEAF_PREFIX: u8 = 0;
+const NODE_PREFIX: u8 = 1;
+
+/// The most sibling hashes that a proof may have, since a tree has at most `u32::MAX` leaves
+pub const MAX_PROOF_DEPTH: u32 = 32;
+
+/// A packet received by a network server as part of a roaming packet bundle
+#[derive(Encode, Decode, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct PacketRecord {
+    pub dev_addr: u32,
+    pub f_cnt: u32,
+    pub timestamp: u64,
+    pub size: u16,
+}
+
+impl PacketRecord {
+    /// The key that the leaves of a tree are sorted by
+    pub fn key(&self) -> (u32, u32) {
+        (self.dev_addr, self.f_cnt)
+    }
+}
+
+/// A packet record at a leaf of a tree and the sibling hashes from the leaf up to the root
+#[derive(Encode, Decode, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct PacketProof {
+    pub leaf_index: u32,
+    pub record: PacketRecord,
+    pub siblings: Vec<H256>,
+}
+
+pub fn leaf_hash(record: &PacketRecord) -> H256 {
+    H256(blake2_256(&(LEAF_PREFIX, record).encode()))
+}
+
+pub fn node_hash(left: &H256, right: &H256) -> H256 {
+    H256(blake2_256(&(NODE_PREFIX, left, right).encode()))
+}
+
+/// Verify that a proof shows its packet record at its leaf index of the tree with the given root and number
+/// of leaves
+pub fn verify_inclusion(root: &H256, leaves_count: u32, proof: &PacketProof) -> bool {
+    if proof.leaf_index >= leaves_count || proof.siblings.len() > MAX_PROOF_DEPTH as usize {
+        return false;
+    }
+
+    let mut hash = leaf_hash(&proof.record);
+    let mut index = proof.leaf_index;
+    let mut width = leaves_count;
+    let mut siblings = proof.siblings.iter();
+    while width > 1 {
+        if index % 2 == 1 {
+            match siblings.next() {
+                Some(sibling) => hash = node_hash(sibling, &hash),
+                None => return false,
+            }
+        } else if index + 1 < width {
+            match siblings.next() {
+                Some(sibling) => hash = node_hash(&hash, sibling),
+                None => return false,
+            }
+        }
+        index /= 2;
+        width = (width + 1) / 2;
+    }
+
+    siblings.next().is_none() && hash == *root
+}
+
+/// Verify that the proofs of the leaves either side of where a packet with the given DevAddr and FCnt would be
+/// sorted show that it was not included in the tree with the given root and number of leaves. The left proof
+/// is omitted when the packet would be sorted before the first leaf, and the right proof is omitted when it
+/// would be sorted after the last leaf.
+pub fn verify_exclusion(
+    root: &H256,
+    leaves_count: u32,
+    dev_addr: u32,
+    f_cnt: u32,
+    left: Option<&PacketProof>,
+    right: Option<&PacketProof>,
+) -> bool {
+    let key = (dev_addr, f_cnt);
+    if leaves_count == 0 {
+        return left.is_none() && right.is_none() && root.is_zero();
+    }
+
+    match (left, right) {
+        (Some(left), Some(right)) => {
+            left.leaf_index.checked_add(1) == Some(right.leaf_index) &&
+                left.record.key() < key &&
+                key < right.record.key() &&
+                verify_inclusion(root, leaves_count, left) &&
+                verify_inclusion(root, leaves_count, right)
+        }
+        (None, Some(right)) => {
+            right.leaf_index == 0 && key < right.record.key() && verify_inclusion(root, leaves_count, right)
+        }
+        (Some(left), None) => {
+            left.leaf_index.checked_add(1) == Some(leaves_count) &&
+                left.record.key() < key &&
+                verify_inclusion(root, leaves_count, left)
+        }
+        (None, None) => false,
+    }
+}
+
+/// A tree built off-chain over the packet records of a packet bundle
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct PacketMerkleTree {
+    records: Vec<PacketRecord>,
+    levels: Vec<Vec<H256>>,
+}
+
+#[cfg(feature = "std")]
+impl PacketMerkleTree {
+    /// Build a tree over packet records. The records are sorted by (DevAddr, FCnt), and only the first record
+    /// of each key is kept since a packet of a device is only counted once.
+    pub fn new(mut records: Vec<PacketRecord>) -> Self {
+        records.sort_by_key(|record| record.key());
+        records.dedup_by_key(|record| record.key());
+
+        let mut levels = vec![records.iter().map(leaf_hash).collect::<Vec<_>>()];
+        while levels[levels.len() - 1].len() > 1 {
+            let next_level = levels[levels.len() - 1]
+                .chunks(2)
+                .map(|pair| {
+                    match pair {
+                        [left, right] => node_hash(left, right),
+                        [node] => *node,
+                        _ => unreachable!("chunks of two are never empty"),
+                    }
+                })
+                .collect();
+            levels.push(next_level);
+        }
+
+        Self {
+            records,
+            levels,
+        }
+    }
+
+    pub fn root(&self) -> H256 {
+        self.levels.last().and_then(|level| level.first()).cloned().unwrap_or_default()
+    }
+
+    pub fn leaves_count(&self) -> u32 {
+        self.records.len() as u32
+    }
+
+    pub fn records(&self) -> &[PacketRecord] {
+        &self.records
+    }
+
+    /// Build the proof of the leaf at an index
+    pub fn proof(&self, leaf_index: u32) -> Option<PacketProof> {
+        let record = self.records.get(leaf_index as usize)?.clone();
+        let mut siblings = Vec::new();
+        let mut index = leaf_index as usize;
+        for level in &self.levels[..self.levels.len() - 1] {
+            if let Some(sibling) = level.get(index ^ 1) {
+                siblings.push(*sibling);
+            }
+            index /= 2;
+        }
+
+        Some(PacketProof {
+            leaf_index,
+            record,
+            siblings,
+        })
+    }
+
+    /// Build the proof that a packet with the given DevAddr and FCnt was included, if it was
+    pub fn inclusion_proof(&self, dev_addr: u32, f_cnt: u32) -> Option<PacketProof> {
+        let leaf_index = self.records.binary_search_by_key(&(dev_addr, f_cnt), |record| record.key()).ok()?;
+        self.proof(leaf_index as u32)
+    }
+
+    /// Build the left and right proofs that a packet with the given DevAddr and FCnt was not included, if it
+    /// was not. See `verify_exclusion`.
+    pub fn exclusion_proof(&self, dev_addr: u32, f_cnt: u32) -> Option<(Option<PacketProof>, Option<PacketProof>)> {
+        match self.records.binary_search_by_key(&(dev_addr, f_cnt), |record| record.key()) {
+            Ok(_) => None,
+            Err(leaf_index) => {
+                let left = leaf_index.checked_sub(1).and_then(|left_index| self.proof(left_index as u32));
+                let right = self.proof(leaf_index as u32);
+                Some((left, right))
+            }
+        }
+    }
+}
@@ -0,0 +1,100 @@
+// Tests to be written here
+
+use super::*;
+
+fn packet_record(dev_addr: u32, f_cnt: u32) -> PacketRecord {
+    PacketRecord {
+        dev_addr,
+        f_cnt,
+        timestamp: 1_600_000_000_000 + f_cnt as u64,
+        size: 12,
+    }
+}
+
+// Five records out of order, including a duplicate of (1, 2), so that the last leaf is carried up unhashed
+fn packet_merkle_tree() -> PacketMerkleTree {
+    PacketMerkleTree::new(vec![
+        packet_record(2, 1),
+        packet_record(1, 2),
+        packet_record(1, 1),
+        packet_record(3, 7),
+        packet_record(1, 2),
+        packet_record(2, 5),
+    ])
+}
+
+#[test]
+fn new_sorts_and_deduplicates_records() {
+    let tree = packet_merkle_tree();
+    assert_eq!(tree.leaves_count(), 5);
+    assert_eq!(
+        tree.records().iter().map(|record| record.key()).collect::<Vec<_>>(),
+        vec![(1, 1), (1, 2), (2, 1), (2, 5), (3, 7)]
+    );
+    let leaves: Vec<_> = tree.records().iter().map(leaf_hash).collect();
+    assert_eq!(
+        tree.root(),
+        node_hash(&node_hash(&node_hash(&leaves[0], &leaves[1]), &node_hash(&leaves[2], &leaves[3])), &leaves[4])
+    );
+}
+
+#[test]
+fn empty_tree_has_zero_root() {
+    let tree = PacketMerkleTree::new(vec![]);
+    assert_eq!(tree.root(), H256::zero());
+    assert_eq!(tree.proof(0), None);
+    assert!(verify_exclusion(&tree.root(), 0, 1, 1, None, None));
+}
+
+#[test]
+fn inclusion_proofs_verify_for_every_leaf() {
+    let tree = packet_merkle_tree();
+    for record in tree.records() {
+        let proof = tree.inclusion_proof(record.dev_addr, record.f_cnt).unwrap();
+        assert!(verify_inclusion(&tree.root(), tree.leaves_count(), &proof));
+    }
+    assert_eq!(tree.inclusion_proof(2, 2), None);
+}
+
+#[test]
+fn inclusion_proof_does_not_verify_for_another_record_or_tree() {
+    let tree = packet_merkle_tree();
+    let mut proof = tree.inclusion_proof(2, 1).unwrap();
+    assert!(!verify_inclusion(&tree.root(), tree.leaves_count() + 1, &proof));
+    assert!(!verify_inclusion(&H256::repeat_byte(1), tree.leaves_count(), &proof));
+    proof.record.size = 13;
+    assert!(!verify_inclusion(&tree.root(), tree.leaves_count(), &proof));
+    proof.record.size = 12;
+    proof.leaf_index = 3;
+    assert!(!verify_inclusion(&tree.root(), tree.leaves_count(), &proof));
+}
+
+#[test]
+fn inclusion_proof_does_not_verify_with_more_siblings_than_the_maximum_depth() {
+    let tree = packet_merkle_tree();
+    let mut proof = tree.inclusion_proof(2, 1).unwrap();
+    proof.siblings.extend(vec![H256::default(); MAX_PROOF_DEPTH as usize]);
+    assert!(!verify_inclusion(&tree.root(), tree.leaves_count(), &proof));
+}
+
+#[test]
+fn exclusion_proofs_verify_for_missing_packets() {
+    let tree = packet_merkle_tree();
+    for (dev_addr, f_cnt) in vec![(0, 9), (1, 3), (2, 2), (3, 8), (4, 0)] {
+        let (left, right) = tree.exclusion_proof(dev_addr, f_cnt).unwrap();
+        assert!(verify_exclusion(&tree.root(), tree.leaves_count(), dev_addr, f_cnt, left.as_ref(), right.as_ref()));
+    }
+    assert_eq!(tree.exclusion_proof(2, 5), None);
+}
+
+#[test]
+fn exclusion_proof_does_not_verify_for_included_or_non_adjacent_packets() {
+    let tree = packet_merkle_tree();
+    let left = tree.proof(1);
+    let right = tree.proof(3);
+    assert!(!verify_exclusion(&tree.root(), tree.leaves_count(), 2, 1, left.as_ref(), right.as_ref()));
+    let right = tree.proof(2);
+    assert!(!verify_exclusion(&tree.root(), tree.leaves_count(), 2, 1, left.as_ref(), right.as_ref()));
+    assert!(!verify_exclusion(&tree.root(), tree.leaves_count(), 2, 1, None, None));
+    assert!(!verify_exclusion(&tree.root(), tree.leaves_count(), 3, 8, None, tree.proof(4).as_ref()));
+}
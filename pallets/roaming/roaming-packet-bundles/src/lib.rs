@@ -6,28 +6,58 @@ use codec::{
 };
 use frame_support::{
     debug,
+    decl_error,
     decl_event,
     decl_module,
     decl_storage,
     ensure,
+    storage::migration::{
+        get_storage_value,
+        take_storage_value,
+    },
     traits::{
+        BalanceStatus,
         Currency,
+        EnsureOrigin,
         ExistenceRequirement,
         Get,
-        Randomness,
     },
+    transactional,
+    weights::Weight,
     Parameter,
 };
 use frame_system::ensure_signed;
-use sp_io::hashing::blake2_128;
+use roaming_operators::{
+    without_roaming_entity,
+    RoamingAuthority,
+    RoamingEntityChildren,
+    RoamingNetworkServerEntity,
+    RoamingSessionEntity,
+};
+use roaming_agreement_policies::RoamingAgreementSettlements;
+use roaming_packet_bundle_merkle::{
+    PacketProof,
+    MAX_PROOF_DEPTH,
+};
+use sp_core::H256;
+use sp_io::hashing::blake2_256;
 use sp_runtime::{
     traits::{
         AtLeast32Bit,
         Bounded,
         Member,
         One,
+        Saturating,
+        Zero,
     },
     DispatchError,
+    DispatchResult,
+    Permill,
+    SaturatedConversion,
+};
+use roaming_registry::{
+    RoamingRegistry,
+    RoamingTradable,
 };
 use sp_std::prelude::*; // Imports Vec
 #[macro_use]
@@ -46,32 +76,175 @@ pub trait Config:
     + roaming_network_servers::Config
     + roaming_devices::Config
     + roaming_sessions::Config
+    + roaming_accounting_policies::Config
+    + roaming_agreement_policies::Config
+    + roaming_service_profiles::Config
 {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
     type RoamingPacketBundleIndex: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
+    /// The registry that holds the roaming packet bundles and their owners
+    type RoamingPacketBundleRegistry: RoamingRegistry<Self::AccountId, Self::RoamingPacketBundleIndex>;
     type RoamingPacketBundleReceivedAtHome: Parameter + Member + Default;
     type RoamingPacketBundleReceivedPacketsCount: Parameter + Member + Default;
-    type RoamingPacketBundleReceivedPacketsOkCount: Parameter + Member + Default;
+    type RoamingPacketBundleReceivedPacketsOkCount: Parameter + Member + AtLeast32Bit + Default + Copy;
     type RoamingPacketBundleExternalDataStorageHash: Parameter + Member + Default;
+    /// The number of blocks after a visited network server finished receiving a packet bundle that the home
+    /// network has to settle it before the visited network may slash the home network's agreement deposits
+    type PacketBundleSettlementPeriod: Get<Self::BlockNumber>;
+    /// The largest difference between the packet counts of the home and visited network servers of a packet
+    /// bundle, as a proportion of the larger count, that is accepted without a dispute
+    type PacketCountTolerance: Get<Permill>;
+    /// The bond reserved from a network server owner that challenges disputed packet counts, which is returned
+    /// if the arbitrated count is at least as close to their count as to the counterparty's, or paid to the
+    /// counterparty otherwise
+    type PacketCountChallengeBond: Get<BondBalanceOf<Self>>;
+    /// The origin that decides the final packet count of a challenged packet bundle
+    type PacketCountArbitrationOrigin: EnsureOrigin<Self::Origin>;
+    /// The number of blocks that the uplink rates of service profiles allow their packets over
+    type RateLimitPeriod: Get<Self::BlockNumber>;
+    /// The multiple of the uplink fee that each packet received over the uplink rate of the service profile of the
+    /// device is charged at
+    type RateOveragePenaltyMultiplier: Get<u32>;
+    /// The pallet that bills the packet bundles received by network servers on invoices
+    type RoamingPacketBundleBilling: RoamingPacketBundleBilling<
+        Self::RoamingPacketBundleIndex,
+        Self::RoamingNetworkServerIndex,
+    >;
+}
+
+/// The billing of the roaming packet bundles received by network servers
+pub trait RoamingPacketBundleBilling<RoamingPacketBundleIndex, RoamingNetworkServerIndex> {
+    /// Whether a packet bundle received by a network server has been billed on an invoice
+    fn is_billed(
+        roaming_packet_bundle_id: RoamingPacketBundleIndex,
+        roaming_network_server_id: RoamingNetworkServerIndex,
+    ) -> bool;
+}
+
+impl<RoamingPacketBundleIndex, RoamingNetworkServerIndex>
+    RoamingPacketBundleBilling<RoamingPacketBundleIndex, RoamingNetworkServerIndex> for ()
+{
+    fn is_billed(
+        _roaming_packet_bundle_id: RoamingPacketBundleIndex,
+        _roaming_network_server_id: RoamingNetworkServerIndex,
+    ) -> bool {
+        false
+    }
 }
 
 type BalanceOf<T> =
     <<T as roaming_operators::Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+// Challenge bonds are reserved in the reservable currency of the agreement policies
+type BondBalanceOf<T> = <<T as roaming_agreement_policies::Config>::Currency as Currency<
+    <T as frame_system::Config>::AccountId,
+>>::Balance;
+
+/// The weight of hashing one node of a packet proof
+const PACKET_PROOF_NODE_WEIGHT: Weight = 1_000;
 
-#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+/// Storage version of the roaming packet bundles pallet
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "std", derive(Debug))]
-pub struct RoamingPacketBundle(pub [u8; 16]);
+pub enum Releases {
+    /// The packet bundles received by each roaming network server are stored as a vector
+    V1_0_0,
+    /// The packet bundles received by each roaming network server are keys of a double map
+    V2_0_0,
+    /// The settlements of packet bundles are stored per packet bundle receiver
+    V3_0_0,
+    /// The block that each packet bundle receiver was received at and the networks of its unsettled receivers are
+    /// recorded
+    V4_0_0,
+    /// The block that each packet bundle was assigned to its session is recorded
+    V5_0_0,
+}
+
+impl Default for Releases {
+    fn default() -> Self {
+        Releases::V1_0_0
+    }
+}
 
 #[cfg_attr(feature = "std", derive(Debug))]
 #[derive(Encode, Decode, Default, Clone, PartialEq)]
 // Generic type parameters - Balance
 pub struct RoamingPacketBundleReceiver<U, V, W, X, Y, Z> {
-    packet_bundle_received_at_home: U,
-    packet_bundle_received_packets_count: V,
-    packet_bundle_received_packets_ok_count: W,
-    packet_bundle_received_started_at_block: X,
-    packet_bundle_received_ended_at_block: Y,
-    packet_bundle_external_data_storage_hash: Z,
+    pub packet_bundle_received_at_home: U,
+    pub packet_bundle_received_packets_count: V,
+    pub packet_bundle_received_packets_ok_count: W,
+    pub packet_bundle_received_started_at_block: X,
+    pub packet_bundle_received_ended_at_block: Y,
+    pub packet_bundle_external_data_storage_hash: Z,
+}
+
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+// Generic type parameters - RoamingNetworkServerIndex, Balance, BlockNumber
+pub struct RoamingPacketBundleSettlement<U, V, W> {
+    pub settlement_network_server_id: U,
+    pub settlement_amount: V,
+    pub settlement_settled_at_block: W,
+}
+
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+// Generic type parameters - Hash, BlockNumber
+pub struct RoamingPacketBundleDataRoot<U, V> {
+    pub data_root: U,
+    pub data_leaves_count: u32,
+    pub data_committed_at_block: V,
+}
+
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum PacketCountStatus {
+    /// The counts of the home and visited network servers agree within the tolerance
+    Agreed,
+    /// The counts differ by more than the tolerance, so the packet bundle may not be settled
+    Disputed,
+    /// A side has challenged the disputed counts with a bond and is waiting for arbitration
+    Challenged,
+    /// The arbitration origin decided the final count
+    Resolved,
+}
+
+impl Default for PacketCountStatus {
+    fn default() -> Self {
+        PacketCountStatus::Agreed
+    }
+}
+
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+// Generic type parameters - RoamingPacketBundleReceivedPacketsOkCount
+pub struct RoamingPacketBundleReconciliation<U> {
+    pub reconciliation_sent_packets_count: U,
+    pub reconciliation_received_packets_ok_count: U,
+    /// The count that the packet bundle is settled for. Zero while the counts are disputed or challenged.
+    pub reconciliation_final_packets_count: U,
+    pub reconciliation_status: PacketCountStatus,
+}
+
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+// Generic type parameters - RoamingPacketBundleReceivedPacketsOkCount
+pub struct RoamingPacketBundleRateCheck<U> {
+    /// The packets that the uplink rate of the service profile of the device allows over the block span of the
+    /// packet bundle
+    pub rate_allowed_packets_count: U,
+    /// The packets received ok over the allowed count when the packet bundle was submitted
+    pub rate_overage_packets_count: U,
+}
+
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+// Generic type parameters - AccountId, RoamingPacketBundleReceivedPacketsOkCount, Balance
+pub struct RoamingPacketBundleChallenge<U, V, W> {
+    pub challenge_challenger: U,
+    pub challenge_counterparty: U,
+    pub challenge_packets_count: V,
+    pub challenge_counterparty_packets_count: V,
+    pub challenge_bond: W,
 }
 
 decl_event!(
@@ -87,16 +260,9 @@ decl_event!(
         <T as roaming_network_servers::Config>::RoamingNetworkServerIndex,
         // <T as roaming_operators::Config>::RoamingOperatorIndex,
         Balance = BalanceOf<T>,
+        BondBalance = BondBalanceOf<T>,
         <T as frame_system::Config>::BlockNumber,
     {
-        /// A roaming packet_bundle is created. (owner, roaming_packet_bundle_id)
-        Created(AccountId, RoamingPacketBundleIndex),
-        /// A roaming packet_bundle is transferred. (from, to, roaming_packet_bundle_id)
-        Transferred(AccountId, AccountId, RoamingPacketBundleIndex),
-        /// A roaming packet_bundle is available for sale. (owner, roaming_packet_bundle_id, price)
-        PriceSet(AccountId, RoamingPacketBundleIndex, Option<Balance>),
-        /// A roaming packet_bundle is sold. (from, to, roaming_packet_bundle_id, price)
-        Sold(AccountId, AccountId, RoamingPacketBundleIndex, Balance),
         // /// A roaming packet_bundle receiveruration
         // RoamingPacketBundleReceiverSet(AccountId, RoamingPacketBundleIndex, RoamingPacketBundleNextBillingAt, RoamingPacketBundleFrequencyInDays),
         /// A roaming packet_bundle receiver was set
@@ -107,24 +273,98 @@ decl_event!(
         // AssignedPacketBundleToOperator(AccountId, RoamingPacketBundleIndex, RoamingOperatorIndex),
         /// A roaming packet_bundle is assigned to a session. (owner of session, roaming_packet_bundle_id, roaming_session_id)
         AssignedPacketBundleToSession(AccountId, RoamingPacketBundleIndex, RoamingSessionIndex),
+        /// A roaming packet_bundle is settled.
+        /// (home network operator owner, visited network operator owner, roaming_packet_bundle_id, visited network server, amount)
+        PacketBundleSettled(AccountId, AccountId, RoamingPacketBundleIndex, RoamingNetworkServerIndex, Balance),
+        /// The agreement deposits of the home network are slashed for an unsettled roaming packet_bundle.
+        /// (home network operator owner, visited network operator owner, roaming_packet_bundle_id, visited network server, amount)
+        PacketBundleSlashed(AccountId, AccountId, RoamingPacketBundleIndex, RoamingNetworkServerIndex, Balance),
+        /// A Merkle root over the packet records of a roaming packet_bundle receiver is committed.
+        /// (owner of network server, roaming_packet_bundle_id, roaming_network_server_id, root, leaves count)
+        PacketBundleDataRootCommitted(AccountId, RoamingPacketBundleIndex, RoamingNetworkServerIndex, H256, u32),
+        /// A packet is proven to have been included or not in the packet records of a roaming packet_bundle receiver.
+        /// (prover, roaming_packet_bundle_id, roaming_network_server_id, dev_addr, f_cnt, included)
+        PacketInclusionVerified(AccountId, RoamingPacketBundleIndex, RoamingNetworkServerIndex, u32, u32, bool),
+        /// The home network server reports the packets it sent in a roaming packet_bundle to a visited network server.
+        /// (owner of home network server, roaming_packet_bundle_id, visited roaming_network_server_id, sent packets count)
+        SentPacketsCountSet(AccountId, RoamingPacketBundleIndex, RoamingNetworkServerIndex, RoamingPacketBundleReceivedPacketsOkCount),
+        /// The packet counts of both sides of a roaming packet_bundle are reconciled.
+        /// (roaming_packet_bundle_id, visited roaming_network_server_id, sent packets count, received packets ok count, status)
+        PacketCountsReconciled(RoamingPacketBundleIndex, RoamingNetworkServerIndex, RoamingPacketBundleReceivedPacketsOkCount,
+            RoamingPacketBundleReceivedPacketsOkCount, PacketCountStatus),
+        /// The disputed packet counts of a roaming packet_bundle are challenged.
+        /// (challenger, roaming_packet_bundle_id, visited roaming_network_server_id, challenger's count, bond)
+        PacketCountsChallenged(AccountId, RoamingPacketBundleIndex, RoamingNetworkServerIndex, RoamingPacketBundleReceivedPacketsOkCount, BondBalance),
+        /// The final packet count of a challenged roaming packet_bundle is decided.
+        /// (roaming_packet_bundle_id, visited roaming_network_server_id, final count, whether the challenger's bond was returned)
+        PacketCountsResolved(RoamingPacketBundleIndex, RoamingNetworkServerIndex, RoamingPacketBundleReceivedPacketsOkCount, bool),
+        /// A visited network server received more packets in a roaming packet_bundle than the uplink rate of the
+        /// service profile of the device allows over its block span.
+        /// (roaming_packet_bundle_id, visited roaming_network_server_id, received packets ok count, allowed packets count)
+        PacketRateExceeded(RoamingPacketBundleIndex, RoamingNetworkServerIndex, RoamingPacketBundleReceivedPacketsOkCount,
+            RoamingPacketBundleReceivedPacketsOkCount),
+        /// A roaming packet_bundle is removed. (owner, roaming_packet_bundle_id)
+        Removed(AccountId, RoamingPacketBundleIndex),
     }
 );
 
+decl_error! {
+    pub enum Error for Module<T: Config> {
+        /// The packet bundle has already been settled
+        PacketBundleAlreadySettled,
+        /// The packet bundle has not been assigned to a roaming session
+        PacketBundleWithoutSession,
+        /// The roaming session of the packet bundle has not been assigned to a device
+        SessionWithoutDevice,
+        /// The device has not been assigned to a home network server
+        DeviceWithoutNetworkServer,
+        /// The network server has not been assigned to a roaming network
+        NetworkServerWithoutNetwork,
+        /// The roaming network has not been assigned to an operator with an owner
+        NetworkWithoutOperator,
+        /// The visited roaming network does not have a configured accounting policy
+        NetworkWithoutAccountingPolicy,
+        /// Only the owner of the home network operator of the device may settle the packet bundle
+        NotHomeNetworkOperator,
+        /// Only the owner of the visited network operator may slash for an unsettled packet bundle
+        NotVisitedNetworkOperator,
+        /// The settlement period of the packet bundle has not yet elapsed
+        SettlementPeriodNotElapsed,
+        /// The home network does not have an agreement deposit for the visited network that may be slashed
+        NoAgreementDeposit,
+        /// A Merkle root has already been committed for the packet bundle receiver
+        DataRootAlreadyCommitted,
+        /// The packet bundle receiver has been billed on an invoice and may no longer be changed
+        PacketBundleAlreadyBilled,
+        /// A Merkle root has not been committed for the packet bundle receiver
+        NoDataRoot,
+        /// The Merkle proof does not verify against the committed root
+        InvalidPacketProof,
+        /// The Merkle proof has more sibling hashes than a tree of the most leaves
+        PacketProofTooDeep,
+        /// Only an account authorized for the home network server of the device of the packet bundle may report the
+        /// sent packets
+        NotHomeNetworkServerOperator,
+        /// The packet counts have been agreed, challenged or resolved, so they may no longer be changed
+        PacketCountsLocked,
+        /// The packet counts of the packet bundle are disputed or challenged
+        PacketCountsDisputed,
+        /// The packet counts of the packet bundle are not disputed
+        PacketCountsNotDisputed,
+        /// The packet counts of the packet bundle have not been challenged
+        PacketCountsNotChallenged,
+        /// Only the owners of the home and visited network servers of the packet bundle may challenge its counts
+        NotPacketBundleCounterparty,
+        /// The packet bundle has been received by a visited network server but has not been settled
+        PacketBundleNotSettled,
+        /// The packet bundle has not been received by the network server
+        PacketBundleNotReceived,
+    }
+}
+
 // This module's storage items.
 decl_storage! {
     trait Store for Module<T: Config> as RoamingPacketBundles {
-        /// Stores all the roaming packet_bundle, key is the roaming packet_bundle id / index
-        pub RoamingPacketBundles get(fn roaming_packet_bundle): map hasher(opaque_blake2_256) T::RoamingPacketBundleIndex => Option<RoamingPacketBundle>;
-
-        /// Stores the total number of roaming packet_bundles. i.e. the next roaming packet_bundle index
-        pub RoamingPacketBundlesCount get(fn roaming_packet_bundles_count): T::RoamingPacketBundleIndex;
-
-        /// Get roaming packet_bundle owner
-        pub RoamingPacketBundleOwners get(fn roaming_packet_bundle_owner): map hasher(opaque_blake2_256) T::RoamingPacketBundleIndex => Option<T::AccountId>;
-
-        /// Get roaming packet_bundle price. None means not for sale.
-        pub RoamingPacketBundlePrices get(fn roaming_packet_bundle_price): map hasher(opaque_blake2_256) T::RoamingPacketBundleIndex => Option<BalanceOf<T>>;
-
         // /// Get roaming packet_bundle receiver
         // pub RoamingPacketBundleReceivers get(fn roaming_packet_bundle_receivers): map hasher(opaque_blake2_256) T::RoamingPacketBundleIndex => Option<RoamingPacketBundleReceiver<T::RoamingPacketBundleNextBillingAt, T::RoamingPacketBundleFrequencyInDays>>;
 
@@ -139,8 +379,12 @@ decl_storage! {
                 T::RoamingPacketBundleExternalDataStorageHash
             >>;
 
-        /// NetworkServer to PacketBundles mapping
-        pub RoamingNetworkServerPacketBundles get(fn roaming_network_server_packet_bundles): map hasher(opaque_blake2_256) T::RoamingNetworkServerIndex => Option<Vec<T::RoamingPacketBundleIndex>>;
+        /// The packet bundles received by each roaming network server, stored as keys so that a packet bundle is
+        /// added or removed without reading the other packet bundles of the network server
+        pub RoamingNetworkServerPacketBundles: double_map hasher(blake2_128_concat) T::RoamingNetworkServerIndex, hasher(blake2_128_concat) T::RoamingPacketBundleIndex => ();
+
+        /// Get the network servers that have received a roaming packet_bundle
+        pub RoamingPacketBundleNetworkServers get(fn roaming_packet_bundle_network_servers): map hasher(opaque_blake2_256) T::RoamingPacketBundleIndex => Option<Vec<T::RoamingNetworkServerIndex>>;
 
         // Device Session mapping
         pub RoamingPacketBundleDeviceSession get(fn roaming_packet_bundle_device_sessions): map hasher(opaque_blake2_256) T::RoamingPacketBundleIndex => Option<(T::RoamingDeviceIndex, T::RoamingSessionIndex)>;
@@ -154,7 +398,54 @@ decl_storage! {
         pub RoamingPacketBundleSession get(fn roaming_packet_bundle_session): map hasher(opaque_blake2_256) T::RoamingPacketBundleIndex => Option<T::RoamingSessionIndex>;
 
         /// Get roaming session's packet bundles
-        pub RoamingSessionPacketBundles get(fn roaming_session_packet_bundles): map hasher(opaque_blake2_256) T::RoamingSessionIndex => Option<Vec<T::RoamingPacketBundleIndex>>
+        pub RoamingSessionPacketBundles get(fn roaming_session_packet_bundles): map hasher(opaque_blake2_256) T::RoamingSessionIndex => Option<Vec<T::RoamingPacketBundleIndex>>;
+
+        /// Get the settlement of the packets received by a network server in a roaming packet_bundle. None means
+        /// the packet bundle receiver has not been settled. Settlements outlive their receivers, so they are only
+        /// removed with the packet bundle.
+        pub RoamingPacketBundleSettlements get(fn roaming_packet_bundle_settlement): double_map hasher(blake2_128_concat) T::RoamingPacketBundleIndex, hasher(blake2_128_concat) T::RoamingNetworkServerIndex => Option<RoamingPacketBundleSettlement<T::RoamingNetworkServerIndex, BalanceOf<T>, T::BlockNumber>>;
+
+        /// Get the packet bundles received by a network server that have not yet been billed
+        pub RoamingNetworkServerUnbilledPacketBundles get(fn roaming_network_server_unbilled_packet_bundles): map hasher(opaque_blake2_256) T::RoamingNetworkServerIndex => Vec<T::RoamingPacketBundleIndex>;
+
+        /// Get the Merkle root committed over the packet records of a packet bundle receiver
+        pub RoamingPacketBundleDataRoots get(fn roaming_packet_bundle_data_root): map hasher(opaque_blake2_256) (T::RoamingPacketBundleIndex, T::RoamingNetworkServerIndex) => Option<RoamingPacketBundleDataRoot<H256, T::BlockNumber>>;
+
+        /// Get whether a packet (dev_addr, f_cnt) has been proven to be included or not in the packet records of a
+        /// packet bundle receiver. None means no proof has been verified.
+        pub RoamingPacketBundleVerifiedPackets get(fn roaming_packet_bundle_verified_packet): map hasher(opaque_blake2_256) (T::RoamingPacketBundleIndex, T::RoamingNetworkServerIndex, u32, u32) => Option<bool>;
+
+        /// Get the packets count that the home network server sent in a packet bundle to a visited network server
+        pub RoamingPacketBundleSentPacketsCounts get(fn roaming_packet_bundle_sent_packets_count): map hasher(opaque_blake2_256) (T::RoamingPacketBundleIndex, T::RoamingNetworkServerIndex) => Option<T::RoamingPacketBundleReceivedPacketsOkCount>;
+
+        /// Get the reconciliation of the packet counts of both sides of a packet bundle
+        pub RoamingPacketBundleReconciliations get(fn roaming_packet_bundle_reconciliation): map hasher(opaque_blake2_256) (T::RoamingPacketBundleIndex, T::RoamingNetworkServerIndex) => Option<RoamingPacketBundleReconciliation<T::RoamingPacketBundleReceivedPacketsOkCount>>;
+
+        /// Get the pending challenge of the disputed packet counts of a packet bundle
+        pub RoamingPacketBundleChallenges get(fn roaming_packet_bundle_challenge): map hasher(opaque_blake2_256) (T::RoamingPacketBundleIndex, T::RoamingNetworkServerIndex) => Option<RoamingPacketBundleChallenge<T::AccountId, T::RoamingPacketBundleReceivedPacketsOkCount, BondBalanceOf<T>>>;
+
+        /// Get the check of the packets received in a packet bundle against the uplink rate of the service profile
+        /// of the device. None means the service profile has no uplink rate.
+        pub RoamingPacketBundleRateChecks get(fn roaming_packet_bundle_rate_check): map hasher(opaque_blake2_256) (T::RoamingPacketBundleIndex, T::RoamingNetworkServerIndex) => Option<RoamingPacketBundleRateCheck<T::RoamingPacketBundleReceivedPacketsOkCount>>;
+
+        /// Get the block at which a network server last set its receiver of a roaming packet_bundle, which starts the
+        /// settlement period of the packet bundle receiver
+        pub RoamingPacketBundleReceivedAtBlocks get(fn roaming_packet_bundle_received_at_block): map hasher(opaque_blake2_256) (T::RoamingPacketBundleIndex, T::RoamingNetworkServerIndex) => Option<T::BlockNumber>;
+
+        /// Get the block at which a roaming packet_bundle was last assigned to a session, which starts the block span
+        /// that the packets received in it are checked against the uplink rate over
+        pub RoamingPacketBundleSessionAssignedAtBlocks get(fn roaming_packet_bundle_session_assigned_at_block): map hasher(opaque_blake2_256) T::RoamingPacketBundleIndex => Option<T::BlockNumber>;
+
+        /// Get the home and visited networks of a roaming packet_bundle receiver that has not yet been settled
+        pub RoamingPacketBundleUnsettledNetworks get(fn roaming_packet_bundle_unsettled_networks): map hasher(opaque_blake2_256) (T::RoamingPacketBundleIndex, T::RoamingNetworkServerIndex) => Option<(T::RoamingNetworkIndex, T::RoamingNetworkIndex)>;
+
+        /// Get the number of roaming packet_bundle receivers of a visited network that a home network has not yet
+        /// settled, keyed by (home network, visited network)
+        pub RoamingNetworkUnsettledPacketBundlesCount get(fn roaming_network_unsettled_packet_bundles_count): map hasher(opaque_blake2_256) (T::RoamingNetworkIndex, T::RoamingNetworkIndex) => u32;
+
+        /// Storage version of the pallet, used to determine which migrations to run on runtime upgrade. A new chain
+        /// starts at the latest version since it has no packet bundles to migrate.
+        pub StorageVersion get(fn storage_version) build(|_| Releases::V5_0_0): Releases;
 
         // /// Get roaming packet_bundle operator
         // pub RoamingPacketBundleOperator get(fn roaming_packet_bundle_operator): map hasher(opaque_blake2_256) T::RoamingPacketBundleIndex => Option<T::RoamingOperatorIndex>;
@@ -170,49 +461,33 @@ decl_module! {
     pub struct Module<T: Config> for enum Call where origin: T::Origin {
         fn deposit_event() = default;
 
-        /// Create a new roaming packet_bundle
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn create(origin) {
-            let sender = ensure_signed(origin)?;
-            let roaming_packet_bundle_id = Self::next_roaming_packet_bundle_id()?;
-
-            // Generate a random 128bit value
-            let unique_id = Self::random_value(&sender);
-
-            // Create and store roaming packet_bundle
-            let roaming_packet_bundle = RoamingPacketBundle(unique_id);
-            Self::insert_roaming_packet_bundle(&sender, roaming_packet_bundle_id, roaming_packet_bundle);
+        type Error = Error<T>;
 
-            Self::deposit_event(RawEvent::Created(sender, roaming_packet_bundle_id));
-        }
+        const PacketBundleSettlementPeriod: T::BlockNumber = T::PacketBundleSettlementPeriod::get();
 
-        /// Transfer a roaming packet_bundle to new owner
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn transfer(origin, to: T::AccountId, roaming_packet_bundle_id: T::RoamingPacketBundleIndex) {
-            let sender = ensure_signed(origin)?;
+        const PacketCountTolerance: Permill = T::PacketCountTolerance::get();
 
-            ensure!(Self::roaming_packet_bundle_owner(roaming_packet_bundle_id) == Some(sender.clone()), "Only owner can transfer roaming packet_bundle");
+        const PacketCountChallengeBond: BondBalanceOf<T> = T::PacketCountChallengeBond::get();
 
-            Self::update_owner(&to, roaming_packet_bundle_id);
+        const RateLimitPeriod: T::BlockNumber = T::RateLimitPeriod::get();
 
-            Self::deposit_event(RawEvent::Transferred(sender, to, roaming_packet_bundle_id));
-        }
-
-        /// Set a price for a roaming packet_bundle for sale
-        /// None to delist the roaming packet_bundle
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn set_price(origin, roaming_packet_bundle_id: T::RoamingPacketBundleIndex, price: Option<BalanceOf<T>>) {
-            let sender = ensure_signed(origin)?;
+        const RateOveragePenaltyMultiplier: u32 = T::RateOveragePenaltyMultiplier::get();
 
-            ensure!(Self::roaming_packet_bundle_owner(roaming_packet_bundle_id) == Some(sender.clone()), "Only owner can set price for roaming packet_bundle");
-
-            if let Some(ref price) = price {
-                <RoamingPacketBundlePrices<T>>::insert(roaming_packet_bundle_id, price);
-            } else {
-                <RoamingPacketBundlePrices<T>>::remove(roaming_packet_bundle_id);
+        fn on_runtime_upgrade() -> Weight {
+            let mut weight = T::DbWeight::get().reads(1);
+            if Self::storage_version() == Releases::V1_0_0 {
+                weight = weight.saturating_add(Self::migrate_to_network_server_packet_bundle_keys());
             }
-
-            Self::deposit_event(RawEvent::PriceSet(sender, roaming_packet_bundle_id, price));
+            if Self::storage_version() == Releases::V2_0_0 {
+                weight = weight.saturating_add(Self::migrate_to_packet_bundle_receiver_settlements());
+            }
+            if Self::storage_version() == Releases::V3_0_0 {
+                weight = weight.saturating_add(Self::migrate_to_recorded_packet_bundle_receipts());
+            }
+            if Self::storage_version() == Releases::V4_0_0 {
+                weight = weight.saturating_add(Self::migrate_to_recorded_packet_bundle_session_assignments());
+            }
+            weight
         }
 
         /// Set roaming packet_bundle receiver
@@ -243,11 +518,21 @@ decl_module! {
             ensure!(is_owned_by_network_server, "Ownership by given network server does not exist");
 
             // Ensure that the caller is owner of the packet bundle receiver they are trying to change
-            ensure!(Self::roaming_packet_bundle_owner(roaming_packet_bundle_id) == Some(sender.clone()), "Only owner can set receiver for roaming packet_bundle");
+            ensure!(T::RoamingPacketBundleRegistry::owner_of(roaming_packet_bundle_id) == Some(sender.clone()), "Only owner can set receiver for roaming packet_bundle");
 
             let is_owned_by_parent_relationship = Self::is_owned_by_required_parent_relationship(roaming_packet_bundle_id, sender.clone()).is_ok();
             ensure!(is_owned_by_parent_relationship, "Ownership by parent does not exist");
 
+            ensure!(
+                Self::roaming_packet_bundle_settlement(roaming_packet_bundle_id, roaming_network_server_id).is_none(),
+                Error::<T>::PacketBundleAlreadySettled
+            );
+            ensure!(
+                !T::RoamingPacketBundleBilling::is_billed(roaming_packet_bundle_id, roaming_network_server_id),
+                Error::<T>::PacketBundleAlreadyBilled
+            );
+            ensure!(!Self::is_packet_counts_locked(roaming_packet_bundle_id, roaming_network_server_id), Error::<T>::PacketCountsLocked);
+
             let packet_bundle_received_at_home = match _packet_bundle_received_at_home {
                 Some(value) => value,
                 None => Default::default() // Default
@@ -318,6 +603,12 @@ decl_module! {
                     &roaming_packet_bundle_receiver_instance
                 );
 
+                <RoamingNetworkServerPacketBundles<T>>::insert(roaming_network_server_id, roaming_packet_bundle_id, ());
+                <RoamingPacketBundleNetworkServers<T>>::append(roaming_packet_bundle_id, roaming_network_server_id);
+
+                // Bill the packet bundle at the next billing point of the network server's network
+                Self::requeue_unbilled_packet_bundle(roaming_network_server_id, roaming_packet_bundle_id);
+
                 debug::info!("Checking inserted values");
                 let fetched_packet_bundle_receiver = <RoamingPacketBundleReceivers<T>>::get((roaming_packet_bundle_id, roaming_network_server_id));
                 if let Some(_packet_bundle_receiver) = fetched_packet_bundle_receiver {
@@ -330,6 +621,16 @@ decl_module! {
                 }
             }
 
+            // The settlement period of the packet bundle receiver starts when it was last received
+            <RoamingPacketBundleReceivedAtBlocks<T>>::insert(
+                (roaming_packet_bundle_id, roaming_network_server_id),
+                <frame_system::Module<T>>::block_number()
+            );
+            Self::insert_unsettled_packet_bundle(roaming_packet_bundle_id, roaming_network_server_id);
+
+            Self::check_packet_rate(roaming_packet_bundle_id, roaming_network_server_id);
+            Self::reconcile_packet_counts(roaming_packet_bundle_id, roaming_network_server_id);
+
             Self::deposit_event(RawEvent::RoamingPacketBundleReceiverSet(
                 sender,
                 roaming_packet_bundle_id,
@@ -343,31 +644,8 @@ decl_module! {
             ));
         }
 
-        /// Buy a roaming packet_bundle with max price willing to pay
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn buy(origin, roaming_packet_bundle_id: T::RoamingPacketBundleIndex, price: BalanceOf<T>) {
-            let sender = ensure_signed(origin)?;
-
-            let owner = Self::roaming_packet_bundle_owner(roaming_packet_bundle_id);
-            ensure!(owner.is_some(), "RoamingPacketBundle owner does not exist");
-            let owner = owner.unwrap();
-
-            let roaming_packet_bundle_price = Self::roaming_packet_bundle_price(roaming_packet_bundle_id);
-            ensure!(roaming_packet_bundle_price.is_some(), "RoamingPacketBundle not for sale");
-
-            let roaming_packet_bundle_price = roaming_packet_bundle_price.unwrap();
-            ensure!(price >= roaming_packet_bundle_price, "Price is too low");
-
-            T::Currency::transfer(&sender, &owner, roaming_packet_bundle_price, ExistenceRequirement::AllowDeath)?;
-
-            <RoamingPacketBundlePrices<T>>::remove(roaming_packet_bundle_id);
-
-            Self::update_owner(&sender, roaming_packet_bundle_id);
-
-            Self::deposit_event(RawEvent::Sold(owner, sender, roaming_packet_bundle_id, roaming_packet_bundle_price));
-        }
-
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        #[weight = 10_000 + T::DbWeight::get().writes(2)]
+        #[transactional]
         pub fn assign_packet_bundle_to_session(
             origin,
             roaming_packet_bundle_id: T::RoamingPacketBundleIndex,
@@ -380,18 +658,22 @@ decl_module! {
                 ::exists_roaming_session(roaming_session_id).is_ok();
             ensure!(is_roaming_session, "RoamingSession does not exist");
 
-            // Ensure that caller of the function is the owner of the session id to assign the packet_bundle to
+            // Ensure that caller of the function is authorized for the session id to assign the packet_bundle to
             ensure!(
-                <roaming_sessions::Module<T>>::is_roaming_session_owner(roaming_session_id, sender.clone()).is_ok(),
-                "Only the roaming session owner can assign itself a roaming packet bundle"
+                <roaming_sessions::Module<T>>::is_authorized(roaming_session_id, &sender),
+                "Only an account authorized for the roaming session can assign itself a roaming packet bundle"
             );
 
-            Self::associate_packet_bundle_with_session(roaming_packet_bundle_id, roaming_session_id)
-                .expect("Unable to associate packet bundle with session");
+            // Ensure that the roaming policy of the device of the session allows its network server
+            if let Some(roaming_device_id) = <roaming_sessions::Module<T>>::roaming_session_device(roaming_session_id) {
+                let session_network_server_id = <roaming_sessions::Module<T>>::session_network_server(roaming_session_id);
+                <roaming_devices::Module<T>>::ensure_roaming_allowed(roaming_device_id, session_network_server_id)?;
+            }
+
+            Self::associate_packet_bundle_with_session(roaming_packet_bundle_id, roaming_session_id)?;
 
             // Ensure that the given packet_bundle id already exists
-            let roaming_packet_bundle = Self::roaming_packet_bundle(roaming_packet_bundle_id);
-            ensure!(roaming_packet_bundle.is_some(), "Invalid roaming_packet_bundle_id");
+            ensure!(T::RoamingPacketBundleRegistry::exists(roaming_packet_bundle_id), "Invalid roaming_packet_bundle_id");
 
             // Ensure that the packet_bundle is not already owned by a different session
             // Unassign the packet_bundle from any existing session since it may only be owned by one session
@@ -399,10 +681,333 @@ decl_module! {
 
             // Assign the packet_bundle owner to the given session (even if already belongs to them)
             <RoamingPacketBundleSession<T>>::insert(roaming_packet_bundle_id, roaming_session_id);
+            <RoamingPacketBundleSessionAssignedAtBlocks<T>>::insert(
+                roaming_packet_bundle_id,
+                <frame_system::Module<T>>::block_number()
+            );
 
             Self::deposit_event(RawEvent::AssignedPacketBundleToSession(sender, roaming_packet_bundle_id, roaming_session_id));
         }
 
+        /// Settle a roaming packet_bundle received by a visited network server.
+        /// The amount owed is the number of packets the visited network server received ok multiplied by the
+        /// uplink fee factor of the visited network's accounting policy, where packets over the uplink rate of the
+        /// service profile of the device are charged at the rate overage penalty multiplier. It is paid from the home network's
+        /// agreement deposits for the visited network first, and any remainder is transferred from the owner of
+        /// the home network operator of the device to the owner of the visited network operator.
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(14, 5)]
+        #[transactional]
+        pub fn settle_packet_bundle(
+            origin,
+            roaming_packet_bundle_id: T::RoamingPacketBundleIndex,
+            roaming_network_server_id: T::RoamingNetworkServerIndex
+        ) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(Self::roaming_packet_bundle_settlement(roaming_packet_bundle_id, roaming_network_server_id).is_none(), Error::<T>::PacketBundleAlreadySettled);
+
+            let (home_roaming_network_id, visited_roaming_network_id, settlement_amount) =
+                Self::packet_bundle_settlement_amount(roaming_packet_bundle_id, roaming_network_server_id)?;
+            let home_operator_account_id = Self::network_operator_account(home_roaming_network_id)?;
+            ensure!(home_operator_account_id == sender, Error::<T>::NotHomeNetworkOperator);
+            let visited_operator_account_id = Self::network_operator_account(visited_roaming_network_id)?;
+
+            Self::pay_settlement_amount(
+                home_roaming_network_id,
+                visited_roaming_network_id,
+                &home_operator_account_id,
+                &visited_operator_account_id,
+                settlement_amount
+            )?;
+
+            Self::insert_packet_bundle_settlement(
+                roaming_packet_bundle_id,
+                roaming_network_server_id,
+                settlement_amount,
+                home_operator_account_id,
+                visited_operator_account_id
+            );
+        }
+
+        /// Slash the home network's agreement deposits for the visited network for a roaming packet_bundle that
+        /// the home network did not settle within the settlement period after the visited network server last set its
+        /// receiver. Only the amount available in the deposits is recovered, and the packet bundle is then
+        /// considered settled for that amount.
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(14, 4)]
+        pub fn slash_unsettled_packet_bundle(
+            origin,
+            roaming_packet_bundle_id: T::RoamingPacketBundleIndex,
+            roaming_network_server_id: T::RoamingNetworkServerIndex
+        ) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(Self::roaming_packet_bundle_settlement(roaming_packet_bundle_id, roaming_network_server_id).is_none(), Error::<T>::PacketBundleAlreadySettled);
+            let received_at_block =
+                Self::roaming_packet_bundle_received_at_block((roaming_packet_bundle_id, roaming_network_server_id))
+                    .ok_or(Error::<T>::PacketBundleNotReceived)?;
+
+            let (home_roaming_network_id, visited_roaming_network_id, settlement_amount) =
+                Self::packet_bundle_settlement_amount(roaming_packet_bundle_id, roaming_network_server_id)?;
+            let visited_operator_account_id = Self::network_operator_account(visited_roaming_network_id)?;
+            ensure!(visited_operator_account_id == sender, Error::<T>::NotVisitedNetworkOperator);
+            let home_operator_account_id = Self::network_operator_account(home_roaming_network_id)?;
+
+            let current_block = <frame_system::Module<T>>::block_number();
+            let settlement_period_ends_at_block =
+                received_at_block.saturating_add(T::PacketBundleSettlementPeriod::get());
+            ensure!(settlement_period_ends_at_block <= current_block, Error::<T>::SettlementPeriodNotElapsed);
+
+            let slashed_amount = Self::draw_agreement_deposit(
+                home_roaming_network_id,
+                visited_roaming_network_id,
+                &visited_operator_account_id,
+                settlement_amount
+            );
+            ensure!(!slashed_amount.is_zero(), Error::<T>::NoAgreementDeposit);
+
+            <RoamingPacketBundleSettlements<T>>::insert(roaming_packet_bundle_id, roaming_network_server_id, RoamingPacketBundleSettlement {
+                settlement_network_server_id: roaming_network_server_id,
+                settlement_amount: slashed_amount,
+                settlement_settled_at_block: current_block,
+            });
+            Self::remove_unsettled_packet_bundle(roaming_packet_bundle_id, roaming_network_server_id);
+
+            Self::deposit_event(RawEvent::PacketBundleSlashed(
+                home_operator_account_id,
+                visited_operator_account_id,
+                roaming_packet_bundle_id,
+                roaming_network_server_id,
+                slashed_amount
+            ));
+        }
+
+        /// Commit a Merkle root over the packet records received by a network server in a roaming packet_bundle.
+        /// The leaves must be the packet records sorted by (DevAddr, FCnt), as built by `roaming-packet-bundle-merkle`,
+        /// so that a counterparty may prove whether a packet was included. A root may only be committed once.
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(5, 1)]
+        pub fn commit_packet_bundle_data_root(
+            origin,
+            roaming_packet_bundle_id: T::RoamingPacketBundleIndex,
+            roaming_network_server_id: T::RoamingNetworkServerIndex,
+            data_root: H256,
+            data_leaves_count: u32
+        ) {
+            let sender = ensure_signed(origin)?;
+
+            Self::exists_roaming_packet_bundle_receiver(roaming_packet_bundle_id, roaming_network_server_id)?;
+
+            // Ensure that the caller is owner of the network server that received the packet bundle
+            Self::is_owned_by_network_server(roaming_network_server_id, sender.clone())?;
+
+            ensure!(
+                Self::roaming_packet_bundle_data_root((roaming_packet_bundle_id, roaming_network_server_id)).is_none(),
+                Error::<T>::DataRootAlreadyCommitted
+            );
+
+            <RoamingPacketBundleDataRoots<T>>::insert((roaming_packet_bundle_id, roaming_network_server_id), RoamingPacketBundleDataRoot {
+                data_root,
+                data_leaves_count,
+                data_committed_at_block: <frame_system::Module<T>>::block_number(),
+            });
+
+            Self::deposit_event(RawEvent::PacketBundleDataRootCommitted(
+                sender,
+                roaming_packet_bundle_id,
+                roaming_network_server_id,
+                data_root,
+                data_leaves_count
+            ));
+        }
+
+        /// Prove that a packet record was included in the committed Merkle root of a roaming packet_bundle receiver
+        #[weight = 10_000 + Module::<T>::packet_proof_weight(proof) + T::DbWeight::get().reads_writes(1, 1)]
+        pub fn verify_packet_inclusion(
+            origin,
+            roaming_packet_bundle_id: T::RoamingPacketBundleIndex,
+            roaming_network_server_id: T::RoamingNetworkServerIndex,
+            proof: PacketProof
+        ) {
+            let sender = ensure_signed(origin)?;
+
+            Self::ensure_packet_proof_depth(&proof)?;
+            let data_root = Self::roaming_packet_bundle_data_root((roaming_packet_bundle_id, roaming_network_server_id))
+                .ok_or(Error::<T>::NoDataRoot)?;
+            ensure!(
+                roaming_packet_bundle_merkle::verify_inclusion(&data_root.data_root, data_root.data_leaves_count, &proof),
+                Error::<T>::InvalidPacketProof
+            );
+
+            Self::insert_verified_packet(
+                sender,
+                roaming_packet_bundle_id,
+                roaming_network_server_id,
+                proof.record.dev_addr,
+                proof.record.f_cnt,
+                true
+            );
+        }
+
+        /// Prove that a packet was not included in the committed Merkle root of a roaming packet_bundle receiver
+        /// using the proofs of the adjacent leaves that it would be sorted between
+        #[weight = 10_000 +
+            left_proof.as_ref().map_or(0, Module::<T>::packet_proof_weight) +
+            right_proof.as_ref().map_or(0, Module::<T>::packet_proof_weight) +
+            T::DbWeight::get().reads_writes(1, 1)]
+        pub fn verify_packet_exclusion(
+            origin,
+            roaming_packet_bundle_id: T::RoamingPacketBundleIndex,
+            roaming_network_server_id: T::RoamingNetworkServerIndex,
+            dev_addr: u32,
+            f_cnt: u32,
+            left_proof: Option<PacketProof>,
+            right_proof: Option<PacketProof>
+        ) {
+            let sender = ensure_signed(origin)?;
+
+            left_proof.as_ref().map_or(Ok(()), Self::ensure_packet_proof_depth)?;
+            right_proof.as_ref().map_or(Ok(()), Self::ensure_packet_proof_depth)?;
+            let data_root = Self::roaming_packet_bundle_data_root((roaming_packet_bundle_id, roaming_network_server_id))
+                .ok_or(Error::<T>::NoDataRoot)?;
+            ensure!(
+                roaming_packet_bundle_merkle::verify_exclusion(
+                    &data_root.data_root,
+                    data_root.data_leaves_count,
+                    dev_addr,
+                    f_cnt,
+                    left_proof.as_ref(),
+                    right_proof.as_ref()
+                ),
+                Error::<T>::InvalidPacketProof
+            );
+
+            Self::insert_verified_packet(sender, roaming_packet_bundle_id, roaming_network_server_id, dev_addr, f_cnt, false);
+        }
+
+        /// Report the packets count that the home network server of the device of a roaming packet_bundle sent to a
+        /// visited network server, as a counter-claim to the packets ok count reported by the visited network server.
+        /// Once both counts are reported they are reconciled.
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(8, 3)]
+        pub fn set_sent_packets_count(
+            origin,
+            roaming_packet_bundle_id: T::RoamingPacketBundleIndex,
+            roaming_network_server_id: T::RoamingNetworkServerIndex,
+            sent_packets_count: T::RoamingPacketBundleReceivedPacketsOkCount
+        ) {
+            let sender = ensure_signed(origin)?;
+
+            let home_network_server_id = Self::home_network_server(roaming_packet_bundle_id)?;
+            ensure!(
+                <roaming_network_servers::Module<T>>::is_authorized(home_network_server_id, &sender),
+                Error::<T>::NotHomeNetworkServerOperator
+            );
+            ensure!(Self::roaming_packet_bundle_settlement(roaming_packet_bundle_id, roaming_network_server_id).is_none(), Error::<T>::PacketBundleAlreadySettled);
+            ensure!(!Self::is_packet_counts_locked(roaming_packet_bundle_id, roaming_network_server_id), Error::<T>::PacketCountsLocked);
+
+            <RoamingPacketBundleSentPacketsCounts<T>>::insert((roaming_packet_bundle_id, roaming_network_server_id), sent_packets_count);
+
+            Self::deposit_event(RawEvent::SentPacketsCountSet(sender, roaming_packet_bundle_id, roaming_network_server_id, sent_packets_count));
+
+            Self::reconcile_packet_counts(roaming_packet_bundle_id, roaming_network_server_id);
+        }
+
+        /// Challenge the disputed packet counts of a roaming packet_bundle by reserving a bond, standing by the count
+        /// reported by the caller's side. May be called by the owner of the home or the visited network server.
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(8, 3)]
+        pub fn challenge_packet_counts(
+            origin,
+            roaming_packet_bundle_id: T::RoamingPacketBundleIndex,
+            roaming_network_server_id: T::RoamingNetworkServerIndex
+        ) {
+            let sender = ensure_signed(origin)?;
+
+            let reconciliation = Self::roaming_packet_bundle_reconciliation((roaming_packet_bundle_id, roaming_network_server_id))
+                .ok_or(Error::<T>::PacketCountsNotDisputed)?;
+            ensure!(reconciliation.reconciliation_status == PacketCountStatus::Disputed, Error::<T>::PacketCountsNotDisputed);
+
+            let home_network_server_id = Self::home_network_server(roaming_packet_bundle_id)?;
+            let home_account_id = <T as roaming_network_servers::Config>::RoamingNetworkServerRegistry::owner_of(home_network_server_id);
+            let visited_account_id = <T as roaming_network_servers::Config>::RoamingNetworkServerRegistry::owner_of(roaming_network_server_id);
+            let (counterparty_account_id, challenge_packets_count, counterparty_packets_count) = if home_account_id == Some(sender.clone()) {
+                (visited_account_id, reconciliation.reconciliation_sent_packets_count, reconciliation.reconciliation_received_packets_ok_count)
+            } else if visited_account_id == Some(sender.clone()) {
+                (home_account_id, reconciliation.reconciliation_received_packets_ok_count, reconciliation.reconciliation_sent_packets_count)
+            } else {
+                return Err(Error::<T>::NotPacketBundleCounterparty.into());
+            };
+            let counterparty_account_id = counterparty_account_id.ok_or(Error::<T>::NotPacketBundleCounterparty)?;
+
+            let bond = T::PacketCountChallengeBond::get();
+            <T as roaming_agreement_policies::Config>::Currency::reserve(&sender, bond)?;
+
+            <RoamingPacketBundleChallenges<T>>::insert((roaming_packet_bundle_id, roaming_network_server_id), RoamingPacketBundleChallenge {
+                challenge_challenger: sender.clone(),
+                challenge_counterparty: counterparty_account_id,
+                challenge_packets_count,
+                challenge_counterparty_packets_count: counterparty_packets_count,
+                challenge_bond: bond,
+            });
+            <RoamingPacketBundleReconciliations<T>>::mutate((roaming_packet_bundle_id, roaming_network_server_id), |reconciliation| {
+                if let Some(reconciliation) = reconciliation {
+                    reconciliation.reconciliation_status = PacketCountStatus::Challenged;
+                }
+            });
+
+            Self::deposit_event(RawEvent::PacketCountsChallenged(
+                sender,
+                roaming_packet_bundle_id,
+                roaming_network_server_id,
+                challenge_packets_count,
+                bond
+            ));
+        }
+
+        /// Decide the final packet count of a challenged roaming packet_bundle. The challenger's bond is returned if
+        /// the final count is at least as close to their count as to the counterparty's, and is paid to the
+        /// counterparty otherwise. The packet bundle is then billed at the next billing point.
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(4, 5)]
+        pub fn resolve_packet_counts(
+            origin,
+            roaming_packet_bundle_id: T::RoamingPacketBundleIndex,
+            roaming_network_server_id: T::RoamingNetworkServerIndex,
+            final_packets_count: T::RoamingPacketBundleReceivedPacketsOkCount
+        ) {
+            T::PacketCountArbitrationOrigin::ensure_origin(origin)?;
+
+            let challenge = Self::roaming_packet_bundle_challenge((roaming_packet_bundle_id, roaming_network_server_id))
+                .ok_or(Error::<T>::PacketCountsNotChallenged)?;
+
+            let challenger_difference = Self::packet_count_difference(final_packets_count, challenge.challenge_packets_count);
+            let counterparty_difference = Self::packet_count_difference(final_packets_count, challenge.challenge_counterparty_packets_count);
+            let is_bond_returned = challenger_difference <= counterparty_difference;
+            if is_bond_returned {
+                <T as roaming_agreement_policies::Config>::Currency::unreserve(&challenge.challenge_challenger, challenge.challenge_bond);
+            } else {
+                <T as roaming_agreement_policies::Config>::Currency::repatriate_reserved(
+                    &challenge.challenge_challenger,
+                    &challenge.challenge_counterparty,
+                    challenge.challenge_bond,
+                    BalanceStatus::Free
+                )?;
+            }
+
+            <RoamingPacketBundleChallenges<T>>::remove((roaming_packet_bundle_id, roaming_network_server_id));
+            <RoamingPacketBundleReconciliations<T>>::mutate((roaming_packet_bundle_id, roaming_network_server_id), |reconciliation| {
+                if let Some(reconciliation) = reconciliation {
+                    reconciliation.reconciliation_final_packets_count = final_packets_count;
+                    reconciliation.reconciliation_status = PacketCountStatus::Resolved;
+                }
+            });
+            // The packet bundle was skipped by any billing point while it was disputed
+            Self::requeue_unbilled_packet_bundle(roaming_network_server_id, roaming_packet_bundle_id);
+
+            Self::deposit_event(RawEvent::PacketCountsResolved(
+                roaming_packet_bundle_id,
+                roaming_network_server_id,
+                final_packets_count,
+                is_bond_returned
+            ));
+        }
+
         // pub fn assign_packet_bundle_to_operator(
         //     origin,
         //     roaming_packet_bundle_id: T::RoamingPacketBundleIndex,
@@ -437,6 +1042,21 @@ decl_module! {
 
         //     Self::deposit_event(RawEvent::AssignedPacketBundleToOperator(sender, roaming_packet_bundle_id, roaming_operator_id));
         // }
+
+        /// Remove a roaming packet_bundle along with its receivers. A packet bundle that has been received by a
+        /// visited network server may only be removed once it has been settled and none of its packet counts are
+        /// challenged.
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(8, 12)]
+        #[transactional]
+        pub fn remove(origin, roaming_packet_bundle_id: T::RoamingPacketBundleIndex) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(T::RoamingPacketBundleRegistry::owner_of(roaming_packet_bundle_id) == Some(sender.clone()), "Only owner can remove roaming packet_bundle");
+
+            Self::remove_roaming_packet_bundle(roaming_packet_bundle_id)?;
+
+            Self::deposit_event(RawEvent::Removed(sender, roaming_packet_bundle_id));
+        }
     }
 }
 
@@ -446,7 +1066,9 @@ impl<T: Config> Module<T> {
         sender: T::AccountId,
     ) -> Result<(), DispatchError> {
         ensure!(
-            Self::roaming_packet_bundle_owner(&roaming_packet_bundle_id).map(|owner| owner == sender).unwrap_or(false),
+            T::RoamingPacketBundleRegistry::owner_of(roaming_packet_bundle_id)
+                .map(|owner| owner == sender)
+                .unwrap_or(false),
             "Sender is not owner of RoamingPacketBundle"
         );
         Ok(())
@@ -460,15 +1082,11 @@ impl<T: Config> Module<T> {
         let packet_bundle_session_id = Self::roaming_packet_bundle_session(roaming_packet_bundle_id);
 
         if let Some(_packet_bundle_session_id) = packet_bundle_session_id {
-            // Ensure that the caller is owner of the session id associated with the packet bundle
+            // Ensure that the caller is authorized for the session id associated with the packet bundle
             ensure!(
-                (<roaming_sessions::Module<T>>::is_roaming_session_owner(
-                    _packet_bundle_session_id.clone(),
-                    sender.clone()
-                ))
-                .is_ok(),
-                "Only owner of the session id associated with the given packet bundle can set an associated roaming \
-                 packet bundle receiver"
+                <roaming_sessions::Module<T>>::is_authorized(_packet_bundle_session_id, &sender),
+                "Only an account authorized for the session id associated with the given packet bundle can set an \
+                 associated roaming packet bundle receiver"
             );
         } else {
             // There must be a packet bundle session id associated with the packet bundle
@@ -479,11 +1097,9 @@ impl<T: Config> Module<T> {
 
     pub fn exists_roaming_packet_bundle(
         roaming_packet_bundle_id: T::RoamingPacketBundleIndex,
-    ) -> Result<RoamingPacketBundle, DispatchError> {
-        match Self::roaming_packet_bundle(roaming_packet_bundle_id) {
-            Some(value) => Ok(value),
-            None => Err(DispatchError::Other("RoamingPacketBundle does not exist")),
-        }
+    ) -> Result<(), DispatchError> {
+        ensure!(T::RoamingPacketBundleRegistry::exists(roaming_packet_bundle_id), "RoamingPacketBundle does not exist");
+        Ok(())
     }
 
     pub fn exists_roaming_packet_bundle_receiver(
@@ -511,18 +1127,15 @@ impl<T: Config> Module<T> {
         sender: T::AccountId,
     ) -> Result<(), DispatchError> {
         debug::info!(
-            "Ensuring that the caller is owner of the given network server id associated with the given packet bundle \
-             id"
+            "Ensuring that the caller is authorized for the given network server id associated with the given packet \
+             bundle id"
         );
-        match <roaming_network_servers::Module<T>>::is_roaming_network_server_owner(roaming_network_server_id, sender) {
-            Ok(_) => Ok(()),
-            Err(_e) => {
-                Err(DispatchError::Other(
-                    "Only owner of the given network server id associated with the given packet bundle id can set it \
-                     as an associated roaming packet bundle receiver",
-                ))
-            }
-        }
+        ensure!(
+            <roaming_network_servers::Module<T>>::is_authorized(roaming_network_server_id, &sender),
+            "Only an account authorized for the given network server id associated with the given packet bundle id \
+             can set it as an associated roaming packet bundle receiver"
+        );
+        Ok(())
     }
 
     pub fn has_value_for_packet_bundle_receiver_index(
@@ -540,6 +1153,493 @@ impl<T: Config> Module<T> {
         Err(DispatchError::Other("No value for packet bundle receiver"))
     }
 
+    /// Calculate the amount owed for the packets that the given visited network server received in a packet bundle.
+    /// Returns the home network that owes the amount, the visited network that is owed the amount, and the amount.
+    ///
+    /// Packet bundles only record the uplink packets that a visited network server received, so only the uplink fee
+    /// factor applies. Downlink packets are not reported in packet bundles, and the subscription fee of the
+    /// accounting policy is charged by its recurring subscriptions rather than per packet bundle.
+    pub fn packet_bundle_settlement_amount(
+        roaming_packet_bundle_id: T::RoamingPacketBundleIndex,
+        roaming_network_server_id: T::RoamingNetworkServerIndex,
+    ) -> Result<(T::RoamingNetworkIndex, T::RoamingNetworkIndex, BalanceOf<T>), DispatchError> {
+        let packet_bundle_receiver =
+            Self::roaming_packet_bundle_receivers((roaming_packet_bundle_id, roaming_network_server_id))
+                .ok_or(DispatchError::Other("RoamingPacketBundleReceiver does not exist"))?;
+
+        // The home network is the network of the network server that the device of the packet bundle's session belongs to
+        let home_network_server_id = Self::home_network_server(roaming_packet_bundle_id)?;
+        let home_network_id = Self::network_server_network(home_network_server_id)?;
+        let visited_network_id = Self::network_server_network(roaming_network_server_id)?;
+
+        // Use the first accounting policy of the visited network that has been configured
+        let accounting_policy_setting =
+            <roaming_accounting_policies::Module<T>>::roaming_network_accounting_policies(visited_network_id)
+                .unwrap_or_default()
+                .into_iter()
+                .find_map(|roaming_accounting_policy_id| {
+                    <roaming_accounting_policies::Module<T>>::roaming_accounting_policy_settings(
+                        roaming_accounting_policy_id,
+                    )
+                })
+                .ok_or(Error::<T>::NetworkWithoutAccountingPolicy)?;
+
+        // Use the reconciled count if the home network server reported the packets it sent
+        let packets_ok_count: u128 =
+            match Self::roaming_packet_bundle_reconciliation((roaming_packet_bundle_id, roaming_network_server_id)) {
+                Some(reconciliation) => {
+                    match reconciliation.reconciliation_status {
+                        PacketCountStatus::Agreed | PacketCountStatus::Resolved => {
+                            reconciliation.reconciliation_final_packets_count.saturated_into()
+                        }
+                        PacketCountStatus::Disputed | PacketCountStatus::Challenged => {
+                            return Err(Error::<T>::PacketCountsDisputed.into());
+                        }
+                    }
+                }
+                None => packet_bundle_receiver.packet_bundle_received_packets_ok_count.saturated_into(),
+            };
+        // Packets over the count allowed by the uplink rate of the service profile of the device are charged at the
+        // penalty multiplier
+        let overage_packets_count: u128 =
+            match Self::roaming_packet_bundle_rate_check((roaming_packet_bundle_id, roaming_network_server_id)) {
+                Some(rate_check) => {
+                    packets_ok_count.saturating_sub(rate_check.rate_allowed_packets_count.saturated_into())
+                }
+                None => 0,
+            };
+        let charged_packets_count = packets_ok_count
+            .saturating_sub(overage_packets_count)
+            .saturating_add(overage_packets_count.saturating_mul(T::RateOveragePenaltyMultiplier::get().into()));
+        let uplink_fee_factor: u128 = accounting_policy_setting.uplink_fee_factor.saturated_into();
+        let settlement_amount: BalanceOf<T> = charged_packets_count.saturating_mul(uplink_fee_factor).saturated_into();
+
+        Ok((home_network_id, visited_network_id, settlement_amount))
+    }
+
+    /// Pay a settlement amount from the home network's agreement deposits for the visited network first, and
+    /// transfer any remainder from the home network operator owner. Must be called in a transactional context
+    /// since the deposits are drawn before the transfer may fail.
+    pub fn pay_settlement_amount(
+        home_roaming_network_id: T::RoamingNetworkIndex,
+        visited_roaming_network_id: T::RoamingNetworkIndex,
+        home_operator_account_id: &T::AccountId,
+        visited_operator_account_id: &T::AccountId,
+        settlement_amount: BalanceOf<T>,
+    ) -> DispatchResult {
+        let deposit_amount = Self::draw_agreement_deposit(
+            home_roaming_network_id,
+            visited_roaming_network_id,
+            visited_operator_account_id,
+            settlement_amount,
+        );
+        let remaining_amount = settlement_amount.saturating_sub(deposit_amount);
+        if !remaining_amount.is_zero() {
+            <T as roaming_operators::Config>::Currency::transfer(
+                home_operator_account_id,
+                visited_operator_account_id,
+                remaining_amount,
+                ExistenceRequirement::KeepAlive,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Record the settlement of a roaming packet bundle that was paid for
+    pub fn insert_packet_bundle_settlement(
+        roaming_packet_bundle_id: T::RoamingPacketBundleIndex,
+        roaming_network_server_id: T::RoamingNetworkServerIndex,
+        settlement_amount: BalanceOf<T>,
+        home_operator_account_id: T::AccountId,
+        visited_operator_account_id: T::AccountId,
+    ) {
+        <RoamingPacketBundleSettlements<T>>::insert(
+            roaming_packet_bundle_id,
+            roaming_network_server_id,
+            RoamingPacketBundleSettlement {
+                settlement_network_server_id: roaming_network_server_id,
+                settlement_amount,
+                settlement_settled_at_block: <frame_system::Module<T>>::block_number(),
+            },
+        );
+        Self::remove_unsettled_packet_bundle(roaming_packet_bundle_id, roaming_network_server_id);
+        Self::deposit_event(RawEvent::PacketBundleSettled(
+            home_operator_account_id,
+            visited_operator_account_id,
+            roaming_packet_bundle_id,
+            roaming_network_server_id,
+            settlement_amount,
+        ));
+    }
+
+    /// Count a roaming packet bundle received by a visited network server as unsettled between the home network of
+    /// its device and the visited network, so that the agreement deposits of the home network for the visited
+    /// network are held until it is settled. Packet bundles whose networks are not known yet cannot be settled.
+    fn insert_unsettled_packet_bundle(
+        roaming_packet_bundle_id: T::RoamingPacketBundleIndex,
+        roaming_network_server_id: T::RoamingNetworkServerIndex,
+    ) {
+        let key = (roaming_packet_bundle_id, roaming_network_server_id);
+        if <RoamingPacketBundleUnsettledNetworks<T>>::contains_key(key) ||
+            Self::roaming_packet_bundle_settlement(roaming_packet_bundle_id, roaming_network_server_id).is_some()
+        {
+            return;
+        }
+        let home_roaming_network_id =
+            match Self::home_network_server(roaming_packet_bundle_id).and_then(Self::network_server_network) {
+                Ok(value) => value,
+                Err(_) => return,
+            };
+        let visited_roaming_network_id = match Self::network_server_network(roaming_network_server_id) {
+            Ok(value) => value,
+            Err(_) => return,
+        };
+        if home_roaming_network_id == visited_roaming_network_id {
+            return;
+        }
+        <RoamingPacketBundleUnsettledNetworks<T>>::insert(key, (home_roaming_network_id, visited_roaming_network_id));
+        <RoamingNetworkUnsettledPacketBundlesCount<T>>::mutate(
+            (home_roaming_network_id, visited_roaming_network_id),
+            |count| *count = count.saturating_add(1),
+        );
+    }
+
+    /// Stop counting a settled roaming packet bundle receiver as unsettled between its networks
+    fn remove_unsettled_packet_bundle(
+        roaming_packet_bundle_id: T::RoamingPacketBundleIndex,
+        roaming_network_server_id: T::RoamingNetworkServerIndex,
+    ) {
+        if let Some(networks) =
+            <RoamingPacketBundleUnsettledNetworks<T>>::take((roaming_packet_bundle_id, roaming_network_server_id))
+        {
+            <RoamingNetworkUnsettledPacketBundlesCount<T>>::mutate_exists(networks, |count| {
+                *count = count.map(|count| count.saturating_sub(1)).filter(|count| *count > 0)
+            });
+        }
+    }
+
+    /// The weight of hashing the leaf and each sibling of a packet proof, counting at most the deepest proof
+    /// since deeper proofs are rejected before they are hashed
+    pub fn packet_proof_weight(proof: &PacketProof) -> Weight {
+        let nodes_count = proof.siblings.len().min(MAX_PROOF_DEPTH as usize) as Weight + 1;
+        nodes_count.saturating_mul(PACKET_PROOF_NODE_WEIGHT)
+    }
+
+    fn ensure_packet_proof_depth(proof: &PacketProof) -> Result<(), Error<T>> {
+        ensure!(proof.siblings.len() <= MAX_PROOF_DEPTH as usize, Error::<T>::PacketProofTooDeep);
+        Ok(())
+    }
+
+    fn insert_verified_packet(
+        prover: T::AccountId,
+        roaming_packet_bundle_id: T::RoamingPacketBundleIndex,
+        roaming_network_server_id: T::RoamingNetworkServerIndex,
+        dev_addr: u32,
+        f_cnt: u32,
+        included: bool,
+    ) {
+        <RoamingPacketBundleVerifiedPackets<T>>::insert(
+            (roaming_packet_bundle_id, roaming_network_server_id, dev_addr, f_cnt),
+            included,
+        );
+        Self::deposit_event(RawEvent::PacketInclusionVerified(
+            prover,
+            roaming_packet_bundle_id,
+            roaming_network_server_id,
+            dev_addr,
+            f_cnt,
+            included,
+        ));
+    }
+
+    /// Get the number of packet bundles received by a network server that have not yet been billed
+    pub fn unbilled_packet_bundles_count(roaming_network_server_id: T::RoamingNetworkServerIndex) -> u32 {
+        <RoamingNetworkServerUnbilledPacketBundles<T>>::decode_len(roaming_network_server_id).unwrap_or(0) as u32
+    }
+
+    /// Take up to the given number of the packet bundles received by a network server that have not yet been
+    /// billed, in the order they were received. The rest are left to be taken later.
+    pub fn take_unbilled_packet_bundles(
+        roaming_network_server_id: T::RoamingNetworkServerIndex,
+        max_packet_bundles_count: u32,
+    ) -> Vec<T::RoamingPacketBundleIndex> {
+        let mut roaming_packet_bundle_ids =
+            <RoamingNetworkServerUnbilledPacketBundles<T>>::take(roaming_network_server_id);
+        let max_packet_bundles_count = max_packet_bundles_count as usize;
+        if roaming_packet_bundle_ids.len() > max_packet_bundles_count {
+            let remaining_packet_bundle_ids = roaming_packet_bundle_ids.split_off(max_packet_bundles_count);
+            <RoamingNetworkServerUnbilledPacketBundles<T>>::insert(
+                roaming_network_server_id,
+                remaining_packet_bundle_ids,
+            );
+        }
+        // A packet bundle is appended again when its disputed packet counts are reconciled
+        roaming_packet_bundle_ids.sort();
+        roaming_packet_bundle_ids.dedup();
+        roaming_packet_bundle_ids
+    }
+
+    /// Append a packet bundle received by a network server that could not be billed yet, so that it is billed at a
+    /// later billing point, unless it is already queued
+    pub fn requeue_unbilled_packet_bundle(
+        roaming_network_server_id: T::RoamingNetworkServerIndex,
+        roaming_packet_bundle_id: T::RoamingPacketBundleIndex,
+    ) {
+        <RoamingNetworkServerUnbilledPacketBundles<T>>::mutate(roaming_network_server_id, |packet_bundle_ids| {
+            if !packet_bundle_ids.contains(&roaming_packet_bundle_id) {
+                packet_bundle_ids.push(roaming_packet_bundle_id);
+            }
+        });
+    }
+
+    /// Get the network server of the device of the session of a packet bundle
+    pub fn home_network_server(
+        roaming_packet_bundle_id: T::RoamingPacketBundleIndex,
+    ) -> Result<T::RoamingNetworkServerIndex, Error<T>> {
+        let roaming_session_id =
+            Self::roaming_packet_bundle_session(roaming_packet_bundle_id).ok_or(Error::<T>::PacketBundleWithoutSession)?;
+        let roaming_device_id = <roaming_sessions::Module<T>>::roaming_session_device(roaming_session_id)
+            .ok_or(Error::<T>::SessionWithoutDevice)?;
+        <roaming_devices::Module<T>>::roaming_device_network_server(roaming_device_id)
+            .ok_or(Error::<T>::DeviceWithoutNetworkServer)
+    }
+
+    /// Get the uplink rate of the service profile of the home network server of the device of a packet bundle, using
+    /// the first service profile assigned to the network server that has an uplink rate
+    pub fn uplink_rate(
+        roaming_packet_bundle_id: T::RoamingPacketBundleIndex,
+    ) -> Option<T::RoamingServiceProfileUplinkRate> {
+        let home_network_server_id = Self::home_network_server(roaming_packet_bundle_id).ok()?;
+        <roaming_service_profiles::Module<T>>::roaming_network_server_service_profiles(home_network_server_id)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|roaming_service_profile_id| {
+                <roaming_service_profiles::Module<T>>::roaming_service_profile_network_server(
+                    roaming_service_profile_id,
+                ) == Some(home_network_server_id)
+            })
+            .find_map(|roaming_service_profile_id| {
+                <roaming_service_profiles::Module<T>>::roaming_service_profile_uplink_rate(roaming_service_profile_id)
+            })
+    }
+
+    /// Check the packets that a visited network server received ok in a packet bundle against the count that the
+    /// uplink rate of the service profile of the device allows over the block span of the packet bundle, from the
+    /// block it was assigned to its session to the block its receiver was set, as recorded on chain rather than as
+    /// reported by the network server. The allowed count is the uplink rate per rate limit period, pro rata and
+    /// rounded up. Any overage is flagged, and the
+    /// allowed count is kept to charge the overage of the final packet count at settlement.
+    fn check_packet_rate(
+        roaming_packet_bundle_id: T::RoamingPacketBundleIndex,
+        roaming_network_server_id: T::RoamingNetworkServerIndex,
+    ) {
+        let key = (roaming_packet_bundle_id, roaming_network_server_id);
+        let (packet_bundle_receiver, uplink_rate) =
+            match (Self::roaming_packet_bundle_receivers(key), Self::uplink_rate(roaming_packet_bundle_id)) {
+                (Some(packet_bundle_receiver), Some(uplink_rate)) => (packet_bundle_receiver, uplink_rate),
+                _ => {
+                    <RoamingPacketBundleRateChecks<T>>::remove(key);
+                    return;
+                }
+            };
+
+        // A packet bundle spans at least the block it was received in
+        let received_at_block =
+            Self::roaming_packet_bundle_received_at_block(key).unwrap_or_else(<frame_system::Module<T>>::block_number);
+        let assigned_at_block = Self::roaming_packet_bundle_session_assigned_at_block(roaming_packet_bundle_id)
+            .unwrap_or(received_at_block);
+        let blocks_count: u128 = received_at_block.saturating_sub(assigned_at_block).max(One::one()).saturated_into();
+        let rate_limit_period: u128 = T::RateLimitPeriod::get().max(One::one()).saturated_into();
+        let uplink_rate: u128 = uplink_rate.saturated_into();
+        let allowed_packets_count =
+            uplink_rate.saturating_mul(blocks_count).saturating_add(rate_limit_period - 1) / rate_limit_period;
+        let received_packets_ok_count: u128 =
+            packet_bundle_receiver.packet_bundle_received_packets_ok_count.saturated_into();
+        let overage_packets_count = received_packets_ok_count.saturating_sub(allowed_packets_count);
+
+        <RoamingPacketBundleRateChecks<T>>::insert(key, RoamingPacketBundleRateCheck {
+            rate_allowed_packets_count: allowed_packets_count.saturated_into(),
+            rate_overage_packets_count: overage_packets_count.saturated_into(),
+        });
+        if overage_packets_count > 0 {
+            Self::deposit_event(RawEvent::PacketRateExceeded(
+                roaming_packet_bundle_id,
+                roaming_network_server_id,
+                packet_bundle_receiver.packet_bundle_received_packets_ok_count,
+                allowed_packets_count.saturated_into(),
+            ));
+        }
+    }
+
+    fn is_packet_counts_locked(
+        roaming_packet_bundle_id: T::RoamingPacketBundleIndex,
+        roaming_network_server_id: T::RoamingNetworkServerIndex,
+    ) -> bool {
+        Self::roaming_packet_bundle_reconciliation((roaming_packet_bundle_id, roaming_network_server_id))
+            .map(|reconciliation| reconciliation.reconciliation_status != PacketCountStatus::Disputed)
+            .unwrap_or(false)
+    }
+
+    /// Reconcile the packets count sent by the home network server with the packets ok count received by the
+    /// visited network server once both have been reported. Counts that agree within the tolerance are accepted at
+    /// the lower count, and otherwise the packet bundle is disputed.
+    fn reconcile_packet_counts(
+        roaming_packet_bundle_id: T::RoamingPacketBundleIndex,
+        roaming_network_server_id: T::RoamingNetworkServerIndex,
+    ) {
+        let sent_packets_count =
+            match Self::roaming_packet_bundle_sent_packets_count((roaming_packet_bundle_id, roaming_network_server_id)) {
+                Some(value) => value,
+                None => return,
+            };
+        let received_packets_ok_count =
+            match Self::roaming_packet_bundle_receivers((roaming_packet_bundle_id, roaming_network_server_id)) {
+                Some(packet_bundle_receiver) => packet_bundle_receiver.packet_bundle_received_packets_ok_count,
+                None => return,
+            };
+
+        let lower_packets_count = sent_packets_count.min(received_packets_ok_count);
+        let higher_packets_count: u128 = sent_packets_count.max(received_packets_ok_count).saturated_into();
+        let tolerated_difference = T::PacketCountTolerance::get() * higher_packets_count;
+        let is_agreed = Self::packet_count_difference(sent_packets_count, received_packets_ok_count) <= tolerated_difference;
+        let (reconciliation_final_packets_count, reconciliation_status) = if is_agreed {
+            (lower_packets_count, PacketCountStatus::Agreed)
+        } else {
+            (Zero::zero(), PacketCountStatus::Disputed)
+        };
+
+        <RoamingPacketBundleReconciliations<T>>::insert(
+            (roaming_packet_bundle_id, roaming_network_server_id),
+            RoamingPacketBundleReconciliation {
+                reconciliation_sent_packets_count: sent_packets_count,
+                reconciliation_received_packets_ok_count: received_packets_ok_count,
+                reconciliation_final_packets_count,
+                reconciliation_status,
+            },
+        );
+        if is_agreed {
+            // The packet bundle may have been skipped by a billing point while it was disputed
+            Self::requeue_unbilled_packet_bundle(roaming_network_server_id, roaming_packet_bundle_id);
+        }
+
+        Self::deposit_event(RawEvent::PacketCountsReconciled(
+            roaming_packet_bundle_id,
+            roaming_network_server_id,
+            sent_packets_count,
+            received_packets_ok_count,
+            reconciliation_status,
+        ));
+    }
+
+    fn packet_count_difference(
+        packets_count: T::RoamingPacketBundleReceivedPacketsOkCount,
+        other_packets_count: T::RoamingPacketBundleReceivedPacketsOkCount,
+    ) -> u128 {
+        let packets_count: u128 = packets_count.saturated_into();
+        let other_packets_count: u128 = other_packets_count.saturated_into();
+        if packets_count > other_packets_count {
+            packets_count - other_packets_count
+        } else {
+            other_packets_count - packets_count
+        }
+    }
+
+    fn draw_agreement_deposit(
+        home_roaming_network_id: T::RoamingNetworkIndex,
+        visited_roaming_network_id: T::RoamingNetworkIndex,
+        beneficiary: &T::AccountId,
+        amount: BalanceOf<T>,
+    ) -> BalanceOf<T> {
+        // The agreement deposits are held in the currency of the agreement policies
+        <roaming_agreement_policies::Module<T>>::draw_agreement_deposit(
+            home_roaming_network_id,
+            visited_roaming_network_id,
+            beneficiary,
+            amount.saturated_into::<u128>().saturated_into(),
+        )
+        .saturated_into::<u128>()
+        .saturated_into()
+    }
+
+    fn network_server_network(
+        roaming_network_server_id: T::RoamingNetworkServerIndex,
+    ) -> Result<T::RoamingNetworkIndex, Error<T>> {
+        <roaming_network_servers::Module<T>>::roaming_network_server_network(roaming_network_server_id)
+            .ok_or(Error::<T>::NetworkServerWithoutNetwork)
+    }
+
+    /// Get the owner of the operator of a roaming network
+    pub fn network_operator_account(roaming_network_id: T::RoamingNetworkIndex) -> Result<T::AccountId, Error<T>> {
+        <roaming_networks::Module<T>>::roaming_network_operator(roaming_network_id)
+            .and_then(|roaming_operator_id| {
+                <T as roaming_operators::Config>::RoamingOperatorRegistry::owner_of(roaming_operator_id)
+            })
+            .ok_or(Error::<T>::NetworkWithoutOperator)
+    }
+
+    /// Remove a roaming packet bundle along with its receivers and its links to its session and device session
+    pub fn remove_roaming_packet_bundle(roaming_packet_bundle_id: T::RoamingPacketBundleIndex) -> DispatchResult {
+        Self::exists_roaming_packet_bundle(roaming_packet_bundle_id)?;
+
+        for roaming_network_server_id in
+            Self::roaming_packet_bundle_network_servers(roaming_packet_bundle_id).unwrap_or_default()
+        {
+            Self::remove_packet_bundle_receiver(roaming_packet_bundle_id, roaming_network_server_id)?;
+        }
+        <RoamingPacketBundleSessionAssignedAtBlocks<T>>::remove(roaming_packet_bundle_id);
+        if let Some(roaming_session_id) = <RoamingPacketBundleSession<T>>::take(roaming_packet_bundle_id) {
+            <RoamingSessionPacketBundles<T>>::mutate_exists(roaming_session_id, |packet_bundle_ids| {
+                *packet_bundle_ids = without_roaming_entity(packet_bundle_ids.take(), &roaming_packet_bundle_id)
+            });
+        }
+        if let Some(device_session) = <RoamingPacketBundleDeviceSession<T>>::take(roaming_packet_bundle_id) {
+            <RoamingDeviceSessionPacketBundles<T>>::mutate_exists(device_session, |packet_bundle_ids| {
+                *packet_bundle_ids = without_roaming_entity(packet_bundle_ids.take(), &roaming_packet_bundle_id)
+            });
+        }
+        T::RoamingPacketBundleRegistry::deregister(roaming_packet_bundle_id)?;
+        <RoamingPacketBundleSettlements<T>>::remove_prefix(roaming_packet_bundle_id);
+        Ok(())
+    }
+
+    /// Remove the receiver of a roaming packet bundle by a network server along with its committed root, its
+    /// packet counts and its reverse indexes. The settlement of a receiver is what the visited network is owed for
+    /// it, so a receiver may only be removed once it has been settled, and not while its packet
+    /// counts are challenged since the challenger's bond is reserved until they are resolved.
+    pub fn remove_packet_bundle_receiver(
+        roaming_packet_bundle_id: T::RoamingPacketBundleIndex,
+        roaming_network_server_id: T::RoamingNetworkServerIndex,
+    ) -> DispatchResult {
+        let key = (roaming_packet_bundle_id, roaming_network_server_id);
+        ensure!(Self::roaming_packet_bundle_challenge(key).is_none(), Error::<T>::PacketCountsDisputed);
+        if let Some(packet_bundle_receiver) = Self::roaming_packet_bundle_receivers(key) {
+            ensure!(
+                Self::roaming_packet_bundle_settlement(roaming_packet_bundle_id, roaming_network_server_id).is_some(),
+                Error::<T>::PacketBundleNotSettled
+            );
+            <RoamingPacketBundleReceivers<T>>::remove(key);
+            <RoamingExternalDataStorageHashPacketBundle<T>>::mutate_exists(
+                packet_bundle_receiver.packet_bundle_external_data_storage_hash,
+                |packet_bundle_ids| {
+                    *packet_bundle_ids = without_roaming_entity(packet_bundle_ids.take(), &roaming_packet_bundle_id)
+                },
+            );
+        }
+        <RoamingPacketBundleReceivedAtBlocks<T>>::remove(key);
+        <RoamingPacketBundleDataRoots<T>>::remove(key);
+        <RoamingPacketBundleSentPacketsCounts<T>>::remove(key);
+        <RoamingPacketBundleReconciliations<T>>::remove(key);
+        <RoamingPacketBundleRateChecks<T>>::remove(key);
+        <RoamingNetworkServerUnbilledPacketBundles<T>>::mutate_exists(roaming_network_server_id, |packet_bundle_ids| {
+            *packet_bundle_ids = without_roaming_entity(packet_bundle_ids.take(), &roaming_packet_bundle_id)
+        });
+        <RoamingNetworkServerPacketBundles<T>>::remove(roaming_network_server_id, roaming_packet_bundle_id);
+        <RoamingPacketBundleNetworkServers<T>>::mutate_exists(roaming_packet_bundle_id, |network_server_ids| {
+            *network_server_ids = without_roaming_entity(network_server_ids.take(), &roaming_network_server_id)
+        });
+        Ok(())
+    }
+
     /// Only push the packet bundle id onto the end of the vector if it does not already exist
     pub fn associate_packet_bundle_with_session(
         roaming_packet_bundle_id: T::RoamingPacketBundleIndex,
@@ -604,36 +1704,202 @@ impl<T: Config> Module<T> {
     //     }
     // }
 
-    fn random_value(sender: &T::AccountId) -> [u8; 16] {
-        let payload = (
-            T::Randomness::random(&[0]),
-            sender,
-            <frame_system::Module<T>>::extrinsic_index(),
-            <frame_system::Module<T>>::block_number(),
-        );
-        payload.using_encoded(blake2_128)
+
+    /// The packet bundles that are still assigned to a roaming session
+    pub fn session_packet_bundles(roaming_session_id: T::RoamingSessionIndex) -> Vec<T::RoamingPacketBundleIndex> {
+        Self::iter_session_packet_bundles_from(roaming_session_id, None).collect()
+    }
+
+    /// The packet bundles that are still assigned to a roaming session in the order they were assigned, starting at
+    /// the given packet bundle. Each packet bundle is only checked to still be assigned as it is read.
+    pub fn iter_session_packet_bundles_from(
+        roaming_session_id: T::RoamingSessionIndex,
+        start: Option<T::RoamingPacketBundleIndex>,
+    ) -> impl Iterator<Item = T::RoamingPacketBundleIndex> {
+        Self::roaming_session_packet_bundles(roaming_session_id)
+            .unwrap_or_default()
+            .into_iter()
+            .skip_while(move |packet_bundle_id| start.map_or(false, |start| *packet_bundle_id != start))
+            .filter(move |packet_bundle_id| {
+                Self::roaming_packet_bundle_session(packet_bundle_id) == Some(roaming_session_id)
+            })
+    }
+
+    /// The packet bundles received by a roaming network server in ascending order
+    pub fn network_server_packet_bundles(
+        roaming_network_server_id: T::RoamingNetworkServerIndex,
+    ) -> Vec<T::RoamingPacketBundleIndex> {
+        let mut packet_bundle_ids: Vec<T::RoamingPacketBundleIndex> =
+            <RoamingNetworkServerPacketBundles<T>>::iter_prefix(roaming_network_server_id)
+                .map(|(packet_bundle_id, _)| packet_bundle_id)
+                .collect();
+        packet_bundle_ids.sort();
+        packet_bundle_ids
+    }
+
+    /// Move the packet bundles received by each roaming network server from a vector into the keys of a double map,
+    /// dropping any packet bundle that the network server no longer has a receiver for
+    fn migrate_to_network_server_packet_bundle_keys() -> Weight {
+        let roaming_network_servers_count = get_storage_value::<T::RoamingNetworkServerIndex>(
+            b"RoamingNetworkServers",
+            b"RoamingNetworkServersCount",
+            &[],
+        )
+        .unwrap_or_else(<T as roaming_network_servers::Config>::RoamingNetworkServerRegistry::entities_count);
+        let mut migrated_count: Weight = 0;
+        let mut packet_bundle_count: Weight = 0;
+
+        let mut roaming_network_server_id: T::RoamingNetworkServerIndex = Zero::zero();
+        while roaming_network_server_id < roaming_network_servers_count {
+            let packet_bundle_ids = take_storage_value::<Vec<T::RoamingPacketBundleIndex>>(
+                b"RoamingPacketBundles",
+                b"RoamingNetworkServerPacketBundles",
+                &blake2_256(&roaming_network_server_id.encode()),
+            )
+            .unwrap_or_default();
+            for roaming_packet_bundle_id in packet_bundle_ids {
+                if <RoamingPacketBundleReceivers<T>>::contains_key((
+                    roaming_packet_bundle_id,
+                    roaming_network_server_id,
+                )) {
+                    <RoamingNetworkServerPacketBundles<T>>::insert(
+                        roaming_network_server_id,
+                        roaming_packet_bundle_id,
+                        (),
+                    );
+                }
+                packet_bundle_count += 1;
+            }
+
+            migrated_count += 1;
+            roaming_network_server_id = roaming_network_server_id + One::one();
+        }
+
+        StorageVersion::put(Releases::V2_0_0);
+        debug::info!("Migrated the packet bundles of {:?} roaming network servers to double map keys", migrated_count);
+
+        T::DbWeight::get()
+            .reads_writes(2 + migrated_count + packet_bundle_count * 2, 1 + migrated_count + packet_bundle_count)
     }
 
-    fn next_roaming_packet_bundle_id() -> Result<T::RoamingPacketBundleIndex, DispatchError> {
-        let roaming_packet_bundle_id = Self::roaming_packet_bundles_count();
-        if roaming_packet_bundle_id == <T::RoamingPacketBundleIndex as Bounded>::max_value() {
-            return Err(DispatchError::Other("RoamingPacketBundles count overflow"));
+    /// Move the settlement of each roaming packet bundle under the network server that it was settled for, so that
+    /// the other network servers that received the packet bundle may still be settled
+    fn migrate_to_packet_bundle_receiver_settlements() -> Weight {
+        let roaming_packet_bundles_count = T::RoamingPacketBundleRegistry::entities_count();
+        let mut migrated_count: Weight = 0;
+
+        let mut roaming_packet_bundle_id: T::RoamingPacketBundleIndex = Zero::zero();
+        while roaming_packet_bundle_id < roaming_packet_bundles_count {
+            if let Some(settlement) = take_storage_value::<
+                RoamingPacketBundleSettlement<T::RoamingNetworkServerIndex, BalanceOf<T>, T::BlockNumber>,
+            >(
+                b"RoamingPacketBundles",
+                b"RoamingPacketBundleSettlements",
+                &blake2_256(&roaming_packet_bundle_id.encode()),
+            ) {
+                <RoamingPacketBundleSettlements<T>>::insert(
+                    roaming_packet_bundle_id,
+                    settlement.settlement_network_server_id,
+                    settlement,
+                );
+                migrated_count += 1;
+            }
+            roaming_packet_bundle_id = roaming_packet_bundle_id + One::one();
         }
-        Ok(roaming_packet_bundle_id)
+
+        StorageVersion::put(Releases::V3_0_0);
+        debug::info!("Migrated {:?} roaming packet bundle settlements to packet bundle receiver keys", migrated_count);
+
+        let roaming_packet_bundles_count: Weight = roaming_packet_bundles_count.saturated_into();
+        T::DbWeight::get().reads_writes(1 + roaming_packet_bundles_count, 1 + migrated_count * 2)
     }
 
-    fn insert_roaming_packet_bundle(
-        owner: &T::AccountId,
-        roaming_packet_bundle_id: T::RoamingPacketBundleIndex,
-        roaming_packet_bundle: RoamingPacketBundle,
-    ) {
-        // Create and store roaming packet_bundle
-        <RoamingPacketBundles<T>>::insert(roaming_packet_bundle_id, roaming_packet_bundle);
-        <RoamingPacketBundlesCount<T>>::put(roaming_packet_bundle_id + One::one());
-        <RoamingPacketBundleOwners<T>>::insert(roaming_packet_bundle_id, owner.clone());
+    /// Start the settlement period of each existing roaming packet bundle receiver at the upgrade, since the block it
+    /// was received at was not recorded, and count the unsettled receivers between their home and visited networks
+    fn migrate_to_recorded_packet_bundle_receipts() -> Weight {
+        let current_block_number = <frame_system::Module<T>>::block_number();
+        let mut migrated_count: Weight = 0;
+
+        for (roaming_network_server_id, roaming_packet_bundle_id, _) in <RoamingNetworkServerPacketBundles<T>>::iter() {
+            let key = (roaming_packet_bundle_id, roaming_network_server_id);
+            if !<RoamingPacketBundleReceivedAtBlocks<T>>::contains_key(key) {
+                <RoamingPacketBundleReceivedAtBlocks<T>>::insert(key, current_block_number);
+            }
+            Self::insert_unsettled_packet_bundle(roaming_packet_bundle_id, roaming_network_server_id);
+            migrated_count += 1;
+        }
+
+        StorageVersion::put(Releases::V4_0_0);
+        debug::info!("Recorded the receipts of {:?} roaming packet bundle receivers", migrated_count);
+
+        T::DbWeight::get().reads_writes(1 + migrated_count * 10, 1 + migrated_count * 3)
+    }
+
+    /// Record the block that each packet bundle assigned to a session was assigned at. The blocks that packet bundles
+    /// were assigned at before they were recorded are unknown, so the block of the upgrade is recorded instead, which
+    /// allows them no more packets than if they had been assigned then.
+    fn migrate_to_recorded_packet_bundle_session_assignments() -> Weight {
+        let current_block_number = <frame_system::Module<T>>::block_number();
+        let mut migrated_count: Weight = 0;
+
+        for (roaming_packet_bundle_id, _) in <RoamingPacketBundleSession<T>>::iter() {
+            if !<RoamingPacketBundleSessionAssignedAtBlocks<T>>::contains_key(roaming_packet_bundle_id) {
+                <RoamingPacketBundleSessionAssignedAtBlocks<T>>::insert(roaming_packet_bundle_id, current_block_number);
+            }
+            migrated_count += 1;
+        }
+
+        StorageVersion::put(Releases::V5_0_0);
+        debug::info!("Recorded the session assignments of {:?} roaming packet bundles", migrated_count);
+
+        T::DbWeight::get().reads_writes(1 + migrated_count * 2, 1 + migrated_count)
+    }
+}
+
+/// The packet bundles that home networks settle with visited networks from their agreement deposits
+impl<T: Config> RoamingAgreementSettlements<T::RoamingNetworkIndex, T::BlockNumber> for Module<T> {
+    fn settlement_period() -> T::BlockNumber {
+        T::PacketBundleSettlementPeriod::get()
+    }
+
+    fn has_unsettled_packet_bundles(
+        home_roaming_network_id: T::RoamingNetworkIndex,
+        visited_roaming_network_id: T::RoamingNetworkIndex,
+    ) -> bool {
+        Self::roaming_network_unsettled_packet_bundles_count((home_roaming_network_id, visited_roaming_network_id)) > 0
+    }
+}
+
+/// The packet bundles of a roaming session, other than those that have since been assigned to another session
+impl<T: Config> RoamingEntityChildren<RoamingSessionEntity, T::RoamingSessionIndex> for Module<T> {
+    fn has_children(roaming_session_id: T::RoamingSessionIndex) -> bool {
+        Self::roaming_session_packet_bundles(roaming_session_id)
+            .unwrap_or_default()
+            .into_iter()
+            .any(|packet_bundle_id| Self::roaming_packet_bundle_session(packet_bundle_id) == Some(roaming_session_id))
     }
 
-    fn update_owner(to: &T::AccountId, roaming_packet_bundle_id: T::RoamingPacketBundleIndex) {
-        <RoamingPacketBundleOwners<T>>::insert(roaming_packet_bundle_id, to);
+    fn remove_children(roaming_session_id: T::RoamingSessionIndex) -> DispatchResult {
+        for packet_bundle_id in <RoamingSessionPacketBundles<T>>::take(roaming_session_id).unwrap_or_default() {
+            if Self::roaming_packet_bundle_session(packet_bundle_id) == Some(roaming_session_id) {
+                Self::remove_roaming_packet_bundle(packet_bundle_id)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The receivers of packet bundles by a roaming network server. Removing them leaves the packet bundles, which
+/// belong to the sessions of their home network.
+impl<T: Config> RoamingEntityChildren<RoamingNetworkServerEntity, T::RoamingNetworkServerIndex> for Module<T> {
+    fn has_children(roaming_network_server_id: T::RoamingNetworkServerIndex) -> bool {
+        <RoamingNetworkServerPacketBundles<T>>::iter_prefix(roaming_network_server_id).next().is_some()
+    }
+
+    fn remove_children(roaming_network_server_id: T::RoamingNetworkServerIndex) -> DispatchResult {
+        for packet_bundle_id in Self::network_server_packet_bundles(roaming_network_server_id) {
+            Self::remove_packet_bundle_receiver(packet_bundle_id, roaming_network_server_id)?;
+        }
+        Ok(())
     }
 }
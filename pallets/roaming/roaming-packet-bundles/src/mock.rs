@@ -1,6 +1,7 @@
 // Creating mock runtime here
 
 use crate::{
+    GenesisConfig,
     Module,
     Config,
 };
@@ -21,6 +22,7 @@ use sp_runtime::{
         IdentityLookup,
     },
     Perbill,
+    Permill,
 };
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
@@ -87,42 +89,243 @@ impl pallet_transaction_payment::Config for Test {
     type TransactionByteFee = TransactionByteFee;
     type WeightToFee = IdentityFee<u64>;
 }
-impl roaming_operators::Config for Test {
+impl roaming_registry::Config<roaming_registry::Instance1> for Test {
     type Currency = Balances;
+    type EntityDeposit = ();
     type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
     type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+impl roaming_operators::Config for Test {
+    type Currency = Balances;
+    type Event = ();
+    type RemovalPolicy = ();
+    type RoamingOperatorChildren = ();
     type RoamingOperatorIndex = u64;
+    type RoamingOperatorRegistry = RoamingOperatorRegistry;
+}
+impl roaming_registry::Config<roaming_registry::Instance2> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
 }
 impl roaming_networks::Config for Test {
     type Event = ();
+    type RoamingNetworkChildren = ();
     type RoamingNetworkIndex = u64;
+    type RoamingNetworkRegistry = RoamingNetworkRegistry;
+}
+impl roaming_registry::Config<roaming_registry::Instance4> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
 }
 impl roaming_network_servers::Config for Test {
     type Event = ();
+    type RoamingNetworkServerChildren = ();
     type RoamingNetworkServerIndex = u64;
+    type RoamingNetworkServerRegistry = RoamingNetworkServerRegistry;
+}
+impl roaming_registry::Config<roaming_registry::Instance3> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
 }
 impl roaming_organizations::Config for Test {
     type Event = ();
+    type RoamingOrganizationChildren = ();
     type RoamingOrganizationIndex = u64;
+    type RoamingOrganizationRegistry = RoamingOrganizationRegistry;
+}
+impl roaming_registry::Config<roaming_registry::Instance5> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+parameter_types! {
+    pub const MaxRoamingPolicyNetworks: u32 = 2;
 }
 impl roaming_devices::Config for Test {
     type Event = ();
+    type MaxRoamingPolicyNetworks = MaxRoamingPolicyNetworks;
+    type RoamingDeviceChildren = ();
     type RoamingDeviceIndex = u64;
+    type RoamingDeviceRegistry = RoamingDeviceRegistry;
+}
+impl roaming_registry::Config<roaming_registry::Instance8> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+parameter_types! {
+    pub const MaxSubscriptionRenewalsPerBlock: u32 = 2;
+    pub const SubscriptionBillingPeriod: u64 = 10;
+}
+impl roaming_accounting_policies::Config for Test {
+    type Event = ();
+    type MaxSubscriptionRenewalsPerBlock = MaxSubscriptionRenewalsPerBlock;
+    type RoamingAccountingPolicyChildren = ();
+    type RoamingAccountingPolicyDownlinkFeeFactor = u32;
+    type RoamingAccountingPolicyIndex = u64;
+    type RoamingAccountingPolicyRegistry = RoamingAccountingPolicyRegistry;
+    type RoamingAccountingPolicyUplinkFeeFactor = u32;
+    type SubscriptionBillingPeriod = SubscriptionBillingPeriod;
+}
+impl roaming_registry::Config<roaming_registry::Instance9> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+impl roaming_agreement_policies::Config for Test {
+    type Currency = Balances;
+    type Event = ();
+    type RoamingAgreementPolicyActivationType = Vec<u8>;
+    type RoamingAgreementPolicyIndex = u64;
+    type RoamingAgreementPolicyRegistry = RoamingAgreementPolicyRegistry;
+    type RoamingAgreementSettlements = RoamingPacketBundleModule;
+}
+impl roaming_registry::Config<roaming_registry::Instance10> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+impl roaming_network_profiles::Config for Test {
+    type Event = ();
+    type RoamingNetworkProfileIndex = u64;
+    type RoamingNetworkProfileRegistry = RoamingNetworkProfileRegistry;
+}
+parameter_types! {
+    pub const MaxSessionDuration: u64 = 100;
+    pub const MaxSessionExpiriesPerBlock: u32 = 2;
+    pub const MaxSessionExpiryDelay: u32 = 2;
+}
+impl roaming_registry::Config<roaming_registry::Instance7> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+impl roaming_service_profiles::Config for Test {
+    type Event = ();
+    type RoamingServiceProfileDownlinkRate = u32;
+    type RoamingServiceProfileIndex = u64;
+    type RoamingServiceProfileRegistry = RoamingServiceProfileRegistry;
+    type RoamingServiceProfileUplinkRate = u32;
+}
+impl roaming_registry::Config<roaming_registry::Instance12> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
 }
 impl roaming_sessions::Config for Test {
     type Event = ();
+    type MaxSessionDuration = MaxSessionDuration;
+    type MaxSessionExpiriesPerBlock = MaxSessionExpiriesPerBlock;
+    type MaxSessionExpiryDelay = MaxSessionExpiryDelay;
+    type RoamingSessionChildren = ();
     type RoamingSessionIndex = u64;
+    type RoamingSessionRegistry = RoamingSessionRegistry;
+}
+parameter_types! {
+    pub const PacketBundleSettlementPeriod: u64 = 10;
+    pub const PacketCountTolerance: Permill = Permill::from_percent(20);
+    pub const PacketCountChallengeBond: u64 = 5;
+    pub const RateLimitPeriod: u64 = 10;
+    pub const RateOveragePenaltyMultiplier: u32 = 3;
+}
+impl roaming_registry::Config<roaming_registry::Instance15> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
 }
 impl Config for Test {
     type Event = ();
+    type PacketBundleSettlementPeriod = PacketBundleSettlementPeriod;
+    type PacketCountArbitrationOrigin = frame_system::EnsureRoot<u64>;
+    type PacketCountChallengeBond = PacketCountChallengeBond;
+    type PacketCountTolerance = PacketCountTolerance;
+    type RateLimitPeriod = RateLimitPeriod;
+    type RateOveragePenaltyMultiplier = RateOveragePenaltyMultiplier;
+    type RoamingPacketBundleBilling = ();
     type RoamingPacketBundleExternalDataStorageHash = H256;
     type RoamingPacketBundleIndex = u64;
     type RoamingPacketBundleReceivedAtHome = bool;
     type RoamingPacketBundleReceivedPacketsCount = u64;
     type RoamingPacketBundleReceivedPacketsOkCount = u64;
+    type RoamingPacketBundleRegistry = RoamingPacketBundleRegistry;
 }
 
 pub type RoamingPacketBundleModule = Module<Test>;
+pub type RoamingOperatorModule = roaming_operators::Module<Test>;
+pub type RoamingNetworkModule = roaming_networks::Module<Test>;
+pub type RoamingNetworkServerModule = roaming_network_servers::Module<Test>;
+pub type RoamingDeviceModule = roaming_devices::Module<Test>;
+pub type RoamingAccountingPolicyModule = roaming_accounting_policies::Module<Test>;
+pub type RoamingAgreementPolicyModule = roaming_agreement_policies::Module<Test>;
+pub type RoamingServiceProfileModule = roaming_service_profiles::Module<Test>;
+pub type RoamingOperatorRegistry = roaming_registry::Module<Test, roaming_registry::Instance1>;
+pub type RoamingNetworkRegistry = roaming_registry::Module<Test, roaming_registry::Instance2>;
+pub type RoamingNetworkServerRegistry = roaming_registry::Module<Test, roaming_registry::Instance4>;
+pub type RoamingOrganizationRegistry = roaming_registry::Module<Test, roaming_registry::Instance3>;
+pub type RoamingDeviceRegistry = roaming_registry::Module<Test, roaming_registry::Instance5>;
+pub type RoamingAccountingPolicyRegistry = roaming_registry::Module<Test, roaming_registry::Instance8>;
+pub type RoamingAgreementPolicyRegistry = roaming_registry::Module<Test, roaming_registry::Instance9>;
+pub type RoamingNetworkProfileRegistry = roaming_registry::Module<Test, roaming_registry::Instance10>;
+pub type RoamingServiceProfileRegistry = roaming_registry::Module<Test, roaming_registry::Instance7>;
+pub type RoamingSessionRegistry = roaming_registry::Module<Test, roaming_registry::Instance12>;
+pub type RoamingPacketBundleRegistry = roaming_registry::Module<Test, roaming_registry::Instance15>;
 
 // This function basically just builds a genesis storage key/value store according to
 // our desired mockup.
@@ -133,6 +336,7 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
     }
     .assimilate_storage(&mut t)
     .unwrap();
+    GenesisConfig::default().assimilate_storage::<Test>(&mut t).unwrap();
     let mut ext = sp_io::TestExternalities::new(t);
     ext.execute_with(|| System::set_block_number(1));
     ext
@@ -5,115 +5,918 @@ use crate::mock::*;
 use frame_support::{
     assert_noop,
     assert_ok,
+    storage::migration::put_storage_value,
+    traits::OnRuntimeUpgrade,
 };
+use roaming_packet_bundle_merkle::{
+    PacketMerkleTree,
+    PacketRecord,
+};
+use sp_core::H256;
+use sp_io::hashing::blake2_256;
+use sp_runtime::traits::BadOrigin;
 
 #[test]
 fn basic_setup_works() {
     new_test_ext().execute_with(|| {
         // Verify Initial Storage
-        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundles_count(), 0);
-        assert!(RoamingPacketBundleModule::roaming_packet_bundle(0).is_none());
-        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_owner(0), None);
-        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_price(0), None);
+        assert_eq!(RoamingPacketBundleRegistry::roaming_entities_count(), 0);
+        assert!(RoamingPacketBundleRegistry::roaming_entity(0).is_none());
+        assert_eq!(RoamingPacketBundleRegistry::roaming_entity_owner(0), None);
+        assert_eq!(RoamingPacketBundleRegistry::roaming_entity_price(0), None);
         assert_eq!(Balances::free_balance(1), 10);
         assert_eq!(Balances::free_balance(2), 20);
     });
 }
 
+// Setup a device of home network 0 (operator 0 owned by 1) whose session 0 has packet bundle 0 received by
+// network server 1 of visited network 1 (operator 1 owned by 2) with an uplink fee factor of 3
+fn setup_packet_bundle_received_by_visited_network(packets_ok_count: u64) {
+    assert_ok!(RoamingOperatorRegistry::create(Origin::signed(1)));
+    assert_ok!(RoamingOperatorRegistry::create(Origin::signed(2)));
+    assert_ok!(RoamingNetworkRegistry::create(Origin::signed(1)));
+    assert_ok!(RoamingNetworkRegistry::create(Origin::signed(2)));
+    assert_ok!(RoamingNetworkModule::assign_network_to_operator(Origin::signed(1), 0, 0));
+    assert_ok!(RoamingNetworkModule::assign_network_to_operator(Origin::signed(2), 1, 1));
+    assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(1)));
+    assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(2)));
+    assert_ok!(RoamingNetworkServerModule::assign_network_server_to_network(Origin::signed(1), 0, 0));
+    assert_ok!(RoamingNetworkServerModule::assign_network_server_to_network(Origin::signed(2), 1, 1));
+    assert_ok!(RoamingDeviceRegistry::create(Origin::signed(1)));
+    assert_ok!(RoamingDeviceModule::assign_device_to_network_server(Origin::signed(1), 0, 0));
+    assert_ok!(RoamingAccountingPolicyRegistry::create(Origin::signed(2)));
+    assert_ok!(RoamingAccountingPolicyModule::set_config(Origin::signed(2), 0, None, None, Some(3), None));
+    assert_ok!(RoamingAccountingPolicyModule::assign_accounting_policy_to_network(Origin::signed(2), 0, 1));
+
+    assert_ok!(RoamingSessionRegistry::create(Origin::signed(1)));
+    <roaming_sessions::RoamingSessionDevices<Test>>::insert(0, 0);
+    assert_ok!(RoamingPacketBundleRegistry::create(Origin::signed(1)));
+    assert_ok!(RoamingPacketBundleModule::assign_packet_bundle_to_session(Origin::signed(1), 0, 0));
+    <RoamingPacketBundleReceivers<Test>>::insert((0, 1), RoamingPacketBundleReceiver {
+        packet_bundle_received_at_home: false,
+        packet_bundle_received_packets_count: packets_ok_count,
+        packet_bundle_received_packets_ok_count: packets_ok_count,
+        packet_bundle_received_started_at_block: 1,
+        packet_bundle_received_ended_at_block: 1,
+        packet_bundle_external_data_storage_hash: H256::default(),
+    });
+    <RoamingPacketBundleReceivedAtBlocks<Test>>::insert((0, 1), 1);
+    RoamingPacketBundleModule::insert_unsettled_packet_bundle(0, 1);
+}
+
+#[test]
+fn settle_packet_bundle_works() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        setup_packet_bundle_received_by_visited_network(2);
+        assert_eq!(RoamingPacketBundleModule::roaming_network_unsettled_packet_bundles_count((0, 1)), 1);
+        // Call Functions
+        assert_ok!(RoamingPacketBundleModule::settle_packet_bundle(Origin::signed(1), 0, 1));
+        // Verify Storage
+        assert_eq!(Balances::free_balance(1), 4);
+        assert_eq!(Balances::free_balance(2), 26);
+        assert_eq!(
+            RoamingPacketBundleModule::roaming_packet_bundle_settlement(0, 1),
+            Some(RoamingPacketBundleSettlement {
+                settlement_network_server_id: 1,
+                settlement_amount: 6,
+                settlement_settled_at_block: 1,
+            })
+        );
+        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_unsettled_networks((0, 1)), None);
+        assert_eq!(RoamingPacketBundleModule::roaming_network_unsettled_packet_bundles_count((0, 1)), 0);
+    });
+}
+
+#[test]
+fn settle_packet_bundle_handles_basic_errors() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        setup_packet_bundle_received_by_visited_network(2);
+        // Call Functions
+        assert_noop!(
+            RoamingPacketBundleModule::settle_packet_bundle(Origin::signed(1), 0, 0),
+            "RoamingPacketBundleReceiver does not exist"
+        );
+        assert_noop!(
+            RoamingPacketBundleModule::settle_packet_bundle(Origin::signed(2), 0, 1),
+            Error::<Test>::NotHomeNetworkOperator
+        );
+        assert_ok!(RoamingPacketBundleModule::settle_packet_bundle(Origin::signed(1), 0, 1));
+        assert_noop!(
+            RoamingPacketBundleModule::settle_packet_bundle(Origin::signed(1), 0, 1),
+            Error::<Test>::PacketBundleAlreadySettled
+        );
+        // Verify Storage
+        assert_eq!(Balances::free_balance(1), 4);
+    });
+}
+
+#[test]
+fn settle_packet_bundle_settles_each_receiver_separately() {
+    new_test_ext().execute_with(|| {
+        // Setup - network server 2 of visited network 1 also received packet bundle 0
+        setup_packet_bundle_received_by_visited_network(2);
+        assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(2)));
+        assert_ok!(RoamingNetworkServerModule::assign_network_server_to_network(Origin::signed(2), 2, 1));
+        <RoamingPacketBundleReceivers<Test>>::insert((0, 2), RoamingPacketBundleReceiver {
+            packet_bundle_received_at_home: false,
+            packet_bundle_received_packets_count: 1,
+            packet_bundle_received_packets_ok_count: 1,
+            packet_bundle_received_started_at_block: 1,
+            packet_bundle_received_ended_at_block: 1,
+            packet_bundle_external_data_storage_hash: H256::default(),
+        });
+        RoamingPacketBundleModule::insert_unsettled_packet_bundle(0, 2);
+        <RoamingPacketBundleNetworkServers<Test>>::insert(0, vec![1, 2]);
+        // Call Functions
+        assert_ok!(RoamingPacketBundleModule::settle_packet_bundle(Origin::signed(1), 0, 1));
+        assert_ok!(RoamingPacketBundleModule::remove_packet_bundle_receiver(0, 1));
+        assert_noop!(
+            RoamingPacketBundleModule::remove_packet_bundle_receiver(0, 2),
+            Error::<Test>::PacketBundleNotSettled
+        );
+        assert_ok!(RoamingPacketBundleModule::settle_packet_bundle(Origin::signed(1), 0, 2));
+        // Verify Storage
+        assert_eq!(Balances::free_balance(1), 1);
+        assert_eq!(Balances::free_balance(2), 29);
+        assert_eq!(
+            RoamingPacketBundleModule::roaming_packet_bundle_settlement(0, 1)
+                .map(|settlement| settlement.settlement_amount),
+            Some(6)
+        );
+        assert_eq!(
+            RoamingPacketBundleModule::roaming_packet_bundle_settlement(0, 2)
+                .map(|settlement| settlement.settlement_amount),
+            Some(3)
+        );
+        assert_ok!(RoamingPacketBundleModule::remove_roaming_packet_bundle(0));
+        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_settlement(0, 1), None);
+        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_settlement(0, 2), None);
+    });
+}
+
+#[test]
+fn set_receiver_refuses_updates_once_packet_bundle_is_settled() {
+    new_test_ext().execute_with(|| {
+        // Setup - 1 is an admin of visited network server 1 as well as the owner of packet bundle 0
+        setup_packet_bundle_received_by_visited_network(2);
+        assert_ok!(RoamingNetworkServerRegistry::grant_admin(Origin::signed(2), 1, 1));
+        assert_ok!(RoamingPacketBundleModule::set_receiver(
+            Origin::signed(1),
+            0,
+            1,
+            None,
+            Some(2),
+            Some(2),
+            None,
+            None,
+            None
+        ));
+        assert_ok!(RoamingPacketBundleModule::settle_packet_bundle(Origin::signed(1), 0, 1));
+        // Call Functions
+        assert_noop!(
+            RoamingPacketBundleModule::set_receiver(Origin::signed(1), 0, 1, None, Some(5), Some(5), None, None, None),
+            Error::<Test>::PacketBundleAlreadySettled
+        );
+        // Verify Storage
+        assert_eq!(
+            RoamingPacketBundleModule::roaming_packet_bundle_receivers((0, 1))
+                .map(|receiver| receiver.packet_bundle_received_packets_ok_count),
+            Some(2)
+        );
+    });
+}
+
+#[test]
+fn requeue_unbilled_packet_bundle_does_not_queue_a_packet_bundle_twice() {
+    new_test_ext().execute_with(|| {
+        // Call Functions
+        RoamingPacketBundleModule::requeue_unbilled_packet_bundle(1, 0);
+        RoamingPacketBundleModule::requeue_unbilled_packet_bundle(1, 2);
+        RoamingPacketBundleModule::requeue_unbilled_packet_bundle(1, 0);
+        // Verify Storage
+        assert_eq!(RoamingPacketBundleModule::roaming_network_server_unbilled_packet_bundles(1), vec![0, 2]);
+    });
+}
+
+// Setup agreement policy 0 of home network 0 that expires at block 20 and activate its agreement with visited
+// network 1 by reserving the given deposit from 1
+fn setup_agreement_deposit_of_home_network(deposit: u64) {
+    assert_ok!(RoamingAgreementPolicyRegistry::create(Origin::signed(1)));
+    assert_ok!(RoamingAgreementPolicyModule::set_config(Origin::signed(1), 0, None, Some(20)));
+    assert_ok!(RoamingAgreementPolicyModule::assign_agreement_policy_to_network(Origin::signed(1), 0, 0));
+    assert_ok!(RoamingAgreementPolicyModule::activate_agreement(Origin::signed(1), 0, 1, deposit));
+}
+
+#[test]
+fn settle_packet_bundle_draws_from_agreement_deposit_first() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        setup_packet_bundle_received_by_visited_network(2);
+        setup_agreement_deposit_of_home_network(4);
+        // Call Functions
+        assert_ok!(RoamingPacketBundleModule::settle_packet_bundle(Origin::signed(1), 0, 1));
+        // Verify Storage
+        assert_eq!(RoamingAgreementPolicyModule::roaming_agreement_deposit((0, 1)), None);
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(1), 4);
+        assert_eq!(Balances::free_balance(2), 26);
+        assert_eq!(
+            RoamingPacketBundleModule::roaming_packet_bundle_settlement(0, 1)
+                .map(|settlement| settlement.settlement_amount),
+            Some(6)
+        );
+    });
+}
+
+#[test]
+fn settle_packet_bundle_does_not_draw_from_deposit_when_transfer_fails() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        setup_packet_bundle_received_by_visited_network(4);
+        setup_agreement_deposit_of_home_network(4);
+        // Call Functions
+        assert!(RoamingPacketBundleModule::settle_packet_bundle(Origin::signed(1), 0, 1).is_err());
+        // Verify Storage
+        assert_eq!(Balances::reserved_balance(1), 4);
+        assert_eq!(Balances::free_balance(2), 20);
+        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_settlement(0, 1), None);
+    });
+}
+
+#[test]
+fn slash_unsettled_packet_bundle_works() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        setup_packet_bundle_received_by_visited_network(2);
+        setup_agreement_deposit_of_home_network(4);
+        System::set_block_number(11);
+        // Call Functions
+        assert_ok!(RoamingPacketBundleModule::slash_unsettled_packet_bundle(Origin::signed(2), 0, 1));
+        // Verify Storage
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(1), 6);
+        assert_eq!(Balances::free_balance(2), 24);
+        assert_eq!(
+            RoamingPacketBundleModule::roaming_packet_bundle_settlement(0, 1),
+            Some(RoamingPacketBundleSettlement {
+                settlement_network_server_id: 1,
+                settlement_amount: 4,
+                settlement_settled_at_block: 11,
+            })
+        );
+        assert_eq!(RoamingPacketBundleModule::roaming_network_unsettled_packet_bundles_count((0, 1)), 0);
+    });
+}
+
+#[test]
+fn slash_unsettled_packet_bundle_counts_settlement_period_from_receive_block() {
+    new_test_ext().execute_with(|| {
+        // Setup - the visited network server last set its receiver at block 5
+        setup_packet_bundle_received_by_visited_network(2);
+        setup_agreement_deposit_of_home_network(4);
+        <RoamingPacketBundleReceivedAtBlocks<Test>>::insert((0, 1), 5);
+        System::set_block_number(11);
+        // Call Functions
+        assert_noop!(
+            RoamingPacketBundleModule::slash_unsettled_packet_bundle(Origin::signed(2), 0, 1),
+            Error::<Test>::SettlementPeriodNotElapsed
+        );
+        <RoamingPacketBundleReceivedAtBlocks<Test>>::remove((0, 1));
+        assert_noop!(
+            RoamingPacketBundleModule::slash_unsettled_packet_bundle(Origin::signed(2), 0, 1),
+            Error::<Test>::PacketBundleNotReceived
+        );
+        System::set_block_number(15);
+        <RoamingPacketBundleReceivedAtBlocks<Test>>::insert((0, 1), 5);
+        assert_ok!(RoamingPacketBundleModule::slash_unsettled_packet_bundle(Origin::signed(2), 0, 1));
+        // Verify Storage
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(2), 24);
+    });
+}
+
+#[test]
+fn agreement_deposit_is_held_until_packet_bundles_are_settled() {
+    new_test_ext().execute_with(|| {
+        // Setup - the agreement policy expires at block 20
+        setup_packet_bundle_received_by_visited_network(1);
+        setup_agreement_deposit_of_home_network(4);
+        System::set_block_number(20 + PacketBundleSettlementPeriod::get());
+        // Call Functions
+        assert_noop!(
+            RoamingAgreementPolicyModule::release_agreement_deposit(Origin::signed(3), 0, 1),
+            roaming_agreement_policies::Error::<Test>::UnsettledPacketBundles
+        );
+        assert_ok!(RoamingPacketBundleModule::settle_packet_bundle(Origin::signed(1), 0, 1));
+        assert_ok!(RoamingAgreementPolicyModule::release_agreement_deposit(Origin::signed(3), 0, 1));
+        // Verify Storage
+        assert_eq!(RoamingAgreementPolicyModule::roaming_agreement_deposit((0, 1)), None);
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(1), 7);
+        assert_eq!(Balances::free_balance(2), 23);
+    });
+}
+
+#[test]
+fn slash_unsettled_packet_bundle_handles_basic_errors() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        setup_packet_bundle_received_by_visited_network(2);
+        // Call Functions
+        assert_noop!(
+            RoamingPacketBundleModule::slash_unsettled_packet_bundle(Origin::signed(1), 0, 1),
+            Error::<Test>::NotVisitedNetworkOperator
+        );
+        assert_noop!(
+            RoamingPacketBundleModule::slash_unsettled_packet_bundle(Origin::signed(2), 0, 1),
+            Error::<Test>::SettlementPeriodNotElapsed
+        );
+        System::set_block_number(11);
+        assert_noop!(
+            RoamingPacketBundleModule::slash_unsettled_packet_bundle(Origin::signed(2), 0, 1),
+            Error::<Test>::NoAgreementDeposit
+        );
+        setup_agreement_deposit_of_home_network(4);
+        assert_ok!(RoamingPacketBundleModule::settle_packet_bundle(Origin::signed(1), 0, 1));
+        assert_noop!(
+            RoamingPacketBundleModule::slash_unsettled_packet_bundle(Origin::signed(2), 0, 1),
+            Error::<Test>::PacketBundleAlreadySettled
+        );
+    });
+}
+
+// Build a tree over the two packets of device DevAddr 7 received in packet bundle 0
+fn packet_merkle_tree_of_packet_bundle() -> PacketMerkleTree {
+    PacketMerkleTree::new(vec![
+        PacketRecord {
+            dev_addr: 7,
+            f_cnt: 1,
+            timestamp: 1_600_000_000_000,
+            size: 12,
+        },
+        PacketRecord {
+            dev_addr: 7,
+            f_cnt: 3,
+            timestamp: 1_600_000_060_000,
+            size: 12,
+        },
+    ])
+}
+
+#[test]
+fn operator_owner_manages_packet_bundles_of_its_sessions() {
+    new_test_ext().execute_with(|| {
+        // Setup - the network server, device and session under operator 0 are owned by account 3, not operator owner 1
+        assert_ok!(RoamingOperatorRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkRegistry::create(Origin::signed(3)));
+        assert_ok!(RoamingNetworkModule::assign_network_to_operator(Origin::signed(1), 0, 0));
+        assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(3)));
+        assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(2)));
+        assert_ok!(RoamingNetworkServerModule::assign_network_server_to_network(Origin::signed(3), 0, 0));
+        assert_ok!(RoamingDeviceRegistry::create(Origin::signed(3)));
+        assert_ok!(RoamingDeviceModule::assign_device_to_network_server(Origin::signed(3), 0, 0));
+        assert_ok!(RoamingSessionRegistry::create(Origin::signed(3)));
+        <roaming_sessions::RoamingSessionDevices<Test>>::insert(0, 0);
+        assert_ok!(RoamingPacketBundleRegistry::create(Origin::signed(1)));
+        // Call Functions
+        assert_noop!(
+            RoamingPacketBundleModule::assign_packet_bundle_to_session(Origin::signed(4), 0, 0),
+            "Only an account authorized for the roaming session can assign itself a roaming packet bundle"
+        );
+        assert_ok!(RoamingPacketBundleModule::assign_packet_bundle_to_session(Origin::signed(1), 0, 0));
+        assert_noop!(
+            RoamingPacketBundleModule::set_sent_packets_count(Origin::signed(4), 0, 1, 2),
+            Error::<Test>::NotHomeNetworkServerOperator
+        );
+        assert_ok!(RoamingPacketBundleModule::set_sent_packets_count(Origin::signed(1), 0, 1, 2));
+        // Verify Storage
+        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_session(0), Some(0));
+        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_sent_packets_count((0, 1)), Some(2));
+    });
+}
+
+#[test]
+fn commit_packet_bundle_data_root_works() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        setup_packet_bundle_received_by_visited_network(2);
+        let tree = packet_merkle_tree_of_packet_bundle();
+        // Call Functions
+        assert_noop!(
+            RoamingPacketBundleModule::commit_packet_bundle_data_root(Origin::signed(1), 0, 1, tree.root(), 2),
+            "Only an account authorized for the given network server id associated with the given packet bundle id \
+             can set it as an associated roaming packet bundle receiver"
+        );
+        assert_noop!(
+            RoamingPacketBundleModule::commit_packet_bundle_data_root(Origin::signed(2), 0, 0, tree.root(), 2),
+            "RoamingPacketBundleReceiver does not exist"
+        );
+        assert_ok!(RoamingPacketBundleModule::commit_packet_bundle_data_root(Origin::signed(2), 0, 1, tree.root(), 2));
+        assert_noop!(
+            RoamingPacketBundleModule::commit_packet_bundle_data_root(Origin::signed(2), 0, 1, tree.root(), 2),
+            Error::<Test>::DataRootAlreadyCommitted
+        );
+        // Verify Storage
+        assert_eq!(
+            RoamingPacketBundleModule::roaming_packet_bundle_data_root((0, 1)),
+            Some(RoamingPacketBundleDataRoot {
+                data_root: tree.root(),
+                data_leaves_count: 2,
+                data_committed_at_block: 1,
+            })
+        );
+    });
+}
+
 #[test]
-fn create_works() {
+fn verify_packet_inclusion_and_exclusion_works() {
     new_test_ext().execute_with(|| {
+        // Setup
+        setup_packet_bundle_received_by_visited_network(2);
+        let tree = packet_merkle_tree_of_packet_bundle();
+        let inclusion_proof = tree.inclusion_proof(7, 3).unwrap();
+        let (left_proof, right_proof) = tree.exclusion_proof(7, 2).unwrap();
+        assert_noop!(
+            RoamingPacketBundleModule::verify_packet_inclusion(Origin::signed(1), 0, 1, inclusion_proof.clone()),
+            Error::<Test>::NoDataRoot
+        );
+        assert_ok!(RoamingPacketBundleModule::commit_packet_bundle_data_root(Origin::signed(2), 0, 1, tree.root(), 2));
         // Call Functions
-        assert_ok!(RoamingPacketBundleModule::create(Origin::signed(1)));
+        assert_ok!(RoamingPacketBundleModule::verify_packet_inclusion(Origin::signed(1), 0, 1, inclusion_proof));
+        assert_ok!(RoamingPacketBundleModule::verify_packet_exclusion(
+            Origin::signed(1),
+            0,
+            1,
+            7,
+            2,
+            left_proof.clone(),
+            right_proof.clone()
+        ));
         // Verify Storage
-        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundles_count(), 1);
-        assert!(RoamingPacketBundleModule::roaming_packet_bundle(0).is_some());
-        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_owner(0), Some(1));
-        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_price(0), None);
+        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_verified_packet((0, 1, 7, 3)), Some(true));
+        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_verified_packet((0, 1, 7, 2)), Some(false));
+        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_verified_packet((0, 1, 7, 1)), None);
+    });
+}
+
+#[test]
+fn verify_packet_inclusion_and_exclusion_handle_invalid_proofs() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        setup_packet_bundle_received_by_visited_network(2);
+        let tree = packet_merkle_tree_of_packet_bundle();
+        assert_ok!(RoamingPacketBundleModule::commit_packet_bundle_data_root(Origin::signed(2), 0, 1, tree.root(), 2));
+        let mut inclusion_proof = tree.inclusion_proof(7, 3).unwrap();
+        inclusion_proof.record.size = 13;
+        let (left_proof, right_proof) = tree.exclusion_proof(7, 2).unwrap();
+        // Call Functions
+        assert_noop!(
+            RoamingPacketBundleModule::verify_packet_inclusion(Origin::signed(1), 0, 1, inclusion_proof),
+            Error::<Test>::InvalidPacketProof
+        );
+        assert_noop!(
+            RoamingPacketBundleModule::verify_packet_exclusion(Origin::signed(1), 0, 1, 7, 3, left_proof, right_proof),
+            Error::<Test>::InvalidPacketProof
+        );
+    });
+}
+
+#[test]
+fn verify_packet_inclusion_and_exclusion_refuse_proofs_deeper_than_the_maximum() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        setup_packet_bundle_received_by_visited_network(2);
+        let tree = packet_merkle_tree_of_packet_bundle();
+        assert_ok!(RoamingPacketBundleModule::commit_packet_bundle_data_root(Origin::signed(2), 0, 1, tree.root(), 2));
+        let mut inclusion_proof = tree.inclusion_proof(7, 3).unwrap();
+        inclusion_proof.siblings = vec![H256::default(); MAX_PROOF_DEPTH as usize + 1];
+        // Call Functions
+        assert_noop!(
+            RoamingPacketBundleModule::verify_packet_inclusion(Origin::signed(1), 0, 1, inclusion_proof.clone()),
+            Error::<Test>::PacketProofTooDeep
+        );
+        assert_noop!(
+            RoamingPacketBundleModule::verify_packet_exclusion(
+                Origin::signed(1),
+                0,
+                1,
+                7,
+                4,
+                Some(inclusion_proof),
+                None
+            ),
+            Error::<Test>::PacketProofTooDeep
+        );
     });
 }
 
 #[test]
-fn create_handles_basic_errors() {
+fn packet_proof_weight_grows_with_the_proof_depth_up_to_the_maximum() {
+    let tree = packet_merkle_tree_of_packet_bundle();
+    let mut proof = tree.inclusion_proof(7, 3).unwrap();
+    assert_eq!(RoamingPacketBundleModule::packet_proof_weight(&proof), 2 * PACKET_PROOF_NODE_WEIGHT);
+    proof.siblings = vec![H256::default(); MAX_PROOF_DEPTH as usize * 2];
+    assert_eq!(
+        RoamingPacketBundleModule::packet_proof_weight(&proof),
+        (MAX_PROOF_DEPTH as Weight + 1) * PACKET_PROOF_NODE_WEIGHT
+    );
+}
+
+#[test]
+fn set_sent_packets_count_within_tolerance_agrees_at_lower_count() {
     new_test_ext().execute_with(|| {
         // Setup
-        <RoamingPacketBundlesCount<Test>>::put(u64::max_value());
+        setup_packet_bundle_received_by_visited_network(5);
         // Call Functions
-        assert_noop!(RoamingPacketBundleModule::create(Origin::signed(1)), "RoamingPacketBundles count overflow");
+        assert_ok!(RoamingPacketBundleModule::set_sent_packets_count(Origin::signed(1), 0, 1, 4));
         // Verify Storage
-        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundles_count(), u64::max_value());
-        assert!(RoamingPacketBundleModule::roaming_packet_bundle(0).is_none());
-        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_owner(0), None);
-        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_price(0), None);
+        assert_eq!(
+            RoamingPacketBundleModule::roaming_packet_bundle_reconciliation((0, 1)),
+            Some(RoamingPacketBundleReconciliation {
+                reconciliation_sent_packets_count: 4,
+                reconciliation_received_packets_ok_count: 5,
+                reconciliation_final_packets_count: 4,
+                reconciliation_status: PacketCountStatus::Agreed,
+            })
+        );
+        assert_eq!(RoamingPacketBundleModule::packet_bundle_settlement_amount(0, 1), Ok((0, 1, 12)));
+        assert_eq!(RoamingPacketBundleModule::roaming_network_server_unbilled_packet_bundles(1), vec![0]);
+        assert_noop!(
+            RoamingPacketBundleModule::set_sent_packets_count(Origin::signed(1), 0, 1, 5),
+            Error::<Test>::PacketCountsLocked
+        );
     });
 }
 
 #[test]
-fn transfer_works() {
+fn disputed_packet_counts_are_settled_at_arbitrated_count() {
     new_test_ext().execute_with(|| {
         // Setup
-        assert_ok!(RoamingPacketBundleModule::create(Origin::signed(1)));
+        setup_packet_bundle_received_by_visited_network(5);
+        // Call Functions
+        assert_ok!(RoamingPacketBundleModule::set_sent_packets_count(Origin::signed(1), 0, 1, 2));
+        // Verify Storage
+        assert_eq!(
+            RoamingPacketBundleModule::roaming_packet_bundle_reconciliation((0, 1))
+                .map(|reconciliation| reconciliation.reconciliation_status),
+            Some(PacketCountStatus::Disputed)
+        );
+        assert_noop!(
+            RoamingPacketBundleModule::settle_packet_bundle(Origin::signed(1), 0, 1),
+            Error::<Test>::PacketCountsDisputed
+        );
+
+        // Call Functions
+        assert_ok!(RoamingPacketBundleModule::challenge_packet_counts(Origin::signed(2), 0, 1));
+        // Verify Storage
+        assert_eq!(Balances::reserved_balance(2), 5);
+        assert_eq!(
+            RoamingPacketBundleModule::roaming_packet_bundle_challenge((0, 1)),
+            Some(RoamingPacketBundleChallenge {
+                challenge_challenger: 2,
+                challenge_counterparty: 1,
+                challenge_packets_count: 5,
+                challenge_counterparty_packets_count: 2,
+                challenge_bond: 5,
+            })
+        );
+
         // Call Functions
-        assert_ok!(RoamingPacketBundleModule::transfer(Origin::signed(1), 2, 0));
+        assert_ok!(RoamingPacketBundleModule::resolve_packet_counts(Origin::root(), 0, 1, 5));
         // Verify Storage
-        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundles_count(), 1);
-        assert!(RoamingPacketBundleModule::roaming_packet_bundle(0).is_some());
-        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_owner(0), Some(2));
-        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_price(0), None);
+        assert_eq!(Balances::reserved_balance(2), 0);
+        assert_eq!(Balances::free_balance(2), 20);
+        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_challenge((0, 1)), None);
+        assert_eq!(
+            RoamingPacketBundleModule::roaming_packet_bundle_reconciliation((0, 1)),
+            Some(RoamingPacketBundleReconciliation {
+                reconciliation_sent_packets_count: 2,
+                reconciliation_received_packets_ok_count: 5,
+                reconciliation_final_packets_count: 5,
+                reconciliation_status: PacketCountStatus::Resolved,
+            })
+        );
+        assert_eq!(RoamingPacketBundleModule::packet_bundle_settlement_amount(0, 1), Ok((0, 1, 15)));
+        assert_eq!(RoamingPacketBundleModule::roaming_network_server_unbilled_packet_bundles(1), vec![0]);
     });
 }
 
 #[test]
-fn transfer_handles_basic_errors() {
+fn losing_challenger_pays_bond_to_counterparty() {
     new_test_ext().execute_with(|| {
         // Setup
-        assert_ok!(RoamingPacketBundleModule::create(Origin::signed(1)));
+        setup_packet_bundle_received_by_visited_network(5);
+        assert_ok!(RoamingPacketBundleModule::set_sent_packets_count(Origin::signed(1), 0, 1, 2));
         // Call Functions
+        assert_ok!(RoamingPacketBundleModule::challenge_packet_counts(Origin::signed(1), 0, 1));
+        assert_ok!(RoamingPacketBundleModule::resolve_packet_counts(Origin::root(), 0, 1, 4));
+        // Verify Storage
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(1), 5);
+        assert_eq!(Balances::free_balance(2), 25);
+        assert_eq!(RoamingPacketBundleModule::packet_bundle_settlement_amount(0, 1), Ok((0, 1, 12)));
+    });
+}
+
+#[test]
+fn packet_count_challenges_handle_basic_errors() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        setup_packet_bundle_received_by_visited_network(5);
+        // Call Functions
+        assert_noop!(
+            RoamingPacketBundleModule::set_sent_packets_count(Origin::signed(2), 0, 1, 2),
+            Error::<Test>::NotHomeNetworkServerOperator
+        );
+        assert_noop!(
+            RoamingPacketBundleModule::challenge_packet_counts(Origin::signed(2), 0, 1),
+            Error::<Test>::PacketCountsNotDisputed
+        );
+        assert_ok!(RoamingPacketBundleModule::set_sent_packets_count(Origin::signed(1), 0, 1, 2));
+        assert_noop!(
+            RoamingPacketBundleModule::challenge_packet_counts(Origin::signed(3), 0, 1),
+            Error::<Test>::NotPacketBundleCounterparty
+        );
         assert_noop!(
-            RoamingPacketBundleModule::transfer(Origin::signed(2), 2, 0),
-            "Only owner can transfer roaming packet_bundle"
+            RoamingPacketBundleModule::resolve_packet_counts(Origin::root(), 0, 1, 5),
+            Error::<Test>::PacketCountsNotChallenged
         );
+        assert_ok!(RoamingPacketBundleModule::challenge_packet_counts(Origin::signed(2), 0, 1));
         assert_noop!(
-            RoamingPacketBundleModule::transfer(Origin::signed(1), 2, 1),
-            "Only owner can transfer roaming packet_bundle"
+            RoamingPacketBundleModule::challenge_packet_counts(Origin::signed(1), 0, 1),
+            Error::<Test>::PacketCountsNotDisputed
+        );
+        assert_noop!(
+            RoamingPacketBundleModule::set_sent_packets_count(Origin::signed(1), 0, 1, 5),
+            Error::<Test>::PacketCountsLocked
+        );
+        assert_noop!(RoamingPacketBundleModule::resolve_packet_counts(Origin::signed(1), 0, 1, 5), BadOrigin);
+    });
+}
+
+// Setup the packet bundle received by the visited network with an uplink fee factor of 1 and a service profile of
+// the home network server of the device that allows the given uplink rate per rate limit period of 10 blocks
+fn setup_packet_bundle_with_uplink_rate(packets_ok_count: u64, uplink_rate: u32) {
+    setup_packet_bundle_received_by_visited_network(packets_ok_count);
+    assert_ok!(RoamingAccountingPolicyModule::set_config(Origin::signed(2), 0, None, None, Some(1), None));
+    assert_ok!(RoamingServiceProfileRegistry::create(Origin::signed(1)));
+    assert_ok!(RoamingServiceProfileModule::set_uplink_rate(Origin::signed(1), 0, Some(uplink_rate)));
+    assert_ok!(RoamingServiceProfileModule::assign_service_profile_to_network_server(Origin::signed(1), 0, 0));
+}
+
+#[test]
+fn check_packet_rate_flags_overage() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        setup_packet_bundle_with_uplink_rate(2, 4);
+        // Call Functions
+        RoamingPacketBundleModule::check_packet_rate(0, 1);
+        // Verify Storage
+        assert_eq!(RoamingPacketBundleModule::uplink_rate(0), Some(4));
+        // A single block of 4 packets per 10 blocks allows 1 packet
+        assert_eq!(
+            RoamingPacketBundleModule::roaming_packet_bundle_rate_check((0, 1)),
+            Some(RoamingPacketBundleRateCheck {
+                rate_allowed_packets_count: 1,
+                rate_overage_packets_count: 1,
+            })
+        );
+    });
+}
+
+#[test]
+fn check_packet_rate_allows_packets_within_rate() {
+    new_test_ext().execute_with(|| {
+        // Setup - packet bundle 0 was assigned to its session at block 1 and received 10 blocks later
+        setup_packet_bundle_with_uplink_rate(2, 4);
+        <RoamingPacketBundleReceivedAtBlocks<Test>>::insert((0, 1), 11);
+        // Call Functions
+        RoamingPacketBundleModule::check_packet_rate(0, 1);
+        // Verify Storage
+        assert_eq!(
+            RoamingPacketBundleModule::roaming_packet_bundle_rate_check((0, 1)),
+            Some(RoamingPacketBundleRateCheck {
+                rate_allowed_packets_count: 4,
+                rate_overage_packets_count: 0,
+            })
+        );
+        assert_eq!(RoamingPacketBundleModule::packet_bundle_settlement_amount(0, 1), Ok((0, 1, 2)));
+    });
+}
+
+#[test]
+fn check_packet_rate_ignores_block_span_reported_by_network_server() {
+    new_test_ext().execute_with(|| {
+        // Setup - 1 is an admin of visited network server 1 as well as the owner of packet bundle 0
+        setup_packet_bundle_with_uplink_rate(2, 4);
+        assert_ok!(RoamingNetworkServerRegistry::grant_admin(Origin::signed(2), 1, 1));
+        // Call Functions
+        assert_ok!(RoamingPacketBundleModule::set_receiver(
+            Origin::signed(1),
+            0,
+            1,
+            None,
+            Some(2),
+            Some(2),
+            Some(1),
+            Some(1_000),
+            None
+        ));
+        // Verify Storage
+        // The packet bundle was assigned and received in block 1, so a single block is allowed 1 packet
+        assert_eq!(
+            RoamingPacketBundleModule::roaming_packet_bundle_rate_check((0, 1)),
+            Some(RoamingPacketBundleRateCheck {
+                rate_allowed_packets_count: 1,
+                rate_overage_packets_count: 1,
+            })
         );
+    });
+}
+
+#[test]
+fn check_packet_rate_without_service_profile_stores_nothing() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        setup_packet_bundle_received_by_visited_network(2);
+        // Call Functions
+        RoamingPacketBundleModule::check_packet_rate(0, 1);
         // Verify Storage
-        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundles_count(), 1);
-        assert!(RoamingPacketBundleModule::roaming_packet_bundle(0).is_some());
-        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_owner(0), Some(1));
-        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_price(0), None);
+        assert_eq!(RoamingPacketBundleModule::uplink_rate(0), None);
+        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_rate_check((0, 1)), None);
+        assert_eq!(RoamingPacketBundleModule::packet_bundle_settlement_amount(0, 1), Ok((0, 1, 6)));
     });
 }
 
 #[test]
-fn set_price_works() {
+fn settle_packet_bundle_charges_rate_overage_at_penalty() {
     new_test_ext().execute_with(|| {
         // Setup
-        assert_ok!(RoamingPacketBundleModule::create(Origin::signed(1)));
+        setup_packet_bundle_with_uplink_rate(2, 4);
+        RoamingPacketBundleModule::check_packet_rate(0, 1);
         // Call Functions
-        assert_ok!(RoamingPacketBundleModule::set_price(Origin::signed(1), 0, Some(10)));
+        assert_ok!(RoamingPacketBundleModule::settle_packet_bundle(Origin::signed(1), 0, 1));
         // Verify Storage
-        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundles_count(), 1);
-        assert!(RoamingPacketBundleModule::roaming_packet_bundle(0).is_some());
-        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_owner(0), Some(1));
-        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_price(0), Some(10));
+        // 1 allowed packet and 1 overage packet charged at 3 times the uplink fee factor of 1
+        assert_eq!(Balances::free_balance(1), 6);
+        assert_eq!(Balances::free_balance(2), 24);
+        assert_eq!(
+            RoamingPacketBundleModule::roaming_packet_bundle_settlement(0, 1)
+                .map(|settlement| settlement.settlement_amount),
+            Some(4)
+        );
     });
 }
 
 #[test]
-fn buy_works() {
+fn migration_moves_network_server_packet_bundles_to_double_map_keys() {
     new_test_ext().execute_with(|| {
         // Setup
-        assert_ok!(RoamingPacketBundleModule::create(Origin::signed(1)));
-        assert_ok!(RoamingPacketBundleModule::set_price(Origin::signed(1), 0, Some(10)));
+        setup_packet_bundle_received_by_visited_network(2);
+        // Network server 0 never received packet bundle 0 and packet bundle 2 does not exist
+        put_storage_value(
+            b"RoamingPacketBundles",
+            b"RoamingNetworkServerPacketBundles",
+            &blake2_256(&0u64.encode()),
+            vec![0u64],
+        );
+        put_storage_value(
+            b"RoamingPacketBundles",
+            b"RoamingNetworkServerPacketBundles",
+            &blake2_256(&1u64.encode()),
+            vec![0u64, 2],
+        );
+        StorageVersion::put(Releases::V1_0_0);
         // Call Functions
-        assert_ok!(RoamingPacketBundleModule::buy(Origin::signed(2), 0, 10));
+        RoamingPacketBundleModule::on_runtime_upgrade();
+        // Verify Storage
+        assert_eq!(RoamingPacketBundleModule::storage_version(), Releases::V5_0_0);
+        assert!(RoamingPacketBundleModule::network_server_packet_bundles(0).is_empty());
+        assert_eq!(RoamingPacketBundleModule::network_server_packet_bundles(1), vec![0]);
+    });
+}
+
+#[test]
+fn migration_moves_packet_bundle_settlements_to_receiver_keys() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        setup_packet_bundle_received_by_visited_network(2);
+        StorageVersion::put(Releases::V2_0_0);
+        let settlement = RoamingPacketBundleSettlement {
+            settlement_network_server_id: 1u64,
+            settlement_amount: 6u64,
+            settlement_settled_at_block: 1u64,
+        };
+        put_storage_value(
+            b"RoamingPacketBundles",
+            b"RoamingPacketBundleSettlements",
+            &blake2_256(&0u64.encode()),
+            settlement.clone(),
+        );
+        // Call Functions
+        RoamingPacketBundleModule::on_runtime_upgrade();
+        // Verify Storage
+        assert_eq!(RoamingPacketBundleModule::storage_version(), Releases::V5_0_0);
+        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_settlement(0, 1), Some(settlement));
+        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_settlement(0, 0), None);
+    });
+}
+
+#[test]
+fn migration_keeps_packet_bundles_already_in_double_map_keys() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        setup_packet_bundle_received_by_visited_network(2);
+        <RoamingNetworkServerPacketBundles<Test>>::insert(1, 0, ());
+        let settlement = RoamingPacketBundleSettlement {
+            settlement_network_server_id: 1u64,
+            settlement_amount: 6u64,
+            settlement_settled_at_block: 1u64,
+        };
+        <RoamingPacketBundleSettlements<Test>>::insert(0, 1, settlement.clone());
+        StorageVersion::put(Releases::V1_0_0);
+        // Call Functions
+        RoamingPacketBundleModule::on_runtime_upgrade();
+        // Verify Storage
+        assert_eq!(RoamingPacketBundleModule::storage_version(), Releases::V5_0_0);
+        assert_eq!(RoamingPacketBundleModule::network_server_packet_bundles(1), vec![0]);
+        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_settlement(0, 1), Some(settlement));
+    });
+}
+
+#[test]
+fn migration_records_receipts_of_packet_bundle_receivers() {
+    new_test_ext().execute_with(|| {
+        // Setup - packet bundle 0 was received by network server 1 before its receive block was recorded
+        setup_packet_bundle_received_by_visited_network(2);
+        <RoamingNetworkServerPacketBundles<Test>>::insert(1, 0, ());
+        <RoamingPacketBundleReceivedAtBlocks<Test>>::remove((0, 1));
+        <RoamingPacketBundleUnsettledNetworks<Test>>::remove((0, 1));
+        <RoamingNetworkUnsettledPacketBundlesCount<Test>>::remove((0, 1));
+        StorageVersion::put(Releases::V3_0_0);
+        System::set_block_number(5);
+        // Call Functions
+        RoamingPacketBundleModule::on_runtime_upgrade();
+        // Verify Storage
+        assert_eq!(RoamingPacketBundleModule::storage_version(), Releases::V5_0_0);
+        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_received_at_block((0, 1)), Some(5));
+        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_unsettled_networks((0, 1)), Some((0, 1)));
+        assert_eq!(RoamingPacketBundleModule::roaming_network_unsettled_packet_bundles_count((0, 1)), 1);
+    });
+}
+
+#[test]
+fn migration_records_session_assignments_of_packet_bundles() {
+    new_test_ext().execute_with(|| {
+        // Setup - packet bundle 0 was assigned to session 0 before its assignment block was recorded
+        setup_packet_bundle_received_by_visited_network(2);
+        <RoamingPacketBundleSessionAssignedAtBlocks<Test>>::remove(0);
+        StorageVersion::put(Releases::V4_0_0);
+        System::set_block_number(5);
+        // Call Functions
+        RoamingPacketBundleModule::on_runtime_upgrade();
+        // Verify Storage
+        assert_eq!(RoamingPacketBundleModule::storage_version(), Releases::V5_0_0);
+        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_session_assigned_at_block(0), Some(5));
+        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_session_assigned_at_block(1), None);
+    });
+}
+
+#[test]
+fn assign_packet_bundle_to_session_follows_device_roaming_policy() {
+    new_test_ext().execute_with(|| {
+        // Setup - session 0 of the device of home network server 0 joined network server 1 of visited network 1
+        assert_ok!(RoamingNetworkRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkRegistry::create(Origin::signed(2)));
+        assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkServerModule::assign_network_server_to_network(Origin::signed(1), 0, 0));
+        assert_ok!(RoamingNetworkServerModule::assign_network_server_to_network(Origin::signed(2), 1, 1));
+        assert_ok!(RoamingDeviceRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingDeviceModule::assign_device_to_network_server(Origin::signed(1), 0, 0));
+        assert_ok!(RoamingSessionRegistry::create(Origin::signed(1)));
+        assert_ok!(roaming_sessions::Module::<Test>::set_join_request(Origin::signed(1), 0, Some(1), 0));
+        <roaming_sessions::RoamingSessionDevices<Test>>::insert(0, 0);
+        assert_ok!(RoamingPacketBundleRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingDeviceModule::set_roaming_policy(
+            Origin::signed(1),
+            0,
+            Some(roaming_devices::RoamingDevicePolicy::HomeOnly)
+        ));
+        // Call Functions
+        assert_noop!(
+            RoamingPacketBundleModule::assign_packet_bundle_to_session(Origin::signed(1), 0, 0),
+            roaming_devices::Error::<Test>::RoamingRestrictedToHomeNetwork
+        );
+        assert_ok!(RoamingDeviceModule::set_roaming_policy(
+            Origin::signed(1),
+            0,
+            Some(roaming_devices::RoamingDevicePolicy::AllowedNetworks(vec![1]))
+        ));
+        assert_ok!(RoamingPacketBundleModule::assign_packet_bundle_to_session(Origin::signed(1), 0, 0));
         // Verify Storage
-        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundles_count(), 1);
-        assert!(RoamingPacketBundleModule::roaming_packet_bundle(0).is_some());
-        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_owner(0), Some(2));
-        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_price(0), None);
-        assert_eq!(Balances::free_balance(1), 20);
-        assert_eq!(Balances::free_balance(2), 10);
+        assert_eq!(RoamingPacketBundleModule::roaming_packet_bundle_session(0), Some(0));
     });
 }
@@ -0,0 +1,22 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+#![allow(clippy::too_many_arguments)]
+#![allow(clippy::unnecessary_mut_passed)]
+
+use codec::Codec;
+use sp_std::prelude::*; // Imports Vec
+
+// Here we declare the runtime API. It is implemented in the `impl` block in
+// runtime amalgamator file (the `runtime/src/lib.rs`)
+sp_api::decl_runtime_apis! {
+    /// Lists the metadata of the roaming entities that have metadata a page at a time. Each call covers the entities
+    /// with ids from `start` up to `start + limit` and skips those without metadata.
+    pub trait RoamingMetadataApi<RoamingEntityIndex, RoamingEntityMetadata> where
+        RoamingEntityIndex: Codec,
+        RoamingEntityMetadata: Codec,
+    {
+        fn roaming_operators_metadata(start: RoamingEntityIndex, limit: u32) -> Vec<(RoamingEntityIndex, RoamingEntityMetadata)>;
+        fn roaming_networks_metadata(start: RoamingEntityIndex, limit: u32) -> Vec<(RoamingEntityIndex, RoamingEntityMetadata)>;
+        fn roaming_organizations_metadata(start: RoamingEntityIndex, limit: u32) -> Vec<(RoamingEntityIndex, RoamingEntityMetadata)>;
+        fn roaming_network_servers_metadata(start: RoamingEntityIndex, limit: u32) -> Vec<(RoamingEntityIndex, RoamingEntityMetadata)>;
+    }
+}
@@ -0,0 +1,521 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{
+    Decode,
+    Encode,
+};
+use frame_support::{
+    decl_event,
+    decl_module,
+    decl_storage,
+    ensure,
+    storage::migration::take_storage_value,
+    traits::{
+        BalanceStatus,
+        Currency,
+        Get,
+        Randomness,
+        ReservableCurrency,
+    },
+    transactional,
+    weights::Weight,
+    Parameter,
+};
+use frame_system::ensure_signed;
+use sp_io::hashing::{
+    blake2_128,
+    blake2_256,
+};
+use sp_runtime::{
+    traits::{
+        AtLeast32Bit,
+        Bounded,
+        Member,
+        One,
+        Saturating,
+        Zero,
+    },
+    DispatchError,
+    DispatchResult,
+};
+use sp_std::prelude::*; // Imports Vec
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// The module's configuration trait. Each instance of the registry holds the roaming entities of one kind.
+pub trait Config<I: Instance = DefaultInstance>: frame_system::Config {
+    type Event: From<Event<Self, I>> + Into<<Self as frame_system::Config>::Event>;
+    type Currency: ReservableCurrency<Self::AccountId>;
+    type Randomness: Randomness<Self::Hash>;
+    type RoamingEntityIndex: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
+    /// The deposit reserved from the creator of a roaming entity and held by its owner until it is removed
+    type EntityDeposit: Get<BalanceOf<Self, I>>;
+    /// Where the roaming entities were held before they moved to the registry, if anywhere
+    type LegacyStorage: Get<Option<LegacyRoamingEntityStorage>>;
+    /// The deposit reserved per byte of the metadata of a roaming entity
+    type MetadataDepositPerByte: Get<BalanceOf<Self, I>>;
+    /// The maximum length of each field of the metadata of a roaming entity. Zero for kinds of roaming entities that
+    /// have no metadata.
+    type MaxMetadataFieldLength: Get<u32>;
+}
+
+type BalanceOf<T, I> = <<T as Config<I>>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+/// Storage version of the roaming registry pallet
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum Releases {
+    /// Roaming entities are held by the pallet of their kind
+    V1_0_0,
+    /// Roaming entities are held by the registry
+    V2_0_0,
+}
+
+impl Default for Releases {
+    fn default() -> Self {
+        Releases::V1_0_0
+    }
+}
+
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct RoamingEntity(pub [u8; 16]);
+
+/// Human-readable metadata of a roaming entity, for dashboards to display
+#[derive(Encode, Decode, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct RoamingEntityMetadata {
+    pub name: Vec<u8>,
+    /// ISO 3166-1 alpha-2 country code, e.g. `DE`
+    pub country_code: Vec<u8>,
+    pub website: Vec<u8>,
+    pub contact: Vec<u8>,
+}
+
+/// The storage items in which a roaming pallet held its entities before they moved to the registry
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct LegacyRoamingEntityStorage {
+    /// The storage prefix of the pallet
+    pub module: &'static [u8],
+    pub entities: &'static [u8],
+    pub count: &'static [u8],
+    pub owners: &'static [u8],
+    pub deposits: &'static [u8],
+    /// The prices of the entities, if they could be sold
+    pub prices: Option<&'static [u8]>,
+    /// The admins of the entities, if they could have admins
+    pub admins: Option<&'static [u8]>,
+}
+
+/// A roaming entity that is owned by an account and may change hands on the roaming marketplace
+pub trait RoamingTradable<AccountId, RoamingEntityIndex> {
+    /// The owner of the entity, or None if it does not exist
+    fn owner_of(roaming_entity_id: RoamingEntityIndex) -> Option<AccountId>;
+
+    /// Hand the entity over to a new owner as a transfer by its owner would, delisting any fixed price it is for
+    /// sale at
+    fn transfer_ownership(roaming_entity_id: RoamingEntityIndex, to: &AccountId) -> DispatchResult;
+}
+
+/// The registry of the roaming entities of a kind, as seen by the pallet that holds their domain-specific settings
+pub trait RoamingRegistry<AccountId, RoamingEntityIndex>: RoamingTradable<AccountId, RoamingEntityIndex> {
+    /// Whether the entity exists
+    fn exists(roaming_entity_id: RoamingEntityIndex) -> bool;
+
+    /// Whether an account is the owner or an admin of the entity
+    fn is_owner_or_admin(roaming_entity_id: RoamingEntityIndex, account: &AccountId) -> bool;
+
+    /// Remove the entity, returning its deposit to its owner
+    fn deregister(roaming_entity_id: RoamingEntityIndex) -> DispatchResult;
+
+    /// The number of entities that have been registered, which is also the id of the next entity
+    fn entities_count() -> RoamingEntityIndex;
+}
+
+decl_event!(
+    pub enum Event<T, I = DefaultInstance> where
+        <T as frame_system::Config>::AccountId,
+        RoamingEntityIndex = <T as Config<I>>::RoamingEntityIndex,
+        Balance = BalanceOf<T, I>,
+    {
+        /// A roaming entity is created. (owner, roaming_entity_id)
+        Created(AccountId, RoamingEntityIndex),
+        /// A roaming entity is transferred. (from, to, roaming_entity_id)
+        Transferred(AccountId, AccountId, RoamingEntityIndex),
+        /// A roaming entity is available for sale. (owner, roaming_entity_id, price)
+        PriceSet(AccountId, RoamingEntityIndex, Option<Balance>),
+        /// A roaming entity admin is granted. (owner, roaming_entity_id, admin)
+        AdminGranted(AccountId, RoamingEntityIndex, AccountId),
+        /// A roaming entity admin is revoked. (owner, roaming_entity_id, admin)
+        AdminRevoked(AccountId, RoamingEntityIndex, AccountId),
+        /// The metadata of a roaming entity is set. (owner, roaming_entity_id, deposit)
+        MetadataSet(AccountId, RoamingEntityIndex, Balance),
+        /// The metadata of a roaming entity is cleared. (owner, roaming_entity_id)
+        MetadataCleared(AccountId, RoamingEntityIndex),
+    }
+);
+
+// This module's storage items.
+decl_storage! {
+    trait Store for Module<T: Config<I>, I: Instance = DefaultInstance> as RoamingRegistry {
+        /// Stores all the roaming entities, key is the roaming entity id / index
+        pub RoamingEntities get(fn roaming_entity): map hasher(opaque_blake2_256) T::RoamingEntityIndex => Option<RoamingEntity>;
+
+        /// Stores the total number of roaming entities. i.e. the next roaming entity index
+        pub RoamingEntitiesCount get(fn roaming_entities_count): T::RoamingEntityIndex;
+
+        /// Get roaming entity owner
+        pub RoamingEntityOwners get(fn roaming_entity_owner): map hasher(opaque_blake2_256) T::RoamingEntityIndex => Option<T::AccountId>;
+
+        /// Get the deposit reserved for a roaming entity, which is held by its owner
+        pub RoamingEntityDeposits get(fn roaming_entity_deposit): map hasher(opaque_blake2_256) T::RoamingEntityIndex => Option<BalanceOf<T, I>>;
+
+        /// Get roaming entity price. None means not for sale.
+        pub RoamingEntityPrices get(fn roaming_entity_price): map hasher(opaque_blake2_256) T::RoamingEntityIndex => Option<BalanceOf<T, I>>;
+
+        /// Get roaming entity admins, who are granted authority over the roaming entity by its owner
+        pub RoamingEntityAdmins get(fn roaming_entity_admins): map hasher(opaque_blake2_256) T::RoamingEntityIndex => Option<Vec<T::AccountId>>;
+
+        /// Get roaming entity metadata
+        pub RoamingEntityMetadataOf get(fn roaming_entity_metadata): map hasher(opaque_blake2_256) T::RoamingEntityIndex => Option<RoamingEntityMetadata>;
+
+        /// Get the deposit reserved for the metadata of a roaming entity, which is held by its owner
+        pub RoamingEntityMetadataDeposits get(fn roaming_entity_metadata_deposit): map hasher(opaque_blake2_256) T::RoamingEntityIndex => Option<BalanceOf<T, I>>;
+
+        /// Storage version of the pallet, used to determine which migrations to run on runtime upgrade. A new chain
+        /// starts at the latest version since there is no legacy storage to move into the registry.
+        pub StorageVersion get(fn storage_version) build(|_| Releases::V2_0_0): Releases;
+    }
+}
+
+// The module's dispatchable functions.
+decl_module! {
+    /// The module declaration.
+    pub struct Module<T: Config<I>, I: Instance = DefaultInstance> for enum Call where origin: T::Origin {
+        fn deposit_event() = default;
+
+        fn on_runtime_upgrade() -> Weight {
+            let mut weight = T::DbWeight::get().reads(1);
+            if Self::storage_version() == Releases::V1_0_0 {
+                if let Some(legacy_storage) = T::LegacyStorage::get() {
+                    weight = weight.saturating_add(Self::migrate_legacy_storage(&legacy_storage));
+                }
+                <StorageVersion<I>>::put(Releases::V2_0_0);
+                weight = weight.saturating_add(T::DbWeight::get().writes(1));
+            }
+            weight
+        }
+
+        /// Create a new roaming entity
+        #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        pub fn create(origin) {
+            let sender = ensure_signed(origin)?;
+            let roaming_entity_id = Self::next_roaming_entity_id()?;
+
+            // Reserve a deposit from the creator for the storage used by the roaming entity
+            let deposit = T::EntityDeposit::get();
+            T::Currency::reserve(&sender, deposit)?;
+
+            // Generate a random 128bit value
+            let unique_id = Self::random_value(&sender);
+
+            // Create and store roaming entity
+            let roaming_entity = RoamingEntity(unique_id);
+            Self::insert_roaming_entity(&sender, roaming_entity_id, roaming_entity);
+            <RoamingEntityDeposits<T, I>>::insert(roaming_entity_id, deposit);
+
+            Self::deposit_event(RawEvent::Created(sender, roaming_entity_id));
+        }
+
+        /// Transfer a roaming entity to new owner
+        #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        pub fn transfer(origin, to: T::AccountId, roaming_entity_id: T::RoamingEntityIndex) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(Self::roaming_entity_owner(roaming_entity_id) == Some(sender.clone()), "Only owner can transfer roaming entity");
+
+            Self::update_owner(&to, roaming_entity_id)?;
+
+            Self::deposit_event(RawEvent::Transferred(sender, to, roaming_entity_id));
+        }
+
+        /// Set an asking price for a roaming entity, which buyers meet by making an offer on the roaming marketplace
+        /// None to delist the roaming entity
+        #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        pub fn set_price(origin, roaming_entity_id: T::RoamingEntityIndex, price: Option<BalanceOf<T, I>>) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(Self::roaming_entity_owner(roaming_entity_id) == Some(sender.clone()), "Only owner can set price for roaming entity");
+
+            if let Some(ref price) = price {
+                <RoamingEntityPrices<T, I>>::insert(roaming_entity_id, price);
+            } else {
+                <RoamingEntityPrices<T, I>>::remove(roaming_entity_id);
+            }
+
+            Self::deposit_event(RawEvent::PriceSet(sender, roaming_entity_id, price));
+        }
+
+        /// Grant an account authority over a roaming entity
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(2, 1)]
+        pub fn grant_admin(origin, roaming_entity_id: T::RoamingEntityIndex, admin: T::AccountId) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(Self::roaming_entity_owner(roaming_entity_id) == Some(sender.clone()), "Only owner can grant admins of roaming entity");
+            let mut admins = Self::roaming_entity_admins(roaming_entity_id).unwrap_or_default();
+            ensure!(!admins.contains(&admin), "Account is already an admin of roaming entity");
+
+            admins.push(admin.clone());
+            <RoamingEntityAdmins<T, I>>::insert(roaming_entity_id, admins);
+
+            Self::deposit_event(RawEvent::AdminGranted(sender, roaming_entity_id, admin));
+        }
+
+        /// Revoke the authority of an admin over a roaming entity
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(2, 1)]
+        pub fn revoke_admin(origin, roaming_entity_id: T::RoamingEntityIndex, admin: T::AccountId) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(Self::roaming_entity_owner(roaming_entity_id) == Some(sender.clone()), "Only owner can revoke admins of roaming entity");
+            let mut admins = Self::roaming_entity_admins(roaming_entity_id).unwrap_or_default();
+            ensure!(admins.contains(&admin), "Account is not an admin of roaming entity");
+
+            admins.retain(|account| *account != admin);
+            if admins.is_empty() {
+                <RoamingEntityAdmins<T, I>>::remove(roaming_entity_id);
+            } else {
+                <RoamingEntityAdmins<T, I>>::insert(roaming_entity_id, admins);
+            }
+
+            Self::deposit_event(RawEvent::AdminRevoked(sender, roaming_entity_id, admin));
+        }
+
+        /// Set the metadata of a roaming entity, reserving a deposit per byte of it
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(3, 2)]
+        #[transactional]
+        pub fn set_metadata(origin, roaming_entity_id: T::RoamingEntityIndex, metadata: RoamingEntityMetadata) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(Self::roaming_entity_owner(roaming_entity_id) == Some(sender.clone()), "Only owner can set metadata of roaming entity");
+            Self::validate_metadata(&metadata)?;
+
+            // The deposit covers the encoded size of the metadata, so it changes with the metadata
+            let deposit = T::MetadataDepositPerByte::get()
+                .saturating_mul(BalanceOf::<T, I>::from(metadata.encoded_size() as u32));
+            let old_deposit = Self::roaming_entity_metadata_deposit(roaming_entity_id).unwrap_or_else(Zero::zero);
+            if deposit > old_deposit {
+                T::Currency::reserve(&sender, deposit - old_deposit)?;
+            } else {
+                T::Currency::unreserve(&sender, old_deposit - deposit);
+            }
+
+            <RoamingEntityMetadataOf<T, I>>::insert(roaming_entity_id, metadata);
+            <RoamingEntityMetadataDeposits<T, I>>::insert(roaming_entity_id, deposit);
+
+            Self::deposit_event(RawEvent::MetadataSet(sender, roaming_entity_id, deposit));
+        }
+
+        /// Clear the metadata of a roaming entity, returning its deposit
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(3, 2)]
+        pub fn clear_metadata(origin, roaming_entity_id: T::RoamingEntityIndex) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(Self::roaming_entity_owner(roaming_entity_id) == Some(sender.clone()), "Only owner can clear metadata of roaming entity");
+            ensure!(<RoamingEntityMetadataOf<T, I>>::contains_key(roaming_entity_id), "Roaming entity has no metadata");
+
+            <RoamingEntityMetadataOf<T, I>>::remove(roaming_entity_id);
+            if let Some(deposit) = <RoamingEntityMetadataDeposits<T, I>>::take(roaming_entity_id) {
+                T::Currency::unreserve(&sender, deposit);
+            }
+
+            Self::deposit_event(RawEvent::MetadataCleared(sender, roaming_entity_id));
+        }
+    }
+}
+
+impl<T: Config<I>, I: Instance> Module<T, I> {
+    pub fn exists_roaming_entity(roaming_entity_id: T::RoamingEntityIndex) -> Result<RoamingEntity, DispatchError> {
+        match Self::roaming_entity(roaming_entity_id) {
+            Some(roaming_entity) => Ok(roaming_entity),
+            None => Err(DispatchError::Other("RoamingEntity does not exist")),
+        }
+    }
+
+    /// Move the entities that a roaming pallet held before they moved to the registry into the registry, keeping
+    /// their indices, owners, deposits, prices and admins. Entities that the registry already holds are kept, and the
+    /// legacy values of their ids are dropped.
+    pub fn migrate_legacy_storage(legacy_storage: &LegacyRoamingEntityStorage) -> Weight {
+        let roaming_entities_count =
+            match take_storage_value::<T::RoamingEntityIndex>(legacy_storage.module, legacy_storage.count, &[]) {
+                Some(roaming_entities_count) => roaming_entities_count,
+                None => return T::DbWeight::get().reads(1),
+            };
+
+        let mut roaming_entity_id: T::RoamingEntityIndex = Zero::zero();
+        let mut migrated_count: Weight = 0;
+        while roaming_entity_id < roaming_entities_count {
+            // The legacy maps were hashed with opaque_blake2_256
+            let hash = blake2_256(&roaming_entity_id.encode());
+            let module = legacy_storage.module;
+            let is_registered = <RoamingEntities<T, I>>::contains_key(roaming_entity_id);
+            let unique_id = take_storage_value::<[u8; 16]>(module, legacy_storage.entities, &hash);
+            let owner = take_storage_value::<T::AccountId>(module, legacy_storage.owners, &hash);
+            let deposit = take_storage_value::<BalanceOf<T, I>>(module, legacy_storage.deposits, &hash);
+            let price =
+                legacy_storage.prices.and_then(|prices| take_storage_value::<BalanceOf<T, I>>(module, prices, &hash));
+            let admins =
+                legacy_storage.admins.and_then(|admins| take_storage_value::<Vec<T::AccountId>>(module, admins, &hash));
+            if !is_registered {
+                if let Some(unique_id) = unique_id {
+                    <RoamingEntities<T, I>>::insert(roaming_entity_id, RoamingEntity(unique_id));
+                }
+                if let Some(owner) = owner {
+                    <RoamingEntityOwners<T, I>>::insert(roaming_entity_id, owner);
+                }
+                if let Some(deposit) = deposit {
+                    <RoamingEntityDeposits<T, I>>::insert(roaming_entity_id, deposit);
+                }
+                if let Some(price) = price {
+                    <RoamingEntityPrices<T, I>>::insert(roaming_entity_id, price);
+                }
+                if let Some(admins) = admins {
+                    <RoamingEntityAdmins<T, I>>::insert(roaming_entity_id, admins);
+                }
+            }
+            roaming_entity_id += One::one();
+            migrated_count += 1;
+        }
+        <RoamingEntitiesCount<T, I>>::mutate(|count| *count = roaming_entities_count.max(*count));
+
+        T::DbWeight::get().reads_writes(2 + migrated_count * 7, 1 + migrated_count * 12)
+    }
+
+    /// The metadata of the roaming entities with ids from `start` up to `start + limit`, skipping those without
+    /// metadata, so that the metadata of all entities can be listed a page at a time
+    pub fn roaming_entities_metadata(
+        start: T::RoamingEntityIndex,
+        limit: u32,
+    ) -> Vec<(T::RoamingEntityIndex, RoamingEntityMetadata)> {
+        let end = start.saturating_add(T::RoamingEntityIndex::from(limit)).min(Self::roaming_entities_count());
+        let mut roaming_entities_metadata = Vec::new();
+        let mut roaming_entity_id = start;
+        while roaming_entity_id < end {
+            if let Some(metadata) = Self::roaming_entity_metadata(roaming_entity_id) {
+                roaming_entities_metadata.push((roaming_entity_id, metadata));
+            }
+            roaming_entity_id += One::one();
+        }
+        roaming_entities_metadata
+    }
+
+    fn validate_metadata(metadata: &RoamingEntityMetadata) -> DispatchResult {
+        let max_field_length = T::MaxMetadataFieldLength::get() as usize;
+        ensure!(max_field_length > 0, "Roaming entities of this kind have no metadata");
+        ensure!(
+            [&metadata.name, &metadata.country_code, &metadata.website, &metadata.contact]
+                .iter()
+                .all(|field| field.len() <= max_field_length),
+            "Roaming entity metadata field is too long"
+        );
+        ensure!(
+            metadata.country_code.is_empty() ||
+                (metadata.country_code.len() == 2 && metadata.country_code.iter().all(u8::is_ascii_uppercase)),
+            "Roaming entity metadata country code must be two uppercase letters"
+        );
+        Ok(())
+    }
+
+    fn random_value(sender: &T::AccountId) -> [u8; 16] {
+        let payload = (
+            T::Randomness::random(&[0]),
+            sender,
+            <frame_system::Module<T>>::extrinsic_index(),
+            <frame_system::Module<T>>::block_number(),
+        );
+        payload.using_encoded(blake2_128)
+    }
+
+    fn next_roaming_entity_id() -> Result<T::RoamingEntityIndex, DispatchError> {
+        let roaming_entity_id = Self::roaming_entities_count();
+        if roaming_entity_id == <T::RoamingEntityIndex as Bounded>::max_value() {
+            return Err(DispatchError::Other("RoamingEntities count overflow"));
+        }
+        Ok(roaming_entity_id)
+    }
+
+    fn insert_roaming_entity(
+        owner: &T::AccountId,
+        roaming_entity_id: T::RoamingEntityIndex,
+        roaming_entity: RoamingEntity,
+    ) {
+        // Create and store roaming entity
+        <RoamingEntities<T, I>>::insert(roaming_entity_id, roaming_entity);
+        <RoamingEntitiesCount<T, I>>::put(roaming_entity_id + One::one());
+        <RoamingEntityOwners<T, I>>::insert(roaming_entity_id, owner.clone());
+    }
+
+    fn update_owner(to: &T::AccountId, roaming_entity_id: T::RoamingEntityIndex) -> DispatchResult {
+        // The deposit of the roaming entity moves with its ownership
+        if let (Some(from), Some(deposit)) =
+            (Self::roaming_entity_owner(roaming_entity_id), Self::roaming_entity_deposit(roaming_entity_id))
+        {
+            T::Currency::repatriate_reserved(&from, to, deposit, BalanceStatus::Reserved)?;
+        }
+        if let (Some(from), Some(deposit)) =
+            (Self::roaming_entity_owner(roaming_entity_id), Self::roaming_entity_metadata_deposit(roaming_entity_id))
+        {
+            T::Currency::repatriate_reserved(&from, to, deposit, BalanceStatus::Reserved)?;
+        }
+        <RoamingEntityOwners<T, I>>::insert(roaming_entity_id, to);
+        // Admins are granted by the owner, so they do not carry over to a new owner
+        <RoamingEntityAdmins<T, I>>::remove(roaming_entity_id);
+        Ok(())
+    }
+}
+
+impl<T: Config<I>, I: Instance> RoamingTradable<T::AccountId, T::RoamingEntityIndex> for Module<T, I> {
+    fn owner_of(roaming_entity_id: T::RoamingEntityIndex) -> Option<T::AccountId> {
+        Self::roaming_entity_owner(roaming_entity_id)
+    }
+
+    fn transfer_ownership(roaming_entity_id: T::RoamingEntityIndex, to: &T::AccountId) -> DispatchResult {
+        <RoamingEntityPrices<T, I>>::remove(roaming_entity_id);
+        Self::update_owner(to, roaming_entity_id)
+    }
+}
+
+impl<T: Config<I>, I: Instance> RoamingRegistry<T::AccountId, T::RoamingEntityIndex> for Module<T, I> {
+    fn exists(roaming_entity_id: T::RoamingEntityIndex) -> bool {
+        <RoamingEntities<T, I>>::contains_key(roaming_entity_id)
+    }
+
+    fn is_owner_or_admin(roaming_entity_id: T::RoamingEntityIndex, account: &T::AccountId) -> bool {
+        Self::roaming_entity_owner(roaming_entity_id).map_or(false, |owner| owner == *account) ||
+            Self::roaming_entity_admins(roaming_entity_id).map_or(false, |admins| admins.contains(account))
+    }
+
+    fn deregister(roaming_entity_id: T::RoamingEntityIndex) -> DispatchResult {
+        Self::exists_roaming_entity(roaming_entity_id)?;
+        <RoamingEntities<T, I>>::remove(roaming_entity_id);
+        let owner = <RoamingEntityOwners<T, I>>::take(roaming_entity_id);
+        if let (Some(owner), Some(deposit)) = (&owner, <RoamingEntityDeposits<T, I>>::take(roaming_entity_id)) {
+            T::Currency::unreserve(owner, deposit);
+        }
+        if let (Some(owner), Some(deposit)) = (&owner, <RoamingEntityMetadataDeposits<T, I>>::take(roaming_entity_id)) {
+            T::Currency::unreserve(owner, deposit);
+        }
+        <RoamingEntityMetadataOf<T, I>>::remove(roaming_entity_id);
+        <RoamingEntityPrices<T, I>>::remove(roaming_entity_id);
+        <RoamingEntityAdmins<T, I>>::remove(roaming_entity_id);
+        Ok(())
+    }
+
+    fn entities_count() -> T::RoamingEntityIndex {
+        Self::roaming_entities_count()
+    }
+}
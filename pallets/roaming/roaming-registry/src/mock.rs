@@ -0,0 +1,133 @@
+// Creating mock runtime here
+
+use crate::{
+    Config,
+    DefaultInstance,
+    GenesisConfig,
+    LegacyRoamingEntityStorage,
+    Module,
+};
+
+use frame_support::{
+    parameter_types,
+    weights::{
+        IdentityFee,
+        Weight,
+    },
+};
+
+use sp_core::H256;
+use sp_runtime::{
+    testing::Header,
+    traits::{
+        BlakeTwo256,
+        IdentityLookup,
+    },
+    Perbill,
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+frame_support::construct_runtime!(
+    pub enum Test where
+        Block = Block,
+        NodeBlock = Block,
+        UncheckedExtrinsic = UncheckedExtrinsic,
+    {
+        System: frame_system::{Module, Call, Config, Storage, Event<T>},
+        Balances: pallet_balances::{Module, Call, Storage, Config<T>, Event<T>},
+        TransactionPayment: pallet_transaction_payment::{Module, Storage},
+        RandomnessCollectiveFlip: pallet_randomness_collective_flip::{Module, Call, Storage},
+    }
+);
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+}
+impl frame_system::Config for Test {
+    type AccountData = pallet_balances::AccountData<u64>;
+    type AccountId = u64;
+    type BaseCallFilter = ();
+    type BlockHashCount = BlockHashCount;
+    type BlockLength = ();
+    type BlockNumber = u64;
+    type BlockWeights = ();
+    type Call = Call;
+    type DbWeight = ();
+    type Event = ();
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type Header = Header;
+    type Index = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type OnKilledAccount = ();
+    type OnNewAccount = ();
+    type Origin = Origin;
+    type PalletInfo = PalletInfo;
+    type SS58Prefix = ();
+    type SystemWeightInfo = ();
+    type Version = ();
+}
+parameter_types! {
+    pub const ExistentialDeposit: u64 = 1;
+}
+impl pallet_balances::Config for Test {
+    type AccountStore = System;
+    type Balance = u64;
+    type DustRemoval = ();
+    type Event = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type MaxLocks = ();
+    type WeightInfo = ();
+}
+parameter_types! {
+    pub const TransactionByteFee: u64 = 1;
+}
+impl pallet_transaction_payment::Config for Test {
+    type FeeMultiplierUpdate = ();
+    type OnChargeTransaction = pallet_transaction_payment::CurrencyAdapter<Balances, ()>;
+    type TransactionByteFee = TransactionByteFee;
+    type WeightToFee = IdentityFee<u64>;
+}
+parameter_types! {
+    pub const EntityDeposit: u64 = 2;
+    pub const MetadataDepositPerByte: u64 = 1;
+    pub const MaxMetadataFieldLength: u32 = 16;
+    pub const LegacyStorage: Option<LegacyRoamingEntityStorage> = Some(LegacyRoamingEntityStorage {
+        module: b"RoamingOperators",
+        entities: b"RoamingOperators",
+        count: b"RoamingOperatorsCount",
+        owners: b"RoamingOperatorOwners",
+        deposits: b"RoamingOperatorDeposits",
+        prices: Some(b"RoamingOperatorPrices"),
+        admins: Some(b"RoamingOperatorAdmins"),
+    });
+}
+impl Config for Test {
+    type Currency = Balances;
+    type EntityDeposit = EntityDeposit;
+    type Event = ();
+    type LegacyStorage = LegacyStorage;
+    type MaxMetadataFieldLength = MaxMetadataFieldLength;
+    type MetadataDepositPerByte = MetadataDepositPerByte;
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+
+pub type RoamingRegistryModule = Module<Test>;
+
+// This function basically just builds a genesis storage key/value store according to
+// our desired mockup.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+    pallet_balances::GenesisConfig::<Test> {
+        balances: vec![(1, 10), (2, 20), (3, 30), (4, 40), (5, 50), (6, 60)],
+    }
+    .assimilate_storage(&mut t)
+    .unwrap();
+    GenesisConfig::default().assimilate_storage::<Test, DefaultInstance>(&mut t).unwrap();
+    let mut ext = sp_io::TestExternalities::new(t);
+    ext.execute_with(|| System::set_block_number(1));
+    ext
+}
@@ -0,0 +1,421 @@
+// Tests to be written here
+
+use super::*;
+use crate::mock::*;
+use frame_support::{
+    assert_noop,
+    assert_ok,
+    storage::migration::{
+        get_storage_value,
+        put_storage_value,
+    },
+    traits::OnRuntimeUpgrade,
+};
+use sp_io::hashing::blake2_256;
+
+#[test]
+fn basic_setup_works() {
+    new_test_ext().execute_with(|| {
+        // Verify Initial Storage
+        assert_eq!(RoamingRegistryModule::roaming_entities_count(), 0);
+        assert!(RoamingRegistryModule::roaming_entity(0).is_none());
+        assert_eq!(RoamingRegistryModule::roaming_entity_owner(0), None);
+        assert_eq!(RoamingRegistryModule::roaming_entity_price(0), None);
+        assert_eq!(Balances::free_balance(1), 10);
+        assert_eq!(Balances::free_balance(2), 20);
+    });
+}
+
+#[test]
+fn create_works() {
+    new_test_ext().execute_with(|| {
+        // Call Functions
+        assert_ok!(RoamingRegistryModule::create(Origin::signed(1)));
+        // Verify Storage
+        assert_eq!(RoamingRegistryModule::roaming_entities_count(), 1);
+        assert!(RoamingRegistryModule::roaming_entity(0).is_some());
+        assert_eq!(RoamingRegistryModule::roaming_entity_owner(0), Some(1));
+        assert_eq!(RoamingRegistryModule::roaming_entity_price(0), None);
+    });
+}
+
+#[test]
+fn create_handles_basic_errors() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        <RoamingEntitiesCount<Test, DefaultInstance>>::put(u64::max_value());
+        // Call Functions
+        assert_noop!(RoamingRegistryModule::create(Origin::signed(1)), "RoamingEntities count overflow");
+        // Verify Storage
+        assert_eq!(RoamingRegistryModule::roaming_entities_count(), u64::max_value());
+        assert!(RoamingRegistryModule::roaming_entity(0).is_none());
+        assert_eq!(RoamingRegistryModule::roaming_entity_owner(0), None);
+        assert_eq!(RoamingRegistryModule::roaming_entity_price(0), None);
+    });
+}
+
+#[test]
+fn transfer_works() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        assert_ok!(RoamingRegistryModule::create(Origin::signed(1)));
+        // Call Functions
+        assert_ok!(RoamingRegistryModule::transfer(Origin::signed(1), 2, 0));
+        // Verify Storage
+        assert_eq!(RoamingRegistryModule::roaming_entities_count(), 1);
+        assert!(RoamingRegistryModule::roaming_entity(0).is_some());
+        assert_eq!(RoamingRegistryModule::roaming_entity_owner(0), Some(2));
+        assert_eq!(RoamingRegistryModule::roaming_entity_price(0), None);
+    });
+}
+
+#[test]
+fn transfer_handles_basic_errors() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        assert_ok!(RoamingRegistryModule::create(Origin::signed(1)));
+        // Call Functions
+        assert_noop!(
+            RoamingRegistryModule::transfer(Origin::signed(2), 2, 0),
+            "Only owner can transfer roaming entity"
+        );
+        assert_noop!(
+            RoamingRegistryModule::transfer(Origin::signed(1), 2, 1),
+            "Only owner can transfer roaming entity"
+        );
+        // Verify Storage
+        assert_eq!(RoamingRegistryModule::roaming_entities_count(), 1);
+        assert!(RoamingRegistryModule::roaming_entity(0).is_some());
+        assert_eq!(RoamingRegistryModule::roaming_entity_owner(0), Some(1));
+        assert_eq!(RoamingRegistryModule::roaming_entity_price(0), None);
+    });
+}
+
+#[test]
+fn set_price_works() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        assert_ok!(RoamingRegistryModule::create(Origin::signed(1)));
+        // Call Functions
+        assert_ok!(RoamingRegistryModule::set_price(Origin::signed(1), 0, Some(10)));
+        // Verify Storage
+        assert_eq!(RoamingRegistryModule::roaming_entities_count(), 1);
+        assert!(RoamingRegistryModule::roaming_entity(0).is_some());
+        assert_eq!(RoamingRegistryModule::roaming_entity_owner(0), Some(1));
+        assert_eq!(RoamingRegistryModule::roaming_entity_price(0), Some(10));
+    });
+}
+
+#[test]
+fn grant_admin_works() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        assert_ok!(RoamingRegistryModule::create(Origin::signed(1)));
+        // Call Functions
+        assert_ok!(RoamingRegistryModule::grant_admin(Origin::signed(1), 0, 2));
+        // Verify Storage
+        assert_eq!(RoamingRegistryModule::roaming_entity_admins(0), Some(vec![2]));
+        assert!(RoamingRegistryModule::is_owner_or_admin(0, &1));
+        assert!(RoamingRegistryModule::is_owner_or_admin(0, &2));
+        assert!(!RoamingRegistryModule::is_owner_or_admin(0, &3));
+    });
+}
+
+#[test]
+fn grant_admin_handles_basic_errors() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        assert_ok!(RoamingRegistryModule::create(Origin::signed(1)));
+        assert_ok!(RoamingRegistryModule::grant_admin(Origin::signed(1), 0, 2));
+        // Call Functions
+        assert_noop!(
+            RoamingRegistryModule::grant_admin(Origin::signed(2), 0, 3),
+            "Only owner can grant admins of roaming entity"
+        );
+        assert_noop!(
+            RoamingRegistryModule::grant_admin(Origin::signed(1), 0, 2),
+            "Account is already an admin of roaming entity"
+        );
+        // Verify Storage
+        assert_eq!(RoamingRegistryModule::roaming_entity_admins(0), Some(vec![2]));
+    });
+}
+
+#[test]
+fn revoke_admin_works() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        assert_ok!(RoamingRegistryModule::create(Origin::signed(1)));
+        assert_ok!(RoamingRegistryModule::grant_admin(Origin::signed(1), 0, 2));
+        // Call Functions
+        assert_noop!(
+            RoamingRegistryModule::revoke_admin(Origin::signed(1), 0, 3),
+            "Account is not an admin of roaming entity"
+        );
+        assert_ok!(RoamingRegistryModule::revoke_admin(Origin::signed(1), 0, 2));
+        // Verify Storage
+        assert_eq!(RoamingRegistryModule::roaming_entity_admins(0), None);
+        assert!(!RoamingRegistryModule::is_owner_or_admin(0, &2));
+    });
+}
+
+#[test]
+fn transfer_revokes_admins() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        assert_ok!(RoamingRegistryModule::create(Origin::signed(1)));
+        assert_ok!(RoamingRegistryModule::grant_admin(Origin::signed(1), 0, 2));
+        // Call Functions
+        assert_ok!(RoamingRegistryModule::transfer(Origin::signed(1), 3, 0));
+        // Verify Storage
+        assert_eq!(RoamingRegistryModule::roaming_entity_admins(0), None);
+        assert!(!RoamingRegistryModule::is_owner_or_admin(0, &1));
+        assert!(!RoamingRegistryModule::is_owner_or_admin(0, &2));
+        assert!(RoamingRegistryModule::is_owner_or_admin(0, &3));
+    });
+}
+
+#[test]
+fn create_reserves_deposit() {
+    new_test_ext().execute_with(|| {
+        // Call Functions
+        assert_ok!(RoamingRegistryModule::create(Origin::signed(1)));
+        assert_noop!(
+            RoamingRegistryModule::create(Origin::signed(7)),
+            pallet_balances::Error::<Test, _>::InsufficientBalance
+        );
+        // Verify Storage
+        assert_eq!(RoamingRegistryModule::roaming_entity_deposit(0), Some(2));
+        assert_eq!(Balances::free_balance(1), 8);
+        assert_eq!(Balances::reserved_balance(1), 2);
+        assert!(RoamingRegistryModule::roaming_entity(1).is_none());
+    });
+}
+
+#[test]
+fn transfer_moves_deposit() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        assert_ok!(RoamingRegistryModule::create(Origin::signed(1)));
+        // Call Functions
+        assert_ok!(RoamingRegistryModule::transfer(Origin::signed(1), 2, 0));
+        // Verify Storage
+        assert_eq!(Balances::free_balance(1), 8);
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(2), 20);
+        assert_eq!(Balances::reserved_balance(2), 2);
+    });
+}
+
+#[test]
+fn deregister_refunds_deposit() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        assert_ok!(RoamingRegistryModule::create(Origin::signed(1)));
+        assert_ok!(RoamingRegistryModule::transfer(Origin::signed(1), 2, 0));
+        // Call Functions
+        assert_ok!(RoamingRegistryModule::deregister(0));
+        // Verify Storage
+        assert!(!RoamingRegistryModule::exists(0));
+        assert_eq!(RoamingRegistryModule::roaming_entity_owner(0), None);
+        assert_eq!(RoamingRegistryModule::roaming_entity_deposit(0), None);
+        assert_eq!(Balances::free_balance(2), 22);
+        assert_eq!(Balances::reserved_balance(2), 0);
+    });
+}
+
+fn acme_metadata() -> RoamingEntityMetadata {
+    RoamingEntityMetadata {
+        name: b"Acme".to_vec(),
+        country_code: b"DE".to_vec(),
+        website: b"acme.io".to_vec(),
+        contact: b"ops".to_vec(),
+    }
+}
+
+#[test]
+fn set_metadata_works() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        assert_ok!(RoamingRegistryModule::create(Origin::signed(6)));
+        // Call Functions
+        assert_ok!(RoamingRegistryModule::set_metadata(Origin::signed(6), 0, acme_metadata()));
+        // Verify Storage
+        assert_eq!(RoamingRegistryModule::roaming_entity_metadata(0), Some(acme_metadata()));
+        // The metadata is 20 bytes once encoded, which is charged 1 per byte
+        assert_eq!(RoamingRegistryModule::roaming_entity_metadata_deposit(0), Some(20));
+        assert_eq!(Balances::reserved_balance(6), 22);
+
+        // Shorter metadata returns the difference of the deposit
+        let metadata = RoamingEntityMetadata {
+            name: b"A".to_vec(),
+            ..Default::default()
+        };
+        assert_ok!(RoamingRegistryModule::set_metadata(Origin::signed(6), 0, metadata.clone()));
+        assert_eq!(RoamingRegistryModule::roaming_entity_metadata(0), Some(metadata));
+        assert_eq!(RoamingRegistryModule::roaming_entity_metadata_deposit(0), Some(5));
+        assert_eq!(Balances::reserved_balance(6), 7);
+
+        assert_ok!(RoamingRegistryModule::clear_metadata(Origin::signed(6), 0));
+        assert_eq!(RoamingRegistryModule::roaming_entity_metadata(0), None);
+        assert_eq!(RoamingRegistryModule::roaming_entity_metadata_deposit(0), None);
+        assert_eq!(Balances::free_balance(6), 58);
+        assert_eq!(Balances::reserved_balance(6), 2);
+    });
+}
+
+#[test]
+fn set_metadata_handles_basic_errors() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        assert_ok!(RoamingRegistryModule::create(Origin::signed(1)));
+        assert_ok!(RoamingRegistryModule::create(Origin::signed(6)));
+        // Call Functions
+        assert_noop!(
+            RoamingRegistryModule::set_metadata(Origin::signed(1), 1, acme_metadata()),
+            "Only owner can set metadata of roaming entity"
+        );
+        assert_noop!(
+            RoamingRegistryModule::set_metadata(Origin::signed(1), 0, acme_metadata()),
+            pallet_balances::Error::<Test, _>::InsufficientBalance
+        );
+        assert_noop!(
+            RoamingRegistryModule::set_metadata(
+                Origin::signed(6),
+                1,
+                RoamingEntityMetadata {
+                    name: b"Acme Roaming Networks".to_vec(),
+                    ..acme_metadata()
+                }
+            ),
+            "Roaming entity metadata field is too long"
+        );
+        for country_code in &[&b"de"[..], &b"DEU"[..], &b"D"[..]] {
+            assert_noop!(
+                RoamingRegistryModule::set_metadata(
+                    Origin::signed(6),
+                    1,
+                    RoamingEntityMetadata {
+                        country_code: country_code.to_vec(),
+                        ..acme_metadata()
+                    }
+                ),
+                "Roaming entity metadata country code must be two uppercase letters"
+            );
+        }
+        assert_noop!(
+            RoamingRegistryModule::clear_metadata(Origin::signed(6), 1),
+            "Roaming entity has no metadata"
+        );
+        assert_ok!(RoamingRegistryModule::set_metadata(Origin::signed(6), 1, acme_metadata()));
+        assert_noop!(
+            RoamingRegistryModule::clear_metadata(Origin::signed(1), 1),
+            "Only owner can clear metadata of roaming entity"
+        );
+    });
+}
+
+#[test]
+fn transfer_moves_metadata_deposit() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        assert_ok!(RoamingRegistryModule::create(Origin::signed(6)));
+        assert_ok!(RoamingRegistryModule::set_metadata(Origin::signed(6), 0, acme_metadata()));
+        // Call Functions
+        assert_ok!(RoamingRegistryModule::transfer(Origin::signed(6), 5, 0));
+        // Verify Storage
+        assert_eq!(RoamingRegistryModule::roaming_entity_metadata(0), Some(acme_metadata()));
+        assert_eq!(Balances::reserved_balance(6), 0);
+        assert_eq!(Balances::reserved_balance(5), 22);
+        assert_ok!(RoamingRegistryModule::clear_metadata(Origin::signed(5), 0));
+        assert_eq!(Balances::free_balance(5), 70);
+    });
+}
+
+#[test]
+fn deregister_removes_metadata() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        assert_ok!(RoamingRegistryModule::create(Origin::signed(6)));
+        assert_ok!(RoamingRegistryModule::set_metadata(Origin::signed(6), 0, acme_metadata()));
+        // Call Functions
+        assert_ok!(RoamingRegistryModule::deregister(0));
+        // Verify Storage
+        assert_eq!(RoamingRegistryModule::roaming_entity_metadata(0), None);
+        assert_eq!(RoamingRegistryModule::roaming_entity_metadata_deposit(0), None);
+        assert_eq!(Balances::free_balance(6), 60);
+        assert_eq!(Balances::reserved_balance(6), 0);
+    });
+}
+
+#[test]
+fn roaming_entities_metadata_lists_a_page_at_a_time() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        for _ in 0..3 {
+            assert_ok!(RoamingRegistryModule::create(Origin::signed(6)));
+        }
+        assert_ok!(RoamingRegistryModule::set_metadata(Origin::signed(6), 0, acme_metadata()));
+        assert_ok!(RoamingRegistryModule::set_metadata(Origin::signed(6), 2, acme_metadata()));
+        // Call Functions
+        assert_eq!(RoamingRegistryModule::roaming_entities_metadata(0, 2), vec![(0, acme_metadata())]);
+        assert_eq!(RoamingRegistryModule::roaming_entities_metadata(2, 2), vec![(2, acme_metadata())]);
+        assert_eq!(
+            RoamingRegistryModule::roaming_entities_metadata(0, 10),
+            vec![(0, acme_metadata()), (2, acme_metadata())]
+        );
+        assert_eq!(RoamingRegistryModule::roaming_entities_metadata(3, 10), vec![]);
+    });
+}
+
+#[test]
+fn migration_moves_legacy_storage_into_registry() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        let hash = blake2_256(&1u64.encode());
+        put_storage_value(b"RoamingOperators", b"RoamingOperatorsCount", &[], 2u64);
+        put_storage_value(b"RoamingOperators", b"RoamingOperators", &hash, [7u8; 16]);
+        put_storage_value(b"RoamingOperators", b"RoamingOperatorOwners", &hash, 3u64);
+        put_storage_value(b"RoamingOperators", b"RoamingOperatorDeposits", &hash, 2u64);
+        put_storage_value(b"RoamingOperators", b"RoamingOperatorPrices", &hash, 10u64);
+        put_storage_value(b"RoamingOperators", b"RoamingOperatorAdmins", &hash, vec![4u64]);
+        StorageVersion::<DefaultInstance>::put(Releases::V1_0_0);
+        // Call Functions
+        RoamingRegistryModule::on_runtime_upgrade();
+        // Verify Storage
+        assert_eq!(RoamingRegistryModule::storage_version(), Releases::V2_0_0);
+        assert_eq!(RoamingRegistryModule::roaming_entities_count(), 2);
+        assert!(RoamingRegistryModule::roaming_entity(0).is_none());
+        assert_eq!(RoamingRegistryModule::roaming_entity(1), Some(RoamingEntity([7u8; 16])));
+        assert_eq!(RoamingRegistryModule::roaming_entity_owner(1), Some(3));
+        assert_eq!(RoamingRegistryModule::roaming_entity_deposit(1), Some(2));
+        assert_eq!(RoamingRegistryModule::roaming_entity_price(1), Some(10));
+        assert_eq!(RoamingRegistryModule::roaming_entity_admins(1), Some(vec![4]));
+        // The next entity created keeps counting from the migrated entities
+        assert_ok!(RoamingRegistryModule::create(Origin::signed(1)));
+        assert_eq!(RoamingRegistryModule::roaming_entity_owner(2), Some(1));
+    });
+}
+
+#[test]
+fn migration_keeps_entities_already_in_registry() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        assert_eq!(RoamingRegistryModule::storage_version(), Releases::V2_0_0);
+        for _ in 0..3 {
+            assert_ok!(RoamingRegistryModule::create(Origin::signed(1)));
+        }
+        let roaming_entity = RoamingRegistryModule::roaming_entity(1);
+        let hash = blake2_256(&1u64.encode());
+        put_storage_value(b"RoamingOperators", b"RoamingOperatorsCount", &[], 2u64);
+        put_storage_value(b"RoamingOperators", b"RoamingOperators", &hash, [7u8; 16]);
+        put_storage_value(b"RoamingOperators", b"RoamingOperatorOwners", &hash, 3u64);
+        // Call Functions
+        RoamingRegistryModule::migrate_legacy_storage(&LegacyStorage::get().unwrap());
+        // Verify Storage
+        assert_eq!(RoamingRegistryModule::roaming_entities_count(), 3);
+        assert_eq!(RoamingRegistryModule::roaming_entity(1), roaming_entity);
+        assert_eq!(RoamingRegistryModule::roaming_entity_owner(1), Some(1));
+        assert_eq!(get_storage_value::<u64>(b"RoamingOperators", b"RoamingOperatorOwners", &hash), None);
+    });
+}
@@ -6,26 +6,42 @@ use codec::{
 };
 use frame_support::{
     debug,
+    decl_error,
     decl_event,
     decl_module,
     decl_storage,
     ensure,
-    traits::{
-        Get,
-        Randomness,
+    storage::migration::{
+        get_storage_value,
+        take_storage_value,
     },
+    traits::Get,
+    transactional,
+    weights::Weight,
     Parameter,
 };
 use frame_system::ensure_signed;
-use sp_io::hashing::blake2_128;
+use sp_io::hashing::blake2_256;
 use sp_runtime::{
     traits::{
         AtLeast32Bit,
         Bounded,
         Member,
         One,
+        Zero,
     },
     DispatchError,
+    DispatchResult,
+};
+use roaming_operators::{
+    without_roaming_entity,
+    RoamingAuthority,
+    RoamingDeviceEntity,
+    RoamingEntityChildren,
+};
+use roaming_registry::{
+    RoamingRegistry,
+    RoamingTradable,
 };
 use sp_std::prelude::*; // Imports Vec
 #[macro_use]
@@ -41,51 +57,193 @@ mod tests;
 pub trait Config: frame_system::Config + roaming_operators::Config + roaming_devices::Config {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
     type RoamingRoutingProfileIndex: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
-    type RoamingRoutingProfileAppServer: Parameter + Member + Default;
+    /// The registry that holds the roaming routing profiles and their owners
+    type RoamingRoutingProfileRegistry: RoamingRegistry<Self::AccountId, Self::RoamingRoutingProfileIndex>;
+    /// The maximum number of endpoints of a roaming routing profile
+    type MaxEndpoints: Get<u32>;
+    /// The maximum length of the URI of an endpoint
+    type MaxEndpointUriLength: Get<u32>;
+}
+
+/// Storage version of the roaming routing profiles pallet
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum Releases {
+    /// Each roaming routing profile has a single app server
+    V1_0_0,
+    /// Each roaming routing profile has a list of endpoints
+    V2_0_0,
+}
+
+impl Default for Releases {
+    fn default() -> Self {
+        Releases::V1_0_0
+    }
+}
+
+/// The protocol that an application server endpoint is reached over
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum RoamingRoutingProfileEndpointProtocol {
+    Http,
+    Mqtt,
+    Grpc,
 }
 
+impl RoamingRoutingProfileEndpointProtocol {
+    /// The URI schemes of the endpoints of the protocol
+    pub fn schemes(&self) -> &'static [&'static [u8]] {
+        match self {
+            RoamingRoutingProfileEndpointProtocol::Http => &[b"http", b"https"],
+            RoamingRoutingProfileEndpointProtocol::Mqtt => &[b"mqtt", b"mqtts"],
+            RoamingRoutingProfileEndpointProtocol::Grpc => &[b"grpc", b"grpcs"],
+        }
+    }
+
+    /// The protocol of the endpoints with a URI scheme, if any
+    pub fn from_scheme(scheme: &[u8]) -> Option<Self> {
+        [
+            RoamingRoutingProfileEndpointProtocol::Http,
+            RoamingRoutingProfileEndpointProtocol::Mqtt,
+            RoamingRoutingProfileEndpointProtocol::Grpc,
+        ]
+        .iter()
+        .find(|protocol| protocol.schemes().contains(&scheme))
+        .copied()
+    }
+
+    /// The well-known port of the endpoints with a URI scheme, if any. gRPC runs over HTTP/2, so it uses the ports of
+    /// HTTP.
+    pub fn default_port(scheme: &[u8]) -> Option<u16> {
+        match scheme {
+            b"http" | b"grpc" => Some(80),
+            b"https" | b"grpcs" => Some(443),
+            b"mqtt" => Some(1883),
+            b"mqtts" => Some(8883),
+            _ => None,
+        }
+    }
+}
+
+/// An application server endpoint that a routing profile forwards to. When an endpoint fails, forwarding fails over
+/// to the endpoint with the next lowest priority value.
 #[derive(Encode, Decode, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "std", derive(Debug))]
-pub struct RoamingRoutingProfile(pub [u8; 16]);
+pub struct RoamingRoutingProfileEndpoint {
+    /// URI of the form `scheme://host:port[/path]`, e.g. `https://as.example.com:8443/uplink`
+    pub uri: Vec<u8>,
+    pub protocol: RoamingRoutingProfileEndpointProtocol,
+    /// Endpoints with lower values are tried first
+    pub priority: u8,
+}
+
+/// Split a URI of the form `scheme://host:port[/path]` into its scheme, host and port, or None if it is not of that
+/// form. The host is a domain name, an IPv4 address or a bracketed IPv6 address.
+pub fn parse_endpoint_uri(uri: &[u8]) -> Option<(&[u8], &[u8], u16)> {
+    let scheme_end = uri.windows(3).position(|separator| separator == b"://")?;
+    let (scheme, rest) = (&uri[..scheme_end], &uri[scheme_end + 3..]);
+    let scheme_valid = scheme.first().map_or(false, u8::is_ascii_lowercase) &&
+        scheme.iter().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || b"+-.".contains(c));
+
+    let authority_end = rest.iter().position(|c| *c == b'/').unwrap_or_else(|| rest.len());
+    let (authority, path) = rest.split_at(authority_end);
+    let port_start = authority.iter().rposition(|c| *c == b':')?;
+    let (host, port) = (&authority[..port_start], &authority[port_start + 1..]);
+    let host_valid = if host.len() > 2 && host.starts_with(b"[") && host.ends_with(b"]") {
+        host[1..host.len() - 1].iter().all(|c| c.is_ascii_hexdigit() || *c == b':')
+    } else {
+        host.split(|c| *c == b'.').all(|label| {
+            !label.is_empty() &&
+                !label.starts_with(b"-") &&
+                !label.ends_with(b"-") &&
+                label.iter().all(|c| c.is_ascii_alphanumeric() || *c == b'-')
+        })
+    };
+    let port = if !port.is_empty() && port.len() <= 5 && port.iter().all(u8::is_ascii_digit) {
+        port.iter().fold(0u32, |port, digit| port * 10 + u32::from(digit - b'0'))
+    } else {
+        0
+    };
+    let port_valid = port > 0 && port <= u32::from(u16::max_value());
+
+    if scheme_valid && host_valid && port_valid && path.iter().all(u8::is_ascii_graphic) {
+        Some((scheme, host, port as u16))
+    } else {
+        None
+    }
+}
+
+/// Add the default port of the scheme of a URI of the form `scheme://host[/path]` after its host, or None if it has no
+/// scheme or its scheme has no default port
+pub fn with_default_port(uri: &[u8]) -> Option<Vec<u8>> {
+    let scheme_end = uri.windows(3).position(|separator| separator == b"://")?;
+    let mut port = RoamingRoutingProfileEndpointProtocol::default_port(&uri[..scheme_end])?;
+    let authority_end =
+        uri[scheme_end + 3..].iter().position(|c| *c == b'/').map_or_else(|| uri.len(), |end| scheme_end + 3 + end);
+
+    let mut port_digits = Vec::new();
+    loop {
+        port_digits.insert(0, b'0' + (port % 10) as u8);
+        port /= 10;
+        if port == 0 {
+            break;
+        }
+    }
+    let mut uri_with_port = uri[..authority_end].to_vec();
+    uri_with_port.push(b':');
+    uri_with_port.extend(port_digits);
+    uri_with_port.extend_from_slice(&uri[authority_end..]);
+    Some(uri_with_port)
+}
 
 decl_event!(
     pub enum Event<T> where
         <T as frame_system::Config>::AccountId,
         <T as Config>::RoamingRoutingProfileIndex,
-        <T as Config>::RoamingRoutingProfileAppServer,
         <T as roaming_devices::Config>::RoamingDeviceIndex,
     {
-        /// A roaming routing_profile is created. (owner, roaming_routing_profile_id)
-        Created(AccountId, RoamingRoutingProfileIndex),
-        /// A roaming routing_profile is transferred. (from, to, roaming_routing_profile_id)
-        Transferred(AccountId, AccountId, RoamingRoutingProfileIndex),
-        /// A roaming routing_profile is assigned an app server. (owner, roaming_routing_profile_id, app server)
-        AppServerSet(AccountId, RoamingRoutingProfileIndex, Option<RoamingRoutingProfileAppServer>),
+        /// The endpoints of a roaming routing_profile are changed, in the order they are tried. (owner,
+        /// roaming_routing_profile_id, endpoints)
+        EndpointsSet(AccountId, RoamingRoutingProfileIndex, Vec<RoamingRoutingProfileEndpoint>),
         /// A roaming routing_profile is assigned to a device. (owner of device, roaming_routing_profile_id, roaming_device_id)
         AssignedRoutingProfileToDevice(AccountId, RoamingRoutingProfileIndex, RoamingDeviceIndex),
+        /// A roaming routing_profile is removed. (owner, roaming_routing_profile_id)
+        Removed(AccountId, RoamingRoutingProfileIndex),
     }
 );
 
+decl_error! {
+    pub enum Error for Module<T: Config> {
+        /// The roaming routing profile would have more than the maximum number of endpoints
+        TooManyEndpoints,
+        /// The URI of an endpoint is longer than the maximum length
+        EndpointUriTooLong,
+        /// The URI of an endpoint is not of the form `scheme://host:port[/path]`
+        InvalidEndpointUri,
+        /// The URI scheme of an endpoint is not one of the schemes of its protocol
+        EndpointSchemeMismatch,
+        /// The roaming routing profile already has an endpoint with the URI
+        DuplicateEndpoint,
+        /// The roaming routing profile has no endpoint with the URI
+        EndpointNotFound,
+    }
+}
+
 // This module's storage items.
 decl_storage! {
     trait Store for Module<T: Config> as RoamingRoutingProfiles {
-        /// Stores all the roaming routing_profiles, key is the roaming routing_profile id / index
-        pub RoamingRoutingProfiles get(fn roaming_routing_profile): map hasher(opaque_blake2_256) T::RoamingRoutingProfileIndex => Option<RoamingRoutingProfile>;
-
-        /// Stores the total number of roaming routing_profiles. i.e. the next roaming routing_profile index
-        pub RoamingRoutingProfilesCount get(fn roaming_routing_profiles_count): T::RoamingRoutingProfileIndex;
-
-        /// Get roaming routing_profile owner
-        pub RoamingRoutingProfileOwners get(fn roaming_routing_profile_owner): map hasher(opaque_blake2_256) T::RoamingRoutingProfileIndex => Option<T::AccountId>;
-
-        /// Get roaming routing_profile app server.
-        pub RoamingRoutingProfileAppServers get(fn roaming_routing_profile_app_server): map hasher(opaque_blake2_256) T::RoamingRoutingProfileIndex => Option<T::RoamingRoutingProfileAppServer>;
+        /// Get roaming routing_profile endpoints, ordered by priority
+        pub RoamingRoutingProfileEndpoints get(fn roaming_routing_profile_endpoints): map hasher(opaque_blake2_256) T::RoamingRoutingProfileIndex => Option<Vec<RoamingRoutingProfileEndpoint>>;
 
         /// Get roaming routing_profile device
         pub RoamingRoutingProfileDevices get(fn roaming_routing_profile_device): map hasher(opaque_blake2_256) T::RoamingRoutingProfileIndex => Option<T::RoamingDeviceIndex>;
 
         /// Get roaming device routing_profiles
-        pub RoamingDeviceRoutingProfiles get(fn roaming_device_routing_profiles): map hasher(opaque_blake2_256) T::RoamingDeviceIndex => Option<Vec<T::RoamingRoutingProfileIndex>>
+        pub RoamingDeviceRoutingProfiles get(fn roaming_device_routing_profiles): map hasher(opaque_blake2_256) T::RoamingDeviceIndex => Option<Vec<T::RoamingRoutingProfileIndex>>;
+
+        /// Storage version of the pallet, used to determine which migrations to run on runtime upgrade. A new chain
+        /// starts at the latest version since it has no app servers to migrate.
+        pub StorageVersion get(fn storage_version) build(|_| Releases::V2_0_0): Releases;
     }
 }
 
@@ -93,53 +251,55 @@ decl_storage! {
 decl_module! {
     /// The module declaration.
     pub struct Module<T: Config> for enum Call where origin: T::Origin {
+        type Error = Error<T>;
+
         fn deposit_event() = default;
 
-        /// Create a new roaming routing_profile
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn create(origin) {
+        fn on_runtime_upgrade() -> Weight {
+            let mut weight = T::DbWeight::get().reads(1);
+            if Self::storage_version() == Releases::V1_0_0 {
+                weight = weight.saturating_add(Self::migrate_app_servers_to_endpoints());
+            }
+            weight
+        }
+
+        /// Replace the endpoints of a roaming routing_profile. No endpoints leaves it without any.
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(1, 1)]
+        pub fn set_endpoints(origin, roaming_routing_profile_id: T::RoamingRoutingProfileIndex, endpoints: Vec<RoamingRoutingProfileEndpoint>) {
             let sender = ensure_signed(origin)?;
-            let roaming_routing_profile_id = Self::next_roaming_routing_profile_id()?;
 
-            // Generate a random 128bit value
-            let unique_id = Self::random_value(&sender);
+            ensure!(Self::is_authorized(roaming_routing_profile_id, &sender), "Only an account authorized for the roaming routing_profile can set its endpoints");
 
-            // Create and store roaming routing_profile
-            let roaming_routing_profile = RoamingRoutingProfile(unique_id);
-            Self::insert_roaming_routing_profile(&sender, roaming_routing_profile_id, roaming_routing_profile);
+            // let is_owned_by_parent_relationship = Self::is_owned_by_required_parent_relationship(roaming_routing_profile_id, sender.clone()).is_ok();
+            // ensure!(is_owned_by_parent_relationship, "Ownership by parent does not exist");
 
-            Self::deposit_event(RawEvent::Created(sender, roaming_routing_profile_id));
+            Self::update_endpoints(sender, roaming_routing_profile_id, endpoints)?;
         }
 
-        /// Transfer a roaming routing_profile to new owner
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn transfer(origin, to: T::AccountId, roaming_routing_profile_id: T::RoamingRoutingProfileIndex) {
+        /// Add an endpoint to a roaming routing_profile
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(2, 1)]
+        pub fn add_endpoint(origin, roaming_routing_profile_id: T::RoamingRoutingProfileIndex, endpoint: RoamingRoutingProfileEndpoint) {
             let sender = ensure_signed(origin)?;
 
-            ensure!(Self::roaming_routing_profile_owner(roaming_routing_profile_id) == Some(sender.clone()), "Only owner can transfer roaming routing_profile");
+            ensure!(Self::is_authorized(roaming_routing_profile_id, &sender), "Only an account authorized for the roaming routing_profile can add endpoints to it");
 
-            Self::update_owner(&to, roaming_routing_profile_id);
-
-            Self::deposit_event(RawEvent::Transferred(sender, to, roaming_routing_profile_id));
+            let mut endpoints = Self::roaming_routing_profile_endpoints(roaming_routing_profile_id).unwrap_or_default();
+            endpoints.push(endpoint);
+            Self::update_endpoints(sender, roaming_routing_profile_id, endpoints)?;
         }
 
-        /// Set app server for a roaming routing_profile
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn set_app_server(origin, roaming_routing_profile_id: T::RoamingRoutingProfileIndex, app_server: Option<T::RoamingRoutingProfileAppServer>) {
+        /// Remove the endpoint with a URI from a roaming routing_profile
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(2, 1)]
+        pub fn remove_endpoint(origin, roaming_routing_profile_id: T::RoamingRoutingProfileIndex, uri: Vec<u8>) {
             let sender = ensure_signed(origin)?;
 
-            ensure!(Self::roaming_routing_profile_owner(roaming_routing_profile_id) == Some(sender.clone()), "Only owner can set app server for roaming routing_profile");
-
-            // let is_owned_by_parent_relationship = Self::is_owned_by_required_parent_relationship(roaming_routing_profile_id, sender.clone()).is_ok();
-            // ensure!(is_owned_by_parent_relationship, "Ownership by parent does not exist");
-
-            if let Some(ref app_server) = app_server {
-                <RoamingRoutingProfileAppServers<T>>::insert(roaming_routing_profile_id, app_server);
-            } else {
-                <RoamingRoutingProfileAppServers<T>>::remove(roaming_routing_profile_id);
-            }
+            ensure!(Self::is_authorized(roaming_routing_profile_id, &sender), "Only an account authorized for the roaming routing_profile can remove endpoints from it");
 
-            Self::deposit_event(RawEvent::AppServerSet(sender, roaming_routing_profile_id, app_server));
+            let mut endpoints = Self::roaming_routing_profile_endpoints(roaming_routing_profile_id).unwrap_or_default();
+            let endpoints_count = endpoints.len();
+            endpoints.retain(|endpoint| endpoint.uri != uri);
+            ensure!(endpoints.len() < endpoints_count, Error::<T>::EndpointNotFound);
+            Self::update_endpoints(sender, roaming_routing_profile_id, endpoints)?;
         }
 
         // Note: This is wrong, routing profile shouldn't be assigned to a device.
@@ -180,17 +340,133 @@ decl_module! {
 
         //     Self::deposit_event(RawEvent::AssignedRoutingProfileToDevice(sender, roaming_routing_profile_id, roaming_device_id));
         // }
+
+        /// Remove a roaming routing profile
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(3, 5)]
+        #[transactional]
+        pub fn remove(origin, roaming_routing_profile_id: T::RoamingRoutingProfileIndex) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(Self::is_authorized(roaming_routing_profile_id, &sender), "Only an account authorized for the roaming routing_profile can remove it");
+
+            Self::remove_roaming_routing_profile(roaming_routing_profile_id)?;
+
+            Self::deposit_event(RawEvent::Removed(sender, roaming_routing_profile_id));
+        }
     }
 }
 
 impl<T: Config> Module<T> {
     pub fn exists_roaming_routing_profile(
         roaming_routing_profile_id: T::RoamingRoutingProfileIndex,
-    ) -> Result<RoamingRoutingProfile, DispatchError> {
-        match Self::roaming_routing_profile(roaming_routing_profile_id) {
-            Some(roaming_routing_profile) => Ok(roaming_routing_profile),
-            None => Err(DispatchError::Other("RoamingRoutingProfile does not exist")),
+    ) -> Result<(), DispatchError> {
+        ensure!(
+            T::RoamingRoutingProfileRegistry::exists(roaming_routing_profile_id),
+            "RoamingRoutingProfile does not exist"
+        );
+        Ok(())
+    }
+
+    /// Check an endpoint of a roaming routing profile, which must have a well-formed URI of a scheme of its protocol
+    pub fn validate_endpoint(endpoint: &RoamingRoutingProfileEndpoint) -> DispatchResult {
+        ensure!(endpoint.uri.len() <= T::MaxEndpointUriLength::get() as usize, Error::<T>::EndpointUriTooLong);
+        let (scheme, _host, _port) = parse_endpoint_uri(&endpoint.uri).ok_or(Error::<T>::InvalidEndpointUri)?;
+        ensure!(endpoint.protocol.schemes().contains(&scheme), Error::<T>::EndpointSchemeMismatch);
+        Ok(())
+    }
+
+    /// Validate and store the endpoints of a roaming routing profile in the order they are tried, so that network
+    /// servers can reconfigure forwarding from the event
+    fn update_endpoints(
+        owner: T::AccountId,
+        roaming_routing_profile_id: T::RoamingRoutingProfileIndex,
+        mut endpoints: Vec<RoamingRoutingProfileEndpoint>,
+    ) -> DispatchResult {
+        ensure!(endpoints.len() <= T::MaxEndpoints::get() as usize, Error::<T>::TooManyEndpoints);
+        for (i, endpoint) in endpoints.iter().enumerate() {
+            Self::validate_endpoint(endpoint)?;
+            ensure!(endpoints[..i].iter().all(|other| other.uri != endpoint.uri), Error::<T>::DuplicateEndpoint);
+        }
+
+        // Endpoints of the same priority keep the order they were given in
+        endpoints.sort_by_key(|endpoint| endpoint.priority);
+        if endpoints.is_empty() {
+            <RoamingRoutingProfileEndpoints<T>>::remove(roaming_routing_profile_id);
+        } else {
+            <RoamingRoutingProfileEndpoints<T>>::insert(roaming_routing_profile_id, &endpoints);
+        }
+
+        Self::deposit_event(RawEvent::EndpointsSet(owner, roaming_routing_profile_id, endpoints));
+        Ok(())
+    }
+
+    /// The number of roaming routing profiles, which this pallet held itself until the registry took them over
+    fn roaming_routing_profiles_count() -> T::RoamingRoutingProfileIndex {
+        get_storage_value::<T::RoamingRoutingProfileIndex>(
+            b"RoamingRoutingProfiles",
+            b"RoamingRoutingProfilesCount",
+            &[],
+        )
+        .unwrap_or_else(T::RoamingRoutingProfileRegistry::entities_count)
+    }
+
+    /// Turn the app server of each roaming routing profile into its only endpoint, adding the default port of its
+    /// scheme if it has none. App servers that still are not a valid endpoint URI of a known protocol are kept under
+    /// their legacy storage key for their owners to set endpoints from. Routing profiles that already have endpoints
+    /// keep them.
+    fn migrate_app_servers_to_endpoints() -> Weight {
+        let roaming_routing_profiles_count = Self::roaming_routing_profiles_count();
+        let mut migrated_count: Weight = 0;
+
+        let mut roaming_routing_profile_id: T::RoamingRoutingProfileIndex = Zero::zero();
+        while roaming_routing_profile_id < roaming_routing_profiles_count {
+            // The app servers were hashed with opaque_blake2_256
+            let hash = blake2_256(&roaming_routing_profile_id.encode());
+            if let Some(app_server) =
+                get_storage_value::<Vec<u8>>(b"RoamingRoutingProfiles", b"RoamingRoutingProfileAppServers", &hash)
+            {
+                if <RoamingRoutingProfileEndpoints<T>>::contains_key(roaming_routing_profile_id) {
+                    debug::info!("Roaming routing profile {:?} already has endpoints", roaming_routing_profile_id);
+                    take_storage_value::<Vec<u8>>(b"RoamingRoutingProfiles", b"RoamingRoutingProfileAppServers", &hash);
+                } else if let Some(endpoint) = Self::app_server_endpoint(app_server.clone()) {
+                    <RoamingRoutingProfileEndpoints<T>>::insert(roaming_routing_profile_id, vec![endpoint]);
+                    take_storage_value::<Vec<u8>>(b"RoamingRoutingProfiles", b"RoamingRoutingProfileAppServers", &hash);
+                } else {
+                    // The owner can set the endpoints of the routing profile from the app server kept in legacy storage
+                    debug::info!(
+                        "Kept app server {:?} of roaming routing profile {:?} that is not a valid endpoint URI",
+                        app_server,
+                        roaming_routing_profile_id
+                    );
+                }
+            }
+
+            migrated_count += 1;
+            roaming_routing_profile_id = roaming_routing_profile_id + One::one();
         }
+
+        StorageVersion::put(Releases::V2_0_0);
+        debug::info!("Migrated the app servers of {:?} roaming routing profiles to endpoints", migrated_count);
+
+        T::DbWeight::get().reads_writes(2 + migrated_count * 2, 1 + migrated_count * 2)
+    }
+
+    /// The endpoint of an app server of a roaming routing profile, with the default port of its scheme if it has no
+    /// port. None if the app server is not a valid endpoint URI, including when it is longer than the maximum length.
+    fn app_server_endpoint(app_server: Vec<u8>) -> Option<RoamingRoutingProfileEndpoint> {
+        let uri = match parse_endpoint_uri(&app_server) {
+            Some(_) => app_server,
+            None => with_default_port(&app_server)?,
+        };
+        let protocol = parse_endpoint_uri(&uri)
+            .and_then(|(scheme, _host, _port)| RoamingRoutingProfileEndpointProtocol::from_scheme(scheme))?;
+        let endpoint = RoamingRoutingProfileEndpoint {
+            uri,
+            protocol,
+            priority: 0,
+        };
+        Self::validate_endpoint(&endpoint).ok()?;
+        Some(endpoint)
     }
 
     // pub fn is_owned_by_required_parent_relationship(roaming_routing_profile_id: T::RoamingRoutingProfileIndex,
@@ -247,36 +523,47 @@ impl<T: Config> Module<T> {
         }
     }
 
-    fn random_value(sender: &T::AccountId) -> [u8; 16] {
-        let payload = (
-            T::Randomness::random(&[0]),
-            sender,
-            <frame_system::Module<T>>::extrinsic_index(),
-            <frame_system::Module<T>>::block_number(),
-        );
-        payload.using_encoded(blake2_128)
+    /// Remove a roaming routing profile along with its link to its device
+    pub fn remove_roaming_routing_profile(roaming_routing_profile_id: T::RoamingRoutingProfileIndex) -> DispatchResult {
+        Self::exists_roaming_routing_profile(roaming_routing_profile_id)?;
+        if let Some(roaming_device_id) = <RoamingRoutingProfileDevices<T>>::take(roaming_routing_profile_id) {
+            <RoamingDeviceRoutingProfiles<T>>::mutate_exists(roaming_device_id, |routing_profile_ids| {
+                *routing_profile_ids = without_roaming_entity(routing_profile_ids.take(), &roaming_routing_profile_id)
+            });
+        }
+        T::RoamingRoutingProfileRegistry::deregister(roaming_routing_profile_id)?;
+        <RoamingRoutingProfileEndpoints<T>>::remove(roaming_routing_profile_id);
+        Ok(())
     }
+}
 
-    fn next_roaming_routing_profile_id() -> Result<T::RoamingRoutingProfileIndex, DispatchError> {
-        let roaming_routing_profile_id = Self::roaming_routing_profiles_count();
-        if roaming_routing_profile_id == <T::RoamingRoutingProfileIndex as Bounded>::max_value() {
-            return Err(DispatchError::Other("RoamingRoutingProfiles count overflow"));
-        }
-        Ok(roaming_routing_profile_id)
+impl<T: Config> RoamingAuthority<T::AccountId, T::RoamingRoutingProfileIndex> for Module<T> {
+    fn is_owner_or_admin(roaming_routing_profile_id: T::RoamingRoutingProfileIndex, account: &T::AccountId) -> bool {
+        T::RoamingRoutingProfileRegistry::is_owner_or_admin(roaming_routing_profile_id, account)
     }
 
-    fn insert_roaming_routing_profile(
-        owner: &T::AccountId,
-        roaming_routing_profile_id: T::RoamingRoutingProfileIndex,
-        roaming_routing_profile: RoamingRoutingProfile,
-    ) {
-        // Create and store roaming routing_profile
-        <RoamingRoutingProfiles<T>>::insert(roaming_routing_profile_id, roaming_routing_profile);
-        <RoamingRoutingProfilesCount<T>>::put(roaming_routing_profile_id + One::one());
-        <RoamingRoutingProfileOwners<T>>::insert(roaming_routing_profile_id, owner.clone());
+    /// The owner and admins of a roaming routing profile are authorized, as is any account authorized for its device
+    fn is_authorized(roaming_routing_profile_id: T::RoamingRoutingProfileIndex, account: &T::AccountId) -> bool {
+        Self::is_owner_or_admin(roaming_routing_profile_id, account) ||
+            Self::roaming_routing_profile_device(roaming_routing_profile_id)
+                .map_or(false, |parent_id| <roaming_devices::Module<T>>::is_authorized(parent_id, account))
+    }
+}
+
+/// The routing profiles of a roaming device, other than those that have since been assigned to another device
+impl<T: Config> RoamingEntityChildren<RoamingDeviceEntity, T::RoamingDeviceIndex> for Module<T> {
+    fn has_children(roaming_device_id: T::RoamingDeviceIndex) -> bool {
+        Self::roaming_device_routing_profiles(roaming_device_id).unwrap_or_default().into_iter().any(
+            |routing_profile_id| Self::roaming_routing_profile_device(routing_profile_id) == Some(roaming_device_id),
+        )
     }
 
-    fn update_owner(to: &T::AccountId, roaming_routing_profile_id: T::RoamingRoutingProfileIndex) {
-        <RoamingRoutingProfileOwners<T>>::insert(roaming_routing_profile_id, to);
+    fn remove_children(roaming_device_id: T::RoamingDeviceIndex) -> DispatchResult {
+        for routing_profile_id in <RoamingDeviceRoutingProfiles<T>>::take(roaming_device_id).unwrap_or_default() {
+            if Self::roaming_routing_profile_device(routing_profile_id) == Some(roaming_device_id) {
+                Self::remove_roaming_routing_profile(routing_profile_id)?;
+            }
+        }
+        Ok(())
     }
 }
@@ -5,77 +5,275 @@ use crate::mock::*;
 use frame_support::{
     assert_noop,
     assert_ok,
+    storage::migration::put_storage_value,
+    traits::OnRuntimeUpgrade,
 };
+use sp_io::hashing::blake2_256;
 
 #[test]
 fn basic_setup_works() {
     new_test_ext().execute_with(|| {
         // Verify Initial Storage
-        assert_eq!(RoamingRoutingProfileModule::roaming_routing_profiles_count(), 0);
-        assert!(RoamingRoutingProfileModule::roaming_routing_profile(0).is_none());
-        assert_eq!(RoamingRoutingProfileModule::roaming_routing_profile_owner(0), None);
+        assert_eq!(RoamingRoutingProfileRegistry::roaming_entities_count(), 0);
+        assert!(RoamingRoutingProfileRegistry::roaming_entity(0).is_none());
+        assert_eq!(RoamingRoutingProfileRegistry::roaming_entity_owner(0), None);
         assert_eq!(Balances::free_balance(1), 10);
         assert_eq!(Balances::free_balance(2), 20);
     });
 }
 
+fn endpoint(
+    uri: &[u8],
+    protocol: RoamingRoutingProfileEndpointProtocol,
+    priority: u8,
+) -> RoamingRoutingProfileEndpoint {
+    RoamingRoutingProfileEndpoint {
+        uri: uri.to_vec(),
+        protocol,
+        priority,
+    }
+}
+
+#[test]
+fn parse_endpoint_uri_works() {
+    assert_eq!(
+        parse_endpoint_uri(b"https://as.example.com:8443/uplink"),
+        Some((&b"https"[..], &b"as.example.com"[..], 8443))
+    );
+    assert_eq!(parse_endpoint_uri(b"mqtt://10.0.0.1:1883"), Some((&b"mqtt"[..], &b"10.0.0.1"[..], 1883)));
+    assert_eq!(parse_endpoint_uri(b"grpc://[::1]:8000"), Some((&b"grpc"[..], &b"[::1]"[..], 8000)));
+    // No scheme, no port, port out of range, bad host and whitespace in the path
+    assert_eq!(parse_endpoint_uri(b"10.0.0.1:80"), None);
+    assert_eq!(parse_endpoint_uri(b"http://10.0.0.1"), None);
+    assert_eq!(parse_endpoint_uri(b"http://10.0.0.1:0"), None);
+    assert_eq!(parse_endpoint_uri(b"http://10.0.0.1:65536"), None);
+    assert_eq!(parse_endpoint_uri(b"http://-as.example.com:80"), None);
+    assert_eq!(parse_endpoint_uri(b"http://as..example.com:80"), None);
+    assert_eq!(parse_endpoint_uri(b"http://as.example.com:80/up link"), None);
+    assert_eq!(parse_endpoint_uri(b"HTTP://as.example.com:80"), None);
+}
+
+#[test]
+fn with_default_port_works() {
+    assert_eq!(
+        with_default_port(b"https://as.example.com/uplink"),
+        Some(b"https://as.example.com:443/uplink".to_vec())
+    );
+    assert_eq!(with_default_port(b"http://10.0.0.1"), Some(b"http://10.0.0.1:80".to_vec()));
+    assert_eq!(with_default_port(b"mqtt://10.0.0.1"), Some(b"mqtt://10.0.0.1:1883".to_vec()));
+    assert_eq!(with_default_port(b"mqtts://[::1]"), Some(b"mqtts://[::1]:8883".to_vec()));
+    assert_eq!(with_default_port(b"grpcs://as.example.com"), Some(b"grpcs://as.example.com:443".to_vec()));
+    // No scheme and a scheme without a default port
+    assert_eq!(with_default_port(b"10.0.0.1"), None);
+    assert_eq!(with_default_port(b"ftp://10.0.0.1"), None);
+}
+
 #[test]
-fn create_works() {
+fn set_endpoints_works() {
     new_test_ext().execute_with(|| {
+        // Setup
+        assert_ok!(RoamingRoutingProfileRegistry::create(Origin::signed(1)));
+        let primary = endpoint(b"https://as1.example.com:443", RoamingRoutingProfileEndpointProtocol::Http, 0);
+        let fallback = endpoint(b"mqtts://as2.example.com:8883", RoamingRoutingProfileEndpointProtocol::Mqtt, 1);
         // Call Functions
-        assert_ok!(RoamingRoutingProfileModule::create(Origin::signed(1)));
+        assert_ok!(RoamingRoutingProfileModule::set_endpoints(
+            Origin::signed(1),
+            0,
+            vec![fallback.clone(), primary.clone()]
+        ));
         // Verify Storage
-        assert_eq!(RoamingRoutingProfileModule::roaming_routing_profiles_count(), 1);
-        assert!(RoamingRoutingProfileModule::roaming_routing_profile(0).is_some());
-        assert_eq!(RoamingRoutingProfileModule::roaming_routing_profile_owner(0), Some(1));
+        assert_eq!(
+            RoamingRoutingProfileModule::roaming_routing_profile_endpoints(0),
+            Some(vec![primary.clone(), fallback.clone()])
+        );
+
+        let backup = endpoint(b"grpcs://as3.example.com:9443", RoamingRoutingProfileEndpointProtocol::Grpc, 1);
+        assert_ok!(RoamingRoutingProfileModule::add_endpoint(Origin::signed(1), 0, backup.clone()));
+        assert_eq!(
+            RoamingRoutingProfileModule::roaming_routing_profile_endpoints(0),
+            Some(vec![primary.clone(), fallback, backup.clone()])
+        );
+
+        assert_ok!(RoamingRoutingProfileModule::remove_endpoint(
+            Origin::signed(1),
+            0,
+            b"mqtts://as2.example.com:8883".to_vec()
+        ));
+        assert_eq!(RoamingRoutingProfileModule::roaming_routing_profile_endpoints(0), Some(vec![primary, backup]));
+
+        assert_ok!(RoamingRoutingProfileModule::set_endpoints(Origin::signed(1), 0, vec![]));
+        assert_eq!(RoamingRoutingProfileModule::roaming_routing_profile_endpoints(0), None);
     });
 }
 
 #[test]
-fn create_handles_basic_errors() {
+fn operator_owner_manages_routing_profiles_of_its_devices() {
     new_test_ext().execute_with(|| {
-        // Setup
-        <RoamingRoutingProfilesCount<Test>>::put(u64::max_value());
+        // Setup - the network, network server, device and routing profile under operator 0 are owned by account 3,
+        // not operator owner 1
+        assert_ok!(RoamingOperatorRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkRegistry::create(Origin::signed(3)));
+        assert_ok!(RoamingNetworkModule::assign_network_to_operator(Origin::signed(1), 0, 0));
+        assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(3)));
+        assert_ok!(RoamingNetworkServerModule::assign_network_server_to_network(Origin::signed(3), 0, 0));
+        assert_ok!(RoamingDeviceRegistry::create(Origin::signed(3)));
+        assert_ok!(RoamingDeviceModule::assign_device_to_network_server(Origin::signed(3), 0, 0));
+        assert_ok!(RoamingRoutingProfileRegistry::create(Origin::signed(3)));
+        <RoamingRoutingProfileDevices<Test>>::insert(0, 0);
+        let endpoints = vec![endpoint(b"https://as.example.com:443", RoamingRoutingProfileEndpointProtocol::Http, 0)];
         // Call Functions
-        assert_noop!(RoamingRoutingProfileModule::create(Origin::signed(1)), "RoamingRoutingProfiles count overflow");
+        assert_noop!(
+            RoamingRoutingProfileModule::set_endpoints(Origin::signed(4), 0, endpoints.clone()),
+            "Only an account authorized for the roaming routing_profile can set its endpoints"
+        );
+        assert_ok!(RoamingRoutingProfileModule::set_endpoints(Origin::signed(1), 0, endpoints.clone()));
+        assert_eq!(RoamingRoutingProfileModule::roaming_routing_profile_endpoints(0), Some(endpoints));
+        assert_noop!(
+            RoamingRoutingProfileModule::remove(Origin::signed(4), 0),
+            "Only an account authorized for the roaming routing_profile can remove it"
+        );
+        assert_ok!(RoamingRoutingProfileModule::remove(Origin::signed(1), 0));
         // Verify Storage
-        assert_eq!(RoamingRoutingProfileModule::roaming_routing_profiles_count(), u64::max_value());
-        assert!(RoamingRoutingProfileModule::roaming_routing_profile(0).is_none());
-        assert_eq!(RoamingRoutingProfileModule::roaming_routing_profile_owner(0), None);
+        assert!(RoamingRoutingProfileRegistry::roaming_entity(0).is_none());
     });
 }
 
 #[test]
-fn transfer_works() {
+fn set_endpoints_handles_basic_errors() {
     new_test_ext().execute_with(|| {
         // Setup
-        assert_ok!(RoamingRoutingProfileModule::create(Origin::signed(1)));
+        assert_ok!(RoamingRoutingProfileRegistry::create(Origin::signed(1)));
+        let http = RoamingRoutingProfileEndpointProtocol::Http;
         // Call Functions
-        assert_ok!(RoamingRoutingProfileModule::transfer(Origin::signed(1), 2, 0));
-        // Verify Storage
-        assert_eq!(RoamingRoutingProfileModule::roaming_routing_profiles_count(), 1);
-        assert!(RoamingRoutingProfileModule::roaming_routing_profile(0).is_some());
-        assert_eq!(RoamingRoutingProfileModule::roaming_routing_profile_owner(0), Some(2));
+        assert_noop!(
+            RoamingRoutingProfileModule::set_endpoints(Origin::signed(2), 0, vec![endpoint(b"http://as:80", http, 0)]),
+            "Only an account authorized for the roaming routing_profile can set its endpoints"
+        );
+        assert_noop!(
+            RoamingRoutingProfileModule::set_endpoints(Origin::signed(1), 0, vec![endpoint(b"as:80", http, 0)]),
+            Error::<Test>::InvalidEndpointUri
+        );
+        assert_noop!(
+            RoamingRoutingProfileModule::set_endpoints(
+                Origin::signed(1),
+                0,
+                vec![endpoint(b"mqtt://as:1883", http, 0)]
+            ),
+            Error::<Test>::EndpointSchemeMismatch
+        );
+        assert_noop!(
+            RoamingRoutingProfileModule::set_endpoints(
+                Origin::signed(1),
+                0,
+                vec![endpoint(&[&b"http://as:80/"[..], &[b'a'; 64][..]].concat(), http, 0)]
+            ),
+            Error::<Test>::EndpointUriTooLong
+        );
+        assert_noop!(
+            RoamingRoutingProfileModule::set_endpoints(
+                Origin::signed(1),
+                0,
+                vec![endpoint(b"http://as:80", http, 0), endpoint(b"http://as:80", http, 1)]
+            ),
+            Error::<Test>::DuplicateEndpoint
+        );
+        assert_noop!(
+            RoamingRoutingProfileModule::set_endpoints(
+                Origin::signed(1),
+                0,
+                vec![
+                    endpoint(b"http://as1:80", http, 0),
+                    endpoint(b"http://as2:80", http, 0),
+                    endpoint(b"http://as3:80", http, 0),
+                    endpoint(b"http://as4:80", http, 0)
+                ]
+            ),
+            Error::<Test>::TooManyEndpoints
+        );
+        assert_noop!(
+            RoamingRoutingProfileModule::remove_endpoint(Origin::signed(1), 0, b"http://as:80".to_vec()),
+            Error::<Test>::EndpointNotFound
+        );
     });
 }
 
 #[test]
-fn transfer_handles_basic_errors() {
+fn migration_turns_app_servers_into_endpoints() {
     new_test_ext().execute_with(|| {
         // Setup
-        assert_ok!(RoamingRoutingProfileModule::create(Origin::signed(1)));
+        for _ in 0..5 {
+            assert_ok!(RoamingRoutingProfileRegistry::create(Origin::signed(1)));
+        }
+        let long_app_server = [&b"https://"[..], &[b'a'; 60][..], &b".com:443"[..]].concat();
+        let app_servers: [&[u8]; 5] = [
+            b"https://as.example.com:443",
+            b"10.0.0.1",
+            b"mqtt://10.0.0.2:1883",
+            b"https://as.example.com/uplink",
+            &long_app_server,
+        ];
+        for (roaming_routing_profile_id, app_server) in app_servers.iter().enumerate() {
+            put_storage_value(
+                b"RoamingRoutingProfiles",
+                b"RoamingRoutingProfileAppServers",
+                &blake2_256(&(roaming_routing_profile_id as u64).encode()),
+                app_server.to_vec(),
+            );
+        }
+        StorageVersion::put(Releases::V1_0_0);
         // Call Functions
-        assert_noop!(
-            RoamingRoutingProfileModule::transfer(Origin::signed(2), 2, 0),
-            "Only owner can transfer roaming routing_profile"
+        RoamingRoutingProfileModule::on_runtime_upgrade();
+        // Verify Storage
+        assert_eq!(
+            RoamingRoutingProfileModule::roaming_routing_profile_endpoints(0),
+            Some(vec![endpoint(b"https://as.example.com:443", RoamingRoutingProfileEndpointProtocol::Http, 0)])
         );
-        assert_noop!(
-            RoamingRoutingProfileModule::transfer(Origin::signed(1), 2, 1),
-            "Only owner can transfer roaming routing_profile"
+        assert_eq!(RoamingRoutingProfileModule::roaming_routing_profile_endpoints(1), None);
+        assert_eq!(
+            RoamingRoutingProfileModule::roaming_routing_profile_endpoints(2),
+            Some(vec![endpoint(b"mqtt://10.0.0.2:1883", RoamingRoutingProfileEndpointProtocol::Mqtt, 0)])
+        );
+        assert_eq!(
+            RoamingRoutingProfileModule::roaming_routing_profile_endpoints(3),
+            Some(vec![endpoint(b"https://as.example.com:443/uplink", RoamingRoutingProfileEndpointProtocol::Http, 0)])
         );
+        // The app server longer than the maximum endpoint URI length is not an endpoint
+        assert_eq!(RoamingRoutingProfileModule::roaming_routing_profile_endpoints(4), None);
+        assert_eq!(RoamingRoutingProfileModule::storage_version(), Releases::V2_0_0);
+        for (roaming_routing_profile_id, app_server) in app_servers.iter().enumerate() {
+            let legacy_app_server = get_storage_value::<Vec<u8>>(
+                b"RoamingRoutingProfiles",
+                b"RoamingRoutingProfileAppServers",
+                &blake2_256(&(roaming_routing_profile_id as u64).encode()),
+            );
+            // App servers that are not valid endpoint URIs are kept under their legacy key
+            if [1, 4].contains(&roaming_routing_profile_id) {
+                assert_eq!(legacy_app_server, Some(app_server.to_vec()));
+            } else {
+                assert_eq!(legacy_app_server, None);
+            }
+        }
+    });
+}
+
+#[test]
+fn migration_keeps_existing_routing_profile_endpoints() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        assert_ok!(RoamingRoutingProfileRegistry::create(Origin::signed(1)));
+        let endpoints = vec![endpoint(b"grpcs://as.example.com:8443", RoamingRoutingProfileEndpointProtocol::Grpc, 1)];
+        <RoamingRoutingProfileEndpoints<Test>>::insert(0, endpoints.clone());
+        put_storage_value(
+            b"RoamingRoutingProfiles",
+            b"RoamingRoutingProfileAppServers",
+            &blake2_256(&0u64.encode()),
+            b"https://as.example.com:443".to_vec(),
+        );
+        StorageVersion::put(Releases::V1_0_0);
+        // Call Functions
+        RoamingRoutingProfileModule::on_runtime_upgrade();
         // Verify Storage
-        assert_eq!(RoamingRoutingProfileModule::roaming_routing_profiles_count(), 1);
-        assert!(RoamingRoutingProfileModule::roaming_routing_profile(0).is_some());
-        assert_eq!(RoamingRoutingProfileModule::roaming_routing_profile_owner(0), Some(1));
+        assert_eq!(RoamingRoutingProfileModule::roaming_routing_profile_endpoints(0), Some(endpoints));
+        assert_eq!(RoamingRoutingProfileModule::storage_version(), Releases::V2_0_0);
     });
 }
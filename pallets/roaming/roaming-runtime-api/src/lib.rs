@@ -0,0 +1,97 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+#![allow(clippy::too_many_arguments)]
+#![allow(clippy::unnecessary_mut_passed)]
+
+use codec::{
+    Codec,
+    Decode,
+    Encode,
+};
+#[cfg(feature = "std")]
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use sp_std::prelude::*; // Imports Vec
+
+/// The most items that a page may hold, whatever limit is asked for
+pub const MAX_PAGE_LIMIT: u32 = 100;
+
+/// A page of the children of a roaming entity, along with the child that the next page starts at
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+pub struct RoamingPage<RoamingEntityIndex, Item = RoamingEntityIndex> {
+    pub items: Vec<Item>,
+    /// The child to pass as the start of the next page, or None if this is the last page
+    pub next_start: Option<RoamingEntityIndex>,
+}
+
+impl<RoamingEntityIndex> RoamingPage<RoamingEntityIndex> {
+    /// Read up to `limit` children from an iterator that starts at the first child of the page, where the limit is at
+    /// most `MAX_PAGE_LIMIT`. Only one child past the page is read, to find where the next page starts.
+    pub fn from_children(mut children: impl Iterator<Item = RoamingEntityIndex>, limit: u32) -> Self {
+        let items = children.by_ref().take(limit.min(MAX_PAGE_LIMIT) as usize).collect();
+        RoamingPage {
+            items,
+            next_start: children.next(),
+        }
+    }
+}
+
+impl<RoamingEntityIndex, Item> RoamingPage<RoamingEntityIndex, Item> {
+    /// Resolve each item of the page
+    pub fn map<Resolved>(self, resolve: impl FnMut(Item) -> Resolved) -> RoamingPage<RoamingEntityIndex, Resolved> {
+        RoamingPage {
+            items: self.items.into_iter().map(resolve).collect(),
+            next_start: self.next_start,
+        }
+    }
+}
+
+/// A roaming network server with the first page of its devices
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+pub struct RoamingNetworkServerSubtree<RoamingEntityIndex> {
+    pub network_server_id: RoamingEntityIndex,
+    pub devices: RoamingPage<RoamingEntityIndex>,
+}
+
+/// A roaming network with a page of its network servers. Each network server has the first page of its devices.
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+pub struct RoamingNetworkSubtree<RoamingEntityIndex> {
+    pub network_id: RoamingEntityIndex,
+    pub network_servers: RoamingPage<RoamingEntityIndex, RoamingNetworkServerSubtree<RoamingEntityIndex>>,
+}
+
+/// A roaming operator with a page of its networks. Each network has the first page of its network servers.
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+pub struct RoamingOperatorSubtree<RoamingEntityIndex> {
+    pub operator_id: RoamingEntityIndex,
+    pub networks: RoamingPage<RoamingEntityIndex, RoamingNetworkSubtree<RoamingEntityIndex>>,
+}
+
+// Here we declare the runtime API. It is implemented in the `impl` block in
+// runtime amalgamator file (the `runtime/src/lib.rs`)
+sp_api::decl_runtime_apis! {
+    /// Resolves the graph of roaming entities. Only the entities that are still assigned to a parent are listed as
+    /// its children. The children of the given entity are paged in the order they are stored, from the child `start`
+    /// (or the first child if it is None) up to `limit` children, and any children below them are resolved to their
+    /// first page. Pages hold at most `MAX_PAGE_LIMIT` items, and the `next_start` of a page is the `start` of the next.
+    pub trait RoamingApi<RoamingEntityIndex> where
+        RoamingEntityIndex: Codec,
+    {
+        /// The networks of an operator with their network servers and devices, if the operator exists
+        fn roaming_operator_subtree(operator_id: RoamingEntityIndex, start: Option<RoamingEntityIndex>, limit: u32) -> Option<RoamingOperatorSubtree<RoamingEntityIndex>>;
+        /// The network servers of a network with their devices, if the network exists
+        fn roaming_network_subtree(network_id: RoamingEntityIndex, start: Option<RoamingEntityIndex>, limit: u32) -> Option<RoamingNetworkSubtree<RoamingEntityIndex>>;
+        fn roaming_network_server_devices(network_server_id: RoamingEntityIndex, start: Option<RoamingEntityIndex>, limit: u32) -> RoamingPage<RoamingEntityIndex>;
+        fn roaming_device_sessions(device_id: RoamingEntityIndex, start: Option<RoamingEntityIndex>, limit: u32) -> RoamingPage<RoamingEntityIndex>;
+        fn roaming_session_packet_bundles(session_id: RoamingEntityIndex, start: Option<RoamingEntityIndex>, limit: u32) -> RoamingPage<RoamingEntityIndex>;
+    }
+}
@@ -10,22 +10,29 @@ use frame_support::{
     decl_module,
     decl_storage,
     ensure,
-    traits::{
-        Get,
-        Randomness,
-    },
+    traits::Get,
+    transactional,
     Parameter,
 };
 use frame_system::ensure_signed;
-use sp_io::hashing::blake2_128;
 use sp_runtime::{
     traits::{
         AtLeast32Bit,
         Bounded,
         Member,
-        One,
     },
     DispatchError,
+    DispatchResult,
+};
+use roaming_operators::{
+    without_roaming_entity,
+    RoamingAuthority,
+    RoamingEntityChildren,
+    RoamingNetworkServerEntity,
+};
+use roaming_registry::{
+    RoamingRegistry,
+    RoamingTradable,
 };
 use sp_std::prelude::*; // Imports Vec
 
@@ -39,14 +46,12 @@ mod tests;
 pub trait Config: frame_system::Config + roaming_operators::Config + roaming_network_servers::Config {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
     type RoamingServiceProfileIndex: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
+    /// The registry that holds the roaming service profiles and their owners
+    type RoamingServiceProfileRegistry: RoamingRegistry<Self::AccountId, Self::RoamingServiceProfileIndex>;
     type RoamingServiceProfileUplinkRate: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
     type RoamingServiceProfileDownlinkRate: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
 }
 
-#[derive(Encode, Decode, Clone, PartialEq, Eq)]
-#[cfg_attr(feature = "std", derive(Debug))]
-pub struct RoamingServiceProfile(pub [u8; 16]);
-
 decl_event!(
     pub enum Event<T> where
         <T as frame_system::Config>::AccountId,
@@ -55,31 +60,20 @@ decl_event!(
         <T as Config>::RoamingServiceProfileDownlinkRate,
         <T as roaming_network_servers::Config>::RoamingNetworkServerIndex,
     {
-        /// A roaming service_profile is created. (owner, roaming_service_profile_id)
-        Created(AccountId, RoamingServiceProfileIndex),
-        /// A roaming service_profile is transferred. (from, to, roaming_service_profile_id)
-        Transferred(AccountId, AccountId, RoamingServiceProfileIndex),
         /// A roaming service_profile is assigned an uplink rate. (owner, roaming_service_profile_id, uplink rate)
         UplinkRateSet(AccountId, RoamingServiceProfileIndex, Option<RoamingServiceProfileUplinkRate>),
         /// A roaming service_profile is assigned an downlink rate. (owner, roaming_service_profile_id, downlink rate)
         DownlinkRateSet(AccountId, RoamingServiceProfileIndex, Option<RoamingServiceProfileDownlinkRate>),
         /// A roaming service_profile is assigned to a network_server. (owner of network_server, roaming_service_profile_id, roaming_network_server_id)
         AssignedServiceProfileToNetworkServer(AccountId, RoamingServiceProfileIndex, RoamingNetworkServerIndex),
+        /// A roaming service_profile is removed. (owner, roaming_service_profile_id)
+        Removed(AccountId, RoamingServiceProfileIndex),
     }
 );
 
 // This module's storage items.
 decl_storage! {
     trait Store for Module<T: Config> as RoamingServiceProfiles {
-        /// Stores all the roaming service_profiles, key is the roaming service_profile id / index
-        pub RoamingServiceProfiles get(fn roaming_service_profile): map hasher(opaque_blake2_256) T::RoamingServiceProfileIndex => Option<RoamingServiceProfile>;
-
-        /// Stores the total number of roaming service_profiles. i.e. the next roaming service_profile index
-        pub RoamingServiceProfilesCount get(fn roaming_service_profiles_count): T::RoamingServiceProfileIndex;
-
-        /// Get roaming service_profile owner
-        pub RoamingServiceProfileOwners get(fn roaming_service_profile_owner): map hasher(opaque_blake2_256) T::RoamingServiceProfileIndex => Option<T::AccountId>;
-
         /// Get roaming service_profile uplink rate.
         pub RoamingServiceProfileUplinkRates get(fn roaming_service_profile_uplink_rate): map hasher(opaque_blake2_256) T::RoamingServiceProfileIndex => Option<T::RoamingServiceProfileUplinkRate>;
 
@@ -100,40 +94,12 @@ decl_module! {
     pub struct Module<T: Config> for enum Call where origin: T::Origin {
         fn deposit_event() = default;
 
-        /// Create a new roaming service_profile
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn create(origin) {
-            let sender = ensure_signed(origin)?;
-            let roaming_service_profile_id = Self::next_roaming_service_profile_id()?;
-
-            // Generate a random 128bit value
-            let unique_id = Self::random_value(&sender);
-
-            // Create and store roaming service_profile
-            let roaming_service_profile = RoamingServiceProfile(unique_id);
-            Self::insert_roaming_service_profile(&sender, roaming_service_profile_id, roaming_service_profile);
-
-            Self::deposit_event(RawEvent::Created(sender, roaming_service_profile_id));
-        }
-
-        /// Transfer a roaming service_profile to new owner
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn transfer(origin, to: T::AccountId, roaming_service_profile_id: T::RoamingServiceProfileIndex) {
-            let sender = ensure_signed(origin)?;
-
-            ensure!(Self::roaming_service_profile_owner(roaming_service_profile_id) == Some(sender.clone()), "Only owner can transfer roaming service_profile");
-
-            Self::update_owner(&to, roaming_service_profile_id);
-
-            Self::deposit_event(RawEvent::Transferred(sender, to, roaming_service_profile_id));
-        }
-
         /// Set uplink_rate for a roaming service_profile
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
         pub fn set_uplink_rate(origin, roaming_service_profile_id: T::RoamingServiceProfileIndex, uplink_rate: Option<T::RoamingServiceProfileUplinkRate>) {
             let sender = ensure_signed(origin)?;
 
-            ensure!(Self::roaming_service_profile_owner(roaming_service_profile_id) == Some(sender.clone()), "Only owner can set uplink_rate for roaming service_profile");
+            ensure!(T::RoamingServiceProfileRegistry::owner_of(roaming_service_profile_id) == Some(sender.clone()), "Only owner can set uplink_rate for roaming service_profile");
 
             // let is_owned_by_parent_relationship = Self::is_owned_by_required_parent_relationship(roaming_service_profile_id, sender.clone()).is_ok();
             // ensure!(is_owned_by_parent_relationship, "Ownership by parent does not exist");
@@ -152,7 +118,7 @@ decl_module! {
         pub fn set_downlink_rate(origin, roaming_service_profile_id: T::RoamingServiceProfileIndex, downlink_rate: Option<T::RoamingServiceProfileDownlinkRate>) {
             let sender = ensure_signed(origin)?;
 
-            ensure!(Self::roaming_service_profile_owner(roaming_service_profile_id) == Some(sender.clone()), "Only owner can set downlink_rate for roaming service_profile");
+            ensure!(T::RoamingServiceProfileRegistry::owner_of(roaming_service_profile_id) == Some(sender.clone()), "Only owner can set downlink_rate for roaming service_profile");
 
             // let is_owned_by_parent_relationship = Self::is_owned_by_required_parent_relationship(roaming_service_profile_id, sender.clone()).is_ok();
             // ensure!(is_owned_by_parent_relationship, "Ownership by parent does not exist");
@@ -169,6 +135,7 @@ decl_module! {
         // Optional: Service Profile is assigned to Network (Roaming Base) Profile, which is associated with a network.
         // This is an override to associate it with a specific Network Server rather than entire networks.
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        #[transactional]
         pub fn assign_service_profile_to_network_server(
             origin,
             roaming_service_profile_id: T::RoamingServiceProfileIndex,
@@ -181,18 +148,16 @@ decl_module! {
                 ::exists_roaming_network_server(roaming_network_server_id).is_ok();
             ensure!(is_roaming_network_server, "RoamingNetworkServer does not exist");
 
-            // Ensure that caller of the function is the owner of the network_server id to assign the service_profile to
+            // Ensure that caller of the function is authorized for the network_server id to assign the service_profile to
             ensure!(
-                <roaming_network_servers::Module<T>>::is_roaming_network_server_owner(roaming_network_server_id, sender.clone()).is_ok(),
-                "Only the roaming network_server owner can assign itself a roaming service_profile"
+                <roaming_network_servers::Module<T>>::is_authorized(roaming_network_server_id, &sender),
+                "Only an account authorized for the roaming network_server can assign itself a roaming service_profile"
             );
 
-            Self::associate_service_profile_with_network_server(roaming_service_profile_id, roaming_network_server_id)
-                .expect("Unable to associate service_profile with network_server");
+            Self::associate_service_profile_with_network_server(roaming_service_profile_id, roaming_network_server_id)?;
 
             // Ensure that the given service_profile id already exists
-            let roaming_service_profile = Self::roaming_service_profile(roaming_service_profile_id);
-            ensure!(roaming_service_profile.is_some(), "Invalid roaming_service_profile_id");
+            ensure!(T::RoamingServiceProfileRegistry::exists(roaming_service_profile_id), "Invalid roaming_service_profile_id");
 
             // Ensure that the service_profile is not already owned by a different network_server
             // Unassign the service_profile from any existing network_server since it may only be owned by one network_server
@@ -203,17 +168,31 @@ decl_module! {
 
             Self::deposit_event(RawEvent::AssignedServiceProfileToNetworkServer(sender, roaming_service_profile_id, roaming_network_server_id));
         }
+
+        /// Remove a roaming service profile
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(3, 6)]
+        #[transactional]
+        pub fn remove(origin, roaming_service_profile_id: T::RoamingServiceProfileIndex) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(T::RoamingServiceProfileRegistry::owner_of(roaming_service_profile_id) == Some(sender.clone()), "Only owner can remove roaming service_profile");
+
+            Self::remove_roaming_service_profile(roaming_service_profile_id)?;
+
+            Self::deposit_event(RawEvent::Removed(sender, roaming_service_profile_id));
+        }
     }
 }
 
 impl<T: Config> Module<T> {
     pub fn exists_roaming_service_profile(
         roaming_service_profile_id: T::RoamingServiceProfileIndex,
-    ) -> Result<RoamingServiceProfile, DispatchError> {
-        match Self::roaming_service_profile(roaming_service_profile_id) {
-            Some(roaming_service_profile) => Ok(roaming_service_profile),
-            None => Err(DispatchError::Other("RoamingServiceProfile does not exist")),
-        }
+    ) -> Result<(), DispatchError> {
+        ensure!(
+            T::RoamingServiceProfileRegistry::exists(roaming_service_profile_id),
+            "RoamingServiceProfile does not exist"
+        );
+        Ok(())
     }
 
     // pub fn is_owned_by_required_parent_relationship(roaming_service_profile_id: T::RoamingServiceProfileIndex,
@@ -285,36 +264,42 @@ impl<T: Config> Module<T> {
         }
     }
 
-    fn random_value(sender: &T::AccountId) -> [u8; 16] {
-        let payload = (
-            T::Randomness::random(&[0]),
-            sender,
-            <frame_system::Module<T>>::extrinsic_index(),
-            <frame_system::Module<T>>::block_number(),
-        );
-        payload.using_encoded(blake2_128)
-    }
-
-    fn next_roaming_service_profile_id() -> Result<T::RoamingServiceProfileIndex, DispatchError> {
-        let roaming_service_profile_id = Self::roaming_service_profiles_count();
-        if roaming_service_profile_id == <T::RoamingServiceProfileIndex as Bounded>::max_value() {
-            return Err(DispatchError::Other("RoamingServiceProfiles count overflow"));
+    /// Remove a roaming service profile along with its link to its network server
+    pub fn remove_roaming_service_profile(roaming_service_profile_id: T::RoamingServiceProfileIndex) -> DispatchResult {
+        Self::exists_roaming_service_profile(roaming_service_profile_id)?;
+        if let Some(roaming_network_server_id) =
+            <RoamingServiceProfileNetworkServer<T>>::take(roaming_service_profile_id)
+        {
+            <RoamingNetworkServerServiceProfiles<T>>::mutate_exists(roaming_network_server_id, |service_profile_ids| {
+                *service_profile_ids = without_roaming_entity(service_profile_ids.take(), &roaming_service_profile_id)
+            });
         }
-        Ok(roaming_service_profile_id)
+        T::RoamingServiceProfileRegistry::deregister(roaming_service_profile_id)?;
+        <RoamingServiceProfileUplinkRates<T>>::remove(roaming_service_profile_id);
+        <RoamingServiceProfileDownlinkRates<T>>::remove(roaming_service_profile_id);
+        Ok(())
     }
+}
 
-    fn insert_roaming_service_profile(
-        owner: &T::AccountId,
-        roaming_service_profile_id: T::RoamingServiceProfileIndex,
-        roaming_service_profile: RoamingServiceProfile,
-    ) {
-        // Create and store roaming service_profile
-        <RoamingServiceProfiles<T>>::insert(roaming_service_profile_id, roaming_service_profile);
-        <RoamingServiceProfilesCount<T>>::put(roaming_service_profile_id + One::one());
-        <RoamingServiceProfileOwners<T>>::insert(roaming_service_profile_id, owner.clone());
+/// The service profiles of a roaming network server, other than those that have since been assigned to another network
+/// server
+impl<T: Config> RoamingEntityChildren<RoamingNetworkServerEntity, T::RoamingNetworkServerIndex> for Module<T> {
+    fn has_children(roaming_network_server_id: T::RoamingNetworkServerIndex) -> bool {
+        Self::roaming_network_server_service_profiles(roaming_network_server_id).unwrap_or_default().into_iter().any(
+            |service_profile_id| {
+                Self::roaming_service_profile_network_server(service_profile_id) == Some(roaming_network_server_id)
+            },
+        )
     }
 
-    fn update_owner(to: &T::AccountId, roaming_service_profile_id: T::RoamingServiceProfileIndex) {
-        <RoamingServiceProfileOwners<T>>::insert(roaming_service_profile_id, to);
+    fn remove_children(roaming_network_server_id: T::RoamingNetworkServerIndex) -> DispatchResult {
+        let service_profile_ids =
+            <RoamingNetworkServerServiceProfiles<T>>::take(roaming_network_server_id).unwrap_or_default();
+        for service_profile_id in service_profile_ids {
+            if Self::roaming_service_profile_network_server(service_profile_id) == Some(roaming_network_server_id) {
+                Self::remove_roaming_service_profile(service_profile_id)?;
+            }
+        }
+        Ok(())
     }
 }
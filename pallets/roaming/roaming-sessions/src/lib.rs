@@ -6,26 +6,43 @@ use codec::{
 };
 use frame_support::{
     debug,
+    decl_error,
     decl_event,
     decl_module,
     decl_storage,
     ensure,
-    traits::{
-        Get,
-        Randomness,
+    storage::migration::{
+        get_storage_value,
+        take_storage_value,
     },
+    traits::Get,
+    transactional,
+    weights::Weight,
     Parameter,
 };
 use frame_system::ensure_signed;
-use sp_io::hashing::blake2_128;
+use sp_io::hashing::blake2_256;
 use sp_runtime::{
     traits::{
         AtLeast32Bit,
         Bounded,
         Member,
         One,
+        Zero,
     },
     DispatchError,
+    DispatchResult,
+};
+use roaming_operators::{
+    iter_double_map_keys_from,
+    RoamingAuthority,
+    RoamingDeviceEntity,
+    RoamingEntityChildren,
+    RoamingSessionEntity,
+};
+use roaming_registry::{
+    RoamingRegistry,
+    RoamingTradable,
 };
 use sp_std::prelude::*; // Imports Vec
 #[macro_use]
@@ -39,15 +56,66 @@ mod tests;
 
 /// The module's configuration trait.
 pub trait Config:
-    frame_system::Config + roaming_operators::Config + roaming_devices::Config + roaming_network_servers::Config
+    frame_system::Config
+    + roaming_operators::Config
+    + roaming_devices::Config
+    + roaming_network_servers::Config
+    + roaming_agreement_policies::Config
+    + roaming_network_profiles::Config
 {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
     type RoamingSessionIndex: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
+    /// The registry that holds the roaming sessions and their owners
+    type RoamingSessionRegistry: RoamingRegistry<Self::AccountId, Self::RoamingSessionIndex>;
+    /// The pallets that hold the children of roaming sessions
+    type RoamingSessionChildren: RoamingEntityChildren<RoamingSessionEntity, Self::RoamingSessionIndex>;
+    /// The maximum number of roaming sessions that may be closed on expiry in a single block
+    type MaxSessionExpiriesPerBlock: Get<u32>;
+    /// The maximum number of blocks after its expiry block that are searched for a block with capacity to close a
+    /// roaming session on expiry
+    type MaxSessionExpiryDelay: Get<u32>;
+    /// The maximum number of blocks that an accepted roaming session join request remains valid
+    type MaxSessionDuration: Get<Self::BlockNumber>;
+}
+
+/// Storage version of the roaming sessions pallet
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum Releases {
+    /// Join request and join accept blocks were supplied by the caller
+    V1_0_0,
+    /// Join request and join accept blocks are derived from the current block number
+    V2_0_0,
+    /// Each roaming session has an explicit status
+    V3_0_0,
+    /// The sessions of each roaming device are keys of a double map rather than a vector
+    V4_0_0,
+    /// Each accepted or active roaming session has a scheduled expiry
+    V5_0_0,
+}
+
+impl Default for Releases {
+    fn default() -> Self {
+        Releases::V1_0_0
+    }
 }
 
-#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+/// Status of a roaming session.
+/// Requested -> Accepted or Rejected, Accepted -> Active, Accepted or Active -> Terminated
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "std", derive(Debug))]
-pub struct RoamingSession(pub [u8; 16]);
+pub enum SessionStatus {
+    /// A join request has been set for the session
+    Requested,
+    /// The join request has been accepted by the network server
+    Accepted,
+    /// The join request has been rejected by the network server
+    Rejected,
+    /// The accepted session has been assigned to a device
+    Active,
+    /// The session has been terminated or has expired
+    Terminated,
+}
 
 #[cfg_attr(feature = "std", derive(Debug))]
 #[derive(Encode, Decode, Default, Clone, PartialEq)]
@@ -65,6 +133,15 @@ pub struct RoamingSessionJoinAccept<U, V> {
     session_join_request_accept_accepted_at_block: V,
 }
 
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+// Generic type parameters - RoamingNetworkServerIndex, BlockNumber
+pub struct RoamingSessionHandover<U, V> {
+    pub handover_from_network_server_id: U,
+    pub handover_to_network_server_id: U,
+    pub handover_at_block: V,
+}
+
 decl_event!(
     pub enum Event<T> where
         <T as frame_system::Config>::AccountId,
@@ -73,42 +150,96 @@ decl_event!(
         <T as roaming_network_servers::Config>::RoamingNetworkServerIndex,
         <T as frame_system::Config>::BlockNumber,
     {
-        /// A roaming session is created. (owner, roaming_session_id)
-        Created(AccountId, RoamingSessionIndex),
-        /// A roaming session is transferred. (from, to, roaming_session_id)
-        Transferred(AccountId, AccountId, RoamingSessionIndex),
         /// A roaming session join request requested
         RoamingSessionJoinRequestRequested(AccountId, RoamingSessionIndex, RoamingNetworkServerIndex, BlockNumber),
         /// A roaming session join request accepted
         RoamingSessionJoinRequestAccepted(AccountId, RoamingSessionIndex, BlockNumber, BlockNumber),
         /// A roaming session is assigned to a device. (owner of device, roaming_session_id, roaming_device_id)
         AssignedSessionToDevice(AccountId, RoamingSessionIndex, RoamingDeviceIndex),
+        /// A roaming session expired and was closed. (roaming_session_id, expired_at_block)
+        SessionExpired(RoamingSessionIndex, BlockNumber),
+        /// A roaming session join request rejected. (owner of network server, roaming_session_id)
+        RoamingSessionJoinRequestRejected(AccountId, RoamingSessionIndex),
+        /// A roaming session is active on its assigned device. (owner of device, roaming_session_id)
+        RoamingSessionActivated(AccountId, RoamingSessionIndex),
+        /// A roaming session is terminated. (terminated by, roaming_session_id)
+        RoamingSessionTerminated(AccountId, RoamingSessionIndex),
+        /// A roaming session is handed over to another network server.
+        /// (owner of network server, roaming_session_id, from network server, to network server, handover_at_block)
+        RoamingSessionHandedOver(AccountId, RoamingSessionIndex, RoamingNetworkServerIndex, RoamingNetworkServerIndex, BlockNumber),
+        /// A roaming session is removed. (owner, roaming_session_id)
+        Removed(AccountId, RoamingSessionIndex),
     }
 );
 
+decl_error! {
+    pub enum Error for Module<T: Config> {
+        /// The roaming session has been terminated and can no longer change status
+        SessionTerminated,
+        /// The roaming session join request has been rejected and can no longer change status
+        SessionRejected,
+        /// The roaming session is not in a status that allows the requested status change
+        InvalidSessionStatusTransition,
+        /// Only an active roaming session may be handed over
+        SessionNotActive,
+        /// The roaming session is already connected to the network server it is being handed over to
+        HandoverToSameNetworkServer,
+        /// The network server has not been assigned to a roaming network
+        NetworkServerWithoutNetwork,
+        /// The network of the network server is not covered by an unexpired roaming agreement policy
+        NoValidAgreementPolicy,
+        /// Neither network of the network servers has an unexpired "handover" roaming agreement policy whose agreement
+        /// with the other network is activated
+        NoHandoverAgreement,
+        /// A network profile of the network being joined does not allow any visiting devices
+        DeviceAccessNotAllowed,
+        /// The device is blacklisted by a network profile of the network being joined
+        DeviceBlacklisted,
+        /// The home network of the device is not whitelisted by a network profile of the network being joined
+        HomeNetworkNotWhitelisted,
+        /// The device is not the device that the roaming session join request was set for
+        DeviceNotInJoinRequest,
+        /// A subscription of the home network of the device to an accounting policy of the network being joined has
+        /// lapsed
+        RoamingSuspended,
+    }
+}
+
 // This module's storage items.
 decl_storage! {
     trait Store for Module<T: Config> as RoamingSessions {
-        /// Stores all the roaming sessions, key is the roaming session id / index
-        pub RoamingSessions get(fn roaming_session): map hasher(opaque_blake2_256) T::RoamingSessionIndex => Option<RoamingSession>;
-
-        /// Stores the total number of roaming sessions. i.e. the next roaming session index
-        pub RoamingSessionsCount get(fn roaming_sessions_count): T::RoamingSessionIndex;
-
-        /// Get roaming session owner
-        pub RoamingSessionOwners get(fn roaming_session_owner): map hasher(opaque_blake2_256) T::RoamingSessionIndex => Option<T::AccountId>;
-
         /// Get roaming session join requests
         pub RoamingSessionJoinRequests get(fn roaming_session_join_requests): map hasher(opaque_blake2_256) T::RoamingSessionIndex => Option<RoamingSessionJoinRequest<T::RoamingNetworkServerIndex, T::BlockNumber>>;
 
         /// Get roaming session join accepts
         pub RoamingSessionJoinAccepts get(fn roaming_session_join_accepts): map hasher(opaque_blake2_256) T::RoamingSessionIndex => Option<RoamingSessionJoinAccept<T::BlockNumber, T::BlockNumber>>;
 
+        /// Get roaming session join request device
+        pub RoamingSessionJoinRequestDevices get(fn roaming_session_join_request_device): map hasher(opaque_blake2_256) T::RoamingSessionIndex => Option<T::RoamingDeviceIndex>;
+
         /// Get roaming session device
         pub RoamingSessionDevices get(fn roaming_session_device): map hasher(opaque_blake2_256) T::RoamingSessionIndex => Option<T::RoamingDeviceIndex>;
 
-        /// Get roaming device sessions
-        pub RoamingDeviceSessions get(fn roaming_device_sessions): map hasher(opaque_blake2_256) T::RoamingDeviceIndex => Option<Vec<T::RoamingSessionIndex>>
+        /// The roaming sessions of each roaming device, stored as keys so that a session is added or removed
+        /// without reading the other sessions of the device
+        pub RoamingDeviceSessions: double_map hasher(blake2_128_concat) T::RoamingDeviceIndex, hasher(blake2_128_concat) T::RoamingSessionIndex => ();
+
+        /// Get the block at which a roaming session is scheduled to expire
+        pub RoamingSessionExpiryBlocks get(fn roaming_session_expiry_block): map hasher(opaque_blake2_256) T::RoamingSessionIndex => Option<T::BlockNumber>;
+
+        /// Get the roaming sessions scheduled to expire at a given block
+        pub RoamingSessionExpiries get(fn roaming_session_expiries): map hasher(opaque_blake2_256) T::BlockNumber => Vec<T::RoamingSessionIndex>;
+
+        /// Get the history of network server handovers of a roaming session. Packet bundles are billed by the network
+        /// server that received them, so the history records where the session was served rather than splitting usage.
+        pub RoamingSessionHandovers get(fn roaming_session_handovers): map hasher(opaque_blake2_256) T::RoamingSessionIndex => Vec<RoamingSessionHandover<T::RoamingNetworkServerIndex, T::BlockNumber>>;
+
+        /// Get roaming session status
+        pub RoamingSessionStatus get(fn roaming_session_status): map hasher(opaque_blake2_256) T::RoamingSessionIndex => Option<SessionStatus>;
+
+        /// Storage version of the pallet, used to determine which migrations to run on runtime upgrade. A new chain
+        /// starts at the latest version since it has no storage to migrate.
+        pub StorageVersion get(fn storage_version) build(|_| Releases::V5_0_0): Releases;
     }
 }
 
@@ -118,32 +249,52 @@ decl_module! {
     pub struct Module<T: Config> for enum Call where origin: T::Origin {
         fn deposit_event() = default;
 
-        /// Create a new roaming session
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn create(origin) {
-            let sender = ensure_signed(origin)?;
-            let roaming_session_id = Self::next_roaming_session_id()?;
+        type Error = Error<T>;
 
-            // Generate a random 128bit value
-            let unique_id = Self::random_value(&sender);
+        /// The maximum number of roaming sessions that may be closed on expiry in a single block
+        const MaxSessionExpiriesPerBlock: u32 = T::MaxSessionExpiriesPerBlock::get();
 
-            // Create and store roaming session
-            let roaming_session = RoamingSession(unique_id);
-            Self::insert_roaming_session(&sender, roaming_session_id, roaming_session);
+        /// The maximum number of blocks after its expiry block that a roaming session may be closed on expiry
+        const MaxSessionExpiryDelay: u32 = T::MaxSessionExpiryDelay::get();
 
-            Self::deposit_event(RawEvent::Created(sender, roaming_session_id));
-        }
+        /// The maximum number of blocks that an accepted roaming session join request remains valid
+        const MaxSessionDuration: T::BlockNumber = T::MaxSessionDuration::get();
 
-        /// Transfer a roaming session to new owner
-        #[weight = 10_000 + T::DbWeight::get().writes(1)]
-        pub fn transfer(origin, to: T::AccountId, roaming_session_id: T::RoamingSessionIndex) {
-            let sender = ensure_signed(origin)?;
+        fn on_runtime_upgrade() -> Weight {
+            let mut weight = T::DbWeight::get().reads(1);
+            if Self::storage_version() == Releases::V1_0_0 {
+                weight = weight.saturating_add(Self::migrate_to_server_derived_blocks());
+            }
+            if Self::storage_version() == Releases::V2_0_0 {
+                weight = weight.saturating_add(Self::migrate_to_session_statuses());
+            }
+            if Self::storage_version() == Releases::V3_0_0 {
+                weight = weight.saturating_add(Self::migrate_to_device_session_keys());
+            }
+            if Self::storage_version() == Releases::V4_0_0 {
+                weight = weight.saturating_add(Self::migrate_to_scheduled_session_expiries());
+            }
+            weight
+        }
 
-            ensure!(Self::roaming_session_owner(roaming_session_id) == Some(sender.clone()), "Only owner can transfer roaming session");
+        /// Close all roaming sessions that are scheduled to expire at the current block
+        fn on_initialize(current_block_number: T::BlockNumber) -> Weight {
+            let expired_sessions = <RoamingSessionExpiries<T>>::take(current_block_number);
+            let expired_sessions_count = expired_sessions.len() as Weight;
 
-            Self::update_owner(&to, roaming_session_id);
+            for roaming_session_id in expired_sessions {
+                debug::info!("Closing expired roaming session {:?}", roaming_session_id);
+                Self::close_roaming_session(roaming_session_id);
+                <RoamingSessionStatus<T>>::insert(roaming_session_id, SessionStatus::Terminated);
+                Self::deposit_event(RawEvent::SessionExpired(roaming_session_id, current_block_number));
+            }
 
-            Self::deposit_event(RawEvent::Transferred(sender, to, roaming_session_id));
+            // Closing each session reads its scheduled expiry and device, and writes them along with the sessions of
+            // its device and its status
+            (expired_sessions_count * 10_000).saturating_add(T::DbWeight::get().reads_writes(
+                1 + expired_sessions_count * 3,
+                1 + expired_sessions_count * 5,
+            ))
         }
 
         /// Set roaming session join request
@@ -152,8 +303,7 @@ decl_module! {
             origin,
             roaming_session_id: T::RoamingSessionIndex,
             _session_network_server_id: Option<T::RoamingNetworkServerIndex>,
-            // FIXME - we shouldn't be passing the requested_at timestamp as an argument, it should be generated from the current time within this function
-            _session_join_requested_at_block: Option<T::BlockNumber>,
+            roaming_device_id: T::RoamingDeviceIndex,
         ) {
             let sender = ensure_signed(origin)?;
 
@@ -162,25 +312,32 @@ decl_module! {
             ensure!(is_roaming_session, "RoamingSession does not exist");
 
             // Ensure that the caller is owner of the session join request they are trying to change
-            ensure!(Self::roaming_session_owner(roaming_session_id) == Some(sender.clone()), "Only owner can set join request for roaming session");
+            ensure!(T::RoamingSessionRegistry::owner_of(roaming_session_id) == Some(sender.clone()), "Only owner can set join request for roaming session");
 
             let session_network_server_id = match _session_network_server_id {
                 Some(value) => value,
                 None => Default::default() // Default
             };
-            let session_join_requested_at_block = match _session_join_requested_at_block {
-                Some(value) => value,
-                None => Default::default()
-            };
+            let session_join_requested_at_block = <frame_system::Module<T>>::block_number();
 
-            debug::info!("Checking that only the owner of the given network server id that the device is trying to connect to can set an associated roaming session join request");
-            // Ensure that the caller is owner of the network server id that the device is trying to connect to for the session join request
-            ensure!((<roaming_network_servers::Module<T>>::is_roaming_network_server_owner(
-                        session_network_server_id.clone(),
-                        sender.clone()
-                    )).is_ok(), "Only owner of the given network server id that the device is trying to connect to can set an associated roaming session join request"
+            debug::info!("Checking that only an account authorized for the given network server id that the device is trying to connect to can set an associated roaming session join request");
+            // Ensure that the caller is authorized for the network server id that the device is trying to connect to for the session join request
+            ensure!(
+                <roaming_network_servers::Module<T>>::is_authorized(session_network_server_id, &sender),
+                "Only an account authorized for the given network server id that the device is trying to connect to can set an associated roaming session join request"
             );
 
+            // Ensure that the given device id already exists
+            let is_roaming_device = <roaming_devices::Module<T>>
+                ::exists_roaming_device(roaming_device_id).is_ok();
+            ensure!(is_roaming_device, "RoamingDevice does not exist");
+
+            Self::ensure_device_may_join_network_server(roaming_device_id, session_network_server_id)?;
+            <roaming_devices::Module<T>>::ensure_roaming_allowed(roaming_device_id, Some(session_network_server_id))?;
+            Self::ensure_roaming_not_suspended(roaming_device_id, session_network_server_id)?;
+
+            Self::ensure_session_status_transition(roaming_session_id, SessionStatus::Requested)?;
+
             // Check if a roaming session join request already exists with the given roaming session id
             // to determine whether to insert new or mutate existing.
             if Self::has_value_for_session_join_request_index(roaming_session_id).is_ok() {
@@ -222,6 +379,9 @@ decl_module! {
                 }
             }
 
+            <RoamingSessionJoinRequestDevices<T>>::insert(roaming_session_id, roaming_device_id);
+            <RoamingSessionStatus<T>>::insert(roaming_session_id, SessionStatus::Requested);
+
             Self::deposit_event(RawEvent::RoamingSessionJoinRequestRequested(
                 sender,
                 roaming_session_id,
@@ -232,13 +392,12 @@ decl_module! {
 
         /// Set roaming session join accept
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        #[transactional]
         pub fn set_join_accept(
             origin,
             roaming_session_id: T::RoamingSessionIndex,
-            // FIXME - this may stay optional, but if it's not provided we need to set a default value for how long until a join accept expires
-            _session_join_request_accept_expiry: Option<T::BlockNumber>,
-            // FIXME - we shouldn't be passing the accepted_at timestamp as an argument, it should be generated from the current time within this function
-            _session_join_request_accept_accepted_at_block: Option<T::BlockNumber>,
+            // Number of blocks that the join accept remains valid for. Defaults to the maximum session duration
+            _session_duration: Option<T::BlockNumber>,
         ) -> Result<(), DispatchError> {
             let sender = ensure_signed(origin)?;
 
@@ -247,31 +406,33 @@ decl_module! {
             ensure!(is_roaming_session, "RoamingSession does not exist");
 
             // Ensure that the caller is owner of the session join accept they are trying to change
-            ensure!(Self::roaming_session_owner(roaming_session_id) == Some(sender.clone()), "Only owner can set join accept for roaming session");
+            ensure!(T::RoamingSessionRegistry::owner_of(roaming_session_id) == Some(sender.clone()), "Only owner can set join accept for roaming session");
 
             debug::info!("Get the network server id associated with the join request of the given session id");
             let session_join_request = Self::roaming_session_join_requests(roaming_session_id);
 
             if let Some(_session_join_request) = session_join_request {
-                // Ensure that the caller is owner of the network server id that the device is trying to connect to for the session join request
-                ensure!((<roaming_network_servers::Module<T>>::is_roaming_network_server_owner(
-                        _session_join_request.session_network_server_id.clone(),
-                        sender.clone()
-                    )).is_ok(), "Only owner of the given network server id that the device is trying to connect to can set an associated roaming session join accept"
+                // Ensure that the caller is authorized for the network server id that the device is trying to connect to for the session join request
+                ensure!(
+                    <roaming_network_servers::Module<T>>::is_authorized(_session_join_request.session_network_server_id, &sender),
+                    "Only an account authorized for the given network server id that the device is trying to connect to can set an associated roaming session join accept"
                 );
             } else {
                 // There must be a session join request associated with the session join accept
                 return Err(DispatchError::Other("RoamingSessionJoinRequest does not exist"));
             }
 
-            let session_join_request_accept_expiry = match _session_join_request_accept_expiry {
-                Some(value) => value,
-                None => Default::default() // Default
-            };
-            let session_join_request_accept_accepted_at_block = match _session_join_request_accept_accepted_at_block {
+            Self::ensure_session_status_transition(roaming_session_id, SessionStatus::Accepted)?;
+
+            let session_duration = match _session_duration {
                 Some(value) => value,
-                None => Default::default()
+                None => T::MaxSessionDuration::get()
             };
+            ensure!(!session_duration.is_zero(), "Session duration must be greater than zero");
+            ensure!(session_duration <= T::MaxSessionDuration::get(), "Session duration exceeds the maximum session duration");
+
+            let session_join_request_accept_accepted_at_block = <frame_system::Module<T>>::block_number();
+            let session_join_request_accept_expiry = session_join_request_accept_accepted_at_block + session_duration;
 
             // Check if a roaming session join accept already exists with the given roaming session id
             // to determine whether to insert new or mutate existing.
@@ -314,6 +475,9 @@ decl_module! {
                 }
             }
 
+            Self::schedule_session_expiry(roaming_session_id, session_join_request_accept_expiry)?;
+            <RoamingSessionStatus<T>>::insert(roaming_session_id, SessionStatus::Accepted);
+
             Self::deposit_event(RawEvent::RoamingSessionJoinRequestAccepted(
                 sender,
                 roaming_session_id,
@@ -325,6 +489,7 @@ decl_module! {
         }
 
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        #[transactional]
         pub fn assign_session_to_device(
             origin,
             roaming_session_id: T::RoamingSessionIndex,
@@ -332,32 +497,189 @@ decl_module! {
         ) {
             let sender = ensure_signed(origin)?;
 
+            // Ensure that the given session id already exists
+            ensure!(T::RoamingSessionRegistry::exists(roaming_session_id), "Invalid roaming_session_id");
+
             // Ensure that the given device id already exists
             let is_roaming_device = <roaming_devices::Module<T>>
                 ::exists_roaming_device(roaming_device_id).is_ok();
             ensure!(is_roaming_device, "RoamingDevice does not exist");
 
-            // Ensure that caller of the function is the owner of the device id to assign the session to
+            // Ensure that caller of the function is authorized for the device id to assign the session to
             ensure!(
-                <roaming_devices::Module<T>>::is_roaming_device_owner(roaming_device_id, sender.clone()).is_ok(),
-                "Only the roaming device owner can assign itself a roaming session"
+                <roaming_devices::Module<T>>::is_authorized(roaming_device_id, &sender),
+                "Only an account authorized for the roaming device can assign itself a roaming session"
             );
 
-            Self::associate_session_with_device(roaming_session_id, roaming_device_id)
-                .expect("Unable to associate session with device");
+            // Only a session whose join request has been accepted may be assigned to a device
+            Self::ensure_session_status_transition(roaming_session_id, SessionStatus::Active)?;
 
-            // Ensure that the given session id already exists
-            let roaming_session = Self::roaming_session(roaming_session_id);
-            ensure!(roaming_session.is_some(), "Invalid roaming_session_id");
+            // Only the device that the join request was checked against may be assigned the session
+            if let Some(join_request_device_id) = Self::roaming_session_join_request_device(roaming_session_id) {
+                ensure!(join_request_device_id == roaming_device_id, Error::<T>::DeviceNotInJoinRequest);
+            }
+
+            Self::associate_session_with_device(roaming_session_id, roaming_device_id)?;
 
             // Ensure that the session is not already owned by a different device
             // Unassign the session from any existing device since it may only be owned by one device
-            <RoamingSessionDevices<T>>::remove(roaming_session_id);
+            if let Some(previous_device_id) = <RoamingSessionDevices<T>>::take(roaming_session_id) {
+                if previous_device_id != roaming_device_id {
+                    <RoamingDeviceSessions<T>>::remove(previous_device_id, roaming_session_id);
+                }
+            }
 
             // Assign the session owner to the given device (even if already belongs to them)
             <RoamingSessionDevices<T>>::insert(roaming_session_id, roaming_device_id);
 
-            Self::deposit_event(RawEvent::AssignedSessionToDevice(sender, roaming_session_id, roaming_device_id));
+            Self::deposit_event(RawEvent::AssignedSessionToDevice(sender.clone(), roaming_session_id, roaming_device_id));
+
+            if Self::roaming_session_status(roaming_session_id) != Some(SessionStatus::Active) {
+                <RoamingSessionStatus<T>>::insert(roaming_session_id, SessionStatus::Active);
+                Self::deposit_event(RawEvent::RoamingSessionActivated(sender, roaming_session_id));
+            }
+        }
+
+        /// Reject a roaming session join request
+        #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        pub fn reject_join_request(origin, roaming_session_id: T::RoamingSessionIndex) -> Result<(), DispatchError> {
+            let sender = ensure_signed(origin)?;
+
+            // Ensure that the roaming session id whose join request we want to reject actually exists
+            let is_roaming_session = Self::exists_roaming_session(roaming_session_id).is_ok();
+            ensure!(is_roaming_session, "RoamingSession does not exist");
+
+            if let Some(_session_join_request) = Self::roaming_session_join_requests(roaming_session_id) {
+                // Ensure that the caller is authorized for the network server id that the device is trying to connect to for the session join request
+                ensure!(
+                    <roaming_network_servers::Module<T>>::is_authorized(_session_join_request.session_network_server_id, &sender),
+                    "Only an account authorized for the given network server id that the device is trying to connect to can reject an associated roaming session join request"
+                );
+            } else {
+                return Err(DispatchError::Other("RoamingSessionJoinRequest does not exist"));
+            }
+
+            Self::ensure_session_status_transition(roaming_session_id, SessionStatus::Rejected)?;
+
+            <RoamingSessionStatus<T>>::insert(roaming_session_id, SessionStatus::Rejected);
+
+            Self::deposit_event(RawEvent::RoamingSessionJoinRequestRejected(sender, roaming_session_id));
+
+            Ok(())
+        }
+
+        /// Hand over an active roaming session to another network server.
+        /// The networks of both network servers must be covered by an unexpired roaming agreement policy, and a
+        /// handover to a network server of another network requires an activated "handover" agreement between them.
+        #[weight = 10_000 + T::DbWeight::get().writes(2)]
+        pub fn handover_session(
+            origin,
+            roaming_session_id: T::RoamingSessionIndex,
+            to_network_server_id: T::RoamingNetworkServerIndex,
+        ) -> Result<(), DispatchError> {
+            let sender = ensure_signed(origin)?;
+
+            // Ensure that the roaming session id that we want to hand over actually exists
+            let is_roaming_session = Self::exists_roaming_session(roaming_session_id).is_ok();
+            ensure!(is_roaming_session, "RoamingSession does not exist");
+
+            // Ensure that the caller is owner of the session they are trying to hand over
+            ensure!(T::RoamingSessionRegistry::owner_of(roaming_session_id) == Some(sender.clone()), "Only owner can hand over roaming session");
+
+            // Ensure that the caller is authorized for the network server that the device is being handed over to
+            ensure!(
+                <roaming_network_servers::Module<T>>::is_authorized(to_network_server_id, &sender),
+                "Only an account authorized for the given network server id that the device is being handed over to can hand over the roaming session"
+            );
+
+            ensure!(Self::roaming_session_status(roaming_session_id) == Some(SessionStatus::Active), Error::<T>::SessionNotActive);
+
+            let from_network_server_id = match Self::roaming_session_join_requests(roaming_session_id) {
+                Some(_session_join_request) => _session_join_request.session_network_server_id,
+                None => return Err(DispatchError::Other("RoamingSessionJoinRequest does not exist")),
+            };
+            ensure!(from_network_server_id != to_network_server_id, Error::<T>::HandoverToSameNetworkServer);
+
+            Self::ensure_network_server_has_valid_agreement_policy(from_network_server_id)?;
+            Self::ensure_network_server_has_valid_agreement_policy(to_network_server_id)?;
+            Self::ensure_handover_agreement_between_network_servers(from_network_server_id, to_network_server_id)?;
+            if let Some(roaming_device_id) = Self::roaming_session_device(roaming_session_id) {
+                <roaming_devices::Module<T>>::ensure_roaming_allowed(roaming_device_id, Some(to_network_server_id))?;
+                Self::ensure_roaming_not_suspended(roaming_device_id, to_network_server_id)?;
+            }
+
+            let handover_at_block = <frame_system::Module<T>>::block_number();
+
+            // Subsequent network server ownership checks of the session apply to the network server it was handed over to
+            <RoamingSessionJoinRequests<T>>::mutate(roaming_session_id, |session_join_request| {
+                if let Some(_session_join_request) = session_join_request {
+                    _session_join_request.session_network_server_id = to_network_server_id;
+                }
+            });
+
+            <RoamingSessionHandovers<T>>::append(roaming_session_id, RoamingSessionHandover {
+                handover_from_network_server_id: from_network_server_id,
+                handover_to_network_server_id: to_network_server_id,
+                handover_at_block,
+            });
+
+            Self::deposit_event(RawEvent::RoamingSessionHandedOver(
+                sender,
+                roaming_session_id,
+                from_network_server_id,
+                to_network_server_id,
+                handover_at_block
+            ));
+
+            Ok(())
+        }
+
+        /// Terminate an accepted or active roaming session.
+        /// May be called by an account authorized for the session or for the network server it joined.
+        #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        pub fn terminate_session(origin, roaming_session_id: T::RoamingSessionIndex) -> Result<(), DispatchError> {
+            let sender = ensure_signed(origin)?;
+
+            // Ensure that the roaming session id that we want to terminate actually exists
+            let is_roaming_session = Self::exists_roaming_session(roaming_session_id).is_ok();
+            ensure!(is_roaming_session, "RoamingSession does not exist");
+
+            let is_session_authorized = Self::is_authorized(roaming_session_id, &sender);
+            let is_network_server_authorized = Self::roaming_session_join_requests(roaming_session_id)
+                .map(|_session_join_request| {
+                    <roaming_network_servers::Module<T>>::is_authorized(
+                        _session_join_request.session_network_server_id,
+                        &sender
+                    )
+                })
+                .unwrap_or(false);
+            ensure!(
+                is_session_authorized || is_network_server_authorized,
+                "Only an account authorized for the roaming session or its network server can terminate the roaming session"
+            );
+
+            Self::ensure_session_status_transition(roaming_session_id, SessionStatus::Terminated)?;
+
+            Self::close_roaming_session(roaming_session_id);
+            <RoamingSessionStatus<T>>::insert(roaming_session_id, SessionStatus::Terminated);
+
+            Self::deposit_event(RawEvent::RoamingSessionTerminated(sender, roaming_session_id));
+
+            Ok(())
+        }
+
+        /// Remove a roaming session after closing it. Its packet bundles are either removed with it or prevent its
+        /// removal, depending on the removal policy.
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(5, 11)]
+        #[transactional]
+        pub fn remove(origin, roaming_session_id: T::RoamingSessionIndex) {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(T::RoamingSessionRegistry::owner_of(roaming_session_id) == Some(sender.clone()), "Only owner can remove roaming session");
+
+            Self::remove_roaming_session(roaming_session_id)?;
+
+            Self::deposit_event(RawEvent::Removed(sender, roaming_session_id));
         }
     }
 }
@@ -368,17 +690,15 @@ impl<T: Config> Module<T> {
         sender: T::AccountId,
     ) -> Result<(), DispatchError> {
         ensure!(
-            Self::roaming_session_owner(&roaming_session_id).map(|owner| owner == sender).unwrap_or(false),
+            T::RoamingSessionRegistry::owner_of(roaming_session_id).map(|owner| owner == sender).unwrap_or(false),
             "Sender is not owner of RoamingSession"
         );
         Ok(())
     }
 
-    pub fn exists_roaming_session(roaming_session_id: T::RoamingSessionIndex) -> Result<RoamingSession, DispatchError> {
-        match Self::roaming_session(roaming_session_id) {
-            Some(roaming_session) => Ok(roaming_session),
-            None => Err(DispatchError::Other("RoamingSession does not exist")),
-        }
+    pub fn exists_roaming_session(roaming_session_id: T::RoamingSessionIndex) -> Result<(), DispatchError> {
+        ensure!(T::RoamingSessionRegistry::exists(roaming_session_id), "RoamingSession does not exist");
+        Ok(())
     }
 
     pub fn exists_roaming_session_join_request(
@@ -423,67 +743,434 @@ impl<T: Config> Module<T> {
         Err(DispatchError::Other("No value for session join accept"))
     }
 
-    /// Only push the session id onto the end of the vector if it does not already exist
+    /// Add the session id to the sessions of the device if it is not already one of them
     pub fn associate_session_with_device(
         roaming_session_id: T::RoamingSessionIndex,
         roaming_device_id: T::RoamingDeviceIndex,
     ) -> Result<(), DispatchError> {
-        // Early exit with error since do not want to append if the given device id already exists as a key,
-        // and where its corresponding value is a vector that already contains the given session id
-        if let Some(device_sessions) = Self::roaming_device_sessions(roaming_device_id) {
-            debug::info!("Device id key {:?} exists with value {:?}", roaming_device_id, device_sessions);
-            let not_device_contains_session = !device_sessions.contains(&roaming_session_id);
-            ensure!(not_device_contains_session, "Device already contains the given session id");
-            debug::info!("Device id key exists but its vector value does not contain the given session id");
-            <RoamingDeviceSessions<T>>::mutate(roaming_device_id, |v| {
-                if let Some(value) = v {
-                    value.push(roaming_session_id);
-                }
+        ensure!(
+            !<RoamingDeviceSessions<T>>::contains_key(roaming_device_id, roaming_session_id),
+            "Device already contains the given session id"
+        );
+        <RoamingDeviceSessions<T>>::insert(roaming_device_id, roaming_session_id, ());
+        debug::info!("Associated session {:?} with device {:?}", roaming_session_id, roaming_device_id);
+        Ok(())
+    }
+
+    /// Ensure that a roaming session may move from its current status to the given status
+    pub fn ensure_session_status_transition(
+        roaming_session_id: T::RoamingSessionIndex,
+        next_status: SessionStatus,
+    ) -> Result<(), Error<T>> {
+        let current_status = Self::roaming_session_status(roaming_session_id);
+        match (current_status, next_status) {
+            (Some(SessionStatus::Terminated), _) => Err(Error::<T>::SessionTerminated),
+            (Some(SessionStatus::Rejected), _) => Err(Error::<T>::SessionRejected),
+            (None, SessionStatus::Requested) |
+            (Some(SessionStatus::Requested), SessionStatus::Requested) |
+            (Some(SessionStatus::Requested), SessionStatus::Accepted) |
+            (Some(SessionStatus::Requested), SessionStatus::Rejected) |
+            (Some(SessionStatus::Accepted), SessionStatus::Accepted) |
+            (Some(SessionStatus::Accepted), SessionStatus::Active) |
+            (Some(SessionStatus::Accepted), SessionStatus::Terminated) |
+            (Some(SessionStatus::Active), SessionStatus::Active) |
+            (Some(SessionStatus::Active), SessionStatus::Terminated) => Ok(()),
+            _ => Err(Error::<T>::InvalidSessionStatusTransition),
+        }
+    }
+
+    /// Ensure that the network of the given network server is covered by an unexpired roaming agreement policy
+    pub fn ensure_network_server_has_valid_agreement_policy(
+        roaming_network_server_id: T::RoamingNetworkServerIndex,
+    ) -> Result<(), Error<T>> {
+        let roaming_network_id =
+            <roaming_network_servers::Module<T>>::roaming_network_server_network(roaming_network_server_id)
+                .ok_or(Error::<T>::NetworkServerWithoutNetwork)?;
+        <roaming_agreement_policies::Module<T>>::has_unexpired_agreement_policy_for_network(roaming_network_id)
+            .map_err(|_| Error::<T>::NoValidAgreementPolicy)
+    }
+
+    /// Ensure that the networks of the given network servers are the same network, or that either has an activated
+    /// "handover" agreement with the other
+    pub fn ensure_handover_agreement_between_network_servers(
+        from_network_server_id: T::RoamingNetworkServerIndex,
+        to_network_server_id: T::RoamingNetworkServerIndex,
+    ) -> Result<(), Error<T>> {
+        let from_network_id =
+            <roaming_network_servers::Module<T>>::roaming_network_server_network(from_network_server_id)
+                .ok_or(Error::<T>::NetworkServerWithoutNetwork)?;
+        let to_network_id = <roaming_network_servers::Module<T>>::roaming_network_server_network(to_network_server_id)
+            .ok_or(Error::<T>::NetworkServerWithoutNetwork)?;
+        ensure!(
+            from_network_id == to_network_id ||
+                <roaming_agreement_policies::Module<T>>::has_handover_agreement_between_networks(
+                    from_network_id,
+                    to_network_id
+                ),
+            Error::<T>::NoHandoverAgreement
+        );
+        Ok(())
+    }
+
+    /// Ensure that the network profiles of the network that the given network server belongs to allow the
+    /// given device to join. A device may not join if any of the network profiles disallow device access,
+    /// blacklist the device, or have whitelisted networks that do not include the home network of the device.
+    pub fn ensure_device_may_join_network_server(
+        roaming_device_id: T::RoamingDeviceIndex,
+        roaming_network_server_id: T::RoamingNetworkServerIndex,
+    ) -> Result<(), Error<T>> {
+        let roaming_network_id =
+            match <roaming_network_servers::Module<T>>::roaming_network_server_network(roaming_network_server_id) {
+                Some(value) => value,
+                // A network server without a network has no network profiles to enforce
+                None => return Ok(()),
+            };
+
+        // The home network of the device is the network of the network server the device belongs to
+        let device_home_network_id = <roaming_devices::Module<T>>::roaming_device_network_server(roaming_device_id)
+            .and_then(|home_network_server_id| {
+                <roaming_network_servers::Module<T>>::roaming_network_server_network(home_network_server_id)
             });
-            debug::info!("Associated session {:?} with device {:?}", roaming_session_id, roaming_device_id);
-            Ok(())
-        } else {
-            debug::info!(
-                "Device id key does not yet exist. Creating the device key {:?} and appending the session id {:?} to \
-                 its vector value",
-                roaming_device_id,
-                roaming_session_id
+
+        let network_profile_ids =
+            <roaming_network_profiles::Module<T>>::roaming_network_network_profiles(roaming_network_id)
+                .unwrap_or_default();
+        for roaming_network_profile_id in network_profile_ids {
+            let device_access_allowed =
+                <roaming_network_profiles::Module<T>>::roaming_network_profile_restricted_access(
+                    roaming_network_profile_id,
+                )
+                .unwrap_or(true);
+            ensure!(device_access_allowed, Error::<T>::DeviceAccessNotAllowed);
+
+            if let Some(blacklisted_devices) =
+                <roaming_network_profiles::Module<T>>::roaming_network_profile_blacklisted_devices(
+                    roaming_network_profile_id,
+                )
+            {
+                ensure!(!blacklisted_devices.contains(&roaming_device_id), Error::<T>::DeviceBlacklisted);
+            }
+
+            if let Some(whitelisted_networks) =
+                <roaming_network_profiles::Module<T>>::roaming_network_profile_whitelisted_networks(
+                    roaming_network_profile_id,
+                )
+            {
+                let is_home_network_whitelisted = whitelisted_networks.is_empty() ||
+                    device_home_network_id.map_or(false, |home_network_id| whitelisted_networks.contains(&home_network_id));
+                ensure!(is_home_network_whitelisted, Error::<T>::HomeNetworkNotWhitelisted);
+            }
+        }
+        Ok(())
+    }
+
+    /// The network server that a roaming session joined, or that it was last handed over to
+    pub fn session_network_server(roaming_session_id: T::RoamingSessionIndex) -> Option<T::RoamingNetworkServerIndex> {
+        Self::roaming_session_join_requests(roaming_session_id)
+            .map(|session_join_request| session_join_request.session_network_server_id)
+    }
+
+    /// Ensure that roaming from the home network of the given device to the network of the given network server has
+    /// not been suspended by a lapsed subscription to an accounting policy of that network
+    pub fn ensure_roaming_not_suspended(
+        roaming_device_id: T::RoamingDeviceIndex,
+        roaming_network_server_id: T::RoamingNetworkServerIndex,
+    ) -> Result<(), Error<T>> {
+        let roaming_network_id =
+            <roaming_network_servers::Module<T>>::roaming_network_server_network(roaming_network_server_id);
+        let device_home_network_id = <roaming_devices::Module<T>>::roaming_device_network_server(roaming_device_id)
+            .and_then(|home_network_server_id| {
+                <roaming_network_servers::Module<T>>::roaming_network_server_network(home_network_server_id)
+            });
+        if let (Some(home_network_id), Some(roaming_network_id)) = (device_home_network_id, roaming_network_id) {
+            ensure!(
+                !<roaming_accounting_policies::Module<T>>::is_roaming_suspended(home_network_id, roaming_network_id),
+                Error::<T>::RoamingSuspended
             );
-            <RoamingDeviceSessions<T>>::insert(roaming_device_id, &vec![roaming_session_id]);
-            Ok(())
         }
+        Ok(())
     }
 
-    fn random_value(sender: &T::AccountId) -> [u8; 16] {
-        let payload = (
-            T::Randomness::random(&[0]),
-            sender,
-            <frame_system::Module<T>>::extrinsic_index(),
-            <frame_system::Module<T>>::block_number(),
-        );
-        payload.using_encoded(blake2_128)
+    /// The number of roaming sessions, which this pallet held itself until the registry took them over
+    fn roaming_sessions_count() -> T::RoamingSessionIndex {
+        get_storage_value::<T::RoamingSessionIndex>(b"RoamingSessions", b"RoamingSessionsCount", &[])
+            .unwrap_or_else(T::RoamingSessionRegistry::entities_count)
     }
 
-    fn next_roaming_session_id() -> Result<T::RoamingSessionIndex, DispatchError> {
-        let roaming_session_id = Self::roaming_sessions_count();
-        if roaming_session_id == <T::RoamingSessionIndex as Bounded>::max_value() {
-            return Err(DispatchError::Other("RoamingSessions count overflow"));
+    /// Move the sessions of each roaming device from a vector into the keys of a double map, dropping any
+    /// session that has since been assigned to another device
+    fn migrate_to_device_session_keys() -> Weight {
+        let roaming_devices_count =
+            get_storage_value::<T::RoamingDeviceIndex>(b"RoamingDevices", b"RoamingDevicesCount", &[])
+                .unwrap_or_else(<T as roaming_devices::Config>::RoamingDeviceRegistry::entities_count);
+        let mut migrated_count: Weight = 0;
+        let mut session_count: Weight = 0;
+
+        let mut roaming_device_id: T::RoamingDeviceIndex = Zero::zero();
+        while roaming_device_id < roaming_devices_count {
+            let session_ids = take_storage_value::<Vec<T::RoamingSessionIndex>>(
+                b"RoamingSessions",
+                b"RoamingDeviceSessions",
+                &blake2_256(&roaming_device_id.encode()),
+            )
+            .unwrap_or_default();
+            for roaming_session_id in session_ids {
+                if Self::roaming_session_device(roaming_session_id) == Some(roaming_device_id) {
+                    <RoamingDeviceSessions<T>>::insert(roaming_device_id, roaming_session_id, ());
+                }
+                session_count += 1;
+            }
+
+            migrated_count += 1;
+            roaming_device_id = roaming_device_id + One::one();
         }
-        Ok(roaming_session_id)
+
+        StorageVersion::put(Releases::V4_0_0);
+        debug::info!("Migrated the sessions of {:?} roaming devices to double map keys", migrated_count);
+
+        T::DbWeight::get().reads_writes(2 + migrated_count + session_count, 1 + migrated_count + session_count)
     }
 
-    fn insert_roaming_session(
-        owner: &T::AccountId,
+    /// Schedule the expiry of each accepted or active roaming session that does not have one at the expiry of its
+    /// join accept, or at the next block if its join accept has already expired
+    fn migrate_to_scheduled_session_expiries() -> Weight {
+        let next_block_number = <frame_system::Module<T>>::block_number() + One::one();
+        let roaming_sessions_count = Self::roaming_sessions_count();
+        let mut migrated_count: Weight = 0;
+        let mut scheduled_count: Weight = 0;
+
+        let mut roaming_session_id: T::RoamingSessionIndex = Zero::zero();
+        while roaming_session_id < roaming_sessions_count {
+            let is_open = match Self::roaming_session_status(roaming_session_id) {
+                Some(SessionStatus::Accepted) | Some(SessionStatus::Active) => true,
+                _ => false,
+            };
+            if is_open && !<RoamingSessionExpiryBlocks<T>>::contains_key(roaming_session_id) {
+                if let Some(session_join_accept) = Self::roaming_session_join_accepts(roaming_session_id) {
+                    let expiry_block = session_join_accept.session_join_request_accept_expiry.max(next_block_number);
+                    match Self::schedule_session_expiry(roaming_session_id, expiry_block) {
+                        Ok(()) => scheduled_count += 1,
+                        Err(e) => {
+                            debug::info!(
+                                "Unable to schedule expiry of roaming session {:?}: {:?}",
+                                roaming_session_id,
+                                e
+                            )
+                        }
+                    }
+                }
+            }
+
+            migrated_count += 1;
+            roaming_session_id = roaming_session_id + One::one();
+        }
+
+        StorageVersion::put(Releases::V5_0_0);
+        debug::info!("Scheduled the expiries of {:?} roaming sessions", scheduled_count);
+
+        T::DbWeight::get().reads_writes(2 + migrated_count * 3 + scheduled_count, 1 + scheduled_count * 2)
+    }
+
+    /// Derive the status of each existing roaming session from its join request, join accept and device. Sessions
+    /// that already have a status keep it, since a status such as rejected or terminated cannot be derived.
+    fn migrate_to_session_statuses() -> Weight {
+        let roaming_sessions_count = Self::roaming_sessions_count();
+        let mut migrated_count: Weight = 0;
+
+        let mut roaming_session_id: T::RoamingSessionIndex = Zero::zero();
+        while roaming_session_id < roaming_sessions_count {
+            let session_status = if <RoamingSessionStatus<T>>::contains_key(roaming_session_id) {
+                None
+            } else if Self::roaming_session_device(roaming_session_id).is_some() &&
+                Self::exists_roaming_session_join_accept(roaming_session_id).is_ok()
+            {
+                Some(SessionStatus::Active)
+            } else if Self::exists_roaming_session_join_accept(roaming_session_id).is_ok() {
+                Some(SessionStatus::Accepted)
+            } else if Self::exists_roaming_session_join_request(roaming_session_id).is_ok() {
+                Some(SessionStatus::Requested)
+            } else {
+                None
+            };
+            if let Some(status) = session_status {
+                <RoamingSessionStatus<T>>::insert(roaming_session_id, status);
+            }
+
+            migrated_count += 1;
+            roaming_session_id = roaming_session_id + One::one();
+        }
+
+        StorageVersion::put(Releases::V3_0_0);
+        debug::info!("Migrated {:?} roaming sessions to explicit session statuses", migrated_count);
+
+        T::DbWeight::get().reads_writes(2 + migrated_count * 4, 1 + migrated_count)
+    }
+
+    /// Replace join request and join accept blocks that were previously supplied by callers with values
+    /// derived from the current block number, capping existing join accepts at the maximum session duration.
+    fn migrate_to_server_derived_blocks() -> Weight {
+        let current_block_number = <frame_system::Module<T>>::block_number();
+        let max_expiry_block = current_block_number + T::MaxSessionDuration::get();
+        let roaming_sessions_count = Self::roaming_sessions_count();
+        let mut migrated_count: Weight = 0;
+
+        // Roaming session ids are allocated sequentially, so iterate over them since the opaque hasher
+        // used by the join request and join accept maps does not allow their keys to be enumerated
+        let mut roaming_session_id: T::RoamingSessionIndex = Zero::zero();
+        while roaming_session_id < roaming_sessions_count {
+            <RoamingSessionJoinRequests<T>>::mutate(roaming_session_id, |session_join_request| {
+                if let Some(_session_join_request) = session_join_request {
+                    // Requested blocks in the future could only have been forged by the caller
+                    if _session_join_request.session_join_requested_at_block > current_block_number {
+                        _session_join_request.session_join_requested_at_block = current_block_number;
+                    }
+                }
+            });
+
+            let mut reschedule_expiry = false;
+            <RoamingSessionJoinAccepts<T>>::mutate(roaming_session_id, |session_join_accept| {
+                if let Some(_session_join_accept) = session_join_accept {
+                    if _session_join_accept.session_join_request_accept_accepted_at_block > current_block_number {
+                        _session_join_accept.session_join_request_accept_accepted_at_block = current_block_number;
+                    }
+                    if _session_join_accept.session_join_request_accept_expiry > max_expiry_block {
+                        _session_join_accept.session_join_request_accept_expiry = max_expiry_block;
+                        reschedule_expiry = true;
+                    }
+                }
+            });
+            if reschedule_expiry {
+                if let Err(e) = Self::schedule_session_expiry(roaming_session_id, max_expiry_block) {
+                    debug::info!("Unable to reschedule expiry of roaming session {:?}: {:?}", roaming_session_id, e);
+                }
+            }
+
+            migrated_count += 1;
+            roaming_session_id = roaming_session_id + One::one();
+        }
+
+        StorageVersion::put(Releases::V2_0_0);
+        debug::info!("Migrated {:?} roaming sessions to server derived join blocks", migrated_count);
+
+        T::DbWeight::get().reads_writes(2 + migrated_count * 3, 1 + migrated_count * 3)
+    }
+
+    /// Schedule a roaming session to be closed at the given expiry block, replacing any previously
+    /// scheduled expiry. If the block already has the maximum number of expiries scheduled then the
+    /// session is scheduled to expire at the next block that has capacity, searching no further than
+    /// the maximum session expiry delay.
+    pub fn schedule_session_expiry(
         roaming_session_id: T::RoamingSessionIndex,
-        roaming_session: RoamingSession,
-    ) {
-        // Create and store roaming session
-        <RoamingSessions<T>>::insert(roaming_session_id, roaming_session);
-        <RoamingSessionsCount<T>>::put(roaming_session_id + One::one());
-        <RoamingSessionOwners<T>>::insert(roaming_session_id, owner.clone());
+        expiry_block: T::BlockNumber,
+    ) -> DispatchResult {
+        Self::unschedule_session_expiry(roaming_session_id);
+
+        // An expiry that is not in the future cannot be processed by `on_initialize`
+        if expiry_block <= <frame_system::Module<T>>::block_number() {
+            debug::info!("Not scheduling expiry of roaming session {:?} since it is not in the future", roaming_session_id);
+            return Ok(());
+        }
+
+        let max_expiries_per_block = T::MaxSessionExpiriesPerBlock::get().max(1) as usize;
+        let mut scheduled_block = expiry_block;
+        let mut delay: u32 = 0;
+        while <RoamingSessionExpiries<T>>::decode_len(scheduled_block).unwrap_or(0) >= max_expiries_per_block {
+            ensure!(
+                delay < T::MaxSessionExpiryDelay::get(),
+                "No block has capacity to close the roaming session on expiry"
+            );
+            scheduled_block = scheduled_block + One::one();
+            delay += 1;
+        }
+
+        debug::info!("Scheduling expiry of roaming session {:?} at block {:?}", roaming_session_id, scheduled_block);
+        <RoamingSessionExpiries<T>>::append(scheduled_block, roaming_session_id);
+        <RoamingSessionExpiryBlocks<T>>::insert(roaming_session_id, scheduled_block);
+        Ok(())
+    }
+
+    /// Remove the scheduled expiry of a roaming session if it has one
+    pub fn unschedule_session_expiry(roaming_session_id: T::RoamingSessionIndex) {
+        if let Some(scheduled_block) = <RoamingSessionExpiryBlocks<T>>::take(roaming_session_id) {
+            <RoamingSessionExpiries<T>>::mutate_exists(scheduled_block, |expiries| {
+                if let Some(session_ids) = expiries.as_mut() {
+                    session_ids.retain(|id| *id != roaming_session_id);
+                }
+                if expiries.as_ref().map_or(false, |session_ids| session_ids.is_empty()) {
+                    *expiries = None;
+                }
+            });
+        }
+    }
+
+    /// Close a roaming session by removing its device associations and any scheduled expiry
+    pub fn close_roaming_session(roaming_session_id: T::RoamingSessionIndex) {
+        Self::unschedule_session_expiry(roaming_session_id);
+
+        if let Some(roaming_device_id) = <RoamingSessionDevices<T>>::take(roaming_session_id) {
+            <RoamingDeviceSessions<T>>::remove(roaming_device_id, roaming_session_id);
+        }
+    }
+
+    /// Remove a roaming session, cleaning up its children according to the removal policy
+    pub fn remove_roaming_session(roaming_session_id: T::RoamingSessionIndex) -> DispatchResult {
+        Self::exists_roaming_session(roaming_session_id)?;
+        T::RoamingSessionChildren::remove_children_with_policy(
+            <T as roaming_operators::Config>::RemovalPolicy::get(),
+            roaming_session_id,
+        )?;
+
+        Self::close_roaming_session(roaming_session_id);
+        T::RoamingSessionRegistry::deregister(roaming_session_id)?;
+        <RoamingSessionJoinRequests<T>>::remove(roaming_session_id);
+        <RoamingSessionJoinAccepts<T>>::remove(roaming_session_id);
+        <RoamingSessionJoinRequestDevices<T>>::remove(roaming_session_id);
+        <RoamingSessionHandovers<T>>::remove(roaming_session_id);
+        <RoamingSessionStatus<T>>::remove(roaming_session_id);
+        Ok(())
     }
 
-    fn update_owner(to: &T::AccountId, roaming_session_id: T::RoamingSessionIndex) {
-        <RoamingSessionOwners<T>>::insert(roaming_session_id, to);
+    /// The sessions of a roaming device in ascending order
+    pub fn device_sessions(roaming_device_id: T::RoamingDeviceIndex) -> Vec<T::RoamingSessionIndex> {
+        let mut session_ids: Vec<T::RoamingSessionIndex> =
+            <RoamingDeviceSessions<T>>::iter_prefix(roaming_device_id).map(|(session_id, _)| session_id).collect();
+        session_ids.sort();
+        session_ids
+    }
+
+    /// The sessions of a roaming device in the order they are stored, starting at the given session
+    pub fn iter_device_sessions_from(
+        roaming_device_id: T::RoamingDeviceIndex,
+        start: Option<T::RoamingSessionIndex>,
+    ) -> impl Iterator<Item = T::RoamingSessionIndex> {
+        iter_double_map_keys_from::<RoamingDeviceSessions<T>, _, _>(roaming_device_id, start)
+    }
+}
+
+impl<T: Config> RoamingAuthority<T::AccountId, T::RoamingSessionIndex> for Module<T> {
+    fn is_owner_or_admin(roaming_session_id: T::RoamingSessionIndex, account: &T::AccountId) -> bool {
+        T::RoamingSessionRegistry::is_owner_or_admin(roaming_session_id, account)
+    }
+
+    /// The owner and admins of a roaming session are authorized, as is any account authorized for its device
+    fn is_authorized(roaming_session_id: T::RoamingSessionIndex, account: &T::AccountId) -> bool {
+        Self::is_owner_or_admin(roaming_session_id, account) ||
+            Self::roaming_session_device(roaming_session_id)
+                .map_or(false, |parent_id| <roaming_devices::Module<T>>::is_authorized(parent_id, account))
+    }
+}
+
+/// The sessions of a roaming device
+impl<T: Config> RoamingEntityChildren<RoamingDeviceEntity, T::RoamingDeviceIndex> for Module<T> {
+    fn has_children(roaming_device_id: T::RoamingDeviceIndex) -> bool {
+        <RoamingDeviceSessions<T>>::iter_prefix(roaming_device_id).next().is_some()
+    }
+
+    fn remove_children(roaming_device_id: T::RoamingDeviceIndex) -> DispatchResult {
+        for session_id in Self::device_sessions(roaming_device_id) {
+            Self::remove_roaming_session(session_id)?;
+        }
+        <RoamingDeviceSessions<T>>::remove_prefix(roaming_device_id);
+        Ok(())
     }
 }
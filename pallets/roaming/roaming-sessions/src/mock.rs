@@ -3,6 +3,7 @@
 use crate::{
     Module,
     Config,
+    GenesisConfig,
 };
 
 use frame_support::{
@@ -87,34 +88,190 @@ impl pallet_transaction_payment::Config for Test {
     type TransactionByteFee = TransactionByteFee;
     type WeightToFee = IdentityFee<u64>;
 }
-impl roaming_operators::Config for Test {
+impl roaming_registry::Config<roaming_registry::Instance1> for Test {
     type Currency = Balances;
+    type EntityDeposit = ();
     type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
     type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+impl roaming_operators::Config for Test {
+    type Currency = Balances;
+    type Event = ();
+    type RemovalPolicy = ();
+    type RoamingOperatorChildren = ();
     type RoamingOperatorIndex = u64;
+    type RoamingOperatorRegistry = RoamingOperatorRegistry;
+}
+impl roaming_registry::Config<roaming_registry::Instance2> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
 }
 impl roaming_networks::Config for Test {
     type Event = ();
+    type RoamingNetworkChildren = ();
     type RoamingNetworkIndex = u64;
+    type RoamingNetworkRegistry = RoamingNetworkRegistry;
+}
+impl roaming_registry::Config<roaming_registry::Instance4> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
 }
 impl roaming_network_servers::Config for Test {
     type Event = ();
+    type RoamingNetworkServerChildren = ();
     type RoamingNetworkServerIndex = u64;
+    type RoamingNetworkServerRegistry = RoamingNetworkServerRegistry;
+}
+impl roaming_registry::Config<roaming_registry::Instance3> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
 }
 impl roaming_organizations::Config for Test {
     type Event = ();
+    type RoamingOrganizationChildren = ();
     type RoamingOrganizationIndex = u64;
+    type RoamingOrganizationRegistry = RoamingOrganizationRegistry;
+}
+impl roaming_registry::Config<roaming_registry::Instance5> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+parameter_types! {
+    pub const MaxRoamingPolicyNetworks: u32 = 2;
 }
 impl roaming_devices::Config for Test {
     type Event = ();
+    type MaxRoamingPolicyNetworks = MaxRoamingPolicyNetworks;
+    type RoamingDeviceChildren = ();
     type RoamingDeviceIndex = u64;
+    type RoamingDeviceRegistry = RoamingDeviceRegistry;
+}
+impl roaming_registry::Config<roaming_registry::Instance8> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+parameter_types! {
+    pub const MaxSubscriptionRenewalsPerBlock: u32 = 2;
+    pub const SubscriptionBillingPeriod: u64 = 10;
+}
+impl roaming_accounting_policies::Config for Test {
+    type Event = ();
+    type MaxSubscriptionRenewalsPerBlock = MaxSubscriptionRenewalsPerBlock;
+    type RoamingAccountingPolicyChildren = ();
+    type RoamingAccountingPolicyDownlinkFeeFactor = u32;
+    type RoamingAccountingPolicyIndex = u64;
+    type RoamingAccountingPolicyRegistry = RoamingAccountingPolicyRegistry;
+    type RoamingAccountingPolicyUplinkFeeFactor = u32;
+    type SubscriptionBillingPeriod = SubscriptionBillingPeriod;
+}
+impl roaming_registry::Config<roaming_registry::Instance9> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+impl roaming_agreement_policies::Config for Test {
+    type Currency = Balances;
+    type Event = ();
+    type RoamingAgreementPolicyActivationType = Vec<u8>;
+    type RoamingAgreementPolicyIndex = u64;
+    type RoamingAgreementPolicyRegistry = RoamingAgreementPolicyRegistry;
+    type RoamingAgreementSettlements = ();
+}
+impl roaming_registry::Config<roaming_registry::Instance10> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+impl roaming_network_profiles::Config for Test {
+    type Event = ();
+    type RoamingNetworkProfileIndex = u64;
+    type RoamingNetworkProfileRegistry = RoamingNetworkProfileRegistry;
+}
+parameter_types! {
+    pub const MaxSessionDuration: u64 = 100;
+    pub const MaxSessionExpiriesPerBlock: u32 = 2;
+    pub const MaxSessionExpiryDelay: u32 = 2;
+}
+impl roaming_registry::Config<roaming_registry::Instance12> for Test {
+    type Currency = Balances;
+    type EntityDeposit = ();
+    type Event = ();
+    type LegacyStorage = ();
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = ();
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
 }
 impl Config for Test {
     type Event = ();
+    type MaxSessionDuration = MaxSessionDuration;
+    type MaxSessionExpiriesPerBlock = MaxSessionExpiriesPerBlock;
+    type MaxSessionExpiryDelay = MaxSessionExpiryDelay;
+    type RoamingSessionChildren = ();
     type RoamingSessionIndex = u64;
+    type RoamingSessionRegistry = RoamingSessionRegistry;
 }
 
 pub type RoamingSessionModule = Module<Test>;
+pub type RoamingNetworkServerModule = roaming_network_servers::Module<Test>;
+pub type RoamingDeviceModule = roaming_devices::Module<Test>;
+pub type RoamingNetworkModule = roaming_networks::Module<Test>;
+pub type RoamingAccountingPolicyModule = roaming_accounting_policies::Module<Test>;
+pub type RoamingAgreementPolicyModule = roaming_agreement_policies::Module<Test>;
+pub type RoamingNetworkProfileModule = roaming_network_profiles::Module<Test>;
+pub type RoamingOperatorRegistry = roaming_registry::Module<Test, roaming_registry::Instance1>;
+pub type RoamingNetworkRegistry = roaming_registry::Module<Test, roaming_registry::Instance2>;
+pub type RoamingNetworkServerRegistry = roaming_registry::Module<Test, roaming_registry::Instance4>;
+pub type RoamingOrganizationRegistry = roaming_registry::Module<Test, roaming_registry::Instance3>;
+pub type RoamingDeviceRegistry = roaming_registry::Module<Test, roaming_registry::Instance5>;
+pub type RoamingAccountingPolicyRegistry = roaming_registry::Module<Test, roaming_registry::Instance8>;
+pub type RoamingAgreementPolicyRegistry = roaming_registry::Module<Test, roaming_registry::Instance9>;
+pub type RoamingNetworkProfileRegistry = roaming_registry::Module<Test, roaming_registry::Instance10>;
+pub type RoamingSessionRegistry = roaming_registry::Module<Test, roaming_registry::Instance12>;
 
 // This function basically just builds a genesis storage key/value store according to
 // our desired mockup.
@@ -125,6 +282,7 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
     }
     .assimilate_storage(&mut t)
     .unwrap();
+    GenesisConfig::default().assimilate_storage::<Test>(&mut t).unwrap();
     let mut ext = sp_io::TestExternalities::new(t);
     ext.execute_with(|| System::set_block_number(1));
     ext
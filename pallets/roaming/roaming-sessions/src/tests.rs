@@ -5,77 +5,646 @@ use crate::mock::*;
 use frame_support::{
     assert_noop,
     assert_ok,
+    storage::migration::put_storage_value,
+    traits::{
+        Get,
+        OnInitialize,
+        OnRuntimeUpgrade,
+    },
 };
+use sp_io::hashing::blake2_256;
 
 #[test]
 fn basic_setup_works() {
     new_test_ext().execute_with(|| {
         // Verify Initial Storage
-        assert_eq!(RoamingSessionModule::roaming_sessions_count(), 0);
-        assert!(RoamingSessionModule::roaming_session(0).is_none());
-        assert_eq!(RoamingSessionModule::roaming_session_owner(0), None);
+        assert_eq!(RoamingSessionRegistry::roaming_entities_count(), 0);
+        assert!(RoamingSessionRegistry::roaming_entity(0).is_none());
+        assert_eq!(RoamingSessionRegistry::roaming_entity_owner(0), None);
         assert_eq!(Balances::free_balance(1), 10);
         assert_eq!(Balances::free_balance(2), 20);
     });
 }
 
+fn setup_accepted_session_with_device(session_duration: u64) {
+    assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(1)));
+    assert_ok!(RoamingDeviceRegistry::create(Origin::signed(1)));
+    assert_ok!(RoamingSessionRegistry::create(Origin::signed(1)));
+    assert_ok!(RoamingSessionModule::set_join_request(Origin::signed(1), 0, Some(0), 0));
+    assert_ok!(RoamingSessionModule::set_join_accept(Origin::signed(1), 0, Some(session_duration)));
+    assert_ok!(RoamingSessionModule::assign_session_to_device(Origin::signed(1), 0, 0));
+}
+
+#[test]
+fn session_expires_on_initialize() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        setup_accepted_session_with_device(4);
+        assert_eq!(RoamingSessionModule::roaming_session_expiry_block(0), Some(5));
+        assert_eq!(RoamingSessionModule::roaming_session_expiries(5), vec![0]);
+        assert_eq!(RoamingSessionModule::roaming_session_device(0), Some(0));
+        // Call Functions
+        RoamingSessionModule::on_initialize(4);
+        assert_eq!(RoamingSessionModule::roaming_session_device(0), Some(0));
+        RoamingSessionModule::on_initialize(5);
+        // Verify Storage
+        assert!(RoamingSessionRegistry::roaming_entity(0).is_some());
+        assert_eq!(RoamingSessionModule::roaming_session_status(0), Some(SessionStatus::Terminated));
+        assert_eq!(RoamingSessionModule::roaming_session_device(0), None);
+        assert!(RoamingSessionModule::device_sessions(0).is_empty());
+        assert_eq!(RoamingSessionModule::roaming_session_expiry_block(0), None);
+        assert!(RoamingSessionModule::roaming_session_expiries(5).is_empty());
+    });
+}
+
+#[test]
+fn session_expiry_is_rescheduled_when_join_accept_changes() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        setup_accepted_session_with_device(4);
+        // Call Functions
+        assert_ok!(RoamingSessionModule::set_join_accept(Origin::signed(1), 0, Some(7)));
+        // Verify Storage
+        assert!(RoamingSessionModule::roaming_session_expiries(5).is_empty());
+        assert_eq!(RoamingSessionModule::roaming_session_expiries(8), vec![0]);
+        RoamingSessionModule::on_initialize(5);
+        assert_eq!(RoamingSessionModule::roaming_session_device(0), Some(0));
+        RoamingSessionModule::on_initialize(8);
+        assert_eq!(RoamingSessionModule::roaming_session_device(0), None);
+    });
+}
+
+#[test]
+fn session_expiries_are_bounded_per_block() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        for roaming_session_id in 0..3 {
+            assert_ok!(RoamingSessionRegistry::create(Origin::signed(1)));
+            assert_ok!(RoamingSessionModule::schedule_session_expiry(roaming_session_id, 5));
+        }
+        // Verify Storage
+        assert_eq!(RoamingSessionModule::roaming_session_expiries(5), vec![0, 1]);
+        assert_eq!(RoamingSessionModule::roaming_session_expiries(6), vec![2]);
+        assert_eq!(RoamingSessionModule::roaming_session_expiry_block(2), Some(6));
+    });
+}
+
+#[test]
+fn session_expiry_fails_when_no_block_within_the_maximum_delay_has_capacity() {
+    new_test_ext().execute_with(|| {
+        // Setup - blocks 5 to 7 are full
+        for roaming_session_id in 0..7 {
+            assert_ok!(RoamingSessionRegistry::create(Origin::signed(1)));
+            if roaming_session_id < 6 {
+                assert_ok!(RoamingSessionModule::schedule_session_expiry(roaming_session_id, 5));
+            }
+        }
+        assert_eq!(RoamingSessionModule::roaming_session_expiries(7), vec![4, 5]);
+        // Call Functions
+        assert_noop!(
+            RoamingSessionModule::schedule_session_expiry(6, 5),
+            "No block has capacity to close the roaming session on expiry"
+        );
+        assert_ok!(RoamingSessionModule::schedule_session_expiry(6, 6));
+        // Verify Storage
+        assert_eq!(RoamingSessionModule::roaming_session_expiry_block(6), Some(8));
+    });
+}
+
+#[test]
+fn migration_schedules_expiries_of_accepted_and_active_sessions() {
+    new_test_ext().execute_with(|| {
+        // Setup - session 0 is active, session 1 is accepted with an expired join accept and session 2 is rejected
+        for roaming_session_id in 0..3 {
+            assert_ok!(RoamingSessionRegistry::create(Origin::signed(1)));
+            <RoamingSessionJoinAccepts<Test>>::insert(roaming_session_id, RoamingSessionJoinAccept {
+                session_join_request_accept_expiry: 5,
+                session_join_request_accept_accepted_at_block: 1,
+            });
+        }
+        <RoamingSessionStatus<Test>>::insert(0, SessionStatus::Active);
+        <RoamingSessionStatus<Test>>::insert(1, SessionStatus::Accepted);
+        <RoamingSessionStatus<Test>>::insert(2, SessionStatus::Rejected);
+        System::set_block_number(6);
+        StorageVersion::put(Releases::V4_0_0);
+        // Call Functions
+        RoamingSessionModule::on_runtime_upgrade();
+        // Verify Storage
+        assert_eq!(RoamingSessionModule::storage_version(), Releases::V5_0_0);
+        assert_eq!(RoamingSessionModule::roaming_session_expiries(7), vec![0, 1]);
+        assert_eq!(RoamingSessionModule::roaming_session_expiry_block(2), None);
+        RoamingSessionModule::on_initialize(7);
+        assert_eq!(RoamingSessionModule::roaming_session_status(1), Some(SessionStatus::Terminated));
+    });
+}
+
+#[test]
+fn join_request_and_accept_blocks_are_derived_from_current_block() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingDeviceRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingSessionRegistry::create(Origin::signed(1)));
+        System::set_block_number(3);
+        // Call Functions
+        assert_ok!(RoamingSessionModule::set_join_request(Origin::signed(1), 0, Some(0), 0));
+        System::set_block_number(4);
+        assert_ok!(RoamingSessionModule::set_join_accept(Origin::signed(1), 0, Some(10)));
+        // Verify Storage
+        assert_eq!(
+            RoamingSessionModule::roaming_session_join_requests(0),
+            Some(RoamingSessionJoinRequest {
+                session_network_server_id: 0,
+                session_join_requested_at_block: 3,
+            })
+        );
+        assert_eq!(
+            RoamingSessionModule::roaming_session_join_accepts(0),
+            Some(RoamingSessionJoinAccept {
+                session_join_request_accept_expiry: 14,
+                session_join_request_accept_accepted_at_block: 4,
+            })
+        );
+        assert_eq!(RoamingSessionModule::roaming_session_expiry_block(0), Some(14));
+    });
+}
+
+#[test]
+fn set_join_accept_handles_session_duration_errors() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingDeviceRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingSessionRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingSessionModule::set_join_request(Origin::signed(1), 0, Some(0), 0));
+        // Call Functions
+        assert_noop!(
+            RoamingSessionModule::set_join_accept(Origin::signed(1), 0, Some(0)),
+            "Session duration must be greater than zero"
+        );
+        assert_noop!(
+            RoamingSessionModule::set_join_accept(Origin::signed(1), 0, Some(MaxSessionDuration::get() + 1)),
+            "Session duration exceeds the maximum session duration"
+        );
+        assert_ok!(RoamingSessionModule::set_join_accept(Origin::signed(1), 0, None));
+        // Verify Storage
+        assert_eq!(RoamingSessionModule::roaming_session_expiry_block(0), Some(1 + MaxSessionDuration::get()));
+    });
+}
+
+#[test]
+fn migration_replaces_caller_supplied_join_blocks() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        assert_ok!(RoamingSessionRegistry::create(Origin::signed(1)));
+        <RoamingSessionJoinRequests<Test>>::insert(0, RoamingSessionJoinRequest {
+            session_network_server_id: 0,
+            session_join_requested_at_block: 1_000,
+        });
+        <RoamingSessionJoinAccepts<Test>>::insert(0, RoamingSessionJoinAccept {
+            session_join_request_accept_expiry: 5_000,
+            session_join_request_accept_accepted_at_block: 1_000,
+        });
+        StorageVersion::put(Releases::V1_0_0);
+        // Call Functions
+        RoamingSessionModule::on_runtime_upgrade();
+        // Verify Storage
+        assert_eq!(RoamingSessionModule::storage_version(), Releases::V5_0_0);
+        assert_eq!(RoamingSessionModule::roaming_session_status(0), Some(SessionStatus::Accepted));
+        assert_eq!(
+            RoamingSessionModule::roaming_session_join_requests(0),
+            Some(RoamingSessionJoinRequest {
+                session_network_server_id: 0,
+                session_join_requested_at_block: 1,
+            })
+        );
+        assert_eq!(
+            RoamingSessionModule::roaming_session_join_accepts(0),
+            Some(RoamingSessionJoinAccept {
+                session_join_request_accept_expiry: 1 + MaxSessionDuration::get(),
+                session_join_request_accept_accepted_at_block: 1,
+            })
+        );
+        assert_eq!(RoamingSessionModule::roaming_session_expiry_block(0), Some(1 + MaxSessionDuration::get()));
+    });
+}
+
+#[test]
+fn migration_keeps_existing_session_statuses() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        assert_eq!(RoamingSessionModule::storage_version(), Releases::V5_0_0);
+        assert_ok!(RoamingSessionRegistry::create(Origin::signed(1)));
+        <RoamingSessionJoinRequests<Test>>::insert(0, RoamingSessionJoinRequest {
+            session_network_server_id: 0,
+            session_join_requested_at_block: 1,
+        });
+        <RoamingSessionStatus<Test>>::insert(0, SessionStatus::Rejected);
+        // Call Functions
+        RoamingSessionModule::on_runtime_upgrade();
+        StorageVersion::put(Releases::V2_0_0);
+        RoamingSessionModule::on_runtime_upgrade();
+        // Verify Storage
+        assert_eq!(RoamingSessionModule::storage_version(), Releases::V5_0_0);
+        assert_eq!(RoamingSessionModule::roaming_session_status(0), Some(SessionStatus::Rejected));
+    });
+}
+
+#[test]
+fn migration_moves_device_sessions_to_double_map_keys() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        for _ in 0..2 {
+            assert_ok!(RoamingDeviceRegistry::create(Origin::signed(1)));
+        }
+        for _ in 0..3 {
+            assert_ok!(RoamingSessionRegistry::create(Origin::signed(1)));
+        }
+        StorageVersion::put(Releases::V3_0_0);
+        // Session 1 has since been assigned from device 0 to device 1
+        <RoamingSessionDevices<Test>>::insert(0, 0);
+        <RoamingSessionDevices<Test>>::insert(1, 1);
+        <RoamingSessionDevices<Test>>::insert(2, 1);
+        put_storage_value(b"RoamingSessions", b"RoamingDeviceSessions", &blake2_256(&0u64.encode()), vec![0u64, 1]);
+        put_storage_value(b"RoamingSessions", b"RoamingDeviceSessions", &blake2_256(&1u64.encode()), vec![2u64, 1]);
+        // Call Functions
+        RoamingSessionModule::on_runtime_upgrade();
+        // Verify Storage
+        assert_eq!(RoamingSessionModule::storage_version(), Releases::V5_0_0);
+        assert_eq!(RoamingSessionModule::device_sessions(0), vec![0]);
+        assert_eq!(RoamingSessionModule::device_sessions(1), vec![1, 2]);
+        assert!(<RoamingDeviceSessions<Test>>::contains_key(1, 1));
+        assert!(!<RoamingDeviceSessions<Test>>::contains_key(0, 1));
+    });
+}
+
+#[test]
+fn session_status_transitions_work() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingDeviceRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingSessionRegistry::create(Origin::signed(1)));
+        assert_eq!(RoamingSessionModule::roaming_session_status(0), None);
+        // Call Functions
+        assert_ok!(RoamingSessionModule::set_join_request(Origin::signed(1), 0, Some(0), 0));
+        assert_eq!(RoamingSessionModule::roaming_session_status(0), Some(SessionStatus::Requested));
+        assert_ok!(RoamingSessionModule::set_join_accept(Origin::signed(1), 0, Some(10)));
+        assert_eq!(RoamingSessionModule::roaming_session_status(0), Some(SessionStatus::Accepted));
+        assert_ok!(RoamingSessionModule::assign_session_to_device(Origin::signed(1), 0, 0));
+        assert_eq!(RoamingSessionModule::roaming_session_status(0), Some(SessionStatus::Active));
+        assert_ok!(RoamingSessionModule::terminate_session(Origin::signed(1), 0));
+        // Verify Storage
+        assert_eq!(RoamingSessionModule::roaming_session_status(0), Some(SessionStatus::Terminated));
+        assert_eq!(RoamingSessionModule::roaming_session_device(0), None);
+        assert!(RoamingSessionModule::device_sessions(0).is_empty());
+        assert_eq!(RoamingSessionModule::roaming_session_expiry_block(0), None);
+    });
+}
+
 #[test]
-fn create_works() {
+fn reject_join_request_works() {
     new_test_ext().execute_with(|| {
+        // Setup
+        assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingDeviceRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingSessionRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingSessionModule::set_join_request(Origin::signed(1), 0, Some(0), 0));
         // Call Functions
-        assert_ok!(RoamingSessionModule::create(Origin::signed(1)));
+        assert_noop!(
+            RoamingSessionModule::reject_join_request(Origin::signed(2), 0),
+            "Only an account authorized for the given network server id that the device is trying to connect to can \
+             reject an associated roaming session join request"
+        );
+        assert_ok!(RoamingSessionModule::reject_join_request(Origin::signed(1), 0));
         // Verify Storage
-        assert_eq!(RoamingSessionModule::roaming_sessions_count(), 1);
-        assert!(RoamingSessionModule::roaming_session(0).is_some());
-        assert_eq!(RoamingSessionModule::roaming_session_owner(0), Some(1));
+        assert_eq!(RoamingSessionModule::roaming_session_status(0), Some(SessionStatus::Rejected));
+        assert_noop!(
+            RoamingSessionModule::set_join_accept(Origin::signed(1), 0, Some(10)),
+            Error::<Test>::SessionRejected
+        );
     });
 }
 
 #[test]
-fn create_handles_basic_errors() {
+fn operator_owner_manages_sessions_joining_its_network_servers() {
+    new_test_ext().execute_with(|| {
+        // Setup - network server 0 of network 0 of operator 0 is owned by account 3, not operator owner 1
+        assert_ok!(RoamingOperatorRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkRegistry::create(Origin::signed(2)));
+        assert_ok!(RoamingNetworkModule::assign_network_to_operator(Origin::signed(1), 0, 0));
+        assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(3)));
+        // The owner of network server 0 lets operator owner 1 assign it to network 0
+        assert_ok!(RoamingNetworkServerRegistry::grant_admin(Origin::signed(3), 0, 1));
+        assert_ok!(RoamingNetworkServerModule::assign_network_server_to_network(Origin::signed(1), 0, 0));
+        assert_ok!(RoamingNetworkServerRegistry::revoke_admin(Origin::signed(3), 0, 1));
+        assert_ok!(RoamingDeviceRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingSessionRegistry::create(Origin::signed(1)));
+        // Call Functions
+        assert_ok!(RoamingSessionModule::set_join_request(Origin::signed(1), 0, Some(0), 0));
+        assert_noop!(
+            RoamingSessionModule::reject_join_request(Origin::signed(4), 0),
+            "Only an account authorized for the given network server id that the device is trying to connect to can \
+             reject an associated roaming session join request"
+        );
+        assert_ok!(RoamingSessionModule::set_join_accept(Origin::signed(1), 0, Some(10)));
+        assert_ok!(RoamingSessionModule::terminate_session(Origin::signed(2), 0));
+        // Verify Storage
+        assert_eq!(RoamingSessionModule::roaming_session_status(0), Some(SessionStatus::Terminated));
+    });
+}
+
+#[test]
+fn session_status_transitions_handle_basic_errors() {
     new_test_ext().execute_with(|| {
         // Setup
-        <RoamingSessionsCount<Test>>::put(u64::max_value());
+        assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingDeviceRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingSessionRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingSessionModule::set_join_request(Origin::signed(1), 0, Some(0), 0));
         // Call Functions
-        assert_noop!(RoamingSessionModule::create(Origin::signed(1)), "RoamingSessions count overflow");
+        assert_noop!(
+            RoamingSessionModule::assign_session_to_device(Origin::signed(1), 1, 0),
+            "Invalid roaming_session_id"
+        );
+        assert_noop!(
+            RoamingSessionModule::assign_session_to_device(Origin::signed(1), 0, 0),
+            Error::<Test>::InvalidSessionStatusTransition
+        );
+        assert_noop!(
+            RoamingSessionModule::terminate_session(Origin::signed(1), 0),
+            Error::<Test>::InvalidSessionStatusTransition
+        );
+        assert_noop!(
+            RoamingSessionModule::terminate_session(Origin::signed(2), 0),
+            "Only an account authorized for the roaming session or its network server can terminate the roaming \
+             session"
+        );
+        assert_ok!(RoamingSessionModule::set_join_accept(Origin::signed(1), 0, Some(10)));
+        assert_ok!(RoamingSessionModule::terminate_session(Origin::signed(1), 0));
+        assert_noop!(
+            RoamingSessionModule::set_join_accept(Origin::signed(1), 0, Some(10)),
+            Error::<Test>::SessionTerminated
+        );
+        assert_noop!(
+            RoamingSessionModule::set_join_request(Origin::signed(1), 0, Some(0), 0),
+            Error::<Test>::SessionTerminated
+        );
         // Verify Storage
-        assert_eq!(RoamingSessionModule::roaming_sessions_count(), u64::max_value());
-        assert!(RoamingSessionModule::roaming_session(0).is_none());
-        assert_eq!(RoamingSessionModule::roaming_session_owner(0), None);
+        assert_eq!(RoamingSessionModule::roaming_session_status(0), Some(SessionStatus::Terminated));
     });
 }
 
+fn setup_network_server_with_agreement_policy(roaming_id: u64, policy_expiry_block: u64) {
+    assert_ok!(RoamingNetworkRegistry::create(Origin::signed(1)));
+    assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(1)));
+    assert_ok!(RoamingNetworkServerModule::assign_network_server_to_network(Origin::signed(1), roaming_id, roaming_id));
+    assert_ok!(RoamingAgreementPolicyRegistry::create(Origin::signed(1)));
+    assert_ok!(RoamingAgreementPolicyModule::set_config(
+        Origin::signed(1),
+        roaming_id,
+        Some(b"handover".to_vec()),
+        Some(policy_expiry_block)
+    ));
+    assert_ok!(RoamingAgreementPolicyModule::assign_agreement_policy_to_network(Origin::signed(1), roaming_id, roaming_id));
+}
+
 #[test]
-fn transfer_works() {
+fn handover_session_works() {
     new_test_ext().execute_with(|| {
         // Setup
-        assert_ok!(RoamingSessionModule::create(Origin::signed(1)));
+        setup_network_server_with_agreement_policy(0, 100);
+        setup_network_server_with_agreement_policy(1, 100);
+        assert_ok!(RoamingAgreementPolicyModule::activate_agreement(Origin::signed(1), 0, 1, 1));
+        assert_ok!(RoamingDeviceRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingSessionRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingSessionModule::set_join_request(Origin::signed(1), 0, Some(0), 0));
+        assert_ok!(RoamingSessionModule::set_join_accept(Origin::signed(1), 0, Some(10)));
+        assert_ok!(RoamingSessionModule::assign_session_to_device(Origin::signed(1), 0, 0));
+        System::set_block_number(3);
         // Call Functions
-        assert_ok!(RoamingSessionModule::transfer(Origin::signed(1), 2, 0));
+        assert_ok!(RoamingSessionModule::handover_session(Origin::signed(1), 0, 1));
         // Verify Storage
-        assert_eq!(RoamingSessionModule::roaming_sessions_count(), 1);
-        assert!(RoamingSessionModule::roaming_session(0).is_some());
-        assert_eq!(RoamingSessionModule::roaming_session_owner(0), Some(2));
+        assert_eq!(
+            RoamingSessionModule::roaming_session_handovers(0),
+            vec![RoamingSessionHandover {
+                handover_from_network_server_id: 0,
+                handover_to_network_server_id: 1,
+                handover_at_block: 3,
+            }]
+        );
+        assert_eq!(
+            RoamingSessionModule::roaming_session_join_requests(0).map(|request| request.session_network_server_id),
+            Some(1)
+        );
+        assert_eq!(RoamingSessionModule::roaming_session_status(0), Some(SessionStatus::Active));
     });
 }
 
 #[test]
-fn transfer_handles_basic_errors() {
+fn handover_session_handles_basic_errors() {
     new_test_ext().execute_with(|| {
         // Setup
-        assert_ok!(RoamingSessionModule::create(Origin::signed(1)));
+        setup_network_server_with_agreement_policy(0, 100);
+        setup_network_server_with_agreement_policy(1, 5);
+        assert_ok!(RoamingDeviceRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingSessionRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingSessionModule::set_join_request(Origin::signed(1), 0, Some(0), 0));
         // Call Functions
+        assert_noop!(RoamingSessionModule::handover_session(Origin::signed(1), 0, 1), Error::<Test>::SessionNotActive);
+        assert_ok!(RoamingSessionModule::set_join_accept(Origin::signed(1), 0, Some(10)));
+        assert_ok!(RoamingSessionModule::assign_session_to_device(Origin::signed(1), 0, 0));
+        assert_noop!(
+            RoamingSessionModule::handover_session(Origin::signed(1), 0, 0),
+            Error::<Test>::HandoverToSameNetworkServer
+        );
+        System::set_block_number(5);
         assert_noop!(
-            RoamingSessionModule::transfer(Origin::signed(2), 2, 0),
-            "Only owner can transfer roaming session"
+            RoamingSessionModule::handover_session(Origin::signed(1), 0, 1),
+            Error::<Test>::NoValidAgreementPolicy
         );
+        // Verify Storage
+        assert!(RoamingSessionModule::roaming_session_handovers(0).is_empty());
+    });
+}
+
+#[test]
+fn handover_session_requires_handover_agreement_between_networks() {
+    new_test_ext().execute_with(|| {
+        // Setup - network 0 has an activated "passive" agreement with network 1
+        setup_network_server_with_agreement_policy(0, 100);
+        setup_network_server_with_agreement_policy(1, 100);
+        assert_ok!(RoamingAgreementPolicyModule::set_config(
+            Origin::signed(1),
+            0,
+            Some(b"passive".to_vec()),
+            Some(100)
+        ));
+        assert_ok!(RoamingAgreementPolicyModule::activate_agreement(Origin::signed(1), 0, 1, 1));
+        assert_ok!(RoamingDeviceRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingSessionRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingSessionModule::set_join_request(Origin::signed(1), 0, Some(0), 0));
+        assert_ok!(RoamingSessionModule::set_join_accept(Origin::signed(1), 0, Some(10)));
+        assert_ok!(RoamingSessionModule::assign_session_to_device(Origin::signed(1), 0, 0));
+        // Call Functions
+        assert_noop!(
+            RoamingSessionModule::handover_session(Origin::signed(1), 0, 1),
+            Error::<Test>::NoHandoverAgreement
+        );
+        // The "handover" agreement of network 1 with network 0 covers handovers in either direction
+        assert_ok!(RoamingAgreementPolicyModule::activate_agreement(Origin::signed(1), 1, 0, 1));
+        assert_ok!(RoamingSessionModule::handover_session(Origin::signed(1), 0, 1));
+        // Verify Storage
+        assert_eq!(RoamingSessionModule::roaming_session_handovers(0).len(), 1);
+    });
+}
+
+#[test]
+fn set_join_request_enforces_network_profile_rules() {
+    new_test_ext().execute_with(|| {
+        // Setup - network server 0 belongs to visited network 0 and the device's home network server 1 to network 1
+        assert_ok!(RoamingNetworkRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkServerModule::assign_network_server_to_network(Origin::signed(1), 0, 0));
+        assert_ok!(RoamingNetworkServerModule::assign_network_server_to_network(Origin::signed(1), 1, 1));
+        assert_ok!(RoamingDeviceRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingDeviceModule::assign_device_to_network_server(Origin::signed(1), 0, 1));
+        assert_ok!(RoamingNetworkProfileRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkProfileModule::assign_network_profile_to_network(Origin::signed(1), 0, 0));
+        assert_ok!(RoamingSessionRegistry::create(Origin::signed(1)));
+        // Call Functions
+        assert_ok!(RoamingNetworkProfileModule::set_device_access_allowed(Origin::signed(1), 0, false));
+        assert_noop!(
+            RoamingSessionModule::set_join_request(Origin::signed(1), 0, Some(0), 0),
+            Error::<Test>::DeviceAccessNotAllowed
+        );
+        assert_ok!(RoamingNetworkProfileModule::set_device_access_allowed(Origin::signed(1), 0, true));
+
+        assert_ok!(RoamingNetworkProfileModule::add_blacklisted_device(Origin::signed(1), 0, 0));
+        assert_noop!(
+            RoamingSessionModule::set_join_request(Origin::signed(1), 0, Some(0), 0),
+            Error::<Test>::DeviceBlacklisted
+        );
+        assert_ok!(RoamingNetworkProfileModule::remove_blacklisted_device(Origin::signed(1), 0, 0));
+
+        assert_ok!(RoamingNetworkProfileModule::add_whitelisted_network(Origin::signed(1), 0, 0));
+        assert_noop!(
+            RoamingSessionModule::set_join_request(Origin::signed(1), 0, Some(0), 0),
+            Error::<Test>::HomeNetworkNotWhitelisted
+        );
+        assert_ok!(RoamingNetworkProfileModule::add_whitelisted_network(Origin::signed(1), 0, 1));
+        assert_ok!(RoamingSessionModule::set_join_request(Origin::signed(1), 0, Some(0), 0));
+        // Verify Storage
+        assert_eq!(RoamingSessionModule::roaming_session_join_request_device(0), Some(0));
+        assert_eq!(RoamingSessionModule::roaming_session_status(0), Some(SessionStatus::Requested));
+    });
+}
+
+#[test]
+fn set_join_request_is_rejected_while_roaming_is_suspended() {
+    new_test_ext().execute_with(|| {
+        // Setup - network server 0 belongs to visited network 0 and the device's home network server 1 to network 1
+        assert_ok!(RoamingNetworkRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkServerModule::assign_network_server_to_network(Origin::signed(1), 0, 0));
+        assert_ok!(RoamingNetworkServerModule::assign_network_server_to_network(Origin::signed(1), 1, 1));
+        assert_ok!(RoamingDeviceRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingDeviceModule::assign_device_to_network_server(Origin::signed(1), 0, 1));
+        assert_ok!(RoamingAccountingPolicyRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingAccountingPolicyModule::assign_accounting_policy_to_network(Origin::signed(1), 0, 0));
+        assert_ok!(RoamingSessionRegistry::create(Origin::signed(1)));
+        <roaming_accounting_policies::RoamingAccountingPolicySubscriptions<Test>>::insert(
+            0,
+            1,
+            roaming_accounting_policies::RoamingAccountingPolicySubscription {
+                subscription_status: roaming_accounting_policies::SubscriptionStatus::Lapsed,
+                subscription_started_at_block: 1,
+                subscription_paid_until_block: 1,
+            },
+        );
+        // Call Functions
+        assert_noop!(
+            RoamingSessionModule::set_join_request(Origin::signed(1), 0, Some(0), 0),
+            Error::<Test>::RoamingSuspended
+        );
+        <roaming_accounting_policies::RoamingAccountingPolicySubscriptions<Test>>::remove(0, 1);
+        assert_ok!(RoamingSessionModule::set_join_request(Origin::signed(1), 0, Some(0), 0));
+        // Verify Storage
+        assert_eq!(RoamingSessionModule::roaming_session_status(0), Some(SessionStatus::Requested));
+    });
+}
+
+#[test]
+fn set_join_request_follows_device_roaming_policy() {
+    new_test_ext().execute_with(|| {
+        // Setup - network server 0 belongs to visited network 0 and the device's home network server 1 to network 1
+        assert_ok!(RoamingNetworkRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingNetworkServerModule::assign_network_server_to_network(Origin::signed(1), 0, 0));
+        assert_ok!(RoamingNetworkServerModule::assign_network_server_to_network(Origin::signed(1), 1, 1));
+        assert_ok!(RoamingDeviceRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingDeviceModule::assign_device_to_network_server(Origin::signed(1), 0, 1));
+        assert_ok!(RoamingSessionRegistry::create(Origin::signed(1)));
+        // Call Functions
+        assert_ok!(RoamingDeviceModule::set_roaming_policy(
+            Origin::signed(1),
+            0,
+            Some(roaming_devices::RoamingDevicePolicy::Disabled)
+        ));
+        assert_noop!(
+            RoamingSessionModule::set_join_request(Origin::signed(1), 0, Some(1), 0),
+            roaming_devices::Error::<Test>::RoamingDisabled
+        );
+        assert_ok!(RoamingDeviceModule::set_roaming_policy(
+            Origin::signed(1),
+            0,
+            Some(roaming_devices::RoamingDevicePolicy::HomeOnly)
+        ));
+        assert_noop!(
+            RoamingSessionModule::set_join_request(Origin::signed(1), 0, Some(0), 0),
+            roaming_devices::Error::<Test>::RoamingRestrictedToHomeNetwork
+        );
+        assert_ok!(RoamingDeviceModule::set_roaming_policy(
+            Origin::signed(1),
+            0,
+            Some(roaming_devices::RoamingDevicePolicy::AllowedNetworks(vec![0]))
+        ));
+        assert_ok!(RoamingSessionModule::set_join_request(Origin::signed(1), 0, Some(0), 0));
+        // Verify Storage
+        assert_eq!(RoamingSessionModule::roaming_session_status(0), Some(SessionStatus::Requested));
+    });
+}
+
+#[test]
+fn handover_session_follows_device_roaming_policy() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        setup_network_server_with_agreement_policy(0, 100);
+        setup_network_server_with_agreement_policy(1, 100);
+        assert_ok!(RoamingAgreementPolicyModule::activate_agreement(Origin::signed(1), 0, 1, 1));
+        assert_ok!(RoamingDeviceRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingDeviceModule::assign_device_to_network_server(Origin::signed(1), 0, 0));
+        assert_ok!(RoamingSessionRegistry::create(Origin::signed(1)));
+        assert_ok!(RoamingSessionModule::set_join_request(Origin::signed(1), 0, Some(0), 0));
+        assert_ok!(RoamingSessionModule::set_join_accept(Origin::signed(1), 0, Some(10)));
+        assert_ok!(RoamingSessionModule::assign_session_to_device(Origin::signed(1), 0, 0));
+        assert_ok!(RoamingDeviceModule::set_roaming_policy(
+            Origin::signed(1),
+            0,
+            Some(roaming_devices::RoamingDevicePolicy::HomeOnly)
+        ));
+        // Call Functions
         assert_noop!(
-            RoamingSessionModule::transfer(Origin::signed(1), 2, 1),
-            "Only owner can transfer roaming session"
+            RoamingSessionModule::handover_session(Origin::signed(1), 0, 1),
+            roaming_devices::Error::<Test>::RoamingRestrictedToHomeNetwork
         );
         // Verify Storage
-        assert_eq!(RoamingSessionModule::roaming_sessions_count(), 1);
-        assert!(RoamingSessionModule::roaming_session(0).is_some());
-        assert_eq!(RoamingSessionModule::roaming_session_owner(0), Some(1));
+        assert!(RoamingSessionModule::roaming_session_handovers(0).is_empty());
     });
 }
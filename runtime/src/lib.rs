@@ -156,6 +156,7 @@ impl_opaque_keys! {
 
 /// Implementations of some helper traits passed into runtime modules as associated types.
 pub mod impls;
+
 pub use impls::Author;
 
 pub use module_primitives::{
@@ -178,6 +179,14 @@ pub use module_primitives::{
 	types::*,
 };
 use sp_runtime::generic::Era;
+use roaming_registry::LegacyRoamingEntityStorage;
+use roaming_runtime_api::{
+    RoamingNetworkServerSubtree,
+    RoamingNetworkSubtree,
+    RoamingOperatorSubtree,
+    RoamingPage,
+    MAX_PAGE_LIMIT,
+};
 
 // Make the WASM binary available.
 #[cfg(feature = "std")]
@@ -865,64 +874,428 @@ impl pallet_democracy::Config for Runtime {
     type MaxProposals = MaxProposals;
 }
 
-impl roaming_operators::Config for Runtime {
+parameter_types! {
+    pub const RemovalPolicy: roaming_operators::RoamingRemovalPolicy = roaming_operators::RoamingRemovalPolicy::Refuse;
+    // Three storage items per entity (the entity, its owner and its deposit); value size is 16+32+16 = 64 bytes.
+    pub const RoamingEntityDeposit: Balance = deposit(3, 64);
+    // Only operators, networks, organizations and network servers have metadata, which is charged per byte
+    pub const RoamingEntityMetadataDepositPerByte: Balance = deposit(0, 1);
+    pub const MaxRoamingEntityMetadataFieldLength: u32 = 128;
+    pub const MiningEntityDeposit: Balance = deposit(3, 64);
+}
+
+// The storage that each roaming pallet held its entities in before they moved to its instance of the registry
+parameter_types! {
+    pub const RoamingOperatorLegacyStorage: Option<LegacyRoamingEntityStorage> = Some(LegacyRoamingEntityStorage {
+        module: b"RoamingOperators",
+        entities: b"RoamingOperators",
+        count: b"RoamingOperatorsCount",
+        owners: b"RoamingOperatorOwners",
+        deposits: b"RoamingOperatorDeposits",
+        prices: Some(b"RoamingOperatorPrices"),
+        admins: Some(b"RoamingOperatorAdmins"),
+    });
+    pub const RoamingNetworkLegacyStorage: Option<LegacyRoamingEntityStorage> = Some(LegacyRoamingEntityStorage {
+        module: b"RoamingNetworks",
+        entities: b"RoamingNetworks",
+        count: b"RoamingNetworksCount",
+        owners: b"RoamingNetworkOwners",
+        deposits: b"RoamingNetworkDeposits",
+        prices: Some(b"RoamingNetworkPrices"),
+        admins: Some(b"RoamingNetworkAdmins"),
+    });
+    pub const RoamingOrganizationLegacyStorage: Option<LegacyRoamingEntityStorage> = Some(LegacyRoamingEntityStorage {
+        module: b"RoamingOrganizations",
+        entities: b"RoamingOrganizations",
+        count: b"RoamingOrganizationsCount",
+        owners: b"RoamingOrganizationOwners",
+        deposits: b"RoamingOrganizationDeposits",
+        prices: Some(b"RoamingOrganizationPrices"),
+        admins: Some(b"RoamingOrganizationAdmins"),
+    });
+    pub const RoamingNetworkServerLegacyStorage: Option<LegacyRoamingEntityStorage> = Some(LegacyRoamingEntityStorage {
+        module: b"RoamingNetworkServers",
+        entities: b"RoamingNetworkServers",
+        count: b"RoamingNetworkServersCount",
+        owners: b"RoamingNetworkServerOwners",
+        deposits: b"RoamingNetworkServerDeposits",
+        prices: Some(b"RoamingNetworkServerPrices"),
+        admins: Some(b"RoamingNetworkServerAdmins"),
+    });
+    pub const RoamingDeviceLegacyStorage: Option<LegacyRoamingEntityStorage> = Some(LegacyRoamingEntityStorage {
+        module: b"RoamingDevices",
+        entities: b"RoamingDevices",
+        count: b"RoamingDevicesCount",
+        owners: b"RoamingDeviceOwners",
+        deposits: b"RoamingDeviceDeposits",
+        prices: Some(b"RoamingDevicePrices"),
+        admins: Some(b"RoamingDeviceAdmins"),
+    });
+    pub const RoamingRoutingProfileLegacyStorage: Option<LegacyRoamingEntityStorage> = Some(LegacyRoamingEntityStorage {
+        module: b"RoamingRoutingProfiles",
+        entities: b"RoamingRoutingProfiles",
+        count: b"RoamingRoutingProfilesCount",
+        owners: b"RoamingRoutingProfileOwners",
+        deposits: b"RoamingRoutingProfileDeposits",
+        prices: None,
+        admins: None,
+    });
+    pub const RoamingServiceProfileLegacyStorage: Option<LegacyRoamingEntityStorage> = Some(LegacyRoamingEntityStorage {
+        module: b"RoamingServiceProfiles",
+        entities: b"RoamingServiceProfiles",
+        count: b"RoamingServiceProfilesCount",
+        owners: b"RoamingServiceProfileOwners",
+        deposits: b"RoamingServiceProfileDeposits",
+        prices: None,
+        admins: None,
+    });
+    pub const RoamingAccountingPolicyLegacyStorage: Option<LegacyRoamingEntityStorage> =
+        Some(LegacyRoamingEntityStorage {
+            module: b"RoamingAccountingPolicies",
+            entities: b"RoamingAccountingPolicies",
+            count: b"RoamingAccountingPoliciesCount",
+            owners: b"RoamingAccountingPolicyOwners",
+            deposits: b"RoamingAccountingPolicyDeposits",
+            prices: None,
+            admins: None,
+        });
+    pub const RoamingAgreementPolicyLegacyStorage: Option<LegacyRoamingEntityStorage> =
+        Some(LegacyRoamingEntityStorage {
+            module: b"RoamingAgreementPolicies",
+            entities: b"RoamingAgreementPolicies",
+            count: b"RoamingAgreementPoliciesCount",
+            owners: b"RoamingAgreementPolicyOwners",
+            deposits: b"RoamingAgreementPolicyDeposits",
+            prices: None,
+            admins: None,
+        });
+    pub const RoamingNetworkProfileLegacyStorage: Option<LegacyRoamingEntityStorage> = Some(LegacyRoamingEntityStorage {
+        module: b"RoamingNetworkProfiles",
+        entities: b"RoamingNetworkProfiles",
+        count: b"RoamingNetworkProfilesCount",
+        owners: b"RoamingNetworkProfileOwners",
+        deposits: b"RoamingNetworkProfileDeposits",
+        prices: None,
+        admins: None,
+    });
+    pub const RoamingDeviceProfileLegacyStorage: Option<LegacyRoamingEntityStorage> = Some(LegacyRoamingEntityStorage {
+        module: b"RoamingDeviceProfiles",
+        entities: b"RoamingDeviceProfiles",
+        count: b"RoamingDeviceProfilesCount",
+        owners: b"RoamingDeviceProfileOwners",
+        deposits: b"RoamingDeviceProfileDeposits",
+        prices: None,
+        admins: None,
+    });
+    pub const RoamingSessionLegacyStorage: Option<LegacyRoamingEntityStorage> = Some(LegacyRoamingEntityStorage {
+        module: b"RoamingSessions",
+        entities: b"RoamingSessions",
+        count: b"RoamingSessionsCount",
+        owners: b"RoamingSessionOwners",
+        deposits: b"RoamingSessionDeposits",
+        prices: None,
+        admins: None,
+    });
+    pub const RoamingBillingPolicyLegacyStorage: Option<LegacyRoamingEntityStorage> = Some(LegacyRoamingEntityStorage {
+        module: b"RoamingBillingPolicies",
+        entities: b"RoamingBillingPolicies",
+        count: b"RoamingBillingPoliciesCount",
+        owners: b"RoamingBillingPolicyOwners",
+        deposits: b"RoamingBillingPolicyDeposits",
+        prices: None,
+        admins: None,
+    });
+    pub const RoamingChargingPolicyLegacyStorage: Option<LegacyRoamingEntityStorage> = Some(LegacyRoamingEntityStorage {
+        module: b"RoamingChargingPolicies",
+        entities: b"RoamingChargingPolicies",
+        count: b"RoamingChargingPoliciesCount",
+        owners: b"RoamingChargingPolicyOwners",
+        deposits: b"RoamingChargingPolicyDeposits",
+        prices: None,
+        admins: None,
+    });
+    pub const RoamingPacketBundleLegacyStorage: Option<LegacyRoamingEntityStorage> = Some(LegacyRoamingEntityStorage {
+        module: b"RoamingPacketBundles",
+        entities: b"RoamingPacketBundles",
+        count: b"RoamingPacketBundlesCount",
+        owners: b"RoamingPacketBundleOwners",
+        deposits: b"RoamingPacketBundleDeposits",
+        prices: Some(b"RoamingPacketBundlePrices"),
+        admins: None,
+    });
+}
+
+impl roaming_registry::Config<roaming_registry::Instance1> for Runtime {
+    type Currency = Balances;
+    type EntityDeposit = RoamingEntityDeposit;
+    type Event = Event;
+    type LegacyStorage = RoamingOperatorLegacyStorage;
+    type MaxMetadataFieldLength = MaxRoamingEntityMetadataFieldLength;
+    type MetadataDepositPerByte = RoamingEntityMetadataDepositPerByte;
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+
+impl roaming_registry::Config<roaming_registry::Instance2> for Runtime {
+    type Currency = Balances;
+    type EntityDeposit = RoamingEntityDeposit;
+    type Event = Event;
+    type LegacyStorage = RoamingNetworkLegacyStorage;
+    type MaxMetadataFieldLength = MaxRoamingEntityMetadataFieldLength;
+    type MetadataDepositPerByte = RoamingEntityMetadataDepositPerByte;
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+
+impl roaming_registry::Config<roaming_registry::Instance3> for Runtime {
+    type Currency = Balances;
+    type EntityDeposit = RoamingEntityDeposit;
+    type Event = Event;
+    type LegacyStorage = RoamingOrganizationLegacyStorage;
+    type MaxMetadataFieldLength = MaxRoamingEntityMetadataFieldLength;
+    type MetadataDepositPerByte = RoamingEntityMetadataDepositPerByte;
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+
+impl roaming_registry::Config<roaming_registry::Instance4> for Runtime {
+    type Currency = Balances;
+    type EntityDeposit = RoamingEntityDeposit;
+    type Event = Event;
+    type LegacyStorage = RoamingNetworkServerLegacyStorage;
+    type MaxMetadataFieldLength = MaxRoamingEntityMetadataFieldLength;
+    type MetadataDepositPerByte = RoamingEntityMetadataDepositPerByte;
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+
+impl roaming_registry::Config<roaming_registry::Instance5> for Runtime {
+    type Currency = Balances;
+    type EntityDeposit = RoamingEntityDeposit;
+    type Event = Event;
+    type LegacyStorage = RoamingDeviceLegacyStorage;
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = RoamingEntityMetadataDepositPerByte;
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+
+impl roaming_registry::Config<roaming_registry::Instance6> for Runtime {
+    type Currency = Balances;
+    type EntityDeposit = RoamingEntityDeposit;
+    type Event = Event;
+    type LegacyStorage = RoamingRoutingProfileLegacyStorage;
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = RoamingEntityMetadataDepositPerByte;
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+
+impl roaming_registry::Config<roaming_registry::Instance7> for Runtime {
+    type Currency = Balances;
+    type EntityDeposit = RoamingEntityDeposit;
+    type Event = Event;
+    type LegacyStorage = RoamingServiceProfileLegacyStorage;
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = RoamingEntityMetadataDepositPerByte;
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+
+impl roaming_registry::Config<roaming_registry::Instance8> for Runtime {
+    type Currency = Balances;
+    type EntityDeposit = RoamingEntityDeposit;
+    type Event = Event;
+    type LegacyStorage = RoamingAccountingPolicyLegacyStorage;
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = RoamingEntityMetadataDepositPerByte;
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+
+impl roaming_registry::Config<roaming_registry::Instance9> for Runtime {
+    type Currency = Balances;
+    type EntityDeposit = RoamingEntityDeposit;
+    type Event = Event;
+    type LegacyStorage = RoamingAgreementPolicyLegacyStorage;
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = RoamingEntityMetadataDepositPerByte;
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+
+impl roaming_registry::Config<roaming_registry::Instance10> for Runtime {
+    type Currency = Balances;
+    type EntityDeposit = RoamingEntityDeposit;
+    type Event = Event;
+    type LegacyStorage = RoamingNetworkProfileLegacyStorage;
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = RoamingEntityMetadataDepositPerByte;
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+
+impl roaming_registry::Config<roaming_registry::Instance11> for Runtime {
     type Currency = Balances;
+    type EntityDeposit = RoamingEntityDeposit;
     type Event = Event;
+    type LegacyStorage = RoamingDeviceProfileLegacyStorage;
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = RoamingEntityMetadataDepositPerByte;
     type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+
+impl roaming_registry::Config<roaming_registry::Instance12> for Runtime {
+    type Currency = Balances;
+    type EntityDeposit = RoamingEntityDeposit;
+    type Event = Event;
+    type LegacyStorage = RoamingSessionLegacyStorage;
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = RoamingEntityMetadataDepositPerByte;
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+
+impl roaming_registry::Config<roaming_registry::Instance13> for Runtime {
+    type Currency = Balances;
+    type EntityDeposit = RoamingEntityDeposit;
+    type Event = Event;
+    type LegacyStorage = RoamingBillingPolicyLegacyStorage;
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = RoamingEntityMetadataDepositPerByte;
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+
+impl roaming_registry::Config<roaming_registry::Instance14> for Runtime {
+    type Currency = Balances;
+    type EntityDeposit = RoamingEntityDeposit;
+    type Event = Event;
+    type LegacyStorage = RoamingChargingPolicyLegacyStorage;
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = RoamingEntityMetadataDepositPerByte;
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+
+impl roaming_registry::Config<roaming_registry::Instance15> for Runtime {
+    type Currency = Balances;
+    type EntityDeposit = RoamingEntityDeposit;
+    type Event = Event;
+    type LegacyStorage = RoamingPacketBundleLegacyStorage;
+    type MaxMetadataFieldLength = ();
+    type MetadataDepositPerByte = RoamingEntityMetadataDepositPerByte;
+    type Randomness = RandomnessCollectiveFlip;
+    type RoamingEntityIndex = u64;
+}
+
+impl roaming_operators::Config for Runtime {
+    type Currency = Balances;
+    type Event = Event;
+    type RemovalPolicy = RemovalPolicy;
+    type RoamingOperatorChildren = (
+        RoamingNetworks,
+        RoamingNetworkServers,
+        RoamingBillingPolicies,
+        RoamingChargingPolicies,
+        RoamingNetworkProfiles,
+    );
     type RoamingOperatorIndex = u64;
+    type RoamingOperatorRegistry = RoamingOperatorRegistry;
 }
 
 impl roaming_networks::Config for Runtime {
     type Event = Event;
+    type RoamingNetworkChildren = (
+        RoamingNetworkServers,
+        RoamingAccountingPolicies,
+        RoamingAgreementPolicies,
+        RoamingBillingPolicies,
+        RoamingChargingPolicies,
+        RoamingNetworkProfiles,
+    );
     type RoamingNetworkIndex = u64;
+    type RoamingNetworkRegistry = RoamingNetworkRegistry;
 }
 
 impl roaming_organizations::Config for Runtime {
     type Event = Event;
+    type RoamingOrganizationChildren = RoamingDevices;
     type RoamingOrganizationIndex = u64;
+    type RoamingOrganizationRegistry = RoamingOrganizationRegistry;
 }
 
 impl roaming_network_servers::Config for Runtime {
     type Event = Event;
+    type RoamingNetworkServerChildren =
+        (RoamingOrganizations, RoamingDevices, RoamingServiceProfiles, RoamingPacketBundles);
     type RoamingNetworkServerIndex = u64;
+    type RoamingNetworkServerRegistry = RoamingNetworkServerRegistry;
+}
+
+parameter_types! {
+    pub const MaxRoamingDevicePolicyNetworks: u32 = 32;
 }
 
 impl roaming_devices::Config for Runtime {
     type Event = Event;
+    type MaxRoamingPolicyNetworks = MaxRoamingDevicePolicyNetworks;
+    type RoamingDeviceChildren = (RoamingDeviceProfiles, RoamingRoutingProfiles, RoamingSessions);
     type RoamingDeviceIndex = u64;
+    type RoamingDeviceRegistry = RoamingDeviceRegistry;
+}
+
+parameter_types! {
+    pub const MaxRoamingRoutingProfileEndpoints: u32 = 8;
+    pub const MaxRoamingRoutingProfileEndpointUriLength: u32 = 256;
 }
 
 impl roaming_routing_profiles::Config for Runtime {
     type Event = Event;
-    // https://polkadot.js.org/api/types/#primitive-types
-    type RoamingRoutingProfileAppServer = Vec<u8>;
+    type MaxEndpointUriLength = MaxRoamingRoutingProfileEndpointUriLength;
+    type MaxEndpoints = MaxRoamingRoutingProfileEndpoints;
     type RoamingRoutingProfileIndex = u64;
+    type RoamingRoutingProfileRegistry = RoamingRoutingProfileRegistry;
 }
 
 impl roaming_service_profiles::Config for Runtime {
     type Event = Event;
     type RoamingServiceProfileDownlinkRate = u32;
     type RoamingServiceProfileIndex = u64;
+    type RoamingServiceProfileRegistry = RoamingServiceProfileRegistry;
     type RoamingServiceProfileUplinkRate = u32;
 }
 
+parameter_types! {
+    pub const MaxSubscriptionRenewalsPerBlock: u32 = 100;
+    pub const SubscriptionBillingPeriod: BlockNumber = 30 * DAYS;
+}
+
 impl roaming_accounting_policies::Config for Runtime {
     type Event = Event;
+    type MaxSubscriptionRenewalsPerBlock = MaxSubscriptionRenewalsPerBlock;
+    type RoamingAccountingPolicyChildren = RoamingAgreementPolicies;
     type RoamingAccountingPolicyDownlinkFeeFactor = u32;
     type RoamingAccountingPolicyIndex = u64;
-    type RoamingAccountingPolicyType = Vec<u8>;
+    type RoamingAccountingPolicyRegistry = RoamingAccountingPolicyRegistry;
     type RoamingAccountingPolicyUplinkFeeFactor = u32;
+    type SubscriptionBillingPeriod = SubscriptionBillingPeriod;
 }
 
 impl roaming_agreement_policies::Config for Runtime {
+    type Currency = Balances;
     type Event = Event;
     type RoamingAgreementPolicyActivationType = Vec<u8>;
     type RoamingAgreementPolicyIndex = u64; // <pallet_timestamp::Module<Runtime> as Config>::Moment` timestamp::Module<Runtime>::Moment;
+    type RoamingAgreementPolicyRegistry = RoamingAgreementPolicyRegistry;
+    type RoamingAgreementSettlements = RoamingPacketBundles;
 }
 
 impl roaming_network_profiles::Config for Runtime {
     type Event = Event;
     type RoamingNetworkProfileIndex = u64;
+    type RoamingNetworkProfileRegistry = RoamingNetworkProfileRegistry;
 }
 
 impl roaming_device_profiles::Config for Runtime {
@@ -931,46 +1304,173 @@ impl roaming_device_profiles::Config for Runtime {
     type RoamingDeviceProfileDevEUI = Vec<u8>;
     type RoamingDeviceProfileIndex = u64;
     type RoamingDeviceProfileJoinEUI = Vec<u8>;
+    type RoamingDeviceProfileRegistry = RoamingDeviceProfileRegistry;
     type RoamingDeviceProfileVendorID = Vec<u8>;
 }
 
+parameter_types! {
+    pub const MaxSessionDuration: BlockNumber = 7 * DAYS;
+    pub const MaxSessionExpiriesPerBlock: u32 = 100;
+    pub const MaxSessionExpiryDelay: u32 = 1_000;
+}
+
 impl roaming_sessions::Config for Runtime {
     type Event = Event;
+    type MaxSessionDuration = MaxSessionDuration;
+    type MaxSessionExpiriesPerBlock = MaxSessionExpiriesPerBlock;
+    type MaxSessionExpiryDelay = MaxSessionExpiryDelay;
+    type RoamingSessionChildren = RoamingPacketBundles;
     type RoamingSessionIndex = u64;
+    type RoamingSessionRegistry = RoamingSessionRegistry;
+}
+
+parameter_types! {
+    pub const MaxBillingPoliciesPerBlock: u32 = 50;
 }
 
 impl roaming_billing_policies::Config for Runtime {
     type Event = Event;
+    type MaxBillingPoliciesPerBlock = MaxBillingPoliciesPerBlock;
     type RoamingBillingPolicyIndex = u64;
+    type RoamingBillingPolicyRegistry = RoamingBillingPolicyRegistry;
 }
 
 impl roaming_charging_policies::Config for Runtime {
     type Event = Event;
     type RoamingChargingPolicyIndex = u64;
+    type RoamingChargingPolicyRegistry = RoamingChargingPolicyRegistry;
+}
+
+parameter_types! {
+    pub const PacketBundleSettlementPeriod: BlockNumber = 1 * DAYS;
+    pub const PacketCountTolerance: Permill = Permill::from_percent(2);
+    pub const PacketCountChallengeBond: Balance = 10 * DOLLARS;
+    // Uplink rates of service profiles are given in packets per hour
+    pub const RateLimitPeriod: BlockNumber = 1 * HOURS;
+    pub const RateOveragePenaltyMultiplier: u32 = 2;
 }
 
 impl roaming_packet_bundles::Config for Runtime {
     type Event = Event;
+    type PacketBundleSettlementPeriod = PacketBundleSettlementPeriod;
+    type PacketCountArbitrationOrigin = EnsureRootOrHalfCouncil;
+    type PacketCountChallengeBond = PacketCountChallengeBond;
+    type PacketCountTolerance = PacketCountTolerance;
+    type RateLimitPeriod = RateLimitPeriod;
+    type RateOveragePenaltyMultiplier = RateOveragePenaltyMultiplier;
+    type RoamingPacketBundleBilling = RoamingInvoices;
     type RoamingPacketBundleExternalDataStorageHash = Hash;
     type RoamingPacketBundleIndex = u64;
     type RoamingPacketBundleReceivedAtHome = bool;
     type RoamingPacketBundleReceivedPacketsCount = u64;
     type RoamingPacketBundleReceivedPacketsOkCount = u64;
+    type RoamingPacketBundleRegistry = RoamingPacketBundleRegistry;
+}
+
+parameter_types! {
+    pub const InvoiceDisputeWindow: BlockNumber = 3 * DAYS;
+    pub const InvoiceDisputeResolutionPeriod: BlockNumber = 7 * DAYS;
+    pub const MaxBilledPacketBundlesPerBlock: u32 = 500;
+    pub const MaxInvoiceChargesPerBlock: u32 = 50;
+}
+
+impl roaming_invoices::Config for Runtime {
+    type Event = Event;
+    type InvoiceArbitrationOrigin = EnsureRootOrHalfCouncil;
+    type InvoiceDisputeResolutionPeriod = InvoiceDisputeResolutionPeriod;
+    type InvoiceDisputeWindow = InvoiceDisputeWindow;
+    type MaxBilledPacketBundlesPerBlock = MaxBilledPacketBundlesPerBlock;
+    type MaxInvoiceChargesPerBlock = MaxInvoiceChargesPerBlock;
+    type RoamingInvoiceIndex = u64;
+}
+
+parameter_types! {
+    pub const MarketplaceFee: Permill = Permill::from_percent(2);
+    pub const MinAuctionDuration: BlockNumber = 1 * HOURS;
+    pub const MaxAuctionsEndingPerBlock: u32 = 50;
+}
+
+impl roaming_marketplace::Config<roaming_marketplace::Instance1> for Runtime {
+    type Currency = Balances;
+    type Event = Event;
+    type MarketplaceFee = MarketplaceFee;
+    type MarketplaceFeeDestination = Treasury;
+    type MaxAuctionsEndingPerBlock = MaxAuctionsEndingPerBlock;
+    type MinAuctionDuration = MinAuctionDuration;
+    type RoamingEntities = RoamingOperatorRegistry;
+    type RoamingEntityIndex = u64;
+}
+
+impl roaming_marketplace::Config<roaming_marketplace::Instance2> for Runtime {
+    type Currency = Balances;
+    type Event = Event;
+    type MarketplaceFee = MarketplaceFee;
+    type MarketplaceFeeDestination = Treasury;
+    type MaxAuctionsEndingPerBlock = MaxAuctionsEndingPerBlock;
+    type MinAuctionDuration = MinAuctionDuration;
+    type RoamingEntities = RoamingNetworkRegistry;
+    type RoamingEntityIndex = u64;
+}
+
+impl roaming_marketplace::Config<roaming_marketplace::Instance3> for Runtime {
+    type Currency = Balances;
+    type Event = Event;
+    type MarketplaceFee = MarketplaceFee;
+    type MarketplaceFeeDestination = Treasury;
+    type MaxAuctionsEndingPerBlock = MaxAuctionsEndingPerBlock;
+    type MinAuctionDuration = MinAuctionDuration;
+    type RoamingEntities = RoamingOrganizationRegistry;
+    type RoamingEntityIndex = u64;
+}
+
+impl roaming_marketplace::Config<roaming_marketplace::Instance4> for Runtime {
+    type Currency = Balances;
+    type Event = Event;
+    type MarketplaceFee = MarketplaceFee;
+    type MarketplaceFeeDestination = Treasury;
+    type MaxAuctionsEndingPerBlock = MaxAuctionsEndingPerBlock;
+    type MinAuctionDuration = MinAuctionDuration;
+    type RoamingEntities = RoamingNetworkServerRegistry;
+    type RoamingEntityIndex = u64;
+}
+
+impl roaming_marketplace::Config<roaming_marketplace::Instance5> for Runtime {
+    type Currency = Balances;
+    type Event = Event;
+    type MarketplaceFee = MarketplaceFee;
+    type MarketplaceFeeDestination = Treasury;
+    type MaxAuctionsEndingPerBlock = MaxAuctionsEndingPerBlock;
+    type MinAuctionDuration = MinAuctionDuration;
+    type RoamingEntities = RoamingDeviceRegistry;
+    type RoamingEntityIndex = u64;
+}
+
+impl roaming_marketplace::Config<roaming_marketplace::Instance6> for Runtime {
+    type Currency = Balances;
+    type Event = Event;
+    type MarketplaceFee = MarketplaceFee;
+    type MarketplaceFeeDestination = Treasury;
+    type MaxAuctionsEndingPerBlock = MaxAuctionsEndingPerBlock;
+    type MinAuctionDuration = MinAuctionDuration;
+    type RoamingEntities = RoamingPacketBundleRegistry;
+    type RoamingEntityIndex = u64;
 }
 
 impl mining_setting_token::Config for Runtime {
+    type EntityDeposit = MiningEntityDeposit;
     type Event = Event;
     // FIXME - restore when stop temporarily using roaming-operators
     // type Currency = Balances;
-    // type Randomness = RandomnessCollectiveFlip;
     type MiningSettingTokenIndex = u64;
     type MiningSettingTokenLockAmount = u64;
     // Mining Speed Boost Token Mining Config
     // FIXME - how to use this enum from std? (including importing `use std::str::FromStr;`)
     type MiningSettingTokenType = Vec<u8>;
+    type Randomness = RandomnessCollectiveFlip;
 }
 
 impl mining_setting_hardware::Config for Runtime {
+    type EntityDeposit = MiningEntityDeposit;
     type Event = Event;
     type MiningSettingHardwareDevEUI = u64;
     // type MiningSettingHardwareType =
@@ -978,15 +1478,16 @@ impl mining_setting_hardware::Config for Runtime {
     type MiningSettingHardwareID = u64;
     // FIXME - restore when stop temporarily using roaming-operators
     // type Currency = Balances;
-    // type Randomness = RandomnessCollectiveFlip;
     type MiningSettingHardwareIndex = u64;
     // Mining Speed Boost Hardware Mining Config
     type MiningSettingHardwareSecure = bool;
     // FIXME - how to use this enum from std? (including importing `use std::str::FromStr;`)
     type MiningSettingHardwareType = Vec<u8>;
+    type Randomness = RandomnessCollectiveFlip;
 }
 
 impl mining_rates_token::Config for Runtime {
+    type EntityDeposit = MiningEntityDeposit;
     type Event = Event;
     type MiningRatesTokenIndex = u64;
     type MiningRatesTokenMaxLoyalty = u32;
@@ -996,9 +1497,11 @@ impl mining_rates_token::Config for Runtime {
     type MiningRatesTokenTokenIOTA = u32;
     // Mining Speed Boost Rate
     type MiningRatesTokenTokenMXC = u32;
+    type Randomness = RandomnessCollectiveFlip;
 }
 
 impl mining_rates_hardware::Config for Runtime {
+    type EntityDeposit = MiningEntityDeposit;
     type Event = Event;
     type MiningRatesHardwareCategory1MaxTokenBonusPerGateway = u32;
     type MiningRatesHardwareCategory2MaxTokenBonusPerGateway = u32;
@@ -1009,34 +1512,43 @@ impl mining_rates_hardware::Config for Runtime {
     type MiningRatesHardwareMaxHardware = u32;
     // Mining Speed Boost Rate
     type MiningRatesHardwareSecure = u32;
+    type Randomness = RandomnessCollectiveFlip;
 }
 
 impl mining_sampling_token::Config for Runtime {
+    type EntityDeposit = MiningEntityDeposit;
     type Event = Event;
     type MiningSamplingTokenIndex = u64;
     type MiningSamplingTokenSampleLockedAmount = u64;
+    type Randomness = RandomnessCollectiveFlip;
 }
 
 impl mining_sampling_hardware::Config for Runtime {
+    type EntityDeposit = MiningEntityDeposit;
     type Event = Event;
     type MiningSamplingHardwareIndex = u64;
     type MiningSamplingHardwareSampleHardwareOnline = u64;
+    type Randomness = RandomnessCollectiveFlip;
 }
 
 impl mining_eligibility_token::Config for Runtime {
+    type EntityDeposit = MiningEntityDeposit;
     type Event = Event;
     type MiningEligibilityTokenCalculatedEligibility = u64;
     type MiningEligibilityTokenIndex = u64;
     type MiningEligibilityTokenLockedPercentage = u32;
     // type MiningEligibilityTokenAuditorAccountID = u64;
+    type Randomness = RandomnessCollectiveFlip;
 }
 
 impl mining_eligibility_hardware::Config for Runtime {
+    type EntityDeposit = MiningEntityDeposit;
     type Event = Event;
     type MiningEligibilityHardwareCalculatedEligibility = u64;
     type MiningEligibilityHardwareIndex = u64;
     type MiningEligibilityHardwareUptimePercentage = u32;
     // type MiningEligibilityHardwareAuditorAccountID = u64;
+    type Randomness = RandomnessCollectiveFlip;
 }
 
 impl mining_eligibility_proxy::Config for Runtime {
@@ -1050,20 +1562,26 @@ impl mining_eligibility_proxy::Config for Runtime {
 }
 
 impl mining_claims_token::Config for Runtime {
+    type EntityDeposit = MiningEntityDeposit;
     type Event = Event;
     type MiningClaimsTokenClaimAmount = u64;
     type MiningClaimsTokenIndex = u64;
+    type Randomness = RandomnessCollectiveFlip;
 }
 
 impl mining_claims_hardware::Config for Runtime {
+    type EntityDeposit = MiningEntityDeposit;
     type Event = Event;
     type MiningClaimsHardwareClaimAmount = u64;
     type MiningClaimsHardwareIndex = u64;
+    type Randomness = RandomnessCollectiveFlip;
 }
 
 impl mining_execution_token::Config for Runtime {
+    type EntityDeposit = MiningEntityDeposit;
     type Event = Event;
     type MiningExecutionTokenIndex = u64;
+    type Randomness = RandomnessCollectiveFlip;
 }
 
 impl exchange_rate::Config for Runtime {
@@ -1074,6 +1592,7 @@ impl exchange_rate::Config for Runtime {
     type FILRate = u64;
     type HBTCRate = u64;
     type IOTARate = u64;
+    type Randomness = RandomnessCollectiveFlip;
 }
 
 impl membership_supernodes::Config for Runtime {
@@ -1117,21 +1636,43 @@ construct_runtime!(
         Bounties: pallet_bounties::{Module, Call, Storage, Event<T>},
         Tips: pallet_tips::{Module, Call, Storage, Event<T>},
         MembershipSupernodes: membership_supernodes::{Module, Call, Storage, Event<T>},
-        RoamingOperators: roaming_operators::{Module, Call, Storage, Event<T>},
+        RoamingOperatorRegistry: roaming_registry::<Instance1>::{Module, Call, Config, Storage, Event<T>},
+        RoamingNetworkRegistry: roaming_registry::<Instance2>::{Module, Call, Config, Storage, Event<T>},
+        RoamingOrganizationRegistry: roaming_registry::<Instance3>::{Module, Call, Config, Storage, Event<T>},
+        RoamingNetworkServerRegistry: roaming_registry::<Instance4>::{Module, Call, Config, Storage, Event<T>},
+        RoamingDeviceRegistry: roaming_registry::<Instance5>::{Module, Call, Config, Storage, Event<T>},
+        RoamingRoutingProfileRegistry: roaming_registry::<Instance6>::{Module, Call, Config, Storage, Event<T>},
+        RoamingServiceProfileRegistry: roaming_registry::<Instance7>::{Module, Call, Config, Storage, Event<T>},
+        RoamingAccountingPolicyRegistry: roaming_registry::<Instance8>::{Module, Call, Config, Storage, Event<T>},
+        RoamingAgreementPolicyRegistry: roaming_registry::<Instance9>::{Module, Call, Config, Storage, Event<T>},
+        RoamingNetworkProfileRegistry: roaming_registry::<Instance10>::{Module, Call, Config, Storage, Event<T>},
+        RoamingDeviceProfileRegistry: roaming_registry::<Instance11>::{Module, Call, Config, Storage, Event<T>},
+        RoamingSessionRegistry: roaming_registry::<Instance12>::{Module, Call, Config, Storage, Event<T>},
+        RoamingBillingPolicyRegistry: roaming_registry::<Instance13>::{Module, Call, Config, Storage, Event<T>},
+        RoamingChargingPolicyRegistry: roaming_registry::<Instance14>::{Module, Call, Config, Storage, Event<T>},
+        RoamingPacketBundleRegistry: roaming_registry::<Instance15>::{Module, Call, Config, Storage, Event<T>},
+        RoamingOperators: roaming_operators::{Module, Call, Event<T>},
         RoamingNetworks: roaming_networks::{Module, Call, Storage, Event<T>},
         RoamingOrganizations: roaming_organizations::{Module, Call, Storage, Event<T>},
         RoamingNetworkServers: roaming_network_servers::{Module, Call, Storage, Event<T>},
-        RoamingDevices: roaming_devices::{Module, Call, Storage, Event<T>},
-        RoamingRoutingProfiles: roaming_routing_profiles::{Module, Call, Storage, Event<T>},
+        RoamingDevices: roaming_devices::{Module, Call, Config, Storage, Event<T>},
+        RoamingRoutingProfiles: roaming_routing_profiles::{Module, Call, Config, Storage, Event<T>},
         RoamingServiceProfiles: roaming_service_profiles::{Module, Call, Storage, Event<T>},
-        RoamingAccountingPolicies: roaming_accounting_policies::{Module, Call, Storage, Event<T>},
+        RoamingAccountingPolicies: roaming_accounting_policies::{Module, Call, Config, Storage, Event<T>},
         RoamingAgreementPolicies: roaming_agreement_policies::{Module, Call, Storage, Event<T>},
         RoamingNetworkProfiles: roaming_network_profiles::{Module, Call, Storage, Event<T>},
         RoamingDeviceProfiles: roaming_device_profiles::{Module, Call, Storage, Event<T>},
-        RoamingSessions: roaming_sessions::{Module, Call, Storage, Event<T>},
+        RoamingSessions: roaming_sessions::{Module, Call, Config, Storage, Event<T>},
         RoamingBillingPolicies: roaming_billing_policies::{Module, Call, Storage, Event<T>},
         RoamingChargingPolicies: roaming_charging_policies::{Module, Call, Storage, Event<T>},
-        RoamingPacketBundles: roaming_packet_bundles::{Module, Call, Storage, Event<T>},
+        RoamingPacketBundles: roaming_packet_bundles::{Module, Call, Config, Storage, Event<T>},
+        RoamingInvoices: roaming_invoices::{Module, Call, Config, Storage, Event<T>},
+        RoamingOperatorMarketplace: roaming_marketplace::<Instance1>::{Module, Call, Storage, Event<T>},
+        RoamingNetworkMarketplace: roaming_marketplace::<Instance2>::{Module, Call, Storage, Event<T>},
+        RoamingOrganizationMarketplace: roaming_marketplace::<Instance3>::{Module, Call, Storage, Event<T>},
+        RoamingNetworkServerMarketplace: roaming_marketplace::<Instance4>::{Module, Call, Storage, Event<T>},
+        RoamingDeviceMarketplace: roaming_marketplace::<Instance5>::{Module, Call, Storage, Event<T>},
+        RoamingPacketBundleMarketplace: roaming_marketplace::<Instance6>::{Module, Call, Storage, Event<T>},
         MiningSettingToken: mining_setting_token::{Module, Call, Storage, Event<T>},
         MiningSettingHardware: mining_setting_hardware::{Module, Call, Storage, Event<T>},
         MiningRatesToken: mining_rates_token::{Module, Call, Storage, Event<T>},
@@ -1141,7 +1682,7 @@ construct_runtime!(
         MiningEligibilityToken: mining_eligibility_token::{Module, Call, Storage, Event<T>},
         MiningEligibilityHardware: mining_eligibility_hardware::{Module, Call, Storage, Event<T>},
         MiningEligibilityProxy: mining_eligibility_proxy::{Module, Call, Storage, Event<T>},
-        MiningClaimsToken: mining_claims_token::{Module, Call, Storage, Event<T>},
+        MiningClaimsToken: mining_claims_token::{Module, Call, Config, Storage, Event<T>},
         MiningClaimsHardware: mining_claims_hardware::{Module, Call, Storage, Event<T>},
         MiningExecutionToken: mining_execution_token::{Module, Call, Storage, Event<T>},
         ExchangeRate: exchange_rate::{Module, Call, Storage, Event<T>},
@@ -1177,6 +1718,61 @@ pub type CheckedExtrinsic = generic::CheckedExtrinsic<AccountId, Call, SignedExt
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<Runtime, Block, frame_system::ChainContext<Runtime>, Runtime, AllModules>;
 
+/// A roaming network server with the first page of its devices
+fn resolve_roaming_network_server_subtree(network_server_id: u64) -> RoamingNetworkServerSubtree<u64> {
+    RoamingNetworkServerSubtree {
+        network_server_id,
+        devices: roaming_network_server_devices_page(network_server_id, None, MAX_PAGE_LIMIT),
+    }
+}
+
+/// A roaming network with a page of its network servers
+fn resolve_roaming_network_subtree(network_id: u64, start: Option<u64>, limit: u32) -> RoamingNetworkSubtree<u64> {
+    RoamingNetworkSubtree {
+        network_id,
+        network_servers: RoamingPage::from_children(
+            RoamingNetworkServers::iter_network_network_servers_from(network_id, start),
+            limit,
+        )
+        .map(resolve_roaming_network_server_subtree),
+    }
+}
+
+/// A roaming operator with a page of its networks, if the operator exists
+pub fn roaming_operator_subtree(
+    operator_id: u64,
+    start: Option<u64>,
+    limit: u32,
+) -> Option<RoamingOperatorSubtree<u64>> {
+    RoamingOperators::exists_roaming_operator(operator_id).ok()?;
+    Some(RoamingOperatorSubtree {
+        operator_id,
+        networks: RoamingPage::from_children(RoamingNetworks::iter_operator_networks_from(operator_id, start), limit)
+            .map(|network_id| resolve_roaming_network_subtree(network_id, None, MAX_PAGE_LIMIT)),
+    })
+}
+
+/// A roaming network with a page of its network servers, if the network exists
+pub fn roaming_network_subtree(network_id: u64, start: Option<u64>, limit: u32) -> Option<RoamingNetworkSubtree<u64>> {
+    RoamingNetworks::exists_roaming_network(network_id).ok()?;
+    Some(resolve_roaming_network_subtree(network_id, start, limit))
+}
+
+/// A page of the devices of a roaming network server
+pub fn roaming_network_server_devices_page(network_server_id: u64, start: Option<u64>, limit: u32) -> RoamingPage<u64> {
+    RoamingPage::from_children(RoamingDevices::iter_network_server_devices_from(network_server_id, start), limit)
+}
+
+/// A page of the sessions of a roaming device
+pub fn roaming_device_sessions_page(device_id: u64, start: Option<u64>, limit: u32) -> RoamingPage<u64> {
+    RoamingPage::from_children(RoamingSessions::iter_device_sessions_from(device_id, start), limit)
+}
+
+/// A page of the packet bundles of a roaming session
+pub fn roaming_session_packet_bundles_page(session_id: u64, start: Option<u64>, limit: u32) -> RoamingPage<u64> {
+    RoamingPage::from_children(RoamingPacketBundles::iter_session_packet_bundles_from(session_id, start), limit)
+}
+
 impl_runtime_apis! {
     impl sp_api::Core<Block> for Runtime {
         fn version() -> RuntimeVersion {
@@ -1344,6 +1940,46 @@ impl_runtime_apis! {
         }
     }
 
+    impl roaming_registry_runtime_api::RoamingMetadataApi<
+        Block,
+        u64,
+        roaming_registry::RoamingEntityMetadata,
+    > for Runtime {
+        fn roaming_operators_metadata(start: u64, limit: u32) -> Vec<(u64, roaming_registry::RoamingEntityMetadata)> {
+            RoamingOperatorRegistry::roaming_entities_metadata(start, limit)
+        }
+        fn roaming_networks_metadata(start: u64, limit: u32) -> Vec<(u64, roaming_registry::RoamingEntityMetadata)> {
+            RoamingNetworkRegistry::roaming_entities_metadata(start, limit)
+        }
+        fn roaming_organizations_metadata(start: u64, limit: u32) -> Vec<(u64, roaming_registry::RoamingEntityMetadata)> {
+            RoamingOrganizationRegistry::roaming_entities_metadata(start, limit)
+        }
+        fn roaming_network_servers_metadata(
+            start: u64,
+            limit: u32,
+        ) -> Vec<(u64, roaming_registry::RoamingEntityMetadata)> {
+            RoamingNetworkServerRegistry::roaming_entities_metadata(start, limit)
+        }
+    }
+
+    impl roaming_runtime_api::RoamingApi<Block, u64> for Runtime {
+        fn roaming_operator_subtree(operator_id: u64, start: Option<u64>, limit: u32) -> Option<RoamingOperatorSubtree<u64>> {
+            roaming_operator_subtree(operator_id, start, limit)
+        }
+        fn roaming_network_subtree(network_id: u64, start: Option<u64>, limit: u32) -> Option<RoamingNetworkSubtree<u64>> {
+            roaming_network_subtree(network_id, start, limit)
+        }
+        fn roaming_network_server_devices(network_server_id: u64, start: Option<u64>, limit: u32) -> RoamingPage<u64> {
+            roaming_network_server_devices_page(network_server_id, start, limit)
+        }
+        fn roaming_device_sessions(device_id: u64, start: Option<u64>, limit: u32) -> RoamingPage<u64> {
+            roaming_device_sessions_page(device_id, start, limit)
+        }
+        fn roaming_session_packet_bundles(session_id: u64, start: Option<u64>, limit: u32) -> RoamingPage<u64> {
+            roaming_session_packet_bundles_page(session_id, start, limit)
+        }
+    }
+
     impl sp_session::SessionKeys<Block> for Runtime {
         fn generate_session_keys(seed: Option<Vec<u8>>) -> Vec<u8> {
             SessionKeys::generate(seed)
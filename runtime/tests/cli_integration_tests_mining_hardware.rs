@@ -128,11 +128,14 @@ mod tests {
     // FIXME - remove this when figure out how to use these types within mining-speed-boost runtime module itself
     impl roaming_operators::Config for Test {
         type Currency = Balances;
+        type EntityDeposit = ();
         type Event = ();
-        type Randomness = RandomnessCollectiveFlip;
+        type RemovalPolicy = ();
+        type RoamingOperatorChildren = ();
         type RoamingOperatorIndex = u64;
     }
     impl MiningSettingHardwareConfig for Test {
+        type EntityDeposit = ();
         type Event = ();
         type MiningSettingHardwareDevEUI = u64;
         // type MiningSettingHardwareType =
@@ -145,8 +148,10 @@ mod tests {
         type MiningSettingHardwareSecure = bool;
         // FIXME - how to use this enum from std? (including importing `use std::str::FromStr;`)
         type MiningSettingHardwareType = Vec<u8>;
+        type Randomness = RandomnessCollectiveFlip;
     }
     impl MiningRatesHardwareConfig for Test {
+        type EntityDeposit = ();
         type Event = ();
         type MiningRatesHardwareCategory1MaxTokenBonusPerGateway = u32;
         type MiningRatesHardwareCategory2MaxTokenBonusPerGateway = u32;
@@ -157,23 +162,30 @@ mod tests {
         type MiningRatesHardwareMaxHardware = u32;
         // Mining Speed Boost Rate
         type MiningRatesHardwareSecure = u32;
+        type Randomness = RandomnessCollectiveFlip;
     }
     impl MiningSamplingHardwareConfig for Test {
+        type EntityDeposit = ();
         type Event = ();
         type MiningSamplingHardwareIndex = u64;
         type MiningSamplingHardwareSampleHardwareOnline = u64;
+        type Randomness = RandomnessCollectiveFlip;
     }
     impl MiningEligibilityHardwareConfig for Test {
+        type EntityDeposit = ();
         type Event = ();
         type MiningEligibilityHardwareCalculatedEligibility = u64;
         type MiningEligibilityHardwareIndex = u64;
         type MiningEligibilityHardwareUptimePercentage = u32;
         // type MiningEligibilityHardwareAuditorAccountID = u64;
+        type Randomness = RandomnessCollectiveFlip;
     }
     impl MiningClaimsHardwareConfig for Test {
+        type EntityDeposit = ();
         type Event = ();
         type MiningClaimsHardwareClaimAmount = u64;
         type MiningClaimsHardwareIndex = u64;
+        type Randomness = RandomnessCollectiveFlip;
     }
 
     pub type MiningSettingHardwareTestModule = MiningSettingHardwareModule<Test>;
@@ -279,12 +279,15 @@ mod tests {
 
     // FIXME - remove this when figure out how to use these types within mining-speed-boost runtime module itself
     impl roaming_operators::Config for Test {
+        type EntityDeposit = ();
         type Event = ();
         type Currency = Balances;
-        type Randomness = RandomnessCollectiveFlip;
+        type RemovalPolicy = ();
+        type RoamingOperatorChildren = ();
         type RoamingOperatorIndex = u64;
     }
     impl MiningSettingTokenConfig for Test {
+        type EntityDeposit = ();
         type Event = ();
         // type Currency = Balances;
         // type Randomness = Randomness;
@@ -293,8 +296,10 @@ mod tests {
         // Mining Speed Boost Token Mining Config
         // FIXME - how to use this enum from std? (including importing `use std::str::FromStr;`)
         type MiningSettingTokenType = Vec<u8>;
+        type Randomness = RandomnessCollectiveFlip;
     }
     impl MiningRatesTokenConfig for Test {
+        type EntityDeposit = ();
         type Event = ();
         type MiningRatesTokenIndex = u64;
         type MiningRatesTokenMaxLoyalty = u32;
@@ -304,18 +309,23 @@ mod tests {
         type MiningRatesTokenTokenIOTA = u32;
         // Mining Speed Boost Rate
         type MiningRatesTokenTokenMXC = u32;
+        type Randomness = RandomnessCollectiveFlip;
     }
     impl MiningSamplingTokenConfig for Test {
+        type EntityDeposit = ();
         type Event = ();
         type MiningSamplingTokenIndex = u64;
         type MiningSamplingTokenSampleLockedAmount = u64;
+        type Randomness = RandomnessCollectiveFlip;
     }
     impl MiningEligibilityTokenConfig for Test {
+        type EntityDeposit = ();
         type Event = ();
         type MiningEligibilityTokenCalculatedEligibility = u64;
         type MiningEligibilityTokenIndex = u64;
         type MiningEligibilityTokenLockedPercentage = u32;
         // type MiningEligibilityTokenAuditorAccountID = u64;
+        type Randomness = RandomnessCollectiveFlip;
     }
     impl MiningEligibilityProxyConfig for Test {
         type Event = ();
@@ -326,13 +336,17 @@ mod tests {
         type RewardsOfDay = u64;
     }
     impl MiningClaimsTokenConfig for Test {
+        type EntityDeposit = ();
         type Event = ();
         type MiningClaimsTokenClaimAmount = u64;
         type MiningClaimsTokenIndex = u64;
+        type Randomness = RandomnessCollectiveFlip;
     }
     impl MiningExecutionTokenConfig for Test {
+        type EntityDeposit = ();
         type Event = ();
         type MiningExecutionTokenIndex = u64;
+        type Randomness = RandomnessCollectiveFlip;
     }
     impl MembershipSupernodesConfig for Test {
         type Event = ();
@@ -381,6 +395,39 @@ mod tests {
         });
     }
 
+    #[test]
+    fn owner_removes_mining_samplings_token_and_its_configuration_link() {
+        new_test_ext().execute_with(|| {
+            // Setup
+            assert_ok!(MiningSettingTokenTestModule::create(Origin::signed(0)));
+            assert_ok!(MiningSamplingTokenTestModule::create(Origin::signed(0)));
+            assert_ok!(MiningSamplingTokenTestModule::set_mining_samplings_token_samplings_config(
+                Origin::signed(0),
+                0,           // mining_token_id
+                0,           // mining_token_sample_id
+                Some(23456), // token_sample_block
+                Some(100),   // token_sample_locked_amount
+            ));
+            assert_ok!(MiningSamplingTokenTestModule::assign_sampling_to_configuration(Origin::signed(0), 0, 0));
+
+            // Call Functions
+            assert_err!(
+                MiningSamplingTokenTestModule::remove(Origin::signed(1), 0),
+                "Only owner can remove mining mining_samplings_token"
+            );
+            assert_ok!(MiningSamplingTokenTestModule::remove(Origin::signed(0), 0));
+
+            // Verify Storage
+            assert!(MiningSamplingTokenTestModule::mining_samplings_token(0).is_none());
+            assert_eq!(MiningSamplingTokenTestModule::mining_samplings_token_owner(0), None);
+            assert_eq!(MiningSamplingTokenTestModule::mining_samplings_token_deposit(0), None);
+            assert_eq!(MiningSamplingTokenTestModule::token_sampling_configuration(0), None);
+            assert_eq!(MiningSamplingTokenTestModule::mining_samplings_token_samplings_configs((0, 0)), None);
+            assert_eq!(MiningSamplingTokenTestModule::token_setting_samplings(0), None);
+            assert_eq!(Balances::reserved_balance(0), 0);
+        });
+    }
+
     #[test]
     fn integration_test() {
         new_test_ext().execute_with(|| {
@@ -584,6 +631,7 @@ mod tests {
             assert_eq!(MiningClaimsTokenTestModule::mining_claims_token_count(), 1);
             assert!(MiningClaimsTokenTestModule::mining_claims_token(0).is_some());
             assert_eq!(MiningClaimsTokenTestModule::mining_claims_token_owner(0), Some(0));
+            assert_eq!(MiningClaimsTokenTestModule::token_setting_claims(0), vec![0]);
             assert_eq!(
                 MiningClaimsTokenTestModule::mining_claims_token_claims_results((0, 0)),
                 Some(MiningClaimsTokenClaimResult {
@@ -41,6 +41,7 @@ mod tests {
     use roaming_accounting_policies::{
         Module as RoamingAccountingPolicyModule,
         RoamingAccountingPolicySetting,
+        RoamingAccountingPolicyType,
         Config as RoamingAccountingPolicyConfig,
     };
     use roaming_agreement_policies::{
@@ -90,6 +91,8 @@ mod tests {
     use roaming_routing_profiles::{
         Module as RoamingRoutingProfileModule,
         Config as RoamingRoutingProfileConfig,
+        RoamingRoutingProfileEndpoint,
+        RoamingRoutingProfileEndpointProtocol,
     };
     use roaming_service_profiles::{
         Module as RoamingServiceProfileModule,
@@ -163,62 +166,222 @@ mod tests {
         type TransactionByteFee = ();
         type WeightToFee = IdentityFee<u64>;
     }
+    impl roaming_registry::Config<roaming_registry::Instance1> for Test {
+        type Currency = Balances;
+        type EntityDeposit = ();
+        type Event = ();
+        type LegacyStorage = ();
+        type MaxMetadataFieldLength = ();
+        type MetadataDepositPerByte = ();
+        type Randomness = RandomnessCollectiveFlip;
+        type RoamingEntityIndex = u64;
+    }
     impl RoamingOperatorConfig for Test {
         type Currency = Balances;
         type Event = ();
         type Randomness = RandomnessCollectiveFlip;
+        type RemovalPolicy = ();
+        type RoamingOperatorChildren = ();
         type RoamingOperatorIndex = u64;
+        type RoamingOperatorRegistry = RoamingOperatorRegistry;
+    }
+    impl roaming_registry::Config<roaming_registry::Instance2> for Test {
+        type Currency = Balances;
+        type EntityDeposit = ();
+        type Event = ();
+        type LegacyStorage = ();
+        type MaxMetadataFieldLength = ();
+        type MetadataDepositPerByte = ();
+        type Randomness = RandomnessCollectiveFlip;
+        type RoamingEntityIndex = u64;
     }
     impl RoamingNetworkConfig for Test {
         type Event = ();
+        type RoamingNetworkChildren = ();
         type RoamingNetworkIndex = u64;
+        type RoamingNetworkRegistry = RoamingNetworkRegistry;
+    }
+    impl roaming_registry::Config<roaming_registry::Instance3> for Test {
+        type Currency = Balances;
+        type EntityDeposit = ();
+        type Event = ();
+        type LegacyStorage = ();
+        type MaxMetadataFieldLength = ();
+        type MetadataDepositPerByte = ();
+        type Randomness = RandomnessCollectiveFlip;
+        type RoamingEntityIndex = u64;
     }
     impl RoamingOrganizationConfig for Test {
         type Event = ();
+        type RoamingOrganizationChildren = ();
         type RoamingOrganizationIndex = u64;
+        type RoamingOrganizationRegistry = RoamingOrganizationRegistry;
+    }
+    impl roaming_registry::Config<roaming_registry::Instance4> for Test {
+        type Currency = Balances;
+        type EntityDeposit = ();
+        type Event = ();
+        type LegacyStorage = ();
+        type MaxMetadataFieldLength = ();
+        type MetadataDepositPerByte = ();
+        type Randomness = RandomnessCollectiveFlip;
+        type RoamingEntityIndex = u64;
     }
     impl RoamingNetworkServerConfig for Test {
         type Event = ();
+        type RoamingNetworkServerChildren = ();
         type RoamingNetworkServerIndex = u64;
+        type RoamingNetworkServerRegistry = RoamingNetworkServerRegistry;
+    }
+    impl roaming_registry::Config<roaming_registry::Instance9> for Test {
+        type Currency = Balances;
+        type EntityDeposit = ();
+        type Event = ();
+        type LegacyStorage = ();
+        type MaxMetadataFieldLength = ();
+        type MetadataDepositPerByte = ();
+        type Randomness = RandomnessCollectiveFlip;
+        type RoamingEntityIndex = u64;
     }
     impl RoamingAgreementPolicyConfig for Test {
         type Event = ();
         type RoamingAgreementPolicyActivationType = Vec<u8>;
         type RoamingAgreementPolicyIndex = u64;
+        type RoamingAgreementPolicyRegistry = RoamingAgreementPolicyRegistry;
+    }
+    impl roaming_registry::Config<roaming_registry::Instance8> for Test {
+        type Currency = Balances;
+        type EntityDeposit = ();
+        type Event = ();
+        type LegacyStorage = ();
+        type MaxMetadataFieldLength = ();
+        type MetadataDepositPerByte = ();
+        type Randomness = RandomnessCollectiveFlip;
+        type RoamingEntityIndex = u64;
+    }
+    parameter_types! {
+        pub const MaxSubscriptionRenewalsPerBlock: u32 = 2;
+        pub const SubscriptionBillingPeriod: u64 = 10;
     }
     impl RoamingAccountingPolicyConfig for Test {
         type Event = ();
+        type MaxSubscriptionRenewalsPerBlock = MaxSubscriptionRenewalsPerBlock;
+        type RoamingAccountingPolicyChildren = ();
         type RoamingAccountingPolicyDownlinkFeeFactor = u32;
         type RoamingAccountingPolicyIndex = u64;
-        type RoamingAccountingPolicyType = Vec<u8>;
+        type RoamingAccountingPolicyRegistry = RoamingAccountingPolicyRegistry;
         type RoamingAccountingPolicyUplinkFeeFactor = u32;
+        type SubscriptionBillingPeriod = SubscriptionBillingPeriod;
+    }
+    impl roaming_registry::Config<roaming_registry::Instance6> for Test {
+        type Currency = Balances;
+        type EntityDeposit = ();
+        type Event = ();
+        type LegacyStorage = ();
+        type MaxMetadataFieldLength = ();
+        type MetadataDepositPerByte = ();
+        type Randomness = RandomnessCollectiveFlip;
+        type RoamingEntityIndex = u64;
+    }
+    parameter_types! {
+        pub const MaxEndpoints: u32 = 8;
+        pub const MaxEndpointUriLength: u32 = 256;
     }
     impl RoamingRoutingProfileConfig for Test {
         type Event = ();
-        type RoamingRoutingProfileAppServer = Vec<u8>;
+        type MaxEndpointUriLength = MaxEndpointUriLength;
+        type MaxEndpoints = MaxEndpoints;
         type RoamingRoutingProfileIndex = u64;
+        type RoamingRoutingProfileRegistry = RoamingRoutingProfileRegistry;
+    }
+    impl roaming_registry::Config<roaming_registry::Instance5> for Test {
+        type Currency = Balances;
+        type EntityDeposit = ();
+        type Event = ();
+        type LegacyStorage = ();
+        type MaxMetadataFieldLength = ();
+        type MetadataDepositPerByte = ();
+        type Randomness = RandomnessCollectiveFlip;
+        type RoamingEntityIndex = u64;
     }
     impl RoamingDeviceConfig for Test {
         type Event = ();
+        type MaxRoamingPolicyNetworks = ();
+        type RoamingDeviceChildren = ();
         type RoamingDeviceIndex = u64;
+        type RoamingDeviceRegistry = RoamingDeviceRegistry;
+    }
+    impl roaming_registry::Config<roaming_registry::Instance7> for Test {
+        type Currency = Balances;
+        type EntityDeposit = ();
+        type Event = ();
+        type LegacyStorage = ();
+        type MaxMetadataFieldLength = ();
+        type MetadataDepositPerByte = ();
+        type Randomness = RandomnessCollectiveFlip;
+        type RoamingEntityIndex = u64;
     }
     impl RoamingServiceProfileConfig for Test {
         type Event = ();
         type RoamingServiceProfileDownlinkRate = u32;
         type RoamingServiceProfileIndex = u64;
+        type RoamingServiceProfileRegistry = RoamingServiceProfileRegistry;
         type RoamingServiceProfileUplinkRate = u32;
     }
+    impl roaming_registry::Config<roaming_registry::Instance13> for Test {
+        type Currency = Balances;
+        type EntityDeposit = ();
+        type Event = ();
+        type LegacyStorage = ();
+        type MaxMetadataFieldLength = ();
+        type MetadataDepositPerByte = ();
+        type Randomness = RandomnessCollectiveFlip;
+        type RoamingEntityIndex = u64;
+    }
     impl RoamingBillingPolicyConfig for Test {
         type Event = ();
         type RoamingBillingPolicyIndex = u64;
+        type RoamingBillingPolicyRegistry = RoamingBillingPolicyRegistry;
+    }
+    impl roaming_registry::Config<roaming_registry::Instance14> for Test {
+        type Currency = Balances;
+        type EntityDeposit = ();
+        type Event = ();
+        type LegacyStorage = ();
+        type MaxMetadataFieldLength = ();
+        type MetadataDepositPerByte = ();
+        type Randomness = RandomnessCollectiveFlip;
+        type RoamingEntityIndex = u64;
     }
     impl RoamingChargingPolicyConfig for Test {
         type Event = ();
         type RoamingChargingPolicyIndex = u64;
+        type RoamingChargingPolicyRegistry = RoamingChargingPolicyRegistry;
+    }
+    impl roaming_registry::Config<roaming_registry::Instance10> for Test {
+        type Currency = Balances;
+        type EntityDeposit = ();
+        type Event = ();
+        type LegacyStorage = ();
+        type MaxMetadataFieldLength = ();
+        type MetadataDepositPerByte = ();
+        type Randomness = RandomnessCollectiveFlip;
+        type RoamingEntityIndex = u64;
     }
     impl RoamingNetworkProfileConfig for Test {
         type Event = ();
         type RoamingNetworkProfileIndex = u64;
+        type RoamingNetworkProfileRegistry = RoamingNetworkProfileRegistry;
+    }
+    impl roaming_registry::Config<roaming_registry::Instance11> for Test {
+        type Currency = Balances;
+        type EntityDeposit = ();
+        type Event = ();
+        type LegacyStorage = ();
+        type MaxMetadataFieldLength = ();
+        type MetadataDepositPerByte = ();
+        type Randomness = RandomnessCollectiveFlip;
+        type RoamingEntityIndex = u64;
     }
     impl RoamingDeviceProfileConfig for Test {
         type Event = ();
@@ -226,6 +389,7 @@ mod tests {
         type RoamingDeviceProfileDevEUI = Vec<u8>;
         type RoamingDeviceProfileIndex = u64;
         type RoamingDeviceProfileJoinEUI = Vec<u8>;
+        type RoamingDeviceProfileRegistry = RoamingDeviceProfileRegistry;
         type RoamingDeviceProfileVendorID = Vec<u8>;
     }
 
@@ -242,6 +406,19 @@ mod tests {
     pub type RoamingChargingPolicyTestModule = RoamingChargingPolicyModule<Test>;
     pub type RoamingNetworkProfileTestModule = RoamingNetworkProfileModule<Test>;
     pub type RoamingDeviceProfileTestModule = RoamingDeviceProfileModule<Test>;
+    pub type RoamingOperatorRegistry = roaming_registry::Module<Test, roaming_registry::Instance1>;
+    pub type RoamingNetworkRegistry = roaming_registry::Module<Test, roaming_registry::Instance2>;
+    pub type RoamingOrganizationRegistry = roaming_registry::Module<Test, roaming_registry::Instance3>;
+    pub type RoamingNetworkServerRegistry = roaming_registry::Module<Test, roaming_registry::Instance4>;
+    pub type RoamingAgreementPolicyRegistry = roaming_registry::Module<Test, roaming_registry::Instance9>;
+    pub type RoamingAccountingPolicyRegistry = roaming_registry::Module<Test, roaming_registry::Instance8>;
+    pub type RoamingRoutingProfileRegistry = roaming_registry::Module<Test, roaming_registry::Instance6>;
+    pub type RoamingDeviceRegistry = roaming_registry::Module<Test, roaming_registry::Instance5>;
+    pub type RoamingServiceProfileRegistry = roaming_registry::Module<Test, roaming_registry::Instance7>;
+    pub type RoamingBillingPolicyRegistry = roaming_registry::Module<Test, roaming_registry::Instance13>;
+    pub type RoamingChargingPolicyRegistry = roaming_registry::Module<Test, roaming_registry::Instance14>;
+    pub type RoamingNetworkProfileRegistry = roaming_registry::Module<Test, roaming_registry::Instance10>;
+    pub type RoamingDeviceProfileRegistry = roaming_registry::Module<Test, roaming_registry::Instance11>;
 
     // This function basically just builds a genesis storage key/value store according to
     // our desired mockup.
@@ -280,17 +457,17 @@ mod tests {
             // Create two network operators with two different admin users
 
             // Call Functions
-            assert_ok!(RoamingOperatorTestModule::create(Origin::signed(0))); // MXC
-            assert_ok!(RoamingOperatorTestModule::create(Origin::signed(1))); // TEX
+            assert_ok!(RoamingOperatorRegistry::create(Origin::signed(0))); // MXC
+            assert_ok!(RoamingOperatorRegistry::create(Origin::signed(1))); // TEX
             // FIXME - create a User runtime module that may be an admin, and
             // may be assigned to an Operator and an Organization
 
             // Verify Storage
-            assert_eq!(RoamingOperatorTestModule::roaming_operators_count(), 2);
-            assert!(RoamingOperatorTestModule::roaming_operator(0).is_some()); // MXC
-            assert!(RoamingOperatorTestModule::roaming_operator(1).is_some()); // TEX
-            assert_eq!(RoamingOperatorTestModule::roaming_operator_owner(0), Some(0));
-            assert_eq!(RoamingOperatorTestModule::roaming_operator_price(0), None);
+            assert_eq!(RoamingOperatorRegistry::roaming_entities_count(), 2);
+            assert!(RoamingOperatorRegistry::roaming_entity(0).is_some()); // MXC
+            assert!(RoamingOperatorRegistry::roaming_entity(1).is_some()); // TEX
+            assert_eq!(RoamingOperatorRegistry::roaming_entity_owner(0), Some(0));
+            assert_eq!(RoamingOperatorRegistry::roaming_entity_price(0), None);
 
             // Create Networks
             //
@@ -303,8 +480,8 @@ mod tests {
                 RoamingNetworkTestModule::is_roaming_network_owner(0, 1).is_err(),
                 "Roaming network does not exist yet"
             );
-            assert_ok!(RoamingNetworkTestModule::create(Origin::signed(0)));
-            assert_ok!(RoamingNetworkTestModule::create(Origin::signed(1)));
+            assert_ok!(RoamingNetworkRegistry::create(Origin::signed(0)));
+            assert_ok!(RoamingNetworkRegistry::create(Origin::signed(1)));
             assert!(RoamingNetworkTestModule::exists_roaming_network(0).is_ok());
             assert!(RoamingNetworkTestModule::exists_roaming_network(1).is_ok());
             assert_ok!(RoamingNetworkTestModule::is_roaming_network_owner(0, 0));
@@ -314,68 +491,68 @@ mod tests {
             assert_ok!(RoamingNetworkTestModule::assign_network_to_operator(Origin::signed(0), 1, 0));
 
             // Verify Storage
-            assert_eq!(RoamingNetworkTestModule::roaming_networks_count(), 2);
-            assert!(RoamingNetworkTestModule::roaming_network(0).is_some());
-            assert!(RoamingNetworkTestModule::roaming_network(1).is_some());
-            assert_eq!(RoamingNetworkTestModule::roaming_network_owner(0), Some(0));
-            assert_eq!(RoamingNetworkTestModule::roaming_network_price(0), None);
+            assert_eq!(RoamingNetworkRegistry::roaming_entities_count(), 2);
+            assert!(RoamingNetworkRegistry::roaming_entity(0).is_some());
+            assert!(RoamingNetworkRegistry::roaming_entity(1).is_some());
+            assert_eq!(RoamingNetworkRegistry::roaming_entity_owner(0), Some(0));
+            assert_eq!(RoamingNetworkRegistry::roaming_entity_price(0), None);
 
             // Create Organizations
             //
             // Create three organizations with the same admin user.
 
             // Call Functions
-            assert_ok!(RoamingOrganizationTestModule::create(Origin::signed(0)));
-            assert_ok!(RoamingOrganizationTestModule::create(Origin::signed(1)));
-            assert_ok!(RoamingOrganizationTestModule::create(Origin::signed(2)));
+            assert_ok!(RoamingOrganizationRegistry::create(Origin::signed(0)));
+            assert_ok!(RoamingOrganizationRegistry::create(Origin::signed(1)));
+            assert_ok!(RoamingOrganizationRegistry::create(Origin::signed(2)));
             // FIXME - assign Users to the organizations
 
             // Verify Storage
-            assert_eq!(RoamingOrganizationTestModule::roaming_organizations_count(), 3);
+            assert_eq!(RoamingOrganizationRegistry::roaming_entities_count(), 3);
 
             // Create Network Servers
             //
             // Create a network server with the same admin user.
 
             // Call Functions
-            assert_ok!(RoamingNetworkServerTestModule::create(Origin::signed(0)));
-            assert_ok!(RoamingNetworkServerTestModule::create(Origin::signed(1)));
+            assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(0)));
+            assert_ok!(RoamingNetworkServerRegistry::create(Origin::signed(1)));
 
             // Verify Storage
-            assert_eq!(RoamingNetworkServerTestModule::roaming_network_servers_count(), 2);
+            assert_eq!(RoamingNetworkServerRegistry::roaming_entities_count(), 2);
 
             // Create Roaming Accounting Policy
 
             // Call Functions
-            assert_ok!(RoamingAccountingPolicyTestModule::create(Origin::signed(0)));
+            assert_ok!(RoamingAccountingPolicyRegistry::create(Origin::signed(0)));
             // Note: This step is optional
             assert_ok!(RoamingAccountingPolicyTestModule::assign_accounting_policy_to_network(Origin::signed(0), 0, 0));
-            assert_eq!(RoamingAccountingPolicyTestModule::roaming_accounting_policy_owner(0), Some(0));
+            assert_eq!(RoamingAccountingPolicyRegistry::roaming_entity_owner(0), Some(0));
             assert_ok!(RoamingAccountingPolicyTestModule::set_config(
                 Origin::signed(0),
-                0,                              // accounting_policy_id
-                Some(b"subscription".to_vec()), // policy_type
-                Some(200),                      // subscription_fee
-                Some(15),                       // uplink_fee_factor
-                Some(10),                       // downlink_fee_factor
+                0,                                                // accounting_policy_id
+                Some(RoamingAccountingPolicyType::Subscription), // policy_type
+                Some(200),                                        // subscription_fee
+                Some(15),                                         // uplink_fee_factor
+                Some(10),                                         // downlink_fee_factor
             ));
 
             // Verify Storage
-            assert_eq!(RoamingAccountingPolicyTestModule::roaming_accounting_policies_count(), 1);
+            assert_eq!(RoamingAccountingPolicyRegistry::roaming_entities_count(), 1);
             assert_eq!(
                 RoamingAccountingPolicyTestModule::roaming_accounting_policy_settings(0),
                 Some(RoamingAccountingPolicySetting {
-                    policy_type: b"subscription".to_vec(), // policy_type
-                    subscription_fee: 200,                 // subscription_fee
-                    uplink_fee_factor: 15,                 // uplink_fee_factor
-                    downlink_fee_factor: 10,               // downlink_fee_factor
+                    policy_type: RoamingAccountingPolicyType::Subscription, // policy_type
+                    subscription_fee: 200,                                  // subscription_fee
+                    uplink_fee_factor: 15,                                  // uplink_fee_factor
+                    downlink_fee_factor: 10,                                // downlink_fee_factor
                 })
             );
 
             // Create Roaming Agreement Policy
 
             // Call Functions
-            assert_ok!(RoamingAgreementPolicyTestModule::create(Origin::signed(0)));
+            assert_ok!(RoamingAgreementPolicyRegistry::create(Origin::signed(0)));
             // Note: This step is optional since it will be assigned to a network when
             // a associated with a network (roaming base) profile
             assert_ok!(RoamingAgreementPolicyTestModule::assign_agreement_policy_to_network(Origin::signed(0), 0, 0));
@@ -383,7 +560,7 @@ mod tests {
             //     RoamingAgreementPolicyTestModule::exists_roaming_agreement_policy(0),
             //     Ok(RoamingAgreementPolicy([0; 16]))
             // );
-            assert_eq!(RoamingAgreementPolicyTestModule::roaming_agreement_policy_owner(0), Some(0));
+            assert_eq!(RoamingAgreementPolicyRegistry::roaming_entity_owner(0), Some(0));
             assert_ok!(RoamingAgreementPolicyTestModule::set_config(
                 Origin::signed(0),
                 0,
@@ -392,7 +569,7 @@ mod tests {
             ));
 
             // Verify Storage
-            assert_eq!(RoamingAgreementPolicyTestModule::roaming_agreement_policies_count(), 1);
+            assert_eq!(RoamingAgreementPolicyRegistry::roaming_entities_count(), 1);
             assert_eq!(
                 RoamingAgreementPolicyTestModule::roaming_agreement_policy_settings(0),
                 Some(RoamingAgreementPolicySetting {
@@ -404,26 +581,28 @@ mod tests {
             // Create Roaming Routing Profile
 
             // Call Functions
-            assert_ok!(RoamingRoutingProfileTestModule::create(Origin::signed(0)));
-            assert_eq!(RoamingRoutingProfileTestModule::roaming_routing_profile_owner(0), Some(0));
-            assert_ok!(RoamingRoutingProfileTestModule::set_app_server(
+            assert_ok!(RoamingRoutingProfileRegistry::create(Origin::signed(0)));
+            assert_eq!(RoamingRoutingProfileRegistry::roaming_entity_owner(0), Some(0));
+            let endpoint = RoamingRoutingProfileEndpoint {
+                uri: b"https://10.0.0.1:8443".to_vec(),
+                protocol: RoamingRoutingProfileEndpointProtocol::Http,
+                priority: 0,
+            };
+            assert_ok!(RoamingRoutingProfileTestModule::set_endpoints(
                 Origin::signed(0),
-                0,                          // routing_profile_id
-                Some(b"10.0.0.1".to_vec()), // app server
+                0, // routing_profile_id
+                vec![endpoint.clone()],
             ));
 
             // Verify Storage
-            assert_eq!(RoamingRoutingProfileTestModule::roaming_routing_profiles_count(), 1);
-            assert_eq!(
-                RoamingRoutingProfileTestModule::roaming_routing_profile_app_server(0),
-                Some(b"10.0.0.1".to_vec())
-            );
+            assert_eq!(RoamingRoutingProfileRegistry::roaming_entities_count(), 1);
+            assert_eq!(RoamingRoutingProfileTestModule::roaming_routing_profile_endpoints(0), Some(vec![endpoint]));
 
             // Create Service Profile
 
             // Call Functions
-            assert_ok!(RoamingServiceProfileTestModule::create(Origin::signed(0)));
-            assert_eq!(RoamingServiceProfileTestModule::roaming_service_profile_owner(0), Some(0));
+            assert_ok!(RoamingServiceProfileRegistry::create(Origin::signed(0)));
+            assert_eq!(RoamingServiceProfileRegistry::roaming_entity_owner(0), Some(0));
             // Note: Optional since it will be assigned to a network when
             // a associated with a network (roaming base) profile, but we can override it to apply to specific
             // network server this way.
@@ -444,14 +623,14 @@ mod tests {
             ));
 
             // Verify Storage
-            assert_eq!(RoamingServiceProfileTestModule::roaming_service_profiles_count(), 1);
+            assert_eq!(RoamingServiceProfileRegistry::roaming_entities_count(), 1);
             assert_eq!(RoamingServiceProfileTestModule::roaming_service_profile_uplink_rate(0), Some(10));
             assert_eq!(RoamingServiceProfileTestModule::roaming_service_profile_downlink_rate(0), Some(5));
 
             // Create Billing Policy
 
             // Call Functions
-            assert_ok!(RoamingBillingPolicyTestModule::create(Origin::signed(0)));
+            assert_ok!(RoamingBillingPolicyRegistry::create(Origin::signed(0)));
             // Note: This step is optional since it will be assigned to a network and operator when
             // associated with a network (roaming base) profile
             // assert_ok!(
@@ -470,7 +649,7 @@ mod tests {
             //         0
             //     )
             // );
-            assert_eq!(RoamingBillingPolicyTestModule::roaming_billing_policy_owner(0), Some(0));
+            assert_eq!(RoamingBillingPolicyRegistry::roaming_entity_owner(0), Some(0));
             assert_ok!(RoamingBillingPolicyTestModule::set_config(
                 Origin::signed(0),
                 0,
@@ -479,7 +658,7 @@ mod tests {
             ));
 
             // Verify Storage
-            assert_eq!(RoamingBillingPolicyTestModule::roaming_billing_policies_count(), 1);
+            assert_eq!(RoamingBillingPolicyRegistry::roaming_entities_count(), 1);
             assert_eq!(
                 RoamingBillingPolicyTestModule::roaming_billing_policy_settings(0),
                 Some(RoamingBillingPolicySetting {
@@ -491,7 +670,7 @@ mod tests {
             // Create Charging Policy
 
             // Call Functions
-            assert_ok!(RoamingChargingPolicyTestModule::create(Origin::signed(0)));
+            assert_ok!(RoamingChargingPolicyRegistry::create(Origin::signed(0)));
             // Note: This step is optional since it will be assigned to a network and operator when
             // associated with a network (roaming base) profile
             // assert_ok!(
@@ -510,7 +689,7 @@ mod tests {
             //         0
             //     )
             // );
-            assert_eq!(RoamingChargingPolicyTestModule::roaming_charging_policy_owner(0), Some(0));
+            assert_eq!(RoamingChargingPolicyRegistry::roaming_entity_owner(0), Some(0));
             assert_ok!(RoamingChargingPolicyTestModule::set_config(
                 Origin::signed(0),
                 0,
@@ -519,7 +698,7 @@ mod tests {
             ));
 
             // Verify Storage
-            assert_eq!(RoamingChargingPolicyTestModule::roaming_charging_policies_count(), 1);
+            assert_eq!(RoamingChargingPolicyRegistry::roaming_entities_count(), 1);
             assert_eq!(
                 RoamingChargingPolicyTestModule::roaming_charging_policy_settings(0),
                 Some(RoamingChargingPolicySetting {
@@ -535,12 +714,12 @@ mod tests {
             // Create Network Profiles
 
             // Call Functions
-            assert_ok!(RoamingNetworkProfileTestModule::create(Origin::signed(0)));
-            assert_ok!(RoamingNetworkProfileTestModule::create(Origin::signed(0)));
-            assert_ok!(RoamingNetworkProfileTestModule::create(Origin::signed(1)));
-            assert_eq!(RoamingNetworkProfileTestModule::roaming_network_profile_owner(0), Some(0));
-            assert_eq!(RoamingNetworkProfileTestModule::roaming_network_profile_owner(1), Some(0));
-            assert_eq!(RoamingNetworkProfileTestModule::roaming_network_profile_owner(2), Some(1));
+            assert_ok!(RoamingNetworkProfileRegistry::create(Origin::signed(0)));
+            assert_ok!(RoamingNetworkProfileRegistry::create(Origin::signed(0)));
+            assert_ok!(RoamingNetworkProfileRegistry::create(Origin::signed(1)));
+            assert_eq!(RoamingNetworkProfileRegistry::roaming_entity_owner(0), Some(0));
+            assert_eq!(RoamingNetworkProfileRegistry::roaming_entity_owner(1), Some(0));
+            assert_eq!(RoamingNetworkProfileRegistry::roaming_entity_owner(2), Some(1));
             assert_ok!(RoamingNetworkProfileTestModule::assign_network_profile_to_network(Origin::signed(0), 0, 0));
             assert_ok!(RoamingNetworkProfileTestModule::assign_network_profile_to_operator(Origin::signed(0), 0, 0));
             assert_ok!(RoamingNetworkProfileTestModule::assign_network_profile_to_network(Origin::signed(0), 1, 0));
@@ -575,7 +754,7 @@ mod tests {
             // );
 
             // Verify Storage
-            assert_eq!(RoamingNetworkProfileTestModule::roaming_network_profiles_count(), 3);
+            assert_eq!(RoamingNetworkProfileRegistry::roaming_entities_count(), 3);
             assert_eq!(
                 RoamingNetworkProfileTestModule::roaming_network_profile_whitelisted_networks(0),
                 Some([0].to_vec())
@@ -588,20 +767,25 @@ mod tests {
             // Create Device
 
             // Call Functions
-            assert_ok!(RoamingDeviceTestModule::create(Origin::signed(0)));
-            assert_eq!(RoamingDeviceTestModule::roaming_device_owner(0), Some(0));
+            assert_ok!(RoamingDeviceRegistry::create(Origin::signed(0)));
+            assert_eq!(RoamingDeviceRegistry::roaming_entity_owner(0), Some(0));
+            // The device owner lets the organization and network server owners assign the device
+            assert_ok!(RoamingDeviceRegistry::grant_admin(Origin::signed(0), 0, 1));
+            assert_ok!(RoamingDeviceRegistry::grant_admin(Origin::signed(0), 0, 2));
             assert_ok!(RoamingDeviceTestModule::assign_device_to_organization(Origin::signed(2), 0, 2));
             assert_ok!(RoamingDeviceTestModule::assign_device_to_network_server(Origin::signed(1), 0, 1));
 
             // Verify Storage
-            assert_eq!(RoamingDeviceTestModule::roaming_devices_count(), 1);
+            assert_eq!(RoamingDeviceRegistry::roaming_entities_count(), 1);
+            assert_eq!(RoamingDeviceTestModule::network_server_devices(1), vec![0]);
+            assert!(RoamingDeviceTestModule::network_server_devices(0).is_empty());
 
             // Create Device Profile
 
             // Call Functions
-            assert_ok!(RoamingDeviceProfileTestModule::create(Origin::signed(0)));
+            assert_ok!(RoamingDeviceProfileRegistry::create(Origin::signed(0)));
             assert_ok!(RoamingDeviceProfileTestModule::assign_device_profile_to_device(Origin::signed(0), 0, 0));
-            assert_eq!(RoamingDeviceProfileTestModule::roaming_device_profile_owner(0), Some(0));
+            assert_eq!(RoamingDeviceProfileRegistry::roaming_entity_owner(0), Some(0));
             assert_ok!(RoamingDeviceProfileTestModule::set_config(
                 Origin::signed(0),
                 0,
@@ -612,7 +796,7 @@ mod tests {
             ));
 
             // Verify Storage
-            assert_eq!(RoamingDeviceProfileTestModule::roaming_device_profiles_count(), 1);
+            assert_eq!(RoamingDeviceProfileRegistry::roaming_entities_count(), 1);
             assert_eq!(
                 RoamingDeviceProfileTestModule::roaming_device_profile_settings(0),
                 Some(RoamingDeviceProfileSetting {
@@ -0,0 +1,170 @@
+extern crate datahighway_runtime as datahighway_runtime;
+
+#[cfg(test)]
+mod tests {
+    use datahighway_runtime::{
+        roaming_device_sessions_page,
+        roaming_network_server_devices_page,
+        roaming_network_subtree,
+        roaming_operator_subtree,
+        roaming_session_packet_bundles_page,
+        Runtime,
+    };
+    use frame_support::{
+        StorageDoubleMap,
+        StorageMap,
+    };
+    use roaming_registry::RoamingEntity;
+    use roaming_runtime_api::{
+        RoamingPage,
+        MAX_PAGE_LIMIT,
+    };
+
+    pub fn new_test_ext() -> sp_io::TestExternalities {
+        frame_system::GenesisConfig::default().build_storage::<Runtime>().unwrap().into()
+    }
+
+    // Read every page of children by following the start of the next page, and return the pages read
+    fn read_all_pages(mut read_page: impl FnMut(Option<u64>) -> RoamingPage<u64>) -> Vec<Vec<u64>> {
+        let mut pages = vec![];
+        let mut start = None;
+        loop {
+            let page = read_page(start);
+            pages.push(page.items);
+            match page.next_start {
+                Some(next_start) => start = Some(next_start),
+                None => return pages,
+            }
+        }
+    }
+
+    fn sorted(mut ids: Vec<u64>) -> Vec<u64> {
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn network_server_devices_are_paged_from_a_start_key() {
+        new_test_ext().execute_with(|| {
+            for device_id in 0..5 {
+                roaming_devices::RoamingNetworkServerDevices::<Runtime>::insert(0, device_id, ());
+            }
+            roaming_devices::RoamingNetworkServerDevices::<Runtime>::insert(1, 5, ());
+
+            let pages = read_all_pages(|start| roaming_network_server_devices_page(0, start, 2));
+            assert_eq!(pages.iter().map(|page| page.len()).collect::<Vec<_>>(), vec![2, 2, 1]);
+            assert_eq!(sorted(pages.concat()), vec![0, 1, 2, 3, 4]);
+
+            // A page that starts at a device that is no longer assigned starts at the next device instead
+            let first_page = roaming_network_server_devices_page(0, None, 2);
+            roaming_devices::RoamingNetworkServerDevices::<Runtime>::remove(0, first_page.items[1]);
+            let page = roaming_network_server_devices_page(0, Some(first_page.items[1]), 2);
+            assert_eq!(page.items.first(), first_page.next_start.as_ref());
+
+            assert_eq!(roaming_network_server_devices_page(2, None, 2), RoamingPage::default());
+        });
+    }
+
+    #[test]
+    fn device_sessions_are_paged_from_a_start_key() {
+        new_test_ext().execute_with(|| {
+            for session_id in 0..4 {
+                roaming_sessions::RoamingDeviceSessions::<Runtime>::insert(0, session_id, ());
+            }
+            roaming_sessions::RoamingDeviceSessions::<Runtime>::insert(1, 4, ());
+
+            let pages = read_all_pages(|start| roaming_device_sessions_page(0, start, 3));
+            assert_eq!(pages.iter().map(|page| page.len()).collect::<Vec<_>>(), vec![3, 1]);
+            assert_eq!(sorted(pages.concat()), vec![0, 1, 2, 3]);
+
+            let page = roaming_device_sessions_page(1, None, 3);
+            assert_eq!(page.items, vec![4]);
+            assert_eq!(page.next_start, None);
+        });
+    }
+
+    #[test]
+    fn session_packet_bundles_are_paged_in_assignment_order() {
+        new_test_ext().execute_with(|| {
+            roaming_packet_bundles::RoamingSessionPacketBundles::<Runtime>::insert(0, vec![3, 1, 4, 2]);
+            for packet_bundle_id in &[3, 1, 4] {
+                roaming_packet_bundles::RoamingPacketBundleSession::<Runtime>::insert(packet_bundle_id, 0);
+            }
+            // Packet bundle 2 has since been assigned to another session
+            roaming_packet_bundles::RoamingPacketBundleSession::<Runtime>::insert(2, 1);
+
+            let page = roaming_session_packet_bundles_page(0, None, 2);
+            assert_eq!(page.items, vec![3, 1]);
+            assert_eq!(page.next_start, Some(4));
+
+            let page = roaming_session_packet_bundles_page(0, Some(4), 2);
+            assert_eq!(page.items, vec![4]);
+            assert_eq!(page.next_start, None);
+        });
+    }
+
+    #[test]
+    fn operator_and_network_subtrees_are_paged() {
+        new_test_ext().execute_with(|| {
+            assert_eq!(roaming_operator_subtree(0, None, 10), None);
+            assert_eq!(roaming_network_subtree(0, None, 10), None);
+
+            roaming_registry::RoamingEntities::<Runtime, roaming_registry::Instance1>::insert(
+                0,
+                RoamingEntity([0; 16]),
+            );
+            for network_id in 0..3 {
+                roaming_registry::RoamingEntities::<Runtime, roaming_registry::Instance2>::insert(
+                    network_id,
+                    RoamingEntity([0; 16]),
+                );
+                roaming_networks::RoamingNetworkOperator::<Runtime>::insert(network_id, 0);
+            }
+            roaming_networks::RoamingOperatorNetworks::<Runtime>::insert(0, vec![0, 1, 2]);
+            roaming_network_servers::RoamingNetworkNetworkServers::<Runtime>::insert(1, vec![0, 1]);
+            for network_server_id in 0..2 {
+                roaming_network_servers::RoamingNetworkServerNetwork::<Runtime>::insert(network_server_id, 1);
+            }
+            roaming_devices::RoamingNetworkServerDevices::<Runtime>::insert(1, 0, ());
+
+            let operator_subtree = roaming_operator_subtree(0, Some(1), 1).unwrap();
+            assert_eq!(operator_subtree.operator_id, 0);
+            assert_eq!(operator_subtree.networks.next_start, Some(2));
+            assert_eq!(operator_subtree.networks.items.len(), 1);
+            let network_subtree = &operator_subtree.networks.items[0];
+            assert_eq!(network_subtree.network_id, 1);
+            assert_eq!(
+                network_subtree
+                    .network_servers
+                    .items
+                    .iter()
+                    .map(|subtree| subtree.network_server_id)
+                    .collect::<Vec<_>>(),
+                vec![0, 1]
+            );
+            assert_eq!(network_subtree.network_servers.items[1].devices.items, vec![0]);
+
+            let network_subtree = roaming_network_subtree(1, Some(1), 10).unwrap();
+            assert_eq!(network_subtree.network_servers.items.len(), 1);
+            assert_eq!(network_subtree.network_servers.items[0].network_server_id, 1);
+            assert_eq!(network_subtree.network_servers.next_start, None);
+        });
+    }
+
+    #[test]
+    fn page_limit_is_capped() {
+        new_test_ext().execute_with(|| {
+            for device_id in 0..u64::from(MAX_PAGE_LIMIT) + 1 {
+                roaming_devices::RoamingNetworkServerDevices::<Runtime>::insert(0, device_id, ());
+            }
+
+            let page = roaming_network_server_devices_page(0, None, u32::max_value());
+            assert_eq!(page.items.len(), MAX_PAGE_LIMIT as usize);
+            assert!(page.next_start.is_some());
+
+            let page = roaming_network_server_devices_page(0, page.next_start, u32::max_value());
+            assert_eq!(page.items.len(), 1);
+            assert_eq!(page.next_start, None);
+        });
+    }
+}